@@ -21,6 +21,8 @@ pub enum Error {
     Banned(String),
     /// Persisted character data is invalid or missing
     InvalidCharacter,
+    /// The answer to the login challenge of the server was wrong
+    ChallengeFailed,
     //TODO: InvalidAlias,
     Other(String),
     SpecsErr(SpecsError),
@@ -50,9 +50,10 @@ use common_net::{
     msg::{
         self, validate_chat_msg,
         world_msg::{EconomyInfo, PoiInfo, SiteId, SiteInfo},
-        ChatMsgValidationError, ClientGeneral, ClientMsg, ClientRegister, ClientType,
-        DisconnectReason, InviteAnswer, Notification, PingMsg, PlayerInfo, PlayerListUpdate,
-        PresenceKind,  ServerGeneral, ServerInit,
+        ChallengeAnswer, ChatMsgValidationError, ClientGeneral, ClientMsg, ClientRegister,
+        ClientType, DisconnectReason, InviteAnswer, LoginChallenge, Notification, PingMsg,
+        PlayerInfo, PlayerListUpdate, PresenceKind, RegisterError, ServerGeneral, ServerInit,
+        ServerRegisterAnswer,
         MAX_BYTES_CHAT_MSG,
     },
     sync::WorldSyncExt,
@@ -62,7 +63,7 @@ use common_systems::add_local_systems;
 use comp::BuffKind;
 use hashbrown::{HashMap, HashSet};
 use image::DynamicImage;
-use network::{ConnectAddr, Network, Participant, Pid, Stream};
+use network::{ConnectAddr, ConnectionQuality, Network, Participant, Pid, Stream};
 use num::traits::FloatConst;
 use rayon::prelude::*;
 
@@ -641,19 +642,36 @@ impl Client {
     }
 
     /// Request a state transition to `ClientState::Registered`.
+    ///
+    /// Returns the challenge if the server wants one solved first, register
+    /// again with its answer then.
     pub async fn register(
         &mut self,
         token_or_username: String,
-        password: String
-    ) -> Result<(), Error> {
+        password: String,
+        challenge_answer: Option<ChallengeAnswer>,
+    ) -> Result<Option<LoginChallenge>, Error> {
         // Authentication
 
         log::info!("# 直接跳过验证, username:{}, password:{}", &token_or_username, &password);
 
-        //直接跳过验证
-        self.send_msg_err(ClientRegister { token_or_username })?;
-        self.registered = true;
-        Ok(())
+        self.send_msg_err(ClientRegister {
+            token_or_username,
+            challenge_answer,
+        })?;
+        match self.register_stream.recv::<ServerRegisterAnswer>().await? {
+            Err(RegisterError::AuthError(err)) => Err(Error::AuthErr(err)),
+            Err(RegisterError::InvalidCharacter) => Err(Error::InvalidCharacter),
+            Err(RegisterError::NotOnWhitelist) => Err(Error::NotOnWhitelist),
+            Err(RegisterError::Kicked(err)) => Err(Error::Kicked(err)),
+            Err(RegisterError::Banned(reason)) => Err(Error::Banned(reason)),
+            Err(RegisterError::Challenge(challenge)) => Ok(Some(challenge)),
+            Err(RegisterError::ChallengeFailed) => Err(Error::ChallengeFailed),
+            Ok(()) => {
+                self.registered = true;
+                Ok(None)
+            },
+        }
     }
 
     fn send_msg_err<S>(&mut self, msg: S) -> Result<(), network::StreamError>
@@ -2164,6 +2182,12 @@ impl Client {
 
     pub fn get_ping_ms(&self) -> f64 { self.last_ping_delta * 1000.0 }
 
+    /// Round trip time, jitter and loss of the connection to the server as
+    /// measured by the network layer, `None` once disconnected
+    pub fn connection_quality(&self) -> Option<ConnectionQuality> {
+        self.participant.as_ref().map(Participant::connection_quality)
+    }
+
     pub fn get_ping_ms_rolling_avg(&self) -> f64 {
         let mut total_weight = 0.;
         let pings = self.ping_deltas.len() as f64;
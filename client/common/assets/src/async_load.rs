@@ -0,0 +1,277 @@
+//! Background loading of assets, ordered by priority
+use crate::{profile, AssetHandle, Error};
+use instant::Instant;
+use lazy_static::lazy_static;
+use serde::Deserialize;
+use std::{
+    cmp::Ordering,
+    collections::BinaryHeap,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Condvar, Mutex},
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+
+/// Higher priorities are loaded first, loads of the same priority in the
+/// order they were requested
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+pub enum LoadPriority {
+    /// Assets which might be needed soon
+    Prefetch,
+    Normal,
+    /// Assets needed for the next frame, e.g. the UI being opened
+    Critical,
+}
+
+struct Job {
+    priority: LoadPriority,
+    seq: u64,
+    run: Box<dyn FnOnce() + Send>,
+}
+
+impl PartialEq for Job {
+    fn eq(&self, other: &Self) -> bool { self.cmp(other) == Ordering::Equal }
+}
+
+impl Eq for Job {}
+
+impl PartialOrd for Job {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl Ord for Job {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+#[derive(Default)]
+struct Queue {
+    jobs: BinaryHeap<Job>,
+    next_seq: u64,
+    /// Moving average of how long a job took on the main thread, used to
+    /// guess whether the next one still fits into the budget of a frame
+    average_cost: Option<Duration>,
+    #[cfg(not(target_arch = "wasm32"))]
+    worker_started: bool,
+}
+
+lazy_static! {
+    static ref QUEUE: (Mutex<Queue>, Condvar) = (Mutex::new(Queue::default()), Condvar::new());
+}
+
+struct Slot<T: 'static> {
+    result: Option<Result<AssetHandle<T>, Error>>,
+    waker: Option<Waker>,
+}
+
+/// An asset being loaded in the background
+///
+/// Poll it with [`AssetFuture::try_take`] from the main loop, or call
+/// [`AssetFuture::wait`] when the asset is needed right away.
+pub struct AssetFuture<T: 'static> {
+    specifier: String,
+    load: fn(&str) -> Result<AssetHandle<T>, Error>,
+    slot: Arc<Mutex<Slot<T>>>,
+}
+
+impl<T: 'static> AssetFuture<T> {
+    pub fn specifier(&self) -> &str { &self.specifier }
+
+    pub fn is_ready(&self) -> bool { self.slot.lock().unwrap().result.is_some() }
+
+    /// The result if the asset finished loading
+    pub fn try_take(&mut self) -> Option<Result<AssetHandle<T>, Error>> {
+        self.slot.lock().unwrap().result.take()
+    }
+
+    /// Block until the asset is loaded, loading it on this thread if the
+    /// background loader didn't get to it yet
+    pub fn wait(mut self) -> Result<AssetHandle<T>, Error> {
+        // The cache makes sure the queued job won't load the asset again
+        self.try_take().unwrap_or_else(|| (self.load)(&self.specifier))
+    }
+}
+
+impl<T: 'static> Future for AssetFuture<T> {
+    type Output = Result<AssetHandle<T>, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut slot = self.slot.lock().unwrap();
+        match slot.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                slot.waker = Some(cx.waker().clone());
+                Poll::Pending
+            },
+        }
+    }
+}
+
+pub(crate) fn enqueue<T: Send + Sync + 'static>(
+    specifier: &str,
+    priority: LoadPriority,
+    load: fn(&str) -> Result<AssetHandle<T>, Error>,
+) -> AssetFuture<T> {
+    let slot = Arc::new(Mutex::new(Slot {
+        result: None,
+        waker: None,
+    }));
+    let job_slot = Arc::clone(&slot);
+    let job_specifier = specifier.to_owned();
+    let queued = Instant::now();
+    let run = Box::new(move || {
+        profile::record_queue_wait(&job_specifier, queued.elapsed());
+        let result = load(&job_specifier);
+        let mut slot = job_slot.lock().unwrap();
+        slot.result = Some(result);
+        if let Some(waker) = slot.waker.take() {
+            waker.wake();
+        }
+    });
+
+    let (queue, condvar) = &*QUEUE;
+    let mut queue = queue.lock().unwrap();
+    let seq = queue.next_seq;
+    queue.next_seq += 1;
+    queue.jobs.push(Job { priority, seq, run });
+    #[cfg(not(target_arch = "wasm32"))]
+    if !queue.worker_started {
+        queue.worker_started = true;
+        spawn_worker();
+    }
+    condvar.notify_one();
+
+    AssetFuture {
+        specifier: specifier.to_owned(),
+        load,
+        slot,
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn spawn_worker() {
+    std::thread::Builder::new()
+        .name("asset-loader".to_owned())
+        .spawn(|| {
+            let (queue, condvar) = &*QUEUE;
+            loop {
+                let job = {
+                    let mut queue = queue.lock().unwrap();
+                    loop {
+                        match queue.jobs.pop() {
+                            Some(job) => break job,
+                            None => queue = condvar.wait(queue).unwrap(),
+                        }
+                    }
+                };
+                (job.run)();
+            }
+        })
+        .expect("Failed to spawn the asset loader thread");
+}
+
+/// Run up to `max_jobs` queued loads on the calling thread, returns how many
+/// were run
+///
+/// There are no threads on the web, so the main loop has to drive the queue
+/// there. Elsewhere this only speeds up the background loader.
+pub fn process_async_loads(max_jobs: usize) -> usize {
+    let (queue, _) = &*QUEUE;
+    let mut done = 0;
+    while done < max_jobs {
+        let job = match queue.lock().unwrap().jobs.pop() {
+            Some(job) => job,
+            None => break,
+        };
+        (job.run)();
+        done += 1;
+    }
+    done
+}
+
+/// Whether another job is started after `elapsed` time was spent this frame,
+/// at least one job is always run so the queue never stalls
+fn fits_budget(done: usize, elapsed: Duration, estimate: Duration, budget: Duration) -> bool {
+    done == 0 || elapsed + estimate <= budget
+}
+
+/// Run queued loads on the calling thread until `budget` is used up, returns
+/// how many were run
+///
+/// A job is only started when the average time of earlier jobs still fits
+/// into the rest of the budget, so several large images requested at once are
+/// decoded over a few frames instead of stalling one.
+pub fn process_async_loads_within(budget: Duration) -> usize {
+    let (queue, _) = &*QUEUE;
+    let start = Instant::now();
+    let mut done = 0;
+    loop {
+        let job = {
+            let mut queue = queue.lock().unwrap();
+            let estimate = queue.average_cost.unwrap_or_default();
+            if !fits_budget(done, start.elapsed(), estimate, budget) {
+                break;
+            }
+            match queue.jobs.pop() {
+                Some(job) => job,
+                None => break,
+            }
+        };
+        let job_start = Instant::now();
+        (job.run)();
+        let cost = job_start.elapsed();
+        let mut queue = queue.lock().unwrap();
+        queue.average_cost = Some(match queue.average_cost {
+            Some(average) => (average * 3 + cost) / 4,
+            None => cost,
+        });
+        done += 1;
+    }
+    done
+}
+
+/// Number of loads still waiting in the queue
+pub fn pending_async_loads() -> usize { QUEUE.0.lock().unwrap().jobs.len() }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn priority_order() {
+        let mut heap = BinaryHeap::new();
+        for (seq, priority) in [
+            LoadPriority::Prefetch,
+            LoadPriority::Critical,
+            LoadPriority::Normal,
+            LoadPriority::Critical,
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            heap.push(Job {
+                priority,
+                seq: seq as u64,
+                run: Box::new(|| {}),
+            });
+        }
+        let order = std::iter::from_fn(|| heap.pop())
+            .map(|job| job.seq)
+            .collect::<Vec<_>>();
+        assert_eq!(order, vec![1, 3, 2, 0]);
+    }
+
+    #[test]
+    fn budget() {
+        let ms = Duration::from_millis;
+        // The first job always runs, even if it is expected to be too slow
+        assert!(fits_budget(0, ms(0), ms(50), ms(8)));
+        assert!(fits_budget(2, ms(3), ms(5), ms(8)));
+        assert!(!fits_budget(2, ms(4), ms(5), ms(8)));
+        assert!(!fits_budget(1, ms(9), ms(0), ms(8)));
+    }
+}
@@ -0,0 +1,27 @@
+//! Problems with assets which didn't stop the game, kept for bug reports
+use crate::placeholder::Substitution;
+use lazy_static::lazy_static;
+use std::sync::Mutex;
+
+#[derive(Clone, Debug)]
+pub enum AssetDiagnostic {
+    /// The asset failed to load and was replaced
+    Placeholder(Substitution),
+    /// The asset was requested by a specifier listed in the alias table
+    DeprecatedAlias { from: String, to: String },
+}
+
+lazy_static! {
+    static ref DIAGNOSTICS: Mutex<Vec<AssetDiagnostic>> = Mutex::new(Vec::new());
+}
+
+pub(crate) fn record(diagnostic: AssetDiagnostic) { DIAGNOSTICS.lock().unwrap().push(diagnostic); }
+
+/// Everything recorded so far, oldest first
+pub fn asset_diagnostics() -> Vec<AssetDiagnostic> { DIAGNOSTICS.lock().unwrap().clone() }
+
+/// Like [`asset_diagnostics`], but `None` instead of waiting while they are
+/// being recorded, e.g. when called from a panic hook
+pub fn try_asset_diagnostics() -> Option<Vec<AssetDiagnostic>> {
+    DIAGNOSTICS.try_lock().ok().map(|diagnostics| diagnostics.clone())
+}
@@ -0,0 +1,75 @@
+//! Listing structured subdirectories, e.g. one folder per language
+use crate::{
+    source::{DirEntry, Source},
+    AssetCache, BoxedError, Compound, DirLoadable, SharedString,
+};
+use std::{io, marker::PhantomData};
+
+/// Decides which entries of a directory [`ManifestDirLoader`] lists
+pub trait DirFilter: Send + Sync + 'static {
+    /// The id to list for `entry`, if it should be listed
+    fn select<S: Source + ?Sized>(source: &S, entry: DirEntry) -> Option<SharedString>;
+}
+
+/// Selects the subdirectories which contain `<name>.<ext>`, listing the id of
+/// that file
+pub fn manifest_in_dir<S: Source + ?Sized>(
+    source: &S,
+    entry: DirEntry,
+    name: &str,
+    ext: &str,
+) -> Option<SharedString> {
+    match entry {
+        DirEntry::Directory(dir) => {
+            let manifest = [dir, ".", name].concat();
+            source
+                .exists(DirEntry::File(&manifest, ext))
+                .then(|| manifest.into())
+        },
+        DirEntry::File(..) => None,
+    }
+}
+
+/// Lists the entries of a directory selected by `F`, without loading them
+///
+/// ```no_run
+/// use veloren_common_assets::{
+///     load_dir, manifest_in_dir,
+///     source::{DirEntry, Source},
+///     DirFilter, ManifestDirLoader, SharedString,
+/// };
+///
+/// struct VoicePacks;
+/// impl DirFilter for VoicePacks {
+///     fn select<S: Source + ?Sized>(source: &S, entry: DirEntry) -> Option<SharedString> {
+///         manifest_in_dir(source, entry, "_pack", "ron")
+///     }
+/// }
+///
+/// let packs = load_dir::<ManifestDirLoader<VoicePacks>>("voxygen.voice").unwrap();
+/// for manifest in packs.ids() {
+///     println!("{}", manifest);
+/// }
+/// ```
+pub struct ManifestDirLoader<F>(PhantomData<F>);
+
+impl<F: DirFilter> Compound for ManifestDirLoader<F> {
+    fn load<S: Source + ?Sized>(_: &AssetCache<S>, _: &str) -> Result<Self, BoxedError> {
+        Ok(Self(PhantomData))
+    }
+}
+
+impl<F: DirFilter> DirLoadable for ManifestDirLoader<F> {
+    fn select_ids<S: Source + ?Sized>(
+        source: &S,
+        specifier: &str,
+    ) -> io::Result<Vec<SharedString>> {
+        let mut ids = Vec::new();
+        source.read_dir(specifier, &mut |entry| {
+            if let Some(id) = F::select(source, entry) {
+                ids.push(id);
+            }
+        })?;
+        Ok(ids)
+    }
+}
@@ -0,0 +1,120 @@
+//! Looking up assets by a pattern, so lists of numbered images or animation
+//! frames pick up new files without code changes
+use crate::ASSETS;
+use std::cmp::Ordering;
+
+/// Whether `name` matches `pattern`, where `*` stands for any number of
+/// characters and `?` for exactly one
+fn matches(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let name = name.chars().collect::<Vec<_>>();
+    let (mut p, mut n) = (0, 0);
+    // Position of the last `*` and the part of the name it covers so far
+    let mut star = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            },
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            },
+            _ => match star {
+                // Let the last `*` cover one more character
+                Some((star_p, star_n)) => {
+                    star = Some((star_p, star_n + 1));
+                    p = star_p + 1;
+                    n = star_n + 1;
+                },
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Compares numbers within the names by their value, so `bg_2` comes before
+/// `bg_10`
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a, b);
+    loop {
+        let (a_char, b_char) = match (a.chars().next(), b.chars().next()) {
+            (Some(a_char), Some(b_char)) => (a_char, b_char),
+            (a_char, b_char) => return a_char.cmp(&b_char),
+        };
+        if a_char.is_ascii_digit() && b_char.is_ascii_digit() {
+            let a_end = a.find(|c: char| !c.is_ascii_digit()).unwrap_or(a.len());
+            let b_end = b.find(|c: char| !c.is_ascii_digit()).unwrap_or(b.len());
+            let a_num = a[..a_end].trim_start_matches('0');
+            let b_num = b[..b_end].trim_start_matches('0');
+            let ordering = a_num.len().cmp(&b_num.len()).then_with(|| a_num.cmp(b_num));
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+            a = &a[a_end..];
+            b = &b[b_end..];
+        } else if a_char != b_char {
+            return a_char.cmp(&b_char);
+        } else {
+            a = &a[a_char.len_utf8()..];
+            b = &b[b_char.len_utf8()..];
+        }
+    }
+}
+
+/// The matching ones of `ids`, each once and numbers sorted by value
+fn select(pattern: &str, ids: Vec<String>) -> Vec<String> {
+    let name_pattern = pattern.rsplit('.').next().unwrap_or(pattern);
+    let mut found = ids
+        .into_iter()
+        .filter(|id| matches(name_pattern, id.rsplit('.').next().unwrap_or(id)))
+        .collect::<Vec<_>>();
+    found.sort_by(|a, b| natural_cmp(a, b));
+    found.dedup();
+    found
+}
+
+/// The specifiers of the assets matching `pattern`, e.g.
+/// `find("voxygen.background.bg_*")`
+///
+/// Only the last part of the pattern may contain wildcards, `*` matches any
+/// number of characters and `?` a single one. Files in the override directory
+/// and, on the web, files which are only downloaded once needed are included.
+/// Assets made up of several files are listed once.
+pub fn find(pattern: &str) -> Vec<String> {
+    let dir = pattern.rsplit_once('.').map_or("", |(dir, _)| dir);
+    select(pattern, ASSETS.source().file_ids(dir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn select_ids() {
+        let ids = ["bg_10", "bg_2", "bg_main", "death", "bg_1", "bg_2", "bg_1x"]
+            .iter()
+            .map(|name| format!("voxygen.background.{}", name))
+            .collect();
+        let found = select("voxygen.background.bg_?*", ids);
+        let names = found
+            .iter()
+            .map(|id| id.rsplit('.').next().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["bg_1", "bg_1x", "bg_2", "bg_10", "bg_main"]);
+
+        assert!(matches("frame_??", "frame_07"));
+        assert!(!matches("frame_??", "frame_7"));
+        assert!(matches("*_idle_*", "knight_idle_3"));
+        assert!(!matches("bg_*", "death"));
+    }
+
+    #[test]
+    fn find_assets() {
+        let found = find("voxygen.background.bg_*");
+        assert!(found.contains(&"voxygen.background.bg_1".to_owned()));
+        assert!(found.iter().all(|id| id.starts_with("voxygen.background.bg_")));
+    }
+}
@@ -0,0 +1,801 @@
+//! Subsetting large fonts to the characters which are actually shown
+//!
+//! CJK fonts are several megabytes, most of it glyphs no language of the game
+//! ever uses. [`font_data`] loads such fonts with only the glyphs of the
+//! characters requested so far through [`request_glyphs`], the i18n crate
+//! requests all characters of a language when it is loaded. Characters which
+//! show up later, e.g. in chat, are loaded in blocks of [`BLOCK_SIZE`] code
+//! points; [`take_font_changes`] tells when the fonts should be reloaded.
+//! Subsetting only happens on the web, where the fonts have to be downloaded
+//! first. Blocks are merged into the font together, in a task of their own
+//! instead of the frame which requested them.
+//!
+//! Glyphs keep their ids when subsetting, the glyphs which aren't needed are
+//! only emptied. That way the glyph positioning tables stay valid and glyphs
+//! can be merged back in later.
+//!
+//! When the full font is missing, e.g. on the web where it isn't downloaded,
+//! the subset `<font>_subset` and the blocks in `<font>_blocks` are used
+//! instead. `i18n-check --font-subsets` generates them.
+use crate::{AssetExt, BoxedError, Error, Loader};
+use std::{
+    borrow::Cow,
+    collections::{BTreeSet, HashMap, HashSet},
+    fmt,
+    sync::Arc,
+};
+
+/// Fonts smaller than this are always loaded whole
+pub const SUBSET_MIN_SIZE: usize = 1 << 20;
+/// Number of code points loaded at once after the initial subset
+pub const BLOCK_SIZE: u32 = 256;
+
+// Only the outlines are rendered, embedded bitmaps are dropped when
+// subsetting. The signature wouldn't match anymore.
+const DROPPED_TABLES: [&[u8; 4]; 5] = [b"EBDT", b"EBLC", b"EBSC", b"BDF ", b"DSIG"];
+
+pub fn block_of(c: char) -> u32 { c as u32 / BLOCK_SIZE }
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FontError {
+    /// The data ends before a table or record it points to
+    Truncated,
+    MissingTable(&'static str),
+    /// A glyph id beyond the number of glyphs of the font
+    InvalidGlyph(u16),
+    /// Only TrueType outlines and unicode cmaps can be subset
+    Unsupported(String),
+}
+
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Truncated => write!(f, "font data is truncated"),
+            Self::MissingTable(tag) => write!(f, "font has no {} table", tag),
+            Self::InvalidGlyph(id) => write!(f, "font has no glyph {}", id),
+            Self::Unsupported(what) => write!(f, "unsupported font: {}", what),
+        }
+    }
+}
+
+impl std::error::Error for FontError {}
+
+fn read_u16(data: &[u8], at: usize) -> Result<u16, FontError> {
+    data.get(at..at + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+        .ok_or(FontError::Truncated)
+}
+
+fn read_u32(data: &[u8], at: usize) -> Result<u32, FontError> {
+    data.get(at..at + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or(FontError::Truncated)
+}
+
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+struct Sfnt<'a> {
+    version: u32,
+    tables: Vec<([u8; 4], &'a [u8])>,
+}
+
+impl<'a> Sfnt<'a> {
+    fn parse(data: &'a [u8]) -> Result<Self, FontError> {
+        let version = read_u32(data, 0)?;
+        if version != 0x0001_0000 && &data[..4] != b"true" {
+            return Err(FontError::Unsupported("not a TrueType font".to_owned()));
+        }
+        let tables = (0..read_u16(data, 4)? as usize)
+            .map(|i| {
+                let record = 12 + 16 * i;
+                let offset = read_u32(data, record + 8)? as usize;
+                let len = read_u32(data, record + 12)? as usize;
+                let mut tag = [0; 4];
+                tag.copy_from_slice(data.get(record..record + 4).ok_or(FontError::Truncated)?);
+                let table = data.get(offset..offset + len).ok_or(FontError::Truncated)?;
+                Ok((tag, table))
+            })
+            .collect::<Result<_, FontError>>()?;
+        Ok(Self { version, tables })
+    }
+
+    fn table(&self, tag: &'static str) -> Result<&'a [u8], FontError> {
+        self.tables
+            .iter()
+            .find(|(t, _)| t == tag.as_bytes())
+            .map(|(_, table)| *table)
+            .ok_or(FontError::MissingTable(tag))
+    }
+
+    fn glyphs(&self) -> Result<Glyphs<'a>, FontError> {
+        let long = read_u16(self.table("head")?, 50)? == 1;
+        let count = read_u16(self.table("maxp")?, 4)? as usize;
+        let loca = self.table("loca")?;
+        let offsets = (0..=count)
+            .map(|i| {
+                if long {
+                    read_u32(loca, i * 4).map(|o| o as usize)
+                } else {
+                    read_u16(loca, i * 2).map(|o| o as usize * 2)
+                }
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Glyphs {
+            glyf: self.table("glyf")?,
+            offsets,
+        })
+    }
+
+    /// Writes the font with the glyph data replaced, always using long loca
+    /// offsets
+    fn rebuild<'b>(
+        &self,
+        glyphs: &Glyphs<'b>,
+        mut glyph: impl FnMut(u16, &'b [u8]) -> &'b [u8],
+    ) -> Result<Vec<u8>, FontError> {
+        let mut glyf = Vec::new();
+        let mut loca = Vec::with_capacity(glyphs.offsets.len() * 4);
+        for id in 0..glyphs.len() {
+            loca.extend_from_slice(&(glyf.len() as u32).to_be_bytes());
+            glyf.extend_from_slice(glyph(id, glyphs.get(id)?));
+            glyf.resize((glyf.len() + 3) & !3, 0);
+        }
+        loca.extend_from_slice(&(glyf.len() as u32).to_be_bytes());
+        let mut head = self.table("head")?.to_vec();
+        head.get_mut(8..12).ok_or(FontError::Truncated)?.fill(0);
+        head.get_mut(50..52)
+            .ok_or(FontError::Truncated)?
+            .copy_from_slice(&1u16.to_be_bytes());
+
+        let tables = self
+            .tables
+            .iter()
+            .filter(|(tag, _)| !DROPPED_TABLES.contains(&tag))
+            .map(|(tag, table)| {
+                let table = match tag {
+                    b"glyf" => &glyf[..],
+                    b"loca" => &loca[..],
+                    b"head" => &head[..],
+                    _ => table,
+                };
+                (*tag, table)
+            })
+            .collect::<Vec<_>>();
+
+        let count = tables.len() as u16;
+        let entry_selector = 15 - count.leading_zeros() as u16;
+        let search_range = (1 << entry_selector) * 16;
+        let mut font = Vec::new();
+        font.extend_from_slice(&self.version.to_be_bytes());
+        for field in [count, search_range, entry_selector, count * 16 - search_range] {
+            font.extend_from_slice(&field.to_be_bytes());
+        }
+        let mut offset = 12 + 16 * tables.len();
+        let mut head_offset = None;
+        for (tag, table) in &tables {
+            if tag == b"head" {
+                head_offset = Some(offset);
+            }
+            font.extend_from_slice(tag);
+            font.extend_from_slice(&checksum(table).to_be_bytes());
+            font.extend_from_slice(&(offset as u32).to_be_bytes());
+            font.extend_from_slice(&(table.len() as u32).to_be_bytes());
+            offset += (table.len() + 3) & !3;
+        }
+        for (_, table) in &tables {
+            font.extend_from_slice(table);
+            font.resize((font.len() + 3) & !3, 0);
+        }
+        let adjustment = 0xB1B0_AFBAu32.wrapping_sub(checksum(&font));
+        let head_offset = head_offset.ok_or(FontError::MissingTable("head"))?;
+        font[head_offset + 8..head_offset + 12].copy_from_slice(&adjustment.to_be_bytes());
+        Ok(font)
+    }
+}
+
+struct Glyphs<'a> {
+    glyf: &'a [u8],
+    offsets: Vec<usize>,
+}
+
+impl<'a> Glyphs<'a> {
+    fn len(&self) -> u16 { (self.offsets.len() - 1) as u16 }
+
+    fn get(&self, id: u16) -> Result<&'a [u8], FontError> {
+        let (start, end) = match self.offsets.get(id as usize..id as usize + 2) {
+            Some(range) => (range[0], range[1]),
+            None => return Err(FontError::InvalidGlyph(id)),
+        };
+        self.glyf.get(start..end.max(start)).ok_or(FontError::Truncated)
+    }
+
+    /// `ids` together with the glyphs composite glyphs among them are made of
+    fn with_components(&self, ids: impl IntoIterator<Item = u16>) -> BTreeSet<u16> {
+        let mut todo = ids.into_iter().collect::<Vec<_>>();
+        let mut all = BTreeSet::new();
+        while let Some(id) = todo.pop() {
+            if id >= self.len() || !all.insert(id) {
+                continue;
+            }
+            if let Ok(components) = self.get(id).and_then(components) {
+                todo.extend(components);
+            }
+        }
+        all
+    }
+}
+
+fn components(glyph: &[u8]) -> Result<Vec<u16>, FontError> {
+    const ARGS_ARE_WORDS: u16 = 0x1;
+    const HAVE_SCALE: u16 = 0x8;
+    const MORE_COMPONENTS: u16 = 0x20;
+    const HAVE_XY_SCALE: u16 = 0x40;
+    const HAVE_2X2: u16 = 0x80;
+
+    let mut components = Vec::new();
+    if glyph.is_empty() || (read_u16(glyph, 0)? as i16) >= 0 {
+        return Ok(components);
+    }
+    let mut at = 10;
+    loop {
+        let flags = read_u16(glyph, at)?;
+        components.push(read_u16(glyph, at + 2)?);
+        at += 4 + if flags & ARGS_ARE_WORDS != 0 { 4 } else { 2 };
+        at += if flags & HAVE_SCALE != 0 {
+            2
+        } else if flags & HAVE_XY_SCALE != 0 {
+            4
+        } else if flags & HAVE_2X2 != 0 {
+            8
+        } else {
+            0
+        };
+        if flags & MORE_COMPONENTS == 0 {
+            return Ok(components);
+        }
+    }
+}
+
+/// All characters the font maps to a glyph
+fn char_map(sfnt: &Sfnt) -> Result<Vec<(char, u16)>, FontError> {
+    let cmap = sfnt.table("cmap")?;
+    let mut subtables = (0..read_u16(cmap, 2)? as usize)
+        .map(|i| {
+            let platform = read_u16(cmap, 4 + 8 * i)?;
+            let encoding = read_u16(cmap, 6 + 8 * i)?;
+            let offset = read_u32(cmap, 8 + 8 * i)? as usize;
+            Ok(((platform, encoding), cmap.get(offset..).ok_or(FontError::Truncated)?))
+        })
+        .collect::<Result<Vec<_>, FontError>>()?;
+    // Prefer the tables covering all of unicode
+    subtables.sort_by_key(|((platform, encoding), _)| match (platform, encoding) {
+        (3, 10) | (0, 4) | (0, 6) => 0,
+        (3, 1) | (0, _) => 1,
+        _ => 2,
+    });
+
+    let mut chars = Vec::new();
+    for ((platform, _), table) in subtables {
+        if platform != 0 && platform != 3 {
+            break;
+        }
+        match read_u16(table, 0)? {
+            4 => {
+                let segments = read_u16(table, 6)? as usize / 2;
+                let ends = 14;
+                let starts = ends + 2 * segments + 2;
+                let deltas = starts + 2 * segments;
+                let range_offsets = deltas + 2 * segments;
+                for i in 0..segments {
+                    let end = read_u16(table, ends + 2 * i)? as u32;
+                    let start = read_u16(table, starts + 2 * i)? as u32;
+                    let delta = read_u16(table, deltas + 2 * i)?;
+                    let range_offset = read_u16(table, range_offsets + 2 * i)? as usize;
+                    for code in start..=end.min(0xFFFE) {
+                        let id = match range_offset {
+                            0 => (code as u16).wrapping_add(delta),
+                            _ => {
+                                let at = range_offsets
+                                    + 2 * i
+                                    + range_offset
+                                    + 2 * (code - start) as usize;
+                                match read_u16(table, at)? {
+                                    0 => 0,
+                                    id => id.wrapping_add(delta),
+                                }
+                            },
+                        };
+                        if let (Some(c), true) = (char::from_u32(code), id != 0) {
+                            chars.push((c, id));
+                        }
+                    }
+                }
+            },
+            12 => {
+                for i in 0..read_u32(table, 12)? as usize {
+                    let start = read_u32(table, 16 + 12 * i)?;
+                    let end = read_u32(table, 20 + 12 * i)?;
+                    let first = read_u32(table, 24 + 12 * i)?;
+                    chars.extend((start..=end.min(0x10FFFF)).filter_map(|code| {
+                        let id = first + (code - start);
+                        char::from_u32(code).zip((id <= u16::MAX as u32).then(|| id as u16))
+                    }));
+                }
+            },
+            _ => continue,
+        }
+        return Ok(chars);
+    }
+    Err(FontError::Unsupported("no unicode cmap".to_owned()))
+}
+
+/// The characters of a font with their glyph ids, by block. Subsets of the
+/// font share it since glyphs keep their ids.
+#[derive(Debug, Default)]
+pub struct CharIndex {
+    blocks: HashMap<u32, Vec<(char, u16)>>,
+}
+
+impl CharIndex {
+    pub fn new(data: &[u8]) -> Result<Self, FontError> {
+        let mut blocks = HashMap::<_, Vec<_>>::new();
+        for (c, id) in char_map(&Sfnt::parse(data)?)? {
+            blocks.entry(block_of(c)).or_default().push((c, id));
+        }
+        Ok(Self { blocks })
+    }
+
+    fn chars(&self) -> impl Iterator<Item = &(char, u16)> { self.blocks.values().flatten() }
+
+    /// The characters which have a glyph with an outline in `data`, the font
+    /// of the index or a subset of it
+    pub fn covered_chars(&self, data: &[u8]) -> Result<HashSet<char>, FontError> {
+        let glyphs = Sfnt::parse(data)?.glyphs()?;
+        Ok(self
+            .chars()
+            .filter(|(_, id)| *id != 0 && glyphs.get(*id).map_or(false, |g| !g.is_empty()))
+            .map(|(c, _)| *c)
+            .collect())
+    }
+
+    /// Empty the glyphs of all characters `keep` returns false for, the
+    /// notdef glyph is always kept
+    pub fn subset(
+        &self,
+        data: &[u8],
+        mut keep: impl FnMut(char) -> bool,
+    ) -> Result<Vec<u8>, FontError> {
+        let sfnt = Sfnt::parse(data)?;
+        let glyphs = sfnt.glyphs()?;
+        let kept = self.chars().filter(|(c, _)| keep(*c)).map(|(_, id)| *id);
+        let kept = glyphs.with_components(kept.chain([0]));
+        sfnt.rebuild(&glyphs, |id, glyph| if kept.contains(&id) { glyph } else { &[] })
+    }
+
+    /// Cut the glyphs of each of `blocks` out of the font
+    pub fn glyph_blocks(
+        &self,
+        data: &[u8],
+        blocks: &[u32],
+    ) -> Result<Vec<(u32, GlyphBlock)>, FontError> {
+        let glyphs = Sfnt::parse(data)?.glyphs()?;
+        blocks
+            .iter()
+            .map(|&block| {
+                let ids = self.blocks.get(&block).into_iter().flatten().map(|(_, id)| *id);
+                let block_glyphs = glyphs
+                    .with_components(ids)
+                    .into_iter()
+                    .map(|id| Ok((id, glyphs.get(id)?.to_vec())))
+                    .filter(|glyph| !matches!(glyph, Ok((_, data)) if data.is_empty()))
+                    .collect::<Result<_, FontError>>()?;
+                Ok((block, GlyphBlock {
+                    glyphs: block_glyphs,
+                }))
+            })
+            .collect()
+    }
+
+    /// The characters of `block` which `glyphs` has an outline for
+    pub fn chars_with_glyphs(&self, block: u32, glyphs: &GlyphBlock) -> Vec<char> {
+        let ids = glyphs.glyphs.iter().map(|(id, _)| *id).collect::<HashSet<_>>();
+        self.blocks
+            .get(&block)
+            .into_iter()
+            .flatten()
+            .filter(|(_, id)| ids.contains(id))
+            .map(|(c, _)| *c)
+            .collect()
+    }
+}
+
+/// Empty the glyphs of all characters `keep` returns false for, see
+/// [`CharIndex::subset`]
+pub fn subset_font(data: &[u8], keep: impl FnMut(char) -> bool) -> Result<Vec<u8>, FontError> {
+    CharIndex::new(data)?.subset(data, keep)
+}
+
+/// The characters of the font which have a glyph with an outline
+pub fn covered_chars(data: &[u8]) -> Result<HashSet<char>, FontError> {
+    CharIndex::new(data)?.covered_chars(data)
+}
+
+/// The glyphs of one block of characters, to be merged into a subset of the
+/// same font with [`merge_glyphs`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GlyphBlock {
+    pub glyphs: Vec<(u16, Vec<u8>)>,
+}
+
+impl GlyphBlock {
+    pub fn is_empty(&self) -> bool { self.glyphs.is_empty() }
+
+    /// A count followed by the id, length and data of each glyph
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = (self.glyphs.len() as u32).to_be_bytes().to_vec();
+        for (id, glyph) in &self.glyphs {
+            bytes.extend_from_slice(&id.to_be_bytes());
+            bytes.extend_from_slice(&(glyph.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(glyph);
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FontError> {
+        let mut at = 4;
+        let glyphs = (0..read_u32(bytes, 0)?)
+            .map(|_| {
+                let id = read_u16(bytes, at)?;
+                let len = read_u32(bytes, at + 2)? as usize;
+                let glyph = bytes.get(at + 6..at + 6 + len).ok_or(FontError::Truncated)?;
+                at += 6 + len;
+                Ok((id, glyph.to_vec()))
+            })
+            .collect::<Result<_, FontError>>()?;
+        Ok(Self { glyphs })
+    }
+}
+
+pub struct GlyphBlockLoader;
+
+impl Loader<GlyphBlock> for GlyphBlockLoader {
+    fn load(content: Cow<[u8]>, _: &str) -> Result<GlyphBlock, BoxedError> {
+        Ok(GlyphBlock::from_bytes(&content)?)
+    }
+}
+
+impl crate::Asset for GlyphBlock {
+    type Loader = GlyphBlockLoader;
+
+    const EXTENSION: &'static str = "glyphs";
+}
+
+/// Fill the empty glyphs of a subset with the ones of `blocks`, the font is
+/// rebuilt once for all of them
+pub fn merge_glyphs<'a>(
+    data: &[u8],
+    blocks: impl IntoIterator<Item = &'a GlyphBlock>,
+) -> Result<Vec<u8>, FontError> {
+    let sfnt = Sfnt::parse(data)?;
+    let glyphs = sfnt.glyphs()?;
+    let merged = blocks
+        .into_iter()
+        .flat_map(|block| &block.glyphs)
+        .map(|(id, glyph)| (*id, &glyph[..]))
+        .collect::<HashMap<_, _>>();
+    sfnt.rebuild(&glyphs, |id, glyph| match merged.get(&id) {
+        Some(new) if glyph.is_empty() => new,
+        _ => glyph,
+    })
+}
+
+/// The raw data of a font file
+pub struct FontFile(pub Vec<u8>);
+
+impl From<Vec<u8>> for FontFile {
+    fn from(data: Vec<u8>) -> Self { Self(data) }
+}
+
+impl crate::Asset for FontFile {
+    type Loader = crate::LoadFrom<Vec<u8>, crate::BytesLoader>;
+
+    const EXTENSION: &'static str = "ttf";
+}
+
+#[cfg(target_arch = "wasm32")]
+pub use subsets::{font_data, request_glyphs, take_font_changes};
+
+/// The data of the font, on the web large fonts only contain the requested
+/// glyphs
+#[cfg(not(target_arch = "wasm32"))]
+pub fn font_data(specifier: &str) -> Result<Arc<Vec<u8>>, Error> {
+    Ok(Arc::new(FontFile::load(specifier)?.read().0.clone()))
+}
+
+/// Make sure the glyphs of `chars` are available, fonts are always loaded
+/// whole outside of the web
+#[cfg(not(target_arch = "wasm32"))]
+pub fn request_glyphs(_chars: impl IntoIterator<Item = char>) {}
+
+/// Whether glyphs were added to the fonts, never outside of the web
+#[cfg(not(target_arch = "wasm32"))]
+pub fn take_font_changes() -> bool { false }
+
+/// Fonts loaded with only some of their glyphs, on the web where downloading
+/// them whole would hold up the start
+#[cfg(target_arch = "wasm32")]
+mod subsets {
+    use super::*;
+    use crate::AssetHandle;
+    use lazy_static::lazy_static;
+    use std::sync::{Mutex, MutexGuard};
+
+    struct SubsetFont {
+        data: Arc<Vec<u8>>,
+        index: Arc<CharIndex>,
+        covered: HashSet<char>,
+        /// Where missing glyphs come from, `None` when only the prebuilt
+        /// subset and blocks are available
+        full: Option<AssetHandle<FontFile>>,
+        loaded_blocks: HashSet<u32>,
+        pending_blocks: BTreeSet<u32>,
+        /// A task merging the pending blocks is scheduled
+        merging: bool,
+    }
+
+    impl SubsetFont {
+        fn add_missing<'a>(&mut self, chars: impl IntoIterator<Item = &'a char>) {
+            let missing = chars
+                .into_iter()
+                .filter(|c| !self.covered.contains(c))
+                .map(|c| block_of(*c))
+                .filter(|block| !self.loaded_blocks.contains(block))
+                .collect::<Vec<_>>();
+            self.pending_blocks.extend(missing);
+        }
+    }
+
+    #[derive(Default)]
+    struct Registry {
+        requested: HashSet<char>,
+        fonts: HashMap<String, SubsetFont>,
+        changed: bool,
+    }
+
+    lazy_static! {
+        static ref FONTS: Mutex<Registry> = Mutex::new(Registry::default());
+    }
+
+    fn registry() -> MutexGuard<'static, Registry> { FONTS.lock().unwrap() }
+
+    /// Merges the pending blocks in a task of their own instead of the frame
+    /// which asked for them
+    fn schedule_merges(registry: &mut Registry) {
+        for (specifier, font) in registry.fonts.iter_mut() {
+            if !font.pending_blocks.is_empty() && !font.merging {
+                font.merging = true;
+                let specifier = specifier.clone();
+                wasm_bindgen_futures::spawn_local(async move { merge_pending(&specifier) });
+            }
+        }
+    }
+
+    /// The prebuilt glyphs of `block`, `None` while they are being downloaded
+    fn prebuilt_block(specifier: &str, block: u32) -> Option<GlyphBlock> {
+        let id = format!("{}_blocks.{:x}", specifier, block);
+        match GlyphBlock::load(&id) {
+            Ok(glyphs) => Some(glyphs.cloned()),
+            Err(_) if crate::fetch_lazy(&id, "glyphs") => None,
+            // Blocks without any glyphs aren't generated
+            Err(_) => Some(GlyphBlock::default()),
+        }
+    }
+
+    /// Merges all pending blocks which are available into the font at once
+    fn merge_pending(specifier: &str) {
+        let (data, index, full, pending) = match registry().fonts.get(specifier) {
+            Some(font) => (
+                Arc::clone(&font.data),
+                Arc::clone(&font.index),
+                font.full,
+                font.pending_blocks.iter().copied().collect::<Vec<_>>(),
+            ),
+            None => return,
+        };
+
+        // Glyphs can be requested meanwhile, the registry isn't locked
+        let blocks = match full {
+            Some(full) => index
+                .glyph_blocks(&full.read().0, &pending)
+                .unwrap_or_else(|e| {
+                    log::warn!("Failed to read the glyphs of {}: {}", specifier, e);
+                    pending.iter().map(|block| (*block, GlyphBlock::default())).collect()
+                }),
+            None => pending
+                .iter()
+                .filter_map(|&block| Some((block, prebuilt_block(specifier, block)?)))
+                .collect(),
+        };
+        let merged = if blocks.iter().all(|(_, glyphs)| glyphs.is_empty()) {
+            Ok(None)
+        } else {
+            merge_glyphs(&data, blocks.iter().map(|(_, glyphs)| glyphs)).map(Some)
+        };
+
+        let mut registry = registry();
+        let Registry { fonts, changed, .. } = &mut *registry;
+        let font = match fonts.get_mut(specifier) {
+            Some(font) => font,
+            None => return,
+        };
+        font.merging = false;
+        for (block, _) in &blocks {
+            font.pending_blocks.remove(block);
+            font.loaded_blocks.insert(*block);
+        }
+        match merged {
+            Ok(Some(merged)) => {
+                for (block, glyphs) in &blocks {
+                    font.covered.extend(index.chars_with_glyphs(*block, glyphs));
+                }
+                font.data = Arc::new(merged);
+                *changed = true;
+            },
+            Ok(None) => {},
+            Err(e) => log::warn!("Failed to merge glyphs into {}: {}", specifier, e),
+        }
+    }
+
+    /// Make sure the glyphs of `chars` are available, fonts loaded later start
+    /// out with them
+    pub fn request_glyphs(chars: impl IntoIterator<Item = char>) {
+        let mut registry = registry();
+        let Registry {
+            requested, fonts, ..
+        } = &mut *registry;
+        let new = chars
+            .into_iter()
+            .filter(|c| !c.is_control() && requested.insert(*c))
+            .collect::<Vec<_>>();
+        if new.is_empty() {
+            return;
+        }
+        for font in fonts.values_mut() {
+            font.add_missing(&new);
+        }
+        schedule_merges(&mut registry);
+    }
+
+    /// Whether glyphs were added to the fonts since the last call, the fonts
+    /// have to be loaded again with [`font_data`] to show them. Also retries
+    /// blocks which weren't downloaded yet.
+    pub fn take_font_changes() -> bool {
+        let mut registry = registry();
+        schedule_merges(&mut registry);
+        std::mem::take(&mut registry.changed)
+    }
+
+    /// The data of the font, only containing the requested glyphs if it is
+    /// large
+    pub fn font_data(specifier: &str) -> Result<Arc<Vec<u8>>, Error> {
+        let mut registry = registry();
+        if let Some(font) = registry.fonts.get(specifier) {
+            return Ok(Arc::clone(&font.data));
+        }
+
+        let (full, index, data) = match FontFile::load(specifier) {
+            Ok(full) if full.read().0.len() < SUBSET_MIN_SIZE => {
+                return Ok(Arc::new(full.read().0.clone()));
+            },
+            Ok(full) => {
+                let requested = &registry.requested;
+                let font = &full.read().0;
+                let subset = CharIndex::new(font).and_then(|index| {
+                    let data = index.subset(font, |c| c.is_ascii() || requested.contains(&c))?;
+                    Ok((index, data))
+                });
+                match subset {
+                    Ok((index, data)) => (Some(full), index, data),
+                    Err(e) => {
+                        log::warn!("Failed to subset {}, loading all glyphs: {}", specifier, e);
+                        return Ok(Arc::new(font.clone()));
+                    },
+                }
+            },
+            Err(e) => match FontFile::load(&format!("{}_subset", specifier)) {
+                Ok(subset) => {
+                    let data = subset.read().0.clone();
+                    let index = CharIndex::new(&data).unwrap_or_else(|e| {
+                        log::warn!("Failed to read the characters of {}: {}", specifier, e);
+                        CharIndex::default()
+                    });
+                    (None, index, data)
+                },
+                Err(_) => return Err(e),
+            },
+        };
+        let covered = index.covered_chars(&data).unwrap_or_else(|e| {
+            log::warn!("Failed to read the glyphs of {}: {}", specifier, e);
+            HashSet::new()
+        });
+        let mut font = SubsetFont {
+            data: Arc::new(data),
+            index: Arc::new(index),
+            covered,
+            full,
+            loaded_blocks: HashSet::new(),
+            pending_blocks: BTreeSet::new(),
+            merging: false,
+        };
+        log::debug!(
+            "Loaded {} with {} of its characters ({} bytes)",
+            specifier,
+            font.covered.len(),
+            font.data.len()
+        );
+        // The prebuilt subset might lack some of the requested characters
+        font.add_missing(&registry.requested);
+        let data = Arc::clone(&font.data);
+        registry.fonts.insert(specifier.to_owned(), font);
+        schedule_merges(&mut registry);
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wizard() -> Vec<u8> {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../voxygen/www/assets/voxygen/font/wizard_extended.ttf"
+        );
+        std::fs::read(path).unwrap()
+    }
+
+    #[test]
+    fn subset_and_merge() {
+        let full = wizard();
+        let all = covered_chars(&full).unwrap();
+        assert!(all.contains(&'a') && all.contains(&'Z'));
+
+        let subset = subset_font(&full, |c| c.is_ascii_lowercase()).unwrap();
+        let covered = covered_chars(&subset).unwrap();
+        assert!(covered.contains(&'a') && !covered.contains(&'Z'));
+        assert!(covered.iter().all(|c| all.contains(c)));
+        assert!(subset.len() < full.len());
+
+        let index = CharIndex::new(&full).unwrap();
+        let (_, block) = index.glyph_blocks(&full, &[block_of('Z')]).unwrap().remove(0);
+        let block = GlyphBlock::from_bytes(&block.to_bytes()).unwrap();
+        let merged = merge_glyphs(&subset, [&block]).unwrap();
+        let merged_chars = covered_chars(&merged).unwrap();
+        let expected = all
+            .iter()
+            .filter(|c| c.is_ascii_lowercase() || block_of(**c) == block_of('Z'))
+            .copied()
+            .collect::<HashSet<_>>();
+        assert_eq!(merged_chars, expected);
+        assert!(index
+            .chars_with_glyphs(block_of('Z'), &block)
+            .iter()
+            .all(|c| merged_chars.contains(c)));
+        assert_eq!(checksum(&merged), 0xB1B0_AFBA);
+    }
+
+    #[test]
+    fn glyph_ids_out_of_range() {
+        let full = wizard();
+        let glyphs = Sfnt::parse(&full).unwrap().glyphs().unwrap();
+        assert!(glyphs.get(glyphs.len() - 1).is_ok());
+        assert_eq!(glyphs.get(glyphs.len()), Err(FontError::InvalidGlyph(glyphs.len())));
+        assert_eq!(glyphs.get(u16::MAX), Err(FontError::InvalidGlyph(u16::MAX)));
+    }
+}
@@ -1,9 +1,16 @@
-use std::{borrow::Cow, io};
+use crate::alias::resolve_source_id;
+use std::{borrow::Cow, io, path::PathBuf};
 
 use assets_manager::{
     source::{DirEntry, FileSystem as RawFs, Source}
 };
 
+/// Directory whose assets are used instead of the default ones, set with the
+/// `VELOREN_ASSETS_OVERRIDE` env variable
+pub fn override_path() -> Option<PathBuf> {
+    std::env::var_os("VELOREN_ASSETS_OVERRIDE").map(PathBuf::from)
+}
+
 /// Loads assets from the default path or `VELOREN_ASSETS_OVERRIDE` env if it is
 /// set.
 #[derive(Debug, Clone)]
@@ -15,7 +22,7 @@ pub struct ResSystem {
 impl ResSystem {
     pub fn new() -> io::Result<Self> {
         let default = RawFs::new(&*super::ASSETS_PATH)?;
-        let override_dir = std::env::var_os("VELOREN_ASSETS_OVERRIDE").and_then(|path| {
+        let override_dir = override_path().and_then(|path| {
             RawFs::new(path)
                 .map_err(|err| log::error!("Error setting override assets directory: {}", err))
                 .ok()
@@ -26,10 +33,8 @@ impl ResSystem {
             override_dir,
         })
     }
-}
 
-impl Source for ResSystem {
-    fn read(&self, id: &str, ext: &str) -> io::Result<Cow<[u8]>> {
+    fn read_impl(&self, id: &str, ext: &str) -> io::Result<Cow<[u8]>> {
         if let Some(dir) = &self.override_dir {
             match dir.read(id, ext) {
                 Ok(content) => return Ok(content),
@@ -50,7 +55,32 @@ impl Source for ResSystem {
         self.default.read(id, ext)
     }
 
+    /// Ids of the files directly in `dir`, from the override and the default
+    /// directory. Ids with several files appear more than once.
+    pub(crate) fn file_ids(&self, dir: &str) -> Vec<String> {
+        let mut ids = Vec::new();
+        for fs in self.override_dir.iter().chain(std::iter::once(&self.default)) {
+            // A directory missing from one of them is fine
+            let _ = fs.read_dir(dir, &mut |entry| {
+                if let DirEntry::File(id, _) = entry {
+                    ids.push(id.to_owned());
+                }
+            });
+        }
+        ids
+    }
+}
+
+impl Source for ResSystem {
+    fn read(&self, id: &str, ext: &str) -> io::Result<Cow<[u8]>> {
+        let start = instant::Instant::now();
+        let result = self.read_impl(&resolve_source_id(id), ext);
+        super::profile::record_io(start.elapsed());
+        result
+    }
+
     fn read_dir(&self, id: &str, f: &mut dyn FnMut(DirEntry)) -> io::Result<()> {
+        let id = &*resolve_source_id(id);
         if let Some(dir) = &self.override_dir {
             match dir.read_dir(id, f) {
                 Ok(()) => return Ok(()),
@@ -72,6 +102,13 @@ impl Source for ResSystem {
     }
 
     fn exists(&self, entry: DirEntry) -> bool {
+        let resolved = match entry {
+            DirEntry::File(id, _) | DirEntry::Directory(id) => resolve_source_id(id),
+        };
+        let entry = match entry {
+            DirEntry::File(_, ext) => DirEntry::File(&resolved, ext),
+            DirEntry::Directory(_) => DirEntry::Directory(&resolved),
+        };
         self.override_dir
             .as_ref()
             .map_or(false, |dir| dir.exists(entry))
@@ -0,0 +1,397 @@
+//! Metadata of png files which changes how an image has to be shown: the EXIF
+//! orientation and an embedded ICC color profile
+//!
+//! Export tools differ in whether they rotate the pixels or only store the
+//! orientation, and in whether they convert to sRGB or only embed the profile
+//! of the source. Both are applied here, so every image ends up upright and in
+//! sRGB like the rest of the art.
+use flate2::read::ZlibDecoder;
+use image::{DynamicImage, RgbaImage};
+use std::{
+    io::Read,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+static PREMULTIPLY_ALPHA: AtomicBool = AtomicBool::new(false);
+
+/// Whether colors of images are multiplied by their alpha on load, off by
+/// default. Only affects images loaded afterwards.
+pub fn set_premultiply_alpha(enabled: bool) {
+    PREMULTIPLY_ALPHA.store(enabled, Ordering::Relaxed);
+}
+
+pub fn premultiply_alpha() -> bool { PREMULTIPLY_ALPHA.load(Ordering::Relaxed) }
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+const EXIF_ORIENTATION: u16 = 0x0112;
+/// Larger profiles are ignored rather than decompressed
+const MAX_ICC_SIZE: u64 = 1 << 20;
+
+#[derive(Default)]
+struct PngMeta {
+    /// The EXIF orientation, 1 is upright
+    orientation: Option<u16>,
+    icc_profile: Option<Vec<u8>>,
+    /// The file declares itself as sRGB, any profile is redundant then
+    srgb: bool,
+}
+
+fn be_u16(data: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(data.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn be_u32(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn png_meta(content: &[u8]) -> PngMeta {
+    let mut meta = PngMeta::default();
+    if !content.starts_with(PNG_SIGNATURE) {
+        return meta;
+    }
+    let mut at = PNG_SIGNATURE.len();
+    while let (Some(len), Some(kind)) = (be_u32(content, at), content.get(at + 4..at + 8)) {
+        let data = match content.get(at + 8..at + 8 + len as usize) {
+            Some(data) => data,
+            None => break,
+        };
+        match kind {
+            b"eXIf" => meta.orientation = exif_orientation(data),
+            b"iCCP" => meta.icc_profile = inflate_icc(data),
+            b"sRGB" => meta.srgb = true,
+            // The metadata has to come before the pixels
+            b"IDAT" | b"IEND" => break,
+            _ => {},
+        }
+        // Length, type and crc
+        at += len as usize + 12;
+    }
+    meta
+}
+
+/// Reads the orientation from the first directory of EXIF data
+fn exif_orientation(data: &[u8]) -> Option<u16> {
+    let tiff = data.strip_prefix(b"Exif\0\0").unwrap_or(data);
+    let big_endian = match tiff.get(..4)? {
+        b"MM\0*" => true,
+        b"II*\0" => false,
+        _ => return None,
+    };
+    let u16_at = |at: usize| {
+        let bytes = tiff.get(at..at + 2)?.try_into().ok()?;
+        Some(if big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        })
+    };
+    let u32_at = |at: usize| {
+        let bytes = tiff.get(at..at + 4)?.try_into().ok()?;
+        Some(if big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    };
+
+    let directory = u32_at(4)? as usize;
+    (0..usize::from(u16_at(directory)?))
+        .map(|i| directory + 2 + i * 12)
+        .find(|&entry| u16_at(entry) == Some(EXIF_ORIENTATION))
+        .and_then(|entry| u16_at(entry + 8))
+}
+
+/// The chunk is the profile name, a compression method and the zlib stream
+fn inflate_icc(data: &[u8]) -> Option<Vec<u8>> {
+    let name_end = data.iter().position(|&b| b == 0)?;
+    let compressed = data.get(name_end + 2..)?;
+    let mut profile = Vec::new();
+    ZlibDecoder::new(compressed)
+        .take(MAX_ICC_SIZE)
+        .read_to_end(&mut profile)
+        .ok()?;
+    Some(profile)
+}
+
+fn orient(image: DynamicImage, orientation: u16) -> DynamicImage {
+    match orientation {
+        2 => image.fliph(),
+        3 => image.rotate180(),
+        4 => image.flipv(),
+        5 => image.rotate90().fliph(),
+        6 => image.rotate90(),
+        7 => image.rotate270().fliph(),
+        8 => image.rotate270(),
+        _ => image,
+    }
+}
+
+/// A tone response curve of a profile, maps encoded values to linear light
+enum Curve {
+    Table(Vec<f32>),
+    Gamma(f32),
+    /// The parameters of `para` curves, filled up to `[g, a, b, c, d, e, f]`
+    Parametric([f32; 7]),
+}
+
+impl Curve {
+    fn linear(&self, x: f32) -> f32 {
+        match self {
+            Self::Gamma(g) => x.powf(*g),
+            Self::Table(table) => {
+                let pos = x * (table.len() - 1) as f32;
+                let i = (pos as usize).min(table.len() - 2);
+                let frac = pos - i as f32;
+                table[i] * (1.0 - frac) + table[i + 1] * frac
+            },
+            Self::Parametric([g, a, b, c, d, e, f]) => {
+                if x >= *d {
+                    (a * x + b).max(0.0).powf(*g) + e
+                } else {
+                    c * x + f
+                }
+            },
+        }
+    }
+}
+
+/// The parts of a matrix/TRC RGB profile needed to convert to sRGB
+struct Profile {
+    /// Columns are the XYZ of the red, green and blue primaries
+    to_xyz: [[f32; 3]; 3],
+    curves: [Curve; 3],
+}
+
+fn s15_fixed16(data: &[u8], at: usize) -> Option<f32> {
+    Some(be_u32(data, at)? as i32 as f32 / 65536.0)
+}
+
+fn parse_curve(tag: &[u8]) -> Option<Curve> {
+    match tag.get(..4)? {
+        b"curv" => {
+            let count = be_u32(tag, 8)? as usize;
+            match count {
+                0 => Some(Curve::Gamma(1.0)),
+                1 => Some(Curve::Gamma(f32::from(be_u16(tag, 12)?) / 256.0)),
+                _ => (0..count)
+                    .map(|i| Some(f32::from(be_u16(tag, 12 + i * 2)?) / 65535.0))
+                    .collect::<Option<_>>()
+                    .map(Curve::Table),
+            }
+        },
+        b"para" => {
+            let param = |i: usize| s15_fixed16(tag, 12 + i * 4);
+            let g = param(0)?;
+            // Written as in the specification, with `d` the start of the
+            // power segment
+            let [a, b, c, d, e, f] = match be_u16(tag, 8)? {
+                0 => [1.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+                1 => {
+                    let (a, b) = (param(1)?, param(2)?);
+                    [a, b, 0.0, -b / a, 0.0, 0.0]
+                },
+                2 => {
+                    let (a, b, c) = (param(1)?, param(2)?, param(3)?);
+                    [a, b, 0.0, -b / a, c, c]
+                },
+                3 => [param(1)?, param(2)?, param(3)?, param(4)?, 0.0, 0.0],
+                4 => [
+                    param(1)?,
+                    param(2)?,
+                    param(3)?,
+                    param(4)?,
+                    param(5)?,
+                    param(6)?,
+                ],
+                _ => return None,
+            };
+            Some(Curve::Parametric([g, a, b, c, d, e, f]))
+        },
+        _ => None,
+    }
+}
+
+fn parse_profile(icc: &[u8]) -> Option<Profile> {
+    if icc.get(16..20)? != b"RGB " {
+        return None;
+    }
+    let tag = |signature: &[u8]| {
+        let count = be_u32(icc, 128)? as usize;
+        (0..count)
+            .map(|i| 132 + i * 12)
+            .find(|&entry| icc.get(entry..entry + 4) == Some(signature))
+            .and_then(|entry| {
+                let offset = be_u32(icc, entry + 4)? as usize;
+                let size = be_u32(icc, entry + 8)? as usize;
+                icc.get(offset..offset + size)
+            })
+    };
+    let primary = |signature: &[u8]| {
+        let xyz = tag(signature)?;
+        if xyz.get(..4)? != b"XYZ " {
+            return None;
+        }
+        Some([
+            s15_fixed16(xyz, 8)?,
+            s15_fixed16(xyz, 12)?,
+            s15_fixed16(xyz, 16)?,
+        ])
+    };
+    let [r, g, b] = [primary(b"rXYZ")?, primary(b"gXYZ")?, primary(b"bXYZ")?];
+    Some(Profile {
+        to_xyz: [0, 1, 2].map(|row| [r[row], g[row], b[row]]),
+        curves: [
+            parse_curve(tag(b"rTRC")?)?,
+            parse_curve(tag(b"gTRC")?)?,
+            parse_curve(tag(b"bTRC")?)?,
+        ],
+    })
+}
+
+/// Linear sRGB from XYZ relative to the D50 white of profiles, Bradford
+/// adapted
+const XYZ_D50_TO_SRGB: [[f32; 3]; 3] = [
+    [3.133_856, -1.616_867, -0.490_615],
+    [-0.978_768, 1.916_142, 0.033_454],
+    [0.071_945, -0.228_991, 1.405_243],
+];
+
+fn mul(a: [[f32; 3]; 3], b: [[f32; 3]; 3]) -> [[f32; 3]; 3] {
+    [0, 1, 2].map(|row| [0, 1, 2].map(|col| (0..3).map(|k| a[row][k] * b[k][col]).sum::<f32>()))
+}
+
+fn srgb_to_linear(x: f32) -> f32 {
+    if x <= 0.04045 {
+        x / 12.92
+    } else {
+        ((x + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(x: f32) -> f32 {
+    if x <= 0.003_130_8 {
+        x * 12.92
+    } else {
+        1.055 * x.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Converts `image` from the color space of `profile` to sRGB. Returns `None`
+/// when the profile is already close enough to sRGB to leave the pixels alone.
+fn convert_to_srgb(image: &DynamicImage, profile: &Profile) -> Option<RgbaImage> {
+    let matrix = mul(XYZ_D50_TO_SRGB, profile.to_xyz);
+    let linear = [0, 1, 2].map(|c| {
+        (0..=255)
+            .map(|v| profile.curves[c].linear(v as f32 / 255.0))
+            .collect::<Vec<_>>()
+    });
+    let identity_matrix = (0..3)
+        .all(|row| (0..3).all(|col| (matrix[row][col] - (row == col) as u8 as f32).abs() < 2e-3));
+    let srgb_curves = linear.iter().all(|curve| {
+        curve
+            .iter()
+            .enumerate()
+            .all(|(v, l)| (l - srgb_to_linear(v as f32 / 255.0)).abs() < 0.5 / 255.0)
+    });
+    if identity_matrix && srgb_curves {
+        return None;
+    }
+
+    // Encoding is done through a table, fine enough for 8 bit output
+    const ENCODE_STEPS: usize = 4096;
+    let encode = (0..=ENCODE_STEPS)
+        .map(|i| (linear_to_srgb(i as f32 / ENCODE_STEPS as f32) * 255.0).round() as u8)
+        .collect::<Vec<_>>();
+    let mut rgba = image.to_rgba8();
+    for pixel in rgba.pixels_mut() {
+        let lin = [0, 1, 2].map(|c| linear[c][usize::from(pixel[c])]);
+        for (c, row) in matrix.iter().enumerate() {
+            let value = (row[0] * lin[0] + row[1] * lin[1] + row[2] * lin[2]).clamp(0.0, 1.0);
+            pixel[c] = encode[(value * ENCODE_STEPS as f32).round() as usize];
+        }
+    }
+    Some(rgba)
+}
+
+fn premultiply(image: DynamicImage) -> DynamicImage {
+    let mut rgba = image.into_rgba8();
+    for pixel in rgba.pixels_mut() {
+        let alpha = u16::from(pixel[3]);
+        for c in 0..3 {
+            pixel[c] = ((u16::from(pixel[c]) * alpha + 127) / 255) as u8;
+        }
+    }
+    DynamicImage::ImageRgba8(rgba)
+}
+
+/// Applies the orientation and color profile stored in `content` to the image
+/// decoded from it, and premultiplies alpha if that is enabled
+pub(crate) fn apply(content: &[u8], mut image: DynamicImage) -> DynamicImage {
+    let meta = png_meta(content);
+    if let Some(profile) = meta
+        .icc_profile
+        .filter(|_| !meta.srgb)
+        .as_deref()
+        .and_then(parse_profile)
+    {
+        if let Some(converted) = convert_to_srgb(&image, &profile) {
+            image = DynamicImage::ImageRgba8(converted);
+        }
+    }
+    if let Some(orientation) = meta.orientation {
+        image = orient(image, orientation);
+    }
+    if premultiply_alpha() {
+        image = premultiply(image);
+    }
+    image
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    #[test]
+    fn exif_orientation_is_applied() {
+        // Big endian, one directory entry: orientation 6 as a short
+        let exif = b"MM\0*\0\0\0\x08\0\x01\x01\x12\0\x03\0\0\0\x01\0\x06\0\0";
+        assert_eq!(exif_orientation(exif), Some(6));
+        let little = b"II*\0\x08\0\0\0\x01\0\x12\x01\x03\0\x01\0\0\0\x03\0\0\0";
+        assert_eq!(exif_orientation(little), Some(3));
+
+        let mut image = RgbaImage::new(2, 1);
+        image.put_pixel(0, 0, Rgba([255, 0, 0, 255]));
+        let rotated = orient(DynamicImage::ImageRgba8(image), 6).into_rgba8();
+        assert_eq!(rotated.dimensions(), (1, 2));
+        assert_eq!(rotated.get_pixel(0, 0), &Rgba([255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn profile_curves_are_converted() {
+        let (a, b, c) = (1.0 / 1.055, 0.055 / 1.055, 1.0 / 12.92);
+        let curve = Curve::Parametric([2.4, a, b, c, 0.04045, 0.0, 0.0]);
+        let srgb = Profile {
+            to_xyz: [
+                [0.436_075, 0.385_065, 0.143_080],
+                [0.222_504, 0.716_879, 0.060_617],
+                [0.013_932, 0.097_105, 0.714_173],
+            ],
+            curves: [curve, Curve::Table(vec![0.0, 1.0]), Curve::Gamma(1.0)],
+        };
+        let image = DynamicImage::ImageRgba8(RgbaImage::from_pixel(1, 1, Rgba([128; 4])));
+        // Linear green and blue curves are brightened
+        let converted = convert_to_srgb(&image, &srgb).unwrap();
+        let pixel = converted.get_pixel(0, 0);
+        assert!((i32::from(pixel[0]) - 128).abs() <= 1);
+        assert!(pixel[1] > 180 && pixel[2] > 180);
+        assert_eq!(pixel[3], 128);
+    }
+
+    #[test]
+    fn premultiplied_alpha() {
+        let image = RgbaImage::from_pixel(1, 1, Rgba([200, 100, 0, 128]));
+        let premultiplied = premultiply(DynamicImage::ImageRgba8(image)).into_rgba8();
+        assert_eq!(premultiplied.get_pixel(0, 0), &Rgba([100, 50, 0, 128]));
+    }
+}
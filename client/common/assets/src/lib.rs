@@ -19,6 +19,33 @@ pub use assets_manager::{
     Asset, AssetCache, BoxedError, Compound, Error, SharedString,
 };
 
+mod async_load;
+pub use async_load::{
+    pending_async_loads, process_async_loads, process_async_loads_within, AssetFuture,
+    LoadPriority,
+};
+mod alias;
+pub use alias::resolve_alias;
+mod diagnostics;
+pub use diagnostics::{asset_diagnostics, try_asset_diagnostics, AssetDiagnostic};
+mod dir_loader;
+pub use dir_loader::{manifest_in_dir, DirFilter, ManifestDirLoader};
+mod find;
+pub use find::find;
+pub mod font;
+mod image_meta;
+pub use image_meta::{premultiply_alpha, set_premultiply_alpha};
+pub use font::{font_data, request_glyphs, take_font_changes};
+pub mod palette;
+mod preload;
+mod placeholder;
+pub use placeholder::{placeholder_substitutions, record_substitution, Placeholder, Substitution};
+mod profile;
+pub use profile::{load_profile, reset_load_profile, LoadProfile, LoadTiming};
+mod weak;
+pub use weak::WeakAssetHandle;
+pub use preload::{preload, PreloadManifest, PreloadProgress};
+
 #[cfg(target_arch = "wasm32")]
 mod wasm_fs;
 #[cfg(target_arch = "wasm32")]
@@ -26,6 +53,7 @@ use wasm_fs as fs;
 
 #[cfg(not(target_arch = "wasm32"))]
 mod fs;
+pub use fs::override_path;
 
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -40,6 +68,12 @@ lazy_static! {
     static ref ASSET_MAP: Mutex<HashMap<String, Vec<u8>>> = Mutex::new(HashMap::new());
 
     static ref ASSET_MAP_DIR: Mutex<HashMap<String, bool>> = Mutex::new(HashMap::new());
+
+    // Files which are only downloaded once they are needed, whether the
+    // download was requested yet
+    static ref LAZY_FILES: Mutex<HashMap<String, bool>> = Mutex::new(HashMap::new());
+
+    static ref FETCH_HOOK: Mutex<Option<fn(&str)>> = Mutex::new(None);
 }
 
 pub enum ResourceError {
@@ -73,6 +107,38 @@ pub fn set_cache_data(name: &str, data: &[u8]) {
     ASSET_MAP.lock().unwrap().insert(name_str, vec);
 }
 
+//记录按需下载的文件, 通过js传入
+pub fn set_cache_lazy(name: &str) {
+    LAZY_FILES.lock().unwrap().entry(name.to_string()).or_insert(false);
+}
+
+/// Called with the name of a lazily downloaded file when it is first needed,
+/// the download should end up in [`set_cache_data`]
+pub fn set_fetch_hook(hook: fn(&str)) { *FETCH_HOOK.lock().unwrap() = Some(hook); }
+
+/// Whether the asset will be available once it was downloaded, requests the
+/// download if that didn't happen yet
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn fetch_lazy(id: &str, ext: &str) -> bool {
+    let name = format!("{}.{}", id, ext);
+    if ASSET_MAP.lock().unwrap().contains_key(&name) {
+        return false;
+    }
+    let mut lazy = LAZY_FILES.lock().unwrap();
+    match lazy.get_mut(&name) {
+        Some(requested) => {
+            if !*requested {
+                *requested = true;
+                if let Some(hook) = *FETCH_HOOK.lock().unwrap() {
+                    hook(&name);
+                }
+            }
+            true
+        },
+        None => false,
+    }
+}
+
 //获取缓存data
 pub fn get_cache_data<'a,'b>(id: &'a str, ext: &'a str) -> Result<Cow<'b, [u8]>,ResourceError>  {
     let mut name = String::from(id);
@@ -138,6 +204,16 @@ pub trait AssetExt: Sized + Send + Sync + 'static {
         Self::load(specifier).unwrap_or_else(|err| Self::get_or_insert(specifier, default(err)))
     }
 
+    /// Like [`AssetExt::load_expect`], but a broken or missing asset is
+    /// replaced by its placeholder instead of panicking. Substitutions are
+    /// logged and listed by [`placeholder_substitutions`].
+    fn load_or_placeholder(specifier: &str) -> AssetHandle<Self>
+    where
+        Self: Placeholder,
+    {
+        Self::load_or_insert_with(specifier, placeholder::substitute)
+    }
+
     /// Function used to load essential assets from the filesystem or the cache.
     /// It will panic if the asset is not found. Example usage:
     /// ```no_run
@@ -174,6 +250,19 @@ pub trait AssetExt: Sized + Send + Sync + 'static {
         Self::load_expect(specifier).cloned()
     }
 
+    /// Load the asset on the background loader, more urgent priorities are
+    /// loaded first. Example usage:
+    /// ```no_run
+    /// use veloren_common_assets::{AssetExt, Image, LoadPriority};
+    ///
+    /// let pending = Image::load_async("core.ui.backgrounds.city", LoadPriority::Critical);
+    /// // ...
+    /// let my_image = pending.wait().unwrap();
+    /// ```
+    fn load_async(specifier: &str, priority: LoadPriority) -> AssetFuture<Self> {
+        async_load::enqueue(specifier, priority, Self::load)
+    }
+
     fn load_owned(specifier: &str) -> Result<Self, Error>;
 
     fn get_or_insert(specifier: &str, default: Self) -> AssetHandle<Self>;
@@ -192,17 +281,25 @@ pub fn load_dir<T: DirLoadable>(
 ) -> Result<AssetDirHandle<T>, Error> {
 
     let specifier = specifier.strip_suffix(".*").unwrap_or(specifier);
-    ASSETS.load_dir(specifier)
+    ASSETS.load_dir(&resolve_alias(specifier))
 }
 
 
 impl<T: Compound> AssetExt for T {
-    fn load(specifier: &str) -> Result<AssetHandle<Self>, Error> { ASSETS.load(specifier) }
+    fn load(specifier: &str) -> Result<AssetHandle<Self>, Error> {
+        let specifier = resolve_alias(specifier);
+        let _scope = profile::LoadScope::begin(&specifier);
+        ASSETS.load(&specifier)
+    }
 
-    fn load_owned(specifier: &str) -> Result<Self, Error> { ASSETS.load_owned(specifier) }
+    fn load_owned(specifier: &str) -> Result<Self, Error> {
+        let specifier = resolve_alias(specifier);
+        let _scope = profile::LoadScope::begin(&specifier);
+        ASSETS.load_owned(&specifier)
+    }
 
     fn get_or_insert(specifier: &str, default: Self) -> AssetHandle<Self> {
-        ASSETS.get_or_insert(specifier, default)
+        ASSETS.get_or_insert(&resolve_alias(specifier), default)
     }
 }
 
@@ -217,7 +314,10 @@ impl Loader<Image> for ImageLoader {
     fn load(content: Cow<[u8]>, ext: &str) -> Result<Image, BoxedError> {
         let format = image::ImageFormat::from_extension(ext)
             .ok_or_else(|| format!("Invalid file extension {}", ext))?;
+        let start = instant::Instant::now();
         let image = image::load_from_memory_with_format(&content, format)?;
+        let image = image_meta::apply(&content, image);
+        profile::record_decode(start.elapsed());
         Ok(Image(Arc::new(image)))
     }
 }
@@ -227,21 +327,43 @@ impl Asset for Image {
     const EXTENSIONS: &'static [&'static str] = &["png"];
 }
 
+/// A voxel model, `model#variant` loads the model with a color variant from
+/// its sidecar file applied, see [`palette`]
 pub struct DotVoxAsset(pub DotVoxData);
 
+/// A `.vox` file as it is stored, without color variants
+pub struct DotVoxFile(pub DotVoxData);
+
 pub struct DotVoxLoader;
-impl Loader<DotVoxAsset> for DotVoxLoader {
-    fn load(content: std::borrow::Cow<[u8]>, _: &str) -> Result<DotVoxAsset, BoxedError> {
+impl Loader<DotVoxFile> for DotVoxLoader {
+    fn load(content: std::borrow::Cow<[u8]>, _: &str) -> Result<DotVoxFile, BoxedError> {
+        let start = instant::Instant::now();
         let data = dot_vox::load_bytes(&content).map_err(|err| err.to_owned())?;
-        Ok(DotVoxAsset(data))
+        profile::record_decode(start.elapsed());
+        Ok(DotVoxFile(data))
     }
 }
 
-impl Asset for DotVoxAsset {
+impl Asset for DotVoxFile {
     type Loader = DotVoxLoader;
     const EXTENSION: &'static str = "vox";
 }
 
+impl Compound for DotVoxAsset {
+    fn load<S: Source + ?Sized>(cache: &AssetCache<S>, id: &str) -> Result<Self, BoxedError> {
+        let (model, variant) = match id.split_once('#') {
+            Some((model, variant)) => (model, Some(variant)),
+            None => (id, None),
+        };
+        // Owned, so the file isn't kept in the cache next to the model
+        let DotVoxFile(mut data) = cache.load_owned::<DotVoxFile>(model)?;
+        if let Some(variant) = variant {
+            palette::apply_variant(cache, model, variant, &mut data)?;
+        }
+        Ok(DotVoxAsset(data))
+    }
+}
+
 
 
 
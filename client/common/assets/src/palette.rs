@@ -0,0 +1,117 @@
+//! Color variants of voxel models, so team colors or seasonal looks don't need
+//! a copy of the `.vox` file each
+//!
+//! The variants of a model are listed in a file next to it, named like the
+//! model with a `_palettes` suffix. Loading `voxel.object.banner#winter` as a
+//! [`DotVoxAsset`](crate::DotVoxAsset) applies the `winter` entry of
+//! `voxel/object/banner_palettes.ron` to the palette of `banner.vox`:
+//!
+//! ```ron
+//! {
+//!     "winter": (
+//!         colors: [((76, 120, 38), (230, 235, 240))],
+//!     ),
+//!     "red_team": (
+//!         indices: {7: (200, 30, 30)},
+//!     ),
+//! }
+//! ```
+//!
+//! Each variant is cached on its own like any other asset.
+use crate::{AssetCache, BoxedError, Ron, Source};
+use dot_vox::DotVoxData;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Suffix of the file with the variants of a model
+pub const PALETTE_SUFFIX: &str = "_palettes";
+
+type Rgb = (u8, u8, u8);
+
+/// The changes of one variant, alpha is kept as it is
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct PaletteRemap {
+    /// New colors by palette index, as used by the voxels of the file. The
+    /// indices shown by MagicaVoxel are one higher.
+    #[serde(default)]
+    pub indices: HashMap<u8, Rgb>,
+    /// Replaces every entry of the first color with the second one
+    #[serde(default)]
+    pub colors: Vec<(Rgb, Rgb)>,
+}
+
+/// The variants of a model by name
+pub type PaletteVariants = HashMap<String, PaletteRemap>;
+
+fn rgb(color: u32) -> Rgb {
+    let [r, g, b, _] = color.to_ne_bytes();
+    (r, g, b)
+}
+
+fn with_rgb(color: u32, (r, g, b): Rgb) -> u32 {
+    let [_, _, _, a] = color.to_ne_bytes();
+    u32::from_ne_bytes([r, g, b, a])
+}
+
+impl PaletteRemap {
+    /// Colors are matched against the original palette, so swapping two
+    /// colors works
+    pub fn apply(&self, palette: &mut [u32]) {
+        let original = palette.to_vec();
+        for (color, old) in palette.iter_mut().zip(original) {
+            if let Some((_, new)) = self.colors.iter().find(|(from, _)| *from == rgb(old)) {
+                *color = with_rgb(old, *new);
+            }
+        }
+        for (&index, &new) in &self.indices {
+            if let Some(color) = palette.get_mut(usize::from(index)) {
+                *color = with_rgb(*color, new);
+            }
+        }
+    }
+}
+
+/// Applies `variant` of the sidecar file of `model` to `data`
+pub(crate) fn apply_variant<S: Source + ?Sized>(
+    cache: &AssetCache<S>,
+    model: &str,
+    variant: &str,
+    data: &mut DotVoxData,
+) -> Result<(), BoxedError> {
+    let variants = cache.load::<Ron<PaletteVariants>>(&[model, PALETTE_SUFFIX].concat())?;
+    let variants = variants.read();
+    let remap = variants
+        .0
+        .get(variant)
+        .ok_or_else(|| format!("{} has no palette variant {:?}", model, variant))?;
+    remap.apply(&mut data.palette);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remap_palette() {
+        let color = |r, g, b, a| u32::from_ne_bytes([r, g, b, a]);
+        let mut palette = vec![
+            color(10, 20, 30, 255),
+            color(40, 50, 60, 128),
+            color(10, 20, 30, 255),
+            color(1, 1, 1, 255),
+        ];
+        let remap: PaletteRemap = ron::from_str(
+            "(colors: [((10, 20, 30), (40, 50, 60)), ((40, 50, 60), (10, 20, 30))], \
+             indices: {3: (9, 9, 9), 200: (0, 0, 0)})",
+        )
+        .unwrap();
+        remap.apply(&mut palette);
+        assert_eq!(palette, vec![
+            color(40, 50, 60, 255),
+            color(10, 20, 30, 128),
+            color(40, 50, 60, 255),
+            color(9, 9, 9, 255),
+        ]);
+    }
+}
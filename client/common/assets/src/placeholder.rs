@@ -0,0 +1,84 @@
+//! Stand-ins for assets which failed to load
+use crate::{
+    diagnostics::{self, AssetDiagnostic},
+    DotVoxAsset, Error, Image,
+};
+use dot_vox::DotVoxData;
+use image::{DynamicImage, Rgba, RgbaImage};
+use std::{any::type_name, sync::Arc};
+
+/// Assets which have a value to use when the real one can't be loaded, see
+/// [`crate::AssetExt::load_or_placeholder`]
+pub trait Placeholder: Sized {
+    fn placeholder() -> Self;
+}
+
+/// Size of the sides of the placeholder image
+const CHECKER_SIZE: u32 = 16;
+const CHECKER_TILE: u32 = 4;
+
+impl Placeholder for Image {
+    /// Magenta and black checker board, hard to miss in game
+    fn placeholder() -> Self {
+        let image = RgbaImage::from_fn(CHECKER_SIZE, CHECKER_SIZE, |x, y| {
+            if (x / CHECKER_TILE + y / CHECKER_TILE) % 2 == 0 {
+                Rgba([255, 0, 255, 255])
+            } else {
+                Rgba([0, 0, 0, 255])
+            }
+        });
+        Image(Arc::new(DynamicImage::ImageRgba8(image)))
+    }
+}
+
+impl Placeholder for DotVoxAsset {
+    fn placeholder() -> Self {
+        DotVoxAsset(DotVoxData {
+            version: 150,
+            models: Vec::new(),
+            palette: Vec::new(),
+            materials: Vec::new(),
+        })
+    }
+}
+
+/// An asset which was replaced by its placeholder
+#[derive(Clone, Debug)]
+pub struct Substitution {
+    pub specifier: String,
+    pub asset_type: &'static str,
+    pub reason: String,
+}
+
+/// Remember that `specifier` was replaced, for assets loaded outside of
+/// [`crate::AssetExt::load_or_placeholder`]
+pub fn record_substitution<T>(specifier: &str, error: &dyn std::fmt::Debug) {
+    let substitution = Substitution {
+        specifier: specifier.to_owned(),
+        asset_type: type_name::<T>(),
+        reason: format!("{:?}", error),
+    };
+    log::warn!(
+        "Using a placeholder for {} ({}): {}",
+        substitution.specifier,
+        substitution.asset_type,
+        substitution.reason
+    );
+    diagnostics::record(AssetDiagnostic::Placeholder(substitution));
+}
+
+pub(crate) fn substitute<T: Placeholder>(error: Error) -> T {
+    record_substitution::<T>(error.id(), &error.reason());
+    T::placeholder()
+}
+
+/// All assets replaced by placeholders so far
+pub fn placeholder_substitutions() -> Vec<Substitution> {
+    diagnostics::asset_diagnostics()
+        .into_iter()
+        .filter_map(|diagnostic| match diagnostic {
+            AssetDiagnostic::Placeholder(substitution) => Some(substitution),
+            _ => None,
+        })
+        .collect()
+}
@@ -0,0 +1,132 @@
+//! Loading groups of assets listed in a manifest
+//!
+//! A preload manifest is a RON file listing what a screen needs:
+//! ```ron
+//! (
+//!     priority: Critical,
+//!     images: ["voxygen.element.v_logo"],
+//!     voxels: [],
+//! )
+//! ```
+use crate::{AssetExt, AssetFuture, DotVoxAsset, Error, Image, LoadPriority, RonLoader};
+use serde::Deserialize;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PreloadManifest {
+    #[serde(default = "default_priority")]
+    pub priority: LoadPriority,
+    #[serde(default)]
+    pub images: Vec<String>,
+    #[serde(default)]
+    pub voxels: Vec<String>,
+    /// Other manifests whose assets are loaded too
+    #[serde(default)]
+    pub include: Vec<String>,
+}
+
+fn default_priority() -> LoadPriority { LoadPriority::Normal }
+
+impl crate::Asset for PreloadManifest {
+    type Loader = RonLoader;
+
+    const EXTENSION: &'static str = "ron";
+}
+
+trait Pending: Send {
+    fn specifier(&self) -> &str;
+    /// `None` while still loading
+    fn poll(&mut self) -> Option<Result<(), Error>>;
+}
+
+impl<T: Send + Sync + 'static> Pending for AssetFuture<T> {
+    fn specifier(&self) -> &str { AssetFuture::specifier(self) }
+
+    fn poll(&mut self) -> Option<Result<(), Error>> { Some(self.try_take()?.map(|_| ())) }
+}
+
+/// The state of a batch of loads started by [`preload`]
+#[derive(Default)]
+pub struct PreloadProgress {
+    pending: Vec<Box<dyn Pending>>,
+    loaded: usize,
+    failed: Vec<Error>,
+}
+
+impl PreloadProgress {
+    /// Track an asset loaded outside of the manifest
+    pub fn push<T: Send + Sync + 'static>(&mut self, future: AssetFuture<T>) {
+        self.pending.push(Box::new(future));
+    }
+
+    /// Collect the loads which finished since the last update
+    pub fn update(&mut self) {
+        let mut i = 0;
+        while i < self.pending.len() {
+            match self.pending[i].poll() {
+                Some(Ok(())) => self.loaded += 1,
+                Some(Err(e)) => {
+                    log::warn!("Failed to preload {}: {:?}", self.pending[i].specifier(), e);
+                    self.failed.push(e);
+                },
+                None => {
+                    i += 1;
+                    continue;
+                },
+            }
+            self.pending.swap_remove(i);
+        }
+    }
+
+    pub fn total(&self) -> usize { self.pending.len() + self.loaded + self.failed.len() }
+
+    /// Finished loads, failed ones included
+    pub fn finished(&self) -> usize { self.loaded + self.failed.len() }
+
+    /// Between 0.0 and 1.0, as of the last [`PreloadProgress::update`]
+    pub fn fraction(&self) -> f32 {
+        match self.total() {
+            0 => 1.0,
+            total => self.finished() as f32 / total as f32,
+        }
+    }
+
+    pub fn is_done(&self) -> bool { self.pending.is_empty() }
+
+    pub fn errors(&self) -> &[Error] { &self.failed }
+}
+
+/// Queue every asset of the manifest on the background loader
+///
+/// ```no_run
+/// let mut progress = veloren_common_assets::preload("voxygen.preload.main_menu").unwrap();
+/// progress.update();
+/// println!("{:.0}%", progress.fraction() * 100.0);
+/// ```
+pub fn preload(specifier: &str) -> Result<PreloadProgress, Error> {
+    let mut progress = PreloadProgress::default();
+    queue_manifest(specifier, &mut progress, &mut Vec::new())?;
+    Ok(progress)
+}
+
+fn queue_manifest(
+    specifier: &str,
+    progress: &mut PreloadProgress,
+    visited: &mut Vec<String>,
+) -> Result<(), Error> {
+    if visited.iter().any(|v| v == specifier) {
+        return Ok(());
+    }
+    visited.push(specifier.to_owned());
+
+    let manifest = PreloadManifest::load_cloned(specifier)?;
+    for image in &manifest.images {
+        progress.push(Image::load_async(image, manifest.priority));
+    }
+    for voxel in &manifest.voxels {
+        progress.push(DotVoxAsset::load_async(voxel, manifest.priority));
+    }
+    for include in &manifest.include {
+        queue_manifest(include, progress, visited)?;
+    }
+    Ok(())
+}
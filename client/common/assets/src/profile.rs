@@ -0,0 +1,219 @@
+//! Timing of asset loads, split by the phase the time was spent in
+use instant::{Duration, Instant};
+use lazy_static::lazy_static;
+use std::{cell::RefCell, collections::HashMap, fmt, sync::Mutex};
+
+/// Time spent loading one asset, summed over all the times it was loaded
+#[derive(Clone, Debug, Default)]
+pub struct LoadTiming {
+    /// Loads which missed the cache
+    pub loads: u32,
+    /// Time spent in the background queue before the load started
+    pub queue_wait: Duration,
+    /// Reading the file
+    pub io: Duration,
+    /// Turning the bytes into the asset. Only measured for the loaders of this
+    /// crate, for others it is counted as post processing.
+    pub decode: Duration,
+    /// Everything else, e.g. building a compound out of its parts
+    pub post_process: Duration,
+}
+
+impl LoadTiming {
+    pub fn total(&self) -> Duration { self.queue_wait + self.io + self.decode + self.post_process }
+
+    fn add(&mut self, other: &LoadTiming) {
+        self.loads += other.loads;
+        self.queue_wait += other.queue_wait;
+        self.io += other.io;
+        self.decode += other.decode;
+        self.post_process += other.post_process;
+    }
+}
+
+/// Snapshot of all timings, see [`load_profile`]
+#[derive(Clone, Debug, Default)]
+pub struct LoadProfile {
+    /// Slowest assets first
+    pub entries: Vec<(String, LoadTiming)>,
+}
+
+impl LoadProfile {
+    pub fn totals(&self) -> LoadTiming {
+        let mut totals = LoadTiming::default();
+        for (_, timing) in &self.entries {
+            totals.add(timing);
+        }
+        totals
+    }
+}
+
+impl fmt::Display for LoadProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn row(f: &mut fmt::Formatter<'_>, name: &str, t: &LoadTiming) -> fmt::Result {
+            writeln!(
+                f,
+                "{:<60} {:>5} {:>10.2?} {:>10.2?} {:>10.2?} {:>10.2?}",
+                name, t.loads, t.queue_wait, t.io, t.decode, t.post_process
+            )
+        }
+        writeln!(
+            f,
+            "{:<60} {:>5} {:>10} {:>10} {:>10} {:>10}",
+            "asset", "loads", "queue", "io", "decode", "post"
+        )?;
+        row(f, "total", &self.totals())?;
+        for (specifier, timing) in self.entries.iter().take(PROFILE_REPORT_ROWS) {
+            row(f, specifier, timing)?;
+        }
+        Ok(())
+    }
+}
+
+const PROFILE_REPORT_ROWS: usize = 20;
+
+lazy_static! {
+    static ref PROFILE: Mutex<HashMap<String, LoadTiming>> = Mutex::new(HashMap::new());
+}
+
+struct Frame {
+    specifier: String,
+    start: Instant,
+    io: Duration,
+    decode: Duration,
+    /// Time spent in loads nested in this one
+    nested: Duration,
+    #[cfg(feature = "tracing")]
+    _span: tracing::span::EnteredSpan,
+}
+
+thread_local! {
+    static STACK: RefCell<Vec<Frame>> = RefCell::new(Vec::new());
+}
+
+/// Measures a load until dropped, loads started in between are measured on
+/// their own
+pub(crate) struct LoadScope(());
+
+impl LoadScope {
+    pub(crate) fn begin(specifier: &str) -> Self {
+        STACK.with(|stack| {
+            stack.borrow_mut().push(Frame {
+                specifier: specifier.to_owned(),
+                start: Instant::now(),
+                io: Duration::ZERO,
+                decode: Duration::ZERO,
+                nested: Duration::ZERO,
+                #[cfg(feature = "tracing")]
+                _span: tracing::trace_span!("load_asset", specifier).entered(),
+            })
+        });
+        Self(())
+    }
+}
+
+impl Drop for LoadScope {
+    fn drop(&mut self) {
+        STACK.with(|stack| {
+            let mut stack = stack.borrow_mut();
+            let frame = match stack.pop() {
+                Some(frame) => frame,
+                None => return,
+            };
+            let elapsed = frame.start.elapsed();
+            if let Some(parent) = stack.last_mut() {
+                parent.nested += elapsed;
+            }
+            // Nothing was read, so the asset came from the cache
+            if frame.io == Duration::ZERO && frame.decode == Duration::ZERO {
+                return;
+            }
+            let post_process = elapsed
+                .saturating_sub(frame.io)
+                .saturating_sub(frame.decode)
+                .saturating_sub(frame.nested);
+            PROFILE
+                .lock()
+                .unwrap()
+                .entry(frame.specifier)
+                .or_default()
+                .add(&LoadTiming {
+                    loads: 1,
+                    io: frame.io,
+                    decode: frame.decode,
+                    post_process,
+                    ..Default::default()
+                });
+        })
+    }
+}
+
+pub(crate) fn record_io(time: Duration) {
+    STACK.with(|stack| {
+        if let Some(frame) = stack.borrow_mut().last_mut() {
+            frame.io += time;
+        }
+    })
+}
+
+pub(crate) fn record_decode(time: Duration) {
+    STACK.with(|stack| {
+        if let Some(frame) = stack.borrow_mut().last_mut() {
+            frame.decode += time;
+        }
+    })
+}
+
+pub(crate) fn record_queue_wait(specifier: &str, time: Duration) {
+    PROFILE
+        .lock()
+        .unwrap()
+        .entry(specifier.to_owned())
+        .or_default()
+        .queue_wait += time;
+}
+
+/// Timings of all asset loads which missed the cache so far
+pub fn load_profile() -> LoadProfile {
+    let mut entries = PROFILE
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(specifier, timing)| (specifier.clone(), timing.clone()))
+        .collect::<Vec<_>>();
+    entries.sort_by(|(_, a), (_, b)| b.total().cmp(&a.total()));
+    LoadProfile { entries }
+}
+
+pub fn reset_load_profile() { PROFILE.lock().unwrap().clear(); }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_loads() {
+        {
+            let _outer = LoadScope::begin("test.outer");
+            record_io(Duration::from_millis(2));
+            {
+                let _inner = LoadScope::begin("test.inner");
+                record_decode(Duration::from_millis(3));
+            }
+            // A cache hit isn't recorded
+            let _cached = LoadScope::begin("test.cached");
+        }
+        let profile = load_profile();
+        let get = |name: &str| {
+            profile
+                .entries
+                .iter()
+                .find(|(specifier, _)| specifier == name)
+                .map(|(_, timing)| timing.clone())
+        };
+        let outer = get("test.outer").unwrap();
+        assert_eq!((outer.loads, outer.io), (1, Duration::from_millis(2)));
+        assert_eq!(get("test.inner").unwrap().decode, Duration::from_millis(3));
+        assert!(get("test.cached").is_none());
+    }
+}
@@ -1,8 +1,12 @@
-use std::{borrow::Cow, io};
+use crate::alias::resolve_source_id;
+use std::{borrow::Cow, io, path::PathBuf};
 use assets_manager::{
     source::{DirEntry, Source},
 };
 
+/// There is no file system to override assets from on the web
+pub fn override_path() -> Option<PathBuf> { None }
+
 /// Loads assets from the default path or `VELOREN_ASSETS_OVERRIDE` env if it is
 /// set.
 #[derive(Debug, Clone)]
@@ -10,10 +14,8 @@ pub struct ResSystem {}
 
 impl ResSystem {
     pub fn new() -> io::Result<Self> { Ok(Self {}) }
-}
 
-impl Source for ResSystem {
-    fn read(&self, id: &str, ext: &str) -> io::Result<Cow<[u8]>> {
+    fn read_impl(&self, id: &str, ext: &str) -> io::Result<Cow<[u8]>> {
 
         let result = super::get_cache_data(id, ext);
         match result {
@@ -26,8 +28,34 @@ impl Source for ResSystem {
         }
     }
 
-    fn read_dir(&self, id: &str, f: &mut dyn FnMut(DirEntry)) -> io::Result<()> {
+    /// Ids of the files directly in `dir`, including the ones which are only
+    /// downloaded once needed. Ids with several files appear more than once.
+    pub(crate) fn file_ids(&self, dir: &str) -> Vec<String> {
+        let prefix = if dir.is_empty() { String::new() } else { [dir, "."].concat() };
+        let files = super::ASSET_MAP.lock().unwrap();
+        let lazy = super::LAZY_FILES.lock().unwrap();
+        files
+            .keys()
+            .chain(lazy.keys())
+            .filter_map(|name| {
+                let (id, _ext) = name.rsplit_once('.')?;
+                let file = id.strip_prefix(&prefix)?;
+                (!file.is_empty() && !file.contains('.')).then(|| id.to_owned())
+            })
+            .collect()
+    }
+}
+
+impl Source for ResSystem {
+    fn read(&self, id: &str, ext: &str) -> io::Result<Cow<[u8]>> {
+        let start = instant::Instant::now();
+        let result = self.read_impl(&resolve_source_id(id), ext);
+        super::profile::record_io(start.elapsed());
+        result
+    }
 
+    fn read_dir(&self, id: &str, f: &mut dyn FnMut(DirEntry)) -> io::Result<()> {
+        let id = &*resolve_source_id(id);
         let map = super::ASSET_MAP_DIR.lock().unwrap();
         for key in map.keys() {
             if key.starts_with(id) {
@@ -53,7 +81,7 @@ impl Source for ResSystem {
 
         //判断文件或者文件夹是否存在
         if let DirEntry::File(id, ext) = entry {
-            let mut name = String::from(id);
+            let mut name = String::from(resolve_source_id(id));
             name.push_str(&".");
             name.push_str(ext);
 
@@ -64,7 +92,7 @@ impl Source for ResSystem {
 
         } else if let DirEntry::Directory(dir) = entry {
             let map = super::ASSET_MAP_DIR.lock().unwrap();
-            if map.contains_key(dir) {
+            if map.contains_key(&*resolve_source_id(dir)) {
                 return true
             }
         }
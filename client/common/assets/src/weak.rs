@@ -0,0 +1,65 @@
+//! Handles which refer to an asset without keeping it in use
+use crate::{AssetExt, AssetHandle, Compound, Error, SharedString, ASSETS};
+use std::{fmt, marker::PhantomData};
+
+/// Refers to an asset by its specifier
+///
+/// Unlike [`AssetHandle`] this doesn't borrow the cached asset. Long lived
+/// structs which only need an asset now and then should keep one of these and
+/// [`WeakAssetHandle::upgrade`] it when needed, so that whatever they derive
+/// from the asset (e.g. a decoded ui graphic) can be dropped in between.
+///
+/// The asset cache itself never evicts assets yet, so `upgrade` currently
+/// always succeeds once the asset was loaded.
+pub struct WeakAssetHandle<T> {
+    specifier: SharedString,
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T: Compound> WeakAssetHandle<T> {
+    pub fn new(handle: &AssetHandle<T>) -> Self {
+        Self {
+            specifier: handle.id().into(),
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn specifier(&self) -> &str { &self.specifier }
+
+    /// The asset if it is still cached
+    pub fn upgrade(&self) -> Option<AssetHandle<T>> { ASSETS.get_cached(&self.specifier) }
+
+    /// The asset, loading it again if it was dropped from the cache
+    pub fn upgrade_or_load(&self) -> Result<AssetHandle<T>, Error> {
+        self.upgrade().map_or_else(|| T::load(&self.specifier), Ok)
+    }
+}
+
+impl<T> Clone for WeakAssetHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            specifier: self.specifier.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for WeakAssetHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("WeakAssetHandle").field(&&*self.specifier).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn upgrade() {
+        let handle = String::get_or_insert("test.weak_handle", "cached".to_owned());
+        let weak = WeakAssetHandle::new(&handle);
+        assert_eq!(weak.specifier(), "test.weak_handle");
+        assert_eq!(weak.upgrade().map(|h| h.cloned()).as_deref(), Some("cached"));
+        assert!(weak.clone().upgrade_or_load().is_ok());
+    }
+}
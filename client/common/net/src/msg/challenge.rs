@@ -0,0 +1,87 @@
+//! Challenges a server can require to be solved before a login is accepted,
+//! to make bot floods expensive
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Difficulties above this are refused by clients, they would never finish
+pub const MAX_PROOF_OF_WORK_DIFFICULTY: u8 = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LoginChallenge {
+    /// A PNG image with a text the player has to type in
+    Captcha { image: Vec<u8> },
+    /// Find a nonce for which [`proof_of_work_valid`] holds, this takes about
+    /// `2^difficulty` hashes
+    ProofOfWork { seed: [u8; 16], difficulty: u8 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChallengeAnswer {
+    Captcha(String),
+    ProofOfWork(u64),
+}
+
+/// Whether the SHA-256 hash of `seed` followed by `nonce` starts with
+/// `difficulty` zero bits
+pub fn proof_of_work_valid(seed: &[u8; 16], difficulty: u8, nonce: u64) -> bool {
+    let hash = Sha256::new()
+        .chain(seed)
+        .chain(nonce.to_le_bytes())
+        .finalize();
+    let mut zeros = 0;
+    for byte in hash.iter() {
+        zeros += byte.leading_zeros();
+        if *byte != 0 {
+            break;
+        }
+    }
+    zeros >= u32::from(difficulty)
+}
+
+/// Searches the smallest valid nonce, `None` if the difficulty is too high
+pub fn solve_proof_of_work(seed: &[u8; 16], difficulty: u8) -> Option<u64> {
+    if difficulty > MAX_PROOF_OF_WORK_DIFFICULTY {
+        return None;
+    }
+    (0..).find(|nonce| proof_of_work_valid(seed, difficulty, *nonce))
+}
+
+impl ChallengeAnswer {
+    /// Captcha answers are compared ignoring case and surrounding whitespace
+    pub fn solves(&self, challenge: &LoginChallenge, captcha_text: &str) -> bool {
+        match (self, challenge) {
+            (ChallengeAnswer::Captcha(answer), LoginChallenge::Captcha { .. }) => {
+                answer.trim().eq_ignore_ascii_case(captcha_text.trim())
+            },
+            (
+                ChallengeAnswer::ProofOfWork(nonce),
+                LoginChallenge::ProofOfWork { seed, difficulty },
+            ) => proof_of_work_valid(seed, *difficulty, *nonce),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn proof_of_work() {
+        let seed = [7; 16];
+        let nonce = solve_proof_of_work(&seed, 12).unwrap();
+        let challenge = LoginChallenge::ProofOfWork {
+            seed,
+            difficulty: 12,
+        };
+        assert!(ChallengeAnswer::ProofOfWork(nonce).solves(&challenge, ""));
+        // the smallest valid nonce was returned
+        assert!((0..nonce).all(|n| !proof_of_work_valid(&seed, 12, n)));
+        assert!(!ChallengeAnswer::Captcha("x".into()).solves(&challenge, "x"));
+        assert_eq!(solve_proof_of_work(&seed, 40), None);
+
+        let captcha = LoginChallenge::Captcha { image: vec![] };
+        assert!(ChallengeAnswer::Captcha(" Vel0ren ".into()).solves(&captcha, "vel0ren"));
+        assert!(!ChallengeAnswer::Captcha("veloren".into()).solves(&captcha, "vel0ren"));
+    }
+}
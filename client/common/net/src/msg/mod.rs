@@ -1,3 +1,4 @@
+pub mod challenge;
 pub mod client;
 pub mod compression;
 pub mod ecs_packet;
@@ -6,6 +7,7 @@ pub mod world_msg;
 
 // Reexports
 pub use self::{
+    challenge::{ChallengeAnswer, LoginChallenge},
     client::{ClientGeneral, ClientMsg, ClientRegister, ClientType},
     compression::{
         CompressedData, GridLtrPacking, PackingFormula, QuadPngEncoding, TriPngEncoding,
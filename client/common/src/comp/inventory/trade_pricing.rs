@@ -1,5 +1,6 @@
 use crate::{
     assets::{self, AssetExt},
+    comp::item::modular,
     lottery::LootSpec,
     recipe::{default_recipe_book, RecipeInput},
     trade::Good,
@@ -11,6 +12,9 @@ use serde::Deserialize;
 use std::cmp::Ordering;
 
 const PRICING_DEBUG: bool = false;
+/// Mantissa bits kept by `round_price`, few enough for the result to be
+/// exactly representable as f32
+const PRICE_PRECISION_BITS: u32 = 20;
 
 #[derive(Default, Debug)]
 pub struct TradePricing {
@@ -20,6 +24,9 @@ pub struct TradePricing {
     potions: Entries,
     food: Entries,
     ingredients: Entries,
+    modular_components: Entries,
+    furniture: Entries,
+    pets: Entries,
     other: Entries,
 
     // good_scaling of coins
@@ -52,7 +59,7 @@ impl Entries {
             if PRICING_DEBUG {
                 log::info!("Update {} {}+{}", asset, old_probability, probability);
             }
-            *old_probability += probability;
+            *old_probability = round_price(f64::from(*old_probability) + f64::from(probability));
         } else {
             if PRICING_DEBUG {
                 log::info!("New {} {}", item_name, probability);
@@ -96,26 +103,29 @@ impl From<Vec<(f32, LootSpec<String>)>> for ProbabilityFile {
         let rescale = if content.is_empty() {
             1.0
         } else {
-            1.0 / content.iter().map(|e| e.0).sum::<f32>()
+            1.0 / content.iter().map(|e| f64::from(e.0)).sum::<f64>()
         };
         Self {
             content: content
                 .into_iter()
-                .flat_map(|(p0, loot)| match loot {
-                    LootSpec::Item(asset) => vec![(p0 * rescale, asset, 1.0)].into_iter(),
-                    LootSpec::ItemQuantity(asset, a, b) => {
-                        vec![(p0 * rescale, asset, (a + b) as f32 * 0.5)].into_iter()
-                    },
-                    LootSpec::LootTable(table_asset) => {
-                        let unscaled = &Self::load_expect(&table_asset).read().content;
-                        let scale = p0 * rescale;
-                        unscaled
+                .flat_map(|(p0, loot)| {
+                    let p0 = f64::from(p0) * rescale;
+                    match loot {
+                        LootSpec::Item(asset) => vec![(round_price(p0), asset, 1.0)].into_iter(),
+                        LootSpec::ItemQuantity(asset, a, b) => {
+                            vec![(round_price(p0), asset, (a + b) as f32 * 0.5)].into_iter()
+                        },
+                        LootSpec::LootTable(table_asset) => Self::load_expect(&table_asset)
+                            .read()
+                            .content
                             .iter()
-                            .map(|(p1, asset, amount)| (*p1 * scale, asset.clone(), *amount))
+                            .map(|(p1, asset, amount)| {
+                                (round_price(f64::from(*p1) * p0), asset.clone(), *amount)
+                            })
                             .collect::<Vec<_>>()
-                            .into_iter()
-                    },
-                    LootSpec::Nothing => Vec::new().into_iter(),
+                            .into_iter(),
+                        LootSpec::Nothing => Vec::new().into_iter(),
+                    }
                 })
                 .collect(),
         }
@@ -209,14 +219,29 @@ fn sort_and_normalize(entryvec: &mut [Entry], scale: f32) {
         });
         if let Some((_, max_scale, _)) = entryvec.last() {
             // most common item has frequency max_scale.  avoid NaN
-            let rescale = scale / max_scale;
+            let rescale = f64::from(scale) / f64::from(*max_scale);
             for i in entryvec.iter_mut() {
-                i.1 *= rescale;
+                i.1 = round_price(f64::from(i.1) * rescale);
             }
         }
     }
 }
 
+/// Rounds a value accumulated in f64 to one which is exactly representable as
+/// f32. It only uses integer operations on the bits, so prices come out the
+/// same on every platform and match the ones of the server.
+#[allow(clippy::cast_possible_truncation)]
+fn round_price(value: f64) -> f32 {
+    if !value.is_finite() {
+        return value as f32;
+    }
+    let dropped = 52 - PRICE_PRECISION_BITS;
+    let half = 1_u64 << (dropped - 1);
+    let mask = !((1_u64 << dropped) - 1);
+    // A carry out of the mantissa correctly rounds up to the next exponent
+    f64::from_bits((value.to_bits() + half) & mask) as f32
+}
+
 fn get_scaling(contents: &AssetGuard<TradingPriceFile>, good: Good) -> f32 {
     contents
         .good_scaling
@@ -241,6 +266,9 @@ impl TradePricing {
             Good::Potions => &self.potions.entries,
             Good::Food => &self.food.entries,
             Good::Ingredients => &self.ingredients.entries,
+            Good::ModularComponents => &self.modular_components.entries,
+            Good::Furniture => &self.furniture.entries,
+            Good::Pets => &self.pets.entries,
             _ => &[],
         }
     }
@@ -252,6 +280,9 @@ impl TradePricing {
             Good::Potions => &mut self.potions.entries,
             Good::Food => &mut self.food.entries,
             Good::Ingredients => &mut self.ingredients.entries,
+            Good::ModularComponents => &mut self.modular_components.entries,
+            Good::Furniture => &mut self.furniture.entries,
+            Good::Pets => &mut self.pets.entries,
             _ => &mut [],
         }
     }
@@ -263,6 +294,10 @@ impl TradePricing {
             // Tools
             _ if name.starts_with("common.items.weapons.") => &self.tools.entries,
             _ if name.starts_with("common.items.tool.") => &self.tools.entries,
+            // Modular components (before the generic crafting ingredients)
+            _ if name.starts_with("common.items.crafting_ing.modular.") => {
+                &self.modular_components.entries
+            },
             // Ingredients
             _ if name.starts_with("common.items.crafting_ing.") => &self.ingredients.entries,
             _ if name.starts_with("common.items.mineral.") => &self.ingredients.entries,
@@ -271,12 +306,17 @@ impl TradePricing {
             _ if name.starts_with("common.items.consumable.") => &self.potions.entries,
             // Food
             _ if name.starts_with("common.items.food.") => &self.food.entries,
+            // Furniture
+            _ if name.starts_with("common.items.lantern.") => &self.furniture.entries,
+            "common.items.boss_drops.lantern" => &self.furniture.entries,
+            "common.items.utility.training_dummy" => &self.furniture.entries,
+            // Pets
+            "common.items.utility.collar" => &self.pets.entries,
             // Other
             _ if name.starts_with("common.items.glider.") => &self.other.entries,
             _ if name.starts_with("common.items.utility.") => &self.other.entries,
             _ if name.starts_with("common.items.boss_drops.") => &self.other.entries,
             _ if name.starts_with("common.items.crafting_tools.") => &self.other.entries,
-            _ if name.starts_with("common.items.lantern.") => &self.other.entries,
             _ => {
                 log::warn!("unknown loot item {}", name);
                 &self.other.entries
@@ -291,6 +331,10 @@ impl TradePricing {
             // Tools
             _ if name.starts_with("common.items.weapons.") => &mut self.tools,
             _ if name.starts_with("common.items.tool.") => &mut self.tools,
+            // Modular components (before the generic crafting ingredients)
+            _ if name.starts_with("common.items.crafting_ing.modular.") => {
+                &mut self.modular_components
+            },
             // Ingredients
             _ if name.starts_with("common.items.crafting_ing.") => &mut self.ingredients,
             _ if name.starts_with("common.items.mineral.") => &mut self.ingredients,
@@ -299,12 +343,17 @@ impl TradePricing {
             _ if name.starts_with("common.items.consumable.") => &mut self.potions,
             // Food
             _ if name.starts_with("common.items.food.") => &mut self.food,
+            // Furniture
+            _ if name.starts_with("common.items.lantern.") => &mut self.furniture,
+            "common.items.boss_drops.lantern" => &mut self.furniture,
+            "common.items.utility.training_dummy" => &mut self.furniture,
+            // Pets
+            "common.items.utility.collar" => &mut self.pets,
             // Other
             _ if name.starts_with("common.items.glider.") => &mut self.other,
             _ if name.starts_with("common.items.utility.") => &mut self.other,
             _ if name.starts_with("common.items.boss_drops.") => &mut self.other,
             _ if name.starts_with("common.items.crafting_tools.") => &mut self.other,
-            _ if name.starts_with("common.items.lantern.") => &mut self.other,
             _ => {
                 log::warn!("unknown loot item {}", name);
                 &mut self.other
@@ -313,7 +362,7 @@ impl TradePricing {
     }
 
     // look up price (inverse frequency) of an item
-    fn price_lookup(&self, eqset: &EqualitySet, requested_name: &str) -> f32 {
+    fn price_lookup(&self, eqset: &EqualitySet, requested_name: &str) -> f64 {
         let canonical_name = eqset.canonical(requested_name);
 
         let goods = self.get_list_by_path(canonical_name);
@@ -323,19 +372,21 @@ impl TradePricing {
             .iter()
             .find(|(name, _, _)| name == canonical_name)
             .map_or(
-                Self::UNAVAILABLE_PRICE / Self::INVEST_FACTOR + 1.0,
-                |(_, freq, _)| 1.0 / freq,
+                f64::from(Self::UNAVAILABLE_PRICE / Self::INVEST_FACTOR + 1.0),
+                |(_, freq, _)| 1.0 / f64::from(*freq),
             )
     }
 
-    #[allow(clippy::cast_precision_loss)]
     fn calculate_material_cost(&self, r: &RememberedRecipe, eqset: &EqualitySet) -> f32 {
-        r.input
-            .iter()
-            .map(|(name, amount)| {
-                self.price_lookup(eqset, name) * (*amount as f32).max(Self::INVEST_FACTOR)
-            })
-            .sum()
+        round_price(
+            r.input
+                .iter()
+                .map(|(name, amount)| {
+                    self.price_lookup(eqset, name)
+                        * f64::from(*amount).max(f64::from(Self::INVEST_FACTOR))
+                })
+                .sum(),
+        )
     }
 
     // re-look up prices and sort the vector by ascending material cost, return
@@ -352,74 +403,113 @@ impl TradePricing {
             .is_some()
     }
 
-    #[allow(clippy::cast_precision_loss)]
     fn read() -> Self {
-        let mut result = Self::default();
         let price_config =
             TradingPriceFile::load_expect("common.trading.item_price_calculation").read();
         let eqset = EqualitySet::load_expect("common.trading.item_price_equality").read();
-        result.equality_set = eqset.clone();
-        for table in &price_config.loot_tables {
-     
-            let (frequency, can_sell, asset_path) = table;
-            let loot = ProbabilityFile::load_expect(asset_path);
-            for (p, item_asset, amount) in &loot.read().content {
+        let loot_tables = price_config
+            .loot_tables
+            .iter()
+            .map(|(frequency, can_sell, asset_path)| {
+                let loot = ProbabilityFile::load_expect(asset_path).read().content.clone();
+                (*frequency, *can_sell, loot)
+            })
+            .collect::<Vec<_>>();
+
+        let mut result = Self::apply_loot_tables(&loot_tables, &eqset);
+        result.apply_recipes(Self::remembered_recipes(), &eqset);
+        result.finalize(&price_config);
+        result
+    }
+
+    /// Accumulate the frequency of items dropped from the configured loot
+    /// tables
+    fn apply_loot_tables(
+        loot_tables: &[(f32, bool, Vec<(f32, String, f32)>)],
+        eqset: &EqualitySet,
+    ) -> Self {
+        let mut result = Self {
+            equality_set: eqset.clone(),
+            ..Self::default()
+        };
+        for (frequency, can_sell, loot) in loot_tables {
+            for (p, item_asset, amount) in loot {
                 result.get_list_by_path_mut(item_asset).add(
-                    &eqset,
+                    eqset,
                     item_asset,
-                    frequency * p * *amount,
+                    round_price(f64::from(*frequency) * f64::from(*p) * f64::from(*amount)),
                     *can_sell,
                 );
             }
         }
+        result
+    }
 
-        // Apply recipe book
+    fn remembered_recipes() -> Vec<RememberedRecipe> {
         let book = default_recipe_book().read();
-        let mut ordered_recipes: Vec<RememberedRecipe> = Vec::new();
-        for (_, recipe) in book.iter() {
-            let (ref asset_path, amount) = recipe.output;
-            ordered_recipes.push(RememberedRecipe {
-                output: asset_path.id().into(),
-                amount,
-                material_cost: Self::UNAVAILABLE_PRICE,
-                input: recipe
-                    .inputs
-                    .iter()
-                    .filter_map(|&(ref recipe_input, count)| {
-                        if let RecipeInput::Item(it) = recipe_input {
-                            // If item is not consumed in craft, ignore it
-                            if count == 0 {
-                                None
+        let mut recipes: Vec<RememberedRecipe> = book
+            .iter()
+            .map(|(_, recipe)| {
+                let (ref asset_path, amount) = recipe.output;
+                RememberedRecipe {
+                    output: asset_path.id().into(),
+                    amount,
+                    material_cost: Self::UNAVAILABLE_PRICE,
+                    input: recipe
+                        .inputs
+                        .iter()
+                        .filter_map(|&(ref recipe_input, count)| {
+                            if let RecipeInput::Item(it) = recipe_input {
+                                // If item is not consumed in craft, ignore it
+                                if count == 0 {
+                                    None
+                                } else {
+                                    Some((it.id().into(), count))
+                                }
                             } else {
-                                Some((it.id().into(), count))
+                                None
                             }
-                        } else {
-                            None
-                        }
-                    })
-                    .collect(),
-            });
-        }
+                        })
+                        .collect(),
+                }
+            })
+            .collect();
+        recipes.extend(modular::recipes_for_pricing().into_iter().map(
+            |(output, amount, input)| RememberedRecipe {
+                output,
+                amount,
+                material_cost: Self::UNAVAILABLE_PRICE,
+                input,
+            },
+        ));
+        // The recipe book has no defined order, sort so the fixpoint is
+        // deterministic
+        recipes.sort_by(|a, b| a.output.cmp(&b.output).then_with(|| a.input.cmp(&b.input)));
+        recipes
+    }
 
+    fn apply_recipes(&mut self, mut ordered_recipes: Vec<RememberedRecipe>, eqset: &EqualitySet) {
         // re-evaluate prices based on crafting tables
         // (start with cheap ones to avoid changing material prices after evaluation)
-        while result.sort_by_price(&mut ordered_recipes, &eqset) {
+        while self.sort_by_price(&mut ordered_recipes, eqset) {
             ordered_recipes.retain(|recipe| {
                 if recipe.material_cost < 1e-5 {
                     false
                 } else if recipe.material_cost < Self::UNAVAILABLE_PRICE {
-                    let actual_cost = result.calculate_material_cost(recipe, &eqset);
+                    let actual_cost = self.calculate_material_cost(recipe, eqset);
                     let output_tradeable = recipe.input.iter().all(|(input, _)| {
-                        result
-                            .get_list_by_path(input)
+                        self.get_list_by_path(input)
                             .iter()
                             .find(|(item, _, _)| item == input)
                             .map_or(false, |(_, _, tradeable)| *tradeable)
                     });
-                    result.get_list_by_path_mut(&recipe.output).add(
-                        &eqset,
+                    self.get_list_by_path_mut(&recipe.output).add(
+                        eqset,
                         &recipe.output,
-                        (recipe.amount as f32) / actual_cost * Self::CRAFTING_FACTOR,
+                        round_price(
+                            f64::from(recipe.amount) / f64::from(actual_cost)
+                                * f64::from(Self::CRAFTING_FACTOR),
+                        ),
                         output_tradeable,
                     );
                     false
@@ -429,29 +519,31 @@ impl TradePricing {
             });
             //info!(?ordered_recipes);
         }
+    }
 
+    /// Normalize the frequencies and derive the material cache
+    fn finalize(&mut self, price_config: &AssetGuard<TradingPriceFile>) {
         let good_list = [
             Good::Armor,
             Good::Tools,
             Good::Potions,
             Good::Food,
             Good::Ingredients,
+            Good::ModularComponents,
+            Good::Furniture,
+            Good::Pets,
         ];
 
         for good in &good_list {
-            sort_and_normalize(
-                result.get_list_mut(*good),
-                get_scaling(&price_config, *good),
-            );
-            let mut materials = result
+            sort_and_normalize(self.get_list_mut(*good), get_scaling(price_config, *good));
+            let mut materials = self
                 .get_list(*good)
                 .iter()
-                .map(|i| (i.0.clone(), (*good, 1.0 / i.1)))
+                .map(|i| (i.0.clone(), (*good, round_price(1.0 / f64::from(i.1)))))
                 .collect::<Vec<_>>();
-            result.material_cache.extend(materials.drain(..));
+            self.material_cache.extend(materials.drain(..));
         }
-        result.coin_scale = get_scaling(&price_config, Good::Coin);
-        result
+        self.coin_scale = get_scaling(price_config, Good::Coin);
     }
 
     #[allow(
@@ -618,6 +710,14 @@ impl TradePricing {
             |_i, _p| String::new(),
             "",
         );
+        printvec(
+            "Modular components",
+            &self.modular_components.entries,
+            |_i, _p| String::new(),
+            "",
+        );
+        printvec("Furniture", &self.furniture.entries, |_i, _p| String::new(), "");
+        printvec("Pets", &self.pets.entries, |_i, _p| String::new(), "");
         printvec("Other", &self.other.entries, |_i, _p| String::new(), "");
         println!("{}, yes, {}, Coin, ,,,", Self::COIN_ITEM, self.coin_scale);
     }
@@ -628,3 +728,80 @@ impl TradePricing {
 pub fn expand_loot_table(loot_table: &str) -> Vec<(f32, String, f32)> {
     ProbabilityFile::from(vec![(1.0, LootSpec::LootTable(loot_table.into()))]).content
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::comp::inventory::trade_pricing::{
+        round_price, sort_and_normalize, EqualitySet, RememberedRecipe, TradePricing,
+    };
+    use crate::trade::Good;
+
+    #[test]
+    fn test_prices() { TradePricing::instance().print_sorted(); }
+
+    #[test]
+    fn test_goods_have_items() {
+        let pricing = TradePricing::instance();
+        for good in [Good::Furniture, Good::Pets] {
+            assert!(!pricing.get_list(good).is_empty(), "{:?} has no items", good);
+        }
+    }
+
+    #[test]
+    fn test_round_price() {
+        assert_eq!(round_price(0.1).to_bits(), 0x3dcc_ccd0);
+        assert_eq!(round_price(1.0 / 3.0).to_bits(), 0x3eaa_aaa8);
+        assert_eq!(round_price(1e6), 1e6);
+        assert_eq!(round_price(f64::INFINITY), f32::INFINITY);
+        assert_eq!(round_price(f64::from(round_price(0.1))), round_price(0.1));
+    }
+
+    // Same golden values as the server copy, both must price identically
+    #[test]
+    fn test_pricing_golden_values() {
+        let (apple, cheese) = ("common.items.food.apple", "common.items.food.cheese");
+        let eqset = EqualitySet::default();
+        let mut pricing = TradePricing::apply_loot_tables(
+            &[
+                (1.0, true, vec![
+                    (0.5, apple.to_owned(), 1.0),
+                    (0.3, cheese.to_owned(), 2.0),
+                    (0.2, apple.to_owned(), 1.0),
+                ]),
+                (0.7, true, vec![(1.0, cheese.to_owned(), 1.5)]),
+            ],
+            &eqset,
+        );
+        let bits = |pricing: &TradePricing| {
+            pricing
+                .food
+                .entries
+                .iter()
+                .map(|(item, p, _)| (item.as_str(), p.to_bits()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(bits(&pricing), [(apple, 0x3f33_3338), (cheese, 0x3fd3_3338)]);
+
+        sort_and_normalize(&mut pricing.food.entries, 1.0);
+        assert_eq!(bits(&pricing), [(apple, 0x3ed9_3650), (cheese, 0x3f80_0000)]);
+
+        let recipes = vec![RememberedRecipe {
+            output: "common.items.food.apple_stick".to_owned(),
+            amount: 1,
+            material_cost: TradePricing::UNAVAILABLE_PRICE,
+            input: vec![(apple.to_owned(), 2), (cheese.to_owned(), 1)],
+        }];
+        assert_eq!(
+            pricing.calculate_material_cost(&recipes[0], &eqset).to_bits(),
+            0x40b6_db70
+        );
+        pricing.apply_recipes(recipes, &eqset);
+        let (_, crafted, _) = pricing
+            .food
+            .entries
+            .iter()
+            .find(|(item, _, _)| item == "common.items.food.apple_stick")
+            .unwrap();
+        assert_eq!(crafted.to_bits(), 0x3e2a_3d70);
+    }
+}
@@ -314,6 +314,10 @@ pub enum Good {
     Potions,
     Coin, // exchange material across sites
     RoadSecurity,
+    // The following aren't simulated by the site economy, see `economy_proxy`
+    ModularComponents,
+    Furniture,
+    Pets,
 }
 
 impl Default for Good {
@@ -326,8 +330,9 @@ impl Good {
     /// The discounting factor applied when selling goods back to a merchant
     pub fn trade_margin(&self) -> f32 {
         match self {
-            Good::Tools | Good::Armor => 0.5,
+            Good::Tools | Good::Armor | Good::ModularComponents | Good::Furniture => 0.5,
             Good::Food | Good::Potions | Good::Ingredients => 0.75,
+            Good::Pets => 0.25,
             Good::Coin => 1.0,
             // Certain abstract goods (like Territory) shouldn't be attached to concrete items;
             // give a sale price of 0 if the player is trying to sell a concrete item that somehow
@@ -335,6 +340,17 @@ impl Good {
             _ => 0.0,
         }
     }
+
+    /// The good whose site price stands in for this one, for goods which sites
+    /// don't produce themselves
+    pub fn economy_proxy(&self) -> Good {
+        match self {
+            Good::ModularComponents => Good::Tools,
+            Good::Furniture => Good::Wood,
+            Good::Pets => Good::Food,
+            _ => *self,
+        }
+    }
 }
 
 // ideally this would be a real Id<Site> but that is from the world crate
@@ -0,0 +1,73 @@
+//! Pick between layouts depending on the available width.
+
+/// A range of available widths.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Breakpoint {
+    /// Narrower than [`Breakpoints::regular`], like small windows
+    Compact,
+
+    /// Between [`Breakpoints::regular`] and [`Breakpoints::wide`]
+    Regular,
+
+    /// At least [`Breakpoints::wide`] wide, like ultra-wide screens
+    Wide,
+}
+
+impl Breakpoint {
+    /// All the [`Breakpoint`]s, from the narrowest to the widest.
+    pub const ALL: [Breakpoint; 3] =
+        [Breakpoint::Compact, Breakpoint::Regular, Breakpoint::Wide];
+
+    /// The position of the [`Breakpoint`] in [`Breakpoint::ALL`].
+    pub fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// The widths at which the [`Breakpoint`]s start.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Breakpoints {
+    /// The width at which [`Breakpoint::Regular`] starts
+    pub regular: f32,
+
+    /// The width at which [`Breakpoint::Wide`] starts
+    pub wide: f32,
+}
+
+impl Breakpoints {
+    /// Creates new [`Breakpoints`] from the widths at which they start.
+    pub const fn new(regular: f32, wide: f32) -> Self {
+        Self { regular, wide }
+    }
+
+    /// Returns the [`Breakpoint`] the given width falls into.
+    pub fn get(&self, width: f32) -> Breakpoint {
+        if width >= self.wide {
+            Breakpoint::Wide
+        } else if width >= self.regular {
+            Breakpoint::Regular
+        } else {
+            Breakpoint::Compact
+        }
+    }
+}
+
+impl Default for Breakpoints {
+    fn default() -> Self {
+        Self::new(1200.0, 2400.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn breakpoints() {
+        let breakpoints = Breakpoints::default();
+
+        assert_eq!(breakpoints.get(800.0), Breakpoint::Compact);
+        assert_eq!(breakpoints.get(1200.0), Breakpoint::Regular);
+        assert_eq!(breakpoints.get(f32::INFINITY), Breakpoint::Wide);
+    }
+}
@@ -20,6 +20,7 @@ pub mod mouse;
 pub mod time;
 
 mod background;
+mod breakpoint;
 mod color;
 mod content_fit;
 mod font;
@@ -34,6 +35,7 @@ pub use alignment::Alignment;
 pub use alignment::Horizontal;
 pub use alignment::Vertical;
 pub use background::Background;
+pub use breakpoint::{Breakpoint, Breakpoints};
 pub use color::Color;
 pub use content_fit::ContentFit;
 pub use font::Font;
@@ -0,0 +1,59 @@
+//! Arrange content differently depending on the available width.
+use crate::{Backend, Primitive, Renderer};
+use iced_native::mouse;
+use iced_native::responsive;
+use iced_native::{Element, Layout, Point, Rectangle};
+
+pub use iced_native::responsive::{Arrangement, Direction};
+pub use iced_native::{Breakpoint, Breakpoints};
+
+/// A container that arranges its contents depending on the available width.
+///
+/// This is an alias of an `iced_native` responsive container with a default
+/// `Renderer`.
+pub type Responsive<'a, Message, Backend> =
+    iced_native::Responsive<'a, Message, Renderer<Backend>>;
+
+impl<B> responsive::Renderer for Renderer<B>
+where
+    B: Backend,
+{
+    fn draw<Message>(
+        &mut self,
+        defaults: &Self::Defaults,
+        content: &[Element<'_, Message, Self>],
+        hidden: &[usize],
+        layout: Layout<'_>,
+        cursor_position: Point,
+        viewport: &Rectangle,
+    ) -> Self::Output {
+        let mut mouse_interaction = mouse::Interaction::default();
+
+        (
+            Primitive::Group {
+                primitives: content
+                    .iter()
+                    .zip(layout.children())
+                    .enumerate()
+                    .filter(|(i, _)| !hidden.contains(i))
+                    .map(|(_, (child, layout))| {
+                        let (primitive, new_mouse_interaction) = child.draw(
+                            self,
+                            defaults,
+                            layout,
+                            cursor_position,
+                            viewport,
+                        );
+
+                        if new_mouse_interaction > mouse_interaction {
+                            mouse_interaction = new_mouse_interaction;
+                        }
+
+                        primitive
+                    })
+                    .collect(),
+            },
+            mouse_interaction,
+        )
+    }
+}
@@ -68,11 +68,41 @@ pub fn resolve<Message, Renderer>(
     align_items: Alignment,
     items: &[Element<'_, Message, Renderer>],
 ) -> Node
+where
+    Renderer: crate::Renderer,
+{
+    resolve_visible(
+        axis,
+        renderer,
+        limits,
+        padding,
+        spacing,
+        align_items,
+        items,
+        &[],
+    )
+}
+
+/// Computes the flex layout like [`resolve`], leaving out the items at the
+/// `hidden` indices.
+///
+/// The hidden items get an empty [`Node`] and no spacing.
+pub fn resolve_visible<Message, Renderer>(
+    axis: Axis,
+    renderer: &Renderer,
+    limits: &Limits,
+    padding: Padding,
+    spacing: f32,
+    align_items: Alignment,
+    items: &[Element<'_, Message, Renderer>],
+    hidden: &[usize],
+) -> Node
 where
     Renderer: crate::Renderer,
 {
     let limits = limits.pad(padding);
-    let total_spacing = spacing * items.len().saturating_sub(1) as f32;
+    let visible = (0..items.len()).filter(|i| !hidden.contains(i)).count();
+    let total_spacing = spacing * visible.saturating_sub(1) as f32;
     let max_cross = axis.cross(limits.max());
 
     let mut fill_sum = 0;
@@ -83,6 +113,10 @@ where
     nodes.resize(items.len(), Node::default());
 
     for (i, child) in items.iter().enumerate() {
+        if hidden.contains(&i) {
+            continue;
+        }
+
         let fill_factor = match axis {
             Axis::Horizontal => child.width(),
             Axis::Vertical => child.height(),
@@ -110,6 +144,10 @@ where
     let remaining = available.max(0.0);
 
     for (i, child) in items.iter().enumerate() {
+        if hidden.contains(&i) {
+            continue;
+        }
+
         let fill_factor = match axis {
             Axis::Horizontal => child.width(),
             Axis::Vertical => child.height(),
@@ -145,10 +183,17 @@ where
     let pad = axis.pack(padding.left as f32, padding.top as f32);
     let mut main = pad.0;
 
+    let mut first = true;
+
     for (i, node) in nodes.iter_mut().enumerate() {
-        if i > 0 {
+        if hidden.contains(&i) {
+            continue;
+        }
+
+        if !first {
             main += spacing;
         }
+        first = false;
 
         let (x, y) = axis.pack(main, pad.1);
 
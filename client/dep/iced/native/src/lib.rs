@@ -61,8 +61,8 @@ mod debug;
 mod debug;
 
 pub use iced_core::{
-    Alignment, Background, Color, Font, Horizontal, Length, Padding,
-    Point, Rectangle, Size, Vector, Vertical,
+    Alignment, Background, Breakpoint, Breakpoints, Color, Font, Horizontal,
+    Length, Padding, Point, Rectangle, Size, Vector, Vertical,
 };
 pub use iced_futures::{executor, futures, Command};
 
@@ -0,0 +1,312 @@
+//! Arrange content differently depending on the available width.
+use crate::event::{self, Event};
+use crate::layout;
+use crate::overlay;
+use crate::{
+    Alignment, Breakpoint, Breakpoints, Clipboard, Element, Hasher, Layout,
+    Length, Padding, Point, Rectangle, Widget,
+};
+
+use std::hash::Hash;
+use std::u32;
+
+/// The direction in which the children of a [`Responsive`] are laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// Next to each other, like a [`Row`](crate::Row)
+    Row,
+
+    /// Below each other, like a [`Column`](crate::Column)
+    Column,
+}
+
+/// How the children of a [`Responsive`] are arranged at a [`Breakpoint`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Arrangement {
+    direction: Direction,
+    hidden: Vec<usize>,
+}
+
+impl Arrangement {
+    /// Lays out the children next to each other.
+    pub fn row() -> Self {
+        Self {
+            direction: Direction::Row,
+            hidden: Vec::new(),
+        }
+    }
+
+    /// Lays out the children below each other.
+    pub fn column() -> Self {
+        Self {
+            direction: Direction::Column,
+            hidden: Vec::new(),
+        }
+    }
+
+    /// Leaves out the child at the given index.
+    pub fn hide(mut self, index: usize) -> Self {
+        self.hidden.push(index);
+        self
+    }
+
+    /// Returns the [`Direction`] of the [`Arrangement`].
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    /// Returns the indices of the children left out.
+    pub fn hidden(&self) -> &[usize] {
+        &self.hidden
+    }
+}
+
+/// A container that picks the [`Arrangement`] of its contents by the
+/// [`Breakpoint`] of the available width.
+///
+/// It always fills the available width, up to its maximum width.
+#[allow(missing_debug_implementations)]
+pub struct Responsive<'a, Message, Renderer> {
+    breakpoints: Breakpoints,
+    arrangements: [Arrangement; 3],
+    spacing: u16,
+    padding: Padding,
+    height: Length,
+    max_width: u32,
+    align_items: Alignment,
+    children: Vec<Element<'a, Message, Renderer>>,
+}
+
+impl<'a, Message, Renderer> Responsive<'a, Message, Renderer> {
+    /// Creates a [`Responsive`] with the given elements, laid out as a row at
+    /// every [`Breakpoint`].
+    pub fn with_children(
+        children: Vec<Element<'a, Message, Renderer>>,
+    ) -> Self {
+        Responsive {
+            breakpoints: Breakpoints::default(),
+            arrangements: [
+                Arrangement::row(),
+                Arrangement::row(),
+                Arrangement::row(),
+            ],
+            spacing: 0,
+            padding: Padding::ZERO,
+            height: Length::Shrink,
+            max_width: u32::MAX,
+            align_items: Alignment::Start,
+            children,
+        }
+    }
+
+    /// Sets the [`Breakpoints`] of the [`Responsive`].
+    pub fn breakpoints(mut self, breakpoints: Breakpoints) -> Self {
+        self.breakpoints = breakpoints;
+        self
+    }
+
+    /// Sets the [`Arrangement`] used at the given [`Breakpoint`].
+    pub fn arrangement(
+        mut self,
+        breakpoint: Breakpoint,
+        arrangement: Arrangement,
+    ) -> Self {
+        self.arrangements[breakpoint.index()] = arrangement;
+        self
+    }
+
+    /// Sets the spacing _between_ elements.
+    pub fn spacing(mut self, units: u16) -> Self {
+        self.spacing = units;
+        self
+    }
+
+    /// Sets the [`Padding`] of the [`Responsive`].
+    pub fn padding<P: Into<Padding>>(mut self, padding: P) -> Self {
+        self.padding = padding.into();
+        self
+    }
+
+    /// Sets the height of the [`Responsive`].
+    pub fn height(mut self, height: Length) -> Self {
+        self.height = height;
+        self
+    }
+
+    /// Sets the maximum width of the [`Responsive`].
+    pub fn max_width(mut self, max_width: u32) -> Self {
+        self.max_width = max_width;
+        self
+    }
+
+    /// Sets the cross axis alignment of the contents of the [`Responsive`].
+    pub fn align_items(mut self, align: Alignment) -> Self {
+        self.align_items = align;
+        self
+    }
+
+    // The layout fills the available width, so its width is the one the
+    // arrangement was picked by
+    fn arrangement_for(&self, width: f32) -> &Arrangement {
+        &self.arrangements[self.breakpoints.get(width).index()]
+    }
+}
+
+impl<'a, Message, Renderer> Widget<Message, Renderer>
+    for Responsive<'a, Message, Renderer>
+where
+    Renderer: self::Renderer,
+{
+    fn width(&self) -> Length {
+        Length::Fill
+    }
+
+    fn height(&self) -> Length {
+        self.height
+    }
+
+    fn layout(
+        &self,
+        renderer: &Renderer,
+        limits: &layout::Limits,
+    ) -> layout::Node {
+        let limits = limits
+            .max_width(self.max_width)
+            .width(Length::Fill)
+            .height(self.height);
+        let arrangement = self.arrangement_for(limits.max().width);
+        let axis = match arrangement.direction {
+            Direction::Row => layout::flex::Axis::Horizontal,
+            Direction::Column => layout::flex::Axis::Vertical,
+        };
+
+        layout::flex::resolve_visible(
+            axis,
+            renderer,
+            &limits,
+            self.padding,
+            self.spacing as f32,
+            self.align_items,
+            &self.children,
+            &arrangement.hidden,
+        )
+    }
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        messages: &mut Vec<Message>,
+    ) -> event::Status {
+        let hidden = self.arrangement_for(layout.bounds().width).hidden.clone();
+
+        self.children
+            .iter_mut()
+            .zip(layout.children())
+            .enumerate()
+            .filter(|(i, _)| !hidden.contains(i))
+            .map(|(_, (child, layout))| {
+                child.widget.on_event(
+                    event.clone(),
+                    layout,
+                    cursor_position,
+                    renderer,
+                    clipboard,
+                    messages,
+                )
+            })
+            .fold(event::Status::Ignored, event::Status::merge)
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut Renderer,
+        defaults: &Renderer::Defaults,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        viewport: &Rectangle,
+    ) -> Renderer::Output {
+        renderer.draw(
+            defaults,
+            &self.children,
+            &self.arrangement_for(layout.bounds().width).hidden,
+            layout,
+            cursor_position,
+            viewport,
+        )
+    }
+
+    fn hash_layout(&self, state: &mut Hasher) {
+        struct Marker;
+        std::any::TypeId::of::<Marker>().hash(state);
+
+        self.breakpoints.regular.to_bits().hash(state);
+        self.breakpoints.wide.to_bits().hash(state);
+        self.arrangements.hash(state);
+        self.height.hash(state);
+        self.max_width.hash(state);
+        self.align_items.hash(state);
+        self.spacing.hash(state);
+        self.padding.hash(state);
+
+        for child in &self.children {
+            child.widget.hash_layout(state);
+        }
+    }
+
+    fn overlay(
+        &mut self,
+        layout: Layout<'_>,
+    ) -> Option<overlay::Element<'_, Message, Renderer>> {
+        let hidden = self.arrangement_for(layout.bounds().width).hidden.clone();
+
+        self.children
+            .iter_mut()
+            .zip(layout.children())
+            .enumerate()
+            .filter(|(i, _)| !hidden.contains(i))
+            .filter_map(|(_, (child, layout))| child.widget.overlay(layout))
+            .next()
+    }
+}
+
+/// The renderer of a [`Responsive`].
+///
+/// Your [renderer] will need to implement this trait before being
+/// able to use a [`Responsive`] in your user interface.
+///
+/// [renderer]: crate::renderer
+pub trait Renderer: crate::Renderer + Sized {
+    /// Draws a [`Responsive`].
+    ///
+    /// It receives:
+    /// - the children of the [`Responsive`]
+    /// - the indices of the children which must not be drawn
+    /// - the [`Layout`] of the [`Responsive`] and its children
+    /// - the cursor position
+    fn draw<Message>(
+        &mut self,
+        defaults: &Self::Defaults,
+        children: &[Element<'_, Message, Self>],
+        hidden: &[usize],
+        layout: Layout<'_>,
+        cursor_position: Point,
+        viewport: &Rectangle,
+    ) -> Self::Output;
+}
+
+impl<'a, Message, Renderer> From<Responsive<'a, Message, Renderer>>
+    for Element<'a, Message, Renderer>
+where
+    Renderer: 'a + self::Renderer,
+    Message: 'a,
+{
+    fn from(
+        responsive: Responsive<'a, Message, Renderer>,
+    ) -> Element<'a, Message, Renderer> {
+        Element::new(responsive)
+    }
+}
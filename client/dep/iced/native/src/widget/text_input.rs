@@ -54,6 +54,9 @@ pub struct TextInput<'a, Message, Renderer: self::Renderer> {
     placeholder: String,
     value: Value,
     is_secure: bool,
+    mask: Option<char>,
+    filter: Option<Box<dyn Fn(char) -> bool>>,
+    max_length: Option<usize>,
     font: Renderer::Font,
     width: Length,
     max_width: u32,
@@ -90,6 +93,9 @@ where
             placeholder: String::from(placeholder),
             value: Value::new(value),
             is_secure: false,
+            mask: None,
+            filter: None,
+            max_length: None,
             font: Default::default(),
             width: Length::Fill,
             max_width: u32::MAX,
@@ -102,11 +108,44 @@ where
     }
 
     /// Converts the [`TextInput`] into a secure password input.
+    ///
+    /// Its value is hidden behind the mask of the [`Renderer`].
     pub fn password(mut self) -> Self {
         self.is_secure = true;
         self
     }
 
+    /// Converts the [`TextInput`] into a secure password input, hiding its
+    /// value behind the given character.
+    pub fn mask(mut self, mask: char) -> Self {
+        self.is_secure = true;
+        self.mask = Some(mask);
+        self
+    }
+
+    /// Only accepts characters for which `filter` returns true, when typing
+    /// or pasting.
+    pub fn filter<F>(mut self, filter: F) -> Self
+    where
+        F: 'static + Fn(char) -> bool,
+    {
+        self.filter = Some(Box::new(filter));
+        self
+    }
+
+    /// Only accepts the digits from 0 to 9.
+    pub fn numeric(self) -> Self {
+        self.filter(|c| c.is_ascii_digit())
+    }
+
+    /// Sets the maximum amount of graphemes of the value.
+    ///
+    /// Input that would make the value longer is cut off.
+    pub fn max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
     /// Sets the [`Font`] of the [`Text`].
     ///
     /// [`Font`]: crate::widget::text::Renderer::Font
@@ -162,6 +201,27 @@ impl<'a, Message, Renderer> TextInput<'a, Message, Renderer>
 where
     Renderer: self::Renderer,
 {
+    fn masked(&self, renderer: &Renderer, value: &Value) -> Value {
+        value.masked(self.mask.unwrap_or_else(|| renderer.mask()))
+    }
+
+    fn accepts(&self, c: char) -> bool {
+        self.filter.as_ref().map_or(true, |filter| filter(c))
+    }
+
+    // How many graphemes can still be inserted, replacing the selection
+    fn remaining_length(&self) -> usize {
+        let selected = self
+            .state
+            .cursor
+            .selection(&self.value)
+            .map_or(0, |(start, end)| end - start);
+
+        self.max_length.map_or(usize::MAX, |max_length| {
+            max_length.saturating_sub(self.value.len() - selected)
+        })
+    }
+
     /// Draws the [`TextInput`] with the given [`Renderer`], overriding its
     /// [`Value`] if provided.
     pub fn draw(
@@ -176,6 +236,8 @@ where
         let text_bounds = layout.children().next().unwrap().bounds();
 
         if self.is_secure {
+            let value = self.masked(renderer, value);
+
             self::Renderer::draw(
                 renderer,
                 bounds,
@@ -184,7 +246,7 @@ where
                 self.font,
                 self.size.unwrap_or(renderer.default_size()),
                 &self.placeholder,
-                &value.secure(),
+                &value,
                 &self.state,
                 &self.style,
             )
@@ -270,7 +332,7 @@ where
                         click::Kind::Single => {
                             if target > 0.0 {
                                 let value = if self.is_secure {
-                                    self.value.secure()
+                                    self.masked(renderer, &self.value)
                                 } else {
                                     self.value.clone()
                                 };
@@ -336,7 +398,7 @@ where
 
                     if target > 0.0 {
                         let value = if self.is_secure {
-                            self.value.secure()
+                            self.masked(renderer, &self.value)
                         } else {
                             self.value.clone()
                         };
@@ -365,6 +427,10 @@ where
                     && !self.state.keyboard_modifiers.command()
                     && !c.is_control() =>
             {
+                if !self.accepts(c) || self.remaining_length() == 0 {
+                    return event::Status::Captured;
+                }
+
                 let mut editor =
                     Editor::new(&mut self.value, &mut self.state.cursor);
 
@@ -553,12 +619,20 @@ where
                                         .read()
                                         .unwrap_or(String::new())
                                         .chars()
-                                        .filter(|c| !c.is_control())
+                                        .filter(|c| {
+                                            !c.is_control() && self.accepts(*c)
+                                        })
                                         .collect();
 
                                     Value::new(&content)
                                 }
                             };
+                            let content = match self.remaining_length() {
+                                remaining if remaining < content.len() => {
+                                    content.until(remaining)
+                                }
+                                _ => content,
+                            };
 
                             let mut editor = Editor::new(
                                 &mut self.value,
@@ -655,6 +729,12 @@ pub trait Renderer: text::Renderer + Sized {
     /// Returns the width of the value of the [`TextInput`].
     fn measure_value(&self, value: &str, size: u16, font: Self::Font) -> f32;
 
+    /// Returns the character shown for each grapheme of a password input,
+    /// unless the [`TextInput`] sets its own mask.
+    fn mask(&self) -> char {
+        '•'
+    }
+
     /// Returns the current horizontal offset of the value of the
     /// [`TextInput`].
     ///
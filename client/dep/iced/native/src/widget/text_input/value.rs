@@ -125,8 +125,14 @@ impl Value {
     /// Returns a new [`Value`] with all its graphemes replaced with the
     /// dot ('•') character.
     pub fn secure(&self) -> Self {
+        self.masked('•')
+    }
+
+    /// Returns a new [`Value`] with all its graphemes replaced with the
+    /// given character.
+    pub fn masked(&self, mask: char) -> Self {
         Self {
-            graphemes: std::iter::repeat(String::from("•"))
+            graphemes: std::iter::repeat(mask.to_string())
                 .take(self.graphemes.len())
                 .collect(),
         }
@@ -25,6 +25,13 @@ pub enum ProtocolEvent {
         data: Bytes,
         sid: Sid,
     },
+    /// Keepalive, MUST be answered with a `Pong` with the same nonce
+    Ping {
+        nonce: u64,
+    },
+    Pong {
+        nonce: u64,
+    },
 }
 
 impl ProtocolEvent {
@@ -43,6 +50,8 @@ impl ProtocolEvent {
                 guaranteed_bandwidth: *guaranteed_bandwidth,
             },
             ProtocolEvent::CloseStream { sid } => OTFrame::CloseStream { sid: *sid },
+            ProtocolEvent::Ping { nonce } => OTFrame::Ping { nonce: *nonce },
+            ProtocolEvent::Pong { nonce } => OTFrame::Pong { nonce: *nonce },
             ProtocolEvent::Message { .. } => {
                 unimplemented!("Event::Message to OTFrame IS NOT supported")
             },
@@ -2,7 +2,7 @@ use crate::{
     error::{InitProtocolError, ProtocolError},
     frame::InitFrame,
     types::{
-        Pid, Sid, STREAM_ID_OFFSET1, STREAM_ID_OFFSET2, VELOREN_MAGIC_NUMBER,
+        HandshakeFeatures, Pid, Sid, STREAM_ID_OFFSET1, STREAM_ID_OFFSET2, VELOREN_MAGIC_NUMBER,
         VELOREN_NETWORK_VERSION,
     },
     InitProtocol,
@@ -21,6 +21,13 @@ use async_trait::async_trait;
 #[async_trait]
 pub trait ReliableDrain {
     async fn send(&mut self, frame: InitFrame) -> Result<(), ProtocolError>;
+
+    /// Features announced to the remote in the handshake
+    fn features(&self) -> HandshakeFeatures { HandshakeFeatures::empty() }
+
+    /// Called with the features both sides announced once the handshake
+    /// frames were exchanged
+    fn negotiated(&mut self, _features: HandshakeFeatures) {}
 }
 
 /// Implement this for auto Handshake with [`ReliableDrain`]. See
@@ -55,12 +62,14 @@ where
 
         let drain = &mut self.0;
         let sink = &mut self.1;
+        let local_features = drain.features();
 
         if initializer {
             drain
                 .send(InitFrame::Handshake {
                     magic_number: VELOREN_MAGIC_NUMBER,
                     version: VELOREN_NETWORK_VERSION,
+                    features: local_features,
                 })
                 .await?;
         }
@@ -69,6 +78,7 @@ where
             InitFrame::Handshake {
                 magic_number,
                 version,
+                features,
             } => {
                 if magic_number != VELOREN_MAGIC_NUMBER {
                     log::error!("Connection with invalid magic_number");
@@ -95,18 +105,20 @@ where
                     Err(InitProtocolError::WrongVersion(version))
                 } else {
                     log::trace!("Handshake Frame completed");
-                    if initializer {
-                        drain
-                            .send(InitFrame::Init {
-                                pid: local_pid,
-                                secret: local_secret,
-                            })
-                            .await?;
-                    } else {
+                    drain.negotiated(local_features & features);
+                    if !initializer {
                         drain
                             .send(InitFrame::Handshake {
                                 magic_number: VELOREN_MAGIC_NUMBER,
                                 version: VELOREN_NETWORK_VERSION,
+                                features: local_features,
+                            })
+                            .await?;
+                        // the remote answers with its own secret once it verified ours
+                        drain
+                            .send(InitFrame::Init {
+                                pid: local_pid,
+                                secret: local_secret,
                             })
                             .await?;
                     }
@@ -130,14 +142,14 @@ where
             InitFrame::Init { pid, secret } => {
                 log::debug!("Participant send their ID {}", pid);
                 let stream_id_offset = if initializer {
-                    STREAM_ID_OFFSET1
-                } else {
                     drain
                         .send(InitFrame::Init {
                             pid: local_pid,
                             secret: local_secret,
                         })
                         .await?;
+                    STREAM_ID_OFFSET1
+                } else {
                     STREAM_ID_OFFSET2
                 };
                 log::info!("This Handshake is now configured!  {}", pid);
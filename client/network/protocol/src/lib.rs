@@ -6,14 +6,19 @@ mod frame;
 mod handshake;
 mod message;
 mod prio;
+mod quality;
 mod tcp;
 mod types;
 mod util;
 
 pub use error::{InitProtocolError, ProtocolError};
 pub use event::ProtocolEvent;
+pub use quality::{ConnectionQuality, QualityEstimator};
 pub use tcp::{TcpRecvProtocol, TcpSendProtocol};
-pub use types::{Bandwidth, Cid, Pid, Prio, Promises, Sid, HIGHEST_PRIO, VELOREN_NETWORK_VERSION};
+pub use types::{
+    Bandwidth, Cid, HandshakeFeatures, Pid, Prio, Promises, Sid, HIGHEST_PRIO,
+    VELOREN_NETWORK_VERSION,
+};
 ///use at own risk, might change any time, for internal benchmarks
 pub mod _internal {
     pub use crate::{
@@ -29,6 +29,8 @@ pub(crate) struct ITMessage {
     pub data: BytesMut,
     pub sid: Sid,
     pub length: u64,
+    /// a data frame failed its checksum, the message is dropped once complete
+    pub corrupt: bool,
 }
 
 impl OTMessage {
@@ -78,6 +80,9 @@ impl OTMessage {
     }
 
     pub(crate) fn get_sid_len(&self) -> (Sid, u64) { (self.sid, self.original_length) }
+
+    /// whether all frames were returned by [`OTMessage::next`]
+    pub(crate) fn is_finished(&self) -> bool { self.send_header && self.data.is_empty() }
 }
 
 impl ITMessage {
@@ -88,6 +93,7 @@ impl ITMessage {
             sid,
             length,
             data: BytesMut::with_capacity((length as usize).min(ALLOC_BLOCK /* anti-ddos */)),
+            corrupt: false,
         }
     }
 }
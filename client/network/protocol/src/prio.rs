@@ -5,6 +5,7 @@ use crate::{
 };
 use bytes::Bytes;
 use std::{
+    cmp::Ordering,
     collections::{HashMap, VecDeque},
 };
 use instant::Duration;
@@ -16,23 +17,45 @@ struct StreamInfo {
     #[allow(dead_code)]
     pub(crate) promises: Promises,
     pub(crate) messages: VecDeque<OTMessage>,
+    /// virtual time at which the last frame of this stream was done
+    pub(crate) finish_tag: f64,
+}
+
+impl StreamInfo {
+    /// prio 0 gets twice the share of prio 1, which gets twice the share of
+    /// prio 2, ...
+    fn weight(&self) -> f64 { f64::from(1u32 << (HIGHEST_PRIO - self.prio.min(HIGHEST_PRIO))) }
 }
 
 /// Responsible for queueing messages.
 /// every stream has a guaranteed bandwidth and a prio 0-7.
-/// when `n` Bytes are available in the buffer, first the guaranteed bandwidth
-/// is used. Then remaining bandwidth is used to fill up the prios.
+/// Frames are picked by start-time fair queuing: streams with data share the
+/// available bandwidth weighted by their prio, so a saturated low prio stream
+/// can't starve a high prio one and still gets its share itself.
+/// The guaranteed bandwidth of a stream is sent even when that exceeds the
+/// available bandwidth.
 #[derive(Debug)]
 pub(crate) struct PrioManager {
     streams: HashMap<Sid, StreamInfo>,
+    /// start tag of the last frame sent
+    virtual_time: f64,
 }
 
 // Send everything ONCE, then keep it till it's confirmed
 
+fn frame_bytes(frame: &OTFrame) -> u64 {
+    (if let OTFrame::Data { data, .. } = frame {
+        crate::frame::TCP_DATA_CNS + 1 + data.len()
+    } else {
+        crate::frame::TCP_DATA_HEADER_CNS + 1
+    }) as u64
+}
+
 impl PrioManager {
     pub fn new() -> Self {
         Self {
             streams: HashMap::new(),
+            virtual_time: 0.0,
         }
     }
 
@@ -48,6 +71,7 @@ impl PrioManager {
             prio,
             promises,
             messages: VecDeque::new(),
+            finish_tag: 0.0,
         });
     }
 
@@ -78,55 +102,54 @@ impl PrioManager {
         let mut cur_bytes = 0u64;
         let mut frames = vec![];
 
-        let mut prios = [0u64; (HIGHEST_PRIO + 1) as usize];
+        let mut guaranteed: HashMap<Sid, i64> = self
+            .streams
+            .iter()
+            .map(|(sid, stream)| {
+                let bytes = (stream.guaranteed_bandwidth as f64 * dt.as_secs_f64()) as i64;
+                (*sid, bytes)
+            })
+            .collect();
 
-        let mut process_stream =
-            |sid: &Sid, stream: &mut StreamInfo, mut bandwidth: i64, cur_bytes: &mut u64| {
-                let mut finished = None;
-                'outer: for (i, msg) in stream.messages.iter_mut().enumerate() {
-                    while let Some(frame) = msg.next() {
-                        let b = if let OTFrame::Data { data, .. } = &frame {
-                            crate::frame::TCP_DATA_CNS + 1 + data.len()
-                        } else {
-                            crate::frame::TCP_DATA_HEADER_CNS + 1
-                        } as u64;
-                        bandwidth -= b as i64;
-                        *cur_bytes += b;
-                        frames.push((*sid, frame));
-                        if bandwidth <= 0 {
-                            break 'outer;
-                        }
-                    }
-                    let (sid, bytes) = msg.get_sid_len();
-                    finished = Some(i);
-                }
-                if let Some(i) = finished {
-                    //cleanup
-                    stream.messages.drain(..=i);
-                }
+        loop {
+            let budget_left = cur_bytes < total_bytes;
+            let virtual_time = self.virtual_time;
+            // Smallest start tag first, the higher prio wins ties
+            let next = self
+                .streams
+                .iter()
+                .filter(|(sid, stream)| {
+                    !stream.messages.is_empty() && (budget_left || guaranteed[*sid] > 0)
+                })
+                .map(|(sid, stream)| (stream.finish_tag.max(virtual_time), stream.prio, *sid))
+                .min_by(|a, b| {
+                    a.0.partial_cmp(&b.0)
+                        .unwrap_or(Ordering::Equal)
+                        .then(a.1.cmp(&b.1))
+                        .then(a.2.cmp(&b.2))
+                });
+            let (start_tag, _, sid) = match next {
+                Some(next) => next,
+                None => break,
             };
 
-        // Add guaranteed bandwidth
-        for (sid, stream) in self.streams.iter_mut() {
-            prios[stream.prio as usize] += 1;
-            let stream_byte_cnt = (stream.guaranteed_bandwidth as f64 * dt.as_secs_f64()) as u64;
-            process_stream(sid, stream, stream_byte_cnt as i64, &mut cur_bytes);
-        }
-
-        if cur_bytes < total_bytes {
-            // Add optional bandwidth
-            for prio in 0..=HIGHEST_PRIO {
-                if prios[prio as usize] == 0 {
-                    continue;
-                }
-                let per_stream_bytes = ((total_bytes - cur_bytes) / prios[prio as usize]) as i64;
-                for (sid, stream) in self.streams.iter_mut() {
-                    if stream.prio != prio {
-                        continue;
-                    }
-                    process_stream(sid, stream, per_stream_bytes, &mut cur_bytes);
-                }
+            let stream = self.streams.get_mut(&sid).unwrap();
+            let msg = stream.messages.front_mut().unwrap();
+            let frame = msg.next();
+            if msg.is_finished() {
+                stream.messages.pop_front();
             }
+            let frame = match frame {
+                Some(frame) => frame,
+                None => continue,
+            };
+
+            let b = frame_bytes(&frame);
+            self.virtual_time = start_tag;
+            stream.finish_tag = start_tag + b as f64 / stream.weight();
+            *guaranteed.get_mut(&sid).unwrap() -= b as i64;
+            cur_bytes += b;
+            frames.push((sid, frame));
         }
         (frames, cur_bytes)
     }
@@ -0,0 +1,199 @@
+use crate::types::Bandwidth;
+use instant::{Duration, Instant};
+use std::collections::VecDeque;
+
+/// Snapshot of how well a connection performs, measured by keepalive pings.
+/// See [`QualityEstimator`]
+///
+/// [`QualityEstimator`]: crate::QualityEstimator
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConnectionQuality {
+    /// smoothed round trip time
+    pub rtt: Duration,
+    /// smoothed deviation of the round trip time
+    pub jitter: Duration,
+    /// share of pings that weren't answered in time, between 0.0 and 1.0
+    pub loss: f32,
+}
+
+impl Default for ConnectionQuality {
+    fn default() -> Self {
+        Self {
+            rtt: Duration::ZERO,
+            jitter: Duration::ZERO,
+            loss: 0.0,
+        }
+    }
+}
+
+impl ConnectionQuality {
+    /// Share of lost pings from which on the connection counts as congested
+    const CONGESTION_LOSS: f32 = 0.05;
+    const MIN_BANDWIDTH: Bandwidth = 64_000;
+    const UNLIMITED_BANDWIDTH: Bandwidth = 1_000_000_000;
+
+    /// Signal strength between 0 (unusable) and 4 (excellent), e.g. for a
+    /// signal indicator
+    pub fn strength(&self) -> u8 {
+        if self.loss > 0.5 {
+            return 0;
+        }
+        let latency = self.rtt + self.jitter * 2;
+        let strength = match latency.as_millis() {
+            0..=79 => 4,
+            80..=149 => 3,
+            150..=299 => 2,
+            300..=599 => 1,
+            _ => 0,
+        };
+        if self.loss > Self::CONGESTION_LOSS {
+            strength.saturating_sub(1)
+        } else {
+            strength
+        }
+    }
+
+    /// Bandwidth to hand to the send protocols given the current throughput
+    /// in bytes per second. While pings get lost it is held below the
+    /// throughput, so queued messages don't congest the connection further.
+    pub fn send_bandwidth(&self, throughput: f32) -> Bandwidth {
+        if self.loss <= Self::CONGESTION_LOSS {
+            Self::UNLIMITED_BANDWIDTH
+        } else {
+            ((throughput * (1.0 - self.loss)) as Bandwidth).max(Self::MIN_BANDWIDTH)
+        }
+    }
+}
+
+/// Estimates RTT, jitter and loss of a channel from keepalive pings.
+///
+/// Ask it for a nonce via [`ping`] regularly, send it as [`Ping`] and feed the
+/// answering [`Pong`] into [`pong`]. RTT and jitter are smoothed like TCP
+/// does (RFC 6298), pings without answer after [`PING_TIMEOUT`] count as lost.
+///
+/// [`ping`]: QualityEstimator::ping
+/// [`pong`]: QualityEstimator::pong
+/// [`Ping`]: crate::ProtocolEvent::Ping
+/// [`Pong`]: crate::ProtocolEvent::Pong
+/// [`PING_TIMEOUT`]: QualityEstimator::PING_TIMEOUT
+#[derive(Debug)]
+pub struct QualityEstimator {
+    pending: VecDeque<(u64, Instant)>,
+    next_nonce: u64,
+    last_ping: Option<Instant>,
+    has_sample: bool,
+    quality: ConnectionQuality,
+}
+
+impl Default for QualityEstimator {
+    fn default() -> Self { Self::new() }
+}
+
+impl QualityEstimator {
+    const LOSS_WEIGHT: f32 = 0.1;
+    pub const PING_INTERVAL: Duration = Duration::from_secs(1);
+    pub const PING_TIMEOUT: Duration = Duration::from_secs(5);
+
+    pub fn new() -> Self {
+        Self {
+            pending: VecDeque::new(),
+            next_nonce: 0,
+            last_ping: None,
+            has_sample: false,
+            quality: ConnectionQuality::default(),
+        }
+    }
+
+    /// Returns the nonce for a new ping if one is due
+    pub fn ping(&mut self, now: Instant) -> Option<u64> {
+        self.expire(now);
+        if let Some(last) = self.last_ping {
+            if since(now, last) < Self::PING_INTERVAL {
+                return None;
+            }
+        }
+        let nonce = self.next_nonce;
+        self.next_nonce = self.next_nonce.wrapping_add(1);
+        self.last_ping = Some(now);
+        self.pending.push_back((nonce, now));
+        Some(nonce)
+    }
+
+    /// Updates the estimate, pongs for unknown or expired nonces are ignored
+    pub fn pong(&mut self, nonce: u64, now: Instant) {
+        let sent = match self.pending.iter().position(|(n, _)| *n == nonce) {
+            Some(i) => self.pending.remove(i).unwrap().1,
+            None => return,
+        };
+        let sample = since(now, sent);
+        let q = &mut self.quality;
+        if self.has_sample {
+            let deviation = if sample > q.rtt {
+                sample - q.rtt
+            } else {
+                q.rtt - sample
+            };
+            q.jitter = q.jitter * 3 / 4 + deviation / 4;
+            q.rtt = q.rtt * 7 / 8 + sample / 8;
+        } else {
+            q.rtt = sample;
+            q.jitter = sample / 2;
+            self.has_sample = true;
+        }
+        q.loss *= 1.0 - Self::LOSS_WEIGHT;
+    }
+
+    fn expire(&mut self, now: Instant) {
+        while let Some((_, sent)) = self.pending.front() {
+            if since(now, *sent) < Self::PING_TIMEOUT {
+                break;
+            }
+            self.pending.pop_front();
+            self.quality.loss = self.quality.loss * (1.0 - Self::LOSS_WEIGHT) + Self::LOSS_WEIGHT;
+        }
+    }
+
+    pub fn quality(&self) -> ConnectionQuality { self.quality }
+}
+
+// Like `Instant::saturating_duration_since`, which the wasm `Instant` lacks
+fn since(now: Instant, earlier: Instant) -> Duration {
+    if now > earlier {
+        now - earlier
+    } else {
+        Duration::ZERO
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rtt_and_loss() {
+        let mut estimator = QualityEstimator::new();
+        let start = Instant::now();
+        let nonce = estimator.ping(start).unwrap();
+        assert_eq!(estimator.ping(start + Duration::from_millis(10)), None);
+        estimator.pong(nonce, start + Duration::from_millis(40));
+        let quality = estimator.quality();
+        assert_eq!(quality.rtt, Duration::from_millis(40));
+        assert_eq!(quality.loss, 0.0);
+        assert_eq!(quality.strength(), 4);
+
+        // the next ping is never answered
+        let lost = start + QualityEstimator::PING_INTERVAL;
+        estimator.ping(lost).unwrap();
+        estimator.ping(lost + QualityEstimator::PING_TIMEOUT).unwrap();
+        assert!(estimator.quality().loss > 0.0);
+        // unknown nonces don't change anything
+        estimator.pong(42, lost + QualityEstimator::PING_TIMEOUT);
+        assert_eq!(estimator.quality().rtt, Duration::from_millis(40));
+
+        assert_eq!(quality.send_bandwidth(500_000.0), 1_000_000_000);
+        let congested = estimator.quality();
+        assert!(congested.loss > ConnectionQuality::CONGESTION_LOSS);
+        assert!(congested.send_bandwidth(500_000.0) < 500_000);
+        assert_eq!(congested.send_bandwidth(0.0), ConnectionQuality::MIN_BANDWIDTH);
+    }
+}
@@ -5,7 +5,7 @@ use crate::{
     handshake::{ReliableDrain, ReliableSink},
     message::{ITMessage, ALLOC_BLOCK},
     prio::PrioManager,
-    types::{Bandwidth, Mid, Promises, Sid},
+    types::{Bandwidth, HandshakeFeatures, Mid, Promises, Sid},
     RecvProtocol, SendProtocol, UnreliableDrain, UnreliableSink,
 };
 use async_trait::async_trait;
@@ -13,6 +13,10 @@ use bytes::BytesMut;
 use hashbrown::HashMap;
 use instant::{Duration, Instant};
 
+/// A stream without ordering or consistency promises is reset after this many
+/// corrupt data frames, others on the first one
+const MAX_CORRUPT_FRAMES: u32 = 3;
+
 /// TCP implementation of [`SendProtocol`]
 ///
 /// [`SendProtocol`]: crate::SendProtocol
@@ -30,6 +34,10 @@ where
     drain: D,
     #[allow(dead_code)]
     last: Instant,
+    /// announce checksums in the handshake
+    request_checksums: bool,
+    /// write data frames with a checksum, both sides agreed on it
+    checksums: bool,
 }
 
 /// TCP implementation of [`RecvProtocol`]
@@ -43,6 +51,10 @@ where
     buffer: BytesMut,
     itmsg_allocator: BytesMut,
     incoming: HashMap<Mid, ITMessage>,
+    /// corrupt data frames per stream since its last reset
+    corrupt_frames: HashMap<Sid, u32>,
+    /// promises of the open streams, a corrupt frame breaks some of them
+    promises: HashMap<Sid, Promises>,
     sink: S,
 }
 
@@ -60,9 +72,21 @@ where
             pending_shutdown: false,
             drain,
             last: Instant::now(),
+            request_checksums: false,
+            checksums: false,
         }
     }
 
+    /// Add a checksum to each data frame, for transports which may corrupt data
+    /// on the way. Checksums are announced in the handshake and only used if
+    /// the remote announces them too. The receiver drops messages with
+    /// corrupt frames and resets their streams, see [`MAX_CORRUPT_FRAMES`].
+    #[must_use]
+    pub fn with_checksums(mut self, checksums: bool) -> Self {
+        self.request_checksums = checksums;
+        self
+    }
+
     /// returns all promises that this Protocol can take care of
     /// If you open a Stream anyway, unsupported promises are ignored.
     pub fn supported_promises() -> Promises {
@@ -82,9 +106,25 @@ where
             buffer: BytesMut::new(),
             itmsg_allocator: BytesMut::with_capacity(ALLOC_BLOCK),
             incoming: HashMap::new(),
+            corrupt_frames: HashMap::new(),
+            promises: HashMap::new(),
             sink,
         }
     }
+
+    /// Removes the message once all of it arrived, returns it unless it was
+    /// corrupt
+    fn try_finish(&mut self, mid: Mid) -> Option<ProtocolEvent> {
+        let m = self.incoming.get(&mid)?;
+        if m.data.len() != m.length as usize {
+            return None;
+        }
+        let m = self.incoming.remove(&mid).unwrap();
+        (!m.corrupt).then(|| ProtocolEvent::Message {
+            sid: m.sid,
+            data: m.data.freeze(),
+        })
+    }
 }
 
 #[async_trait]
@@ -141,6 +181,10 @@ where
                     self.pending_shutdown = true;
                 }
             },
+            ProtocolEvent::Ping { .. } | ProtocolEvent::Pong { .. } => {
+                event.to_frame().write_bytes(&mut self.buffer);
+                self.drain.send(self.buffer.split()).await?;
+            },
             ProtocolEvent::Message { data, sid } => {
                 self.store.add(data, self.next_mid, sid);
                 self.next_mid += 1;
@@ -163,7 +207,11 @@ where
                 data_bandwidth += data.len();
                 data_frames += 1;
             }
-            frame.write_bytes(&mut self.buffer);
+            if self.checksums {
+                frame.write_bytes_checked(&mut self.buffer);
+            } else {
+                frame.write_bytes(&mut self.buffer);
+            }
         }
         self.drain.send(self.buffer.split()).await?;
 
@@ -216,6 +264,7 @@ where
                                 promises,
                                 guaranteed_bandwidth,
                             } => {
+                                self.promises.insert(sid, promises);
                                 break 'outer Ok(ProtocolEvent::OpenStream {
                                     sid,
                                     prio: prio.min(crate::types::HIGHEST_PRIO),
@@ -224,8 +273,16 @@ where
                                 });
                             },
                             ITFrame::CloseStream { sid } => {
+                                self.promises.remove(&sid);
+                                self.corrupt_frames.remove(&sid);
                                 break 'outer Ok(ProtocolEvent::CloseStream { sid });
                             },
+                            ITFrame::Ping { nonce } => {
+                                break 'outer Ok(ProtocolEvent::Ping { nonce });
+                            },
+                            ITFrame::Pong { nonce } => {
+                                break 'outer Ok(ProtocolEvent::Pong { nonce });
+                            },
                             ITFrame::DataHeader { sid, mid, length } => {
                                 let m = ITMessage::new(sid, length, &mut self.itmsg_allocator);
                                 self.incoming.insert(mid, m);
@@ -243,13 +300,39 @@ where
                                     },
                                 };
                                 m.data.extend_from_slice(&data);
-                                if m.data.len() == m.length as usize {
-                                    // finished, yay
-                                    let m = self.incoming.remove(&mid).unwrap();
-                                    break 'outer Ok(ProtocolEvent::Message {
-                                        sid: m.sid,
-                                        data: m.data.freeze(),
-                                    });
+                                if let Some(event) = self.try_finish(mid) {
+                                    break 'outer Ok(event);
+                                }
+                            },
+                            ITFrame::CorruptData { mid, length } => {
+                                // the mid itself might be what got corrupted
+                                let m = match self.incoming.get_mut(&mid) {
+                                    Some(m) => m,
+                                    None => continue,
+                                };
+                                let sid = m.sid;
+                                log::warn!("dropping message {} with a corrupt data frame", mid);
+                                m.corrupt = true;
+                                // keep counting towards the end of the message
+                                let len = (m.data.len() + length).min(m.length as usize);
+                                m.data.resize(len, 0);
+                                let _ = self.try_finish(mid);
+
+                                // later messages can't be delivered in order or
+                                // without a gap anymore
+                                let strict = self.promises.get(&sid).map_or(false, |p| {
+                                    p.intersects(Promises::ORDERED | Promises::CONSISTENCY)
+                                });
+                                let count = self.corrupt_frames.entry(sid).or_insert(0);
+                                *count += 1;
+                                if strict || *count >= MAX_CORRUPT_FRAMES {
+                                    log::warn!("resetting stream {} after corruption", sid);
+                                    self.corrupt_frames.remove(&sid);
+                                    self.promises.remove(&sid);
+                                    for m in self.incoming.values_mut().filter(|m| m.sid == sid) {
+                                        m.corrupt = true;
+                                    }
+                                    break 'outer Ok(ProtocolEvent::CloseStream { sid });
                                 }
                             },
                         };
@@ -278,6 +361,18 @@ where
         frame.write_bytes(&mut buffer);
         self.drain.send(buffer).await
     }
+
+    fn features(&self) -> HandshakeFeatures {
+        if self.request_checksums {
+            HandshakeFeatures::CHECKSUMS
+        } else {
+            HandshakeFeatures::empty()
+        }
+    }
+
+    fn negotiated(&mut self, features: HandshakeFeatures) {
+        self.checksums = features.contains(HandshakeFeatures::CHECKSUMS);
+    }
 }
 
 #[async_trait]
@@ -7,7 +7,9 @@ use bytes::Bytes;
 use hashbrown::HashMap;
 #[cfg(feature = "compression")]
 use lz_fear::raw::DecodeError;
-use network_protocol::{Bandwidth, InitProtocolError, Pid, Prio, Promises, Sid};
+use network_protocol::{
+    Bandwidth, ConnectionQuality, InitProtocolError, Pid, Prio, Promises, Sid,
+};
 use serde::{de::DeserializeOwned, Serialize};
 use std::{
     net::SocketAddr,
@@ -51,6 +53,7 @@ pub struct Participant {
     a2b_open_stream_s: Mutex<mpsc::UnboundedSender<A2bStreamOpen>>,
     b2a_stream_opened_r: Mutex<mpsc::UnboundedReceiver<Stream>>,
     b2a_bandwidth_stats_r: watch::Receiver<f32>,
+    b2a_quality_r: watch::Receiver<ConnectionQuality>,
     a2s_disconnect_s: A2sDisconnect,
 }
 
@@ -477,6 +480,7 @@ impl Participant {
         a2b_open_stream_s: mpsc::UnboundedSender<A2bStreamOpen>,
         b2a_stream_opened_r: mpsc::UnboundedReceiver<Stream>,
         b2a_bandwidth_stats_r: watch::Receiver<f32>,
+        b2a_quality_r: watch::Receiver<ConnectionQuality>,
         a2s_disconnect_s: mpsc::UnboundedSender<(Pid, S2bShutdownBparticipant)>,
     ) -> Self {
         Self {
@@ -485,6 +489,7 @@ impl Participant {
             a2b_open_stream_s: Mutex::new(a2b_open_stream_s),
             b2a_stream_opened_r: Mutex::new(b2a_stream_opened_r),
             b2a_bandwidth_stats_r,
+            b2a_quality_r,
             a2s_disconnect_s: Arc::new(Mutex::new(Some(a2s_disconnect_s))),
         }
     }
@@ -708,6 +713,10 @@ impl Participant {
     /// This WILL fluctuate based on the amount/size of send messages.
     pub fn bandwidth(&self) -> f32 { *self.b2a_bandwidth_stats_r.borrow() }
 
+    /// Returns the latest estimate of round trip time, jitter and loss,
+    /// measured with keepalive pings about once a second.
+    pub fn connection_quality(&self) -> ConnectionQuality { *self.b2a_quality_r.borrow() }
+
     /// Returns the remote [`Pid`](network_protocol::Pid)
     pub fn remote_pid(&self) -> Pid { self.remote_pid }
 }
@@ -111,4 +111,4 @@ pub use api::{
     ParticipantError, Stream, StreamError, StreamParams,
 };
 pub use message::Message;
-pub use network_protocol::{InitProtocolError, Pid, Promises};
+pub use network_protocol::{ConnectionQuality, InitProtocolError, Pid, Promises};
@@ -7,8 +7,8 @@ use bytes::Bytes;
 use futures_util::{FutureExt, StreamExt};
 use hashbrown::HashMap;
 use network_protocol::{
-    Bandwidth, Cid, Pid, Prio, Promises, ProtocolEvent, RecvProtocol, SendProtocol, Sid,
-    _internal::SortedVec,
+    Bandwidth, Cid, ConnectionQuality, Pid, Prio, Promises, ProtocolEvent, QualityEstimator,
+    RecvProtocol, SendProtocol, Sid, _internal::SortedVec,
 };
 use std::{
     sync::{
@@ -54,6 +54,7 @@ struct ControlChannels {
     b2a_stream_opened_s: mpsc::UnboundedSender<Stream>,
     s2b_create_channel_r: mpsc::UnboundedReceiver<S2bCreateChannel>,
     b2a_bandwidth_stats_s: watch::Sender<f32>,
+    b2a_quality_s: watch::Sender<ConnectionQuality>,
     s2b_shutdown_bparticipant_r: oneshot::Receiver<S2bShutdownBparticipant>, /* own */
 }
 
@@ -95,18 +96,21 @@ impl BParticipant {
         mpsc::UnboundedSender<S2bCreateChannel>,
         oneshot::Sender<S2bShutdownBparticipant>,
         watch::Receiver<f32>,
+        watch::Receiver<ConnectionQuality>,
     ) {
         let (a2b_open_stream_s, a2b_open_stream_r) = mpsc::unbounded_channel::<A2bStreamOpen>();
         let (b2a_stream_opened_s, b2a_stream_opened_r) = mpsc::unbounded_channel::<Stream>();
         let (s2b_shutdown_bparticipant_s, s2b_shutdown_bparticipant_r) = oneshot::channel();
         let (s2b_create_channel_s, s2b_create_channel_r) = mpsc::unbounded_channel();
         let (b2a_bandwidth_stats_s, b2a_bandwidth_stats_r) = watch::channel::<f32>(0.0);
+        let (b2a_quality_s, b2a_quality_r) = watch::channel(ConnectionQuality::default());
 
         let run_channels = Some(ControlChannels {
             a2b_open_stream_r,
             b2a_stream_opened_s,
             s2b_create_channel_r,
             b2a_bandwidth_stats_s,
+            b2a_quality_s,
             s2b_shutdown_bparticipant_r,
         });
 
@@ -129,6 +133,7 @@ impl BParticipant {
             s2b_create_channel_s,
             s2b_shutdown_bparticipant_s,
             b2a_bandwidth_stats_r,
+            b2a_quality_r,
         )
     }
 
@@ -145,6 +150,10 @@ impl BParticipant {
             crossbeam_channel::unbounded::<(Cid, Sid, Prio, Promises, u64)>();
         let (b2b_notify_send_of_recv_close_s, b2b_notify_send_of_recv_close_r) =
             crossbeam_channel::unbounded::<(Cid, Sid)>();
+        let (b2b_notify_send_of_recv_ping_s, b2b_notify_send_of_recv_ping_r) =
+            crossbeam_channel::unbounded::<(Cid, u64)>();
+        let (b2b_notify_send_of_recv_pong_s, b2b_notify_send_of_recv_pong_r) =
+            crossbeam_channel::unbounded::<(u64, Instant)>();
 
         let (a2b_close_stream_s, a2b_close_stream_r) = mpsc::unbounded_channel::<Sid>();
         let (a2b_msg_s, a2b_msg_r) = crossbeam_channel::unbounded::<(Sid, Bytes)>();
@@ -164,8 +173,11 @@ impl BParticipant {
                 b2b_close_send_protocol_r,
                 b2b_notify_send_of_recv_open_r,
                 b2b_notify_send_of_recv_close_r,
+                b2b_notify_send_of_recv_ping_r,
+                b2b_notify_send_of_recv_pong_r,
                 b2s_prio_statistic_s,
                 run_channels.b2a_bandwidth_stats_s,
+                run_channels.b2a_quality_s,
             ),
             self.recv_mgr(
                 run_channels.b2a_stream_opened_s,
@@ -174,6 +186,8 @@ impl BParticipant {
                 b2b_close_send_protocol_s.clone(),
                 b2b_notify_send_of_recv_open_s,
                 b2b_notify_send_of_recv_close_s,
+                b2b_notify_send_of_recv_ping_s,
+                b2b_notify_send_of_recv_pong_s,
             ),
              self.create_channel_mgr(
                 run_channels.s2b_create_channel_r,
@@ -215,8 +229,11 @@ impl BParticipant {
             Bandwidth,
         )>,
         b2b_notify_send_of_recv_close_r: crossbeam_channel::Receiver<(Cid, Sid)>,
+        b2b_notify_send_of_recv_ping_r: crossbeam_channel::Receiver<(Cid, u64)>,
+        b2b_notify_send_of_recv_pong_r: crossbeam_channel::Receiver<(u64, Instant)>,
         _b2s_prio_statistic_s: mpsc::UnboundedSender<B2sPrioStatistic>,
         b2a_bandwidth_stats_s: watch::Sender<f32>,
+        b2a_quality_s: watch::Sender<ConnectionQuality>,
     ) {
         let mut sorted_send_protocols = SortedVec::<Cid, SendProtocols>::default();
         let mut sorted_stream_protocols = SortedVec::<Sid, Cid>::default();
@@ -224,6 +241,7 @@ impl BParticipant {
         let mut last_instant = Instant::now();
         let mut stream_ids = self.offset_sid;
         let mut part_bandwidth = 0.0f32;
+        let mut quality = QualityEstimator::new();
 
         log::trace!("workaround, actively wait for first protocol");
         if let Some((c, p)) = b2b_add_protocol_r.recv().await {
@@ -321,6 +339,27 @@ impl BParticipant {
                     };
                 }
 
+                // keepalive, answer the remote on the channel it pinged and measure our own
+                for (c, nonce) in b2b_notify_send_of_recv_ping_r.try_iter() {
+                    if let Some(p) = sorted_send_protocols.get_mut(&c) {
+                        cid = c;
+                        p.send(ProtocolEvent::Pong { nonce }).await?;
+                    }
+                }
+                for (nonce, received) in b2b_notify_send_of_recv_pong_r.try_iter() {
+                    quality.pong(nonce, received);
+                }
+                if let Some(nonce) = quality.ping(Instant::now()) {
+                    cid = Self::best_protocol(&sorted_send_protocols, Promises::empty())
+                        .unwrap();
+                    sorted_send_protocols
+                        .get_mut(&cid)
+                        .unwrap()
+                        .send(ProtocolEvent::Ping { nonce })
+                        .await?;
+                    let _ = b2a_quality_s.send(quality.quality());
+                }
+
                 if let Some(sid) = close {
                     log::trace!("delete stream {:?}", stream_ids);
                     self.delete_stream(sid).await;
@@ -341,9 +380,11 @@ impl BParticipant {
                 let diff = send_time.duration_since(last_instant);
                 last_instant = send_time;
                 let mut cnt = 0;
+                // back off while pings get lost
+                let bandwidth = quality.quality().send_bandwidth(part_bandwidth);
                 for (c, p) in sorted_send_protocols.data.iter_mut() {
                     cid = *c;
-                    cnt += p.flush(1_000_000_000, diff).await?; //this actually blocks, so we cant set streams while it.
+                    cnt += p.flush(bandwidth, diff).await?; //this actually blocks, so we cant set streams while it.
                 }
                 let flush_time = send_time.elapsed().as_secs_f32();
                 part_bandwidth = 0.99 * part_bandwidth + 0.01 * (cnt as f32 / flush_time);
@@ -400,6 +441,8 @@ impl BParticipant {
             Bandwidth,
         )>,
         b2b_notify_send_of_recv_close_s: crossbeam_channel::Sender<(Cid, Sid)>,
+        b2b_notify_send_of_recv_ping_s: crossbeam_channel::Sender<(Cid, u64)>,
+        b2b_notify_send_of_recv_pong_s: crossbeam_channel::Sender<(u64, Instant)>,
     ) {
         let mut recv_protocols: HashMap<Cid, JoinHandle<()>> = HashMap::new();
         // we should be able to directly await futures imo
@@ -490,6 +533,14 @@ impl BParticipant {
                         };
                         retrigger(cid, p, &mut recv_protocols);
                     },
+                    Ok(ProtocolEvent::Ping { nonce }) => {
+                        let _ = b2b_notify_send_of_recv_ping_s.send((cid, nonce));
+                        retrigger(cid, p, &mut recv_protocols);
+                    },
+                    Ok(ProtocolEvent::Pong { nonce }) => {
+                        let _ = b2b_notify_send_of_recv_pong_s.send((nonce, Instant::now()));
+                        retrigger(cid, p, &mut recv_protocols);
+                    },
                     Ok(ProtocolEvent::Shutdown) => {
                         log::info!("shutdown protocol {}", cid);
                         if let Err(e) = b2b_close_send_protocol_s.send(cid).await {
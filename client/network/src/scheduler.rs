@@ -354,6 +354,7 @@ impl Scheduler {
                                 s2b_create_channel_s,
                                 s2b_shutdown_bparticipant_s,
                                 b2a_bandwidth_stats_r,
+                                b2a_quality_r,
                             ) = BParticipant::new(local_pid, pid, sid);
 
                             let participant = Participant::new(
@@ -362,6 +363,7 @@ impl Scheduler {
                                 a2b_open_stream_s,
                                 b2a_stream_opened_r,
                                 b2a_bandwidth_stats_r,
+                                b2a_quality_r,
                                 participant_channels.a2s_disconnect_s,
                             );
 
@@ -2,6 +2,7 @@ use crate::{
     gitfragments::{
         read_file_from_path, transform_fragment, LocalizationEntryState, LocalizationState,
     },
+    lint,
     path::{BasePath, LangPath},
     raw::{self, RawFragment, RawLanguage},
     stats::{
@@ -233,6 +234,18 @@ pub fn test_specific_localizations(
     if analysis.len() > 1 {
         print_overall_stats(analysis);
     }
+
+    let policy = lint::KeyPolicy::load(path);
+    for &language_identifier in language_identifiers {
+        let violations = lint::lint_language(&path.i18n_path(language_identifier), &policy);
+        if !violations.is_empty() {
+            println!(
+                "{} key naming violations in {}, run with --lint for details",
+                violations.len(),
+                language_identifier
+            );
+        }
+    }
 }
 
 /// Test all localizations
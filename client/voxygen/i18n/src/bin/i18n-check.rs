@@ -1,5 +1,5 @@
 use clap::{App, Arg};
-use veloren_voxygen_i18n::{analysis, verification, BasePath};
+use veloren_voxygen_i18n::{analysis, font_subsets, lint, suggest, verification, BasePath};
 
 fn main() {
     let matches = App::new("i18n-check")
@@ -21,6 +21,28 @@ fn main() {
                 .long("test")
                 .help("test all localizations"),
         )
+        .arg(
+            Arg::with_name("lint")
+                .long("lint")
+                .help("check key names of all localizations against the key policy"),
+        )
+        .arg(
+            Arg::with_name("fix")
+                .long("fix")
+                .requires("lint")
+                .help("rename keys which violate the key policy where possible, in the code too"),
+        )
+        .arg(
+            Arg::with_name("review")
+                .long("review")
+                .requires("CODE")
+                .help("write a review file of the keys missing in the language for translators"),
+        )
+        .arg(
+            Arg::with_name("font-subsets")
+                .long("font-subsets")
+                .help("write subsets of the large fonts for clients not downloading them whole"),
+        )
         .arg(
             Arg::with_name("verbose")
                 .short("v")
@@ -41,7 +63,11 @@ fn main() {
     let csv_enabled = matches.is_present("csv");
 
     if let Some(code) = matches.value_of("CODE") {
-        analysis::test_specific_localizations(&path, &[code], be_verbose, csv_enabled);
+        if matches.is_present("review") {
+            suggest::write_review(&path, code, &suggest::NoTranslator);
+        } else {
+            analysis::test_specific_localizations(&path, &[code], be_verbose, csv_enabled);
+        }
     }
     if matches.is_present("test") {
         analysis::test_all_localizations(&path, be_verbose, csv_enabled);
@@ -49,4 +75,10 @@ fn main() {
     if matches.is_present("verify") {
         verification::verify_all_localizations(&path);
     }
+    if matches.is_present("lint") {
+        lint::lint_all_localizations(&path, matches.is_present("fix"));
+    }
+    if matches.is_present("font-subsets") {
+        font_subsets::write_font_subsets(&path);
+    }
 }
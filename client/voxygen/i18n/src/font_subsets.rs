@@ -0,0 +1,83 @@
+//! Generate the font subsets used when a large font isn't available whole,
+//! see `common_assets::font`
+use crate::{path::BasePath, raw};
+use common_assets::font::{self, CharIndex};
+use hashbrown::{HashMap, HashSet};
+use std::{collections::BTreeSet, fs};
+
+/// For each font larger than `font::SUBSET_MIN_SIZE`, write `<font>_subset`
+/// with the characters of all languages using it and `<font>_blocks` with the
+/// remaining glyphs, to be loaded on demand
+pub fn write_font_subsets(path: &BasePath) {
+    let mut font_chars = HashMap::<String, HashSet<char>>::new();
+    for language in path.i18n_directories() {
+        let manifest = raw::load_manifest(&language).expect("failed to load language manifest");
+        let fonts = manifest
+            .fonts
+            .values()
+            .map(|font| font.asset_key.clone())
+            .collect::<HashSet<_>>();
+        let raw = raw::load_raw_language(&language, manifest).expect("failed to load language");
+        let chars = raw
+            .fragments
+            .values()
+            .flat_map(|fragment| {
+                fragment
+                    .string_map
+                    .values()
+                    .chain(fragment.vector_map.values().flatten())
+            })
+            .flat_map(|text| text.chars())
+            .collect::<HashSet<_>>();
+        for asset_key in fonts {
+            font_chars.entry(asset_key).or_default().extend(&chars);
+        }
+    }
+
+    for (asset_key, chars) in font_chars {
+        let base = path
+            .root_path()
+            .join("assets")
+            .join(asset_key.replace('.', "/"));
+        let data = fs::read(base.with_extension("ttf")).expect("couldn't read font");
+        if data.len() < font::SUBSET_MIN_SIZE {
+            continue;
+        }
+        let name = asset_key.rsplit('.').next().unwrap_or(&asset_key);
+        let index = CharIndex::new(&data).expect("failed to read font");
+        let subset = index
+            .subset(&data, |c| c.is_ascii() || chars.contains(&c))
+            .expect("failed to subset font");
+        fs::write(base.with_file_name(format!("{}_subset.ttf", name)), &subset)
+            .expect("couldn't write font subset");
+
+        let covered = index.covered_chars(&subset).expect("failed to read font subset");
+        let blocks = index
+            .covered_chars(&data)
+            .expect("failed to read font")
+            .into_iter()
+            .filter(|c| !covered.contains(c))
+            .map(font::block_of)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        let blocks_dir = base.with_file_name(format!("{}_blocks", name));
+        // Blocks of an older version of the font would be merged wrongly
+        let _ = fs::remove_dir_all(&blocks_dir);
+        fs::create_dir_all(&blocks_dir).expect("couldn't create glyph block directory");
+        let glyph_blocks = index
+            .glyph_blocks(&data, &blocks)
+            .expect("failed to read glyphs");
+        for (block, glyphs) in glyph_blocks {
+            fs::write(blocks_dir.join(format!("{:x}.glyphs", block)), glyphs.to_bytes())
+                .expect("couldn't write glyph block");
+        }
+        println!(
+            "{}: {} of {} bytes in the subset, {} blocks",
+            asset_key,
+            subset.len(),
+            data.len(),
+            blocks.len()
+        );
+    }
+}
@@ -1,21 +1,37 @@
 #[cfg(any(feature = "bin", test))]
 pub mod analysis;
 #[cfg(any(feature = "bin", test))]
+pub mod font_subsets;
+#[cfg(any(feature = "bin", test))]
 mod gitfragments;
+pub mod lint;
+mod memo;
 mod path;
 mod raw;
 #[cfg(any(feature = "bin", test))] pub mod stats;
+#[cfg(any(feature = "bin", test))]
+pub mod suggest;
 pub mod verification;
 
 //reexport
 pub use path::BasePath;
 
 use crate::path::{LANG_EXTENSION, LANG_MANIFEST_FILE};
-use common_assets::{self, source::DirEntry, AssetExt, AssetGuard, AssetHandle};
+use common_assets::{
+    self, source::DirEntry, AssetExt, AssetGuard, AssetHandle, ManifestDirLoader, Placeholder,
+};
 use hashbrown::{HashMap, HashSet};
+use lazy_static::lazy_static;
+use memo::MissingKeys;
+pub use memo::MissingKey;
 use raw::{RawFragment, RawLanguage, RawManifest};
 use serde::{Deserialize, Serialize};
-use std::{io, path::PathBuf};
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    path::PathBuf,
+    sync::{Mutex, RwLock},
+};
 
 /// The reference language, aka the more up-to-date localization data.
 /// Also the default language at first startup.
@@ -27,6 +43,15 @@ pub struct LanguageMetadata {
     /// A human friendly language name (e.g. "English (US)")
     pub language_name: String,
 
+    /// The name of the language in itself (e.g. "Deutsch"), shown in the
+    /// language pickers. `language_name` is used if it is empty.
+    #[serde(default)]
+    pub native_name: String,
+
+    /// Asset key of an image shown next to the name, usually a flag
+    #[serde(default)]
+    pub icon: Option<String>,
+
     /// A short text identifier for this language (e.g. "en_US")
     ///
     /// On the opposite of `language_name` that can change freely,
@@ -34,6 +59,21 @@ pub struct LanguageMetadata {
     /// is used by setting components to store the language
     /// selected by the user.
     pub language_identifier: String,
+
+    /// People who translated this language, shown in the credits
+    #[serde(default)]
+    pub translators: Vec<String>,
+}
+
+impl LanguageMetadata {
+    /// The name to show in language pickers
+    pub fn display_name(&self) -> &str {
+        if self.native_name.is_empty() {
+            &self.language_name
+        } else {
+            &self.native_name
+        }
+    }
 }
 
 /// Store font metadata
@@ -54,6 +94,36 @@ impl Font {
 /// Store font metadata
 pub type Fonts = HashMap<String, Font>;
 
+/// Hints on how text of a language should be laid out, the defaults are tuned
+/// for latin scripts
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LayoutHints {
+    /// Multiplier applied to the font's line height
+    pub line_height: f32,
+    /// Extra space between two characters, in (unscaled) pixels
+    pub letter_spacing: f32,
+    /// Font sizes are never scaled below this
+    pub min_font_size: u32,
+}
+
+impl Default for LayoutHints {
+    fn default() -> Self {
+        Self {
+            line_height: 1.0,
+            letter_spacing: 0.0,
+            min_font_size: 0,
+        }
+    }
+}
+
+impl LayoutHints {
+    /// Scale input size with `font` and clamp it to the minimum size
+    pub fn scale(&self, font: &Font, value: u32) -> u32 {
+        font.scale(value).max(self.min_font_size)
+    }
+}
+
 /// Store internationalization data
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 struct Language {
@@ -74,12 +144,14 @@ struct Language {
     /// Font configuration is stored here
     pub(crate) fonts: Fonts,
 
+    pub(crate) layout: LayoutHints,
+
     pub(crate) metadata: LanguageMetadata,
 }
 
 impl Language {
     /// Get a localized text from the given key
-    pub fn get<'a>(&'a self, key: &'a str) -> Option<&str> {
+    pub fn get(&self, key: &str) -> Option<&str> {
         self.string_map.get(key).map(String::as_str)
     }
 
@@ -98,6 +170,14 @@ impl Language {
             }
         })
     }
+
+    /// All characters the texts of the language are made of
+    fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.string_map
+            .values()
+            .chain(self.vector_map.values().flatten())
+            .flat_map(|text| text.chars())
+    }
 }
 
 impl common_assets::Compound for Language {
@@ -106,22 +186,18 @@ impl common_assets::Compound for Language {
         asset_key: &str,
     ) -> Result<Self, common_assets::BoxedError> {
        
-        log::info!("start load Language, key:{}, file:{}", asset_key, LANG_MANIFEST_FILE);
+        log::debug!("start load Language, key:{}", asset_key);
 
         let manifest_path = [asset_key, ".", LANG_MANIFEST_FILE].concat();
         let manifest = cache.load::<RawManifest>(&manifest_path)?.cloned();
-        log::info!("load Language manifest over");
 
         let ids = cache.load_dir::<RawFragment<String>>(asset_key)?.ids();
-        log::info!("load Language ids over");
 
         // Walk through files in the folder, collecting localization fragment to merge
         // inside the asked_localization
         let mut fragments = HashMap::new();
         
         for id in ids {
-            log::info!("load Language: {}", id);
-
             // Don't try to load manifests
             if let Some(id) = id.strip_suffix(LANG_MANIFEST_FILE) {
                 if id.ends_with('.') {
@@ -136,12 +212,14 @@ impl common_assets::Compound for Language {
                     fragments.insert(PathBuf::from(id), fragment.clone());
                 },
                 Err(e) => {
-                    log::warn!("Unable to load asset {}, error={:?}", id, e);
+                    // Missing keys fall back to the reference language
+                    common_assets::record_substitution::<RawFragment<String>>(id, &e);
+                    fragments.insert(PathBuf::from(id), RawFragment::placeholder());
                 },
             }
         }
 
-        log::info!("end load Language");
+        log::debug!("end load Language");
         Ok(Language::from(RawLanguage {
             manifest,
             fragments,
@@ -158,6 +236,23 @@ pub struct LocalizationHandle {
     pub use_english_fallback: bool,
 }
 
+lazy_static! {
+    static ref MISSING_KEYS: RwLock<MissingKeys> = RwLock::new(MissingKeys::default());
+    // Installed while the game runs, after the list of languages was loaded
+    static ref SIDE_LOADED: Mutex<Vec<LanguageMetadata>> = Mutex::new(Vec::new());
+    // Identifier of the language loaded last, listed first
+    static ref ACTIVE: Mutex<String> = Mutex::new(REFERENCE_LANG.to_owned());
+}
+
+/// Keys recently looked up but missing in the active language, most recent
+/// first. Recorded in all builds so translators can report what they
+/// actually came across.
+pub fn missing_keys() -> Vec<MissingKey> {
+    MISSING_KEYS
+        .read()
+        .map_or_else(|_| Vec::new(), |missing| missing.list())
+}
+
 // RAII guard returned from Localization::read(), resembles AssetGuard
 pub struct LocalizationGuard {
     active: AssetGuard<Language>,
@@ -176,13 +271,29 @@ impl LocalizationGuard {
     /// then the key is returned.
     pub fn get<'a>(&'a self, key: &'a str) -> &str {
         self.active.get(key).unwrap_or_else(|| {
-            self.fallback
-                .as_ref()
-                .and_then(|f| f.get(key))
-                .unwrap_or(key)
+            let fallback = self.fallback.as_ref().and_then(|f| f.get(key));
+            self.record_missing(key, fallback.is_some());
+            fallback.unwrap_or(key)
         })
     }
 
+    /// Get a localized text from the given key, `None` if neither the active
+    /// language nor the fallback know the key
+    ///
+    /// Unlike `get` this allows telling a missing key apart from a text which
+    /// happens to equal its key.
+    pub fn get_opt(&self, key: &str) -> Option<&str> {
+        self.active
+            .get(key)
+            .or_else(|| self.fallback.as_ref().and_then(|f| f.get(key)))
+    }
+
+    /// Get a localized text from the given key, or `default` if the key is
+    /// missing
+    pub fn get_or<'a>(&'a self, key: &str, default: &'a str) -> &'a str {
+        self.get_opt(key).unwrap_or(default)
+    }
+
     /// Get a variation of localized text from the given key
     ///
     /// `index` should be a random number from `0` to `u16::max()`
@@ -191,13 +302,39 @@ impl LocalizationGuard {
     /// then the key is returned.
     pub fn get_variation<'a>(&'a self, key: &'a str, index: u16) -> &str {
         self.active.get_variation(key, index).unwrap_or_else(|| {
-            self.fallback
+            let fallback = self
+                .fallback
                 .as_ref()
-                .and_then(|f| f.get_variation(key, index))
-                .unwrap_or(key)
+                .and_then(|f| f.get_variation(key, index));
+            self.record_missing(key, fallback.is_some());
+            fallback.unwrap_or(key)
         })
     }
 
+    fn record_missing(&self, key: &str, fallback: bool) {
+        let language = &self.active.metadata.language_identifier;
+        // Keys missing every frame were recorded before and only need to be
+        // counted
+        if MISSING_KEYS
+            .read()
+            .map_or(false, |missing| missing.hit_known(language, key))
+        {
+            return;
+        }
+        if let Ok(mut missing) = MISSING_KEYS.write() {
+            missing.hit(language, key, fallback);
+        }
+    }
+
+    /// Get a variation of localized text which stays the same for a given
+    /// `entity_seed`, so e.g. an npc keeps greeting with the same words
+    ///
+    /// If the key is not present in the localization object
+    /// then the key is returned.
+    pub fn get_variation_stable<'a>(&'a self, key: &'a str, entity_seed: u64) -> &str {
+        self.get_variation(key, variation_index(key, entity_seed))
+    }
+
     /// Return the missing keys compared to the reference language
     fn list_missing_entries(&self) -> (HashSet<String>, HashSet<String>) {
         if let Some(ref_lang) = &self.fallback {
@@ -242,6 +379,8 @@ impl LocalizationGuard {
 
     pub fn fonts(&self) -> &Fonts { &self.active.fonts }
 
+    pub fn layout_hints(&self) -> &LayoutHints { &self.active.layout }
+
     pub fn metadata(&self) -> &LanguageMetadata { &self.active.metadata }
 }
 
@@ -266,13 +405,21 @@ impl LocalizationHandle {
         let language_key = ["voxygen.i18n.", specifier].concat();
         let is_default = language_key == default_key;
         let active = Language::load(&language_key)?;
+        let fallback = if is_default {
+            None
+        } else {
+            Language::load(&default_key).ok()
+        };
+        // Large fonts only contain the glyphs which were asked for
+        for language in std::iter::once(active).chain(fallback) {
+            common_assets::request_glyphs(language.read().chars());
+        }
+        if let Ok(mut identifier) = ACTIVE.lock() {
+            identifier.clone_from(&active.read().metadata.language_identifier);
+        }
         Ok(Self {
             active,
-            fallback: if is_default {
-                None
-            } else {
-                Language::load(&default_key).ok()
-            },
+            fallback,
             use_english_fallback: false,
         })
     }
@@ -282,34 +429,15 @@ impl LocalizationHandle {
     }
 }
 
-struct FindManifests;
+/// Directories with a language manifest
+struct LanguageManifests;
 
-impl common_assets::Compound for FindManifests {
-    fn load<S: common_assets::Source + ?Sized>(
-        _: &common_assets::AssetCache<S>,
-        _: &str,
-    ) -> Result<Self, common_assets::BoxedError> {
-        Ok(Self)
-    }
-}
-
-impl common_assets::DirLoadable for FindManifests {
-    fn select_ids<S: common_assets::Source + ?Sized>(
+impl common_assets::DirFilter for LanguageManifests {
+    fn select<S: common_assets::Source + ?Sized>(
         source: &S,
-        specifier: &str,
-    ) -> io::Result<Vec<common_assets::SharedString>> {
-        let mut specifiers = Vec::new();
-
-        source.read_dir(specifier, &mut |entry| {
-            if let DirEntry::Directory(spec) = entry {
-                let manifest_spec = [spec, ".", LANG_MANIFEST_FILE].concat();
-                if source.exists(DirEntry::File(&manifest_spec, LANG_EXTENSION)) {
-                    specifiers.push(manifest_spec.into());
-                }
-            }
-        })?;
-
-        Ok(specifiers)
+        entry: DirEntry,
+    ) -> Option<common_assets::SharedString> {
+        common_assets::manifest_in_dir(source, entry, LANG_MANIFEST_FILE, LANG_EXTENSION)
     }
 }
 
@@ -325,7 +453,7 @@ impl common_assets::Compound for LocalizationList {
 
         log::info!("common_assets::Compound LocalizationList load_dir Start"); 
 
-        let languages = common_assets::load_dir::<FindManifests>(specifier)
+        let languages = common_assets::load_dir::<ManifestDirLoader<LanguageManifests>>(specifier)
             .unwrap_or_else(|e| panic!("Failed to get manifests from {}: {:?}", specifier, e))
             .ids()
             .filter_map(|spec| cache.load::<RawManifest>(spec).ok())
@@ -337,7 +465,127 @@ impl common_assets::Compound for LocalizationList {
     }
 }
 
-/// Load all the available languages located in the voxygen asset directory
+/// Puts the language `active` first and sorts the others by their native name
+fn sort_localizations(languages: &mut [LanguageMetadata], active: &str) {
+    languages.sort_by(|a, b| {
+        (a.language_identifier != active)
+            .cmp(&(b.language_identifier != active))
+            .then_with(|| a.display_name().to_lowercase().cmp(&b.display_name().to_lowercase()))
+    });
+}
+
+/// Load all the available languages located in the voxygen asset directory,
+/// along with the side loaded ones
+///
+/// The active language comes first, the others are sorted by native name.
 pub fn list_localizations() -> Vec<LanguageMetadata> {
-    LocalizationList::load_expect_cloned("voxygen.i18n").0
+    let mut languages = LocalizationList::load_expect_cloned("voxygen.i18n").0;
+    if let Ok(side_loaded) = SIDE_LOADED.lock() {
+        for metadata in side_loaded.iter() {
+            if !languages
+                .iter()
+                .any(|l| l.language_identifier == metadata.language_identifier)
+            {
+                languages.push(metadata.clone());
+            }
+        }
+    }
+    if let Ok(active) = ACTIVE.lock() {
+        sort_localizations(&mut languages, &active);
+    }
+    languages
+}
+
+/// Offer a language which was installed into the asset override directory
+/// while the game runs
+///
+/// Languages already loaded this session keep their old texts until restart.
+pub fn side_load_language(language: verification::VerifiedLanguage) {
+    if let Ok(mut side_loaded) = SIDE_LOADED.lock() {
+        side_loaded.retain(|l| l.language_identifier != language.0.language_identifier);
+        side_loaded.push(language.0);
+    }
+}
+
+/// Share of the reference language's entries a language translates, between
+/// 0.0 and 1.0
+pub fn completeness(language_identifier: &str) -> f32 {
+    let load = |identifier: &str| Language::load(&["voxygen.i18n.", identifier].concat());
+    let (language, reference) = match (load(language_identifier), load(REFERENCE_LANG)) {
+        (Ok(language), Ok(reference)) => (language.read(), reference.read()),
+        _ => return 0.0,
+    };
+    let total = reference.string_map.len() + reference.vector_map.len();
+    if total == 0 {
+        return 1.0;
+    }
+    let translated = reference
+        .string_map
+        .keys()
+        .filter(|key| language.string_map.contains_key(*key))
+        .count()
+        + reference
+            .vector_map
+            .keys()
+            .filter(|key| language.vector_map.contains_key(*key))
+            .count();
+    translated as f32 / total as f32
+}
+
+/// The same for the same key and seed, across sessions too since the hasher
+/// isn't randomly keyed
+fn variation_index(key: &str, seed: u64) -> u16 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    seed.hash(&mut hasher);
+    hasher.finish() as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stable_variations() {
+        let first = variation_index("npc.speech.villager", 42);
+        assert_eq!(variation_index("npc.speech.villager", 42), first);
+        assert!((0..16).any(|seed| variation_index("npc.speech.villager", seed) != first));
+    }
+
+    #[test]
+    fn sorted_by_native_name() {
+        let language = |identifier: &str, name: &str, native_name: &str| LanguageMetadata {
+            language_name: name.to_owned(),
+            native_name: native_name.to_owned(),
+            icon: None,
+            language_identifier: identifier.to_owned(),
+            translators: Vec::new(),
+        };
+        let mut languages = vec![
+            language("en", "English", ""),
+            language("ja_JP", "Japanese", "日本語"),
+            language("de_DE", "German", "Deutsch"),
+            language("fr_FR", "French", "français"),
+        ];
+        sort_localizations(&mut languages, "ja_JP");
+        let order = languages
+            .iter()
+            .map(|l| l.display_name())
+            .collect::<Vec<_>>();
+        assert_eq!(order, vec!["日本語", "Deutsch", "English", "français"]);
+    }
+
+    #[test]
+    fn icons_exist() {
+        for language in list_localizations() {
+            if let Some(icon) = &language.icon {
+                assert!(
+                    common_assets::Image::load(icon).is_ok(),
+                    "Missing icon {} of {}",
+                    icon,
+                    language.language_identifier
+                );
+            }
+        }
+    }
 }
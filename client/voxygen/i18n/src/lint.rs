@@ -0,0 +1,305 @@
+//! Key naming rules for localization fragments
+//!
+//! Keys are lowercase, dot separated and start with the path of the fragment
+//! they live in (`hud/chat.ron` holds `hud.chat.*`). Exceptions are listed in
+//! the policy file next to the language folders.
+use crate::{
+    path::{BasePath, LangPath, LANG_EXTENSION},
+    raw::RawFragment,
+    REFERENCE_LANG,
+};
+use hashbrown::HashMap;
+use ron::de::from_reader;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+pub const POLICY_FILE: &str = "key_policy";
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyPolicy {
+    pub require_lowercase: bool,
+    pub require_fragment_prefix: bool,
+    /// Other prefixes allowed in a fragment, by fragment path without
+    /// extension (e.g. `hud/chat`)
+    pub extra_prefixes: HashMap<String, Vec<String>>,
+}
+
+impl Default for KeyPolicy {
+    fn default() -> Self {
+        Self {
+            require_lowercase: true,
+            require_fragment_prefix: true,
+            extra_prefixes: HashMap::new(),
+        }
+    }
+}
+
+impl KeyPolicy {
+    /// Load the policy of the i18n folder, the defaults if there is none
+    pub fn load(path: &BasePath) -> Self {
+        let file = path
+            .i18n_root_path()
+            .join(format!("{}.{}", POLICY_FILE, LANG_EXTENSION));
+        match fs::File::open(&file) {
+            Ok(f) => from_reader(f)
+                .unwrap_or_else(|e| panic!("Could not parse {:?} RON file, error: {}", file, e)),
+            Err(_) => Self::default(),
+        }
+    }
+
+    fn allowed_prefixes(&self, fragment: &Path) -> Vec<String> {
+        let name = fragment
+            .with_extension("")
+            .to_string_lossy()
+            .replace('\\', "/");
+        let mut prefixes = vec![name.replace('/', ".")];
+        if let Some(extra) = self.extra_prefixes.get(&name) {
+            prefixes.extend(extra.iter().cloned());
+        }
+        prefixes
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ViolationKind {
+    /// Contains uppercase letters, whitespace or other unexpected characters
+    Malformed,
+    /// An empty segment between two dots
+    EmptySegment,
+    WrongPrefix { expected: String },
+}
+
+#[derive(Clone, Debug)]
+pub struct Violation {
+    pub fragment: PathBuf,
+    pub key: String,
+    pub kind: ViolationKind,
+}
+
+impl Violation {
+    /// The key this violation can be fixed to, if it can be fixed
+    /// automatically. Wrong prefixes need a human as code refers to the keys.
+    pub fn fix(&self) -> Option<String> {
+        match self.kind {
+            ViolationKind::Malformed | ViolationKind::EmptySegment => {
+                let fixed = self
+                    .key
+                    .to_lowercase()
+                    .chars()
+                    .map(|c| if c.is_whitespace() || c == '-' { '_' } else { c })
+                    .collect::<String>()
+                    .split('.')
+                    .filter(|segment| !segment.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(".");
+                (is_well_formed(&fixed) && fixed != self.key).then(|| fixed)
+            },
+            ViolationKind::WrongPrefix { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ViolationKind::Malformed => {
+                write!(f, "{:?}: {:?} is malformed", self.fragment, self.key)
+            },
+            ViolationKind::EmptySegment => {
+                write!(f, "{:?}: {:?} has an empty segment", self.fragment, self.key)
+            },
+            ViolationKind::WrongPrefix { expected } => write!(
+                f,
+                "{:?}: {:?} should start with {:?}",
+                self.fragment, self.key, expected
+            ),
+        }
+    }
+}
+
+fn is_well_formed(key: &str) -> bool {
+    key.split('.').all(|segment| {
+        !segment.is_empty()
+            && segment
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+    })
+}
+
+/// Check a single key against the policy
+pub fn check_key(policy: &KeyPolicy, fragment: &Path, key: &str) -> Option<ViolationKind> {
+    if key.split('.').any(str::is_empty) {
+        return Some(ViolationKind::EmptySegment);
+    }
+    if policy.require_lowercase && !is_well_formed(key) {
+        return Some(ViolationKind::Malformed);
+    }
+    if policy.require_fragment_prefix {
+        let prefixes = policy.allowed_prefixes(fragment);
+        if !prefixes
+            .iter()
+            .any(|prefix| key == prefix || key.starts_with(&format!("{}.", prefix)))
+        {
+            return Some(ViolationKind::WrongPrefix {
+                expected: prefixes[0].clone(),
+            });
+        }
+    }
+    None
+}
+
+/// All violations of one language, sorted by fragment and key
+pub fn lint_language(path: &LangPath, policy: &KeyPolicy) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    for fragment in path.fragments().expect("failed to get all files in language") {
+        let f = fs::File::open(path.sub_path(&fragment)).expect("failed to open fragment");
+        let raw: RawFragment<String> = from_reader(f)
+            .unwrap_or_else(|e| panic!("Could not parse {:?} RON file, error: {}", fragment, e));
+        for key in raw.string_map.keys().chain(raw.vector_map.keys()) {
+            if let Some(kind) = check_key(policy, &fragment, key) {
+                violations.push(Violation {
+                    fragment: fragment.clone(),
+                    key: key.clone(),
+                    kind,
+                });
+            }
+        }
+    }
+    violations.sort_by(|a, b| (&a.fragment, &a.key).cmp(&(&b.fragment, &b.key)));
+    violations
+}
+
+/// Replaces the string literals of renamed keys in Rust source, keys which
+/// are put together at runtime are not found
+fn rename_in_source(source: &str, renames: &[(String, String)]) -> String {
+    renames.iter().fold(source.to_owned(), |source, (from, to)| {
+        source.replace(&format!("\"{}\"", from), &format!("\"{}\"", to))
+    })
+}
+
+/// Applies the renames to every `.rs` file below `dir`
+fn fix_sources(dir: &Path, renames: &[(String, String)]) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            println!("can't update the code in {:?}: {}", dir, e);
+            return;
+        },
+    };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.is_dir() {
+            fix_sources(&path, renames);
+        } else if path.extension().and_then(|ext| ext.to_str()) == Some("rs") {
+            let content = fs::read_to_string(&path).expect("failed to read source file");
+            let fixed = rename_in_source(&content, renames);
+            if fixed != content {
+                println!("fixing {:?}", path);
+                fs::write(&path, fixed).expect("failed to write source file");
+            }
+        }
+    }
+}
+
+/// Rename the fixable keys in every language and in the code below `sources`
+/// which looks them up, returns how many keys were renamed in the reference
+/// language
+pub fn fix_all_localizations(path: &BasePath, policy: &KeyPolicy, sources: &[PathBuf]) -> usize {
+    let renames = lint_language(&path.i18n_path(REFERENCE_LANG), policy)
+        .iter()
+        .filter_map(|v| Some((v.key.clone(), v.fix()?)))
+        .collect::<Vec<_>>();
+    if renames.is_empty() {
+        return 0;
+    }
+    for language in path.i18n_directories() {
+        for fragment in language.fragments().expect("failed to get all files in language") {
+            let file = language.sub_path(&fragment);
+            let content = fs::read_to_string(&file).expect("failed to read fragment");
+            let mut fixed = content.clone();
+            for (from, to) in &renames {
+                fixed = fixed.replace(&format!("\"{}\":", from), &format!("\"{}\":", to));
+            }
+            if fixed != content {
+                println!("fixing {:?}", file);
+                fs::write(&file, fixed).expect("failed to write fragment");
+            }
+        }
+    }
+    for dir in sources {
+        fix_sources(dir, &renames);
+    }
+    renames.len()
+}
+
+/// Print the violations of all languages, optionally fixing what can be fixed
+/// in the fragments and in the voxygen code
+pub fn lint_all_localizations(path: &BasePath, fix: bool) {
+    let policy = KeyPolicy::load(path);
+    if fix {
+        let sources = [Path::new(env!("CARGO_MANIFEST_DIR")).join("../src")];
+        let fixed = fix_all_localizations(path, &policy, &sources);
+        println!("renamed {} keys", fixed);
+    }
+    for language in path.i18n_directories() {
+        let violations = lint_language(&language, &policy);
+        if !violations.is_empty() {
+            println!(
+                "{} violations in {:?}:",
+                violations.len(),
+                language.language_identifier()
+            );
+            for violation in violations {
+                println!("  {}", violation);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_rules() {
+        let mut policy = KeyPolicy::default();
+        policy
+            .extra_prefixes
+            .insert("hud/chat".to_owned(), vec!["hud.outcome".to_owned()]);
+        let fragment = Path::new("hud/chat.ron");
+
+        assert_eq!(check_key(&policy, fragment, "hud.chat.all"), None);
+        assert_eq!(check_key(&policy, fragment, "hud.outcome.burning"), None);
+        assert_eq!(
+            check_key(&policy, fragment, "hud.Chat.all"),
+            Some(ViolationKind::Malformed)
+        );
+        assert_eq!(
+            check_key(&policy, fragment, "hud..all"),
+            Some(ViolationKind::EmptySegment)
+        );
+        assert_eq!(
+            check_key(&policy, fragment, "main.login"),
+            Some(ViolationKind::WrongPrefix {
+                expected: "hud.chat".to_owned()
+            })
+        );
+
+        let violation = Violation {
+            fragment: fragment.to_path_buf(),
+            key: "hud.chat.Online Msg".to_owned(),
+            kind: ViolationKind::Malformed,
+        };
+        assert_eq!(violation.fix().as_deref(), Some("hud.chat.online_msg"));
+
+        let renames = [("hud.chat.Online Msg".to_owned(), "hud.chat.online_msg".to_owned())];
+        let source = r#"i18n.get("hud.chat.Online Msg"); i18n.get("hud.chat.Online Msgs")"#;
+        assert_eq!(
+            rename_in_source(source, &renames),
+            r#"i18n.get("hud.chat.online_msg"); i18n.get("hud.chat.Online Msgs")"#
+        );
+    }
+}
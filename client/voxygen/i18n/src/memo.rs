@@ -0,0 +1,144 @@
+//! Remembers which keys were missing in the active language
+use hashbrown::HashMap;
+use std::{
+    collections::VecDeque,
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+};
+
+/// Missing keys kept before the least recently hit ones get evicted
+pub(crate) const MISSING_KEYS_CAPACITY: usize = 256;
+
+/// A key the active language doesn't translate, see [`missing_keys`]
+///
+/// [`missing_keys`]: crate::missing_keys
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MissingKey {
+    pub language: String,
+    pub key: String,
+    /// How often the key was looked up since the game started
+    pub hits: u64,
+    /// Whether the fallback language provided the text instead
+    pub fallback: bool,
+}
+
+struct MissingEntry {
+    hits: AtomicU64,
+    fallback: bool,
+    last_hit: AtomicU64,
+    /// Hit since it was last considered for eviction
+    referenced: AtomicBool,
+}
+
+/// Keys which were hit before are counted with shared access only, so the
+/// lookups of a missing key every frame don't contend for exclusive access
+#[derive(Default)]
+pub(crate) struct MissingKeys {
+    // language -> key -> entry
+    entries: HashMap<String, HashMap<String, MissingEntry>>,
+    // Eviction candidates, oldest first
+    order: VecDeque<(String, String)>,
+    clock: AtomicU64,
+}
+
+impl MissingKeys {
+    /// Counts a hit of a key recorded before, false if it is new
+    pub(crate) fn hit_known(&self, language: &str, key: &str) -> bool {
+        // Lookups don't allocate, this runs for every lookup of a missing key
+        match self.entries.get(language).and_then(|keys| keys.get(key)) {
+            Some(entry) => {
+                entry.hits.fetch_add(1, Ordering::Relaxed);
+                let clock = self.clock.fetch_add(1, Ordering::Relaxed) + 1;
+                entry.last_hit.store(clock, Ordering::Relaxed);
+                entry.referenced.store(true, Ordering::Relaxed);
+                true
+            },
+            None => false,
+        }
+    }
+
+    pub(crate) fn hit(&mut self, language: &str, key: &str, fallback: bool) {
+        if self.hit_known(language, key) {
+            return;
+        }
+        if self.order.len() >= MISSING_KEYS_CAPACITY {
+            self.evict();
+        }
+        let clock = *self.clock.get_mut() + 1;
+        *self.clock.get_mut() = clock;
+        self.entries
+            .entry(language.to_owned())
+            .or_default()
+            .insert(key.to_owned(), MissingEntry {
+                hits: AtomicU64::new(1),
+                fallback,
+                last_hit: AtomicU64::new(clock),
+                referenced: AtomicBool::new(false),
+            });
+        self.order.push_back((language.to_owned(), key.to_owned()));
+    }
+
+    /// Forget the oldest key which wasn't hit since it was last considered,
+    /// hit ones get a second chance
+    fn evict(&mut self) {
+        while let Some((language, key)) = self.order.pop_front() {
+            let keys = match self.entries.get_mut(&language) {
+                Some(keys) => keys,
+                None => continue,
+            };
+            if keys
+                .get_mut(&key)
+                .map_or(false, |entry| std::mem::take(entry.referenced.get_mut()))
+            {
+                self.order.push_back((language, key));
+                continue;
+            }
+            keys.remove(&key);
+            if keys.is_empty() {
+                self.entries.remove(&language);
+            }
+            return;
+        }
+    }
+
+    /// Most recently hit first
+    pub(crate) fn list(&self) -> Vec<MissingKey> {
+        let mut list = self
+            .entries
+            .iter()
+            .flat_map(|(language, keys)| {
+                keys.iter().map(move |(key, entry)| {
+                    (entry.last_hit.load(Ordering::Relaxed), MissingKey {
+                        language: language.clone(),
+                        key: key.clone(),
+                        hits: entry.hits.load(Ordering::Relaxed),
+                        fallback: entry.fallback,
+                    })
+                })
+            })
+            .collect::<Vec<_>>();
+        list.sort_unstable_by_key(|(last_hit, _)| std::cmp::Reverse(*last_hit));
+        list.into_iter().map(|(_, missing)| missing).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_keys_bounded() {
+        let mut missing = MissingKeys::default();
+        missing.hit("de_DE", "hud.chat", true);
+        for i in 0..MISSING_KEYS_CAPACITY {
+            missing.hit("de_DE", &format!("key.{}", i), false);
+            assert!(missing.hit_known("de_DE", "hud.chat"));
+        }
+        assert!(!missing.hit_known("de_DE", "key.unknown"));
+        let list = missing.list();
+        assert_eq!(list.len(), MISSING_KEYS_CAPACITY);
+        assert_eq!(list[0].key, "hud.chat");
+        assert_eq!(list[0].hits, MISSING_KEYS_CAPACITY as u64 + 1);
+        // The first of the other keys was evicted
+        assert!(list.iter().all(|missing| missing.key != "key.0"));
+    }
+}
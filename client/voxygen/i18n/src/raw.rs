@@ -1,7 +1,7 @@
 //! handle the loading of a `Language`
 use crate::{
     path::{LangPath, LANG_EXTENSION, LANG_MANIFEST_FILE},
-    Fonts, Language, LanguageMetadata,
+    Fonts, Language, LanguageMetadata, LayoutHints,
 };
 use deunicode::deunicode;
 use hashbrown::hash_map::HashMap;
@@ -15,6 +15,8 @@ use std::{fs, path::PathBuf};
 pub(crate) struct RawManifest {
     pub(crate) convert_utf8_to_ascii: bool,
     pub(crate) fonts: Fonts,
+    #[serde(default)]
+    pub(crate) layout: LayoutHints,
     pub(crate) metadata: LanguageMetadata,
 }
 
@@ -26,6 +28,16 @@ pub(crate) struct RawFragment<T> {
     pub(crate) vector_map: HashMap<String, Vec<T>>,
 }
 
+/// An empty fragment, so a broken file only loses its own keys
+impl<T> common_assets::Placeholder for RawFragment<T> {
+    fn placeholder() -> Self {
+        Self {
+            string_map: HashMap::new(),
+            vector_map: HashMap::new(),
+        }
+    }
+}
+
 pub(crate) struct RawLanguage<T> {
     pub(crate) manifest: RawManifest,
     pub(crate) fragments: HashMap</* relative to i18n_path */ PathBuf, RawFragment<T>>,
@@ -89,12 +101,18 @@ impl From<RawLanguage<String>> for Language {
         }
         let mut metadata = raw.manifest.metadata;
         metadata.language_name = deunicode(&metadata.language_name);
+        if convert_utf8_to_ascii {
+            for translator in metadata.translators.iter_mut() {
+                *translator = deunicode(translator);
+            }
+        }
 
         Self {
             string_map,
             vector_map,
             convert_utf8_to_ascii,
             fonts: raw.manifest.fonts,
+            layout: raw.manifest.layout,
             metadata,
         }
     }
@@ -0,0 +1,202 @@
+//! Review files for translators, listing the keys a language is missing next
+//! to the reference strings and optionally a suggested translation
+//!
+//! Translators go through the file and set the `decision` of each entry,
+//! accepted suggestions can then be copied into the fragments.
+use crate::{
+    path::{BasePath, LangPath},
+    raw::{self, RawFragment},
+    REFERENCE_LANG,
+};
+use hashbrown::{HashMap, HashSet};
+use ron::ser::{to_string_pretty, PrettyConfig};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf};
+
+const REVIEW_HEADER: &str = "\
+// Set `decision` to `Accept` to take the suggestion, or to `Reject` and write
+// a translation of your own into the fragment.
+";
+
+/// Suggests translations, e.g. by asking a machine translation service.
+/// Implement it to plug in a backend, none is used by default.
+pub trait Translator {
+    /// `from` and `to` are language identifiers, like `en` and `de_DE`
+    fn translate(&self, text: &str, from: &str, to: &str) -> Option<String>;
+}
+
+/// Never suggests anything, so no network is used
+pub struct NoTranslator;
+
+impl Translator for NoTranslator {
+    fn translate(&self, _text: &str, _from: &str, _to: &str) -> Option<String> { None }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Decision {
+    Pending,
+    Accept,
+    Reject,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReviewEntry {
+    /// The fragment of the reference language the key is in
+    pub fragment: PathBuf,
+    pub key: String,
+    /// Index of the variation, for keys of the vector map
+    pub variation: Option<usize>,
+    pub reference: String,
+    pub suggestion: Option<String>,
+    pub decision: Decision,
+}
+
+fn fragment_keys(fragments: &HashMap<PathBuf, RawFragment<String>>) -> HashSet<&str> {
+    fragments
+        .values()
+        .flat_map(|fragment| fragment.string_map.keys().chain(fragment.vector_map.keys()))
+        .map(String::as_str)
+        .collect()
+}
+
+/// The entries for all keys of `reference` which are missing in `language`,
+/// sorted by fragment and key
+pub(crate) fn review_fragments(
+    reference: &HashMap<PathBuf, RawFragment<String>>,
+    language: &HashMap<PathBuf, RawFragment<String>>,
+    language_identifier: &str,
+    translator: &dyn Translator,
+) -> Vec<ReviewEntry> {
+    let known = fragment_keys(language);
+    let entry = |fragment: &PathBuf, key: &str, variation, text: &str| ReviewEntry {
+        fragment: fragment.clone(),
+        key: key.to_owned(),
+        variation,
+        reference: text.to_owned(),
+        suggestion: translator.translate(text, REFERENCE_LANG, language_identifier),
+        decision: Decision::Pending,
+    };
+
+    let mut entries = Vec::new();
+    for (fragment, raw) in reference {
+        for (key, text) in &raw.string_map {
+            if !known.contains(key.as_str()) {
+                entries.push(entry(fragment, key, None, text));
+            }
+        }
+        for (key, texts) in &raw.vector_map {
+            if !known.contains(key.as_str()) {
+                for (i, text) in texts.iter().enumerate() {
+                    entries.push(entry(fragment, key, Some(i), text));
+                }
+            }
+        }
+    }
+    entries.sort_by(|a, b| {
+        (&a.fragment, &a.key, a.variation).cmp(&(&b.fragment, &b.key, b.variation))
+    });
+    entries
+}
+
+fn load_fragments(path: &LangPath) -> HashMap<PathBuf, RawFragment<String>> {
+    let manifest = raw::load_manifest(path).expect("failed to load language manifest");
+    raw::load_raw_language(path, manifest)
+        .expect("failed to load language")
+        .fragments
+}
+
+/// The review entries for the keys missing in one language
+pub fn review_language(
+    path: &BasePath,
+    language_identifier: &str,
+    translator: &dyn Translator,
+) -> Vec<ReviewEntry> {
+    review_fragments(
+        &load_fragments(&path.i18n_path(REFERENCE_LANG)),
+        &load_fragments(&path.i18n_path(language_identifier)),
+        language_identifier,
+        translator,
+    )
+}
+
+/// Write the review file of a language to the root of the repository,
+/// returns where it was written to
+pub fn write_review(
+    path: &BasePath,
+    language_identifier: &str,
+    translator: &dyn Translator,
+) -> PathBuf {
+    let entries = review_language(path, language_identifier, translator);
+    let review = to_string_pretty(&entries, PrettyConfig::new())
+        .expect("failed to serialize review entries");
+    let output = path
+        .root_path()
+        .join(format!("{}_review.ron", language_identifier));
+    fs::write(&output, format!("{}{}\n", REVIEW_HEADER, review))
+        .expect("couldn't write review file");
+    println!(
+        "{} missing entries of {} written to {:?}",
+        entries.len(),
+        language_identifier,
+        output
+    );
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Shouting;
+
+    impl Translator for Shouting {
+        fn translate(&self, text: &str, _from: &str, to: &str) -> Option<String> {
+            (to == "xx").then(|| text.to_uppercase())
+        }
+    }
+
+    fn fragment(strings: &[(&str, &str)], vectors: &[(&str, &[&str])]) -> RawFragment<String> {
+        RawFragment {
+            string_map: strings
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            vector_map: vectors
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.iter().map(|s| s.to_string()).collect()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn missing_keys_are_reviewed() {
+        let main = PathBuf::from("main.ron");
+        let strings = [("main.login", "Login"), ("main.quit", "Quit")];
+        let vectors = [("main.tips", &["Jump", "Run"][..])];
+        let reference: HashMap<_, _> = [(main.clone(), fragment(&strings, &vectors))]
+            .into_iter()
+            .collect();
+        // Keys may live in another fragment than in the reference
+        let language: HashMap<_, _> = [(
+            PathBuf::from("other.ron"),
+            fragment(&[("main.quit", "Beenden")], &[]),
+        )]
+        .into_iter()
+        .collect();
+
+        let entries = review_fragments(&reference, &language, "xx", &Shouting);
+        let summary = entries
+            .iter()
+            .map(|e| (e.key.as_str(), e.variation, e.suggestion.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(summary, [
+            ("main.login", None, Some("LOGIN")),
+            ("main.tips", Some(0), Some("JUMP")),
+            ("main.tips", Some(1), Some("RUN")),
+        ]);
+        assert!(entries.iter().all(|e| e.fragment == main));
+
+        let entries = review_fragments(&reference, &language, "xx", &NoTranslator);
+        assert!(entries.iter().all(|e| e.suggestion.is_none()));
+    }
+}
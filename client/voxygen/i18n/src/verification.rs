@@ -1,6 +1,11 @@
-use crate::path::{BasePath, LangPath, LANG_MANIFEST_FILE};
+use crate::path::{BasePath, LangPath, LANG_EXTENSION, LANG_MANIFEST_FILE};
 
-use crate::{raw, REFERENCE_LANG};
+use crate::{
+    raw::{self, RawFragment, RawManifest},
+    LanguageMetadata, REFERENCE_LANG,
+};
+use ron::de::from_reader;
+use std::{fs, path::Path};
 
 /// Test to verify all languages that they are VALID and loadable, without
 /// need of git just on the local assets folder
@@ -32,3 +37,76 @@ fn verify_localization_directory(path: &LangPath) {
     let manifest = raw::load_manifest(path).expect("error accessing manifest file");
     raw::load_raw_language(path, manifest).expect("error accessing fragment file");
 }
+
+/// A language directory which passed [`verify_language_directory`]
+#[derive(Clone, Debug)]
+pub struct VerifiedLanguage(pub(crate) LanguageMetadata);
+
+impl VerifiedLanguage {
+    pub fn metadata(&self) -> &LanguageMetadata { &self.0 }
+}
+
+/// Check a language directory outside of the repository, e.g. an installed
+/// translation pack, before offering it to players. Unlike the other checks
+/// this doesn't panic, the directory may be named differently than the
+/// language while it is being installed.
+pub fn verify_language_directory(
+    dir: &Path,
+    language_identifier: &str,
+) -> Result<VerifiedLanguage, String> {
+    let manifest_file = dir.join(format!("{}.{}", LANG_MANIFEST_FILE, LANG_EXTENSION));
+    let file = fs::File::open(&manifest_file).map_err(|e| format!("no manifest: {}", e))?;
+    let manifest: RawManifest =
+        from_reader(file).map_err(|e| format!("invalid manifest: {}", e))?;
+    if manifest.metadata.language_identifier != language_identifier {
+        return Err(format!(
+            "manifest is for {:?} instead of {:?}",
+            manifest.metadata.language_identifier, language_identifier
+        ));
+    }
+
+    let fragments = verify_fragments(dir, dir)?;
+    if fragments == 0 {
+        return Err("no translations".to_owned());
+    }
+    Ok(VerifiedLanguage(manifest.metadata))
+}
+
+// Parses every fragment below `dir`, returns how many there are
+fn verify_fragments(root: &Path, dir: &Path) -> Result<usize, String> {
+    let mut count = 0;
+    for entry in fs::read_dir(dir).map_err(|e| e.to_string())?.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            count += verify_fragments(root, &path)?;
+            continue;
+        }
+        let stem = path.file_stem().and_then(|stem| stem.to_str());
+        if path.extension().and_then(|ext| ext.to_str()) != Some(LANG_EXTENSION)
+            || (dir == root && matches!(stem, Some(LANG_MANIFEST_FILE | "template")))
+        {
+            continue;
+        }
+        let file = fs::File::open(&path).map_err(|e| e.to_string())?;
+        let _: RawFragment<String> = from_reader(file).map_err(|e| {
+            let name = path.strip_prefix(root).unwrap_or(&path);
+            format!("invalid fragment {}: {}", name.display(), e)
+        })?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verify_language_directories() {
+        let dir = common_assets::ASSETS_PATH.join("voxygen/i18n/de_DE");
+        let verified = verify_language_directory(&dir, "de_DE").unwrap();
+        assert_eq!(verified.metadata().language_identifier, "de_DE");
+        assert!(verify_language_directory(&dir, "fr_FR").is_err());
+        assert!(verify_language_directory(&dir.join("missing"), "de_DE").is_err());
+    }
+}
@@ -25,7 +25,12 @@ void main() {
     // Image
     // HACK: bit 0 is set for both ordinary and north-facing images.
     } else if ((f_mode & uint(1)) == uint(1)) {
-        tgt_color = f_color * textureLod(sampler2D(t_tex, s_tex), f_uv, 0);
+        vec4 tex_color = textureLod(sampler2D(t_tex, s_tex), f_uv, 0);
+        // Grayscale images
+        if ((f_mode & uint(8)) == uint(8)) {
+            tex_color.rgb = vec3(dot(tex_color.rgb, vec3(0.2126, 0.7152, 0.0722)));
+        }
+        tgt_color = f_color * tex_color;
     // 2D Geometry
     } else if (f_mode == uint(2)) {
         tgt_color = f_color;
@@ -0,0 +1,131 @@
+//! Writes a report with the state of the game when it panics, the main menu
+//! offers it on the next launch so it can be attached to bug reports
+
+use common::assets::try_asset_diagnostics;
+use lazy_static::lazy_static;
+use std::{fmt::Write, panic::PanicInfo, sync::Mutex};
+
+/// Only the most recent asset diagnostics are included
+const MAX_DIAGNOSTICS: usize = 20;
+
+#[derive(Default)]
+struct Context {
+    play_state: &'static str,
+    menu_screen: &'static str,
+    network: String,
+    locale: String,
+}
+
+lazy_static! {
+    static ref CONTEXT: Mutex<Context> = Mutex::new(Context::default());
+}
+
+fn with_context(f: impl FnOnce(&mut Context)) {
+    // Don't panic in case the hook itself poisoned the lock
+    if let Ok(mut context) = CONTEXT.lock() {
+        f(&mut context);
+    }
+}
+
+pub fn set_play_state(name: &'static str) { with_context(|c| c.play_state = name); }
+
+pub fn set_menu_screen(name: &'static str) { with_context(|c| c.menu_screen = name); }
+
+pub fn set_network(state: impl Into<String>) {
+    let state = state.into();
+    with_context(|c| c.network = state);
+}
+
+pub fn set_locale(locale: &str) { with_context(|c| c.locale = locale.to_owned()); }
+
+/// Writes a crash report on panics, in addition to what the previous hook
+/// does
+pub fn install() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let report = report(info);
+        storage::store(&report);
+        previous(info);
+    }));
+}
+
+/// The report of the last crash, it is removed from the storage
+pub fn take() -> Option<String> {
+    let report = storage::load()?;
+    storage::remove();
+    Some(report)
+}
+
+fn report(info: &PanicInfo) -> String {
+    let mut report = String::new();
+    let _ = writeln!(report, "Veloren {}", *common::util::DISPLAY_VERSION_LONG);
+    let _ = writeln!(report, "Panic: {}", info);
+    if let Ok(context) = CONTEXT.try_lock() {
+        let _ = writeln!(report, "Play state: {}", context.play_state);
+        let _ = writeln!(report, "Menu screen: {}", context.menu_screen);
+        let _ = writeln!(report, "Network: {}", context.network);
+        let _ = writeln!(report, "Locale: {}", context.locale);
+    }
+    // The panic may have happened while recording a diagnostic
+    if let Some(diagnostics) = try_asset_diagnostics() {
+        let _ = writeln!(report, "Asset diagnostics ({}):", diagnostics.len());
+        let skip = diagnostics.len().saturating_sub(MAX_DIAGNOSTICS);
+        for diagnostic in diagnostics.iter().skip(skip) {
+            let _ = writeln!(report, "  {:?}", diagnostic);
+        }
+    }
+    report
+}
+
+#[cfg(target_arch = "wasm32")]
+mod storage {
+    const KEY: &str = "veloren_crash_report";
+
+    fn local_storage() -> Option<web_sys::Storage> {
+        web_sys::window()?.local_storage().ok().flatten()
+    }
+
+    pub fn store(report: &str) {
+        if let Some(storage) = local_storage() {
+            let _ = storage.set_item(KEY, report);
+        }
+    }
+
+    pub fn load() -> Option<String> { local_storage()?.get_item(KEY).ok().flatten() }
+
+    pub fn remove() {
+        if let Some(storage) = local_storage() {
+            let _ = storage.remove_item(KEY);
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod storage {
+    use std::path::PathBuf;
+
+    fn path() -> Option<PathBuf> {
+        directories_next::ProjectDirs::from("net", "veloren", "voxygen")
+            .map(|dirs| dirs.data_dir().join("crash_report.txt"))
+    }
+
+    pub fn store(report: &str) {
+        if let Some(path) = path() {
+            let written = path
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|()| std::fs::write(&path, report));
+            if let Err(e) = written {
+                eprintln!("Failed to write crash report to {}: {}", path.display(), e);
+            }
+        }
+    }
+
+    pub fn load() -> Option<String> { std::fs::read_to_string(path()?).ok() }
+
+    pub fn remove() {
+        if let Some(path) = path() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
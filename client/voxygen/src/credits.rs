@@ -1,4 +1,6 @@
 use common::assets;
+use hashbrown::HashMap;
+use i18n::LanguageMetadata;
 use serde::Deserialize;
 use std::path::PathBuf;
 
@@ -50,13 +52,56 @@ pub struct Contributor {
     pub contributions: String,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum SectionKind {
+    Music,
+    Fonts,
+    OtherArt,
+    Contributors,
+    /// Translators of the active language, skipped if there are none
+    Translators,
+}
+
+/// A section of the credits screen, shown in the order they are listed
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct Section {
+    pub kind: SectionKind,
+    /// Localization key of the title, `{language}` in it is replaced with the
+    /// name of the active language
+    pub title: String,
+}
+
+fn default_sections() -> Vec<Section> {
+    [
+        (SectionKind::Music, "main.credits.music"),
+        (SectionKind::Fonts, "main.credits.fonts"),
+        (SectionKind::OtherArt, "main.credits.other_art"),
+        (SectionKind::Contributors, "main.credits.contributors"),
+        (SectionKind::Translators, "main.credits.translators"),
+    ]
+    .iter()
+    .map(|(kind, title)| Section {
+        kind: *kind,
+        title: (*title).to_owned(),
+    })
+    .collect()
+}
+
 /// Credits manifest processed into format for display in the UI
 #[derive(Clone, Deserialize)]
 pub struct Credits {
+    /// The sections and their titles, all of them in the default order if
+    /// omitted
+    #[serde(default = "default_sections")]
+    pub sections: Vec<Section>,
     pub music: Vec<Art>,
     pub fonts: Vec<Art>,
     pub other_art: Vec<Art>,
     pub contributors: Vec<Contributor>,
+    /// Translators by language identifier, in addition to the ones listed in
+    /// the manifest of each language
+    #[serde(default)]
+    pub translators: HashMap<String, Vec<Contributor>>,
     // TODO: include credits for dependencies where the license requires attribution?
 }
 
@@ -64,4 +109,57 @@ impl assets::Asset for Credits {
     type Loader = assets::RonLoader;
 
     const EXTENSION: &'static str = "ron";
-}
\ No newline at end of file
+}
+
+impl Credits {
+    /// Translators of `language`, the ones from the credits followed by those
+    /// from its manifest which aren't credited yet
+    pub fn translators(&self, language: &LanguageMetadata) -> Vec<Contributor> {
+        let mut translators = self
+            .translators
+            .get(&language.language_identifier)
+            .cloned()
+            .unwrap_or_default();
+        for name in &language.translators {
+            if !translators.iter().any(|t| &t.name == name) {
+                translators.push(Contributor {
+                    name: name.clone(),
+                    contributions: String::new(),
+                });
+            }
+        }
+        translators
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::assets::AssetExt;
+
+    #[test]
+    fn sections() {
+        let credits: Credits =
+            ron::de::from_str("(music: [], fonts: [], other_art: [], contributors: [])").unwrap();
+        assert_eq!(credits.sections, default_sections());
+
+        let credits: Credits = ron::de::from_str(
+            r#"(
+                sections: [
+                    (kind: Contributors, title: "main.credits.contributors"),
+                    (kind: Music, title: "main.credits.music"),
+                ],
+                music: [],
+                fonts: [],
+                other_art: [],
+                contributors: [(name: "Pfau")],
+            )"#,
+        )
+        .unwrap();
+        let kinds = credits.sections.iter().map(|s| s.kind).collect::<Vec<_>>();
+        assert_eq!(kinds, vec![SectionKind::Contributors, SectionKind::Music]);
+
+        let credits = Credits::load_expect_cloned("common.credits");
+        assert_eq!(credits.sections, default_sections());
+    }
+}
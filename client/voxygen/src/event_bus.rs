@@ -0,0 +1,131 @@
+//! Queue for events between parts of the game, e.g. from the main menu ui to
+//! the play state acting on them
+//!
+//! Events can be published with a [`Priority`] and a delay, which allows flows
+//! like "retry connecting in 5s" without extra state in the play states.
+//! [`EventBus::drain`] hands out the due events, higher priorities first and
+//! otherwise in the order they were published.
+
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use instant::{Duration, Instant};
+use std::cmp::Reverse;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    Low,
+    Normal,
+    /// Handled before everything else published for the same frame, e.g.
+    /// cancelling or quitting
+    High,
+}
+
+struct Queued<E> {
+    event: E,
+    priority: Priority,
+    /// `None` if it can be delivered right away
+    due: Option<Instant>,
+    /// Keeps events of the same priority in order
+    seq: u64,
+}
+
+pub struct EventBus<E> {
+    queue: Vec<Queued<E>>,
+    next_seq: u64,
+    subscribers: Vec<Sender<E>>,
+}
+
+impl<E> Default for EventBus<E> {
+    fn default() -> Self {
+        Self {
+            queue: Vec::new(),
+            next_seq: 0,
+            subscribers: Vec::new(),
+        }
+    }
+}
+
+impl<E: Clone> EventBus<E> {
+    pub fn publish(&mut self, event: E) { self.push(event, Priority::Normal, None); }
+
+    pub fn publish_with(&mut self, event: E, priority: Priority) {
+        self.push(event, priority, None);
+    }
+
+    /// Delivers the event once `delay` passed
+    pub fn publish_after(&mut self, event: E, delay: Duration) {
+        self.push(event, Priority::Normal, Some(Instant::now() + delay));
+    }
+
+    fn push(&mut self, event: E, priority: Priority, due: Option<Instant>) {
+        self.queue.push(Queued {
+            event,
+            priority,
+            due,
+            seq: self.next_seq,
+        });
+        self.next_seq += 1;
+    }
+
+    /// Drops queued events for which `f` is true, e.g. a pending retry after
+    /// the player cancelled
+    pub fn cancel(&mut self, f: impl Fn(&E) -> bool) { self.queue.retain(|q| !f(&q.event)); }
+
+    /// Whether events wait for their delay to pass
+    pub fn has_deferred(&self) -> bool { self.queue.iter().any(|q| q.due.is_some()) }
+
+    /// Receives a copy of every event when it is delivered
+    pub fn subscribe(&mut self) -> Receiver<E> {
+        let (sender, receiver) = unbounded();
+        self.subscribers.push(sender);
+        receiver
+    }
+
+    /// The due events, highest priority first
+    pub fn drain(&mut self) -> Vec<E> { self.drain_at(Instant::now()) }
+
+    fn drain_at(&mut self, now: Instant) -> Vec<E> {
+        let (mut due, deferred): (Vec<_>, Vec<_>) = std::mem::take(&mut self.queue)
+            .into_iter()
+            .partition(|q| q.due.map_or(true, |due| due <= now));
+        self.queue = deferred;
+        due.sort_by_key(|q| (Reverse(q.priority), q.seq));
+
+        let events = due.into_iter().map(|q| q.event).collect::<Vec<_>>();
+        if !events.is_empty() {
+            self.subscribers.retain(|subscriber| {
+                events
+                    .iter()
+                    .all(|event| subscriber.send(event.clone()).is_ok())
+            });
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delivery_order() {
+        let mut bus = EventBus::default();
+        let receiver = bus.subscribe();
+        bus.publish("first");
+        bus.publish_after("retry", Duration::from_secs(5));
+        bus.publish_with("quit", Priority::High);
+        bus.publish_with("later", Priority::Low);
+        bus.publish("second");
+
+        let now = Instant::now();
+        assert_eq!(bus.drain_at(now), vec!["quit", "first", "second", "later"]);
+        assert_eq!(receiver.try_iter().count(), 4);
+        assert!(bus.has_deferred());
+        assert!(bus.drain_at(now).is_empty());
+        assert_eq!(bus.drain_at(now + Duration::from_secs(6)), vec!["retry"]);
+
+        bus.publish_after("retry", Duration::from_secs(5));
+        bus.cancel(|event| *event == "retry");
+        assert!(!bus.has_deferred());
+        assert!(bus.drain_at(now + Duration::from_secs(6)).is_empty());
+    }
+}
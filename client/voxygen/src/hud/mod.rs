@@ -68,15 +68,19 @@ use crate::{
     },
     settings::chat::ChatFilter,
     ui::{
-        self, fonts::Fonts, img_ids::Rotations, slot, slot::SlotKey, Graphic, Ingameable,
-        ScaleMode, Ui,
+        self,
+        fonts::Fonts,
+        img_ids::{GraphicCreator, ImageGraphic, Rotations},
+        slot,
+        slot::SlotKey,
+        Graphic, Ingameable, ScaleMode, Ui,
     },
     window::Event as WinEvent,
     GlobalState,
 };
 use client::Client;
 use common::{
-    combat,
+    assets, combat,
     comp::{
         self,
         ability::AuxiliaryAbility,
@@ -262,6 +266,7 @@ widget_ids! {
         // Help
         help,
         help_info,
+        connection_info,
         debug_info,
         lantern_info,
 
@@ -943,6 +948,8 @@ pub struct Hud {
     world_map: (/* Id */ Vec<Rotations>, Vec2<u32>),
     imgs: Imgs,
     item_imgs: ItemImgs,
+    /// Icons of the language picker by language identifier
+    language_icons: HashMap<String, conrod_core::image::Id>,
     fonts: Fonts,
     rot_imgs: ImgsRot,
     failed_block_pickups: HashMap<Vec3<i32>, f32>,
@@ -996,6 +1003,20 @@ impl Hud {
         let rot_imgs = ImgsRot::load(&mut ui).expect("Failed to load rot images!");
         // Load item images.
         let item_imgs = ItemImgs::new(&mut ui, imgs.not_found);
+        // Load language icons.
+        let language_icons = i18n::list_localizations()
+            .into_iter()
+            .filter_map(|language| {
+                let icon = language.icon?;
+                match ImageGraphic::new_graphic(&icon) {
+                    Ok(graphic) => Some((language.language_identifier, ui.add_graphic(graphic))),
+                    Err(e) => {
+                        log::warn!("Failed to load language icon {}: {:?}", icon, e);
+                        None
+                    },
+                }
+            })
+            .collect();
         // Load fonts.
         let fonts = Fonts::load(global_state.i18n.read().fonts(), &mut ui)
             .expect("Impossible to load fonts!");
@@ -1031,6 +1052,7 @@ impl Hud {
             world_map,
             rot_imgs,
             item_imgs,
+            language_icons,
             fonts,
             ids,
             failed_block_pickups: HashMap::default(),
@@ -2643,6 +2665,26 @@ impl Hud {
             }
         }
 
+        // Connection quality, one bar per step of signal strength
+        if let Some(quality) = client.connection_quality() {
+            let strength = quality.strength();
+            let bars = format!(
+                "{}{}",
+                "|".repeat(strength as usize),
+                ".".repeat(4 - strength as usize)
+            );
+            Text::new(&i18n.get("hud.connection_quality_fmt").replace("{bars}", &bars))
+                .color(match strength {
+                    0..=1 => CRITICAL_HP_COLOR,
+                    2 => LOW_HP_COLOR,
+                    _ => HP_COLOR,
+                })
+                .bottom_left_with_margins_on(ui_widgets.window, 190.0, 10.0)
+                .font_id(self.fonts.cyri.conrod_id)
+                .font_size(self.fonts.cyri.scale(12))
+                .set(self.ids.connection_info, ui_widgets);
+        }
+
         // Bag button and nearby icons
         let ecs = client.state().ecs();
         let entity = client.entity();
@@ -3086,6 +3128,7 @@ impl Hud {
                 global_state,
                 &self.show,
                 &self.imgs,
+                &self.language_icons,
                 &self.fonts,
                 i18n,
                 fps as f32,
@@ -3747,7 +3790,11 @@ impl Hud {
         self.new_loot_messages.push_back(item);
     }
 
-    pub fn new_message(&mut self, msg: comp::ChatMsg) { self.new_messages.push_back(msg); }
+    pub fn new_message(&mut self, msg: comp::ChatMsg) {
+        // Messages may contain characters no text of the language has
+        assets::request_glyphs(msg.message.chars());
+        self.new_messages.push_back(msg);
+    }
 
     pub fn new_notification(&mut self, msg: Notification) { self.new_notifications.push_back(msg); }
 
@@ -5,11 +5,17 @@ use crate::{
     GlobalState,
 };
 use conrod_core::{
-    color,
-    widget::{self, Button, Rectangle, Scrollbar, Text},
+    color, image,
+    widget::{self, Button, Image, Rectangle, Scrollbar, Text},
     widget_ids, Colorable, Labelable, Positionable, Sizeable, Widget, WidgetCommon,
 };
-use i18n::{list_localizations, Localization};
+use hashbrown::HashMap;
+use i18n::{completeness, list_localizations, missing_keys, Localization};
+
+/// Missing keys listed below the languages
+const MAX_MISSING_KEYS: usize = 20;
+/// Size of the icons next to the language names
+const ICON_SIZE: [f64; 2] = [36.0, 24.0];
 
 widget_ids! {
     struct Ids {
@@ -19,6 +25,9 @@ widget_ids! {
         english_fallback_button_label,
         window_scrollbar,
         language_list[],
+        language_icons[],
+        missing_keys_header,
+        missing_keys_list[],
     }
 }
 
@@ -27,6 +36,7 @@ pub struct Language<'a> {
     global_state: &'a GlobalState,
     localized_strings: &'a Localization,
     imgs: &'a Imgs,
+    icons: &'a HashMap<String, image::Id>,
     fonts: &'a Fonts,
     #[conrod(common_builder)]
     common: widget::CommonBuilder,
@@ -35,6 +45,7 @@ impl<'a> Language<'a> {
     pub fn new(
         global_state: &'a GlobalState,
         imgs: &'a Imgs,
+        icons: &'a HashMap<String, image::Id>,
         fonts: &'a Fonts,
         localized_strings: &'a Localization,
     ) -> Self {
@@ -42,6 +53,7 @@ impl<'a> Language<'a> {
             global_state,
             localized_strings,
             imgs,
+            icons,
             fonts,
             common: widget::CommonBuilder::default(),
         }
@@ -50,6 +62,9 @@ impl<'a> Language<'a> {
 
 pub struct State {
     ids: Ids,
+    /// Share of translated entries by language identifier, computing it loads
+    /// the whole language so it's done once
+    completeness: HashMap<String, f32>,
 }
 
 impl<'a> Widget for Language<'a> {
@@ -60,6 +75,7 @@ impl<'a> Widget for Language<'a> {
     fn init_state(&self, id_gen: widget::id::Generator) -> Self::State {
         State {
             ids: Ids::new(id_gen),
+            completeness: HashMap::new(),
         }
     }
 
@@ -91,12 +107,24 @@ impl<'a> Widget for Language<'a> {
         let language_list = list_localizations();
         if state.ids.language_list.len() < language_list.len() {
             state.update(|state| {
-                state
-                    .ids
-                    .language_list
-                    .resize(language_list.len(), &mut ui.widget_id_generator())
+                let id_gen = &mut ui.widget_id_generator();
+                state.ids.language_list.resize(language_list.len(), id_gen);
+                state.ids.language_icons.resize(language_list.len(), id_gen);
             });
         };
+        let unknown = language_list
+            .iter()
+            .filter(|language| !state.completeness.contains_key(&language.language_identifier))
+            .map(|language| language.language_identifier.clone())
+            .collect::<Vec<_>>();
+        if !unknown.is_empty() {
+            state.update(|state| {
+                for identifier in unknown {
+                    let completeness = completeness(&identifier);
+                    state.completeness.insert(identifier, completeness);
+                }
+            });
+        }
         for (i, language) in language_list.iter().enumerate() {
             let button_w = 400.0;
             let button_h = 50.0;
@@ -110,8 +138,14 @@ impl<'a> Widget for Language<'a> {
             } else {
                 button.mid_bottom_with_margin_on(state.ids.language_list[i - 1], -button_h)
             };
-            if button
-                .label(&language.language_name)
+            let completeness = state
+                .completeness
+                .get(&language.language_identifier)
+                .copied()
+                .unwrap_or_default();
+            let label = format!("{} ({:.0}%)", language.display_name(), completeness * 100.0);
+            let clicked = button
+                .label(&label)
                 .w_h(button_w, button_h)
                 .hover_image(self.imgs.selection_hover)
                 .press_image(self.imgs.selection_press)
@@ -120,8 +154,15 @@ impl<'a> Widget for Language<'a> {
                 .label_font_id(self.fonts.cyri.conrod_id)
                 .label_y(conrod_core::position::Relative::Scalar(2.0))
                 .set(state.ids.language_list[i], ui)
-                .was_clicked()
-            {
+                .was_clicked();
+            if let Some(icon) = self.icons.get(&language.language_identifier) {
+                Image::new(*icon)
+                    .wh(ICON_SIZE)
+                    .mid_left_with_margin_on(state.ids.language_list[i], 12.0)
+                    .graphics_for(state.ids.language_list[i])
+                    .set(state.ids.language_icons[i], ui);
+            }
+            if clicked {
                 events.push(ChangeLanguage(Box::new(language.to_owned())));
             }
         }
@@ -156,6 +197,49 @@ impl<'a> Widget for Language<'a> {
             .color(TEXT_COLOR)
             .set(state.ids.english_fallback_button_label, ui);
 
+        // Keys this session came across which the selected language lacks, for
+        // translators to report
+        let missing = missing_keys()
+            .into_iter()
+            .filter(|missing| &missing.language == selected_language)
+            .take(MAX_MISSING_KEYS)
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            Text::new(self.localized_strings.get("hud.settings.missing_translations"))
+                .down_from(state.ids.english_fallback_button, 20.0)
+                .font_size(self.fonts.cyri.scale(18))
+                .font_id(self.fonts.cyri.conrod_id)
+                .color(TEXT_COLOR)
+                .set(state.ids.missing_keys_header, ui);
+            if state.ids.missing_keys_list.len() < missing.len() {
+                state.update(|state| {
+                    state
+                        .ids
+                        .missing_keys_list
+                        .resize(missing.len(), &mut ui.widget_id_generator())
+                });
+            }
+            for (i, missing) in missing.iter().enumerate() {
+                let previous = if i == 0 {
+                    state.ids.missing_keys_header
+                } else {
+                    state.ids.missing_keys_list[i - 1]
+                };
+                let text = format!(
+                    "{} ({}){}",
+                    missing.key,
+                    missing.hits,
+                    if missing.fallback { "" } else { " *" }
+                );
+                Text::new(&text)
+                    .down_from(previous, 5.0)
+                    .font_size(self.fonts.cyri.scale(14))
+                    .font_id(self.fonts.cyri.conrod_id)
+                    .color(TEXT_COLOR)
+                    .set(state.ids.missing_keys_list[i], ui);
+            }
+        }
+
         events
     }
 }
@@ -13,10 +13,11 @@ use crate::{
     GlobalState,
 };
 use conrod_core::{
-    color,
+    color, image,
     widget::{self, Button, Image, Rectangle, Text},
     widget_ids, Colorable, Labelable, Positionable, Sizeable, Widget, WidgetCommon,
 };
+use hashbrown::HashMap;
 use i18n::Localization;
 
 use strum::IntoEnumIterator;
@@ -87,6 +88,7 @@ pub struct SettingsWindow<'a> {
     global_state: &'a GlobalState,
     show: &'a Show,
     imgs: &'a Imgs,
+    language_icons: &'a HashMap<String, image::Id>,
     fonts: &'a Fonts,
     localized_strings: &'a Localization,
     fps: f32,
@@ -99,6 +101,7 @@ impl<'a> SettingsWindow<'a> {
         global_state: &'a GlobalState,
         show: &'a Show,
         imgs: &'a Imgs,
+        language_icons: &'a HashMap<String, image::Id>,
         fonts: &'a Fonts,
         localized_strings: &'a Localization,
         fps: f32,
@@ -107,6 +110,7 @@ impl<'a> SettingsWindow<'a> {
             global_state,
             show,
             imgs,
+            language_icons,
             fonts,
             localized_strings,
             fps,
@@ -243,6 +247,7 @@ impl<'a> Widget for SettingsWindow<'a> {
         let global_state = self.global_state;
         let show = self.show;
         let imgs = self.imgs;
+        let language_icons = self.language_icons;
         let fonts = self.fonts;
         let localized_strings = self.localized_strings;
         match self.show.settings_tab {
@@ -311,10 +316,16 @@ impl<'a> Widget for SettingsWindow<'a> {
                 }
             },
             SettingsTab::Lang => {
-                for change in language::Language::new(global_state, imgs, fonts, localized_strings)
-                    .top_left_with_margins_on(state.ids.settings_content_align, 0.0, 0.0)
-                    .wh_of(state.ids.settings_content_align)
-                    .set(state.ids.language, ui)
+                for change in language::Language::new(
+                    global_state,
+                    imgs,
+                    language_icons,
+                    fonts,
+                    localized_strings,
+                )
+                .top_left_with_margins_on(state.ids.settings_content_align, 0.0, 0.0)
+                .wh_of(state.ids.settings_content_align)
+                .set(state.ids.language, ui)
                 {
                     events.push(Event::SettingsChange(change.into()));
                 }
@@ -10,7 +10,7 @@ use crate::{
         PresentMode, RenderMode, ShadowMapMode, ShadowMode, UpscaleMode,
     },
     session::settings_change::Graphics as GraphicsChange,
-    settings::Fps,
+    settings::{Fps, VIEW_DISTANCE_RANGE},
     ui::{fonts::Fonts, ImageSlider, ToggleButton},
     window::{FullScreenSettings, FullscreenMode},
     GlobalState,
@@ -276,10 +276,8 @@ impl<'a> Widget for Video<'a> {
 
         if let Some(new_val) = ImageSlider::discrete(
             self.global_state.settings.graphics.view_distance,
-            1,
-            // FIXME: Move back to 64 once we support multiple texture atlases, or figure out a
-            // way to increase the size of the terrain atlas.
-            65,
+            VIEW_DISTANCE_RANGE.0,
+            VIEW_DISTANCE_RANGE.1,
             self.imgs.slider_indicator,
             self.imgs.slider,
         )
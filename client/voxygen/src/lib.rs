@@ -17,9 +17,11 @@
 pub mod ui;
 pub mod audio;
 pub mod controller;
+pub mod crash_report;
 mod credits;
 mod ecs;
 pub mod error;
+pub mod event_bus;
 pub mod game_input;
 pub mod hud;
 pub mod key_state;
@@ -29,8 +31,10 @@ pub mod profile;
 pub mod render;
 pub mod run;
 pub mod scene;
+pub mod screenshot;
 pub mod session;
 pub mod settings;
+pub mod telemetry;
 pub mod window;
 
 use crate::{
@@ -38,6 +42,7 @@ use crate::{
     profile::Profile,
     render::{Drawer, GlobalsBindGroup},
     settings::Settings,
+    telemetry::Telemetry,
     window::{Event, Window},
     scene::terrain::SpriteRenderContext,
     settings::{get_fps, AudioOutput},
@@ -61,6 +66,7 @@ pub struct GlobalState {
     pub clipboard: iced::Clipboard,
     pub client_error: Option<String>,
     pub clear_shadows_next_frame: bool,
+    pub telemetry: Telemetry,
 }
 
 impl GlobalState {
@@ -71,7 +77,9 @@ impl GlobalState {
 
     pub fn maintain(&mut self, dt: Duration) {
         self.audio.maintain(dt);
-        self.window.renderer().maintain()
+        self.telemetry.maintain(&self.settings.telemetry);
+        settings::persistence::maintain();
+        self.window.renderer_mut().maintain()
     }
 
     pub fn paused(&self) -> bool { false }
@@ -115,6 +123,10 @@ pub trait PlayState {
 
     fn globals_bind_group(&self) -> &GlobalsBindGroup;
 
+    /// Whether the next frame would differ from the last one drawn, otherwise
+    /// drawing it is skipped
+    fn needs_redraw(&self) -> bool { true }
+
     /// Draw the play state.
     fn render<'a>(&'a self, drawer: &mut Drawer<'a>, settings: &Settings);
 }
@@ -149,22 +161,40 @@ pub fn set_resource_dir(name: &str) {
     res::set_cache_dir(name);
 }
 
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn set_resource_lazy(name: &str) {
+    res::set_cache_lazy(name);
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+extern "C" {
+    /// Downloads a file registered with `set_resource_lazy`, see download.js
+    #[wasm_bindgen(js_name = fetchLazyResource)]
+    fn fetch_lazy_resource(name: &str);
+}
+
 #[cfg(target_arch = "wasm32")]
 #[wasm_bindgen]
 pub fn start() {
     wasm_logger::init(wasm_logger::Config::new(log::Level::Info));
     std::panic::set_hook(Box::new(console_error_panic_hook::hook));
     log::info!("start wasm");
+    res::set_fetch_hook(fetch_lazy_resource);
     //wasm_bindgen_futures::spawn_local(start_game());
     start_game();
 }
 
 pub fn start_game() {
+    crash_report::install();
 
     //load setting
     log::info!("start init settings");
     let mut settings = Settings::load();
     settings.display_warnings();
+    // Before any image is loaded
+    common::assets::set_premultiply_alpha(settings.graphics.premultiply_alpha);
 
     log::info!("start init tokio_runtime");
 
@@ -233,6 +263,7 @@ pub fn start_game() {
         });
     i18n.read().log_missing_entries();
     i18n.set_english_fallback(settings.language.use_english_fallback);
+    crash_report::set_locale(&settings.language.selected_language);
     
 
     //创建运行窗体
@@ -252,6 +283,7 @@ pub fn start_game() {
         window,
         tokio_runtime,
         clock: Clock::new(Duration::from_secs_f64(1.0 / get_fps(settings.graphics.max_fps) as f64)),
+        telemetry: Telemetry::new(settings.telemetry_opt_in),
         settings,
         info_message: None,
         i18n,
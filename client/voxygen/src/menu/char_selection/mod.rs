@@ -9,7 +9,7 @@ use crate::{
     Direction, GlobalState, PlayState, PlayStateResult,
 };
 use client::{self, Client};
-use common::{comp, resources::DeltaTime};
+use common::{assets, comp, resources::DeltaTime};
 use specs::WorldExt;
 use std::{cell::RefCell, mem, rc::Rc};
 use ui::CharSelectionUi;
@@ -78,7 +78,10 @@ impl PlayState for CharSelectionState {
     }
 
     fn tick(&mut self, global_state: &mut GlobalState, events: Vec<WinEvent>) -> PlayStateResult {
-        
+        if assets::take_font_changes() {
+            self.char_selection_ui.update_language(global_state.i18n);
+        }
+
         let (client_presence, client_registered) = {
             let client = self.client.borrow();
             (client.presence(), client.registered())
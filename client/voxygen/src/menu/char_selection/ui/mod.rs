@@ -279,6 +279,8 @@ struct Controls {
     mode: Mode,
     // Id of the selected character
     selected: Option<CharacterId>,
+    // Alias of the character to select once the list is loaded
+    preferred_character: Option<String>,
     default_name: String,
 }
 
@@ -319,6 +321,7 @@ impl Controls {
         fonts: Fonts,
         imgs: Imgs,
         selected: Option<CharacterId>,
+        preferred_character: Option<String>,
         default_name: String,
         server_info: &ServerInfo,
     ) -> Self {
@@ -338,6 +341,7 @@ impl Controls {
             mouse_detector: Default::default(),
             mode: Mode::select(Some(InfoContent::LoadingCharacters)),
             selected,
+            preferred_character,
             default_name,
         }
     }
@@ -404,6 +408,20 @@ impl Controls {
                 ref mut yes_button,
                 ref mut no_button,
             } => {
+                // The server overrides take precedence over the last selection
+                if !client.character_list().loading {
+                    if let Some(alias) = self.preferred_character.take() {
+                        if let Some(id) = client
+                            .character_list()
+                            .characters
+                            .iter()
+                            .find(|i| i.character.alias == alias)
+                            .and_then(|i| i.character.id)
+                        {
+                            self.selected = Some(id);
+                        }
+                    }
+                }
                 // If no character is selected then select the first one
                 // Note: we don't need to persist this because it is the default
                 if self.selected.is_none() {
@@ -1638,14 +1656,22 @@ impl CharSelectionUi {
         )
         .unwrap();
 
-        let fonts = Fonts::load(i18n.fonts(), &mut ui).expect("Impossible to load fonts");
+        let fonts = Fonts::load(&i18n, &mut ui).expect("Impossible to load fonts");
 
         let default_name = global_state.settings.networking.username.clone();
-
+        let networking = &global_state.settings.networking;
+        let preferred_character = networking
+            .server_overrides
+            .get(&networking.default_server)
+            .and_then(|overrides| overrides.character.clone());
+
+        let imgs = Imgs::load(&mut ui).expect("Failed to load images");
+        ui.pack_graphics(global_state.window.renderer_mut(), &imgs.all());
         let controls = Controls::new(
             fonts,
-            Imgs::load(&mut ui).expect("Failed to load images"),
+            imgs,
             selected_character,
+            preferred_character,
             default_name,
             client.server_info(),
         );
@@ -1699,7 +1725,7 @@ impl CharSelectionUi {
 
         self.ui.clear_fonts(font);
         self.controls.fonts =
-            Fonts::load(i18n.fonts(), &mut self.ui).expect("Impossible to load fonts!");
+            Fonts::load(&i18n, &mut self.ui).expect("Impossible to load fonts!");
     }
 
     pub fn set_scale_mode(&mut self, scale_mode: ui::ScaleMode) {
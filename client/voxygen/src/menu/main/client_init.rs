@@ -3,7 +3,11 @@ use client::{
     error::{Error as ClientError, NetworkConnectError, NetworkError},
     Client, ServerInfo,
 };
-use crossbeam_channel::{unbounded, Receiver, TryRecvError};
+use common_net::msg::{
+    challenge::{proof_of_work_valid, MAX_PROOF_OF_WORK_DIFFICULTY},
+    ChallengeAnswer, LoginChallenge,
+};
+use crossbeam_channel::{unbounded, Receiver, Sender, TryRecvError};
 use std::{
     sync::{
         atomic::{AtomicBool, Ordering},
@@ -11,7 +15,7 @@ use std::{
     },
 };
 use instant::Duration;
-use tokio::runtime;
+use tokio::{runtime, sync::mpsc};
 
 #[derive(Debug)]
 #[allow(clippy::enum_variant_names)] //TODO: evaluate ClientError ends with Enum name
@@ -26,6 +30,9 @@ pub enum Error {
 
 #[allow(clippy::large_enum_variant)] // TODO: Pending review in #587
 pub enum Msg {
+    /// The server wants this solved before the login, captchas are answered
+    /// with [`ClientInit::answer_captcha`]
+    Challenge(LoginChallenge),
     Done(Result<Client, Error>),
 }
 
@@ -35,6 +42,7 @@ pub enum Msg {
 pub struct ClientInit {
     rx: Receiver<Msg>,
     cancel: Arc<AtomicBool>,
+    captcha_answers: mpsc::UnboundedSender<String>,
 }
 impl ClientInit {
     pub fn new(
@@ -44,6 +52,7 @@ impl ClientInit {
         runtime: Arc<runtime::Runtime>,
     ) -> Self {
         let (tx, rx) = unbounded();
+        let (captcha_answers, mut captcha_answers_r) = mpsc::unbounded_channel();
         let cancel = Arc::new(AtomicBool::new(false));
         let cancel2 = Arc::clone(&cancel);
 
@@ -69,12 +78,37 @@ impl ClientInit {
                     Ok(mut client) => {
 
                         //验证登录模块
-                        if let Err(e) = client.register(username, password).await {
-                            last_err = Some(Error::ClientError {
-                                error: e,
-                                mismatched_server_info: None,
-                            });
-                            break 'tries;
+                        let mut answer = None;
+                        loop {
+                            let challenge = match client
+                                .register(username.clone(), password.clone(), answer.take())
+                                .await
+                            {
+                                Ok(Some(challenge)) => challenge,
+                                Ok(None) => break,
+                                Err(e) => {
+                                    last_err = Some(Error::ClientError {
+                                        error: e,
+                                        mismatched_server_info: None,
+                                    });
+                                    break 'tries;
+                                },
+                            };
+                            answer = answer_challenge(
+                                challenge,
+                                &tx,
+                                &mut captcha_answers_r,
+                                &cancel2,
+                            )
+                            .await;
+                            if answer.is_none() {
+                                // Cancelled, or too hard to solve
+                                last_err = Some(Error::ClientError {
+                                    error: ClientError::ChallengeFailed,
+                                    mismatched_server_info: None,
+                                });
+                                break 'tries;
+                            }
                         }
                         let _ = tx.send(Msg::Done(Ok(client)));
 
@@ -113,6 +147,7 @@ impl ClientInit {
         ClientInit {
             rx,
             cancel,
+            captcha_answers,
         }
     }
 
@@ -127,9 +162,51 @@ impl ClientInit {
         }
     }
 
+    pub fn answer_captcha(&self, text: String) { let _ = self.captcha_answers.send(text); }
+
     pub fn cancel(&mut self) { self.cancel.store(true, Ordering::Relaxed); }
 }
 
+/// Nonces tried between yields to the runtime while solving a proof of work
+const PROOF_OF_WORK_CHUNK: u64 = 4096;
+
+/// Passes the challenge on to the ui and waits for the player to answer a
+/// captcha, or computes the proof of work. `None` if the connection attempt
+/// was cancelled meanwhile or the challenge can't be solved.
+async fn answer_challenge(
+    challenge: LoginChallenge,
+    tx: &Sender<Msg>,
+    captcha_answers: &mut mpsc::UnboundedReceiver<String>,
+    cancel: &AtomicBool,
+) -> Option<ChallengeAnswer> {
+    let _ = tx.send(Msg::Challenge(challenge.clone()));
+    match challenge {
+        LoginChallenge::Captcha { .. } => {
+            captcha_answers.recv().await.map(ChallengeAnswer::Captcha)
+        },
+        // There are no blocking threads on the web, so the search is split into
+        // chunks which let the other tasks run in between
+        LoginChallenge::ProofOfWork { seed, difficulty } => {
+            if difficulty > MAX_PROOF_OF_WORK_DIFFICULTY {
+                return None;
+            }
+            let mut start = 0;
+            loop {
+                let end = start + PROOF_OF_WORK_CHUNK;
+                let valid = |nonce: &u64| proof_of_work_valid(&seed, difficulty, *nonce);
+                if let Some(nonce) = (start..end).find(valid) {
+                    return Some(ChallengeAnswer::ProofOfWork(nonce));
+                }
+                if cancel.load(Ordering::Relaxed) {
+                    return None;
+                }
+                start = end;
+                tokio::task::yield_now().await;
+            }
+        },
+    }
+}
+
 impl Drop for ClientInit {
     fn drop(&mut self) { self.cancel(); }
 }
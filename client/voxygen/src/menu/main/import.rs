@@ -0,0 +1,208 @@
+//! Importing files dropped onto the main menu
+use serde::Deserialize;
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Name of the manifest every language directory contains
+const MANIFEST_FILE: &str = "_manifest.ron";
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Json(serde_json::Error),
+    Zip(zip::result::ZipError),
+    /// Neither a server list nor a translation pack
+    UnsupportedFile,
+    /// The zip doesn't contain exactly one language directory
+    NotATranslationPack,
+    /// There is no asset override directory to install into
+    NoModDirectory,
+    /// The language of the pack failed verification
+    InvalidTranslationPack(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{}", e),
+            Self::Json(e) => write!(f, "{}", e),
+            Self::Zip(e) => write!(f, "{}", e),
+            Self::UnsupportedFile => write!(f, "unsupported file type"),
+            Self::NotATranslationPack => write!(f, "not a translation pack"),
+            Self::NoModDirectory => write!(f, "VELOREN_ASSETS_OVERRIDE is not set"),
+            Self::InvalidTranslationPack(e) => write!(f, "invalid translation pack: {}", e),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self { Self::Io(e) }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self { Self::Json(e) }
+}
+
+impl From<zip::result::ZipError> for Error {
+    fn from(e: zip::result::ZipError) -> Self { Self::Zip(e) }
+}
+
+/// What a dropped file contains, applied once the player confirms
+#[derive(Clone, Debug)]
+pub enum Import {
+    Servers(Vec<String>),
+    TranslationPack { path: PathBuf, language: String },
+}
+
+/// Either a plain list of addresses or an object with a `servers` list
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ServerList {
+    Plain(Vec<String>),
+    Object { servers: Vec<String> },
+}
+
+/// Find out what `path` contains without changing anything
+pub fn inspect(path: &Path) -> Result<Import, Error> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => {
+            let servers = match serde_json::from_slice(&fs::read(path)?)? {
+                ServerList::Plain(servers) | ServerList::Object { servers } => servers,
+            };
+            Ok(Import::Servers(
+                servers
+                    .into_iter()
+                    .map(|server| server.trim().to_owned())
+                    .filter(|server| !server.is_empty())
+                    .collect(),
+            ))
+        },
+        Some("zip") => Ok(Import::TranslationPack {
+            path: path.to_owned(),
+            language: pack_language(&mut zip::ZipArchive::new(fs::File::open(path)?)?)?,
+        }),
+        _ => Err(Error::UnsupportedFile),
+    }
+}
+
+/// The language directory of a translation pack, identified by its manifest
+fn pack_language<R: io::Read + io::Seek>(
+    archive: &mut zip::ZipArchive<R>,
+) -> Result<String, Error> {
+    let mut languages = archive.file_names().filter_map(|name| {
+        let (language, file) = name.split_once('/')?;
+        (file == MANIFEST_FILE).then(|| language.to_owned())
+    });
+    match (languages.next(), languages.next()) {
+        (Some(language), None) if is_language_identifier(&language) => Ok(language),
+        _ => Err(Error::NotATranslationPack),
+    }
+}
+
+/// Language identifiers end up in paths, only allow plain names like `pt_BR`
+fn is_language_identifier(language: &str) -> bool {
+    !language.is_empty()
+        && language
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Extract the language directory of the pack into the i18n directory of the
+/// asset override directory
+///
+/// The language is verified before it replaces an installed one and is then
+/// offered in the language lists right away.
+pub fn install_translation_pack(path: &Path) -> Result<PathBuf, Error> {
+    let root = common::assets::override_path().ok_or(Error::NoModDirectory)?;
+    let mut archive = zip::ZipArchive::new(fs::File::open(path)?)?;
+    let language = pack_language(&mut archive)?;
+    let i18n_dir = root.join("voxygen").join("i18n");
+    // Not picked up as a language, it has no valid asset name
+    let staging = i18n_dir.join(format!(".{}.staging", language));
+    if staging.exists() {
+        fs::remove_dir_all(&staging)?;
+    }
+
+    if let Err(e) = extract_language(&mut archive, &language, &staging) {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(e);
+    }
+    let verified = match i18n::verification::verify_language_directory(&staging, &language) {
+        Ok(verified) => verified,
+        Err(e) => {
+            let _ = fs::remove_dir_all(&staging);
+            return Err(Error::InvalidTranslationPack(e));
+        },
+    };
+
+    let target = i18n_dir.join(&language);
+    // Never remove anything but a language directory
+    if target.parent() != Some(i18n_dir.as_path()) {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(Error::NotATranslationPack);
+    }
+    if target.exists() {
+        fs::remove_dir_all(&target)?;
+    }
+    fs::rename(&staging, &target)?;
+    i18n::side_load_language(verified);
+
+    Ok(target)
+}
+
+fn extract_language<R: io::Read + io::Seek>(
+    archive: &mut zip::ZipArchive<R>,
+    language: &str,
+    dir: &Path,
+) -> Result<(), Error> {
+    fs::create_dir_all(dir)?;
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        // Skips paths escaping the target directory
+        let name = match file
+            .enclosed_name()
+            .and_then(|name| name.strip_prefix(language).ok())
+        {
+            Some(name) if !name.as_os_str().is_empty() => name.to_owned(),
+            _ => continue,
+        };
+        let target = dir.join(name);
+        if file.is_dir() {
+            fs::create_dir_all(&target)?;
+        } else {
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            io::copy(&mut file, &mut fs::File::create(&target)?)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn translation_pack_language() {
+        let mut buffer = io::Cursor::new(Vec::new());
+        {
+            let mut writer = zip::ZipWriter::new(&mut buffer);
+            let options = zip::write::FileOptions::default();
+            writer.start_file("eo/_manifest.ron", options).unwrap();
+            writer.write_all(b"()").unwrap();
+            writer.start_file("eo/main.ron", options).unwrap();
+            writer.finish().unwrap();
+        }
+        let mut archive = zip::ZipArchive::new(buffer).unwrap();
+        assert_eq!(pack_language(&mut archive).unwrap(), "eo");
+
+        assert!(is_language_identifier("pt_BR"));
+        for language in ["", "..", ".eo.staging", "eo-x", "/etc", "C:"] {
+            assert!(!is_language_identifier(language), "{:?}", language);
+        }
+    }
+}
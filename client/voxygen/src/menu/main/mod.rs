@@ -1,11 +1,16 @@
 mod client_init;
+mod import;
 mod scene;
 mod ui;
 
 use super::char_selection::CharSelectionState;
 use crate::{
+    crash_report,
+    game_input::GameInput,
     render::{Drawer, GlobalsBindGroup},
-    settings::Settings,
+    settings::{persistence, ServerOverrides, Settings, SettingsSection},
+    screenshot,
+    telemetry::TelemetryEvent,
     window::Event,
     Direction, GlobalState, PlayState, PlayStateResult,
 };
@@ -15,13 +20,20 @@ use client::{
     Client, ServerInfo,
 };
 use client_init::{ClientInit, Error as InitError, Msg as InitMsg};
-use common::comp;
+use import::Import;
+use common::{assets, comp};
+use crossbeam_channel::Receiver;
 use i18n::LocalizationHandle;
 use scene::Scene;
+use instant::{Duration, Instant};
 use std::sync::Arc;
 use tokio::runtime;
 
-use ui::{Event as MainMenuEvent, MainMenuUi};
+use ui::{Event as MainMenuEvent, LoginInfo, MainMenuUi};
+
+/// Login attempts retried after network errors before the error is shown
+const LOGIN_RETRIES: u32 = 3;
+const LOGIN_RETRY_DELAY: Duration = Duration::from_secs(5);
 
 // TODO: show status messages for waiting on server creation, client init, and
 // pipeline creation (we can show progress of pipeline creation)
@@ -47,21 +59,79 @@ pub struct MainMenuState {
     main_menu_ui: MainMenuUi,
     init: InitState,
     scene: Scene,
+    // Last screen recorded as visited
+    visited_screen: Option<&'static str>,
+    connect_start: Option<Instant>,
+    // The last login attempt and how often it was retried already
+    last_login: Option<LoginInfo>,
+    login_retries: u32,
+    // Saved setting changes the menu has to follow
+    settings_changes: Receiver<SettingsSection>,
 }
 
 impl MainMenuState {
     /// Create a new `MainMenuState`.
     pub fn new(global_state: &mut GlobalState) -> Self {
+        let start = Instant::now();
+        let main_menu_ui = MainMenuUi::new(global_state);
+        global_state.telemetry.record(TelemetryEvent::LoadTiming {
+            what: "main_menu",
+            millis: start.elapsed().as_millis() as u64,
+        });
+
         Self {
-            main_menu_ui: MainMenuUi::new(global_state),
+            main_menu_ui,
             init: InitState::None,
             scene: Scene::new(global_state.window.renderer_mut()),
+            visited_screen: None,
+            connect_start: None,
+            last_login: None,
+            login_retries: 0,
+            settings_changes: persistence::subscribe(),
+        }
+    }
+
+    /// Undoes the server overrides applied when logging in
+    fn restore_settings(&mut self, global_state: &mut GlobalState) {
+        if let Some(replaced) = global_state.settings.replaced_by_overrides.take() {
+            apply_overrides(global_state, &replaced);
+            global_state.settings.save();
+            self.main_menu_ui
+                .update_language(global_state.i18n, &global_state.settings);
         }
     }
+
+    /// Publishes the last login attempt again after a delay if it failed for a
+    /// reason that may pass, returns whether it did
+    fn retry_login(&mut self, error: &client_init::Error) -> bool {
+        let login = match &self.last_login {
+            Some(login) if is_transient(error) && self.login_retries < LOGIN_RETRIES => login,
+            _ => {
+                self.login_retries = 0;
+                return false;
+            },
+        };
+        self.login_retries += 1;
+        log::info!(
+            "Retrying the login in {:?} ({}/{})",
+            LOGIN_RETRY_DELAY,
+            self.login_retries,
+            LOGIN_RETRIES
+        );
+        let event = MainMenuEvent::LoginAttempt {
+            username: login.username.clone(),
+            password: login.password.clone(),
+            server_address: login.server.clone(),
+        };
+        self.main_menu_ui
+            .events()
+            .publish_after(event, LOGIN_RETRY_DELAY);
+        true
+    }
 }
 
 impl PlayState for MainMenuState {
-    fn enter(&mut self, global_state: &mut GlobalState, _: Direction) {
+    fn enter(&mut self, global_state: &mut GlobalState, direction: Direction) {
 
         log::info!("PlayState for MainMenuState : enter");
 
@@ -70,6 +140,12 @@ impl PlayState for MainMenuState {
             global_state.audio.play_title_music();
         }
 
+        // Back from playing on a server
+        if let Direction::Backwards = direction {
+            crash_report::set_network("disconnected");
+        }
+        self.restore_settings(global_state);
+
         // Updated localization in case the selected language was changed
         self.main_menu_ui
             .update_language(global_state.i18n, &global_state.settings);
@@ -82,6 +158,21 @@ impl PlayState for MainMenuState {
     fn tick(&mut self, global_state: &mut GlobalState, events: Vec<Event>) -> PlayStateResult {
         
         //log::info!("PlayState for MainMenuState : tic");
+        if assets::take_font_changes() {
+            self.main_menu_ui
+                .update_language(global_state.i18n, &global_state.settings);
+        }
+        for section in self.settings_changes.try_iter() {
+            match section {
+                SettingsSection::Language => self
+                    .main_menu_ui
+                    .update_language(global_state.i18n, &global_state.settings),
+                SettingsSection::Interface => self
+                    .main_menu_ui
+                    .set_scale_mode(global_state.settings.interface.ui_scale),
+                _ => {},
+            }
+        }
         // Pull in localizations
         let localized_strings = &global_state.i18n.read();
        
@@ -94,30 +185,58 @@ impl PlayState for MainMenuState {
 
             match event {
                 Event::Close => return PlayStateResult::Shutdown,
+                Event::InputUpdate(GameInput::ToggleDebug, true) => {
+                    self.main_menu_ui.toggle_debug_bounds();
+                },
+                Event::InputUpdate(GameInput::Screenshot, true) => {
+                    self.main_menu_ui.capture_frame(
+                        global_state.window.renderer_mut(),
+                        self.scene.global_bind_group(),
+                        Box::new(screenshot::save),
+                    );
+                },
                 // Ignore all other events.
                 _ => {},
             }
         }
         // Poll client creation.
         match self.init.client().and_then(|init| init.poll()) {
+            Some(InitMsg::Challenge(challenge)) => self.main_menu_ui.show_challenge(challenge),
             Some(InitMsg::Done(Ok(mut client))) => {
+                crash_report::set_network("connected");
+                self.login_retries = 0;
+                if let Some(start) = self.connect_start.take() {
+                    global_state.telemetry.record(TelemetryEvent::LoadTiming {
+                        what: "connect",
+                        millis: start.elapsed().as_millis() as u64,
+                    });
+                }
                 // Register voxygen components / resources
                 crate::ecs::init(client.state_mut().ecs_mut());
                 self.init = InitState::Pipeline(Box::new(client));
             },
             Some(InitMsg::Done(Err(e))) => {
                 self.init = InitState::None;
+                self.connect_start = None;
+                let reason = failure_reason(&e);
+                crash_report::set_network(format!("connect failed ({})", reason));
+                global_state
+                    .telemetry
+                    .record(TelemetryEvent::ConnectFailed { reason });
                 log::error!("{:?} Client Init failed raw error", e);
-                let e = get_client_msg_error(e, &global_state.i18n);
-                // Log error for possible additional use later or in case that the error
-                // displayed is cut of.
-                log::error!("{:?}  Client Init failed", e);
-                global_state.info_message = Some(
-                    localized_strings
-                        .get("main.login.client_init_failed")
-                        .to_owned()
-                        .replace("{init_fail_reason}", e.as_str()),
-                );
+                // Stays on the connecting screen while retrying, cancelling it drops the retry
+                if !self.retry_login(&e) {
+                    let e = get_client_msg_error(e, &global_state.i18n);
+                    // Log error for possible additional use later or in case that the error
+                    // displayed is cut of.
+                    log::error!("{:?}  Client Init failed", e);
+                    global_state.info_message = Some(
+                        localized_strings
+                            .get("main.login.client_init_failed")
+                            .to_owned()
+                            .replace("{init_fail_reason}", e.as_str()),
+                    );
+                }
             },
            
             None => {},
@@ -174,10 +293,8 @@ impl PlayState for MainMenuState {
         }
 
         // Maintain the UI.
-        for event in self
-            .main_menu_ui
-            .maintain(global_state, global_state.clock.dt())
-        {
+        self.main_menu_ui.maintain(global_state, global_state.clock.dt());
+        for event in self.main_menu_ui.events().drain() {
             match event {
 
                 MainMenuEvent::LoginAttempt {
@@ -193,6 +310,22 @@ impl PlayState for MainMenuState {
                     }
 
                     global_state.settings.save();
+                    self.last_login = Some(LoginInfo {
+                        username: username.clone(),
+                        password: password.clone(),
+                        server: server_address.clone(),
+                    });
+
+                    if let Some(overrides) = global_state
+                        .settings
+                        .networking
+                        .server_overrides
+                        .get(&server_address)
+                        .cloned()
+                    {
+                        let replaced = apply_overrides(global_state, &overrides);
+                        global_state.settings.replaced_by_overrides = Some(replaced);
+                    }
 
                     //初始化网络
                     let connection_args = ConnectionArgs::Tcp {
@@ -200,6 +333,8 @@ impl PlayState for MainMenuState {
                     };
 
                     log::info!("### try MainMenuEvent => LoginAttempt");
+                    self.connect_start = Some(Instant::now());
+                    crash_report::set_network("connecting");
                     attempt_login(
                         &mut global_state.info_message,
                         username,
@@ -211,22 +346,27 @@ impl PlayState for MainMenuState {
                     );
                 },
 
+                MainMenuEvent::CaptchaAnswer(answer) => {
+                    if let Some(init) = self.init.client() {
+                        init.answer_captcha(answer);
+                    }
+                },
                 MainMenuEvent::CancelLoginAttempt => {
+                    // Retries published for later are cancelled as well
+                    self.main_menu_ui
+                        .events()
+                        .cancel(|event| matches!(event, MainMenuEvent::LoginAttempt { .. }));
+                    self.login_retries = 0;
                     self.init = InitState::None;
+                    crash_report::set_network("cancelled");
                     self.main_menu_ui.cancel_connection();
                 },
                 MainMenuEvent::ChangeLanguage(new_language) => {
                     global_state.settings.language.selected_language =
                         new_language.language_identifier;
-                    global_state.i18n = LocalizationHandle::load_expect(
-                        &global_state.settings.language.selected_language,
-                    );
-                    global_state.i18n.read().log_missing_entries();
-                    global_state
-                        .i18n
-                        .set_english_fallback(global_state.settings.language.use_english_fallback);
-                    self.main_menu_ui
-                        .update_language(global_state.i18n, &global_state.settings);
+                    global_state.settings.save();
+                    // The menu follows once the change comes in
+                    load_language(global_state);
                 },
                 
                 MainMenuEvent::Quit => return PlayStateResult::Shutdown,
@@ -241,9 +381,81 @@ impl PlayState for MainMenuState {
 
                     global_state.settings.save();
                 },
+                MainMenuEvent::SetServerOverrides { server, overrides } => {
+                    let server_overrides = &mut global_state.settings.networking.server_overrides;
+                    if overrides.is_empty() {
+                        server_overrides.remove(&server);
+                    } else {
+                        server_overrides.insert(server, overrides);
+                    }
+
+                    global_state.settings.save();
+                },
+                MainMenuEvent::SetUiScale(scale_mode) => {
+                    global_state.settings.interface.ui_scale = scale_mode;
+                    self.main_menu_ui.set_scale_mode(scale_mode);
+
+                    global_state.settings.save();
+                },
+                MainMenuEvent::FinishOnboarding { telemetry_opt_in } => {
+                    global_state.settings.first_run = false;
+                    persistence::finish_onboarding();
+                    global_state.settings.telemetry_opt_in = telemetry_opt_in;
+                    global_state.telemetry.set_enabled(telemetry_opt_in);
+
+                    global_state.settings.save();
+                },
+                MainMenuEvent::CopyCrashReport(report) => {
+                    global_state.clipboard.write(report);
+                },
+                MainMenuEvent::SetTelemetry(enabled) => {
+                    global_state.settings.telemetry_opt_in = enabled;
+                    global_state.telemetry.set_enabled(enabled);
+
+                    global_state.settings.save();
+                },
+                MainMenuEvent::Import(Import::Servers(servers)) => {
+                    let net_settings = &mut global_state.settings.networking;
+                    for server in servers {
+                        if !net_settings.servers.contains(&server) {
+                            net_settings.servers.push(server);
+                        }
+                    }
+
+                    global_state.settings.save();
+                },
+                MainMenuEvent::Import(Import::TranslationPack { path, .. }) => {
+                    let i18n = global_state.i18n.read();
+                    global_state.info_message = Some(match import::install_translation_pack(&path) {
+                        Ok(dir) => {
+                            log::info!("Installed translation pack into {}", dir.display());
+                            i18n.get("main.import.translation_installed").to_owned()
+                        },
+                        Err(e) => {
+                            log::warn!("Failed to install {}: {}", path.display(), e);
+                            i18n.get("main.import.failed")
+                                .replace("{file}", &path.display().to_string())
+                                .replace("{error}", &e.to_string())
+                        },
+                    });
+                },
             }
         }
 
+        let screen = self.main_menu_ui.screen_name();
+        if self.visited_screen != Some(screen) {
+            self.visited_screen = Some(screen);
+            crash_report::set_menu_screen(screen);
+            global_state
+                .telemetry
+                .record(TelemetryEvent::ScreenVisit { screen });
+        }
+
+        // The login failed or was cancelled
+        if matches!(self.init, InitState::None) {
+            self.restore_settings(global_state);
+        }
+
         if let Some(info) = global_state.info_message.take() {
             self.main_menu_ui.show_info(info);
         }
@@ -257,6 +469,8 @@ impl PlayState for MainMenuState {
 
     fn globals_bind_group(&self) -> &GlobalsBindGroup { self.scene.global_bind_group() }
 
+    fn needs_redraw(&self) -> bool { self.main_menu_ui.needs_redraw() }
+
     fn render<'a>(&'a self, drawer: &mut Drawer<'a>, _: &Settings) {
 
         //log::info!("PlayState for MainMenuState : render");
@@ -268,6 +482,84 @@ impl PlayState for MainMenuState {
     }
 }
 
+fn load_language(global_state: &mut GlobalState) {
+    global_state.i18n =
+        LocalizationHandle::load_expect(&global_state.settings.language.selected_language);
+    global_state.i18n.read().log_missing_entries();
+    global_state
+        .i18n
+        .set_english_fallback(global_state.settings.language.use_english_fallback);
+    crash_report::set_locale(&global_state.settings.language.selected_language);
+}
+
+/// Applies `overrides` to the settings and the state depending on them,
+/// returns the replaced settings
+fn apply_overrides(global_state: &mut GlobalState, overrides: &ServerOverrides) -> ServerOverrides {
+    let replaced = overrides.apply(&mut global_state.settings);
+    if replaced.language.is_some() {
+        load_language(global_state);
+    }
+    if replaced.audio.is_some() {
+        let audio = &global_state.settings.audio;
+        global_state.audio.set_master_volume(audio.master_volume);
+        global_state.audio.set_music_volume(audio.music_volume);
+        global_state.audio.set_sfx_volume(audio.sfx_volume);
+    }
+    replaced
+}
+
+/// Category of a failed connection attempt without any details which could
+/// identify the player or server
+fn failure_reason(error: &client_init::Error) -> &'static str {
+    use client::Error;
+    match error {
+        InitError::ClientError { error, .. } => match error {
+            Error::SpecsErr(_) => "internal",
+            Error::AuthErr(_) => "auth",
+            Error::Kicked(_) => "kicked",
+            Error::TooManyPlayers => "server_full",
+            Error::AuthServerNotTrusted => "untrusted_auth_server",
+            Error::ServerTimeout => "timeout",
+            Error::ServerShutdown => "server_shut_down",
+            Error::NotOnWhitelist => "not_on_whitelist",
+            Error::Banned(_) => "banned",
+            Error::InvalidCharacter => "invalid_character",
+            Error::ChallengeFailed => "challenge_failed",
+            Error::NetworkErr(NetworkError::ConnectFailed(NetworkConnectError::Handshake(
+                InitProtocolError::WrongVersion(_),
+            ))) => "wrong_version",
+            Error::NetworkErr(_) => "network",
+            Error::ParticipantErr(_) => "participant",
+            Error::StreamErr(_) => "stream",
+            Error::HostnameLookupFailed(_) => "hostname_lookup",
+            Error::Other(_) => "other",
+            Error::AuthServerUrlInvalid(_) => "auth_server_url_invalid",
+        },
+        InitError::ClientCrashed => "client_crashed",
+        InitError::ServerNotFound => "server_not_found",
+    }
+}
+
+/// Whether the same login may succeed later, e.g. once the network is back
+fn is_transient(error: &client_init::Error) -> bool {
+    use client::Error;
+    match error {
+        // The server can't be talked to, retrying won't change that
+        InitError::ClientError {
+            error:
+                Error::NetworkErr(NetworkError::ConnectFailed(
+                    NetworkConnectError::Handshake(_) | NetworkConnectError::InvalidSecret,
+                )),
+            ..
+        } => false,
+        InitError::ClientError {
+            error: Error::NetworkErr(_) | Error::ServerTimeout,
+            mismatched_server_info: None,
+        } => true,
+        _ => false,
+    }
+}
+
 fn get_client_msg_error(
     error: client_init::Error,
     localized_strings: &LocalizationHandle,
@@ -324,6 +616,7 @@ fn get_client_msg_error(
                 format!("{}: {}", localization.get("main.login.banned"), reason)
             },
             Error::InvalidCharacter => localization.get("main.login.invalid_character").into(),
+            Error::ChallengeFailed => localization.get("main.login.challenge_failed").into(),
             Error::NetworkErr(NetworkError::ConnectFailed(NetworkConnectError::Handshake(
                 InitProtocolError::WrongVersion(_),
             ))) => net_error(
@@ -7,53 +7,147 @@ use crate::{
     settings::ControlSettings,
     ui::{
         fonts::IcedFonts as Fonts,
-        ice::{component::neat_button, style, widget::Image, Element, IcedUi as Ui, Id},
-        Graphic,
+        ice::{
+            component::neat_button,
+            style,
+            widget::{BackgroundContainer, Image, Padding},
+            Element, IcedUi as Ui, Id,
+        },
+        Graphic, KeybindCache,
     },
 };
-use common::assets::{self, AssetExt};
+use common::{
+    assets::{self, AssetExt, LoadPriority},
+    calendar::{Calendar, CalendarEvent},
+};
 use i18n::Localization;
 use iced::{Length,Alignment};
-use iced::widget::{button, Column, Container, Row, Space, Text};
+use iced::widget::{button, Column, Container, Row, Space, Text, TextInput};
 
+use hashbrown::HashSet;
 use keyboard_keynames::key_layout::KeyLayout;
+use rand::{seq::SliceRandom, thread_rng};
 use serde::{Deserialize, Serialize};
 
+/// Width of the captcha image and its input
+const CAPTCHA_WIDTH: u16 = 300;
+
+const LOADER_MANIFEST: &str = "voxygen.element.animation.loaders.manifest";
+
+/// An animation shown while connecting, as listed in the loader manifest
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+pub(super) struct LoadingAnimationInfo {
+    id: String,
+    fps: f32,
+    frames: Vec<String>,
+    /// How likely it is picked compared to the others
+    #[serde(default = "default_weight")]
+    weight: f32,
+    /// Only picked during one of these events, all year if empty
+    #[serde(default)]
+    seasons: Vec<CalendarEvent>,
+}
+
+fn default_weight() -> f32 { 1.0 }
+
+/// The animations which can be shown, ones with missing frames are left out
+#[derive(Debug, Clone)]
+struct LoadingAnimationManifest(Vec<LoadingAnimationInfo>);
+
+impl assets::Compound for LoadingAnimationManifest {
+    fn load<S: assets::Source + ?Sized>(
+        cache: &assets::AssetCache<S>,
+        specifier: &str,
+    ) -> Result<Self, assets::BoxedError> {
+        let animations = cache
+            .load::<assets::Ron<Vec<LoadingAnimationInfo>>>(specifier)?
+            .cloned()
+            .0;
+        let dirs = animations
+            .iter()
+            .flat_map(|animation| &animation.frames)
+            .filter_map(|frame| frame.rsplit_once('.').map(|(dir, _)| dir))
+            .collect::<HashSet<_>>();
+        let existing = dirs
+            .into_iter()
+            .flat_map(|dir| assets::find(&[dir, ".*"].concat()))
+            .collect::<HashSet<_>>();
+        let animations = animations
+            .into_iter()
+            .filter(|animation| {
+                let missing = animation
+                    .frames
+                    .iter()
+                    .filter(|frame| !existing.contains(*frame))
+                    .collect::<Vec<_>>();
+                if !missing.is_empty() {
+                    log::warn!(
+                        "Loading animation {} is missing the frames {:?}, it is left out",
+                        animation.id,
+                        missing
+                    );
+                }
+                missing.is_empty() && !animation.frames.is_empty() && animation.fps > 0.0
+            })
+            .collect();
+        Ok(Self(animations))
+    }
+}
+
+/// Picks an animation for the next connecting screen, weighted and limited to
+/// the current season, and starts loading its frames
+pub(super) fn choose_loading_animation() -> Option<LoadingAnimationInfo> {
+    let manifest = match LoadingAnimationManifest::load(LOADER_MANIFEST) {
+        Ok(manifest) => manifest.cloned().0,
+        Err(e) => {
+            log::warn!("Failed to load the loading animations: {:?}", e);
+            return None;
+        },
+    };
+    let calendar = Calendar::from_tz(None);
+    let available = manifest
+        .into_iter()
+        .filter(|animation| {
+            animation.seasons.is_empty()
+                || animation.seasons.iter().any(|event| calendar.is_event(*event))
+        })
+        .collect::<Vec<_>>();
+    let animation = available
+        .choose_weighted(&mut thread_rng(), |animation| animation.weight.max(0.0))
+        .ok()?
+        .clone();
+    for frame in &animation.frames {
+        assets::Image::load_async(frame, LoadPriority::Prefetch);
+    }
+    Some(animation)
+}
+
 struct LoadingAnimation {
-    speed_factor: f32,
+    fps: f32,
     frames: Vec<Id>,
 }
+
 impl LoadingAnimation {
-    fn new(raw: &(f32, Vec<String>), ui: &mut Ui) -> Self {
-        let mut frames = vec![];
-        for frame_path in raw.1.iter() {
-            if let Ok(image) = assets::Image::load(frame_path) {
-                frames.push(ui.add_graphic(Graphic::Image(image.read().to_image(), None)));
-            } else {
-                frames.push(
-                    ui.add_graphic(Graphic::Image(
-                        assets::Image::load("voxygen.element.not_found")
-                            .unwrap_or_else(|_| panic!("Missing asset '{}'", frame_path))
-                            .read()
-                            .to_image(),
-                        None,
-                    )),
-                )
-            }
-        }
-        Self {
-            speed_factor: raw.0,
-            frames,
-        }
+    fn new(info: Option<&LoadingAnimationInfo>, ui: &mut Ui) -> Self {
+        let (fps, frames) = match info {
+            Some(info) => (info.fps, info.frames.as_slice()),
+            None => (0.0, &[][..]),
+        };
+        let frames = frames
+            .iter()
+            .map(|frame| {
+                let image = assets::Image::load_or_placeholder(frame).read().to_image();
+                ui.add_graphic(Graphic::Image(image, None))
+            })
+            .collect();
+        Self { fps, frames }
     }
-}
-
-#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
-struct LoadingAnimationManifest(Vec<(f32, Vec<String>)>);
-impl assets::Asset for LoadingAnimationManifest {
-    type Loader = assets::RonLoader;
 
-    const EXTENSION: &'static str = "ron";
+    /// The frame to show `time` seconds in, `None` without an animation
+    fn frame(&self, time: f64) -> Option<Id> {
+        let index = (time * f64::from(self.fps)) as usize % self.frames.len().max(1);
+        self.frames.get(index).copied()
+    }
 }
 
 /// Connecting screen for the main menu
@@ -61,34 +155,64 @@ pub struct Screen {
     cancel_button: button::State,
     add_button: button::State,
     tip_number: u16,
+    tip: Option<String>,
+    keybinds: KeybindCache,
     loading_animation: LoadingAnimation,
 }
 
 impl Screen {
-    pub fn new(ui: &mut Ui) -> Self {
-        let animations =
-            LoadingAnimationManifest::load("voxygen.element.animation.loaders.manifest")
-                .expect(
-                    "Missing loader manifest file 'voxygen/element/animation/loaders/manifest.ron'",
-                )
-                .cloned()
-                .0;
+    pub fn new(animation: Option<&LoadingAnimationInfo>, ui: &mut Ui) -> Self {
         Self {
             cancel_button: Default::default(),
             add_button: Default::default(),
             tip_number: rand::random(),
-            loading_animation: LoadingAnimation::new(
-                &animations[rand::random::<usize>() % animations.len()],
-                ui,
-            ),
+            tip: None,
+            keybinds: KeybindCache::default(),
+            loading_animation: LoadingAnimation::new(animation, ui),
         }
     }
 
+    /// The tip with the placeholders replaced by the bound keys
+    fn build_tip(
+        &mut self,
+        i18n: &Localization,
+        controls: &ControlSettings,
+        key_layout: &Option<KeyLayout>,
+    ) -> String {
+        let tip = &i18n.get_variation("loading.tips", self.tip_number);
+        let mut new_tip = String::with_capacity(tip.len());
+        let mut last_index = 0;
+
+        // This could be done with regex instead, but adding new dependencies is
+        // scary...
+        tip.match_indices("{gameinput.").for_each(|(start, s)| {
+            if let Some(end) = tip[start + s.len()..].find('}') {
+                let end = start + s.len() + end;
+                if let Ok(game_input) = GameInput::from_str(&tip[start + 1..end]) {
+                    new_tip.push_str(&tip[last_index..start]);
+                    new_tip.push_str(
+                        self.keybinds
+                            .get(game_input, controls, key_layout)
+                            .unwrap_or_else(|| i18n.get("main.unbound_key_tip")),
+                    );
+                    last_index = end + 1;
+                }
+            }
+        });
+        // If there is any text left over append it
+        if last_index < tip.len() {
+            new_tip.push_str(&tip[last_index..]);
+        }
+
+        format!("{} {}", i18n.get("main.tip"), new_tip)
+    }
+
     pub(super) fn view(
         &mut self,
         fonts: &Fonts,
         imgs: &Imgs,
-        connection_state: &ConnectionState,
+        connection_state: &mut ConnectionState,
+        captcha_img: Id,
         time: f64,
         i18n: &Localization,
         button_style: style::button::Style,
@@ -96,113 +220,128 @@ impl Screen {
         controls: &ControlSettings,
         key_layout: &Option<KeyLayout>,
     ) -> Element<Message> {
-        // TODO: add built in support for animated images
-        let frame_index = (time * self.loading_animation.speed_factor as f64)
-            % self.loading_animation.frames.len() as f64;
-        let frame_id = self.loading_animation.frames[frame_index as usize];
-
-        let children = match connection_state {
-            ConnectionState::InProgress => {
-                let tip = if show_tip {
-                    let tip = &i18n.get_variation("loading.tips", self.tip_number);
-                    let mut new_tip = String::with_capacity(tip.len());
-                    let mut last_index = 0;
-
-                    // This could be done with regex instead, but adding new dependencies is
-                    // scary...
-                    tip.match_indices("{gameinput.").for_each(|(start, s)| {
-                        if let Some(end) = tip[start + s.len()..].find('}') {
-                            let end = start + s.len() + end;
-                            if let Ok(game_input) = GameInput::from_str(&tip[start + 1..end]) {
-                                new_tip.push_str(&tip[last_index..start]);
-                                new_tip.push_str(
-                                    match controls.keybindings.get(&game_input) {
-                                        Some(Some(key_mouse)) => {
-                                            key_mouse.display_string(key_layout)
-                                        },
-                                        Some(None) => i18n.get("main.unbound_key_tip").to_string(),
-                                        None => ControlSettings::default_binding(game_input)
-                                            .display_string(key_layout),
-                                    }
-                                    .as_str(),
-                                );
-                                last_index = end + 1;
-                            }
-                        }
-                    });
-                    // If there is any text left over append it
-                    if last_index < tip.len() {
-                        new_tip.push_str(&tip[last_index..]);
-                    }
-
-                    let tip = format!("{} {}", i18n.get("main.tip"), new_tip.as_str());
-                    Container::new(Text::new(tip).size(fonts.cyri.scale(25)))
-                        .width(Length::Fill)
-                        .height(Length::Fill)
-                        .center_x()
-                        .align_y(Alignment::End)
-                        .into()
-                } else {
-                    Space::new(Length::Fill, Length::Fill).into()
-                };
-
-                let cancel = Container::new(neat_button(
-                    &mut self.cancel_button,
-                    i18n.get("common.cancel"),
+        let top: Element<Message> = match connection_state {
+            ConnectionState::InProgress => Space::new(Length::Fill, Length::Fill).into(),
+            ConnectionState::ProofOfWork => {
+                Text::new(i18n.get("main.login.proof_of_work"))
+                    .size(fonts.cyri.scale(25))
+                    .into()
+            },
+            ConnectionState::Captcha {
+                answer,
+                input,
+                submit_button,
+            } => Column::with_children(vec![
+                Image::new(captcha_img)
+                    .width(Length::Units(CAPTCHA_WIDTH))
+                    .fix_aspect_ratio()
+                    .into(),
+                Text::new(i18n.get("main.login.captcha_prompt"))
+                    .size(fonts.cyri.scale(20))
+                    .into(),
+                BackgroundContainer::new(
+                    Image::new(imgs.input_bg)
+                        .width(Length::Units(CAPTCHA_WIDTH))
+                        .fix_aspect_ratio(),
+                    TextInput::new(input, "", answer, Message::CaptchaText)
+                        .size(fonts.cyri.scale(20))
+                        .on_submit(Message::SubmitCaptcha),
+                )
+                .padding(Padding::new().horizontal(7).top(5))
+                .into(),
+                Container::new(neat_button(
+                    submit_button,
+                    i18n.get("common.confirm"),
                     0.7,
                     button_style,
-                    Some(Message::CancelConnect),
+                    (!answer.trim().is_empty()).then(|| Message::SubmitCaptcha),
                 ))
-                .width(Length::Fill)
+                .width(Length::Units(CAPTCHA_WIDTH / 2))
                 .height(Length::Units(fonts.cyri.scale(30)))
-                .center_x()
-                .padding(3);
-
-                let tip_cancel = Column::with_children(vec![tip, cancel.into()])
-                    .width(Length::FillPortion(3))
-                    .align_items(Alignment::Center)
-                    .spacing(5)
-                    .padding(5);
-
-                let gear = Container::new(
-                    Image::new(frame_id)
-                        .width(Length::Units(64))
-                        .height(Length::Units(64)),
-                )
+                .into(),
+            ])
+            .width(Length::Units(CAPTCHA_WIDTH))
+            .spacing(8)
+            .align_items(Alignment::Center)
+            .into(),
+        };
+        let top = Container::new(top)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y();
+
+        let tip = if show_tip {
+            // The tip only has to be built again if the bindings changed
+            if self.keybinds.validate(controls) || self.tip.is_none() {
+                self.tip = Some(self.build_tip(i18n, controls, key_layout));
+            }
+            let tip = self.tip.as_deref().unwrap_or_default();
+            Container::new(Text::new(tip).size(fonts.cyri.scale(25)))
                 .width(Length::Fill)
-                .padding(10)
-                .align_x(Alignment::End);
-
-                let bottom_content = Row::with_children(vec![
-                    Space::new(Length::Fill, Length::Shrink).into(),
-                    tip_cancel.into(),
-                    gear.into(),
-                ])
-                .align_items(Alignment::Center)
-                .width(Length::Fill);
-
-                let left_art = Image::new(imgs.loading_art_l)
-                    .width(Length::Units(12))
-                    .height(Length::Units(12));
-                let right_art = Image::new(imgs.loading_art_r)
-                    .width(Length::Units(12))
-                    .height(Length::Units(12));
-
-                let bottom_bar = Container::new(Row::with_children(vec![
-                    left_art.into(),
-                    bottom_content.into(),
-                    right_art.into(),
-                ]))
-                .height(Length::Units(85))
-                .style(style::container::Style::image(imgs.loading_art));
-
-                vec![
-                    Space::new(Length::Fill, Length::Fill).into(),
-                    bottom_bar.into(),
-                ]
-            },
-            
+                .height(Length::Fill)
+                .center_x()
+                .align_y(Alignment::End)
+                .into()
+        } else {
+            Space::new(Length::Fill, Length::Fill).into()
+        };
+
+        let cancel = Container::new(neat_button(
+            &mut self.cancel_button,
+            i18n.get("common.cancel"),
+            0.7,
+            button_style,
+            Some(Message::CancelConnect),
+        ))
+        .width(Length::Fill)
+        .height(Length::Units(fonts.cyri.scale(30)))
+        .center_x()
+        .padding(3);
+
+        let tip_cancel = Column::with_children(vec![tip, cancel.into()])
+            .width(Length::FillPortion(3))
+            .align_items(Alignment::Center)
+            .spacing(5)
+            .padding(5);
+
+        // TODO: add built in support for animated images
+        let gear: Element<Message> = match self.loading_animation.frame(time) {
+            Some(frame_id) => Image::new(frame_id)
+                .width(Length::Units(64))
+                .height(Length::Units(64))
+                .into(),
+            None => Space::new(Length::Units(64), Length::Units(64)).into(),
         };
+        let gear = Container::new(gear)
+            .width(Length::Fill)
+            .padding(10)
+            .align_x(Alignment::End);
+
+        let bottom_content = Row::with_children(vec![
+            Space::new(Length::Fill, Length::Shrink).into(),
+            tip_cancel.into(),
+            gear.into(),
+        ])
+        .align_items(Alignment::Center)
+        .width(Length::Fill);
+
+        let left_art = Image::new(imgs.loading_art_l)
+            .width(Length::Units(12))
+            .height(Length::Units(12));
+        let right_art = Image::new(imgs.loading_art_r)
+            .width(Length::Units(12))
+            .height(Length::Units(12));
+
+        let bottom_bar = Container::new(Row::with_children(vec![
+            left_art.into(),
+            bottom_content.into(),
+            right_art.into(),
+        ]))
+        .height(Length::Units(85))
+        .style(style::container::Style::image(imgs.loading_art));
+
+        let children = vec![top.into(), bottom_bar.into()];
 
         Column::with_children(children)
             .width(Length::Fill)
@@ -210,3 +349,15 @@ impl Screen {
             .into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loader_frames_exist() {
+        let listed = assets::Ron::<Vec<LoadingAnimationInfo>>::load_expect(LOADER_MANIFEST);
+        let valid = LoadingAnimationManifest::load_expect(LOADER_MANIFEST);
+        assert_eq!(valid.read().0, listed.read().0);
+    }
+}
@@ -1,9 +1,13 @@
 use super::Message;
 use crate::{
-    credits::Credits,
+    credits::{Credits, SectionKind},
     ui::{
         fonts::IcedFonts as Fonts,
-        ice::{component::neat_button, style, Element},
+        ice::{
+            component::label_button,
+            style::{self, button::ButtonState},
+            Element,
+        },
     },
 };
 use i18n::Localization;
@@ -65,22 +69,24 @@ impl Screen {
                 Ok(text)
             };
 
-        let music_header_color = iced::Color::from_rgb8(0xfc, 0x71, 0x76);
-        let fonts_header_color = iced::Color::from_rgb8(0xf7, 0xd1, 0x81);
-        let other_art_header_color = iced::Color::from_rgb8(0xc5, 0xe9, 0x80);
-        let contributors_header_color = iced::Color::from_rgb8(0x4a, 0xa6, 0x7b);
+        let header_color = |kind| match kind {
+            SectionKind::Music => iced::Color::from_rgb8(0xfc, 0x71, 0x76),
+            SectionKind::Fonts => iced::Color::from_rgb8(0xf7, 0xd1, 0x81),
+            SectionKind::OtherArt => iced::Color::from_rgb8(0xc5, 0xe9, 0x80),
+            SectionKind::Contributors => iced::Color::from_rgb8(0x4a, 0xa6, 0x7b),
+            SectionKind::Translators => iced::Color::from_rgb8(0x7b, 0x9c, 0xd8),
+        };
 
         fn credit_section<'a, T>(
-            header_i18n_key: &str,
+            header: &str,
             header_color: iced::Color,
             credit_iter: impl Iterator<Item = T>,
             format_credit: impl Fn(T) -> Result<String, core::fmt::Error>,
             fonts: &Fonts,
-            i18n: &Localization,
         ) -> Element<'a, Message> {
             Column::with_children(
                 core::iter::once(
-                    Text::new(i18n.get(header_i18n_key))
+                    Text::new(header)
                         .font(fonts.cyri.id)
                         .size(fonts.cyri.scale(30))
                         .color(header_color)
@@ -106,17 +112,41 @@ impl Screen {
             .into()
         }
 
-        let art_section = |header_i18n_key, header_color, art: &[_]| {
-            credit_section(
-                header_i18n_key,
-                header_color,
-                art.iter(),
-                format_art_credit,
-                fonts,
-                i18n,
-            )
+        let art_section = |header: &str, header_color, art: &[_]| {
+            credit_section(header, header_color, art.iter(), format_art_credit, fonts)
         };
 
+        let metadata = i18n.metadata();
+        let translators = credits.translators(metadata);
+        let mut scrollable = Scrollable::new(&mut self.scroll);
+        for section in &credits.sections {
+            let header = i18n
+                .get(&section.title)
+                .replace("{language}", &metadata.language_name);
+            let color = header_color(section.kind);
+            let element = match section.kind {
+                SectionKind::Music => art_section(&header, color, &credits.music),
+                SectionKind::Fonts => art_section(&header, color, &credits.fonts),
+                SectionKind::OtherArt => art_section(&header, color, &credits.other_art),
+                SectionKind::Contributors => credit_section(
+                    &header,
+                    color,
+                    credits.contributors.iter(),
+                    format_contributor_credit,
+                    fonts,
+                ),
+                SectionKind::Translators if translators.is_empty() => continue,
+                SectionKind::Translators => credit_section(
+                    &header,
+                    color,
+                    translators.iter(),
+                    format_contributor_credit,
+                    fonts,
+                ),
+            };
+            scrollable = scrollable.push(element);
+        }
+
         Container::new(
             Container::new(
                 Column::with_children(vec![
@@ -127,39 +157,17 @@ impl Screen {
                         .horizontal_alignment(Horizontal::Center)
                         .into(),
                     Space::new(Length::Fill, Length::Units(25)).into(),
-                    Scrollable::new(&mut self.scroll)
-                        .push(art_section(
-                            "main.credits.music",
-                            music_header_color,
-                            &credits.music,
-                        ))
-                        .push(art_section(
-                            "main.credits.fonts",
-                            fonts_header_color,
-                            &credits.fonts,
-                        ))
-                        .push(art_section(
-                            "main.credits.other_art",
-                            other_art_header_color,
-                            &credits.other_art,
-                        ))
-                        .push(credit_section(
-                            "main.credits.contributors",
-                            contributors_header_color,
-                            credits.contributors.iter(),
-                            format_contributor_credit,
-                            fonts,
-                            i18n,
-                        ))
+                    scrollable
                         .height(Length::FillPortion(1))
                         .width(Length::Fill)
                         .into(),
                     Container::new(
-                        Container::new(neat_button(
+                        Container::new(label_button(
                             &mut self.back_button,
                             i18n.get("common.back"),
-                            0.7,
-                            button_style,
+                            &fonts.cyri,
+                            fonts.cyri.scale(28),
+                            button_style.text(ButtonState::Hover, |text| text.size_delta(2)),
                             Some(Message::Back),
                         ))
                         .height(Length::Units(fonts.cyri.scale(50))),
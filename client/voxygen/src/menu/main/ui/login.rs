@@ -1,4 +1,7 @@
-use super::{Imgs, LoginInfo, Message, FILL_FRAC_ONE, FILL_FRAC_TWO};
+use super::{
+    list_entry_style, Imgs, LanguageDetails, LoginInfo, Message, Tooltips, FILL_FRAC_ONE,
+    FILL_FRAC_TWO,
+};
 use crate::ui::{
     fonts::IcedFonts as Fonts,
     ice::{
@@ -6,21 +9,31 @@ use crate::ui::{
         style,
         widget::{
             compound_graphic::{CompoundGraphic, Graphic},
-            BackgroundContainer, Image, Padding,
+            dropdown,
+            BackgroundContainer, Dropdown, Image, Padding,
         },
         Element,
     },
 };
 use i18n::{LanguageMetadata, Localization};
-use iced::{Length, Alignment};
+use iced::{Alignment, Breakpoint, Length};
 use iced::widget::{
-    button, scrollable, text_input, Button, Column, Container, Row, Scrollable,
-    Space, Text, TextInput,
+    button,
+    responsive::{Arrangement, Responsive},
+    text_input, Column, Container, Row, Space, Text, TextInput,
 };
+use std::net::{IpAddr, Ipv6Addr};
 use vek::*;
 
 const INPUT_WIDTH: u16 = 230;
 const INPUT_TEXT_SIZE: u16 = 20;
+// Height of the input background at `INPUT_WIDTH`
+const INPUT_HEIGHT: u16 = 34;
+const PORT_WIDTH: u16 = 70;
+/// Width the columns spread out to at most, see [`Breakpoints`]
+///
+/// [`Breakpoints`]: iced::Breakpoints
+const MAX_WIDTH: u32 = 2400;
 
 /// Login screen for the main menu
 pub struct Screen {
@@ -28,10 +41,9 @@ pub struct Screen {
     // settings_button: button::State,
     servers_button: button::State,
     credits_button: button::State,
+    telemetry_button: button::State,
     language_select_button: button::State,
 
-    error_okay_button: button::State,
-
     pub banner: LoginBanner,
     language_selection: LanguageSelectBanner,
 }
@@ -41,30 +53,30 @@ impl Screen {
         Self {
             servers_button: Default::default(),
             credits_button: Default::default(),
+            telemetry_button: Default::default(),
             // settings_button: Default::default(),
             quit_button: Default::default(),
             language_select_button: Default::default(),
 
-            error_okay_button: Default::default(),
-
             banner: LoginBanner::new(),
             language_selection: LanguageSelectBanner::new(),
         }
     }
 
-    pub(super) fn view(
-        &mut self,
+    pub(super) fn view<'a>(
+        &'a mut self,
         fonts: &Fonts,
         imgs: &Imgs,
         login_info: &LoginInfo,
-        error: Option<&str>,
         i18n: &Localization,
         is_selecting_language: bool,
         selected_language_index: Option<usize>,
         language_metadatas: &[LanguageMetadata],
+        language_details: &LanguageDetails,
         button_style: style::button::Style,
+        tooltips: Tooltips<'a>,
         version: &str,
-    ) -> Element<Message> {
+    ) -> Element<'a, Message> {
         let buttons = Column::with_children(vec![
             neat_button(
                 &mut self.servers_button,
@@ -94,6 +106,13 @@ impl Screen {
                 button_style,
                 Some(Message::ShowCredits),
             ),
+            neat_button(
+                &mut self.telemetry_button,
+                i18n.get("main.telemetry"),
+                FILL_FRAC_ONE,
+                button_style,
+                Some(Message::ShowTelemetry),
+            ),
             neat_button(
                 &mut self.quit_button,
                 i18n.get("common.quit"),
@@ -134,44 +153,16 @@ impl Screen {
             .padding(27)
             .into();
 
-        let central_content = if let Some(error) = error {
-            Container::new(
-                Column::with_children(vec![
-                    Container::new(Text::new(error)).height(Length::Fill).into(),
-                    Container::new(neat_button(
-                        &mut self.error_okay_button,
-                        i18n.get("common.okay"),
-                        FILL_FRAC_ONE,
-                        button_style,
-                        Some(Message::CloseError),
-                    ))
-                    .width(Length::Fill)
-                    .height(Length::Units(30))
-                    .center_x()
-                    .into(),
-                ])
-                .height(Length::Fill)
-                .width(Length::Fill),
-            )
-            .style(
-                style::container::Style::color_with_double_cornerless_border(
-                    (22, 18, 16, 255).into(),
-                    (11, 11, 11, 255).into(),
-                    (54, 46, 38, 255).into(),
-                ),
-            )
-            .width(Length::Units(400))
-            .height(Length::Units(180))
-            .padding(20)
-            .into()
-        } else if is_selecting_language {
+        let central_content = if is_selecting_language {
             self.language_selection.view(
                 fonts,
                 imgs,
                 i18n,
                 language_metadatas,
+                language_details,
                 selected_language_index,
                 button_style,
+                tooltips,
             )
         } else {
             self.banner
@@ -197,97 +188,98 @@ impl Screen {
         .height(Length::Fill)
         .align_x(Alignment::End);
 
-        Row::with_children(vec![
+        // Small windows leave out the logo, on ultra-wide ones the columns
+        // stay close to the center instead of spreading out to the edges
+        let columns = Responsive::with_children(vec![
             left_column,
             central_column.into(),
             right_column.into(),
         ])
-        .width(Length::Fill)
+        .arrangement(Breakpoint::Compact, Arrangement::row().hide(2))
+        .max_width(MAX_WIDTH)
         .height(Length::Fill)
-        .spacing(10)
-        .into()
+        .spacing(10);
+
+        Container::new(columns)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .into()
     }
 }
 
 pub struct LanguageSelectBanner {
     okay_button: button::State,
-    language_buttons: Vec<button::State>,
-
-    selection_list: scrollable::State,
+    language_dropdown: dropdown::State,
 }
 
 impl LanguageSelectBanner {
     fn new() -> Self {
         Self {
             okay_button: Default::default(),
-            language_buttons: Default::default(),
-            selection_list: Default::default(),
+            language_dropdown: Default::default(),
         }
     }
 
-    fn view(
-        &mut self,
+    fn view<'a>(
+        &'a mut self,
         fonts: &Fonts,
         imgs: &Imgs,
         i18n: &Localization,
         language_metadatas: &[LanguageMetadata],
+        language_details: &LanguageDetails,
         selected_language_index: Option<usize>,
         button_style: style::button::Style,
-    ) -> Element<Message> {
-        // Reset button states if languages were added / removed
-        if self.language_buttons.len() != language_metadatas.len() {
-            self.language_buttons = vec![Default::default(); language_metadatas.len()];
-        }
-
+        tooltips: Tooltips<'a>,
+    ) -> Element<'a, Message> {
         let title = Text::new(i18n.get("main.login.select_language"))
             .size(fonts.cyri.scale(35))
             .horizontal_alignment(iced::Horizontal::Center);
 
-        let mut list = Scrollable::new(&mut self.selection_list)
-            .spacing(8)
-            .height(Length::Fill)
-            .align_items(Alignment::Start);
-
-        let list_items = self
-            .language_buttons
-            .iter_mut()
-            .zip(language_metadatas)
-            .enumerate()
-            .map(|(i, (state, lang))| {
-                let color = if Some(i) == selected_language_index {
-                    (97, 255, 18)
-                } else {
-                    (97, 97, 25)
-                };
-                let button = Button::new(
-                    state,
-                    Row::with_children(vec![
-                        Space::new(Length::FillPortion(5), Length::Units(0)).into(),
-                        Text::new(lang.language_name.clone())
-                            .width(Length::FillPortion(95))
-                            .size(fonts.cyri.scale(25))
-                            .vertical_alignment(iced::Vertical::Center)
-                            .into(),
-                    ]),
-                )
-                .style(
-                    style::button::Style::new(imgs.selection)
-                        .hover_image(imgs.selection_hover)
-                        .press_image(imgs.selection_press)
-                        .image_color(vek::Rgba::new(color.0, color.1, color.2, 192)),
-                )
-                .min_height(56)
-                .on_press(Message::LanguageChanged(i));
-                Row::with_children(vec![
-                    Space::new(Length::FillPortion(3), Length::Units(0)).into(),
-                    button.width(Length::FillPortion(92)).into(),
-                    Space::new(Length::FillPortion(5), Length::Units(0)).into(),
-                ])
-            });
+        let languages = language_metadatas
+            .iter()
+            .map(|lang| format!("{} ({}%)", lang.display_name(), language_details.percent(lang)))
+            .collect();
+        let icons = language_metadatas
+            .iter()
+            .map(|lang| language_details.icon(lang))
+            .collect();
+
+        let dropdown = Dropdown::new(
+            &mut self.language_dropdown,
+            languages,
+            selected_language_index,
+            Message::LanguageChanged,
+        )
+        .style(
+            style::dropdown::Style::new(
+                list_entry_style(imgs, true, 192),
+                list_entry_style(imgs, false, 192),
+            )
+            .selected_option(list_entry_style(imgs, true, 192)),
+        )
+        .row_height(40)
+        .text_size(fonts.cyri.scale(25))
+        .font(fonts.cyri.id)
+        .icons(icons);
+
+        // Describes the selected language, the open dropdown takes precedence
+        let dropdown = match selected_language_index.and_then(|i| language_metadatas.get(i)) {
+            Some(language) => {
+                let completeness = i18n
+                    .get("main.login.language_completeness")
+                    .replace("{percent}", &language_details.percent(language).to_string());
+                tooltips.rich(dropdown, language.display_name().to_owned(), move || {
+                    vec![completeness.clone()]
+                })
+            },
+            None => dropdown.into(),
+        };
 
-        for item in list_items {
-            list = list.push(item);
-        }
+        let selection = Container::new(dropdown)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .padding(10);
 
         let okay_button = Container::new(neat_button(
             &mut self.okay_button,
@@ -299,11 +291,12 @@ impl LanguageSelectBanner {
         .center_x()
         .max_width(200);
 
-        let content = Column::with_children(vec![title.into(), list.into(), okay_button.into()])
-            .spacing(8)
-            .width(Length::Fill)
-            .height(Length::FillPortion(38))
-            .align_items(Alignment::Center);
+        let content =
+            Column::with_children(vec![title.into(), selection.into(), okay_button.into()])
+                .spacing(8)
+                .width(Length::Fill)
+                .height(Length::FillPortion(38))
+                .align_items(Alignment::Center);
 
         let selection_menu = BackgroundContainer::new(
             CompoundGraphic::from_graphics(vec![
@@ -327,6 +320,7 @@ pub struct LoginBanner {
     pub username: text_input::State,
     pub password: text_input::State,
     pub server: text_input::State,
+    pub port: text_input::State,
 
     multiplayer_button: button::State,
 }
@@ -337,6 +331,7 @@ impl LoginBanner {
             username: Default::default(),
             password: Default::default(),
             server: Default::default(),
+            port: Default::default(),
             multiplayer_button: Default::default(),
         }
     }
@@ -350,6 +345,7 @@ impl LoginBanner {
         button_style: style::button::Style,
     ) -> Element<Message> {
         let input_text_size = fonts.cyri.scale(INPUT_TEXT_SIZE);
+        let (host, port) = split_port(&login_info.server);
 
         let banner_content = Column::with_children(vec![
             Column::with_children(vec![
@@ -384,20 +380,36 @@ impl LoginBanner {
                 )
                 .padding(Padding::new().horizontal(7).top(5))
                 .into(),
-                BackgroundContainer::new(
-                    Image::new(imgs.input_bg)
-                        .width(Length::Units(INPUT_WIDTH))
-                        .fix_aspect_ratio(),
-                    TextInput::new(
-                        &mut self.server,
-                        i18n.get("main.server"),
-                        &login_info.server,
-                        Message::Server,
+                Row::with_children(vec![
+                    BackgroundContainer::new(
+                        Image::new(imgs.input_bg)
+                            .width(Length::Units(INPUT_WIDTH - PORT_WIDTH - 5))
+                            .height(Length::Units(INPUT_HEIGHT)),
+                        TextInput::new(
+                            &mut self.server,
+                            i18n.get("main.server"),
+                            host,
+                            Message::Server,
+                        )
+                        .size(input_text_size)
+                        .on_submit(Message::Multiplayer),
                     )
-                    .size(input_text_size)
-                    .on_submit(Message::Multiplayer),
-                )
-                .padding(Padding::new().horizontal(7).top(5))
+                    .padding(Padding::new().horizontal(7).top(5))
+                    .into(),
+                    BackgroundContainer::new(
+                        Image::new(imgs.input_bg)
+                            .width(Length::Units(PORT_WIDTH))
+                            .height(Length::Units(INPUT_HEIGHT)),
+                        TextInput::new(&mut self.port, i18n.get("main.port"), port, Message::Port)
+                            .size(input_text_size)
+                            .numeric()
+                            .max_length(5)
+                            .on_submit(Message::Multiplayer),
+                    )
+                    .padding(Padding::new().horizontal(7).top(5))
+                    .into(),
+                ])
+                .spacing(5)
                 .into(),
             ])
             .spacing(5)
@@ -426,3 +438,70 @@ impl LoginBanner {
             .into()
     }
 }
+
+/// Splits the port off a server address, it is empty if there is none
+///
+/// Follows the rules of socket addresses and URLs: the port is made up of
+/// digits and IPv6 hosts need brackets to have one, so `::1` has no port.
+/// Ports out of range are still split off to be reported on login.
+pub(super) fn split_port(address: &str) -> (&str, &str) {
+    if address.parse::<IpAddr>().is_ok() {
+        return (address, "");
+    }
+    match address.rsplit_once(':') {
+        Some((host, port))
+            if !port.is_empty()
+                && port.bytes().all(|b| b.is_ascii_digit())
+                && is_port_host(host) =>
+        {
+            (host, port)
+        },
+        _ => (address, ""),
+    }
+}
+
+// Colons in the host are only allowed in bracketed IPv6 addresses
+fn is_port_host(host: &str) -> bool {
+    match host.strip_prefix('[').and_then(|host| host.strip_suffix(']')) {
+        Some(ip) => ip.parse::<Ipv6Addr>().is_ok(),
+        None => !host.is_empty() && !host.contains(':'),
+    }
+}
+
+/// Inverse of [`split_port`], brackets IPv6 hosts which get a port
+pub(super) fn join_port(host: &str, port: &str) -> String {
+    if port.is_empty() {
+        host.to_owned()
+    } else if host.parse::<Ipv6Addr>().is_ok() {
+        format!("[{}]:{}", host, port)
+    } else {
+        format!("{}:{}", host, port)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ports() {
+        assert_eq!(split_port("server.veloren.net"), ("server.veloren.net", ""));
+        assert_eq!(split_port("localhost:14004"), ("localhost", "14004"));
+        assert_eq!(split_port("[::1]:14004"), ("[::1]", "14004"));
+        assert_eq!(split_port("::1"), ("::1", ""));
+        assert_eq!(split_port("fe80::1:14004"), ("fe80::1:14004", ""));
+        assert_eq!(split_port("[::1]"), ("[::1]", ""));
+        assert_eq!(split_port("[not:ip]:14004"), ("[not:ip]:14004", ""));
+        assert_eq!(split_port("localhost:"), ("localhost:", ""));
+        assert_eq!(split_port("localhost:port"), ("localhost:port", ""));
+        assert_eq!(split_port("127.0.0.1:99999"), ("127.0.0.1", "99999"));
+        assert_eq!(join_port("localhost", ""), "localhost");
+        assert_eq!(join_port("[::1]", "14004"), "[::1]:14004");
+        assert_eq!(join_port("::1", "14004"), "[::1]:14004");
+        assert_eq!(join_port("::1", ""), "::1");
+        for address in &["localhost:14004", "[fe80::1]:80", "::1", "10.0.0.1:1"] {
+            let (host, port) = split_port(address);
+            assert_eq!(join_port(host, port), *address);
+        }
+    }
+}
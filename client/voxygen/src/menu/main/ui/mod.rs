@@ -3,28 +3,48 @@ mod connecting;
 //mod disclaimer;
 mod credits;
 mod login;
+mod modal;
+mod onboarding;
 mod servers;
+#[cfg(test)]
+mod snapshot;
+mod telemetry;
 
 use crate::{
+    crash_report,
     credits::Credits,
-    render::ThirdPassDrawer,
+    event_bus::{EventBus, Priority},
+    render::{GlobalsBindGroup, OnCapture, Renderer, ThirdPassDrawer},
+    telemetry::Telemetry,
     ui::{
         self,
         fonts::IcedFonts as Fonts,
-        ice::{load_font, style, widget, Element, IcedUi as Ui},
-        img_ids::ImageGraphic,
+        ice::{
+            component::tooltip::{self, WithTooltip},
+            load_font,
+            style::{self, button::ButtonState},
+            widget::{self, TooltipManager},
+            Element, IcedUi as Ui,
+        },
+        img_ids::{GraphicCreator, ImageGraphic},
         Graphic,
     },
     window, GlobalState,
 };
 use i18n::{LanguageMetadata, LocalizationHandle};
+use modal::{Modal, ModalKind};
+use super::import::{self, Import};
+use std::{cell::Cell, path::PathBuf, sync::Arc};
 use iced::{Length, Horizontal};
-use iced::widget::{Text, Column, Container, text_input, Row, Space};
+use iced::widget::{button, Text, Column, Container, text_input, Row, Space};
 
 use keyboard_keynames::key_layout::KeyLayout;
-//ImageFrame, Tooltip,
-use crate::settings::Settings;
-use common::assets::{self, AssetExt};
+use crate::settings::{get_fps, ServerOverrides, Settings, VIEW_DISTANCE_RANGE};
+use common::assets::{
+    self, AssetExt, AssetFuture, LoadPriority, PreloadProgress, WeakAssetHandle,
+};
+use hashbrown::HashMap;
+use common_net::msg::LoginChallenge;
 use rand::{seq::SliceRandom, thread_rng};
 use instant::Duration;
 
@@ -33,10 +53,15 @@ use instant::Duration;
 
 pub const TEXT_COLOR: iced::Color = iced::Color::from_rgb(1.0, 1.0, 1.0);
 pub const DISABLED_TEXT_COLOR: iced::Color = iced::Color::from_rgba(1.0, 1.0, 1.0, 0.2);
+pub const HOVER_TEXT_COLOR: iced::Color = iced::Color::from_rgb(1.0, 0.9, 0.6);
 
 pub const FILL_FRAC_ONE: f32 = 0.67;
 pub const FILL_FRAC_TWO: f32 = 0.53;
 
+const TOOLTIP_BACK_COLOR: vek::Rgba<u8> = vek::Rgba::new(20, 18, 10, 255);
+const TOOLTIP_HOVER_DUR: Duration = Duration::from_millis(500);
+const TOOLTIP_FADE_DUR: Duration = Duration::from_millis(250);
+
 image_ids_ice! {
     struct Imgs {
         <ImageGraphic>
@@ -54,27 +79,24 @@ image_ids_ice! {
         selection: "voxygen.element.ui.generic.frames.selection",
         selection_hover: "voxygen.element.ui.generic.frames.selection_hover",
         selection_press: "voxygen.element.ui.generic.frames.selection_press",
+        tt_edge: "voxygen.element.ui.generic.frames.tooltip.edge",
+        tt_corner: "voxygen.element.ui.generic.frames.tooltip.corner",
     }
 }
 
-// Randomly loaded background images
-const BG_IMGS: [&str; 14] = [
-    "voxygen.background.bg_1",
-    "voxygen.background.bg_2",
-    "voxygen.background.bg_3",
-    "voxygen.background.bg_4",
-    "voxygen.background.bg_5",
-    "voxygen.background.bg_6",
-    "voxygen.background.bg_7",
-    "voxygen.background.bg_8",
-    "voxygen.background.bg_9",
-    "voxygen.background.bg_10",
-    "voxygen.background.bg_11",
-    "voxygen.background.bg_12",
-    "voxygen.background.bg_13",
-    "voxygen.background.bg_14",
-];
+/// Background of most screens, the connecting screen falls back to it
+const BG_MAIN: &str = "voxygen.background.bg_main";
 
+/// Backgrounds the connecting screen randomly picks from, new ones are
+/// picked up by their name
+fn bg_imgs() -> Vec<String> {
+    assets::find("voxygen.background.bg_*")
+        .into_iter()
+        .filter(|id| id != BG_MAIN)
+        .collect()
+}
+
+#[derive(Clone)]
 pub enum Event {
     LoginAttempt {
         username: String,
@@ -82,11 +104,23 @@ pub enum Event {
         server_address: String,
     },
     CancelLoginAttempt,
+    CaptchaAnswer(String),
     ChangeLanguage(LanguageMetadata),
     Quit,
     DeleteServer {
         server_index: usize,
     },
+    SetServerOverrides {
+        server: String,
+        overrides: ServerOverrides,
+    },
+    Import(Import),
+    SetUiScale(ui::ScaleMode),
+    FinishOnboarding {
+        telemetry_opt_in: bool,
+    },
+    SetTelemetry(bool),
+    CopyCrashReport(String),
 }
 
 pub struct LoginInfo {
@@ -97,6 +131,14 @@ pub struct LoginInfo {
 
 enum ConnectionState {
     InProgress,
+    /// The server wants the text of the captcha in `Controls::captcha_img`
+    Captcha {
+        answer: String,
+        input: text_input::State,
+        submit_button: button::State,
+    },
+    /// Solving the proof of work the server asked for
+    ProofOfWork,
 }
 
 enum Screen {
@@ -109,12 +151,16 @@ enum Screen {
     },
     Login {
         screen: Box<login::Screen>, // boxed to avoid large variant
-        // Error to display in a box
-        error: Option<String>,
     },
     Servers {
         screen: servers::Screen,
     },
+    Onboarding {
+        screen: Box<onboarding::Screen>,
+    },
+    Telemetry {
+        screen: telemetry::Screen,
+    },
     Connecting {
         screen: connecting::Screen,
         connection_state: ConnectionState,
@@ -125,6 +171,13 @@ struct Controls {
     fonts: Fonts,
     imgs: Imgs,
     bg_img: widget::image::Handle,
+    // Decoded into `bg_img` only while the connecting screen shows it
+    bg_asset: Option<WeakAssetHandle<assets::Image>>,
+    bg_img_shown: bool,
+    // Blank until the server sends a captcha
+    captcha_img: widget::image::Handle,
+    // Shown by the next connecting screen, its frames are loaded ahead
+    loading_animation: Option<connecting::LoadingAnimationInfo>,
     i18n: LocalizationHandle,
     // Voxygen version
     version: String,
@@ -137,10 +190,14 @@ struct Controls {
 
     is_selecting_language: bool,
     selected_language_index: Option<usize>,
+    languages: LanguageDetails,
 
     time: f64,
 
     screen: Screen,
+    // Dialog shown above the screen
+    modal: Option<modal::Modal>,
+    tooltip_manager: TooltipManager,
 }
 
 #[derive(Clone)]
@@ -149,17 +206,31 @@ enum Message {
     Back,
     ShowServers,
     ShowCredits,
+    ShowTelemetry,
     Multiplayer,
     LanguageChanged(usize),
     OpenLanguageMenu,
     Username(String),
     Password(String),
     Server(String),
+    Port(String),
     ServerChanged(usize),
     FocusPassword,
     CancelConnect,
-    CloseError,
+    CaptchaText(String),
+    SubmitCaptcha,
     DeleteServer,
+    OverrideCharacter(String),
+    OverrideLanguage(usize),
+    OverrideViewDistance(String),
+    OverrideAudio,
+    OnboardingNext,
+    OnboardingBack,
+    OnboardingUiScale(usize),
+    OpenAccountPage,
+    ToggleTelemetry,
+    ConfirmModal,
+    CloseModal,
     /* Note: Keeping in case we re-add the disclaimer
      *AcceptDisclaimer, */
 }
@@ -169,6 +240,8 @@ impl Controls {
         fonts: Fonts,
         imgs: Imgs,
         bg_img: widget::image::Handle,
+        captcha_img: widget::image::Handle,
+        language_icons: HashMap<String, widget::image::Handle>,
         i18n: LocalizationHandle,
         settings: &Settings,
     ) -> Self {
@@ -180,9 +253,15 @@ impl Controls {
 
         log::info!("MainUI Controls new: Screen::Login");
 
-        let screen = Screen::Login {
-            screen: Box::new(login::Screen::new()),
-            error: None,
+        let language_metadatas = i18n::list_localizations();
+        let screen = if settings.first_run {
+            Screen::Onboarding {
+                screen: Box::new(onboarding::Screen::new(&language_metadatas)),
+            }
+        } else {
+            Screen::Login {
+                screen: Box::new(login::Screen::new()),
+            }
         };
 
         log::info!("MainUI Controls new: LoginInfo");
@@ -199,7 +278,6 @@ impl Controls {
             .iter()
             .position(|f| f == &login_info.server);
 
-        let language_metadatas = i18n::list_localizations();
         let selected_language_index = language_metadatas
             .iter()
             .position(|f| f.language_identifier == settings.language.selected_language);
@@ -210,6 +288,10 @@ impl Controls {
             fonts,
             imgs,
             bg_img,
+            bg_asset: None,
+            bg_img_shown: false,
+            captcha_img,
+            loading_animation: connecting::choose_loading_animation(),
             i18n,
             version,
             alpha,
@@ -220,27 +302,51 @@ impl Controls {
 
             is_selecting_language: false,
             selected_language_index,
+            languages: LanguageDetails {
+                icons: language_icons,
+                completeness: HashMap::new(),
+            },
 
             time: 0.0,
 
             screen,
+            modal: None,
+            tooltip_manager: TooltipManager::new(TOOLTIP_HOVER_DUR, TOOLTIP_FADE_DUR),
         }
     }
 
     fn view(
         &mut self,
         settings: &Settings,
+        telemetry: &Telemetry,
         key_layout: &Option<KeyLayout>,
         dt: f32,
     ) -> Element<Message> {
         self.time += dt as f64;
+        self.tooltip_manager.maintain();
 
         // TODO: consider setting this as the default in the renderer
         let button_style = style::button::Style::new(self.imgs.button)
             .hover_image(self.imgs.button_hover)
             .press_image(self.imgs.button_press)
             .text_color(TEXT_COLOR)
-            .disabled_text_color(DISABLED_TEXT_COLOR);
+            .disabled_text_color(DISABLED_TEXT_COLOR)
+            .disabled_grayscale()
+            .text(ButtonState::Hover, |text| text.color(HOVER_TEXT_COLOR));
+
+        let tooltips = Tooltips {
+            manager: &self.tooltip_manager,
+            style: tooltip::Style {
+                container: style::container::Style::color_with_image_border(
+                    TOOLTIP_BACK_COLOR,
+                    self.imgs.tt_corner,
+                    self.imgs.tt_edge,
+                ),
+                text_color: TEXT_COLOR,
+                text_size: self.fonts.cyri.scale(17),
+                padding: 10,
+            },
+        };
 
         let alpha = Text::new(&self.alpha)
             .size(self.fonts.cyri.scale(12))
@@ -271,6 +377,7 @@ impl Controls {
         };
 
         let language_metadatas = i18n::list_localizations();
+        self.languages.update(&language_metadatas);
 
         // TODO: make any large text blocks scrollable so that if the area is to
         // small they can still be read
@@ -280,23 +387,50 @@ impl Controls {
             Screen::Credits { screen } => {
                 screen.view(&self.fonts, &self.i18n.read(), &self.credits, button_style)
             },
-            Screen::Login { screen, error } => screen.view(
+            Screen::Login { screen } => screen.view(
                 &self.fonts,
                 &self.imgs,
                 &self.login_info,
-                error.as_deref(),
                 &self.i18n.read(),
                 self.is_selecting_language,
                 self.selected_language_index,
                 &language_metadatas,
+                &self.languages,
                 button_style,
+                tooltips,
                 &self.version,
             ),
-            Screen::Servers { screen } => screen.view(
+            Screen::Servers { screen } => {
+                let selected_server = self
+                    .selected_server_index
+                    .and_then(|i| settings.networking.servers.get(i));
+                let overrides = selected_server
+                    .and_then(|server| settings.networking.server_overrides.get(server));
+                screen.view(
+                    &self.fonts,
+                    &self.imgs,
+                    &settings.networking.servers,
+                    self.selected_server_index,
+                    overrides,
+                    &settings.networking.server_pings,
+                    &language_metadatas,
+                    &self.i18n.read(),
+                    button_style,
+                    tooltips,
+                )
+            },
+            Screen::Onboarding { screen } => screen.view(
                 &self.fonts,
                 &self.imgs,
-                &settings.networking.servers,
-                self.selected_server_index,
+                self.selected_language_index,
+                &language_metadatas,
+                &self.i18n.read(),
+                button_style,
+            ),
+            Screen::Telemetry { screen } => screen.view(
+                &self.fonts,
+                settings.telemetry_opt_in,
+                telemetry,
                 &self.i18n.read(),
                 button_style,
             ),
@@ -307,6 +441,7 @@ impl Controls {
                 &self.fonts,
                 &self.imgs,
                 connection_state,
+                self.captcha_img,
                 self.time,
                 &self.i18n.read(),
                 button_style,
@@ -316,20 +451,30 @@ impl Controls {
             ),
         };
 
-        Container::new(
-            Column::with_children(vec![top_text.into(), content])
-                .spacing(3)
-                .width(Length::Fill)
-                .height(Length::Fill),
+        let dialog = self
+            .modal
+            .as_mut()
+            .map(|modal| modal.view(&self.fonts, &self.i18n.read(), button_style));
+
+        widget::Modal::new(
+            Container::new(
+                Column::with_children(vec![top_text.into(), content])
+                    .spacing(3)
+                    .width(Length::Fill)
+                    .height(Length::Fill),
+            )
+            .style(style::container::Style::image(bg_img)),
         )
-        .style(style::container::Style::image(bg_img))
+        .dialog(dialog)
+        .on_confirm(Message::ConfirmModal)
+        .on_cancel(Message::CloseModal)
         .into()
     }
 
     fn update(
         &mut self,
         message: Message,
-        events: &mut Vec<Event>,
+        events: &mut EventBus<Event>,
         settings: &Settings,
         ui: &mut Ui,
     ) {
@@ -337,11 +482,10 @@ impl Controls {
         let mut language_metadatas = i18n::list_localizations();
 
         match message {
-            Message::Quit => events.push(Event::Quit),
+            Message::Quit => self.modal = Some(Modal::new(ModalKind::Quit)),
             Message::Back => {
                 self.screen = Screen::Login {
                     screen: Box::new(login::Screen::new()),
-                    error: None,
                 };
             },
             Message::ShowServers => {
@@ -349,7 +493,10 @@ impl Controls {
                     self.selected_server_index =
                         servers.iter().position(|f| f == &self.login_info.server);
                     self.screen = Screen::Servers {
-                        screen: servers::Screen::new(),
+                        screen: servers::Screen::new(view_distance_text(
+                            settings,
+                            &self.login_info.server,
+                        )),
                     };
                 }
             },
@@ -358,13 +505,32 @@ impl Controls {
                     screen: credits::Screen::new(),
                 };
             },
+            Message::ShowTelemetry => {
+                self.screen = Screen::Telemetry {
+                    screen: telemetry::Screen::new(),
+                };
+            },
             Message::Multiplayer => {
+                let (_, port) = login::split_port(&self.login_info.server);
+                if !port.is_empty() && !matches!(port.parse::<u16>(), Ok(port) if port != 0) {
+                    self.modal = Some(Modal::new(ModalKind::Error(
+                        self.i18n
+                            .read()
+                            .get("main.login.invalid_port")
+                            .replace("{port}", port),
+                    )));
+                    return;
+                }
+                let animation = std::mem::replace(
+                    &mut self.loading_animation,
+                    connecting::choose_loading_animation(),
+                );
                 self.screen = Screen::Connecting {
-                    screen: connecting::Screen::new(ui),
+                    screen: connecting::Screen::new(animation.as_ref(), ui),
                     connection_state: ConnectionState::InProgress,
                 };
 
-                events.push(Event::LoginAttempt {
+                events.publish(Event::LoginAttempt {
                     username: self.login_info.username.trim().to_string(),
                     password: self.login_info.password.clone(),
                     server_address: self.login_info.server.clone(),
@@ -372,16 +538,25 @@ impl Controls {
             },
             Message::Username(new_value) => self.login_info.username = new_value,
             Message::LanguageChanged(new_value) => {
-                events.push(Event::ChangeLanguage(language_metadatas.remove(new_value)));
+                events.publish(Event::ChangeLanguage(language_metadatas.remove(new_value)));
             },
             Message::OpenLanguageMenu => self.is_selecting_language = !self.is_selecting_language,
             Message::Password(new_value) => self.login_info.password = new_value,
             Message::Server(new_value) => {
-                self.login_info.server = new_value;
+                let (_, port) = login::split_port(&self.login_info.server);
+                self.login_info.server = login::join_port(&new_value, port);
+            },
+            Message::Port(new_value) => {
+                let (host, _) = login::split_port(&self.login_info.server);
+                self.login_info.server = login::join_port(host, &new_value);
             },
             Message::ServerChanged(new_value) => {
                 self.selected_server_index = Some(new_value);
                 self.login_info.server = servers[new_value].clone();
+                if let Screen::Servers { screen } = &mut self.screen {
+                    screen.overrides.view_distance =
+                        view_distance_text(settings, &self.login_info.server);
+                }
             },
             Message::FocusPassword => {
                 if let Screen::Login { screen, .. } = &mut self.screen {
@@ -391,45 +566,204 @@ impl Controls {
             },
             Message::CancelConnect => {
                 self.exit_connect_screen();
-                events.push(Event::CancelLoginAttempt);
+                events.publish_with(Event::CancelLoginAttempt, Priority::High);
             },
-            Message::CloseError => {
-                if let Screen::Login { error, .. } = &mut self.screen {
-                    *error = None;
+            Message::CaptchaText(text) => {
+                if let Screen::Connecting {
+                    connection_state: ConnectionState::Captcha { answer, .. },
+                    ..
+                } = &mut self.screen
+                {
+                    *answer = text;
+                }
+            },
+            Message::SubmitCaptcha => {
+                if let Screen::Connecting {
+                    connection_state: state @ ConnectionState::Captcha { .. },
+                    ..
+                } = &mut self.screen
+                {
+                    if let ConnectionState::Captcha { answer, .. } =
+                        std::mem::replace(state, ConnectionState::InProgress)
+                    {
+                        events.publish(Event::CaptchaAnswer(answer));
+                    }
                 }
             },
             Message::DeleteServer => {
                 if let Some(server_index) = self.selected_server_index {
-                    events.push(Event::DeleteServer { server_index });
+                    self.modal = Some(Modal::new(ModalKind::DeleteServer {
+                        server_index,
+                        server: servers[server_index].clone(),
+                    }));
+                }
+            },
+            Message::OverrideCharacter(_)
+            | Message::OverrideLanguage(_)
+            | Message::OverrideViewDistance(_)
+            | Message::OverrideAudio => {
+                let server = match self.selected_server_index.and_then(|i| servers.get(i)) {
+                    Some(server) => server.clone(),
+                    None => return,
+                };
+                let mut overrides = settings
+                    .networking
+                    .server_overrides
+                    .get(&server)
+                    .cloned()
+                    .unwrap_or_default();
+                match message {
+                    Message::OverrideCharacter(alias) => {
+                        overrides.character = (!alias.is_empty()).then(|| alias);
+                    },
+                    // The first option keeps the regular language
+                    Message::OverrideLanguage(index) => {
+                        overrides.language = index
+                            .checked_sub(1)
+                            .map(|i| language_metadatas.remove(i).language_identifier);
+                    },
+                    Message::OverrideViewDistance(text) => {
+                        if let Screen::Servers { screen } = &mut self.screen {
+                            screen.overrides.view_distance = text.clone();
+                        }
+                        let (min, max) = VIEW_DISTANCE_RANGE;
+                        overrides.view_distance =
+                            text.trim().parse().ok().map(|vd: u32| vd.clamp(min, max));
+                    },
+                    Message::OverrideAudio => {
+                        overrides.audio = match overrides.audio {
+                            Some(_) => None,
+                            None => Some(settings.audio.profile()),
+                        };
+                    },
+                    _ => unreachable!(),
+                }
+                events.publish(Event::SetServerOverrides { server, overrides });
+            },
+            Message::OnboardingNext => {
+                if let Screen::Onboarding { screen } = &mut self.screen {
+                    match screen.step.next() {
+                        Some(step) => screen.step = step,
+                        None => {
+                            events.publish(Event::FinishOnboarding {
+                                telemetry_opt_in: screen.telemetry,
+                            });
+                            self.screen = Screen::Login {
+                                screen: Box::new(login::Screen::new()),
+                            };
+                        },
+                    }
+                }
+            },
+            Message::OnboardingBack => {
+                if let Screen::Onboarding { screen } = &mut self.screen {
+                    if let Some(step) = screen.step.previous() {
+                        screen.step = step;
+                    }
                 }
             },
+            Message::OnboardingUiScale(index) => {
+                if let Screen::Onboarding { screen } = &mut self.screen {
+                    screen.ui_scale = Some(index);
+                }
+                events.publish(Event::SetUiScale(onboarding::ui_scale_mode(index)));
+            },
+            Message::OpenAccountPage => onboarding::open_url(onboarding::ACCOUNT_URL),
+            Message::ToggleTelemetry => match &mut self.screen {
+                // Only applied once the onboarding is finished
+                Screen::Onboarding { screen } => screen.telemetry = !screen.telemetry,
+                _ => events.publish(Event::SetTelemetry(!settings.telemetry_opt_in)),
+            },
+            Message::ConfirmModal => match self.modal.take().map(|modal| modal.kind) {
+                Some(ModalKind::DeleteServer { server_index, .. }) => {
+                    events.publish(Event::DeleteServer { server_index })
+                },
+                Some(ModalKind::Quit) => events.publish_with(Event::Quit, Priority::High),
+                Some(ModalKind::Import { import, .. }) => events.publish(Event::Import(import)),
+                Some(ModalKind::CrashReport(report)) => {
+                    events.publish(Event::CopyCrashReport(report))
+                },
+                Some(ModalKind::Error(_)) | None => {},
+            },
+            Message::CloseModal => self.modal = None,
         }
     }
 
+    /// Name of the current screen, e.g. for telemetry
+    fn screen_name(&self) -> &'static str {
+        match &self.screen {
+            Screen::Credits { .. } => "credits",
+            Screen::Login { .. } => "login",
+            Screen::Servers { .. } => "servers",
+            Screen::Onboarding { .. } => "onboarding",
+            Screen::Telemetry { .. } => "telemetry",
+            Screen::Connecting { .. } => "connecting",
+        }
+    }
+
+    /// Whether the view changes over time, not only on input
+    fn is_animated(&self) -> bool {
+        matches!(&self.screen, Screen::Connecting { .. }) || self.tooltip_manager.is_active()
+    }
+
     // Connection successful of failed
     fn exit_connect_screen(&mut self) {
         if matches!(&self.screen, Screen::Connecting { .. }) {
             self.screen = Screen::Login {
                 screen: Box::new(login::Screen::new()),
-                error: None,
             }
         }
     }
 
-    fn connection_error(&mut self, error: String) {
-        if matches!(&self.screen, Screen::Connecting { .. })
-            || matches!(&self.screen, Screen::Login { .. })
+    fn show_challenge(&mut self, challenge: &LoginChallenge) {
+        if let Screen::Connecting {
+            connection_state, ..
+        } = &mut self.screen
         {
+            *connection_state = match challenge {
+                LoginChallenge::Captcha { .. } => ConnectionState::Captcha {
+                    answer: String::new(),
+                    input: text_input::State::focused(),
+                    submit_button: Default::default(),
+                },
+                LoginChallenge::ProofOfWork { .. } => ConnectionState::ProofOfWork,
+            };
+        }
+    }
+
+    fn connection_error(&mut self, error: String) {
+        if matches!(&self.screen, Screen::Connecting { .. }) {
             self.screen = Screen::Login {
                 screen: Box::new(login::Screen::new()),
-                error: Some(error),
-            }
-        } else {
-            log::warn!("connection_error invoked on unhandled screen!");
+            };
         }
+        self.modal = Some(Modal::new(ModalKind::Error(error)));
+    }
+
+    /// Ask before importing a file dropped onto the window
+    fn file_dropped(&mut self, path: PathBuf) {
+        let file = path.file_name().map_or_else(
+            || path.display().to_string(),
+            |name| name.to_string_lossy().into_owned(),
+        );
+        let kind = match import::inspect(&path) {
+            Ok(import) => ModalKind::Import { file, import },
+            Err(e) => ModalKind::Error(
+                self.i18n
+                    .read()
+                    .get("main.import.failed")
+                    .replace("{file}", &file)
+                    .replace("{error}", &e.to_string()),
+            ),
+        };
+        self.modal = Some(Modal::new(kind));
     }
 
     fn tab(&mut self) {
+        // Focus stays in the dialog
+        if self.modal.is_some() {
+            return;
+        }
         if let Screen::Login { screen, .. } = &mut self.screen {
             // TODO: add select all function in iced
             if screen.banner.username.is_focused() {
@@ -449,17 +783,144 @@ impl Controls {
     }
 }
 
+/// Text of the view distance override input of the servers screen
+fn view_distance_text(settings: &Settings, server: &str) -> String {
+    settings
+        .networking
+        .server_overrides
+        .get(server)
+        .and_then(|overrides| overrides.view_distance)
+        .map_or_else(String::new, |view_distance| view_distance.to_string())
+}
+
+/// The icons of the languages by language identifier
+fn load_language_icons(ui: &mut Ui) -> HashMap<String, widget::image::Handle> {
+    i18n::list_localizations()
+        .into_iter()
+        .filter_map(|language| {
+            let icon = language.icon?;
+            match ImageGraphic::new_graphic(&icon) {
+                Ok(graphic) => Some((language.language_identifier, ui.add_graphic(graphic))),
+                Err(e) => {
+                    log::warn!("Failed to load language icon {}: {:?}", icon, e);
+                    None
+                },
+            }
+        })
+        .collect()
+}
+
+/// What the language dropdowns show next to the names
+struct LanguageDetails {
+    /// Icons by language identifier
+    icons: HashMap<String, widget::image::Handle>,
+    /// Share of translated entries by language identifier, computing it loads
+    /// the whole language so it's done once
+    completeness: HashMap<String, f32>,
+}
+
+impl LanguageDetails {
+    /// Computes the completeness of languages which weren't listed before
+    fn update(&mut self, languages: &[LanguageMetadata]) {
+        for language in languages {
+            if !self.completeness.contains_key(&language.language_identifier) {
+                let completeness = i18n::completeness(&language.language_identifier);
+                self.completeness
+                    .insert(language.language_identifier.clone(), completeness);
+            }
+        }
+    }
+
+    fn icon(&self, language: &LanguageMetadata) -> Option<widget::image::Handle> {
+        self.icons.get(&language.language_identifier).copied()
+    }
+
+    /// Share of translated entries in percent
+    fn percent(&self, language: &LanguageMetadata) -> f32 {
+        let completeness = self.completeness.get(&language.language_identifier);
+        (completeness.copied().unwrap_or_default() * 100.0).round()
+    }
+}
+
+/// What the screens need to attach tooltips to their widgets
+#[derive(Clone, Copy)]
+struct Tooltips<'a> {
+    manager: &'a TooltipManager,
+    style: tooltip::Style,
+}
+
+impl<'a> Tooltips<'a> {
+    /// Shows `text` when `content` is hovered
+    fn text(self, content: impl Into<Element<'a, Message>>, text: String) -> Element<'a, Message> {
+        self.rich(content, text, Vec::new)
+    }
+
+    /// Shows `title` above the lines returned by `details` when `content` is
+    /// hovered, `details` is only called while the tooltip is shown
+    fn rich(
+        self,
+        content: impl Into<Element<'a, Message>>,
+        title: String,
+        details: impl 'a + Fn() -> Vec<String>,
+    ) -> Element<'a, Message> {
+        content
+            .with_tooltip(self.manager, move || self.content(title.clone(), &details()))
+            .into()
+    }
+
+    /// The tooltip itself, for widgets which show it on their own
+    fn content(self, title: String, details: &[String]) -> Element<'a, Message> {
+        tooltip::rich(title, details, self.style)
+    }
+}
+
+/// Style of the server and language dropdowns and their options
+fn list_entry_style(imgs: &Imgs, selected: bool, alpha: u8) -> style::button::Style {
+    let color = if selected {
+        vek::Rgba::new(97, 255, 18, alpha)
+    } else {
+        vek::Rgba::new(97, 97, 25, alpha)
+    };
+    style::button::Style::new(imgs.selection)
+        .hover_image(imgs.selection_hover)
+        .press_image(imgs.selection_press)
+        .image_color(color)
+        .text(ButtonState::Hover, |text| text.color(HOVER_TEXT_COLOR))
+        .text(ButtonState::Press, |text| text.size_delta(-1))
+}
+
 pub struct MainMenuUi {
     ui: Ui,
     // TODO: re add this
     // tip_no: u16,
     controls: Controls,
+    // Time not yet passed on to `Controls::view`
+    pending_dt: Duration,
+    // Whether the ui changed since it was last drawn
+    redraw: Cell<bool>,
+    // The background shown while connecting, blank until it is decoded
+    pending_bg_img: Option<AssetFuture<assets::Image>>,
+    // Assets of the preload manifest, `None` once all finished
+    preload: Option<PreloadProgress>,
+    // Published by the ui and the play state, drained by the play state
+    events: EventBus<Event>,
 }
 
 impl MainMenuUi {
     pub fn new(global_state: &mut GlobalState) -> Self {
 
         log::info!("MainMenuUi new start");
+        // Load images in the background while fonts and the ui are set up
+        let bg_img_spec = bg_imgs()
+            .choose(&mut thread_rng())
+            .cloned()
+            .unwrap_or_else(|| BG_MAIN.to_owned());
+        let bg_img = assets::Image::load_async(&bg_img_spec, LoadPriority::Critical);
+        let preload = assets::preload("voxygen.preload.main_menu")
+            .map_err(|e| log::warn!("Failed to load the main menu preload manifest: {:?}", e))
+            .ok();
+        Credits::load_async("common.credits", LoadPriority::Prefetch);
+
         // Load language
         let i18n = &global_state.i18n.read();
         // TODO: don't add default font twice
@@ -474,24 +935,87 @@ impl MainMenuUi {
         .unwrap();
 
         log::info!("MainMenuUi LoadFont start");
-        let fonts = Fonts::load(i18n.fonts(), &mut ui).expect("Impossible to load fonts");
-
-        log::info!("MainMenuUi bg_img_spec start");
-        let bg_img_spec = BG_IMGS.choose(&mut thread_rng()).unwrap();
+        let fonts = Fonts::load(&i18n, &mut ui).expect("Impossible to load fonts");
 
-        log::info!("MainMenuUi bg_img start");
-        let bg_img = assets::Image::load_expect(bg_img_spec).read().to_image();
-        let controls = Controls::new(
+        let imgs = Imgs::load(&mut ui).expect("Failed to load images");
+        ui.pack_graphics(global_state.window.renderer_mut(), &imgs.all());
+        let language_icons = load_language_icons(&mut ui);
+        let mut controls = Controls::new(
             fonts,
-            Imgs::load(&mut ui).expect("Failed to load images"),
-            ui.add_graphic(Graphic::Image(bg_img, None)),
+            imgs,
+            ui.add_graphic(Graphic::Blank),
+            ui.add_graphic(Graphic::Blank),
+            language_icons,
             global_state.i18n,
             &global_state.settings,
         );
+        // Offer the report if the game crashed last time
+        if let Some(report) = crash_report::take() {
+            controls.modal = Some(Modal::new(ModalKind::CrashReport(report)));
+        }
 
         log::info!("MainMenuUi New End");
 
-        Self { ui, controls }
+        Self {
+            ui,
+            controls,
+            pending_dt: Duration::ZERO,
+            redraw: Cell::new(true),
+            pending_bg_img: Some(bg_img),
+            preload,
+            events: EventBus::default(),
+        }
+    }
+
+    /// Reports how the preloaded assets loaded once all finished
+    fn poll_preload(&mut self) {
+        if let Some(preload) = &mut self.preload {
+            preload.update();
+            if preload.is_done() {
+                log::debug!(
+                    "Preloaded {} main menu assets, {} failed",
+                    preload.total(),
+                    preload.errors().len()
+                );
+                log::debug!("Asset loads so far:\n{}", assets::load_profile());
+                self.preload = None;
+            }
+        }
+    }
+
+    /// Remember the background once the loader finished it
+    fn poll_bg_img(&mut self) {
+        let result = match self.pending_bg_img.as_mut().and_then(AssetFuture::try_take) {
+            Some(result) => result,
+            None => return,
+        };
+        let pending = self.pending_bg_img.take().unwrap();
+        let bg_img =
+            result.unwrap_or_else(|_| assets::Image::load_or_placeholder(pending.specifier()));
+        self.controls.bg_asset = Some(WeakAssetHandle::new(&bg_img));
+    }
+
+    /// Only keep the decoded background in the ui while a screen shows it
+    fn sync_bg_img(&mut self) {
+        let shown = matches!(&self.controls.screen, Screen::Connecting { .. });
+        if shown == self.controls.bg_img_shown {
+            return;
+        }
+        let graphic = if shown {
+            match self.controls.bg_asset.as_ref().map(WeakAssetHandle::upgrade_or_load) {
+                Some(Ok(bg_img)) => Graphic::Image(bg_img.read().to_image(), None),
+                Some(Err(e)) => {
+                    log::warn!("Failed to reload the background: {:?}", e);
+                    return;
+                },
+                // Still loading
+                None => return,
+            }
+        } else {
+            Graphic::Blank
+        };
+        self.ui.replace_graphic(self.controls.bg_img, graphic);
+        self.controls.bg_img_shown = shown;
     }
 
     pub fn update_language(&mut self, i18n: LocalizationHandle, settings: &Settings) {
@@ -500,18 +1024,43 @@ impl MainMenuUi {
         let font = load_font(&i18n.fonts().get("cyri").unwrap().asset_key);
         self.ui.clear_fonts(font);
         self.controls.fonts =
-            Fonts::load(i18n.fonts(), &mut self.ui).expect("Impossible to load fonts!");
+            Fonts::load(&i18n, &mut self.ui).expect("Impossible to load fonts!");
         let language_metadatas = i18n::list_localizations();
         self.controls.selected_language_index = language_metadatas
             .iter()
             .position(|f| f.language_identifier == settings.language.selected_language);
+        self.ui.request_redraw();
+    }
+
+    pub fn show_info(&mut self, msg: String) {
+        self.controls.connection_error(msg);
+        self.ui.request_redraw();
     }
 
-    pub fn show_info(&mut self, msg: String) { self.controls.connection_error(msg); }
+    /// Show the challenge of the server on the connecting screen
+    pub fn show_challenge(&mut self, challenge: LoginChallenge) {
+        if let LoginChallenge::Captcha { image: png } = &challenge {
+            match image::load_from_memory_with_format(png, image::ImageFormat::Png) {
+                Ok(image) => self.ui.replace_graphic(
+                    self.controls.captcha_img,
+                    Graphic::Image(Arc::new(image), None),
+                ),
+                Err(e) => log::warn!("{:?} Failed to decode the captcha of the server", e),
+            }
+        }
+        self.controls.show_challenge(&challenge);
+        self.ui.request_redraw();
+    }
 
-    pub fn connected(&mut self) { self.controls.exit_connect_screen(); }
+    pub fn connected(&mut self) {
+        self.controls.exit_connect_screen();
+        self.ui.request_redraw();
+    }
 
-    pub fn cancel_connection(&mut self) { self.controls.exit_connect_screen(); }
+    pub fn cancel_connection(&mut self) {
+        self.controls.exit_connect_screen();
+        self.ui.request_redraw();
+    }
 
     pub fn handle_event(&mut self, event: window::Event) -> bool {
         match event {
@@ -524,6 +1073,11 @@ impl MainMenuUi {
                 self.ui.scale_factor_changed(s);
                 false
             },
+            window::Event::FileDropped(path) => {
+                self.controls.file_dropped(path);
+                self.ui.request_redraw();
+                true
+            },
             _ => false,
         }
     }
@@ -544,16 +1098,37 @@ impl MainMenuUi {
         self.ui.handle_event(event);
     }
 
+    pub fn toggle_debug_bounds(&mut self) { self.ui.toggle_debug_bounds(); }
+
     pub fn set_scale_mode(&mut self, scale_mode: ui::ScaleMode) {
         self.ui.set_scaling_mode(scale_mode);
     }
 
-    pub fn maintain(&mut self, global_state: &mut GlobalState, dt: Duration) -> Vec<Event> {
-        let mut events = Vec::new();
+    /// Events of the ui and the ones published for it, see [`Self::events`]
+    pub fn events(&mut self) -> &mut EventBus<Event> { &mut self.events }
+
+    pub fn maintain(&mut self, global_state: &mut GlobalState, dt: Duration) {
+        self.poll_preload();
+        self.poll_bg_img();
+        self.sync_bg_img();
+
+        // Only rebuild the ui when something changed, animations are limited to
+        // their own frame rate
+        self.pending_dt += dt;
+        let animation_frame = Duration::from_secs_f32(
+            1.0 / get_fps(global_state.settings.graphics.max_menu_animation_fps).max(1) as f32,
+        );
+        let animation_due = self.controls.is_animated() && self.pending_dt >= animation_frame;
+        if !animation_due && !self.ui.needs_maintain() {
+            return;
+        }
+        let dt = std::mem::take(&mut self.pending_dt);
+        self.redraw.set(true);
 
         let (messages, _) = self.ui.maintain(
             self.controls.view(
                 &global_state.settings,
+                &global_state.telemetry,
                 &global_state.window.key_layout,
                 dt.as_secs_f32(),
             ),
@@ -564,11 +1139,25 @@ impl MainMenuUi {
 
         messages.into_iter().for_each(|message| {
             self.controls
-                .update(message, &mut events, &global_state.settings, &mut self.ui)
+                .update(message, &mut self.events, &global_state.settings, &mut self.ui)
         });
+    }
 
-        events
+    pub fn screen_name(&self) -> &'static str { self.controls.screen_name() }
+
+    pub fn needs_redraw(&self) -> bool { self.redraw.get() }
+
+    pub fn render<'a>(&'a self, drawer: &mut ThirdPassDrawer<'a>) {
+        self.redraw.set(false);
+        self.ui.render(drawer);
     }
 
-    pub fn render<'a>(&'a self, drawer: &mut ThirdPassDrawer<'a>) { self.ui.render(drawer); }
+    pub fn capture_frame(
+        &self,
+        renderer: &mut Renderer,
+        globals: &GlobalsBindGroup,
+        on_capture: OnCapture,
+    ) {
+        self.ui.capture_frame(renderer, globals, on_capture);
+    }
 }
@@ -0,0 +1,131 @@
+use super::{super::import::Import, Message, FILL_FRAC_ONE};
+use crate::ui::{
+    fonts::IcedFonts as Fonts,
+    ice::{component::neat_button, style, Element},
+};
+use i18n::Localization;
+use iced::widget::{button, Column, Container, Row, Text};
+use iced::Length;
+
+pub enum ModalKind {
+    DeleteServer { server_index: usize, server: String },
+    /// Connection errors and other messages
+    Error(String),
+    Quit,
+    /// A file dropped onto the window
+    Import { file: String, import: Import },
+    /// Report written when the game crashed last time
+    CrashReport(String),
+}
+
+/// Dialog shown above the current screen, see `Controls::modal`
+pub struct Modal {
+    pub kind: ModalKind,
+    confirm_button: button::State,
+    cancel_button: button::State,
+}
+
+impl Modal {
+    pub fn new(kind: ModalKind) -> Self {
+        Self {
+            kind,
+            confirm_button: Default::default(),
+            cancel_button: Default::default(),
+        }
+    }
+
+    pub(super) fn view(
+        &mut self,
+        fonts: &Fonts,
+        i18n: &Localization,
+        button_style: style::button::Style,
+    ) -> Element<Message> {
+        let (text, confirm_label) = match &self.kind {
+            ModalKind::DeleteServer { server, .. } => (
+                i18n.get("main.servers.delete_server_prompt")
+                    .replace("{server}", server),
+                Some(i18n.get("common.delete_server")),
+            ),
+            // Only informs, so there is nothing to confirm
+            ModalKind::Error(error) => (error.clone(), None),
+            ModalKind::Quit => (
+                i18n.get("main.login.quit_prompt").to_owned(),
+                Some(i18n.get("common.quit")),
+            ),
+            ModalKind::Import {
+                file,
+                import: Import::Servers(servers),
+            } => (
+                i18n.get("main.import.servers_prompt")
+                    .replace("{count}", &servers.len().to_string())
+                    .replace("{file}", file),
+                Some(i18n.get("common.confirm")),
+            ),
+            ModalKind::Import {
+                file,
+                import: Import::TranslationPack { language, .. },
+            } => (
+                i18n.get("main.import.translation_prompt")
+                    .replace("{language}", language)
+                    .replace("{file}", file),
+                Some(i18n.get("common.confirm")),
+            ),
+            ModalKind::CrashReport(_) => (
+                i18n.get("main.crash_report.prompt").to_owned(),
+                Some(i18n.get("main.crash_report.copy")),
+            ),
+        };
+
+        let buttons = match confirm_label {
+            Some(confirm_label) => vec![
+                neat_button(
+                    &mut self.cancel_button,
+                    i18n.get("common.cancel"),
+                    FILL_FRAC_ONE,
+                    button_style,
+                    Some(Message::CloseModal),
+                ),
+                neat_button(
+                    &mut self.confirm_button,
+                    confirm_label,
+                    FILL_FRAC_ONE,
+                    button_style,
+                    Some(Message::ConfirmModal),
+                ),
+            ],
+            None => vec![neat_button(
+                &mut self.confirm_button,
+                i18n.get("common.okay"),
+                FILL_FRAC_ONE,
+                button_style,
+                Some(Message::CloseModal),
+            )],
+        };
+
+        Container::new(
+            Column::with_children(vec![
+                Container::new(Text::new(text).size(fonts.cyri.scale(20)))
+                    .height(Length::Fill)
+                    .into(),
+                Container::new(Row::with_children(buttons).spacing(20))
+                    .width(Length::Fill)
+                    .height(Length::Units(30))
+                    .center_x()
+                    .into(),
+            ])
+            .height(Length::Fill)
+            .width(Length::Fill),
+        )
+        .style(
+            style::container::Style::color_with_double_cornerless_border(
+                (22, 18, 16, 255).into(),
+                (11, 11, 11, 255).into(),
+                (54, 46, 38, 255).into(),
+            ),
+        )
+        .width(Length::Units(400))
+        .height(Length::Units(180))
+        .padding(20)
+        .into()
+    }
+}
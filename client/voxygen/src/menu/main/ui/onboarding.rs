@@ -0,0 +1,291 @@
+use super::{list_entry_style, Imgs, Message, FILL_FRAC_ONE};
+use crate::ui::{
+    fonts::IcedFonts as Fonts,
+    ice::{
+        component::neat_button,
+        style,
+        widget::{dropdown, Dropdown},
+        Element,
+    },
+    ScaleMode,
+};
+use hashbrown::HashMap;
+use i18n::{LanguageMetadata, Localization};
+use iced::{Alignment, Horizontal, Length};
+use iced::widget::{button, Column, Container, Row, Space, Text};
+
+pub const ACCOUNT_URL: &str = "https://veloren.net/account/";
+
+/// Ui scales offered on the ui scale step, the first one follows the window
+/// size
+pub const UI_SCALES: [Option<f64>; 5] = [None, Some(0.75), Some(1.0), Some(1.25), Some(1.5)];
+
+pub fn ui_scale_mode(index: usize) -> ScaleMode {
+    match UI_SCALES.get(index).copied().flatten() {
+        Some(scale) => ScaleMode::Absolute(scale),
+        None => ScaleMode::RelativeToWindow([1920.0, 1080.0].into()),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Step {
+    Language,
+    UiScale,
+    Account,
+    Telemetry,
+}
+
+impl Step {
+    pub fn next(self) -> Option<Self> {
+        match self {
+            Step::Language => Some(Step::UiScale),
+            Step::UiScale => Some(Step::Account),
+            Step::Account => Some(Step::Telemetry),
+            Step::Telemetry => None,
+        }
+    }
+
+    pub fn previous(self) -> Option<Self> {
+        match self {
+            Step::Language => None,
+            Step::UiScale => Some(Step::Language),
+            Step::Account => Some(Step::UiScale),
+            Step::Telemetry => Some(Step::Account),
+        }
+    }
+}
+
+/// Screens shown on the first launch instead of the login screen
+pub struct Screen {
+    pub(super) step: Step,
+    pub(super) ui_scale: Option<usize>,
+    pub(super) telemetry: bool,
+    // Share of translated entries by language identifier, the list is reordered
+    // when the active language changes
+    completeness: HashMap<String, f32>,
+
+    language_dropdown: dropdown::State,
+    ui_scale_dropdown: dropdown::State,
+    account_button: button::State,
+    telemetry_button: button::State,
+    back_button: button::State,
+    next_button: button::State,
+}
+
+impl Screen {
+    pub fn new(language_metadatas: &[LanguageMetadata]) -> Self {
+        Self {
+            step: Step::Language,
+            ui_scale: None,
+            telemetry: false,
+            completeness: language_metadatas
+                .iter()
+                .map(|lang| {
+                    let identifier = &lang.language_identifier;
+                    (identifier.clone(), i18n::completeness(identifier))
+                })
+                .collect(),
+
+            language_dropdown: Default::default(),
+            ui_scale_dropdown: Default::default(),
+            account_button: Default::default(),
+            telemetry_button: Default::default(),
+            back_button: Default::default(),
+            next_button: Default::default(),
+        }
+    }
+
+    pub(super) fn view(
+        &mut self,
+        fonts: &Fonts,
+        imgs: &Imgs,
+        selected_language_index: Option<usize>,
+        language_metadatas: &[LanguageMetadata],
+        i18n: &Localization,
+        button_style: style::button::Style,
+    ) -> Element<Message> {
+        let dropdown_style = style::dropdown::Style::new(
+            list_entry_style(imgs, true, 255),
+            list_entry_style(imgs, false, 255),
+        )
+        .selected_option(list_entry_style(imgs, true, 255));
+        let text = |key| {
+            Text::new(i18n.get(key))
+                .size(fonts.cyri.scale(23))
+                .width(Length::Fill)
+                .horizontal_alignment(Horizontal::Center)
+        };
+
+        let (title, content): (_, Element<_>) = match self.step {
+            Step::Language => {
+                let languages = language_metadatas
+                    .iter()
+                    .map(|lang| {
+                        let completeness = self
+                            .completeness
+                            .get(&lang.language_identifier)
+                            .copied()
+                            .unwrap_or_default();
+                        format!("{} ({:.0}%)", lang.display_name(), completeness * 100.0)
+                    })
+                    .collect();
+                let dropdown = Dropdown::new(
+                    &mut self.language_dropdown,
+                    languages,
+                    selected_language_index,
+                    Message::LanguageChanged,
+                )
+                .style(dropdown_style)
+                .row_height(50)
+                .max_rows(6)
+                .text_size(fonts.cyri.scale(30))
+                .font(fonts.cyri.id);
+                ("main.onboarding.language", dropdown.into())
+            },
+            Step::UiScale => {
+                let scales = UI_SCALES
+                    .iter()
+                    .map(|scale| match scale {
+                        Some(scale) => format!("{:.0}%", scale * 100.0),
+                        None => i18n.get("main.onboarding.ui_scale_window").to_owned(),
+                    })
+                    .collect();
+                let dropdown = Dropdown::new(
+                    &mut self.ui_scale_dropdown,
+                    scales,
+                    self.ui_scale,
+                    Message::OnboardingUiScale,
+                )
+                .style(dropdown_style)
+                .row_height(50)
+                .max_rows(6)
+                .text_size(fonts.cyri.scale(30))
+                .font(fonts.cyri.id);
+                let content = Column::with_children(vec![
+                    text("main.onboarding.ui_scale_preview").into(),
+                    dropdown.into(),
+                ])
+                .spacing(10);
+                ("main.onboarding.ui_scale", content.into())
+            },
+            Step::Account => {
+                let content = Column::with_children(vec![
+                    text("main.onboarding.account_info").into(),
+                    Text::new(ACCOUNT_URL)
+                        .size(fonts.cyri.scale(23))
+                        .width(Length::Fill)
+                        .horizontal_alignment(Horizontal::Center)
+                        .into(),
+                    Container::new(neat_button(
+                        &mut self.account_button,
+                        i18n.get("main.onboarding.account_open"),
+                        FILL_FRAC_ONE,
+                        button_style,
+                        Some(Message::OpenAccountPage),
+                    ))
+                    .max_width(250)
+                    .height(Length::Units(fonts.cyri.scale(50)))
+                    .into(),
+                ])
+                .spacing(10)
+                .align_items(Alignment::Center);
+                ("main.onboarding.account", content.into())
+            },
+            Step::Telemetry => {
+                let content = Column::with_children(vec![
+                    text("main.onboarding.telemetry_info").into(),
+                    Container::new(neat_button(
+                        &mut self.telemetry_button,
+                        i18n.get(if self.telemetry {
+                            "main.onboarding.telemetry_on"
+                        } else {
+                            "main.onboarding.telemetry_off"
+                        }),
+                        FILL_FRAC_ONE,
+                        button_style,
+                        Some(Message::ToggleTelemetry),
+                    ))
+                    .max_width(250)
+                    .height(Length::Units(fonts.cyri.scale(50)))
+                    .into(),
+                ])
+                .spacing(10)
+                .align_items(Alignment::Center);
+                ("main.onboarding.telemetry", content.into())
+            },
+        };
+
+        let back_button = neat_button(
+            &mut self.back_button,
+            i18n.get("common.back"),
+            FILL_FRAC_ONE,
+            button_style,
+            self.step.previous().map(|_| Message::OnboardingBack),
+        );
+        let next_button = neat_button(
+            &mut self.next_button,
+            i18n.get(if self.step.next().is_some() {
+                "common.next"
+            } else {
+                "main.onboarding.finish"
+            }),
+            FILL_FRAC_ONE,
+            button_style,
+            Some(Message::OnboardingNext),
+        );
+
+        Container::new(
+            Container::new(
+                Column::with_children(vec![
+                    Text::new(i18n.get(title))
+                        .font(fonts.alkhemi.id)
+                        .size(fonts.alkhemi.scale(35))
+                        .width(Length::Fill)
+                        .horizontal_alignment(Horizontal::Center)
+                        .into(),
+                    Space::new(Length::Fill, Length::Units(20)).into(),
+                    Container::new(content)
+                        .width(Length::Fill)
+                        .height(Length::Fill)
+                        .into(),
+                    Row::with_children(vec![
+                        Container::new(back_button).max_width(200).into(),
+                        Space::new(Length::Fill, Length::Shrink).into(),
+                        Container::new(next_button).max_width(200).into(),
+                    ])
+                    .height(Length::Units(fonts.cyri.scale(50)))
+                    .width(Length::Fill)
+                    .into(),
+                ])
+                .spacing(5)
+                .padding(20)
+                .width(Length::Fill)
+                .height(Length::Fill),
+            )
+            .style(
+                style::container::Style::color_with_double_cornerless_border(
+                    (22, 19, 17, 255).into(),
+                    (11, 11, 11, 255).into(),
+                    (54, 46, 38, 255).into(),
+                ),
+            )
+            .max_width(600),
+        )
+        .center_x()
+        .center_y()
+        .padding(70)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+}
+
+/// Opens `url` in the browser, where the game runs in
+pub fn open_url(url: &str) {
+    #[cfg(target_arch = "wasm32")]
+    if let Err(e) = web_sys::window().map_or(Ok(None), |window| window.open_with_url(url)) {
+        log::warn!("Failed to open {}: {:?}", url, e);
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    log::info!("Open {} in a browser", url);
+}
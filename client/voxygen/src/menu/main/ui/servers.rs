@@ -1,40 +1,52 @@
-use super::{Imgs, Message, FILL_FRAC_ONE};
+use super::{list_entry_style, Imgs, Message, Tooltips, FILL_FRAC_ONE};
 use crate::ui::{
     fonts::IcedFonts as Fonts,
-    ice::{component::neat_button, style, Element},
+    ice::{
+        component::neat_button,
+        style,
+        widget::{dropdown, Dropdown},
+        Element,
+    },
 };
-use i18n::Localization;
+use crate::settings::ServerOverrides;
+use hashbrown::HashMap;
+use i18n::{LanguageMetadata, Localization};
 use iced::{Length, Alignment};
-use iced::widget::{
-    button, scrollable, Button, Column, Container, Row, Scrollable, Space, Text,
-};
+use iced::widget::{button, text_input, Column, Container, Row, Text, TextInput};
+
+const OVERRIDE_TEXT_SIZE: u16 = 20;
 
 pub struct Screen {
     back_button: button::State,
     delete_button: button::State,
-    server_buttons: Vec<button::State>,
-    servers_list: scrollable::State,
+    servers_dropdown: dropdown::State,
+    pub(super) overrides: OverridesPanel,
 }
 
 impl Screen {
-    pub fn new() -> Self {
+    pub fn new(view_distance: String) -> Self {
         Self {
             back_button: Default::default(),
             delete_button: Default::default(),
-            server_buttons: vec![],
-            servers_list: Default::default(),
+            servers_dropdown: Default::default(),
+            overrides: OverridesPanel::new(view_distance),
         }
     }
 
-    pub(super) fn view(
-        &mut self,
+    pub(super) fn view<'a>(
+        &'a mut self,
         fonts: &Fonts,
         imgs: &Imgs,
         servers: &[impl AsRef<str>],
         selected_server_index: Option<usize>,
+        overrides: Option<&ServerOverrides>,
+        // Last measured ping by server address in milliseconds
+        pings: &HashMap<String, u32>,
+        language_metadatas: &[LanguageMetadata],
         i18n: &Localization,
         button_style: style::button::Style,
-    ) -> Element<Message> {
+        tooltips: Tooltips<'a>,
+    ) -> Element<'a, Message> {
         let title = Text::new(i18n.get("main.servers.select_server"))
             .size(fonts.cyri.scale(35))
             .width(Length::Fill)
@@ -54,79 +66,88 @@ impl Screen {
         .align_x(Alignment::Center);
 
         let delete_button = Container::new(
-            Container::new(neat_button(
-                &mut self.delete_button,
-                i18n.get("common.delete_server"),
-                FILL_FRAC_ONE,
-                button_style,
-                Some(Message::DeleteServer),
+            Container::new(tooltips.text(
+                neat_button(
+                    &mut self.delete_button,
+                    i18n.get("common.delete_server"),
+                    FILL_FRAC_ONE,
+                    button_style,
+                    Some(Message::DeleteServer),
+                ),
+                i18n.get("main.servers.delete_server_tooltip").to_owned(),
             ))
             .max_width(200),
         )
         .width(Length::Fill)
         .align_x(Alignment::Center);
 
-        let mut list = Scrollable::new(&mut self.servers_list)
-            .spacing(8)
-            .align_items(Alignment::Start)
+        let servers = servers
+            .iter()
+            .map(|server| server.as_ref().to_owned())
+            .collect::<Vec<_>>();
+        // Address and ping of each server, shown when hovering it
+        let details = servers
+            .iter()
+            .map(|server| (server.clone(), ping_text(i18n, pings.get(server).copied())))
+            .collect::<Vec<_>>();
+        let selected_details = selected_server_index.and_then(|i| details.get(i)).cloned();
+
+        let dropdown = Dropdown::new(
+            &mut self.servers_dropdown,
+            servers,
+            selected_server_index,
+            Message::ServerChanged,
+        )
+        .style(
+            style::dropdown::Style::new(
+                list_entry_style(imgs, true, 255),
+                list_entry_style(imgs, false, 255),
+            )
+            .selected_option(list_entry_style(imgs, true, 255)),
+        )
+        .row_height(50)
+        .max_rows(6)
+        .text_size(fonts.cyri.scale(30))
+        .font(fonts.cyri.id)
+        .option_tooltip(move |i| {
+            let (address, ping) = &details[i];
+            tooltips.content(address.clone(), &[ping.clone()])
+        });
+
+        // Describes the selected server while the dropdown is closed
+        let dropdown = match selected_details {
+            Some((address, ping)) => tooltips.rich(dropdown, address, move || vec![ping.clone()]),
+            None => dropdown.into(),
+        };
+
+        let selection = Container::new(dropdown)
             .width(Length::Fill)
             .height(Length::Fill);
 
-        // Reset button states if servers were added / removed
-        if self.server_buttons.len() != servers.len() {
-            self.server_buttons = vec![Default::default(); servers.len()];
-        }
-
-        let list_items =
-            self.server_buttons
-                .iter_mut()
-                .zip(servers)
-                .enumerate()
-                .map(|(i, (state, server))| {
-                    let color = if Some(i) == selected_server_index {
-                        (97, 255, 18)
-                    } else {
-                        (97, 97, 25)
-                    };
-                    let button = Button::new(
-                        state,
-                        Row::with_children(vec![
-                            Space::new(Length::FillPortion(5), Length::Units(0)).into(),
-                            Text::new(server.as_ref())
-                                .size(fonts.cyri.scale(30))
-                                .width(Length::FillPortion(95))
-                                .vertical_alignment(iced::Vertical::Center)
-                                .into(),
-                        ]),
-                    )
-                    .style(
-                        style::button::Style::new(imgs.selection)
-                            .hover_image(imgs.selection_hover)
-                            .press_image(imgs.selection_press)
-                            .image_color(vek::Rgba::new(color.0, color.1, color.2, 255)),
-                    )
-                    .min_height(100)
-                    .on_press(Message::ServerChanged(i));
-                    Row::with_children(vec![
-                        Space::new(Length::FillPortion(3), Length::Units(0)).into(),
-                        button.width(Length::FillPortion(92)).into(),
-                        Space::new(Length::FillPortion(5), Length::Units(0)).into(),
-                    ])
-                });
-
-        for item in list_items {
-            list = list.push(item);
-        }
+        let overrides = selected_server_index.map(|_| {
+            self.overrides.view(
+                fonts,
+                imgs,
+                overrides.cloned().unwrap_or_default(),
+                language_metadatas,
+                i18n,
+                button_style,
+            )
+        });
 
         Container::new(
             Container::new(
-                Column::with_children(vec![
-                    title.into(),
-                    list.into(),
-                    Row::with_children(vec![delete_button.into(), back_button.into()])
-                        .width(Length::Fill)
-                        .into(),
-                ])
+                Column::with_children(
+                    vec![title.into(), selection.into()]
+                        .into_iter()
+                        .chain(overrides)
+                        .chain(core::iter::once(
+                            Row::with_children(vec![delete_button.into(), back_button.into()])
+                                .width(Length::Fill)
+                                .into(),
+                        ))
+                        .collect(),
+                )
                 .width(Length::Fill)
                 .height(Length::Fill)
                 .spacing(10)
@@ -147,3 +168,136 @@ impl Screen {
         .into()
     }
 }
+
+/// Last measured ping to a server as shown in its tooltip
+fn ping_text(i18n: &Localization, ping: Option<u32>) -> String {
+    match ping {
+        Some(ping) => i18n
+            .get("main.servers.ping")
+            .replace("{ping}", &ping.to_string()),
+        None => i18n.get("main.servers.ping_unknown").to_owned(),
+    }
+}
+
+/// Edits the settings used while playing on the selected server
+pub struct OverridesPanel {
+    character_input: text_input::State,
+    language_dropdown: dropdown::State,
+    view_distance_input: text_input::State,
+    audio_button: button::State,
+    // Kept separately so invalid input can be edited
+    pub(super) view_distance: String,
+}
+
+impl OverridesPanel {
+    fn new(view_distance: String) -> Self {
+        Self {
+            character_input: Default::default(),
+            language_dropdown: Default::default(),
+            view_distance_input: Default::default(),
+            audio_button: Default::default(),
+            view_distance,
+        }
+    }
+
+    fn view(
+        &mut self,
+        fonts: &Fonts,
+        imgs: &Imgs,
+        overrides: ServerOverrides,
+        language_metadatas: &[LanguageMetadata],
+        i18n: &Localization,
+        button_style: style::button::Style,
+    ) -> Element<Message> {
+        let text_size = fonts.cyri.scale(OVERRIDE_TEXT_SIZE);
+        let label = |key| {
+            Text::new(i18n.get(key))
+                .size(text_size)
+                .width(Length::FillPortion(2))
+        };
+        let row = |label: Text, input: Element<'_, Message>| {
+            Row::with_children(vec![label.into(), input])
+                .spacing(10)
+                .align_items(Alignment::Center)
+        };
+
+        let character = TextInput::new(
+            &mut self.character_input,
+            i18n.get("main.servers.override_default"),
+            overrides.character.as_deref().unwrap_or_default(),
+            Message::OverrideCharacter,
+        )
+        .size(text_size)
+        .width(Length::FillPortion(3));
+
+        let languages = core::iter::once(i18n.get("main.servers.override_default").to_owned())
+            .chain(language_metadatas.iter().map(|lang| lang.display_name().to_owned()))
+            .collect();
+        let selected_language = overrides.language.as_ref().and_then(|language| {
+            language_metadatas
+                .iter()
+                .position(|lang| &lang.language_identifier == language)
+                .map(|i| i + 1)
+        });
+        let language = Dropdown::new(
+            &mut self.language_dropdown,
+            languages,
+            Some(selected_language.unwrap_or(0)),
+            Message::OverrideLanguage,
+        )
+        .style(
+            style::dropdown::Style::new(
+                list_entry_style(imgs, true, 255),
+                list_entry_style(imgs, false, 255),
+            )
+            .selected_option(list_entry_style(imgs, true, 255)),
+        )
+        .row_height(30)
+        .max_rows(6)
+        .text_size(text_size)
+        .font(fonts.cyri.id);
+
+        let view_distance = TextInput::new(
+            &mut self.view_distance_input,
+            i18n.get("main.servers.override_default"),
+            &self.view_distance,
+            Message::OverrideViewDistance,
+        )
+        .size(text_size)
+        .numeric()
+        .max_length(4)
+        .width(Length::FillPortion(3));
+
+        let audio = Container::new(neat_button(
+            &mut self.audio_button,
+            i18n.get(if overrides.audio.is_some() {
+                "main.servers.audio_clear"
+            } else {
+                "main.servers.audio_use_current"
+            }),
+            FILL_FRAC_ONE,
+            button_style,
+            Some(Message::OverrideAudio),
+        ))
+        .width(Length::FillPortion(3));
+
+        Column::with_children(vec![
+            Text::new(i18n.get("main.servers.overrides"))
+                .size(fonts.cyri.scale(25))
+                .into(),
+            row(label("main.servers.character"), character.into()).into(),
+            row(
+                label("main.servers.language"),
+                Container::new(language)
+                    .width(Length::FillPortion(3))
+                    .into(),
+            )
+            .into(),
+            row(label("main.servers.view_distance"), view_distance.into()).into(),
+            row(label("main.servers.audio"), audio.into()).into(),
+        ])
+        .spacing(5)
+        .width(Length::Fill)
+        .into()
+    }
+}
@@ -0,0 +1,283 @@
+//! Headless harness for snapshot testing the main menu screens
+//!
+//! Builds `Controls` against a ui without a window, feeds it messages and
+//! compares the serialized primitive tree against the files stored in
+//! `snapshots/`. A missing snapshot fails the test, run the tests with
+//! `UPDATE_SNAPSHOTS=1` to (re)create them and commit the files.
+
+use super::{bg_imgs, load_language_icons, onboarding, Controls, Event, Imgs, Message, Screen};
+use crate::{
+    event_bus::EventBus,
+    settings::Settings,
+    telemetry::{Telemetry, TelemetryEvent},
+    ui::{
+        fonts::IcedFonts as Fonts,
+        ice::{load_font, IcedUi as Ui},
+        Graphic, ScaleMode,
+    },
+};
+use common::assets::{self, AssetExt};
+use i18n::LocalizationHandle;
+use std::path::PathBuf;
+use vek::*;
+
+const RESOLUTION: Vec2<u32> = Vec2 { x: 1280, y: 720 };
+
+pub struct Harness {
+    ui: Ui,
+    controls: Controls,
+    settings: Settings,
+    telemetry: Telemetry,
+    events: Vec<Event>,
+}
+
+impl Harness {
+    pub fn new(language: &str) -> Self {
+        let i18n = LocalizationHandle::load_expect(language);
+        let mut settings = Settings::default();
+        // Tips are picked randomly
+        settings.interface.loading_tips = false;
+        // Start at the login screen, onboarding has its own test
+        settings.first_run = false;
+
+        let font = load_font(&i18n.read().fonts().get("cyri").unwrap().asset_key);
+        let mut ui = Ui::headless(font, RESOLUTION, ScaleMode::Absolute(1.0));
+        let fonts = Fonts::load(&i18n.read(), &mut ui).expect("Impossible to load fonts");
+        // Always use the same background so snapshots are deterministic
+        let bg_img = assets::Image::load_expect(&bg_imgs()[0]).read().to_image();
+
+        let mut controls = Controls::new(
+            fonts,
+            Imgs::load(&mut ui).expect("Failed to load images"),
+            ui.add_graphic(Graphic::Image(bg_img, None)),
+            ui.add_graphic(Graphic::Blank),
+            load_language_icons(&mut ui),
+            i18n,
+            &settings,
+        );
+        // Don't depend on the git version of the build
+        controls.version = "Version".to_owned();
+        controls.alpha = "Veloren".to_owned();
+
+        Self {
+            ui,
+            controls,
+            telemetry: Telemetry::new(settings.telemetry_opt_in),
+            settings,
+            events: Vec::new(),
+        }
+    }
+
+    pub fn settings_mut(&mut self) -> &mut Settings { &mut self.settings }
+
+    /// Process a message as if it was produced by the ui
+    pub fn send(&mut self, message: Message) -> &mut Self {
+        let mut bus = EventBus::default();
+        self.controls
+            .update(message, &mut bus, &self.settings, &mut self.ui);
+        self.events.extend(bus.drain());
+        self
+    }
+
+    /// Events emitted so far
+    pub fn events(&self) -> &[Event] { &self.events }
+
+    /// Run `view()` and serialize the result
+    /// Messages produced by the ui are processed like `MainMenuUi::maintain`
+    /// does
+    pub fn render(&mut self) -> String {
+        let (messages, snapshot) = self.ui.snapshot(self.controls.view(
+            &self.settings,
+            &self.telemetry,
+            &None,
+            // Keep animations frozen
+            0.0,
+        ));
+        for message in messages {
+            self.send(message);
+        }
+        snapshot
+    }
+
+    /// Text of the last render which looks like an untranslated i18n key
+    pub fn missing_keys(&self) -> Vec<String> {
+        self.ui
+            .recorded_text()
+            .filter(|text| looks_like_key(text))
+            .map(str::to_owned)
+            .collect()
+    }
+
+    /// Render and compare against the stored snapshot called `name`
+    #[track_caller]
+    pub fn assert_snapshot(&mut self, name: &str) {
+        let snapshot = self.render();
+        let missing = self.missing_keys();
+        assert!(missing.is_empty(), "Missing i18n keys in {}: {:?}", name, missing);
+
+        let path = snapshot_path(name);
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            std::fs::create_dir_all(path.parent().unwrap())
+                .expect("Failed to create snapshot directory");
+            std::fs::write(&path, snapshot).expect("Failed to write snapshot");
+            return;
+        }
+        // A missing baseline fails too, otherwise a forgotten file passes on CI
+        let stored = std::fs::read_to_string(&path).unwrap_or_else(|e| {
+            panic!(
+                "No snapshot {} at {:?} ({}), create it with UPDATE_SNAPSHOTS=1 and commit \
+                 it\n--- new\n{}",
+                name, path, e, snapshot
+            )
+        });
+        assert!(
+            stored == snapshot,
+            "Snapshot {} changed, rerun with UPDATE_SNAPSHOTS=1 if this is \
+             intended\n--- stored\n{}\n--- new\n{}",
+            name,
+            stored,
+            snapshot
+        );
+    }
+}
+
+fn snapshot_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("src/menu/main/ui/snapshots")
+        .join(name)
+        .with_extension("snap")
+}
+
+// i18n keys are lowercase dot separated identifiers, e.g. `main.login.server_full`
+fn looks_like_key(text: &str) -> bool {
+    text.contains('.')
+        && text.split('.').all(|part| {
+            !part.is_empty()
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_detection() {
+        assert!(looks_like_key("main.login.server_full"));
+        assert!(!looks_like_key("Server full"));
+        assert!(!looks_like_key("Veloren 0.12.0"));
+        assert!(!looks_like_key("server.veloren.net:14004"));
+    }
+
+    #[test]
+    fn login_screen() { Harness::new("en").assert_snapshot("login"); }
+
+    #[test]
+    fn language_selection() {
+        Harness::new("en")
+            .send(Message::OpenLanguageMenu)
+            .assert_snapshot("language_selection");
+    }
+
+    #[test]
+    fn servers_screen() {
+        let mut harness = Harness::new("en");
+        harness.settings_mut().networking.servers =
+            vec!["server.veloren.net:14004".to_owned(), "localhost".to_owned()];
+        harness
+            .send(Message::ShowServers)
+            .assert_snapshot("servers");
+    }
+
+    #[test]
+    fn credits_screen() {
+        Harness::new("en")
+            .send(Message::ShowCredits)
+            .assert_snapshot("credits");
+    }
+
+    #[test]
+    fn onboarding() {
+        let mut harness = Harness::new("en");
+        harness.controls.screen = Screen::Onboarding {
+            screen: Box::new(onboarding::Screen::new(&i18n::list_localizations())),
+        };
+        harness.assert_snapshot("onboarding");
+        harness
+            .send(Message::OnboardingNext)
+            .send(Message::OnboardingUiScale(2))
+            .send(Message::OnboardingNext)
+            .send(Message::OnboardingNext)
+            .send(Message::ToggleTelemetry)
+            .assert_snapshot("onboarding_telemetry");
+        harness.send(Message::OnboardingNext);
+        assert!(matches!(harness.events(), [
+            Event::SetUiScale(ScaleMode::Absolute(_)),
+            Event::FinishOnboarding {
+                telemetry_opt_in: true
+            }
+        ]));
+        assert!(matches!(harness.controls.screen, Screen::Login { .. }));
+    }
+
+    #[test]
+    fn telemetry_screen() {
+        let mut harness = Harness::new("en");
+        harness.settings.telemetry_opt_in = true;
+        harness.telemetry.set_enabled(true);
+        harness.telemetry.record(TelemetryEvent::ScreenVisit { screen: "login" });
+        harness
+            .send(Message::ShowTelemetry)
+            .assert_snapshot("telemetry");
+        harness.send(Message::ToggleTelemetry);
+        assert!(matches!(harness.events(), [Event::SetTelemetry(false)]));
+    }
+
+    #[test]
+    fn connection_error() {
+        let mut harness = Harness::new("en");
+        harness.controls.connection_error("main.login.server_full".to_owned());
+        // The error text is raw on purpose, it should be flagged as missing
+        harness.render();
+        assert_eq!(harness.missing_keys(), vec![
+            "main.login.server_full".to_owned()
+        ]);
+    }
+
+    #[test]
+    fn quit_prompt() {
+        let mut harness = Harness::new("en");
+        harness.send(Message::Quit).assert_snapshot("quit_prompt");
+        assert!(harness.events().is_empty());
+        harness.send(Message::ConfirmModal);
+        assert!(matches!(harness.events(), [Event::Quit]));
+    }
+
+    #[test]
+    fn invalid_port() {
+        let mut harness = Harness::new("en");
+        harness
+            .send(Message::Server("localhost".to_owned()))
+            .send(Message::Port("65536".to_owned()))
+            .send(Message::Multiplayer)
+            .assert_snapshot("invalid_port");
+        assert!(harness.events().is_empty());
+    }
+
+    #[test]
+    fn login_attempt_event() {
+        let mut harness = Harness::new("en");
+        harness
+            .send(Message::Username("player".to_owned()))
+            .send(Message::Multiplayer);
+        assert!(matches!(
+            harness.events(),
+            [Event::LoginAttempt { username, .. }] if username == "player"
+        ));
+        // The loading animation is random so only check for missing keys here
+        harness.render();
+        assert!(harness.missing_keys().is_empty());
+    }
+}
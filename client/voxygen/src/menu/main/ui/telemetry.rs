@@ -0,0 +1,123 @@
+use super::{Message, FILL_FRAC_ONE};
+use crate::{
+    telemetry::Telemetry,
+    ui::{
+        fonts::IcedFonts as Fonts,
+        ice::{component::neat_button, style, Element},
+    },
+};
+use i18n::Localization;
+use iced::{Horizontal, Length};
+use iced::widget::{button, scrollable, Column, Container, Row, Scrollable, Space, Text};
+
+/// Shows whether usage data is collected and what will be sent
+pub struct Screen {
+    toggle_button: button::State,
+    back_button: button::State,
+    scroll: scrollable::State,
+}
+
+impl Screen {
+    pub fn new() -> Self {
+        Self {
+            toggle_button: Default::default(),
+            back_button: Default::default(),
+            scroll: Default::default(),
+        }
+    }
+
+    pub(super) fn view(
+        &mut self,
+        fonts: &Fonts,
+        enabled: bool,
+        telemetry: &Telemetry,
+        i18n: &Localization,
+        button_style: style::button::Style,
+    ) -> Element<Message> {
+        let queued = telemetry
+            .queued()
+            .map(|event| event.to_json())
+            .collect::<Vec<_>>();
+        let queued = if queued.is_empty() {
+            Scrollable::new(&mut self.scroll).push(
+                Text::new(i18n.get("main.telemetry.nothing_queued"))
+                    .size(fonts.cyri.scale(18))
+                    .width(Length::Fill)
+                    .horizontal_alignment(Horizontal::Center),
+            )
+        } else {
+            queued
+                .into_iter()
+                .fold(Scrollable::new(&mut self.scroll), |scrollable, event| {
+                    scrollable.push(Text::new(event).size(fonts.cyri.scale(18)))
+                })
+        };
+
+        let buttons = Row::with_children(vec![
+            Container::new(neat_button(
+                &mut self.toggle_button,
+                i18n.get(if enabled {
+                    "main.telemetry.disable"
+                } else {
+                    "main.telemetry.enable"
+                }),
+                FILL_FRAC_ONE,
+                button_style,
+                Some(Message::ToggleTelemetry),
+            ))
+            .max_width(250)
+            .into(),
+            Space::new(Length::Fill, Length::Shrink).into(),
+            Container::new(neat_button(
+                &mut self.back_button,
+                i18n.get("common.back"),
+                FILL_FRAC_ONE,
+                button_style,
+                Some(Message::Back),
+            ))
+            .max_width(200)
+            .into(),
+        ])
+        .height(Length::Units(fonts.cyri.scale(50)))
+        .width(Length::Fill);
+
+        Container::new(
+            Container::new(
+                Column::with_children(vec![
+                    Text::new(i18n.get("main.telemetry"))
+                        .font(fonts.alkhemi.id)
+                        .size(fonts.alkhemi.scale(35))
+                        .width(Length::Fill)
+                        .horizontal_alignment(Horizontal::Center)
+                        .into(),
+                    Text::new(i18n.get("main.onboarding.telemetry_info"))
+                        .size(fonts.cyri.scale(20))
+                        .into(),
+                    Space::new(Length::Fill, Length::Units(15)).into(),
+                    Text::new(i18n.get("main.telemetry.queued"))
+                        .size(fonts.cyri.scale(23))
+                        .into(),
+                    queued.height(Length::FillPortion(1)).into(),
+                    buttons.into(),
+                ])
+                .spacing(5)
+                .padding(20)
+                .width(Length::Fill)
+                .height(Length::Fill),
+            )
+            .style(
+                style::container::Style::color_with_double_cornerless_border(
+                    (22, 19, 17, 255).into(),
+                    (11, 11, 11, 255).into(),
+                    (54, 46, 38, 255).into(),
+                ),
+            ),
+        )
+        .center_x()
+        .center_y()
+        .padding(70)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+}
@@ -45,6 +45,7 @@ pub use self::{
         GlobalModel, Globals, GlobalsBindGroup, GlobalsLayouts, Light, Shadow,
     },
     renderer::{
+        capture::{CaptureTarget, OnCapture},
         drawer::{
             Drawer, FirstPassDrawer, SecondPassDrawer, ShadowPassDrawer, ThirdPassDrawer,
         },
@@ -63,6 +63,9 @@ pub const MODE_IMAGE_SOURCE_NORTH: u32 = 3;
 ///
 /// FIXME: Make more principled.
 pub const MODE_IMAGE_TARGET_NORTH: u32 = 5;
+/// Draw an image from the texture at `tex` in the fragment shader, converted
+/// to grayscale before applying the color.
+pub const MODE_IMAGE_GRAYSCALE: u32 = 9;
 
 pub enum Mode {
     Text,
@@ -70,6 +73,7 @@ pub enum Mode {
     Geometry,
     ImageSourceNorth,
     ImageTargetNorth,
+    ImageGrayscale,
 }
 
 impl Mode {
@@ -80,6 +84,7 @@ impl Mode {
             Mode::Geometry => MODE_GEOMETRY,
             Mode::ImageSourceNorth => MODE_IMAGE_SOURCE_NORTH,
             Mode::ImageTargetNorth => MODE_IMAGE_TARGET_NORTH,
+            Mode::ImageGrayscale => MODE_IMAGE_GRAYSCALE,
         }
     }
 }
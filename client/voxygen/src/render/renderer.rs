@@ -1,4 +1,5 @@
 mod binding;
+pub(super) mod capture;
 pub(super) mod drawer;
 // Consts and bind groups for post-process and clouds
 mod locals;
@@ -138,6 +139,9 @@ pub struct Renderer {
     // This checks is added because windows resizes the window to 0,0 when
     // minimizing and this causes a bunch of validation errors
     is_minimized: bool,
+
+    // Frames waiting to be read back from the gpu
+    captures: Vec<capture::PendingCapture>,
 }
 
 impl Renderer {
@@ -388,6 +392,8 @@ impl Renderer {
             resolution: Vec2::new(dims.width, dims.height),
 
             is_minimized: false,
+
+            captures: Vec::new(),
         })
     }
 
@@ -512,12 +518,13 @@ impl Renderer {
         }
     }
 
-    pub fn maintain(&self) {
+    pub fn maintain(&mut self) {
         if self.is_minimized {
             self.queue.submit(std::iter::empty());
         }
 
-        self.device.poll(wgpu::Maintain::Poll)
+        self.device.poll(wgpu::Maintain::Poll);
+        self.maintain_captures();
     }
 
     /// Create render target views
@@ -0,0 +1,168 @@
+//! Drawing frames into an offscreen target and reading them back, e.g. to
+//! capture the ui
+
+use super::Renderer;
+use std::{
+    future::Future,
+    num::NonZeroU32,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll, Wake, Waker},
+};
+use vek::*;
+
+type Mapped = Pin<Box<dyn Future<Output = Result<(), wgpu::BufferAsyncError>> + Send>>;
+
+/// Called with the captured frame once it was read back from the gpu
+pub type OnCapture = Box<dyn FnOnce(Result<image::RgbaImage, String>)>;
+
+/// A texture to draw a frame to instead of the window, see
+/// [`Renderer::create_capture_target`]
+pub struct CaptureTarget {
+    texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    size: Vec2<u32>,
+}
+
+pub(super) struct PendingCapture {
+    buffer: wgpu::Buffer,
+    mapped: Mapped,
+    size: Vec2<u32>,
+    padded_row: u32,
+    bgra: bool,
+    on_capture: OnCapture,
+}
+
+// The mapping is checked every frame anyway, no need to wake anything
+struct NoopWaker;
+
+impl Wake for NoopWaker {
+    fn wake(self: Arc<Self>) {}
+}
+
+impl Renderer {
+    /// A target with the size and format of the window
+    pub fn create_capture_target(&self) -> CaptureTarget {
+        let size = self.resolution;
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("capture target"),
+            size: wgpu::Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.sc_desc.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        CaptureTarget {
+            texture,
+            view,
+            size,
+        }
+    }
+
+    /// Submits `encoder`, which drew a frame into `target`, and reads the
+    /// frame back. `on_capture` is called from [`Renderer::maintain`] once
+    /// the image is available.
+    pub fn read_capture(
+        &mut self,
+        target: CaptureTarget,
+        mut encoder: wgpu::CommandEncoder,
+        on_capture: OnCapture,
+    ) {
+        let size = target.size;
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_row = (size.x * 4 + align - 1) / align * align;
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("capture buffer"),
+            size: padded_row as u64 * size.y as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture: &target.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: NonZeroU32::new(padded_row),
+                    rows_per_image: None,
+                },
+            },
+            wgpu::Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            },
+        );
+        self.queue.submit(Some(encoder.finish()));
+
+        let mapped = Box::pin(buffer.slice(..).map_async(wgpu::MapMode::Read));
+        self.captures.push(PendingCapture {
+            buffer,
+            mapped,
+            size,
+            padded_row,
+            bgra: matches!(
+                self.sc_desc.format,
+                wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb
+            ),
+            on_capture,
+        });
+    }
+
+    /// Hands out the captures which finished reading back
+    pub(super) fn maintain_captures(&mut self) {
+        if self.captures.is_empty() {
+            return;
+        }
+        let waker = Waker::from(Arc::new(NoopWaker));
+        let mut context = Context::from_waker(&waker);
+
+        let mut i = 0;
+        while i < self.captures.len() {
+            let result = match self.captures[i].mapped.as_mut().poll(&mut context) {
+                Poll::Pending => {
+                    i += 1;
+                    continue;
+                },
+                Poll::Ready(result) => result,
+            };
+            let capture = self.captures.swap_remove(i);
+            let image = result
+                .map_err(|e| e.to_string())
+                .and_then(|()| capture.image());
+            (capture.on_capture)(image);
+        }
+    }
+}
+
+impl PendingCapture {
+    fn image(&self) -> Result<image::RgbaImage, String> {
+        let data = self.buffer.slice(..).get_mapped_range();
+        let row = self.size.x as usize * 4;
+        let mut pixels = Vec::with_capacity(row * self.size.y as usize);
+        for padded in data.chunks(self.padded_row as usize) {
+            pixels.extend_from_slice(&padded[..row]);
+        }
+        drop(data);
+        self.buffer.unmap();
+
+        if self.bgra {
+            pixels.chunks_exact_mut(4).for_each(|pixel| pixel.swap(0, 2));
+        }
+        image::RgbaImage::from_raw(self.size.x, self.size.y, pixels)
+            .ok_or_else(|| "Captured frame has the wrong size".to_owned())
+    }
+}
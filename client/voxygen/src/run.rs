@@ -1,4 +1,5 @@
 use crate::{
+    crash_report,
     menu::main::MainMenuState,
     settings::get_fps,
     ui,
@@ -84,9 +85,9 @@ pub fn run(mut global_state: GlobalState, event_loop: EventLoop) {
                 global_state.window.handle_device_event(event)
             },
             winit::event::Event::LoopDestroyed => {
-               
                 //save
                 global_state.settings.save();
+                crate::settings::persistence::flush();
                 global_state.profile.save();
             },
             _ => {},
@@ -103,9 +104,16 @@ fn handle_main_events_cleared(
     global_state
         .window
         .resolve_deduplicated_events(&mut global_state.settings);
+
+    // Without threads queued asset loads only progress here
+    #[cfg(target_arch = "wasm32")]
+    common::assets::process_async_loads_within(Duration::from_millis(u64::from(
+        global_state.settings.graphics.asset_load_budget_ms,
+    )));
     
     let mut exit = true;
     while let Some(state_result) = states.last_mut().map(|last| {
+        crash_report::set_play_state(last.name());
         let events = global_state.window.fetch_events();
         last.tick(global_state, events)
     }) {
@@ -162,7 +170,10 @@ fn handle_main_events_cleared(
 
     if let Some(last) = states.last_mut() {
         capped_fps = last.capped_fps();
+    }
 
+    // The last frame stays on screen if the play state has nothing new to draw
+    if let Some(last) = states.last_mut().filter(|state| state.needs_redraw()) {
         let renderer_mut = global_state.window.renderer_mut();
 
         match renderer_mut.surface.get_current_texture() {
@@ -0,0 +1,57 @@
+//! Storing frames captured with [`IcedUi::capture_frame`], the latest one is
+//! kept around so it can be attached to bug reports
+//!
+//! [`IcedUi::capture_frame`]: crate::ui::ice::IcedUi::capture_frame
+
+use lazy_static::lazy_static;
+use std::sync::Mutex;
+
+lazy_static! {
+    static ref LATEST: Mutex<Option<Vec<u8>>> = Mutex::new(None);
+}
+
+/// The latest screenshot encoded as png
+pub fn latest() -> Option<Vec<u8>> { LATEST.lock().ok()?.clone() }
+
+/// Meant to be passed to `capture_frame`
+pub fn save(capture: Result<image::RgbaImage, String>) {
+    let mut png = Vec::new();
+    let encoded = capture.and_then(|image| {
+        image::DynamicImage::ImageRgba8(image)
+            .write_to(&mut png, image::ImageOutputFormat::Png)
+            .map_err(|e| e.to_string())
+    });
+    if let Err(e) = encoded {
+        log::error!("Failed to take a screenshot: {}", e);
+        return;
+    }
+
+    storage::store(&png);
+    if let Ok(mut latest) = LATEST.lock() {
+        *latest = Some(png);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod storage {
+    // There is no place to put files, the screenshot is only kept in memory
+    pub fn store(png: &[u8]) { log::info!("Took a screenshot ({} bytes)", png.len()); }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod storage {
+    pub fn store(png: &[u8]) {
+        let dir = match directories_next::ProjectDirs::from("net", "veloren", "voxygen") {
+            Some(dirs) => dirs.data_dir().join("screenshots"),
+            None => return,
+        };
+        let path = dir.join(format!(
+            "{}.png",
+            chrono::Local::now().format("%Y-%m-%d_%H-%M-%S%.3f")
+        ));
+        match std::fs::create_dir_all(&dir).and_then(|()| std::fs::write(&path, png)) {
+            Ok(()) => log::info!("Saved screenshot to {}", path.display()),
+            Err(e) => log::error!("Failed to save screenshot to {}: {}", path.display(), e),
+        }
+    }
+}
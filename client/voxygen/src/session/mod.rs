@@ -10,7 +10,7 @@ use vek::*;
 
 use client::{self, Client};
 use common::{
-    assets::AssetExt,
+    assets::{self, AssetExt},
     comp,
     comp::{
         inventory::slot::{EquipSlot, Slot},
@@ -77,6 +77,11 @@ pub struct SessionState {
     selected_entity: Option<(specs::Entity, Instant)>,
     interactable: Option<Interactable>,
     hitboxes: HashMap<specs::Entity, DebugShapeId>,
+    /// Address of the server we're connected to
+    server_address: String,
+    /// Latest rolling average ping in milliseconds, stored for the server list
+    /// once the session ends
+    ping_ms: Option<f64>,
 }
 
 /// Represents an active game session (i.e., the one being played).
@@ -118,6 +123,8 @@ impl SessionState {
             interactable: None,
             #[cfg(not(target_os = "macos"))]
             hitboxes: HashMap::new(),
+            server_address: global_state.settings.networking.default_server.clone(),
+            ping_ms: None,
         }
     }
 
@@ -134,7 +141,10 @@ impl SessionState {
         global_state: &mut GlobalState,
         outcomes: &mut Vec<Outcome>,
     ) -> Result<TickAction, Error> {
-        
+        // Glyphs of chat messages were loaded since the last tick
+        if assets::take_font_changes() {
+            self.hud.update_fonts(&global_state.i18n.read());
+        }
 
         let mut client = self.client.borrow_mut();
         self.scene
@@ -270,24 +280,24 @@ impl SessionState {
 
     /// Clean up the session (and the client attached to it) after a tick.
     pub fn cleanup(&mut self) { self.client.borrow_mut().cleanup(); }
-}
-
-impl PlayState for SessionState {
-    fn enter(&mut self, global_state: &mut GlobalState, _: Direction) {
-        // Trap the cursor.
-        global_state.window.grab_cursor(true);
-
-        self.client.borrow_mut().clear_terrain();
 
-        // Send startup commands to the server
-        if global_state.settings.send_logon_commands {
-            for cmd in &global_state.settings.logon_commands {
-                self.client.borrow_mut().send_chat(cmd.to_string());
-            }
+    /// Stores the last ping for the server list, once when leaving the session
+    fn store_ping(&mut self, global_state: &mut GlobalState) {
+        if let Some(ping_ms) = self.ping_ms.take() {
+            global_state
+                .settings
+                .networking
+                .record_ping(&self.server_address, ping_ms);
+            global_state.settings.save();
         }
     }
 
-    fn tick(&mut self, global_state: &mut GlobalState, events: Vec<Event>) -> PlayStateResult {
+    /// Handles the events and ticks the session, see [`PlayState::tick`]
+    fn tick_play_state(
+        &mut self,
+        global_state: &mut GlobalState,
+        events: Vec<Event>,
+    ) -> PlayStateResult {
         
         // TODO: let mut client = self.client.borrow_mut();
 
@@ -296,6 +306,11 @@ impl PlayState for SessionState {
             let client = self.client.borrow();
             (client.presence(), client.registered())
         };
+        // Shown in the server list, there is no average before the first ping
+        let ping_ms = self.client.borrow().get_ping_ms_rolling_avg();
+        if ping_ms.is_finite() {
+            self.ping_ms = Some(ping_ms);
+        }
         if client_presence.is_some() {
             let camera = self.scene.camera_mut();
 
@@ -1436,6 +1451,29 @@ impl PlayState for SessionState {
         }
     }
 
+impl PlayState for SessionState {
+    fn enter(&mut self, global_state: &mut GlobalState, _: Direction) {
+        // Trap the cursor.
+        global_state.window.grab_cursor(true);
+
+        self.client.borrow_mut().clear_terrain();
+
+        // Send startup commands to the server
+        if global_state.settings.send_logon_commands {
+            for cmd in &global_state.settings.logon_commands {
+                self.client.borrow_mut().send_chat(cmd.to_string());
+            }
+        }
+    }
+
+    fn tick(&mut self, global_state: &mut GlobalState, events: Vec<Event>) -> PlayStateResult {
+        let result = self.tick_play_state(global_state, events);
+        if !matches!(result, PlayStateResult::Continue) {
+            self.store_ping(global_state);
+        }
+        result
+    }
+
     fn name(&self) -> &'static str { "Session" }
 
     fn capped_fps(&self) -> bool { false }
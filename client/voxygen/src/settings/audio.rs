@@ -31,6 +31,30 @@ pub struct AudioSettings {
     pub output: AudioOutput,
 }
 
+/// The volumes of `AudioSettings`, e.g. to use different ones per server
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AudioProfile {
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+}
+
+impl AudioSettings {
+    pub fn profile(&self) -> AudioProfile {
+        AudioProfile {
+            master_volume: self.master_volume,
+            music_volume: self.music_volume,
+            sfx_volume: self.sfx_volume,
+        }
+    }
+
+    pub fn set_profile(&mut self, profile: AudioProfile) {
+        self.master_volume = profile.master_volume;
+        self.music_volume = profile.music_volume;
+        self.sfx_volume = profile.sfx_volume;
+    }
+}
+
 impl Default for AudioSettings {
     fn default() -> Self {
         Self {
@@ -1,5 +1,6 @@
 use crate::{game_input::GameInput, window::KeyMouse};
 use hashbrown::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
 use strum::IntoEnumIterator;
 use winit::event::{MouseButton, VirtualKeyCode};
 
@@ -31,6 +32,13 @@ impl From<ControlSettings> for ControlSettingsSerde {
 pub struct ControlSettings {
     pub keybindings: HashMap<GameInput, Option<KeyMouse>>,
     pub inverse_keybindings: HashMap<KeyMouse, HashSet<GameInput>>, // used in event loop
+    // Changed by every binding change, see `revision`
+    revision: u64,
+}
+
+fn next_revision() -> u64 {
+    static REVISION: AtomicU64 = AtomicU64::new(0);
+    REVISION.fetch_add(1, Ordering::Relaxed)
 }
 
 impl From<ControlSettingsSerde> for ControlSettings {
@@ -58,7 +66,12 @@ const MIDDLE_CLICK_KEY: KeyMouse = KeyMouse::Key(VirtualKeyCode::Grave);
 const MIDDLE_CLICK_KEY: KeyMouse = KeyMouse::Mouse(MouseButton::Middle);
 
 impl ControlSettings {
+    /// Differs between any two states of the bindings changed through the
+    /// methods of this type, even between separate instances
+    pub fn revision(&self) -> u64 { self.revision }
+
     pub fn remove_binding(&mut self, game_input: GameInput) {
+        self.revision = next_revision();
         if let Some(inverse) = self
             .keybindings
             .insert(game_input, None)
@@ -78,6 +91,7 @@ impl ControlSettings {
     }
 
     pub fn insert_binding(&mut self, game_input: GameInput, key_mouse: KeyMouse) {
+        self.revision = next_revision();
         self.keybindings.insert(game_input, Some(key_mouse));
         self.inverse_keybindings
             .entry(key_mouse)
@@ -86,6 +100,7 @@ impl ControlSettings {
     }
 
     pub fn modify_binding(&mut self, game_input: GameInput, key_mouse: KeyMouse) {
+        self.revision = next_revision();
         // For the KeyMouse->GameInput hashmap, we first need to remove the GameInput
         // from the old binding
         if let Some(old_binding) = self.get_binding(game_input) {
@@ -193,6 +208,7 @@ impl Default for ControlSettings {
         let mut new_settings = Self {
             keybindings: HashMap::new(),
             inverse_keybindings: HashMap::new(),
+            revision: next_revision(),
         };
         // Sets the initial keybindings for those GameInputs.
         for game_input in GameInput::iter() {
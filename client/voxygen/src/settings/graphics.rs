@@ -24,6 +24,11 @@ impl fmt::Display for Fps {
     }
 }
 
+/// The range of the view distance slider
+// FIXME: Move back to 64 once we support multiple texture atlases, or figure out a
+// way to increase the size of the terrain atlas.
+pub const VIEW_DISTANCE_RANGE: (u32, u32) = (1, 65);
+
 /// `GraphicsSettings` contains settings related to framerate and in-game
 /// visuals.
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -36,6 +41,15 @@ pub struct GraphicsSettings {
     pub figure_lod_render_distance: u32,
     pub max_fps: Fps,
     pub max_background_fps: Fps,
+    /// How often animations of the main menu are redrawn, the menu is only
+    /// redrawn on input otherwise
+    pub max_menu_animation_fps: Fps,
+    /// Milliseconds of each frame spent on decoding queued assets, at least
+    /// one asset is decoded per frame regardless
+    pub asset_load_budget_ms: u32,
+    /// Whether image colors are multiplied by their alpha when loaded, only
+    /// applied at startup
+    pub premultiply_alpha: bool,
     pub fov: u16,
     pub gamma: f32,
     pub exposure: f32,
@@ -56,6 +70,9 @@ impl Default for GraphicsSettings {
             figure_lod_render_distance: 300,
             max_fps: Fps::Max(60),
             max_background_fps: Fps::Max(30),
+            max_menu_animation_fps: Fps::Max(30),
+            asset_load_budget_ms: 8,
+            premultiply_alpha: false,
             fov: 70,
             gamma: 1.0,
             exposure: 1.0,
@@ -7,22 +7,31 @@ pub mod graphics;
 pub mod interface;
 pub mod language;
 pub mod networking;
+pub mod persistence;
+pub mod telemetry;
 
-pub use audio::{AudioOutput, AudioSettings};
+pub use audio::{AudioOutput, AudioProfile, AudioSettings};
 pub use chat::ChatSettings;
 pub use control::ControlSettings;
 pub use gamepad::GamepadSettings;
 pub use gameplay::GameplaySettings;
-pub use graphics::{get_fps, Fps, GraphicsSettings};
+pub use graphics::{get_fps, Fps, GraphicsSettings, VIEW_DISTANCE_RANGE};
 pub use interface::InterfaceSettings;
 pub use language::LanguageSettings;
-pub use networking::NetworkingSettings;
+pub use networking::{NetworkingSettings, ServerOverrides};
+pub use persistence::SettingsSection;
+pub use telemetry::TelemetrySettings;
+
+use serde::{Deserialize, Serialize};
 
 /// `Settings` contains everything that can be configured in the settings.ron
 /// file.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Settings {
     pub chat: ChatSettings,
+    /// Key bindings are not stored yet
+    #[serde(skip)]
     pub controls: ControlSettings,
     pub interface: InterfaceSettings,
     pub gameplay: GameplaySettings,
@@ -30,16 +39,25 @@ pub struct Settings {
     pub graphics: GraphicsSettings,
     pub audio: AudioSettings,
     pub show_disclaimer: bool,
+    /// Whether to show the onboarding screens instead of the login screen
+    pub first_run: bool,
+    /// Whether the user agreed to send anonymous usage data
+    pub telemetry_opt_in: bool,
     pub send_logon_commands: bool,
     // TODO: Remove at a later date, for dev testing
     pub logon_commands: Vec<String>,
     pub language: LanguageSettings,
     pub controller: GamepadSettings,
+    pub telemetry: TelemetrySettings,
+    /// The regular settings replaced by the overrides of the server we play
+    /// on, these are stored instead of the overrides
+    #[serde(skip)]
+    pub replaced_by_overrides: Option<ServerOverrides>,
 }
 
 impl Default for Settings {
     fn default() -> Self {
-        let settings = Settings {
+        Settings {
             chat: ChatSettings::default(),
             controls: ControlSettings::default(),
             interface: InterfaceSettings::default(),
@@ -48,29 +66,39 @@ impl Default for Settings {
             graphics: GraphicsSettings::default(),
             audio: AudioSettings::default(),
             show_disclaimer: true,
+            first_run: true,
+            telemetry_opt_in: false,
             send_logon_commands: false,
             logon_commands: Vec::new(),
             language: LanguageSettings::default(),
             controller: GamepadSettings::default(),
-        };
-        settings.save();
-        settings
+            telemetry: TelemetrySettings::default(),
+            replaced_by_overrides: None,
+        }
     }
 }
 
 impl Settings {
+    /// The stored settings, the defaults if there are none yet
     pub fn load() -> Self {
-     
-        log::warn!("todo load setting data, 目前仅使用默认");
-
-        //wasm 仅用默认
-        let default_settings = Self::default();
-        default_settings
+        let mut settings = match persistence::load() {
+            Some(settings) => {
+                persistence::loaded(&settings);
+                settings
+            },
+            None => {
+                let settings = Self::default();
+                settings.save();
+                settings
+            },
+        };
+        settings.first_run &= !persistence::onboarded();
+        persistence::flush_on_leave();
+        settings
     }
 
-    pub fn save(&self) {
-        log::warn!("todo save setting data");
-    }
+    /// Schedules writing the settings if they changed, see [`persistence`]
+    pub fn save(&self) { persistence::save(self); }
 
     pub fn display_warnings(&self) {
         if !self.graphics.render_mode.experimental_shaders.is_empty() {
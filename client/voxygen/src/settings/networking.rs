@@ -1,4 +1,5 @@
-use hashbrown::HashSet;
+use super::{AudioProfile, Settings, VIEW_DISTANCE_RANGE};
+use hashbrown::{HashMap, HashSet};
 use serde::{Deserialize, Serialize};
 
 /// `NetworkingSettings` stores server and networking settings.
@@ -9,6 +10,10 @@ pub struct NetworkingSettings {
     pub servers: Vec<String>,
     pub default_server: String,
     pub trusted_auth_servers: HashSet<String>,
+    /// Overrides by server address, see [`ServerOverrides`]
+    pub server_overrides: HashMap<String, ServerOverrides>,
+    /// Last measured ping in milliseconds by server address
+    pub server_pings: HashMap<String, u32>,
 }
 
 impl Default for NetworkingSettings {
@@ -21,6 +26,64 @@ impl Default for NetworkingSettings {
                 .iter()
                 .map(|s| s.to_string())
                 .collect(),
+            server_overrides: HashMap::new(),
+            server_pings: HashMap::new(),
         }
     }
 }
+
+impl NetworkingSettings {
+    /// Remembers the last ping to `server`, shown in the server list
+    pub fn record_ping(&mut self, server: &str, ping_ms: f64) {
+        let ping_ms = ping_ms.round() as u32;
+        match self.server_pings.get_mut(server) {
+            Some(ping) => *ping = ping_ms,
+            None => {
+                self.server_pings.insert(server.to_owned(), ping_ms);
+            },
+        }
+    }
+}
+
+/// Settings which replace the regular ones while playing on a certain server,
+/// `None` keeps the regular setting
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerOverrides {
+    /// Alias of the character to select in the character selection
+    pub character: Option<String>,
+    /// Language identifier
+    pub language: Option<String>,
+    pub view_distance: Option<u32>,
+    pub audio: Option<AudioProfile>,
+}
+
+impl ServerOverrides {
+    pub fn is_empty(&self) -> bool { *self == Self::default() }
+
+    /// Applies the overrides to `settings` and returns the replaced values,
+    /// applying those undoes the change.
+    ///
+    /// The character isn't a setting, the character selection picks it up.
+    pub fn apply(&self, settings: &mut Settings) -> Self {
+        let mut replaced = Self::default();
+        if let Some(language) = &self.language {
+            replaced.language = Some(std::mem::replace(
+                &mut settings.language.selected_language,
+                language.clone(),
+            ));
+        }
+        if let Some(view_distance) = self.view_distance {
+            let (min, max) = VIEW_DISTANCE_RANGE;
+            replaced.view_distance = Some(std::mem::replace(
+                &mut settings.graphics.view_distance,
+                view_distance.clamp(min, max),
+            ));
+        }
+        if let Some(audio) = self.audio {
+            replaced.audio = Some(settings.audio.profile());
+            settings.audio.set_profile(audio);
+        }
+        replaced
+    }
+}
@@ -0,0 +1,337 @@
+//! Writes the settings shortly after they change instead of only when the game
+//! exits cleanly
+//!
+//! [`Settings::save`] only schedules a write, [`maintain`] does it once no
+//! further change came in for a moment, so dragging a slider doesn't write
+//! every frame. Files are written next to the old one and renamed over it, a
+//! crash while writing leaves the previous settings intact.
+
+use super::Settings;
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use instant::{Duration, Instant};
+use lazy_static::lazy_static;
+use serde::Serialize;
+use std::sync::{Mutex, MutexGuard};
+
+/// Writes wait until there was no change for this long
+const DEBOUNCE: Duration = Duration::from_millis(500);
+/// Settings which keep changing are still written this often
+const MAX_DELAY: Duration = Duration::from_secs(5);
+
+/// The parts of the settings which are reported to subscribers when they
+/// change
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SettingsSection {
+    /// The disclaimer, onboarding and logon command settings
+    General,
+    Chat,
+    Interface,
+    Gameplay,
+    Networking,
+    Graphics,
+    Audio,
+    Language,
+    Controller,
+    Telemetry,
+}
+
+struct Pending {
+    document: String,
+    first_change: Instant,
+    due: Instant,
+}
+
+#[derive(Default)]
+struct Store {
+    /// Each section serialized as of the last save, empty before that
+    sections: Vec<(SettingsSection, String)>,
+    pending: Option<Pending>,
+    subscribers: Vec<Sender<SettingsSection>>,
+}
+
+lazy_static! {
+    static ref STORE: Mutex<Store> = Mutex::new(Store::default());
+}
+
+fn store() -> MutexGuard<'static, Store> {
+    STORE.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn ron<T: Serialize>(value: &T) -> String { ron::to_string(value).unwrap_or_default() }
+
+fn sections(settings: &Settings) -> Vec<(SettingsSection, String)> {
+    use SettingsSection::*;
+    vec![
+        (
+            General,
+            ron(&(
+                settings.show_disclaimer,
+                settings.first_run,
+                settings.send_logon_commands,
+                &settings.logon_commands,
+            )),
+        ),
+        (Chat, ron(&settings.chat)),
+        (Interface, ron(&settings.interface)),
+        (Gameplay, ron(&settings.gameplay)),
+        (Networking, ron(&settings.networking)),
+        (Graphics, ron(&settings.graphics)),
+        (Audio, ron(&settings.audio)),
+        (Language, ron(&settings.language)),
+        (Controller, ron(&settings.controller)),
+        (Telemetry, ron(&(settings.telemetry_opt_in, &settings.telemetry))),
+    ]
+}
+
+/// The sections of `new` which differ from `old`
+fn changed_sections(
+    old: &[(SettingsSection, String)],
+    new: &[(SettingsSection, String)],
+) -> Vec<SettingsSection> {
+    new.iter()
+        .filter(|(section, value)| !old.iter().any(|(s, v)| s == section && v == value))
+        .map(|(section, _)| *section)
+        .collect()
+}
+
+/// When changes are written, given when the first unwritten one was made
+fn due(first_change: Instant, now: Instant) -> Instant {
+    (now + DEBOUNCE).min(first_change + MAX_DELAY)
+}
+
+/// Receives the section whenever a saved change touched it
+pub fn subscribe() -> Receiver<SettingsSection> {
+    let (sender, receiver) = unbounded();
+    store().subscribers.push(sender);
+    receiver
+}
+
+/// Remembers loaded settings, so saving them unchanged doesn't write anything
+pub(super) fn loaded(settings: &Settings) { store().sections = sections(settings); }
+
+/// Schedules a write if anything changed since the last save
+pub(super) fn save(settings: &Settings) {
+    // Server overrides only last for the session
+    let restored;
+    let settings = match &settings.replaced_by_overrides {
+        Some(replaced) => {
+            let mut copy = settings.clone();
+            replaced.apply(&mut copy);
+            restored = copy;
+            &restored
+        },
+        None => settings,
+    };
+    let sections = sections(settings);
+    let mut store = store();
+    let changed = changed_sections(&store.sections, &sections);
+    if changed.is_empty() {
+        return;
+    }
+    let document = match ron::ser::to_string_pretty(settings, ron::ser::PrettyConfig::new()) {
+        Ok(document) => document,
+        Err(e) => {
+            log::warn!("Failed to serialize settings: {}", e);
+            return;
+        },
+    };
+    store.sections = sections;
+    store
+        .subscribers
+        .retain(|subscriber| changed.iter().all(|section| subscriber.send(*section).is_ok()));
+
+    let now = Instant::now();
+    let first_change = store.pending.as_ref().map_or(now, |p| p.first_change);
+    store.pending = Some(Pending {
+        document,
+        first_change,
+        due: due(first_change, now),
+    });
+}
+
+/// Writes scheduled changes once they are due, called every frame
+pub fn maintain() {
+    let mut store = store();
+    if store.pending.as_ref().map_or(false, |p| p.due <= Instant::now()) {
+        if let Some(pending) = store.pending.take() {
+            storage::write(&pending.document);
+        }
+    }
+}
+
+/// Writes scheduled changes right away, e.g. when the game exits
+pub fn flush() {
+    if let Some(pending) = store().pending.take() {
+        storage::write(&pending.document);
+    }
+}
+
+/// Writes scheduled changes when the game goes away without the game loop
+/// noticing, e.g. when the browser tab is hidden or closed
+pub(super) fn flush_on_leave() { storage::on_leave(flush); }
+
+/// Remembers that the onboarding was finished, written right away and apart
+/// from the settings so broken settings don't bring it back
+pub fn finish_onboarding() { storage::write_onboarded(); }
+
+/// Whether the onboarding was finished before
+pub(super) fn onboarded() -> bool { storage::read_onboarded() }
+
+/// The stored settings, `None` if there are none or they can't be read
+pub(super) fn load() -> Option<Settings> {
+    let document = storage::read()?;
+    match ron::from_str(&document) {
+        Ok(settings) => Some(settings),
+        Err(e) => {
+            log::warn!("Failed to parse settings, using the defaults: {}", e);
+            None
+        },
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod storage {
+    const KEY: &str = "veloren_settings";
+    const ONBOARDED_KEY: &str = "veloren_onboarded";
+
+    fn local_storage() -> Option<web_sys::Storage> {
+        web_sys::window()?.local_storage().ok().flatten()
+    }
+
+    // A single item is replaced at once, there is no need for a rename
+    pub fn write(document: &str) {
+        if let Some(storage) = local_storage() {
+            if storage.set_item(KEY, document).is_err() {
+                log::warn!("Failed to store settings");
+            }
+        }
+    }
+
+    pub fn read() -> Option<String> { local_storage()?.get_item(KEY).ok().flatten() }
+
+    pub fn write_onboarded() {
+        if let Some(storage) = local_storage() {
+            if storage.set_item(ONBOARDED_KEY, "1").is_err() {
+                log::warn!("Failed to store that the onboarding was finished");
+            }
+        }
+    }
+
+    pub fn read_onboarded() -> bool {
+        local_storage()
+            .and_then(|storage| storage.get_item(ONBOARDED_KEY).ok().flatten())
+            .is_some()
+    }
+
+    // Hidden tabs may be discarded without further notice, so they count too
+    pub fn on_leave(f: fn()) {
+        use wasm_bindgen::{closure::Closure, JsCast};
+
+        let window = match web_sys::window() {
+            Some(window) => window,
+            None => return,
+        };
+        let on_hidden = Closure::wrap(Box::new(move || {
+            let hidden = web_sys::window()
+                .and_then(|window| window.document())
+                .map_or(false, |document| document.hidden());
+            if hidden {
+                f();
+            }
+        }) as Box<dyn FnMut()>);
+        let on_unload = Closure::wrap(Box::new(f) as Box<dyn FnMut()>);
+
+        let registered = window
+            .document()
+            .map_or(Ok(()), |document| {
+                document.add_event_listener_with_callback(
+                    "visibilitychange",
+                    on_hidden.as_ref().unchecked_ref(),
+                )
+            })
+            .and_then(|_| {
+                window.add_event_listener_with_callback(
+                    "beforeunload",
+                    on_unload.as_ref().unchecked_ref(),
+                )
+            });
+        if registered.is_err() {
+            log::warn!("Failed to listen for the page being left to write the settings");
+        }
+        // The listeners stay for the lifetime of the page
+        on_hidden.forget();
+        on_unload.forget();
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod storage {
+    use std::{fs, io, path::PathBuf};
+
+    fn config_dir() -> Option<PathBuf> {
+        directories_next::ProjectDirs::from("net", "veloren", "voxygen")
+            .map(|dirs| dirs.config_dir().to_path_buf())
+    }
+
+    fn path() -> Option<PathBuf> { config_dir().map(|dir| dir.join("settings.ron")) }
+
+    // An empty marker file, its presence is all that matters
+    fn onboarded_path() -> Option<PathBuf> { config_dir().map(|dir| dir.join("onboarded")) }
+
+    fn write_atomic(path: &PathBuf, document: &str) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let tmp = path.with_extension("ron.tmp");
+        fs::write(&tmp, document)?;
+        fs::rename(&tmp, path)
+    }
+
+    pub fn write(document: &str) {
+        if let Some(path) = path() {
+            if let Err(e) = write_atomic(&path, document) {
+                log::warn!("Failed to write settings to {}: {}", path.display(), e);
+            }
+        }
+    }
+
+    pub fn read() -> Option<String> { fs::read_to_string(path()?).ok() }
+
+    pub fn write_onboarded() {
+        if let Some(path) = onboarded_path() {
+            if let Err(e) = write_atomic(&path, "") {
+                log::warn!("Failed to write {}: {}", path.display(), e);
+            }
+        }
+    }
+
+    pub fn read_onboarded() -> bool { onboarded_path().map_or(false, |path| path.exists()) }
+
+    // The game loop gets to flush before the process exits
+    pub fn on_leave(_f: fn()) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changes_and_deadlines() {
+        let mut settings = Settings::default();
+        let old = sections(&settings);
+        assert!(changed_sections(&old, &old).is_empty());
+        assert_eq!(changed_sections(&[], &old).len(), old.len());
+
+        settings.language.selected_language = "de_DE".to_owned();
+        settings.first_run = false;
+        assert_eq!(changed_sections(&old, &sections(&settings)), vec![
+            SettingsSection::General,
+            SettingsSection::Language,
+        ]);
+
+        let start = Instant::now();
+        assert_eq!(due(start, start), start + DEBOUNCE);
+        let late = start + MAX_DELAY;
+        assert_eq!(due(start, late), start + MAX_DELAY);
+    }
+}
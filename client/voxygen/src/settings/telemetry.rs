@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+/// `TelemetrySettings` configures the collection of anonymous usage data,
+/// whether it is collected at all is [`Settings::telemetry_opt_in`].
+///
+/// [`Settings::telemetry_opt_in`]: super::Settings::telemetry_opt_in
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TelemetrySettings {
+    /// Where batches of events are sent to over HTTPS, nothing is sent while
+    /// empty
+    pub endpoint: String,
+}
+
+impl Default for TelemetrySettings {
+    fn default() -> Self {
+        Self {
+            endpoint: String::new(),
+        }
+    }
+}
@@ -0,0 +1,233 @@
+//! Opt-in collection of anonymous usage data
+//!
+//! Events are only recorded while the user agreed to it. They are kept in a
+//! local queue and sent in batches to the endpoint from the
+//! [`TelemetrySettings`]. Events stay queued until the endpoint accepted
+//! them, a failed batch is sent again with the next one after the flush
+//! interval. Only the web build can send, so native builds record nothing.
+//! Events never contain anything identifying the player, like names,
+//! addresses or free-form error messages.
+
+use crate::settings::TelemetrySettings;
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use instant::{Duration, Instant};
+use serde::Serialize;
+use std::collections::VecDeque;
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TelemetryEvent {
+    /// A main menu screen was shown
+    ScreenVisit { screen: &'static str },
+    /// Connecting to a server failed, see `client_init::Error`
+    ConnectFailed { reason: &'static str },
+    /// How long something took to load
+    LoadTiming { what: &'static str, millis: u64 },
+}
+
+impl TelemetryEvent {
+    /// The event exactly as it will be sent
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|e| format!("<{}>", e))
+    }
+}
+
+pub struct Telemetry {
+    enabled: bool,
+    queue: VecDeque<TelemetryEvent>,
+    last_flush: Instant,
+    /// Id and length of the batch being sent, its events are the first ones
+    /// in the queue
+    in_flight: Option<(u64, usize)>,
+    /// The last batch was refused, wait for the flush interval before the
+    /// next one even if a full batch is queued
+    backoff: bool,
+    next_batch: u64,
+    /// Whether a batch was accepted, by its id
+    results: (Sender<(u64, bool)>, Receiver<(u64, bool)>),
+}
+
+impl Telemetry {
+    /// Sent early once this many events are queued
+    const BATCH_SIZE: usize = 100;
+    const FLUSH_INTERVAL: Duration = Duration::from_secs(300);
+    /// The oldest events are dropped if they can't be sent
+    const MAX_QUEUED: usize = 1000;
+    /// Whether batches can be sent at all, tests fake the endpoint
+    const CAN_SEND: bool = cfg!(any(target_arch = "wasm32", test));
+
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled: enabled && Self::CAN_SEND,
+            queue: VecDeque::new(),
+            last_flush: Instant::now(),
+            in_flight: None,
+            backoff: false,
+            next_batch: 0,
+            results: unbounded(),
+        }
+    }
+
+    /// Queued events are discarded when disabling
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if !enabled {
+            self.queue.clear();
+            self.in_flight = None;
+        }
+        self.enabled = enabled && Self::CAN_SEND;
+    }
+
+    pub fn record(&mut self, event: TelemetryEvent) {
+        if !self.enabled {
+            return;
+        }
+        if self.queue.len() >= Self::MAX_QUEUED {
+            self.queue.pop_front();
+            if let Some((_, len)) = &mut self.in_flight {
+                *len = len.saturating_sub(1);
+            }
+        }
+        self.queue.push_back(event);
+    }
+
+    /// Events which will be sent with the next batch
+    pub fn queued(&self) -> impl Iterator<Item = &TelemetryEvent> { self.queue.iter() }
+
+    /// Sends the queued events if a batch is due and removes the ones which
+    /// were accepted
+    pub fn maintain(&mut self, settings: &TelemetrySettings) {
+        while let Ok((batch, accepted)) = self.results.1.try_recv() {
+            // Results of batches sent before disabling are ignored
+            if let Some((id, len)) = self.in_flight {
+                if id == batch {
+                    if accepted {
+                        self.queue.drain(..len.min(self.queue.len()));
+                    }
+                    self.backoff = !accepted;
+                    self.in_flight = None;
+                }
+            }
+        }
+
+        if !self.enabled
+            || self.in_flight.is_some()
+            || settings.endpoint.is_empty()
+            || self.queue.is_empty()
+        {
+            return;
+        }
+        if (self.backoff || self.queue.len() < Self::BATCH_SIZE)
+            && self.last_flush.elapsed() < Self::FLUSH_INTERVAL
+        {
+            return;
+        }
+        self.last_flush = Instant::now();
+
+        let len = self.queue.len();
+        match serde_json::to_string(&self.queue) {
+            Ok(body) => {
+                let batch = self.next_batch;
+                self.next_batch += 1;
+                self.in_flight = Some((batch, len));
+                send(&settings.endpoint, body, batch, self.results.0.clone());
+            },
+            Err(e) => log::warn!("Failed to serialize telemetry: {}", e),
+        }
+    }
+}
+
+/// Reports on `results` whether the endpoint accepted the batch
+fn send(endpoint: &str, body: String, batch: u64, results: Sender<(u64, bool)>) {
+    if !endpoint.starts_with("https://") {
+        log::warn!("Not sending telemetry to {}, only https is allowed", endpoint);
+        let _ = results.send((batch, false));
+        return;
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        use wasm_bindgen::{JsCast, JsValue};
+
+        let window = match web_sys::window() {
+            Some(window) => window,
+            None => {
+                let _ = results.send((batch, false));
+                return;
+            },
+        };
+        let mut init = web_sys::RequestInit::new();
+        init.method("POST").body(Some(&JsValue::from_str(&body)));
+        let request = window.fetch_with_str_and_init(endpoint, &init);
+        wasm_bindgen_futures::spawn_local(async move {
+            let accepted = match wasm_bindgen_futures::JsFuture::from(request).await {
+                Ok(response) => response
+                    .dyn_into::<web_sys::Response>()
+                    .map_or(false, |response| response.ok()),
+                Err(e) => {
+                    log::debug!("Failed to send telemetry: {:?}", e);
+                    false
+                },
+            };
+            let _ = results.send((batch, accepted));
+        });
+    }
+    // Nothing is recorded on other platforms, see `Telemetry::CAN_SEND`
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        drop(body);
+        let _ = results.send((batch, false));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opt_in() {
+        let mut telemetry = Telemetry::new(false);
+        telemetry.record(TelemetryEvent::ScreenVisit { screen: "login" });
+        assert_eq!(telemetry.queued().count(), 0);
+
+        telemetry.set_enabled(true);
+        telemetry.record(TelemetryEvent::ConnectFailed { reason: "timeout" });
+        assert_eq!(
+            telemetry.queued().next().unwrap().to_json(),
+            r#"{"kind":"connect_failed","reason":"timeout"}"#
+        );
+
+        telemetry.set_enabled(false);
+        assert_eq!(telemetry.queued().count(), 0);
+    }
+
+    #[test]
+    fn kept_until_accepted() {
+        let mut telemetry = Telemetry::new(true);
+        for _ in 0..Telemetry::BATCH_SIZE {
+            telemetry.record(TelemetryEvent::ScreenVisit { screen: "login" });
+        }
+        // Refused right away, only https is allowed
+        let settings = TelemetrySettings {
+            endpoint: "http://localhost".to_owned(),
+        };
+        telemetry.maintain(&settings);
+        telemetry.maintain(&settings);
+        assert_eq!(telemetry.queued().count(), Telemetry::BATCH_SIZE);
+        // Not sent again before the flush interval passed
+        assert!(telemetry.in_flight.is_none());
+
+        // The batch was sent again and refused again
+        telemetry.last_flush = Instant::now()
+            .checked_sub(Telemetry::FLUSH_INTERVAL)
+            .unwrap();
+        telemetry.maintain(&settings);
+        let (batch, _) = telemetry.in_flight.unwrap();
+        assert_eq!(telemetry.results.1.try_recv(), Ok((batch, false)));
+        telemetry.record(TelemetryEvent::ConnectFailed { reason: "timeout" });
+        telemetry.results.0.send((batch, true)).unwrap();
+        telemetry.maintain(&settings);
+        assert_eq!(telemetry.queued().collect::<Vec<_>>(), vec![
+            &TelemetryEvent::ConnectFailed { reason: "timeout" }
+        ]);
+    }
+}
@@ -1,5 +1,5 @@
-use crate::ui::ice::RawFont;
-use common::assets::{self, AssetExt};
+use crate::ui::ice::{FontLayout, RawFont};
+use common::assets;
 
 pub struct Font {
     metadata: i18n::Font,
@@ -8,7 +8,7 @@ pub struct Font {
 
 impl Font {
     fn new(font: &i18n::Font, ui: &mut crate::ui::Ui) -> Result<Self, assets::Error> {
-        let raw_font = RawFont::load(&font.asset_key)?.cloned();
+        let raw_font = RawFont(assets::font_data(&font.asset_key)?.to_vec());
 
         Ok(Self {
             metadata: font.clone(),
@@ -44,22 +44,34 @@ conrod_fonts! {
 
 pub struct IcedFont {
     metadata: i18n::Font,
+    hints: i18n::LayoutHints,
     pub id: crate::ui::ice::FontId,
 }
 
 impl IcedFont {
-    fn new(font: &i18n::Font, ui: &mut crate::ui::ice::IcedUi) -> Result<Self, assets::Error> {
-        let raw_font = RawFont::load(&font.asset_key)?.cloned();
+    fn new(
+        font: &i18n::Font,
+        hints: &i18n::LayoutHints,
+        ui: &mut crate::ui::ice::IcedUi,
+    ) -> Result<Self, assets::Error> {
+        let raw_font = RawFont(assets::font_data(&font.asset_key)?.to_vec());
+        let layout = FontLayout {
+            line_height: hints.line_height,
+            letter_spacing: hints.letter_spacing,
+        };
 
         Ok(Self {
             metadata: font.clone(),
-            id: ui.add_font(raw_font),
+            hints: hints.clone(),
+            id: ui.add_font_with_layout(raw_font, layout),
         })
     }
 
     /// Scale input size to final UI size
     /// TODO: change metadata to use u16
-    pub fn scale(&self, value: u16) -> u16 { self.metadata.scale(value as u32) as u16 }
+    pub fn scale(&self, value: u16) -> u16 {
+        self.hints.scale(&self.metadata, value as u32) as u16
+    }
 }
 
 macro_rules! iced_fonts {
@@ -70,9 +82,11 @@ macro_rules! iced_fonts {
             }
 
             impl IcedFonts {
-                pub fn load(fonts: &i18n::Fonts, ui: &mut crate::ui::ice::IcedUi) -> Result<Self, assets::Error> {
+                /// Loads the fonts of the language, laid out according to its hints
+                pub fn load(i18n: &i18n::Localization, ui: &mut crate::ui::ice::IcedUi) -> Result<Self, assets::Error> {
+                    let (fonts, hints) = (i18n.fonts(), i18n.layout_hints());
                     Ok(Self {
-                        $( $name: IcedFont::new(fonts.get(stringify!($name)).unwrap(), ui)?, )*
+                        $( $name: IcedFont::new(fonts.get(stringify!($name)).unwrap(), hints, ui)?, )*
                     })
                 }
             }
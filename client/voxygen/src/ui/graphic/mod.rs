@@ -1,3 +1,4 @@
+mod packed;
 mod pixel_art;
 mod renderer;
 
@@ -147,6 +148,10 @@ pub struct GraphicCache {
     textures: Slab<(Texture, UiTextureBindGroup)>,
     // Stores the location of graphics rendered at a particular resolution and cached on the cpu
     cache_map: HashMap<Parameters, CachedDetails>,
+    // Images packed at load time with the index of their texture, these
+    // textures are kept when the cache is cleared
+    packed: HashMap<Id, (usize, Aabr<u16>)>,
+    packed_textures: Vec<usize>,
 
     keyed_jobs: KeyedJobs<(Id, Vec2<u16>), Option<(RgbaImage, Option<Rgba<f32>>)>>,
 }
@@ -160,6 +165,24 @@ impl GraphicCache {
             atlases: vec![(atlas, 0)],
             textures: core::iter::once((0, texture)).collect(),
             cache_map: HashMap::default(),
+            packed: HashMap::default(),
+            packed_textures: Vec::new(),
+            keyed_jobs: KeyedJobs::new("IMAGE_PROCESSING"),
+        }
+    }
+
+    /// Creates a cache without any gpu textures
+    /// Graphics can be added and queried for their dimensions but `cache_res`
+    /// must not be called until `clear_cache` has been invoked with a renderer
+    pub fn headless() -> Self {
+        Self {
+            graphic_map: HashMap::default(),
+            next_id: 0,
+            atlases: Vec::new(),
+            textures: Slab::new(),
+            cache_map: HashMap::default(),
+            packed: HashMap::default(),
+            packed_textures: Vec::new(),
             keyed_jobs: KeyedJobs::new("IMAGE_PROCESSING"),
         }
     }
@@ -175,6 +198,8 @@ impl GraphicCache {
     }
 
     pub fn replace_graphic(&mut self, id: Id, graphic: Graphic) {
+        // The new graphic is cached like any other
+        self.packed.remove(&id);
         if self.graphic_map.insert(id, graphic).is_none() {
             // This was not an update, so no need to search for keys.
             return;
@@ -228,9 +253,48 @@ impl GraphicCache {
     pub fn clear_cache(&mut self, renderer: &mut Renderer) {
         self.cache_map.clear();
 
+        // Packed textures are moved over to the new slab
+        let textures = &mut self.textures;
+        let packed_textures = self
+            .packed_textures
+            .iter()
+            .map(|&index| (index, textures.remove(index)))
+            .collect::<Vec<_>>();
         let (atlas, texture) = create_atlas_texture(renderer);
         self.atlases = vec![(atlas, 0)];
         self.textures = core::iter::once((0, texture)).collect();
+
+        let textures = &mut self.textures;
+        let moved = packed_textures
+            .into_iter()
+            .map(|(index, texture)| (index, textures.insert(texture)))
+            .collect::<HashMap<_, _>>();
+        self.packed
+            .values_mut()
+            .for_each(|(index, _)| *index = moved[&*index]);
+        self.packed_textures = moved.values().copied().collect();
+    }
+
+    /// Packs the images into a few textures right away, drawing them at their
+    /// own size only needs sub-rects of these instead of cached textures.
+    /// Meant for the many small images of a ui which are all shown at once.
+    pub fn pack_graphics(&mut self, renderer: &mut Renderer, ids: &[Id]) {
+        let packed = packed::pack(renderer, &self.graphic_map, ids);
+        let indices = packed
+            .textures
+            .into_iter()
+            .map(|texture| self.textures.insert(texture))
+            .collect::<Vec<_>>();
+        log::debug!(
+            "Packed {} of {} images into {} textures",
+            packed.placements.len(),
+            ids.len(),
+            indices.len()
+        );
+        for (id, texture, aabr) in packed.placements {
+            self.packed.insert(id, (indices[texture], aabr));
+        }
+        self.packed_textures.extend(indices);
     }
 
     /// Source rectangle should be from 0 to 1, and represents a bounding box
@@ -277,6 +341,14 @@ impl GraphicCache {
         // TODO: Verify rotation is being applied correctly.
         let transformed_aabr = |aabr| rotated_aabr(scaled_aabr(aabr));
 
+        // Packed images are stored at their own size, at other sizes they are
+        // resized as pixel art and cached like any other image
+        if let Some(&(index, aabr)) = self.packed.get(&graphic_id) {
+            if Vec2::from(aabr.size()) == dims {
+                return Some((transformed_aabr(aabr.map(|e| e as f64)), TexId(index)));
+            }
+        }
+
         let Self {
             textures,
             atlases,
@@ -0,0 +1,153 @@
+//! Images packed into a few shared textures at load time, see
+//! [`GraphicCache::pack_graphics`](super::GraphicCache::pack_graphics)
+//!
+//! Unlike the atlases of the cache, packed images are stored at their original
+//! size, so all of them are in place before the first frame. They are only
+//! used where they are drawn at that size, scaling them on the gpu would blur
+//! the pixel art. At other sizes they are resized with
+//! [`resize_pixel_art`](super::pixel_art::resize_pixel_art) and cached as
+//! before.
+
+use super::{upload_image, Graphic, GraphicMap, Id};
+use crate::render::{Renderer, Texture, UiTextureBindGroup};
+use guillotiere::{size2, SimpleAtlasAllocator};
+use image::GenericImageView;
+use std::cmp::Reverse;
+use vek::*;
+
+/// Size of the textures, unless the gpu supports less
+const PACKED_TEXTURE_SIZE: u32 = 2048;
+/// Images larger than this fraction of a texture are cached as before
+const PACKED_CUTOFF_FRAC: f32 = 0.5;
+/// Transparent space around each image, so sampling never picks up a neighbour
+const PADDING: u16 = 1;
+
+pub(super) struct Packed {
+    pub textures: Vec<(Texture, UiTextureBindGroup)>,
+    /// The index into `textures` and the area of each packed image
+    pub placements: Vec<(Id, usize, Aabr<u16>)>,
+}
+
+/// Places images of the given sizes on textures of `texture_size`, tallest
+/// first. Returns the texture index and area of each image in input order,
+/// `None` for the ones which don't fit on a texture at all.
+fn arrange(sizes: &[Vec2<u16>], texture_size: u16) -> Vec<Option<(usize, Aabr<u16>)>> {
+    let mut order = (0..sizes.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&i| Reverse(sizes[i].y));
+
+    let mut allocators = Vec::<SimpleAtlasAllocator>::new();
+    let mut placements = vec![None; sizes.len()];
+    for i in order {
+        let padded = sizes[i].map(|e| i32::from(e) + 2 * i32::from(PADDING));
+        if sizes[i].map(|e| e == 0).reduce_or()
+            || padded.map(|e| e > i32::from(texture_size)).reduce_or()
+        {
+            continue;
+        }
+        let padded = size2(padded.x, padded.y);
+        let allocated = allocators
+            .iter_mut()
+            .enumerate()
+            .find_map(|(texture, allocator)| Some((texture, allocator.allocate(padded)?)));
+        let (texture, rect) = match allocated {
+            Some(allocated) => allocated,
+            None => {
+                let size = i32::from(texture_size);
+                let mut allocator = SimpleAtlasAllocator::new(size2(size, size));
+                let rect = allocator
+                    .allocate(padded)
+                    .expect("Image is smaller than an empty texture");
+                allocators.push(allocator);
+                (allocators.len() - 1, rect)
+            },
+        };
+        let min = Vec2::new(rect.min.x as u16, rect.min.y as u16) + PADDING;
+        placements[i] = Some((texture, Aabr {
+            min,
+            max: min + sizes[i],
+        }));
+    }
+    placements
+}
+
+/// Packs the images among `ids`, voxel graphics and images with a border
+/// color are left out
+pub(super) fn pack(renderer: &mut Renderer, graphic_map: &GraphicMap, ids: &[Id]) -> Packed {
+    let texture_size = PACKED_TEXTURE_SIZE.min(renderer.max_texture_size());
+    let cutoff = (texture_size as f32 * PACKED_CUTOFF_FRAC) as u32;
+    let images = ids
+        .iter()
+        .filter_map(|id| match graphic_map.get(id) {
+            Some(Graphic::Image(image, None))
+                if image.width() <= cutoff && image.height() <= cutoff =>
+            {
+                Some((*id, image))
+            },
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let sizes = images
+        .iter()
+        .map(|(_, image)| Vec2::new(image.width() as u16, image.height() as u16))
+        .collect::<Vec<_>>();
+
+    let mut textures = Vec::new();
+    let mut placements = Vec::new();
+    for ((id, image), placement) in images.iter().zip(arrange(&sizes, texture_size as u16)) {
+        let (texture, aabr) = match placement {
+            Some(placement) => placement,
+            None => continue,
+        };
+        while textures.len() <= texture {
+            let tex = renderer.create_dynamic_texture(Vec2::broadcast(texture_size));
+            let bind = renderer.ui_bind_texture(&tex);
+            textures.push((tex, bind));
+        }
+        upload_image(renderer, aabr, &textures[texture].0, &image.to_rgba8());
+        placements.push((*id, texture, aabr));
+    }
+
+    Packed {
+        textures,
+        placements,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arrange_images() {
+        let sizes = [
+            Vec2::new(100, 20),
+            Vec2::new(60, 60),
+            Vec2::new(0, 10),
+            Vec2::new(300, 10),
+            Vec2::new(60, 60),
+        ];
+        let placements = arrange(&sizes, 128);
+
+        // Empty images and images larger than a texture are skipped
+        assert!(placements[2].is_none() && placements[3].is_none());
+        let placed = placements
+            .iter()
+            .zip(&sizes)
+            .filter_map(|(placement, size)| Some((placement.as_ref()?, size)))
+            .collect::<Vec<_>>();
+        assert_eq!(placed.len(), 3);
+        for (i, ((texture, aabr), size)) in placed.iter().enumerate() {
+            assert_eq!(aabr.size(), Extent2::from(**size));
+            assert!(aabr.min.map(|e| e >= PADDING).reduce_and());
+            assert!(aabr.max.map(|e| e + PADDING <= 128).reduce_and());
+            // Padded areas on the same texture may touch but not overlap
+            for ((other_texture, other), _) in &placed[i + 1..] {
+                let before = |a: &Aabr<u16>, b: &Aabr<u16>| {
+                    a.min.map2(b.max, |min, max| min < max + 2 * PADDING)
+                };
+                let overlap = before(aabr, other).map2(before(other, aabr), |a, b| a && b);
+                assert!(texture != other_texture || !overlap.reduce_and());
+            }
+        }
+    }
+}
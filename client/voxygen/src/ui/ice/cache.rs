@@ -3,7 +3,7 @@ use crate::{
     error::Error,
     render::{Renderer, Texture, UiTextureBindGroup},
 };
-use common::assets::{self, AssetExt};
+use common::assets;
 use glyph_brush::GlyphBrushBuilder;
 use std::cell::{RefCell, RefMut};
 use vek::*;
@@ -21,15 +21,39 @@ type GlyphBrush = glyph_brush::GlyphBrush<(Aabr<f32>, Aabr<f32>), ()>;
 // TODO: might not need pub
 pub type Font = glyph_brush::ab_glyph::FontArc;
 
-pub fn load_font(specifier: &str) -> Font { Font::load_expect(specifier).cloned() }
+/// Large fonts only contain the glyphs requested so far, see
+/// `assets::font_data`
+pub fn load_font(specifier: &str) -> Font {
+    let data = assets::font_data(specifier).expect("Failed to load font");
+    Font::try_from_vec(data.to_vec()).expect("Invalid font")
+}
 
 #[derive(Clone, Copy, Default)]
 pub struct FontId(pub(super) glyph_brush::FontId);
 
+/// Adjustments to how text of a font is laid out, see `i18n::LayoutHints`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FontLayout {
+    pub line_height: f32,
+    pub letter_spacing: f32,
+}
+
+impl Default for FontLayout {
+    fn default() -> Self {
+        Self {
+            line_height: 1.0,
+            letter_spacing: 0.0,
+        }
+    }
+}
+
 pub struct Cache {
     glyph_brush: RefCell<GlyphBrush>,
-    glyph_cache_tex: (Texture, UiTextureBindGroup),
+    // `None` when running headless
+    glyph_cache_tex: Option<(Texture, UiTextureBindGroup)>,
     graphic_cache: GraphicCache,
+    // Indexed by font id
+    font_layouts: Vec<FontLayout>,
 }
 
 // TODO: Should functions be returning UiError instead of Error?
@@ -56,15 +80,41 @@ impl Cache {
 
         Ok(Self {
             glyph_brush: RefCell::new(glyph_brush),
-            glyph_cache_tex,
+            glyph_cache_tex: Some(glyph_cache_tex),
             graphic_cache: GraphicCache::new(renderer),
+            font_layouts: Vec::new(),
         })
     }
 
-    pub fn glyph_cache_tex(&self) -> &(Texture, UiTextureBindGroup) { &self.glyph_cache_tex }
+    /// Creates a cache that can be used for layout and primitive generation
+    /// without a renderer (e.g. for snapshot tests of the ui)
+    pub fn headless(default_font: Font) -> Self {
+        let glyph_brush = GlyphBrushBuilder::using_font(default_font)
+            .draw_cache_scale_tolerance(SCALE_TOLERANCE)
+            .draw_cache_position_tolerance(POSITION_TOLERANCE)
+            .build();
+
+        Self {
+            glyph_brush: RefCell::new(glyph_brush),
+            glyph_cache_tex: None,
+            graphic_cache: GraphicCache::headless(),
+            font_layouts: Vec::new(),
+        }
+    }
+
+    pub fn glyph_cache_tex(&self) -> &(Texture, UiTextureBindGroup) {
+        self.glyph_cache_tex
+            .as_ref()
+            .expect("Headless cache has no glyph cache texture")
+    }
 
     pub fn glyph_cache_mut_and_tex(&mut self) -> (&mut GlyphBrush, &(Texture, UiTextureBindGroup)) {
-        (self.glyph_brush.get_mut(), &self.glyph_cache_tex)
+        (
+            self.glyph_brush.get_mut(),
+            self.glyph_cache_tex
+                .as_ref()
+                .expect("Headless cache has no glyph cache texture"),
+        )
     }
 
     pub fn glyph_cache_mut(&mut self) -> &mut GlyphBrush { self.glyph_brush.get_mut() }
@@ -78,6 +128,21 @@ impl Cache {
         FontId(id)
     }
 
+    pub fn set_font_layout(&mut self, font: FontId, layout: FontLayout) {
+        let index = (font.0).0;
+        if self.font_layouts.len() <= index {
+            self.font_layouts.resize(index + 1, FontLayout::default());
+        }
+        self.font_layouts[index] = layout;
+    }
+
+    pub fn font_layout(&self, font: FontId) -> FontLayout {
+        self.font_layouts
+            .get((font.0).0)
+            .copied()
+            .unwrap_or_default()
+    }
+
     /// Allows clearing out the fonts when switching languages
     pub fn clear_fonts(&mut self, default_font: Font) {
         self.glyph_brush = RefCell::new(
@@ -91,6 +156,7 @@ impl Cache {
                 })
                 .build(),
         );
+        self.font_layouts.clear();
     }
 
     pub fn graphic_cache(&self) -> &GraphicCache { &self.graphic_cache }
@@ -123,11 +189,11 @@ impl Cache {
             .initial_cache_size((cache_dims.x, cache_dims.y))
             .build();
 
-        self.glyph_cache_tex = {
+        self.glyph_cache_tex = Some({
             let tex = renderer.create_dynamic_texture(cache_dims);
             let bind = renderer.ui_bind_texture(&tex);
             (tex, bind)
-        };
+        });
 
         Ok(())
     }
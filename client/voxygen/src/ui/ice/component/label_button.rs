@@ -0,0 +1,64 @@
+use crate::ui::{fonts::IcedFont, ice as ui};
+use iced::{Alignment, Element, Length};
+use iced::widget::{button::State, Button, Container, Row, Text};
+
+use ui::{
+    style::button::Style,
+    widget::{AspectRatioContainer, Image},
+};
+
+/// Space between the icon and the label
+const ICON_SPACING: u16 = 8;
+
+/// Button with a label of a fixed `size` and the icon of the style left of it
+///
+/// The label is laid out at the largest size the style gives it in any state,
+/// so a label growing on hover stays within the button.
+pub fn label_button<M: Clone + 'static>(
+    state: &mut State,
+    label: impl Into<String>,
+    font: &IcedFont,
+    size: u16,
+    button_style: Style,
+    message: Option<M>,
+) -> Element<M, ui::IcedRenderer> {
+    let label_size = (size as i32 + button_style.max_size_delta() as i32).max(1) as u16;
+    let mut content = Row::new()
+        .spacing(ICON_SPACING)
+        .align_items(Alignment::Center);
+    if let Some(icon) = button_style.icon_image() {
+        content = content.push(
+            Image::new(icon)
+                .width(Length::Units(label_size))
+                .height(Length::Units(label_size))
+                .fix_aspect_ratio()
+                .grayscale(message.is_none()),
+        );
+    }
+    content = content.push(Text::new(label).font(font.id).size(label_size));
+
+    let button = Button::new(
+        state,
+        Container::new(content)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y(),
+    )
+    .height(Length::Fill)
+    .width(Length::Fill)
+    .style(button_style);
+
+    let button = match message {
+        Some(message) => button.on_press(message),
+        None => button,
+    };
+
+    let container = AspectRatioContainer::new(button);
+    let container = match button_style.active().0 {
+        Some((img, _)) => container.ratio_of_image(img),
+        None => container,
+    };
+
+    container.into()
+}
@@ -1,6 +1,8 @@
 /// Various composable helpers for making iced ui's
+pub mod label_button;
 pub mod neat_button;
 pub mod tooltip;
 
+pub use label_button::label_button;
 pub use neat_button::neat_button;
-//pub use tooltip::WithTooltip;
+pub use tooltip::WithTooltip;
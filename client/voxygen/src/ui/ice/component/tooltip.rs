@@ -1,6 +1,6 @@
 use crate::ui::ice as ui;
 use iced::Element;
-use iced::widget::{Container, Text};
+use iced::widget::{Column, Container, Text};
 
 use ui::{
     style,
@@ -28,6 +28,32 @@ pub fn text<'a, M: 'a>(text: &'a str, style: Style) -> Element<'a, M, ui::IcedRe
     .into()
 }
 
+/// Tooltip with a title above lines of details in a smaller size
+pub fn rich<'a, M: 'a>(
+    title: impl Into<String>,
+    details: &[String],
+    style: Style,
+) -> Element<'a, M, ui::IcedRenderer> {
+    let detail_size = style.text_size * 4 / 5;
+    let title = Text::new(title)
+        .color(style.text_color)
+        .size(style.text_size);
+    let details = details.iter().map(|detail| {
+        Text::new(detail.as_str())
+            .color(style.text_color)
+            .size(detail_size)
+            .into()
+    });
+
+    Container::new(
+        Column::with_children(core::iter::once(title.into()).chain(details).collect())
+            .spacing(style.padding / 2),
+    )
+    .style(style.container)
+    .padding(style.padding)
+    .into()
+}
+
 pub trait WithTooltip<'a, M, R: ui::widget::tooltip::Renderer> {
     fn with_tooltip<H>(self, manager: &'a TooltipManager, hover_content: H) -> Tooltip<'a, M, R>
     where
@@ -4,7 +4,7 @@ pub mod component;
 mod renderer;
 pub mod widget;
 
-pub use cache::{load_font, Font, FontId, RawFont};
+pub use cache::{load_font, Font, FontId, FontLayout, RawFont};
 pub use graphic::{Id, Rotation};
 pub use iced::{Event, Cache};
 pub use iced::conversion::window_event;
@@ -16,7 +16,7 @@ use super::{
 };
 use crate::{
     error::Error,
-    render::{Renderer, ThirdPassDrawer},
+    render::{GlobalsBindGroup, OnCapture, Renderer, ThirdPassDrawer},
     window::Window,
 };
 use common::slowjob::SlowJobPool;
@@ -34,6 +34,8 @@ pub struct IcedUi {
     // Scaling of the ui
     scale: Scale,
     scale_changed: bool,
+    // Something changed since the last `maintain`
+    dirty: bool,
 }
 impl IcedUi {
     pub fn new(
@@ -62,24 +64,89 @@ impl IcedUi {
             cursor_position: Vec2::zero(),
             scale,
             scale_changed: false,
+            dirty: true,
         })
     }
 
+    /// Creates a ui that isn't attached to a window
+    /// It can't be rendered but `snapshot` can be used to inspect what would
+    /// be drawn
+    pub fn headless(
+        default_font: Font,
+        physical_resolution: Vec2<u32>,
+        scale_mode: ScaleMode,
+    ) -> Self {
+        let scale = Scale::new(physical_resolution, 1.0, scale_mode, 1.2);
+        let scaled_resolution = scale.scaled_resolution().map(|e| e as f32);
+
+        Self {
+            renderer: IcedRenderer::headless(
+                scaled_resolution,
+                physical_resolution,
+                default_font,
+            ),
+            cache: Some(Cache::new()),
+            events: Vec::new(),
+            cursor_position: Vec2::zero(),
+            scale,
+            scale_changed: false,
+            dirty: true,
+        }
+    }
+
     /// Add a new font that is referncable via the returned Id
-    pub fn add_font(&mut self, font: RawFont) -> FontId { self.renderer.add_font(font) }
+    pub fn add_font(&mut self, font: RawFont) -> FontId {
+        self.dirty = true;
+        self.renderer.add_font(font)
+    }
+
+    /// Like `add_font` but with adjusted line height and letter spacing
+    pub fn add_font_with_layout(&mut self, font: RawFont, layout: FontLayout) -> FontId {
+        let id = self.add_font(font);
+        self.renderer.set_font_layout(id, layout);
+        id
+    }
 
     /// Allows clearing out the fonts when switching languages
-    pub fn clear_fonts(&mut self, default_font: Font) { self.renderer.clear_fonts(default_font); }
+    pub fn clear_fonts(&mut self, default_font: Font) {
+        self.dirty = true;
+        self.renderer.clear_fonts(default_font);
+    }
 
     /// Add a new graphic that is referencable via the returned Id
     pub fn add_graphic(&mut self, graphic: Graphic) -> graphic::Id {
+        self.dirty = true;
         self.renderer.add_graphic(graphic)
     }
 
+    /// Packs the images into a few textures at once, see
+    /// [`graphic::GraphicCache::pack_graphics`]
+    pub fn pack_graphics(&mut self, renderer: &mut Renderer, ids: &[graphic::Id]) {
+        self.dirty = true;
+        self.renderer.pack_graphics(renderer, ids);
+    }
+
     pub fn replace_graphic(&mut self, id: graphic::Id, graphic: Graphic) {
+        self.dirty = true;
         self.renderer.replace_graphic(id, graphic);
     }
 
+    /// Whether `maintain` would produce something different than last time
+    /// Uis which only change on input can skip building their view and
+    /// calling `maintain` while this is false
+    pub fn needs_maintain(&self) -> bool { self.dirty || self.scale_changed }
+
+    /// Mark the ui as changed, e.g. when state used by the view was modified
+    /// outside of message handling
+    pub fn request_redraw(&mut self) { self.dirty = true; }
+
+    /// Toggles outlining the bounds of widgets, see
+    /// [`IcedRenderer::set_debug_bounds`]
+    pub fn toggle_debug_bounds(&mut self) {
+        self.renderer.set_debug_bounds(!self.renderer.debug_bounds());
+        self.dirty = true;
+    }
+
     pub fn scale(&self) -> Scale { self.scale }
 
     pub fn set_scaling_mode(&mut self, mode: ScaleMode) {
@@ -95,6 +162,7 @@ impl IcedUi {
 
     pub fn handle_event(&mut self, event: Event) {
         use iced::window;
+        self.dirty = true;
         match event {
             // Intercept resizing events
             // We check if the resolution of the renderer has changed to determine if a resize has
@@ -202,6 +270,7 @@ impl IcedUi {
         self.events.clear();
 
         
+        self.renderer.reset_debug_ids();
         let (primitive, mouse_interaction) =
             user_interface.draw(&mut self.renderer, cursor_position);
         
@@ -210,8 +279,81 @@ impl IcedUi {
 
         self.renderer.draw(primitive, renderer, pool);
 
+        // Handling the messages will change what the view looks like
+        self.dirty = !messages.is_empty();
+
         (messages, mouse_interaction)
     }
 
+    /// Equivalent of `maintain` for headless uis
+    /// Instead of drawing, the primitive tree is serialized into a stable
+    /// textual form that can be compared against stored snapshots
+    pub fn snapshot<'a, M, E: Into<Element<'a, M>>>(&mut self, root: E) -> (Vec<M>, String) {
+        let cursor_position = iced::Point {
+            x: self.cursor_position.x,
+            y: self.cursor_position.y,
+        };
+        let window_size = self.scale.scaled_resolution().map(|e| e as f32);
+        self.renderer.clear_recorded_text();
+
+        let mut user_interface = UserInterface::build(
+            root,
+            Size::new(window_size.x, window_size.y),
+            self.cache.take().unwrap(),
+            &mut self.renderer,
+        );
+
+        let mut messages = Vec::new();
+        user_interface.update(
+            &self.events,
+            cursor_position,
+            &self.renderer,
+            &mut iced::native::clipboard::Null,
+            &mut messages,
+        );
+        self.events.clear();
+
+        self.renderer.reset_debug_ids();
+        let (primitive, _) = user_interface.draw(&mut self.renderer, cursor_position);
+        self.cache = Some(user_interface.into_cache());
+
+        (messages, self.renderer.snapshot(&primitive))
+    }
+
+    /// Text drawn during the last `snapshot`
+    pub fn recorded_text(&self) -> impl Iterator<Item = &str> { self.renderer.recorded_text() }
+
     pub fn render<'a>(&'a self, drawer: &mut ThirdPassDrawer<'a>) { self.renderer.render(drawer); }
+
+    /// Draws the ui as it was last maintained into an offscreen target,
+    /// `on_capture` receives the image once it was read back from the gpu
+    pub fn capture_frame(
+        &self,
+        renderer: &mut Renderer,
+        globals: &GlobalsBindGroup,
+        on_capture: OnCapture,
+    ) {
+        let target = renderer.create_capture_target();
+        let mut encoder = renderer
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("ui capture encoder"),
+            });
+        match renderer.start_recording_frame(globals, &mut encoder, &target.view) {
+            Ok(Some(mut drawer)) => {
+                let mut third_pass = drawer.third_pass();
+                third_pass.init_ui();
+                self.render(&mut third_pass);
+            },
+            Ok(None) => {
+                on_capture(Err("The window is minimized".to_owned()));
+                return;
+            },
+            Err(e) => {
+                on_capture(Err(format!("{:?}", e)));
+                return;
+            },
+        }
+        renderer.read_capture(target, encoder, on_capture);
+    }
 }
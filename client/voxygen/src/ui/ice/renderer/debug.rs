@@ -0,0 +1,119 @@
+//! Outlines around widgets to debug layouts without print statements, see
+//! [`IcedRenderer::set_debug_bounds`]
+
+use super::{super::FontId, IcedRenderer, Primitive};
+use iced::{Horizontal, Rectangle, Vertical};
+use vek::Rgba;
+
+const OUTLINE_WIDTH: f32 = 1.0;
+const ID_SIZE: u16 = 12;
+const PLACEHOLDER_ALPHA: f32 = 0.15;
+
+/// What a debug outline marks, each kind gets its own color
+#[derive(Clone, Copy)]
+pub(super) enum Outline {
+    /// The bounds of a widget
+    Bounds,
+    /// The area inside of the padding of a widget
+    Content,
+    /// A widget without contents of its own, e.g. a space
+    Placeholder,
+    Image,
+}
+
+impl Outline {
+    fn linear_color(self) -> Rgba<f32> {
+        match self {
+            Self::Bounds => Rgba::new(1.0, 0.1, 0.1, 0.8),
+            Self::Content => Rgba::new(0.1, 0.4, 1.0, 0.8),
+            Self::Placeholder => Rgba::new(1.0, 0.9, 0.0, 0.8),
+            Self::Image => Rgba::new(0.1, 1.0, 0.3, 0.8),
+        }
+    }
+}
+
+impl IcedRenderer {
+    /// Draw the bounds, padding and ids of containers, spaces and images on
+    /// top of them, the ids count up in drawing order
+    pub fn set_debug_bounds(&mut self, enabled: bool) {
+        self.debug_ids = if enabled { Some(0) } else { None };
+    }
+
+    pub fn debug_bounds(&self) -> bool { self.debug_ids.is_some() }
+
+    /// Starts counting the ids from the beginning, called before each draw of
+    /// the ui so the ids stay the same between frames
+    pub fn reset_debug_ids(&mut self) {
+        if let Some(next_id) = &mut self.debug_ids {
+            *next_id = 0;
+        }
+    }
+
+    /// Adds the debug outlines to `primitive` when they are enabled, the first
+    /// outline is labeled with the id of the widget
+    pub(super) fn debug_outlines(
+        &mut self,
+        primitive: Primitive,
+        outlines: &[(Rectangle, Outline)],
+    ) -> Primitive {
+        let id = match &mut self.debug_ids {
+            Some(next_id) => {
+                *next_id += 1;
+                *next_id - 1
+            },
+            None => return primitive,
+        };
+
+        let mut primitives = vec![primitive];
+        for (bounds, outline) in outlines {
+            if let Outline::Placeholder = outline {
+                primitives.push(Primitive::Rectangle {
+                    bounds: *bounds,
+                    linear_color: Rgba {
+                        a: PLACEHOLDER_ALPHA,
+                        ..outline.linear_color()
+                    },
+                });
+            }
+            primitives.extend(outline_rectangles(*bounds, outline.linear_color()));
+        }
+        if let Some((bounds, outline)) = outlines.first() {
+            let id = id.to_string();
+            let glyphs = self.position_glyphs(
+                *bounds,
+                Horizontal::Left,
+                Vertical::Top,
+                &id,
+                ID_SIZE,
+                FontId::default(),
+            );
+            primitives.push(Primitive::Text {
+                glyphs,
+                bounds: *bounds,
+                linear_color: outline.linear_color(),
+            });
+        }
+
+        Primitive::Group { primitives }
+    }
+}
+
+fn outline_rectangles(bounds: Rectangle, linear_color: Rgba<f32>) -> [Primitive; 4] {
+    let width = OUTLINE_WIDTH.min(bounds.width / 2.0).min(bounds.height / 2.0);
+    let edge = |x, y, w, h| Primitive::Rectangle {
+        bounds: Rectangle {
+            x,
+            y,
+            width: w,
+            height: h,
+        },
+        linear_color,
+    };
+
+    [
+        edge(bounds.x, bounds.y, bounds.width, width),
+        edge(bounds.x, bounds.y + bounds.height - width, bounds.width, width),
+        edge(bounds.x, bounds.y, width, bounds.height),
+        edge(bounds.x + bounds.width - width, bounds.y, width, bounds.height),
+    ]
+}
@@ -1,12 +1,16 @@
 // TODO: expose to user
 pub struct Defaults {
     pub text_color: iced::Color,
+    /// Added to the size of text, e.g. to shrink the label of a button from
+    /// the size it was laid out at while it isn't hovered
+    pub text_size_delta: i16,
 }
 
 impl Default for Defaults {
     fn default() -> Self {
         Self {
             text_color: iced::Color::WHITE,
+            text_size_delta: 0,
         }
     }
 }
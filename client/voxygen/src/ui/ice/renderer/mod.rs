@@ -1,5 +1,7 @@
+mod debug;
 mod defaults;
 mod primitive;
+mod snapshot;
 pub mod style;
 mod widget;
 
@@ -11,7 +13,7 @@ use super::{
     super::graphic::{self, Graphic, TexId},
     cache::Cache,
     widget::image,
-    Font, FontId, RawFont, Rotation,
+    Font, FontId, FontLayout, RawFont, Rotation,
 };
 use crate::{
     error::Error,
@@ -74,6 +76,14 @@ enum State {
     Plain,
 }
 
+// Gpu resources used to draw the ui, not present when running headless
+struct Gpu {
+    // Model for drawing the ui
+    model: DynamicModel<UiVertex>,
+    // Consts for default ui drawing position (ie the interface)
+    interface_locals: UiBoundLocals,
+}
+
 // Optimization idea inspired by what I think iced wgpu renderer may be doing:
 // Could have layers of things which don't intersect and thus can be reordered
 // arbitrarily
@@ -81,12 +91,9 @@ enum State {
 pub struct IcedRenderer {
     //image_map: Map<(Image, Rotation)>,
     cache: Cache,
-    // Model for drawing the ui
-    model: DynamicModel<UiVertex>,
+    gpu: Option<Gpu>,
     // Consts to specify positions of ingame elements (e.g. Nametags)
     ingame_locals: Vec<UiBoundLocals>,
-    // Consts for default ui drawing position (ie the interface)
-    interface_locals: UiBoundLocals,
 
     // Used to delay cache resizing until after current frame is drawn
     //need_cache_resize: bool,
@@ -112,6 +119,10 @@ pub struct IcedRenderer {
     start: usize,
     // Draw commands for the next render
     draw_commands: Vec<DrawCommand>,
+    // Text drawn while headless, kept so snapshots can show the actual strings
+    headless_text: Vec<(iced::Rectangle, String)>,
+    // Id of the next widget outlined, `None` unless debugging the layout
+    debug_ids: Option<u32>,
 }
 impl IcedRenderer {
     pub fn new(
@@ -128,8 +139,10 @@ impl IcedRenderer {
         Ok(Self {
             cache: Cache::new(renderer, default_font)?,
             draw_commands: Vec::new(),
-            model: renderer.create_dynamic_model(100),
-            interface_locals,
+            gpu: Some(Gpu {
+                model: renderer.create_dynamic_model(100),
+                interface_locals,
+            }),
             ingame_locals: Vec::new(),
             mesh: Mesh::new(),
             glyphs: Vec::new(),
@@ -141,11 +154,49 @@ impl IcedRenderer {
             win_dims: scaled_resolution,
             window_scissor: default_scissor(physical_resolution),
             start: 0,
+            headless_text: Vec::new(),
+            debug_ids: None,
         })
     }
 
+    /// Creates a renderer which only produces primitives, nothing can be drawn
+    /// to the screen with it
+    pub fn headless(
+        scaled_resolution: Vec2<f32>,
+        physical_resolution: Vec2<u32>,
+        default_font: Font,
+    ) -> Self {
+        let (half_res, align, p_scale) =
+            Self::calculate_resolution_dependents(physical_resolution, scaled_resolution);
+
+        Self {
+            cache: Cache::headless(default_font),
+            draw_commands: Vec::new(),
+            gpu: None,
+            ingame_locals: Vec::new(),
+            mesh: Mesh::new(),
+            glyphs: Vec::new(),
+            last_glyph_verts: Vec::new(),
+            current_state: State::Plain,
+            half_res,
+            align,
+            p_scale,
+            win_dims: scaled_resolution,
+            window_scissor: default_scissor(physical_resolution),
+            start: 0,
+            headless_text: Vec::new(),
+            debug_ids: None,
+        }
+    }
+
+    pub fn is_headless(&self) -> bool { self.gpu.is_none() }
+
     pub fn add_font(&mut self, font: RawFont) -> FontId { self.cache.add_font(font) }
 
+    pub fn set_font_layout(&mut self, font: FontId, layout: FontLayout) {
+        self.cache.set_font_layout(font, layout);
+    }
+
     /// Allows clearing out the fonts when switching languages
     pub fn clear_fonts(&mut self, default_font: Font) { self.cache.clear_fonts(default_font); }
 
@@ -153,6 +204,10 @@ impl IcedRenderer {
         self.cache.add_graphic(graphic)
     }
 
+    pub fn pack_graphics(&mut self, renderer: &mut Renderer, ids: &[graphic::Id]) {
+        self.cache.graphic_cache_mut().pack_graphics(renderer, ids);
+    }
+
     pub fn replace_graphic(&mut self, id: graphic::Id, graphic: Graphic) {
         self.cache.replace_graphic(id, graphic);
     }
@@ -308,13 +363,17 @@ impl IcedRenderer {
             },
         }
 
+        let gpu = self
+            .gpu
+            .as_mut()
+            .expect("Headless renderer can't draw, use `snapshot` instead");
         // Create a larger dynamic model if the mesh is larger than the current model
         // size.
-        if self.model.len() < self.mesh.vertices().len() {
-            self.model = renderer.create_dynamic_model(self.mesh.vertices().len() * 4 / 3);
+        if gpu.model.len() < self.mesh.vertices().len() {
+            gpu.model = renderer.create_dynamic_model(self.mesh.vertices().len() * 4 / 3);
         }
         // Update model with new mesh.
-        renderer.update_model(&self.model, &self.mesh, 0);
+        renderer.update_model(&gpu.model, &self.mesh, 0);
     }
 
     // Returns (half_res, align)
@@ -400,7 +459,8 @@ impl IcedRenderer {
             }],
         };
 
-        self
+        let layout = self.cache.font_layout(font);
+        let mut glyphs: Vec<glyph_brush::SectionGlyph> = self
             .cache
             .glyph_cache_mut()
             .glyphs(section)
@@ -417,7 +477,11 @@ impl IcedRenderer {
                     .is_whitespace()
             })
             .cloned()
-            .collect()
+            .collect();
+        if layout != FontLayout::default() {
+            apply_font_layout(&mut glyphs, layout, p_scale);
+        }
+        glyphs
     }
 
     fn draw_primitive(
@@ -439,6 +503,7 @@ impl IcedRenderer {
                 bounds,
                 color,
                 source_rect,
+                grayscale,
             } => {
                 let color = srgba_to_linear(color.map(|e| e as f32 / 255.0));
                 let color = apply_alpha(color, alpha);
@@ -563,8 +628,12 @@ impl IcedRenderer {
                 // texture id was being used.
                 self.switch_state(State::Image(tex_id));
 
-                self.mesh
-                    .push_quad(create_ui_quad(gl_aabr, uv_aabr, color, UiMode::Image));
+                let mode = if grayscale {
+                    UiMode::ImageGrayscale
+                } else {
+                    UiMode::Image
+                };
+                self.mesh.push_quad(create_ui_quad(gl_aabr, uv_aabr, color, mode));
             },
             Primitive::Gradient {
                 bounds,
@@ -773,7 +842,11 @@ impl IcedRenderer {
     }
 
     pub fn render<'a>(&'a self,  drawer: &mut ThirdPassDrawer<'a>) {
-        drawer.ui_prepare(&self.interface_locals, &self.model, self.window_scissor);
+        let gpu = match &self.gpu {
+            Some(gpu) => gpu,
+            None => return,
+        };
+        drawer.ui_prepare(&gpu.interface_locals, &gpu.model, self.window_scissor);
         for draw_command in self.draw_commands.iter() {
             match draw_command {
                 DrawCommand::Scissor(new_scissor) => {
@@ -781,7 +854,7 @@ impl IcedRenderer {
                 },
                 DrawCommand::WorldPos(index) => {
                     drawer.ui_set_locals(
-                        index.map_or(&self.interface_locals, |i| &self.ingame_locals[i]),
+                        index.map_or(&gpu.interface_locals, |i| &self.ingame_locals[i]),
                     );
                 },
                 DrawCommand::Draw { kind, verts } => {
@@ -860,6 +933,27 @@ impl iced::Renderer for IcedRenderer {
     }
 }
 
+// Spread the lines and characters positioned by glyph_brush according to the
+// font's layout
+fn apply_font_layout(glyphs: &mut [glyph_brush::SectionGlyph], layout: FontLayout, p_scale: f32) {
+    let first_y = match glyphs.first() {
+        Some(glyph) => glyph.glyph.position.y,
+        None => return,
+    };
+    let mut line_y = first_y;
+    let mut column = 0;
+    for glyph in glyphs {
+        let position = &mut glyph.glyph.position;
+        if (position.y - line_y).abs() > 0.5 {
+            line_y = position.y;
+            column = 0;
+        }
+        position.x += column as f32 * layout.letter_spacing * p_scale;
+        position.y = first_y + (position.y - first_y) * layout.line_height;
+        column += 1;
+    }
+}
+
 fn apply_alpha(color: Rgba<f32>, alpha: f32) -> Rgba<f32> {
     Rgba {
         a: alpha * color.a,
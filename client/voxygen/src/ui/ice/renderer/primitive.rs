@@ -9,8 +9,11 @@ pub enum Primitive {
     Image {
         handle: (image::Handle, graphic::Rotation),
         bounds: iced::Rectangle,
+        // Tint, multiplied with the image
         color: vek::Rgba<u8>,
         source_rect: Option<vek::Aabr<f32>>,
+        // Drop the colors of the image before tinting it
+        grayscale: bool,
     },
     // A vertical gradient
     // TODO: could be combined with rectangle
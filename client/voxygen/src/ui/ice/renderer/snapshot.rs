@@ -0,0 +1,159 @@
+use super::{IcedRenderer, Primitive};
+use core::fmt::Write;
+use vek::*;
+
+impl IcedRenderer {
+    /// Remember the text drawn at `bounds` so it can be included in snapshots
+    /// Does nothing unless the renderer is headless
+    pub(super) fn record_text(&mut self, bounds: iced::Rectangle, content: &str) {
+        if self.is_headless() {
+            self.headless_text.push((bounds, content.to_owned()));
+        }
+    }
+
+    pub fn clear_recorded_text(&mut self) { self.headless_text.clear(); }
+
+    /// Serializes the primitive tree into a stable human readable format that
+    /// can be compared against stored snapshots
+    pub fn snapshot(&self, primitive: &Primitive) -> String {
+        let mut out = String::new();
+        write_primitive(&mut out, primitive, &self.headless_text, 0)
+            .expect("Writing to a String can't fail");
+        out
+    }
+
+    /// Text drawn since the recorded text was last cleared, in draw order
+    pub fn recorded_text(&self) -> impl Iterator<Item = &str> {
+        self.headless_text.iter().map(|(_, text)| text.as_str())
+    }
+}
+
+fn write_primitive(
+    out: &mut String,
+    primitive: &Primitive,
+    texts: &[(iced::Rectangle, String)],
+    depth: usize,
+) -> core::fmt::Result {
+    let indent = "  ".repeat(depth);
+    match primitive {
+        Primitive::Group { primitives } => {
+            writeln!(out, "{}Group", indent)?;
+            primitives
+                .iter()
+                .try_for_each(|p| write_primitive(out, p, texts, depth + 1))?;
+        },
+        Primitive::Image {
+            handle: (id, rotation),
+            bounds,
+            color,
+            source_rect,
+            grayscale,
+        } => {
+            write!(
+                out,
+                "{}Image {:?} {:?} {} color={}",
+                indent,
+                id,
+                rotation,
+                fmt_bounds(bounds),
+                fmt_rgba_u8(*color),
+            )?;
+            if *grayscale {
+                write!(out, " grayscale")?;
+            }
+            if let Some(source_rect) = source_rect {
+                write!(
+                    out,
+                    " source=[{:.1}, {:.1}, {:.1}, {:.1}]",
+                    source_rect.min.x, source_rect.min.y, source_rect.max.x, source_rect.max.y
+                )?;
+            }
+            writeln!(out)?;
+        },
+        Primitive::Gradient {
+            bounds,
+            top_linear_color,
+            bottom_linear_color,
+        } => writeln!(
+            out,
+            "{}Gradient {} top={} bottom={}",
+            indent,
+            fmt_bounds(bounds),
+            fmt_rgba_f32(*top_linear_color),
+            fmt_rgba_f32(*bottom_linear_color),
+        )?,
+        Primitive::Rectangle {
+            bounds,
+            linear_color,
+        } => writeln!(
+            out,
+            "{}Rectangle {} color={}",
+            indent,
+            fmt_bounds(bounds),
+            fmt_rgba_f32(*linear_color),
+        )?,
+        Primitive::Text {
+            glyphs,
+            bounds,
+            linear_color,
+        } => {
+            let text = texts
+                .iter()
+                .find(|(text_bounds, _)| text_bounds == bounds)
+                .map_or("<unknown>", |(_, text)| text.as_str());
+            writeln!(
+                out,
+                "{}Text {:?} glyphs={} {} color={}",
+                indent,
+                text,
+                glyphs.len(),
+                fmt_bounds(bounds),
+                fmt_rgba_f32(*linear_color),
+            )?
+        },
+        Primitive::Clip {
+            bounds,
+            offset,
+            content,
+        } => {
+            writeln!(
+                out,
+                "{}Clip {} offset=({}, {})",
+                indent,
+                fmt_bounds(bounds),
+                offset.x,
+                offset.y
+            )?;
+            write_primitive(out, content, texts, depth + 1)?;
+        },
+        Primitive::Opacity { alpha, content } => {
+            writeln!(out, "{}Opacity {:.3}", indent, alpha)?;
+            write_primitive(out, content, texts, depth + 1)?;
+        },
+        Primitive::Nothing => {},
+    }
+
+    Ok(())
+}
+
+// Rounded to one decimal so tiny float differences don't break snapshots
+fn fmt_bounds(bounds: &iced::Rectangle) -> String {
+    format!(
+        "[{:.1}, {:.1}, {:.1}x{:.1}]",
+        bounds.x, bounds.y, bounds.width, bounds.height
+    )
+}
+
+fn fmt_rgba_u8(color: Rgba<u8>) -> String {
+    format!(
+        "#{:02x}{:02x}{:02x}{:02x}",
+        color.r, color.g, color.b, color.a
+    )
+}
+
+fn fmt_rgba_f32(color: Rgba<f32>) -> String {
+    format!(
+        "({:.3}, {:.3}, {:.3}, {:.3})",
+        color.r, color.g, color.b, color.a
+    )
+}
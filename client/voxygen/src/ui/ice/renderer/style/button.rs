@@ -2,12 +2,70 @@ use super::super::super::widget::image;
 use iced::Color;
 use vek::Rgba;
 
+/// How the background image is drawn in one state of the button
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tint {
+    pub color: Rgba<u8>,
+    pub grayscale: bool,
+}
+
+impl Tint {
+    fn new(color: Rgba<u8>) -> Self {
+        Self {
+            color,
+            grayscale: false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ButtonState {
+    Normal,
+    Hover,
+    Press,
+    Disabled,
+}
+
+/// Text of the button in one state
+#[derive(Clone, Copy, Debug)]
+pub struct TextStyle {
+    pub color: Color,
+    /// Added to the font size of the button's text
+    pub size_delta: i16,
+}
+
+impl TextStyle {
+    fn new(color: Color) -> Self {
+        Self {
+            color,
+            size_delta: 0,
+        }
+    }
+
+    #[must_use]
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    #[must_use]
+    pub fn size_delta(mut self, size_delta: i16) -> Self {
+        self.size_delta = size_delta;
+        self
+    }
+
+}
+
 #[derive(Clone, Copy)]
 struct Background {
     default: image::Handle,
     hover: image::Handle,
     press: image::Handle,
     color: Rgba<u8>,
+    // Multiplied with `color` in the respective state
+    hover_tint: Tint,
+    press_tint: Tint,
+    disabled_tint: Tint,
 }
 
 impl Background {
@@ -17,16 +75,27 @@ impl Background {
             hover: image,
             press: image,
             color: Rgba::white(),
+            hover_tint: Tint::new(Rgba::white()),
+            press_tint: Tint::new(Rgba::white()),
+            disabled_tint: Tint::new(Rgba::white()),
         }
     }
+
+    fn tinted(&self, image: image::Handle, tint: Tint) -> (image::Handle, Tint) {
+        let color = self.color.map2(tint.color, |a, b| (a as u16 * b as u16 / 255) as u8);
+        (image, Tint { color, ..tint })
+    }
 }
 // TODO: consider a different place for this
 // Note: for now all buttons have an image background
 #[derive(Clone, Copy)]
 pub struct Style {
     background: Option<Background>,
-    enabled_text: Color,
-    disabled_text: Color,
+    normal_text: TextStyle,
+    hover_text: TextStyle,
+    press_text: TextStyle,
+    disabled_text: TextStyle,
+    icon: Option<image::Handle>,
 }
 
 impl Style {
@@ -71,43 +140,129 @@ impl Style {
         self
     }
 
+    /// Tint the image while hovered, so one image can serve several states
+    #[must_use]
+    pub fn hover_tint(mut self, color: Rgba<u8>) -> Self {
+        if let Some(background) = &mut self.background {
+            background.hover_tint.color = color;
+        }
+        self
+    }
+
+    #[must_use]
+    pub fn press_tint(mut self, color: Rgba<u8>) -> Self {
+        if let Some(background) = &mut self.background {
+            background.press_tint.color = color;
+        }
+        self
+    }
+
+    #[must_use]
+    pub fn disabled_tint(mut self, color: Rgba<u8>) -> Self {
+        if let Some(background) = &mut self.background {
+            background.disabled_tint.color = color;
+        }
+        self
+    }
+
+    /// Draw the image in grayscale while disabled
+    #[must_use]
+    pub fn disabled_grayscale(mut self) -> Self {
+        if let Some(background) = &mut self.background {
+            background.disabled_tint.grayscale = true;
+        }
+        self
+    }
+
+    /// Text color of all states but disabled
     #[must_use]
     pub fn text_color(mut self, color: Color) -> Self {
-        self.enabled_text = color;
+        self.normal_text.color = color;
+        self.hover_text.color = color;
+        self.press_text.color = color;
         self
     }
 
     #[must_use]
     pub fn disabled_text_color(mut self, color: Color) -> Self {
-        self.disabled_text = color;
+        self.disabled_text.color = color;
+        self
+    }
+
+    /// Change the text of one state, e.g.
+    /// `.text(ButtonState::Hover, |text| text.color(HIGHLIGHT).size_delta(2))`
+    #[must_use]
+    pub fn text(mut self, state: ButtonState, f: impl FnOnce(TextStyle) -> TextStyle) -> Self {
+        let text = match state {
+            ButtonState::Normal => &mut self.normal_text,
+            ButtonState::Hover => &mut self.hover_text,
+            ButtonState::Press => &mut self.press_text,
+            ButtonState::Disabled => &mut self.disabled_text,
+        };
+        *text = f(*text);
         self
     }
 
-    pub fn disabled(&self) -> (Option<(image::Handle, Rgba<u8>)>, Color) {
+    /// Icon shown left of the label, see [`label_button`]
+    ///
+    /// [`label_button`]: crate::ui::ice::component::label_button
+    #[must_use]
+    pub fn icon(mut self, icon: image::Handle) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    pub fn icon_image(&self) -> Option<image::Handle> { self.icon }
+
+    /// The largest size delta of all states, labels are laid out at this size
+    /// so growing them doesn't overflow the button
+    pub fn max_size_delta(&self) -> i16 {
+        [
+            self.normal_text,
+            self.hover_text,
+            self.press_text,
+            self.disabled_text,
+        ]
+        .iter()
+        .map(|text| text.size_delta)
+        .max()
+        .unwrap_or(0)
+        .max(0)
+    }
+
+    pub fn disabled(&self) -> (Option<(image::Handle, Tint)>, TextStyle) {
         (
-            self.background.as_ref().map(|b| (b.default, b.color)),
+            self.background
+                .as_ref()
+                .map(|b| b.tinted(b.default, b.disabled_tint)),
             self.disabled_text,
         )
     }
 
-    pub fn pressed(&self) -> (Option<(image::Handle, Rgba<u8>)>, Color) {
+    pub fn pressed(&self) -> (Option<(image::Handle, Tint)>, TextStyle) {
         (
-            self.background.as_ref().map(|b| (b.press, b.color)),
-            self.enabled_text,
+            self.background
+                .as_ref()
+                .map(|b| b.tinted(b.press, b.press_tint)),
+            self.press_text,
         )
     }
 
-    pub fn hovered(&self) -> (Option<(image::Handle, Rgba<u8>)>, Color) {
+    pub fn hovered(&self) -> (Option<(image::Handle, Tint)>, TextStyle) {
         (
-            self.background.as_ref().map(|b| (b.hover, b.color)),
-            self.enabled_text,
+            self.background
+                .as_ref()
+                .map(|b| b.tinted(b.hover, b.hover_tint)),
+            self.hover_text,
         )
     }
 
-    pub fn active(&self) -> (Option<(image::Handle, Rgba<u8>)>, Color) {
+    pub fn active(&self) -> (Option<(image::Handle, Tint)>, TextStyle) {
         (
-            self.background.as_ref().map(|b| (b.default, b.color)),
-            self.enabled_text,
+            self.background
+                .as_ref()
+                .map(|b| b.tinted(b.default, Tint::new(Rgba::white()))),
+            self.normal_text,
         )
     }
 }
@@ -116,8 +271,11 @@ impl Default for Style {
     fn default() -> Self {
         Self {
             background: None,
-            enabled_text: Color::WHITE,
-            disabled_text: Color::from_rgb(0.5, 0.5, 0.5),
+            normal_text: TextStyle::new(Color::WHITE),
+            hover_text: TextStyle::new(Color::WHITE),
+            press_text: TextStyle::new(Color::WHITE),
+            disabled_text: TextStyle::new(Color::from_rgb(0.5, 0.5, 0.5)),
+            icon: None,
         }
     }
 }
@@ -0,0 +1,54 @@
+use super::button;
+use vek::Rgba;
+
+#[derive(Clone, Copy)]
+pub struct Style {
+    /// The closed dropdown, drawn pressed while the list is open
+    pub header: button::Style,
+    pub option: button::Style,
+    pub selected_option: button::Style,
+    /// Behind the options of the open list
+    pub menu_color: Rgba<u8>,
+    pub scroller_color: Rgba<u8>,
+}
+
+impl Style {
+    pub fn new(header: button::Style, option: button::Style) -> Self {
+        Self {
+            header,
+            option,
+            selected_option: option,
+            ..Default::default()
+        }
+    }
+
+    #[must_use]
+    pub fn selected_option(mut self, style: button::Style) -> Self {
+        self.selected_option = style;
+        self
+    }
+
+    #[must_use]
+    pub fn menu_color(mut self, color: Rgba<u8>) -> Self {
+        self.menu_color = color;
+        self
+    }
+
+    #[must_use]
+    pub fn scroller_color(mut self, color: Rgba<u8>) -> Self {
+        self.scroller_color = color;
+        self
+    }
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            header: Default::default(),
+            option: Default::default(),
+            selected_option: Default::default(),
+            menu_color: Rgba::new(0, 0, 0, 230),
+            scroller_color: Rgba::new(128, 128, 128, 255),
+        }
+    }
+}
@@ -1,5 +1,6 @@
 pub mod button;
 pub mod checkbox;
 pub mod container;
+pub mod dropdown;
 pub mod scrollable;
 pub mod slider;
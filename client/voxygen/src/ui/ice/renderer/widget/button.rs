@@ -21,7 +21,7 @@ impl button::Renderer for IcedRenderer {
     ) -> Self::Output {
         let is_mouse_over = bounds.contains(cursor_position);
 
-        let (maybe_image, text_color) = if is_disabled {
+        let (maybe_image, text) = if is_disabled {
             style.disabled()
         } else if is_mouse_over {
             if is_pressed {
@@ -33,20 +33,25 @@ impl button::Renderer for IcedRenderer {
             style.active()
         };
 
+        // The label was laid out at the largest size of all states
         let (content, _) = content.draw(
             self,
-            &Defaults { text_color },
+            &Defaults {
+                text_color: text.color,
+                text_size_delta: text.size_delta - style.max_size_delta(),
+            },
             content_layout,
             cursor_position,
             &bounds,
         );
 
-        let primitive = if let Some((handle, color)) = maybe_image {
+        let primitive = if let Some((handle, tint)) = maybe_image {
             let background = Primitive::Image {
                 handle: (handle, Rotation::None),
                 bounds,
-                color,
+                color: tint.color,
                 source_rect: None,
+                grayscale: tint.grayscale,
             };
 
             Primitive::Group {
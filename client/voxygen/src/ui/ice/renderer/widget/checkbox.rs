@@ -35,6 +35,7 @@ impl checkbox::Renderer for IcedRenderer {
                 bounds,
                 color: vek::Rgba::broadcast(255),
                 source_rect: None,
+                grayscale: false,
             })
             .unwrap_or_else(default_rect);
 
@@ -48,6 +49,7 @@ impl checkbox::Renderer for IcedRenderer {
                             bounds,
                             color: vek::Rgba::broadcast(255),
                             source_rect: None,
+                            grayscale: false,
                         })
                         .unwrap_or_else(default_rect);
 
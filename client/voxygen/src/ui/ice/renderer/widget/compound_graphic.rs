@@ -20,6 +20,7 @@ impl compound_graphic::Renderer for IcedRenderer {
                             bounds,
                             color,
                             source_rect: None,
+                            grayscale: false,
                         },
                         GraphicKind::Color(color) => Primitive::Rectangle {
                             bounds,
@@ -1,4 +1,4 @@
-use super::super::{super::Rotation, style, IcedRenderer, Primitive};
+use super::super::{super::Rotation, debug::Outline, style, IcedRenderer, Primitive};
 use common::util::srgba_to_linear;
 use iced::{Element, Layout, Point, Rectangle};
 use iced::widget::container;
@@ -21,6 +21,7 @@ impl container::Renderer for IcedRenderer {
         content: &Element<'_, M, Self>,
         content_layout: Layout<'_>,
     ) -> Self::Output {
+        let content_bounds = content_layout.bounds();
         let (content, mouse_interaction) =
             content.draw(self, defaults, content_layout, cursor_position, viewport);
 
@@ -31,6 +32,7 @@ impl container::Renderer for IcedRenderer {
                     bounds,
                     color: *color,
                     source_rect: None,
+                    grayscale: false,
                 };
 
                 Primitive::Group {
@@ -180,6 +182,7 @@ impl container::Renderer for IcedRenderer {
                             },
                             color,
                             source_rect: None,
+                            grayscale: false,
                         };
 
                         let tr_corner = Primitive::Image {
@@ -192,6 +195,7 @@ impl container::Renderer for IcedRenderer {
                             },
                             color,
                             source_rect: None,
+                            grayscale: false,
                         };
 
                         let bl_corner = Primitive::Image {
@@ -204,6 +208,7 @@ impl container::Renderer for IcedRenderer {
                             },
                             color,
                             source_rect: None,
+                            grayscale: false,
                         };
 
                         let br_corner = Primitive::Image {
@@ -216,6 +221,7 @@ impl container::Renderer for IcedRenderer {
                             },
                             color,
                             source_rect: None,
+                            grayscale: false,
                         };
 
                         let top_edge = Primitive::Image {
@@ -228,6 +234,7 @@ impl container::Renderer for IcedRenderer {
                             },
                             color,
                             source_rect: None,
+                            grayscale: false,
                         };
 
                         let bottom_edge = Primitive::Image {
@@ -240,6 +247,7 @@ impl container::Renderer for IcedRenderer {
                             },
                             color,
                             source_rect: None,
+                            grayscale: false,
                         };
 
                         let left_edge = Primitive::Image {
@@ -252,6 +260,7 @@ impl container::Renderer for IcedRenderer {
                             },
                             color,
                             source_rect: None,
+                            grayscale: false,
                         };
 
                         let right_edge = Primitive::Image {
@@ -264,6 +273,7 @@ impl container::Renderer for IcedRenderer {
                             },
                             color,
                             source_rect: None,
+                            grayscale: false,
                         };
 
                         // Is this worth it as opposed to using a giant image? (Probably)
@@ -286,6 +296,10 @@ impl container::Renderer for IcedRenderer {
             },
             Self::Style::None => content,
         };
+        let prim = self.debug_outlines(prim, &[
+            (bounds, Outline::Bounds),
+            (content_bounds, Outline::Content),
+        ]);
 
         (prim, mouse_interaction)
     }
@@ -0,0 +1,228 @@
+use super::super::{
+    super::{
+        widget::{dropdown, image},
+        FontId, Rotation,
+    },
+    style, Defaults, IcedRenderer, Primitive,
+};
+use common::util::srgba_to_linear;
+use iced::{mouse, Element, Horizontal, Layout, Point, Rectangle, Vertical};
+use vek::Rgba;
+
+/// Space left of the text of an option
+const TEXT_PADDING: f32 = 10.0;
+/// Width of the icon of an option compared to its height, fits flags
+const ICON_ASPECT: f32 = 1.5;
+/// Height of the icon of an option compared to the height of the row
+const ICON_HEIGHT: f32 = 0.6;
+const SCROLLER_WIDTH: f32 = 4.0;
+
+impl IcedRenderer {
+    /// Background and text of the dropdown itself or one of its options
+    fn dropdown_row(
+        &mut self,
+        defaults: &Defaults,
+        bounds: Rectangle,
+        label: &str,
+        icon: Option<image::Handle>,
+        (maybe_image, text): (
+            Option<(image::Handle, style::button::Tint)>,
+            style::button::TextStyle,
+        ),
+        text_size: u16,
+        font: FontId,
+    ) -> Primitive {
+        self.record_text(bounds, label);
+        let size = text_size as i32 + defaults.text_size_delta as i32 + text.size_delta as i32;
+        let icon = icon.map(|handle| {
+            let height = bounds.height * ICON_HEIGHT;
+            let bounds = Rectangle {
+                x: bounds.x + TEXT_PADDING,
+                y: bounds.y + (bounds.height - height) / 2.0,
+                width: height * ICON_ASPECT,
+                height,
+            };
+            Primitive::Image {
+                handle: (handle, Rotation::None),
+                bounds,
+                color: Rgba::white(),
+                source_rect: None,
+                grayscale: false,
+            }
+        });
+        let text_x = match &icon {
+            Some(Primitive::Image { bounds, .. }) => bounds.x + bounds.width + TEXT_PADDING,
+            _ => bounds.x + TEXT_PADDING,
+        };
+        let text_bounds = Rectangle {
+            x: text_x,
+            width: (bounds.x + bounds.width - text_x).max(0.0),
+            ..bounds
+        };
+        let glyphs = self.position_glyphs(
+            text_bounds,
+            Horizontal::Left,
+            Vertical::Center,
+            label,
+            size.max(1) as u16,
+            font,
+        );
+        let text = Primitive::Text {
+            glyphs,
+            bounds: text_bounds,
+            linear_color: text.color.into_linear().into(),
+        };
+
+        let background = maybe_image.map(|(handle, tint)| Primitive::Image {
+            handle: (handle, Rotation::None),
+            bounds,
+            color: tint.color,
+            source_rect: None,
+            grayscale: tint.grayscale,
+        });
+        Primitive::Group {
+            primitives: background.into_iter().chain(icon).chain(Some(text)).collect(),
+        }
+    }
+}
+
+impl dropdown::Renderer for IcedRenderer {
+    type Style = style::dropdown::Style;
+
+    fn draw(
+        &mut self,
+        defaults: &Self::Defaults,
+        bounds: Rectangle,
+        cursor_position: Point,
+        label: Option<&str>,
+        icon: Option<image::Handle>,
+        is_open: bool,
+        text_size: u16,
+        font: Self::Font,
+        style: &Self::Style,
+    ) -> Self::Output {
+        let is_mouse_over = bounds.contains(cursor_position);
+
+        let state_style = if is_open {
+            style.header.pressed()
+        } else if is_mouse_over {
+            style.header.hovered()
+        } else {
+            style.header.active()
+        };
+
+        let primitive = self.dropdown_row(
+            defaults,
+            bounds,
+            label.unwrap_or_default(),
+            icon,
+            state_style,
+            text_size,
+            font,
+        );
+
+        let mouse_interaction = if is_mouse_over {
+            mouse::Interaction::Pointer
+        } else {
+            mouse::Interaction::default()
+        };
+
+        (primitive, mouse_interaction)
+    }
+
+    fn draw_menu(
+        &mut self,
+        defaults: &Self::Defaults,
+        bounds: Rectangle,
+        cursor_position: Point,
+        options: &[String],
+        icons: &[Option<image::Handle>],
+        first: usize,
+        total: usize,
+        hovered: Option<usize>,
+        selected: Option<usize>,
+        row_height: f32,
+        text_size: u16,
+        font: Self::Font,
+        style: &Self::Style,
+    ) -> Self::Output {
+        let mut primitives = vec![Primitive::Rectangle {
+            bounds,
+            linear_color: srgba_to_linear(style.menu_color.map(|e| e as f32 / 255.0)),
+        }];
+
+        let is_scrollable = options.len() < total;
+        let row_width = if is_scrollable {
+            bounds.width - SCROLLER_WIDTH
+        } else {
+            bounds.width
+        };
+
+        for (i, label) in options.iter().enumerate() {
+            let index = first + i;
+            let row_style = if Some(index) == selected {
+                &style.selected_option
+            } else {
+                &style.option
+            };
+            let state_style = if Some(index) == hovered {
+                row_style.hovered()
+            } else {
+                row_style.active()
+            };
+            let row_bounds = Rectangle {
+                x: bounds.x,
+                y: bounds.y + i as f32 * row_height,
+                width: row_width,
+                height: row_height,
+            };
+            primitives.push(self.dropdown_row(
+                defaults,
+                row_bounds,
+                label,
+                icons.get(i).copied().flatten(),
+                state_style,
+                text_size,
+                font,
+            ));
+        }
+
+        if is_scrollable {
+            let height = bounds.height * options.len() as f32 / total as f32;
+            primitives.push(Primitive::Rectangle {
+                bounds: Rectangle {
+                    x: bounds.x + row_width,
+                    y: bounds.y + bounds.height * first as f32 / total as f32,
+                    width: SCROLLER_WIDTH,
+                    height,
+                },
+                linear_color: srgba_to_linear(style.scroller_color.map(|e| e as f32 / 255.0)),
+            });
+        }
+
+        let mouse_interaction = if bounds.contains(cursor_position) {
+            mouse::Interaction::Pointer
+        } else {
+            mouse::Interaction::default()
+        };
+
+        (Primitive::Group { primitives }, mouse_interaction)
+    }
+
+    fn draw_menu_tooltip<M>(
+        &mut self,
+        (menu, mouse_interaction): Self::Output,
+        defaults: &Self::Defaults,
+        cursor_position: Point,
+        tooltip: &Element<'_, M, Self>,
+        layout: Layout<'_>,
+    ) -> Self::Output {
+        let (tooltip, _) =
+            tooltip.draw(self, defaults, layout, cursor_position, &layout.bounds());
+        (
+            Primitive::Group {
+                primitives: vec![menu, tooltip],
+            },
+            mouse_interaction,
+        )
+    }
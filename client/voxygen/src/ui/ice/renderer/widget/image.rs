@@ -1,5 +1,6 @@
 use super::super::{
     super::{widget::image, Rotation},
+    debug::Outline,
     IcedRenderer, Primitive,
 };
 use iced::mouse;
@@ -12,15 +13,19 @@ impl image::Renderer for IcedRenderer {
         &mut self,
         handle: image::Handle,
         color: Rgba<u8>,
+        grayscale: bool,
         layout: iced::Layout<'_>,
     ) -> Self::Output {
+        let bounds = layout.bounds();
+        let image = Primitive::Image {
+            handle: (handle, Rotation::None),
+            bounds,
+            color,
+            source_rect: None,
+            grayscale,
+        };
         (
-            Primitive::Image {
-                handle: (handle, Rotation::None),
-                bounds: layout.bounds(),
-                color,
-                source_rect: None,
-            },
+            self.debug_outlines(image, &[(bounds, Outline::Image)]),
             mouse::Interaction::default(),
         )
     }
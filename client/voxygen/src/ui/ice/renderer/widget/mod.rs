@@ -5,9 +5,12 @@ mod checkbox;
 mod column;
 mod compound_graphic;
 mod container;
+mod dropdown;
 mod image;
+mod modal;
 mod mouse_detector;
 mod overlay;
+mod responsive;
 mod row;
 mod scrollable;
 mod slider;
@@ -0,0 +1,39 @@
+use super::super::{super::widget::modal, IcedRenderer, Primitive};
+use common::util::srgba_to_linear;
+use iced::{Element, Layout, Point, Rectangle};
+use vek::Rgba;
+
+impl modal::Renderer for IcedRenderer {
+    fn draw<M>(
+        &mut self,
+        defaults: &Self::Defaults,
+        bounds: Rectangle,
+        cursor_position: Point,
+        viewport: &Rectangle,
+        backdrop: Rgba<u8>,
+        content: &Element<'_, M, Self>,
+        content_layout: Layout<'_>,
+        dialog: Option<(&Element<'_, M, Self>, Layout<'_>)>,
+    ) -> Self::Output {
+        let (dialog, dialog_layout) = match dialog {
+            Some(dialog) => dialog,
+            None => return content.draw(self, defaults, content_layout, cursor_position, viewport),
+        };
+
+        // Nothing below the dialog reacts to the cursor
+        let (content, _) =
+            content.draw(self, defaults, content_layout, Point::new(-1.0, -1.0), viewport);
+        let backdrop = Primitive::Rectangle {
+            bounds,
+            linear_color: srgba_to_linear(backdrop.map(|e| e as f32 / 255.0)),
+        };
+        let (dialog, mouse_interaction) =
+            dialog.draw(self, defaults, dialog_layout, cursor_position, viewport);
+
+        let prim = Primitive::Group {
+            primitives: vec![content, backdrop, dialog],
+        };
+
+        (prim, mouse_interaction)
+    }
+}
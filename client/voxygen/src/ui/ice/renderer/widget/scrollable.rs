@@ -129,6 +129,7 @@ impl scrollable::Renderer for IcedRenderer {
                                 },
                                 color,
                                 source_rect: None,
+                                grayscale: false,
                             });
                             // Middle
                             primitives.push(Primitive::Image {
@@ -140,6 +141,7 @@ impl scrollable::Renderer for IcedRenderer {
                                 },
                                 color,
                                 source_rect: None,
+                                grayscale: false,
                             });
                             // Bottom
                             primitives.push(Primitive::Image {
@@ -151,6 +153,7 @@ impl scrollable::Renderer for IcedRenderer {
                                 },
                                 color,
                                 source_rect: None,
+                                grayscale: false,
                             });
                         },
                     }
@@ -167,6 +170,7 @@ impl scrollable::Renderer for IcedRenderer {
                             bounds: scrollbar.bounds,
                             color,
                             source_rect: None,
+                            grayscale: false,
                         },
                     });
                 }
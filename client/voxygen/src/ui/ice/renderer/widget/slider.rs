@@ -41,6 +41,7 @@ impl slider::Renderer for IcedRenderer {
                 },
                 color,
                 source_rect: None,
+                grayscale: false,
             },
         };
 
@@ -66,6 +67,7 @@ impl slider::Renderer for IcedRenderer {
                 bounds: cursor_bounds,
                 color,
                 source_rect: None,
+                grayscale: false,
             },
         };
 
@@ -1,8 +1,11 @@
-use super::super::{IcedRenderer, Primitive};
+use super::super::{debug::Outline, IcedRenderer, Primitive};
 use iced::{mouse, widget::space, Rectangle};
 
 impl space::Renderer for IcedRenderer {
-    fn draw(&mut self, _bounds: Rectangle) -> Self::Output {
-        (Primitive::Nothing, mouse::Interaction::default())
+    fn draw(&mut self, bounds: Rectangle) -> Self::Output {
+        (
+            self.debug_outlines(Primitive::Nothing, &[(bounds, Outline::Placeholder)]),
+            mouse::Interaction::default(),
+        )
     }
 }
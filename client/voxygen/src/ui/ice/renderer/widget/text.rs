@@ -25,9 +25,15 @@ impl text::Renderer for IcedRenderer {
             }],
         };
 
+        let layout = self.cache.font_layout(font);
         let maybe_rect = self.cache.glyph_calculator().glyph_bounds(section);
         maybe_rect.map_or((0.0, 0.0), |rect| {
-            (rect.width() / p_scale, rect.height() / p_scale)
+            // Approximation, assumes the longest line is the whole content
+            let spacing = content.chars().count().saturating_sub(1) as f32 * layout.letter_spacing;
+            (
+                rect.width() / p_scale + spacing,
+                rect.height() / p_scale * layout.line_height,
+            )
         })
     }
 
@@ -42,6 +48,8 @@ impl text::Renderer for IcedRenderer {
         horizontal_alignment: Horizontal,
         vertical_alignment: Vertical,
     ) -> Self::Output {
+        self.record_text(bounds, content);
+        let size = (size as i32 + defaults.text_size_delta as i32).max(1) as u16;
         let glyphs = self.position_glyphs(
             bounds,
             horizontal_alignment,
@@ -13,6 +13,9 @@ const EXTRA_OFFSET: f32 = 10.0;
 impl text_input::Renderer for IcedRenderer {
     type Style = ();
 
+    // Not all of the game fonts have a glyph for the default dot
+    fn mask(&self) -> char { '*' }
+
     fn measure_value(&self, value: &str, size: u16, font: Self::Font) -> f32 {
         // Using the physical scale might make this cached info usable below?
         // Although we also have a position of the screen there so this could be useless
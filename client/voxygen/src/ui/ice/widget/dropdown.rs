@@ -0,0 +1,562 @@
+use iced::{
+    keyboard, layout, mouse, Element, Event, Hasher, Layout, Length, Point, Rectangle, Size,
+    Widget,
+};
+use super::image;
+use std::hash::Hash;
+
+const DEFAULT_ROW_HEIGHT: u16 = 30;
+const DEFAULT_MAX_ROWS: usize = 8;
+/// Space between the open list and the tooltip of an option
+const TOOLTIP_PADDING: f32 = 8.0;
+
+#[derive(Debug, Default)]
+pub struct State {
+    is_open: bool,
+    /// Option under the mouse or picked with the arrow keys
+    hovered: Option<usize>,
+    /// First option shown in the list
+    scroll: usize,
+}
+
+impl State {
+    pub fn is_open(&self) -> bool { self.is_open }
+
+    fn open(&mut self, selected: Option<usize>) {
+        self.is_open = true;
+        self.hovered = selected;
+        // Clamped to the option count once the list size is known
+        self.scroll = selected.unwrap_or(0);
+    }
+
+    fn close(&mut self) {
+        self.is_open = false;
+        self.hovered = None;
+    }
+}
+
+/// Shows the selected option, clicking it opens a scrollable list of all
+/// options on top of the other widgets
+pub struct Dropdown<'a, M, R: self::Renderer> {
+    state: &'a mut State,
+    options: Vec<String>,
+    /// Drawn left of the label of the option with the same index
+    icons: Vec<Option<image::Handle>>,
+    selected: Option<usize>,
+    on_selected: Box<dyn Fn(usize) -> M + 'a>,
+    width: Length,
+    row_height: u16,
+    /// Longer lists are scrolled
+    max_rows: usize,
+    text_size: Option<u16>,
+    font: R::Font,
+    style: <R as self::Renderer>::Style,
+    /// Shown next to the hovered option of the open list
+    option_tooltip: Option<Box<dyn Fn(usize) -> Element<'a, M, R> + 'a>>,
+}
+
+impl<'a, M, R: self::Renderer> Dropdown<'a, M, R> {
+    pub fn new<F>(
+        state: &'a mut State,
+        options: Vec<String>,
+        selected: Option<usize>,
+        on_selected: F,
+    ) -> Self
+    where
+        F: 'a + Fn(usize) -> M,
+    {
+        Self {
+            state,
+            options,
+            icons: Vec::new(),
+            selected,
+            on_selected: Box::new(on_selected),
+            width: Length::Fill,
+            row_height: DEFAULT_ROW_HEIGHT,
+            max_rows: DEFAULT_MAX_ROWS,
+            text_size: None,
+            font: Default::default(),
+            style: Default::default(),
+            option_tooltip: None,
+        }
+    }
+
+    #[must_use]
+    pub fn width(mut self, width: Length) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Height of the dropdown and of each option in the list
+    #[must_use]
+    pub fn row_height(mut self, row_height: u16) -> Self {
+        self.row_height = row_height;
+        self
+    }
+
+    #[must_use]
+    pub fn max_rows(mut self, max_rows: usize) -> Self {
+        self.max_rows = max_rows.max(1);
+        self
+    }
+
+    #[must_use]
+    pub fn text_size(mut self, size: u16) -> Self {
+        self.text_size = Some(size);
+        self
+    }
+
+    #[must_use]
+    pub fn font(mut self, font: R::Font) -> Self {
+        self.font = font;
+        self
+    }
+
+    #[must_use]
+    pub fn style(mut self, style: impl Into<<R as self::Renderer>::Style>) -> Self {
+        self.style = style.into();
+        self
+    }
+
+    /// Images drawn left of the labels, in the order of the options
+    #[must_use]
+    pub fn icons(mut self, icons: Vec<Option<image::Handle>>) -> Self {
+        self.icons = icons;
+        self
+    }
+
+    /// Shows the element returned for the hovered option next to the open
+    /// list
+    #[must_use]
+    pub fn option_tooltip<F>(mut self, tooltip: F) -> Self
+    where
+        F: 'a + Fn(usize) -> Element<'a, M, R>,
+    {
+        self.option_tooltip = Some(Box::new(tooltip));
+        self
+    }
+}
+
+impl<'a, M, R> Widget<M, R> for Dropdown<'a, M, R>
+where
+    R: self::Renderer,
+{
+    fn width(&self) -> Length { self.width }
+
+    fn height(&self) -> Length { Length::Units(self.row_height) }
+
+    fn layout(&self, _renderer: &R, limits: &layout::Limits) -> layout::Node {
+        let limits = limits
+            .width(self.width)
+            .height(Length::Units(self.row_height));
+
+        layout::Node::new(limits.resolve(Size::ZERO))
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut R,
+        defaults: &R::Defaults,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        _viewport: &Rectangle,
+    ) -> R::Output {
+        renderer.draw(
+            defaults,
+            layout.bounds(),
+            cursor_position,
+            self.selected
+                .and_then(|i| self.options.get(i))
+                .map(String::as_str),
+            self.selected.and_then(|i| self.icons.get(i).copied().flatten()),
+            self.state.is_open,
+            self.text_size.unwrap_or_else(|| renderer.default_size()),
+            self.font,
+            &self.style,
+        )
+    }
+
+    fn hash_layout(&self, state: &mut Hasher) {
+        struct Marker;
+        std::any::TypeId::of::<Marker>().hash(state);
+        self.width.hash(state);
+        self.row_height.hash(state);
+    }
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        _renderer: &R,
+        _clipboard: &mut dyn iced::native::Clipboard,
+        _messages: &mut Vec<M>,
+    ) -> iced::event::Status {
+        if let Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) = event {
+            if layout.bounds().contains(cursor_position) {
+                if self.state.is_open {
+                    self.state.close();
+                } else if !self.options.is_empty() {
+                    self.state.open(self.selected);
+                }
+                return iced::event::Status::Captured;
+            } else if self.state.is_open {
+                // Clicked somewhere else, clicks on the list are handled by the overlay
+                self.state.close();
+            }
+        }
+
+        iced::event::Status::Ignored
+    }
+
+    fn overlay(&mut self, layout: Layout<'_>) -> Option<iced::overlay::Element<'_, M, R>> {
+        if !self.state.is_open {
+            return None;
+        }
+
+        let tooltip = self
+            .state
+            .hovered
+            .zip(self.option_tooltip.as_ref())
+            .map(|(index, tooltip)| tooltip(index));
+
+        Some(iced::overlay::Element::new(
+            Point::ORIGIN,
+            Box::new(Menu {
+                state: &mut *self.state,
+                options: &self.options,
+                icons: &self.icons,
+                selected: self.selected,
+                on_selected: &*self.on_selected,
+                header: layout.bounds(),
+                row_height: f32::from(self.row_height),
+                max_rows: self.max_rows,
+                text_size: self.text_size,
+                font: self.font,
+                style: &self.style,
+                tooltip,
+            }),
+        ))
+    }
+}
+
+impl<'a, M, R> From<Dropdown<'a, M, R>> for Element<'a, M, R>
+where
+    R: 'a + self::Renderer,
+    M: 'a,
+{
+    fn from(dropdown: Dropdown<'a, M, R>) -> Element<'a, M, R> { Element::new(dropdown) }
+}
+
+/// The open list of options
+struct Menu<'a, M, R: self::Renderer> {
+    state: &'a mut State,
+    options: &'a [String],
+    icons: &'a [Option<image::Handle>],
+    selected: Option<usize>,
+    on_selected: &'a dyn Fn(usize) -> M,
+    /// Bounds of the closed dropdown, the list is placed below or above it
+    header: Rectangle,
+    row_height: f32,
+    max_rows: usize,
+    text_size: Option<u16>,
+    font: R::Font,
+    style: &'a <R as self::Renderer>::Style,
+    /// Tooltip of the hovered option
+    tooltip: Option<Element<'a, M, R>>,
+}
+
+impl<'a, M, R: self::Renderer> Menu<'a, M, R> {
+    fn visible_rows(&self, layout: Layout<'_>) -> usize {
+        (layout.bounds().height / self.row_height).round() as usize
+    }
+
+    /// First visible option, keeping the list filled when scrolled to the end
+    fn first_row(&self, visible_rows: usize) -> usize {
+        self.state
+            .scroll
+            .min(self.options.len().saturating_sub(visible_rows))
+    }
+
+    /// Option under the cursor
+    fn row_at(
+        &self,
+        bounds: Rectangle,
+        cursor_position: Point,
+        visible_rows: usize,
+    ) -> Option<usize> {
+        let row = ((cursor_position.y - bounds.y) / self.row_height) as usize;
+        Some(self.first_row(visible_rows) + row).filter(|i| *i < self.options.len())
+    }
+
+    fn scroll_by(&mut self, rows: isize, visible_rows: usize) {
+        let first = self.first_row(visible_rows) as isize + rows;
+        self.state.scroll = first.max(0) as usize;
+        self.state.scroll = self.first_row(visible_rows);
+    }
+
+    /// Scroll so `index` is visible
+    fn reveal(&mut self, index: usize, visible_rows: usize) {
+        let first = self.first_row(visible_rows);
+        if index < first {
+            self.state.scroll = index;
+        } else if index >= first + visible_rows {
+            self.state.scroll = index + 1 - visible_rows;
+        }
+    }
+
+    fn select(&mut self, index: usize, messages: &mut Vec<M>) {
+        if Some(index) != self.selected {
+            messages.push((self.on_selected)(index));
+        }
+        self.state.close();
+    }
+}
+
+impl<'a, M, R> iced::Overlay<M, R> for Menu<'a, M, R>
+where
+    R: self::Renderer,
+{
+    fn layout(&self, renderer: &R, bounds: Size, position: Point) -> layout::Node {
+        let header = Rectangle {
+            x: self.header.x + position.x,
+            y: self.header.y + position.y,
+            ..self.header
+        };
+
+        let space_above = header.y.max(0.0);
+        let space_below = (bounds.height - header.y - header.height).max(0.0);
+        let wanted = self.options.len().min(self.max_rows) as f32 * self.row_height;
+        // Open downwards unless the list only fits above
+        let below = wanted <= space_below || space_below >= space_above;
+        let space = if below { space_below } else { space_above };
+        let rows = (wanted.min(space) / self.row_height).floor().max(1.0);
+        let height = rows * self.row_height;
+
+        let size = Size::new(header.width, height);
+        let position = Point {
+            x: header.x.min(bounds.width - header.width).max(0.0),
+            y: if below {
+                header.y + header.height
+            } else {
+                header.y - height
+            },
+        };
+
+        // Next to the hovered row, on the right unless it only fits on the left
+        let first = self.first_row(rows as usize);
+        let tooltip = self
+            .tooltip
+            .as_ref()
+            .zip(self.state.hovered.and_then(|i| i.checked_sub(first)))
+            .filter(|(_, row)| *row < rows as usize)
+            .map(|(tooltip, row)| {
+                let mut node = tooltip.layout(renderer, &layout::Limits::new(Size::ZERO, bounds));
+                let tooltip_size = node.size();
+                let right = position.x + size.width + TOOLTIP_PADDING;
+                let x = if right + tooltip_size.width <= bounds.width {
+                    right
+                } else {
+                    (position.x - tooltip_size.width - TOOLTIP_PADDING).max(0.0)
+                };
+                let y = (position.y + row as f32 * self.row_height)
+                    .min(bounds.height - tooltip_size.height)
+                    .max(0.0);
+                // Children are placed relative to the list
+                node.move_to(Point::new(x - position.x, y - position.y));
+                node
+            });
+
+        let mut node = layout::Node::with_children(size, tooltip.into_iter().collect());
+        node.move_to(position);
+
+        node
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut R,
+        defaults: &R::Defaults,
+        layout: Layout<'_>,
+        cursor_position: Point,
+    ) -> R::Output {
+        let visible_rows = self.visible_rows(layout);
+        let first = self.first_row(visible_rows);
+        let last = (first + visible_rows).min(self.options.len());
+
+        let menu = renderer.draw_menu(
+            defaults,
+            layout.bounds(),
+            cursor_position,
+            &self.options[first..last],
+            self.icons.get(first..last.min(self.icons.len())).unwrap_or_default(),
+            first,
+            self.options.len(),
+            self.state.hovered,
+            self.selected,
+            self.row_height,
+            self.text_size.unwrap_or_else(|| renderer.default_size()),
+            self.font,
+            self.style,
+        );
+
+        match (&self.tooltip, layout.children().next()) {
+            (Some(tooltip), Some(tooltip_layout)) => renderer.draw_menu_tooltip(
+                menu,
+                defaults,
+                cursor_position,
+                tooltip,
+                tooltip_layout,
+            ),
+            _ => menu,
+        }
+    }
+
+    fn hash_layout(&self, state: &mut Hasher, position: Point) {
+        struct Marker;
+        std::any::TypeId::of::<Marker>().hash(state);
+
+        (position.x as u32).hash(state);
+        (position.y as u32).hash(state);
+        (self.header.x as u32).hash(state);
+        (self.header.y as u32).hash(state);
+        (self.header.width as u32).hash(state);
+        (self.header.height as u32).hash(state);
+        (self.row_height as u32).hash(state);
+        self.max_rows.hash(state);
+        self.options.len().hash(state);
+        self.state.hovered.hash(state);
+        if let Some(tooltip) = &self.tooltip {
+            tooltip.hash_layout(state);
+        }
+    }
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        _renderer: &R,
+        _clipboard: &mut dyn iced::native::Clipboard,
+        messages: &mut Vec<M>,
+    ) -> iced::event::Status {
+        let bounds = layout.bounds();
+        let visible_rows = self.visible_rows(layout);
+
+        match event {
+            Event::Mouse(mouse::Event::CursorMoved { .. }) if bounds.contains(cursor_position) => {
+                self.state.hovered = self.row_at(bounds, cursor_position, visible_rows);
+            },
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left))
+                if bounds.contains(cursor_position) =>
+            {
+                if let Some(index) = self.row_at(bounds, cursor_position, visible_rows) {
+                    self.select(index, messages);
+                }
+                return iced::event::Status::Captured;
+            },
+            Event::Mouse(mouse::Event::WheelScrolled { delta })
+                if bounds.contains(cursor_position) =>
+            {
+                let rows = match delta {
+                    mouse::ScrollDelta::Lines { y, .. } => -y.signum() as isize,
+                    mouse::ScrollDelta::Pixels { y, .. } => {
+                        -(y / self.row_height).round() as isize
+                    },
+                };
+                self.scroll_by(rows, visible_rows);
+                self.state.hovered = self.row_at(bounds, cursor_position, visible_rows);
+                return iced::event::Status::Captured;
+            },
+            Event::Keyboard(keyboard::Event::KeyPressed { key_code, .. }) => {
+                let last = self.options.len().saturating_sub(1);
+                let hovered = match key_code {
+                    keyboard::KeyCode::Up => {
+                        self.state.hovered.map_or(last, |i| i.saturating_sub(1))
+                    },
+                    keyboard::KeyCode::Down => {
+                        self.state.hovered.map_or(0, |i| (i + 1).min(last))
+                    },
+                    keyboard::KeyCode::PageUp => self
+                        .state
+                        .hovered
+                        .map_or(0, |i| i.saturating_sub(visible_rows)),
+                    keyboard::KeyCode::PageDown => self
+                        .state
+                        .hovered
+                        .map_or(0, |i| (i + visible_rows).min(last)),
+                    keyboard::KeyCode::Home => 0,
+                    keyboard::KeyCode::End => last,
+                    keyboard::KeyCode::Enter | keyboard::KeyCode::NumpadEnter => {
+                        match self.state.hovered {
+                            Some(index) => self.select(index, messages),
+                            None => self.state.close(),
+                        }
+                        return iced::event::Status::Captured;
+                    },
+                    keyboard::KeyCode::Escape => {
+                        self.state.close();
+                        return iced::event::Status::Captured;
+                    },
+                    _ => return iced::event::Status::Ignored,
+                };
+                self.state.hovered = Some(hovered);
+                self.reveal(hovered, visible_rows);
+                return iced::event::Status::Captured;
+            },
+            _ => {},
+        }
+
+        iced::event::Status::Ignored
+    }
+}
+
+pub trait Renderer: iced::text::Renderer {
+    type Style: Default;
+
+    /// The closed dropdown showing `label` and `icon` of the selected option
+    fn draw(
+        &mut self,
+        defaults: &Self::Defaults,
+        bounds: Rectangle,
+        cursor_position: Point,
+        label: Option<&str>,
+        icon: Option<image::Handle>,
+        is_open: bool,
+        text_size: u16,
+        font: Self::Font,
+        style: &<Self as Renderer>::Style,
+    ) -> Self::Output;
+
+    /// The open list, `options` are the visible ones starting at index
+    /// `first` of the `total` options. `icons` belong to the options with the
+    /// same index and may be fewer.
+    fn draw_menu(
+        &mut self,
+        defaults: &Self::Defaults,
+        bounds: Rectangle,
+        cursor_position: Point,
+        options: &[String],
+        icons: &[Option<image::Handle>],
+        first: usize,
+        total: usize,
+        hovered: Option<usize>,
+        selected: Option<usize>,
+        row_height: f32,
+        text_size: u16,
+        font: Self::Font,
+        style: &<Self as Renderer>::Style,
+    ) -> Self::Output;
+
+    /// Draws `tooltip` on top of the open list drawn as `menu`
+    fn draw_menu_tooltip<M>(
+        &mut self,
+        menu: Self::Output,
+        defaults: &Self::Defaults,
+        cursor_position: Point,
+        tooltip: &Element<'_, M, Self>,
+        layout: Layout<'_>,
+    ) -> Self::Output
+    where
+        Self: Sized;
+}
@@ -15,6 +15,7 @@ pub struct Image {
     height: Length,
     fix_aspect_ratio: bool,
     color: Rgba<u8>,
+    grayscale: bool,
 }
 
 impl Image {
@@ -27,6 +28,7 @@ impl Image {
             height,
             fix_aspect_ratio: false,
             color: Rgba::broadcast(255),
+            grayscale: false,
         }
     }
 
@@ -53,6 +55,20 @@ impl Image {
         self.color = color;
         self
     }
+
+    /// Multiplied with the alpha of the color
+    #[must_use]
+    pub fn opacity(mut self, opacity: f32) -> Self {
+        self.color.a = (self.color.a as f32 * opacity.clamp(0.0, 1.0)).round() as u8;
+        self
+    }
+
+    /// Draw the image without its colors, e.g. for disabled elements
+    #[must_use]
+    pub fn grayscale(mut self, grayscale: bool) -> Self {
+        self.grayscale = grayscale;
+        self
+    }
 }
 
 impl<M, R> Widget<M, R> for Image
@@ -92,7 +108,7 @@ where
         _cursor_position: Point,
         _viewport: &Rectangle,
     ) -> R::Output {
-        renderer.draw(self.handle, self.color, layout)
+        renderer.draw(self.handle, self.color, self.grayscale, layout)
     }
 
     fn hash_layout(&self, state: &mut Hasher) {
@@ -108,7 +124,13 @@ where
 
 pub trait Renderer: iced::Renderer {
     fn dimensions(&self, handle: Handle) -> (u32, u32);
-    fn draw(&mut self, handle: Handle, color: Rgba<u8>, layout: Layout<'_>) -> Self::Output;
+    fn draw(
+        &mut self,
+        handle: Handle,
+        color: Rgba<u8>,
+        grayscale: bool,
+        layout: Layout<'_>,
+    ) -> Self::Output;
 }
 
 impl<'a, M, R> From<Image> for Element<'a, M, R>
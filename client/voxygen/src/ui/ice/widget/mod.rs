@@ -1,8 +1,10 @@
 pub mod aspect_ratio_container;
 pub mod background_container;
 pub mod compound_graphic;
+pub mod dropdown;
 pub mod fill_text;
 pub mod image;
+pub mod modal;
 pub mod mouse_detector;
 pub mod overlay;
 pub mod stack;
@@ -11,8 +13,10 @@ pub mod tooltip;
 pub use self::{
     aspect_ratio_container::AspectRatioContainer,
     background_container::{BackgroundContainer, Padding},
+    dropdown::Dropdown,
     fill_text::FillText,
     image::Image,
+    modal::Modal,
     mouse_detector::MouseDetector,
     overlay::Overlay,
     tooltip::{Tooltip, TooltipManager},
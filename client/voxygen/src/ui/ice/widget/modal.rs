@@ -0,0 +1,204 @@
+use iced::{
+    keyboard, layout, Alignment, Element, Event, Hasher, Layout, Length, Point, Rectangle, Size,
+    Widget,
+};
+use std::hash::Hash;
+use vek::Rgba;
+
+/// Shows a dialog centered over the content, dimming the content and keeping
+/// all input away from it until the dialog is closed
+///
+/// Enter and Escape produce the confirm and cancel messages if set and the
+/// dialog didn't use the key itself.
+pub struct Modal<'a, M, R: self::Renderer> {
+    content: Element<'a, M, R>,
+    dialog: Option<Element<'a, M, R>>,
+    backdrop: Rgba<u8>,
+    on_confirm: Option<M>,
+    on_cancel: Option<M>,
+}
+
+impl<'a, M, R> Modal<'a, M, R>
+where
+    R: self::Renderer,
+{
+    pub fn new<C>(content: C) -> Self
+    where
+        C: Into<Element<'a, M, R>>,
+    {
+        Self {
+            content: content.into(),
+            dialog: None,
+            backdrop: Rgba::new(0, 0, 0, 160),
+            on_confirm: None,
+            on_cancel: None,
+        }
+    }
+
+    #[must_use]
+    pub fn dialog<D>(mut self, dialog: Option<D>) -> Self
+    where
+        D: Into<Element<'a, M, R>>,
+    {
+        self.dialog = dialog.map(Into::into);
+        self
+    }
+
+    /// Color drawn over the content while the dialog is shown
+    #[must_use]
+    pub fn backdrop(mut self, color: Rgba<u8>) -> Self {
+        self.backdrop = color;
+        self
+    }
+
+    #[must_use]
+    pub fn on_confirm(mut self, message: M) -> Self {
+        self.on_confirm = Some(message);
+        self
+    }
+
+    #[must_use]
+    pub fn on_cancel(mut self, message: M) -> Self {
+        self.on_cancel = Some(message);
+        self
+    }
+}
+
+impl<'a, M, R> Widget<M, R> for Modal<'a, M, R>
+where
+    M: Clone,
+    R: self::Renderer,
+{
+    fn width(&self) -> Length { self.content.width() }
+
+    fn height(&self) -> Length { self.content.height() }
+
+    fn layout(&self, renderer: &R, limits: &layout::Limits) -> layout::Node {
+        let content = self.content.layout(renderer, limits);
+        let size = content.size();
+
+        let mut children = vec![content];
+        if let Some(dialog) = &self.dialog {
+            let mut dialog = dialog.layout(renderer, &layout::Limits::new(Size::ZERO, size));
+            dialog.align(Alignment::Center, Alignment::Center, size);
+            children.push(dialog);
+        }
+
+        layout::Node::with_children(size, children)
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut R,
+        defaults: &R::Defaults,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        viewport: &Rectangle,
+    ) -> R::Output {
+        let mut children = layout.children();
+        let content_layout = children.next().unwrap();
+        renderer.draw(
+            defaults,
+            layout.bounds(),
+            cursor_position,
+            viewport,
+            self.backdrop,
+            &self.content,
+            content_layout,
+            self.dialog.as_ref().zip(children.next()),
+        )
+    }
+
+    fn hash_layout(&self, state: &mut Hasher) {
+        struct Marker;
+        std::any::TypeId::of::<Marker>().hash(state);
+
+        self.content.hash_layout(state);
+        if let Some(dialog) = &self.dialog {
+            dialog.hash_layout(state);
+        }
+    }
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        renderer: &R,
+        clipboard: &mut dyn iced::native::Clipboard,
+        messages: &mut Vec<M>,
+    ) -> iced::event::Status {
+        let mut children = layout.children();
+        let content_layout = children.next().unwrap();
+
+        let dialog = match (&mut self.dialog, children.next()) {
+            (Some(dialog), Some(dialog_layout)) => (dialog, dialog_layout),
+            _ => {
+                return self.content.on_event(
+                    event,
+                    content_layout,
+                    cursor_position,
+                    renderer,
+                    clipboard,
+                    messages,
+                );
+            },
+        };
+
+        let status = dialog.0.on_event(
+            event.clone(),
+            dialog.1,
+            cursor_position,
+            renderer,
+            clipboard,
+            messages,
+        );
+
+        if status == iced::event::Status::Ignored {
+            if let Event::Keyboard(keyboard::Event::KeyPressed { key_code, .. }) = event {
+                let message = match key_code {
+                    keyboard::KeyCode::Enter | keyboard::KeyCode::NumpadEnter => &self.on_confirm,
+                    keyboard::KeyCode::Escape => &self.on_cancel,
+                    _ => &None,
+                };
+                if let Some(message) = message {
+                    messages.push(message.clone());
+                }
+            }
+        }
+
+        // The content doesn't get any input while the dialog is open
+        iced::event::Status::Captured
+    }
+
+    fn overlay(&mut self, layout: Layout<'_>) -> Option<iced::overlay::Element<'_, M, R>> {
+        let mut children = layout.children();
+        let content_layout = children.next().unwrap();
+        match (&mut self.dialog, children.next()) {
+            (Some(dialog), Some(dialog_layout)) => dialog.overlay(dialog_layout),
+            _ => self.content.overlay(content_layout),
+        }
+    }
+}
+
+pub trait Renderer: iced::Renderer {
+    fn draw<M>(
+        &mut self,
+        defaults: &Self::Defaults,
+        bounds: Rectangle,
+        cursor_position: Point,
+        viewport: &Rectangle,
+        backdrop: Rgba<u8>,
+        content: &Element<'_, M, Self>,
+        content_layout: Layout<'_>,
+        dialog: Option<(&Element<'_, M, Self>, Layout<'_>)>,
+    ) -> Self::Output;
+}
+
+impl<'a, M, R> From<Modal<'a, M, R>> for Element<'a, M, R>
+where
+    R: 'a + self::Renderer,
+    M: 'a + Clone,
+{
+    fn from(modal: Modal<'a, M, R>) -> Element<'a, M, R> { Element::new(modal) }
+}
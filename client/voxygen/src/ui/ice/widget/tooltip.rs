@@ -117,6 +117,10 @@ impl TooltipManager {
         };
     }
 
+    /// Whether a tooltip is about to be shown, showing or fading, uis which
+    /// only rebuild on input need to keep rebuilding while this is true
+    pub fn is_active(&self) -> bool { !matches!(self.state, State::Idle) }
+
     fn update(&self, update: Update) { *self.update.lock().unwrap() = Some(update); }
 
     /// Returns an options with the position of the cursor when the tooltip
@@ -235,6 +239,10 @@ where
     }
 
     fn overlay(&mut self, layout: Layout<'_>) -> Option<iced::overlay::Element<'_, M, R>> {
+        // Overlays of the content, e.g. an open dropdown, take precedence
+        if let Some(overlay) = self.content.overlay(layout) {
+            return Some(overlay);
+        }
         let bounds = layout.bounds();
         let aabr = aabr_from_bounds(bounds);
 
@@ -320,13 +328,16 @@ where
 
         let size = node.size();
 
+        // Keep the tooltip on the screen, even if it's wider or taller than
+        // the space left next to the widget
+        let y = if space_above >= space_below {
+            avoid.y - size.height - PAD
+        } else {
+            avoid.y + avoid.height + PAD
+        };
         node.move_to(Point {
-            x: (bounds.width - size.width).min(cursor_position.x),
-            y: if space_above >= space_below {
-                avoid.y - size.height - PAD
-            } else {
-                avoid.y + avoid.height + PAD
-            },
+            x: cursor_position.x.min(bounds.width - size.width).max(0.0),
+            y: y.min(bounds.height - size.height).max(0.0),
         });
 
         node
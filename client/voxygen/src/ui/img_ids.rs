@@ -179,6 +179,12 @@ macro_rules! image_ids_ice {
                         $($( $name: ui.add_graphic(<$T as GraphicCreator>::new_graphic($specifier)?), )*)*
                     })
                 }
+
+                /// All images, e.g. to pack them with `IcedUi::pack_graphics`
+                #[allow(dead_code)]
+                pub fn all(&self) -> Vec<crate::ui::GraphicId> {
+                    vec![$($( self.$name, )*)*]
+                }
             }
         )*
     };
@@ -0,0 +1,95 @@
+//! Display strings of the bound keys, so they don't have to be rendered again
+//! every frame
+
+use crate::{game_input::GameInput, settings::ControlSettings};
+use hashbrown::HashMap;
+use keyboard_keynames::key_layout::KeyLayout;
+
+// The layout is created once with the window and never replaced, so its
+// address identifies it
+type LayoutId = Option<usize>;
+
+fn layout_id(key_layout: &Option<KeyLayout>) -> LayoutId {
+    key_layout
+        .as_ref()
+        .map(|layout| layout as *const KeyLayout as usize)
+}
+
+#[derive(Default)]
+pub struct KeybindCache {
+    revision: Option<u64>,
+    // `None` for unbound inputs
+    strings: HashMap<(GameInput, LayoutId), Option<String>>,
+}
+
+impl KeybindCache {
+    /// Forgets everything if the bindings changed since the strings were
+    /// rendered, returns whether it did
+    pub fn validate(&mut self, controls: &ControlSettings) -> bool {
+        if self.revision == Some(controls.revision()) {
+            return false;
+        }
+        self.revision = Some(controls.revision());
+        self.strings.clear();
+        true
+    }
+
+    /// Renders the keys bound to `game_inputs` up front, e.g. for all inputs
+    /// a screen will show
+    pub fn prerender(
+        &mut self,
+        game_inputs: impl IntoIterator<Item = GameInput>,
+        controls: &ControlSettings,
+        key_layout: &Option<KeyLayout>,
+    ) {
+        for game_input in game_inputs {
+            self.get(game_input, controls, key_layout);
+        }
+    }
+
+    /// Description of the key bound to `game_input`, `None` if it's unbound
+    pub fn get(
+        &mut self,
+        game_input: GameInput,
+        controls: &ControlSettings,
+        key_layout: &Option<KeyLayout>,
+    ) -> Option<&str> {
+        self.validate(controls);
+        self.strings
+            .entry((game_input, layout_id(key_layout)))
+            .or_insert_with(|| match controls.keybindings.get(&game_input) {
+                Some(Some(key_mouse)) => Some(key_mouse.display_string(key_layout)),
+                Some(None) => None,
+                None => Some(
+                    ControlSettings::default_binding(game_input).display_string(key_layout),
+                ),
+            })
+            .as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window::KeyMouse;
+    use winit::event::VirtualKeyCode;
+
+    #[test]
+    fn invalidated_by_binding_changes() {
+        let mut cache = KeybindCache::default();
+        let mut controls = ControlSettings::default();
+        cache.prerender([GameInput::Jump, GameInput::Sneak], &controls, &None);
+        assert!(!cache.validate(&controls));
+
+        controls.modify_binding(GameInput::Jump, KeyMouse::Key(VirtualKeyCode::J));
+        assert_eq!(cache.get(GameInput::Jump, &controls, &None), Some("J"));
+        controls.remove_binding(GameInput::Jump);
+        assert_eq!(cache.get(GameInput::Jump, &controls, &None), None);
+
+        // Resetting replaces the settings, which must not look unchanged
+        let revision = controls.revision();
+        controls = ControlSettings::default();
+        assert_ne!(controls.revision(), revision);
+        assert!(cache.validate(&controls));
+    }
+}
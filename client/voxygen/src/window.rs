@@ -12,6 +12,7 @@ use hashbrown::HashMap;
 use itertools::Itertools;
 use keyboard_keynames::key_layout::KeyLayout;
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use vek::*;
 use winit::monitor::VideoMode;
 use winit::window::WindowBuilder;
@@ -93,6 +94,8 @@ pub enum Event {
     AnalogGameInput(AnalogGameInput),
     /// We tried to save a screenshot
     ScreenshotMessage(String),
+    /// A file was dropped onto the window
+    FileDropped(PathBuf),
 }
 
 pub type MouseButton = winit::event::MouseButton;
@@ -872,6 +875,7 @@ impl Window {
                     .push(Event::Moved(Vec2::new(x as u32, y as u32)));
             },
             WindowEvent::ReceivedCharacter(c) => self.events.push(Event::Char(c)),
+            WindowEvent::DroppedFile(path) => self.events.push(Event::FileDropped(path)),
             WindowEvent::MouseInput { button, state, .. } => {
                 if let (true, Some(game_inputs)) =
                     // Mouse input not mapped to input if it is not grabbed
@@ -944,14 +948,6 @@ impl Window {
                                     input.state,
                                 );
                             },
-                            GameInput::Screenshot => {
-                                log::warn!("todo gameInput screenshot");
-				 Self::set_pressed(
-                                    &mut self.keypress_map,
-                                    GameInput::Screenshot,
-                                    input.state,
-                                );	
-                            },
                             _ => self.events.push(Event::InputUpdate(
                                 *game_input,
                                 input.state == winit::event::ElementState::Pressed,
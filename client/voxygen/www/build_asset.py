@@ -6,6 +6,8 @@ dir = os.getcwd()
 result = {}
 result["dirs"] = []
 result["files"] = []
+#按需下载的文件: 有子集的字体和字形块, 见 i18n-check --font-subsets
+result["lazy"] = []
 
 #按照目录结构生成index
 for root, dirs, files in os.walk(dir + "\\assets"):
@@ -15,7 +17,11 @@ for root, dirs, files in os.walk(dir + "\\assets"):
     for file in files:
         fullPath = root+'\\'+file
         path = fullPath.replace(dir+'\\assets\\', '') 
-        result["files"].append(path)
+        subset = fullPath[:-len(".ttf")] + "_subset.ttf"
+        if root.endswith("_blocks") or (file.endswith(".ttf") and os.path.exists(subset)):
+            result["lazy"].append(path)
+        else:
+            result["files"].append(path)
 
     for p in dirs:
         dirFullPath = root+'\\'+ p
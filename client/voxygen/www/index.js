@@ -1,11 +1,12 @@
 
-import init, { start, set_resource_dir, set_resource_data } from "./pkg/veloren_voxygen.js";
+import init, { start, set_resource_dir, set_resource_data, set_resource_lazy } from "./pkg/veloren_voxygen.js";
 
 (async function main() {
   await init();
   window.rust_func = {
     SetResourceData: set_resource_data,
     SetResourceDir: set_resource_dir,
+    SetResourceLazy: set_resource_lazy,
   }
   DownAllRes(start)
 })();
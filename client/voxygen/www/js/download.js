@@ -1,5 +1,7 @@
 let db
 let objectStore
+//按需下载的文件, rust名字 => 路径
+let lazyPaths = {}
 
 function DownAllRes(callBack) {
 
@@ -36,6 +38,7 @@ function startDownload(callBack) {
         let json = res.data
         let dirArray = json["dirs"]
         let fileArray =  json["files"]
+        let lazyArray = json["lazy"] || []
         let downCount = 0
         let loading = document.getElementById("loading");
         
@@ -45,6 +48,14 @@ function startDownload(callBack) {
             let rustPath = path.replace(/\\/g, ".")
             window.rust_func.SetResourceDir(rustPath)
         }
+
+        //按需下载的文件只登记, 由rust通过fetchLazyResource请求
+        for (var idx in lazyArray) {
+            let path = lazyArray[idx]
+            let rustPath = path.replace(/\\/g, ".")
+            lazyPaths[rustPath] = path
+            window.rust_func.SetResourceLazy(rustPath)
+        }
        
         let loadover = function () {
             downCount = downCount + 1;
@@ -97,6 +108,16 @@ function downResFile(assetName, callback) {
     })
 }
 
+//rust请求按需下载的文件
+function fetchLazyResource(rName) {
+    let path = lazyPaths[rName]
+    if (path) {
+        downResFile(path, function () {})
+    } else {
+        console.error('JS: unknown lazy resource ' + rName)
+    }
+}
+
 function requestRes(rName, callback) {
 
     let objectStore = getStore();
@@ -0,0 +1,116 @@
+//! Redirects from old asset specifiers to new ones
+//!
+//! `aliases.ron` in the asset root maps specifiers to their new location:
+//! ```ron
+//! {
+//!     "common.items.armor.old_chest": "common.items.armor.cloth.chest",
+//!     // Keys ending with a dot move a whole folder
+//!     "common.items.old_folder.": "common.items.new_folder.",
+//! }
+//! ```
+//!
+//! Aliases are resolved by the asset source, so they also apply to assets
+//! loaded by other assets and to directories.
+use crate::{Source, ASSETS};
+use lazy_static::lazy_static;
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    sync::Mutex,
+};
+
+const ALIASES_FILE: &str = "aliases";
+/// Aliases may point to other aliases, but not endlessly
+const MAX_ALIAS_HOPS: usize = 8;
+
+lazy_static! {
+    static ref ALIASES: HashMap<String, String> = load_aliases();
+    static ref WARNED: Mutex<HashSet<String>> = Mutex::new(HashSet::new());
+}
+
+fn load_aliases() -> HashMap<String, String> {
+    match ASSETS.source().read(ALIASES_FILE, "ron") {
+        Ok(content) => ron::de::from_bytes(&content).unwrap_or_else(|e| {
+            tracing::error!(?e, "Failed to parse the asset alias table");
+            HashMap::new()
+        }),
+        Err(_) => HashMap::new(),
+    }
+}
+
+fn lookup(aliases: &HashMap<String, String>, specifier: &str) -> Option<String> {
+    if let Some(to) = aliases.get(specifier) {
+        return Some(to.clone());
+    }
+    aliases
+        .iter()
+        .filter(|(from, _)| from.ends_with('.') && specifier.starts_with(from.as_str()))
+        .max_by_key(|(from, _)| from.len())
+        .map(|(from, to)| [to.as_str(), &specifier[from.len()..]].concat())
+}
+
+fn resolve_with<'a>(aliases: &HashMap<String, String>, specifier: &'a str) -> Cow<'a, str> {
+    let mut resolved = Cow::Borrowed(specifier);
+    for _ in 0..MAX_ALIAS_HOPS {
+        match lookup(aliases, &resolved) {
+            Some(next) if next != resolved => resolved = Cow::Owned(next),
+            _ => break,
+        }
+    }
+    resolved
+}
+
+/// Like [`resolve_alias`], for ids read through the asset source. The alias
+/// table itself is read through the source as well and never redirected.
+pub(crate) fn resolve_source_id(id: &str) -> Cow<str> {
+    if id == ALIASES_FILE {
+        Cow::Borrowed(id)
+    } else {
+        resolve_alias(id)
+    }
+}
+
+/// The specifier `specifier` was moved to, or itself if it wasn't
+///
+/// The first use of every alias is logged as deprecated.
+pub fn resolve_alias(specifier: &str) -> Cow<str> {
+    if ALIASES.is_empty() {
+        return Cow::Borrowed(specifier);
+    }
+    let resolved = resolve_with(&ALIASES, specifier);
+    if resolved != specifier && WARNED.lock().unwrap().insert(specifier.to_owned()) {
+        tracing::warn!(
+            "Asset specifier {} is deprecated, use {} instead",
+            specifier,
+            resolved
+        );
+    }
+    resolved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve() {
+        let aliases = [
+            ("common.items.old.", "common.items.new."),
+            ("common.items.new.sword", "common.items.weapons.sword"),
+            ("common.items.a", "common.items.b"),
+            ("common.items.b", "common.items.a"),
+        ]
+        .iter()
+        .map(|(from, to)| (from.to_string(), to.to_string()))
+        .collect::<HashMap<_, _>>();
+
+        assert_eq!(resolve_with(&aliases, "common.items.old.axe"), "common.items.new.axe");
+        assert_eq!(
+            resolve_with(&aliases, "common.items.old.sword"),
+            "common.items.weapons.sword"
+        );
+        assert_eq!(resolve_with(&aliases, "common.items.other"), "common.items.other");
+        // Cycles end after a few hops instead of hanging
+        resolve_with(&aliases, "common.items.a");
+    }
+}
@@ -1,3 +1,4 @@
+use crate::alias::resolve_source_id;
 use std::{borrow::Cow, io};
 
 use assets_manager::{
@@ -32,6 +33,7 @@ impl FileSystem {
 
 impl Source for FileSystem {
     fn read(&self, id: &str, ext: &str) -> io::Result<Cow<[u8]>> {
+        let id = &*resolve_source_id(id);
         if let Some(dir) = &self.override_dir {
             match dir.read(id, ext) {
                 Ok(content) => return Ok(content),
@@ -53,6 +55,7 @@ impl Source for FileSystem {
     }
 
     fn read_dir(&self, id: &str, f: &mut dyn FnMut(DirEntry)) -> io::Result<()> {
+        let id = &*resolve_source_id(id);
         if let Some(dir) = &self.override_dir {
             match dir.read_dir(id, f) {
                 Ok(()) => return Ok(()),
@@ -74,6 +77,13 @@ impl Source for FileSystem {
     }
 
     fn exists(&self, entry: DirEntry) -> bool {
+        let resolved = match entry {
+            DirEntry::File(id, _) | DirEntry::Directory(id) => resolve_source_id(id),
+        };
+        let entry = match entry {
+            DirEntry::File(_, ext) => DirEntry::File(&resolved, ext),
+            DirEntry::Directory(_) => DirEntry::Directory(&resolved),
+        };
         self.override_dir
             .as_ref()
             .map_or(false, |dir| dir.exists(entry))
@@ -15,6 +15,8 @@ pub use assets_manager::{
     Asset, AssetCache, BoxedError, Compound, Error, SharedString,
 };
 
+mod alias;
+pub use alias::resolve_alias;
 mod fs;
 
 lazy_static! {
@@ -113,7 +115,7 @@ pub fn load_dir<T: DirLoadable>(
     recursive: bool,
 ) -> Result<AssetDirHandle<T>, Error> {
     let specifier = specifier.strip_suffix(".*").unwrap_or(specifier);
-    ASSETS.load_dir(specifier, recursive)
+    ASSETS.load_dir(&resolve_alias(specifier), recursive)
 }
 
 /// Loads directory and all files in it
@@ -144,12 +146,16 @@ pub fn read_expect_dir<T: DirLoadable>(
 }
 
 impl<T: Compound> AssetExt for T {
-    fn load(specifier: &str) -> Result<AssetHandle<Self>, Error> { ASSETS.load(specifier) }
+    fn load(specifier: &str) -> Result<AssetHandle<Self>, Error> {
+        ASSETS.load(&resolve_alias(specifier))
+    }
 
-    fn load_owned(specifier: &str) -> Result<Self, Error> { ASSETS.load_owned(specifier) }
+    fn load_owned(specifier: &str) -> Result<Self, Error> {
+        ASSETS.load_owned(&resolve_alias(specifier))
+    }
 
     fn get_or_insert(specifier: &str, default: Self) -> AssetHandle<Self> {
-        ASSETS.get_or_insert(specifier, default)
+        ASSETS.get_or_insert(&resolve_alias(specifier), default)
     }
 }
 
@@ -1,4 +1,4 @@
-use super::{world_msg::SiteId, PingMsg};
+use super::{world_msg::SiteId, ChallengeAnswer, PingMsg};
 use common::{
     character::CharacterId,
     comp,
@@ -41,6 +41,10 @@ pub enum ClientType {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ClientRegister {
     pub token_or_username: String,
+    /// Answer to the [`RegisterError::Challenge`] of the previous attempt
+    ///
+    /// [`RegisterError::Challenge`]: super::RegisterError::Challenge
+    pub challenge_answer: Option<ChallengeAnswer>,
 }
 
 /// Messages sent from the client to the server
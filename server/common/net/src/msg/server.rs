@@ -1,6 +1,6 @@
 use super::{
-    world_msg::EconomyInfo, ClientType, CompressedData, EcsCompPacket, PingMsg, QuadPngEncoding,
-    TriPngEncoding, WidePacking, WireChonk,
+    world_msg::EconomyInfo, ClientType, CompressedData, EcsCompPacket, LoginChallenge, PingMsg,
+    QuadPngEncoding, TriPngEncoding, WidePacking, WireChonk,
 };
 use crate::sync;
 use common::{
@@ -257,6 +257,9 @@ pub enum RegisterError {
     Kicked(String),
     InvalidCharacter,
     NotOnWhitelist,
+    /// The server wants this solved first, register again with the answer
+    Challenge(LoginChallenge),
+    ChallengeFailed,
     //TODO: InvalidAlias,
 }
 
@@ -305,6 +305,22 @@ fn initialize_modular_assets() -> (HashMap<String, RawItemDef>, RawRecipeBook) {
     (itemdefs, RawRecipeBook(recipes))
 }
 
+/// Modular recipes as `(output, amount, inputs)` with each tag input replaced
+/// by the lowest tier component carrying that tag, used to estimate prices
+pub(crate) fn recipes_for_pricing() -> Vec<(String, u32, Vec<(String, u32)>)> {
+    SUPPORTED_TOOLKINDS
+        .iter()
+        .map(|&toolkind| {
+            let (output, _) = make_weapon_def(toolkind);
+            let inputs = MODKINDS
+                .iter()
+                .map(|&modkind| (make_component_def(toolkind, modkind, 0).0, 1))
+                .collect();
+            (output, 1, inputs)
+        })
+        .collect()
+}
+
 lazy_static! {
     static ref ITEM_DEFS_AND_RECIPES: (HashMap<String, RawItemDef>, RawRecipeBook) =
         initialize_modular_assets();
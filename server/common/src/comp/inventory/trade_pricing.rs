@@ -1,19 +1,35 @@
 use crate::{
     assets::{self, AssetExt},
+    comp::item::{modular, ItemDef, Quality},
     lottery::LootSpec,
     recipe::{default_recipe_book, RecipeInput},
     trade::Good,
 };
-use assets::AssetGuard;
-use hashbrown::HashMap;
+use assets::{AssetGuard, ReloadWatcher};
+use hashbrown::{HashMap, HashSet};
 use lazy_static::lazy_static;
-use serde::Deserialize;
-use std::cmp::Ordering;
-use tracing::{info, warn};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    cmp::Ordering,
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+use tracing::{debug, info, warn};
 
 const PRICING_DEBUG: bool = false;
 
-#[derive(Default, Debug)]
+/// Bump this whenever the pricing algorithm or the cached format changes so
+/// stale caches get discarded
+const PRICING_CACHE_VERSION: u32 = 5;
+const PRICING_CACHE_FILE: &str = "trade_pricing_cache.json";
+/// Mantissa bits kept by `round_price`, few enough for the result to be
+/// exactly representable as f32
+const PRICE_PRECISION_BITS: u32 = 20;
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
 pub struct TradePricing {
     // items of different good kinds
     tools: Entries,
@@ -21,6 +37,9 @@ pub struct TradePricing {
     potions: Entries,
     food: Entries,
     ingredients: Entries,
+    modular_components: Entries,
+    furniture: Entries,
+    pets: Entries,
     other: Entries,
 
     // good_scaling of coins
@@ -29,12 +48,67 @@ pub struct TradePricing {
     // get amount of material per item
     material_cache: HashMap<String, (Good, f32)>,
     equality_set: EqualitySet,
+    // items which only enter the economy through crafting
+    crafted_only: HashSet<String>,
+
+    // from `PriceOverrides`, applied after the cache was loaded
+    #[serde(skip)]
+    pinned_prices: HashMap<String, f32>,
+    #[serde(skip)]
+    unsellable: HashSet<String>,
+}
+
+/// Outcome of asking for the trade value of an item
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PriceQueryResult {
+    /// One item is worth `amount` units of `good`
+    Priced { good: Good, amount: f32 },
+    /// Known item which is deliberately kept out of trade (e.g. gliders,
+    /// lanterns, boss drops) or which merchants don't stock
+    NotTradable,
+    /// Neither pricing information nor an item definition exists
+    UnknownItem,
+    /// Only obtainable by crafting it from ingredients merchants don't deal in
+    OnlyCraftable,
+    /// Unique items (artifact or debug quality) which are never bought or sold
+    Sentimental,
+}
+
+impl PriceQueryResult {
+    pub fn is_priced(&self) -> bool { matches!(self, Self::Priced { .. }) }
+}
+
+/// Which share of its budget an NPC wants to spend on each kind of good,
+/// e.g. `[(Good::Food, 0.3), (Good::Tools, 0.2)]`. The remaining share is
+/// kept, shares summing up to more than one are scaled down. Each share is
+/// spent in units of its own good, items priced in another good are skipped.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ShoppingNeeds(pub Vec<(Good, f32)>);
+
+/// Items an NPC intends to buy, see [`TradePricing::shopping_list`]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ShoppingList {
+    /// Item asset specifiers and how many of them to buy
+    pub items: Vec<(String, u32)>,
+    /// The summed up price of the items per good they are priced in
+    pub costs: Vec<(Good, f32)>,
+}
+
+impl ShoppingList {
+    /// The summed up price of the items priced in `good`
+    pub fn cost(&self, good: Good) -> f32 {
+        self.costs
+            .iter()
+            .filter(|(g, _)| *g == good)
+            .map(|(_, cost)| cost)
+            .sum()
+    }
 }
 
 // item asset specifier, probability, whether it's sellable by merchants
 type Entry = (String, f32, bool);
 
-#[derive(Default, Debug)]
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
 struct Entries {
     entries: Vec<Entry>,
 }
@@ -53,7 +127,7 @@ impl Entries {
             if PRICING_DEBUG {
                 info!("Update {} {}+{}", asset, old_probability, probability);
             }
-            *old_probability += probability;
+            *old_probability = round_price(f64::from(*old_probability) + f64::from(probability));
         } else {
             if PRICING_DEBUG {
                 info!("New {} {}", item_name, probability);
@@ -75,6 +149,29 @@ impl Entries {
 
 lazy_static! {
     static ref TRADE_PRICING: TradePricing = TradePricing::read();
+    static ref PRICING_CACHE_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+    static ref EXPANDED_TABLES: Mutex<ExpansionCache> = Mutex::new(ExpansionCache::default());
+}
+
+type ExpandedTable = Arc<Vec<(f32, String, f32)>>;
+
+/// How well the expansion of nested loot tables is memoized, see
+/// [`ProbabilityFile::expansion_cache_stats`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ExpansionCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Tables currently held
+    pub tables: usize,
+}
+
+#[derive(Default)]
+struct ExpansionCache {
+    // canonical table id -> flattened and normalized content, expanded again
+    // once the watcher reports the table was reloaded
+    tables: HashMap<String, (ExpandedTable, ReloadWatcher)>,
+    hits: u64,
+    misses: u64,
 }
 
 #[derive(Clone)]
@@ -91,32 +188,79 @@ impl assets::Asset for ProbabilityFile {
     const EXTENSION: &'static str = "ron";
 }
 
+impl ProbabilityFile {
+    /// The flattened content of a loot table, shared tables (like the
+    /// creature commons) are only expanded once until they are reloaded
+    pub fn expanded(table: &str) -> ExpandedTable {
+        let id = canonical_table_id(table);
+        {
+            let mut cache = EXPANDED_TABLES.lock().unwrap();
+            let cached = match cache.tables.get_mut(id) {
+                Some((content, watcher)) if !watcher.reloaded() => Some(Arc::clone(content)),
+                _ => None,
+            };
+            if let Some(content) = cached {
+                cache.hits += 1;
+                return content;
+            }
+            cache.misses += 1;
+        }
+        // Don't hold the lock while loading, the loader expands nested tables
+        let handle = Self::load_expect(id);
+        let watcher = handle.reload_watcher();
+        let content = Arc::new(handle.read().content.clone());
+        EXPANDED_TABLES
+            .lock()
+            .unwrap()
+            .tables
+            .insert(id.to_owned(), (Arc::clone(&content), watcher));
+        content
+    }
+
+    #[must_use]
+    pub fn expansion_cache_stats() -> ExpansionCacheStats {
+        let cache = EXPANDED_TABLES.lock().unwrap();
+        ExpansionCacheStats {
+            hits: cache.hits,
+            misses: cache.misses,
+            tables: cache.tables.len(),
+        }
+    }
+}
+
+// Specifiers written slightly differently still refer to the same table
+fn canonical_table_id(table: &str) -> &str {
+    let table = table.trim();
+    table.strip_suffix(".ron").unwrap_or(table)
+}
+
 impl From<Vec<(f32, LootSpec<String>)>> for ProbabilityFile {
     #[allow(clippy::cast_precision_loss)]
     fn from(content: Vec<(f32, LootSpec<String>)>) -> Self {
         let rescale = if content.is_empty() {
             1.0
         } else {
-            1.0 / content.iter().map(|e| e.0).sum::<f32>()
+            1.0 / content.iter().map(|e| f64::from(e.0)).sum::<f64>()
         };
         Self {
             content: content
                 .into_iter()
-                .flat_map(|(p0, loot)| match loot {
-                    LootSpec::Item(asset) => vec![(p0 * rescale, asset, 1.0)].into_iter(),
-                    LootSpec::ItemQuantity(asset, a, b) => {
-                        vec![(p0 * rescale, asset, (a + b) as f32 * 0.5)].into_iter()
-                    },
-                    LootSpec::LootTable(table_asset) => {
-                        let unscaled = &Self::load_expect(&table_asset).read().content;
-                        let scale = p0 * rescale;
-                        unscaled
+                .flat_map(|(p0, loot)| {
+                    let p0 = f64::from(p0) * rescale;
+                    match loot {
+                        LootSpec::Item(asset) => vec![(round_price(p0), asset, 1.0)].into_iter(),
+                        LootSpec::ItemQuantity(asset, a, b) => {
+                            vec![(round_price(p0), asset, (a + b) as f32 * 0.5)].into_iter()
+                        },
+                        LootSpec::LootTable(table_asset) => Self::expanded(&table_asset)
                             .iter()
-                            .map(|(p1, asset, amount)| (*p1 * scale, asset.clone(), *amount))
+                            .map(|(p1, asset, amount)| {
+                                (round_price(f64::from(*p1) * p0), asset.clone(), *amount)
+                            })
                             .collect::<Vec<_>>()
-                            .into_iter()
-                    },
-                    LootSpec::Nothing => Vec::new().into_iter(),
+                            .into_iter(),
+                        LootSpec::Nothing => Vec::new().into_iter(),
+                    }
                 })
                 .collect(),
         }
@@ -136,7 +280,24 @@ impl assets::Asset for TradingPriceFile {
     const EXTENSION: &'static str = "ron";
 }
 
-#[derive(Clone, Debug, Default)]
+/// Hotfixes by server admins for the computed prices, applied to exactly the
+/// items listed (not to their equality sets)
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct PriceOverrides {
+    // item asset specifier -> price in coins
+    prices: BTreeMap<String, f32>,
+    // items which are neither bought nor sold by merchants
+    unsellable: Vec<String>,
+}
+
+impl assets::Asset for PriceOverrides {
+    type Loader = assets::RonLoader;
+
+    const EXTENSION: &'static str = "ron";
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 struct EqualitySet {
     // which item should this item's occurrences be counted towards
     equivalence_class: HashMap<String, String>,
@@ -153,6 +314,16 @@ impl EqualitySet {
     }
 }
 
+impl EqualitySet {
+    // HashMap iteration order is random, needed for stable hashing
+    fn sorted_classes(&self) -> BTreeMap<&str, &str> {
+        self.equivalence_class
+            .iter()
+            .map(|(item, class)| (item.as_str(), class.as_str()))
+            .collect()
+    }
+}
+
 impl assets::Compound for EqualitySet {
     fn load<S: assets::source::Source + ?Sized>(
         cache: &assets::AssetCache<S>,
@@ -172,7 +343,7 @@ impl assets::Compound for EqualitySet {
         for set in manifest {
             let items = match set {
                 EqualitySpec::LootTable(table) => {
-                    let acc = &ProbabilityFile::load_expect(table).read().content;
+                    let acc = ProbabilityFile::expanded(table);
 
                     acc.iter().map(|(_p, item, _)| item).cloned().collect()
                 },
@@ -193,10 +364,11 @@ impl assets::Compound for EqualitySet {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 struct RememberedRecipe {
     output: String,
     amount: u32,
+    #[serde(skip)]
     material_cost: f32,
     input: Vec<(String, u32)>,
 }
@@ -210,14 +382,29 @@ fn sort_and_normalize(entryvec: &mut [Entry], scale: f32) {
         });
         if let Some((_, max_scale, _)) = entryvec.last() {
             // most common item has frequency max_scale.  avoid NaN
-            let rescale = scale / max_scale;
+            let rescale = f64::from(scale) / f64::from(*max_scale);
             for i in entryvec.iter_mut() {
-                i.1 *= rescale;
+                i.1 = round_price(f64::from(i.1) * rescale);
             }
         }
     }
 }
 
+/// Rounds a value accumulated in f64 to one which is exactly representable as
+/// f32. It only uses integer operations on the bits, so prices come out the
+/// same on every platform.
+#[allow(clippy::cast_possible_truncation)]
+fn round_price(value: f64) -> f32 {
+    if !value.is_finite() {
+        return value as f32;
+    }
+    let dropped = 52 - PRICE_PRECISION_BITS;
+    let half = 1_u64 << (dropped - 1);
+    let mask = !((1_u64 << dropped) - 1);
+    // A carry out of the mantissa correctly rounds up to the next exponent
+    f64::from_bits((value.to_bits() + half) & mask) as f32
+}
+
 fn get_scaling(contents: &AssetGuard<TradingPriceFile>, good: Good) -> f32 {
     contents
         .good_scaling
@@ -231,6 +418,8 @@ impl TradePricing {
     const CRAFTING_FACTOR: f32 = 0.95;
     // increase price a bit compared to sum of ingredients
     const INVEST_FACTOR: f32 = 0.33;
+    // bounds the length of shopping lists when items are very cheap
+    const SHOPPING_LIST_MAX_PICKS: usize = 32;
     const UNAVAILABLE_PRICE: f32 = 1_000_000.0;
 
     // add this much of a non-consumed crafting tool price
@@ -242,6 +431,9 @@ impl TradePricing {
             Good::Potions => &self.potions.entries,
             Good::Food => &self.food.entries,
             Good::Ingredients => &self.ingredients.entries,
+            Good::ModularComponents => &self.modular_components.entries,
+            Good::Furniture => &self.furniture.entries,
+            Good::Pets => &self.pets.entries,
             _ => &[],
         }
     }
@@ -253,6 +445,9 @@ impl TradePricing {
             Good::Potions => &mut self.potions.entries,
             Good::Food => &mut self.food.entries,
             Good::Ingredients => &mut self.ingredients.entries,
+            Good::ModularComponents => &mut self.modular_components.entries,
+            Good::Furniture => &mut self.furniture.entries,
+            Good::Pets => &mut self.pets.entries,
             _ => &mut [],
         }
     }
@@ -264,6 +459,10 @@ impl TradePricing {
             // Tools
             _ if name.starts_with("common.items.weapons.") => &self.tools.entries,
             _ if name.starts_with("common.items.tool.") => &self.tools.entries,
+            // Modular components (before the generic crafting ingredients)
+            _ if name.starts_with("common.items.crafting_ing.modular.") => {
+                &self.modular_components.entries
+            },
             // Ingredients
             _ if name.starts_with("common.items.crafting_ing.") => &self.ingredients.entries,
             _ if name.starts_with("common.items.mineral.") => &self.ingredients.entries,
@@ -272,12 +471,17 @@ impl TradePricing {
             _ if name.starts_with("common.items.consumable.") => &self.potions.entries,
             // Food
             _ if name.starts_with("common.items.food.") => &self.food.entries,
+            // Furniture
+            _ if name.starts_with("common.items.lantern.") => &self.furniture.entries,
+            "common.items.boss_drops.lantern" => &self.furniture.entries,
+            "common.items.utility.training_dummy" => &self.furniture.entries,
+            // Pets
+            "common.items.utility.collar" => &self.pets.entries,
             // Other
             _ if name.starts_with("common.items.glider.") => &self.other.entries,
             _ if name.starts_with("common.items.utility.") => &self.other.entries,
             _ if name.starts_with("common.items.boss_drops.") => &self.other.entries,
             _ if name.starts_with("common.items.crafting_tools.") => &self.other.entries,
-            _ if name.starts_with("common.items.lantern.") => &self.other.entries,
             _ => {
                 warn!("unknown loot item {}", name);
                 &self.other.entries
@@ -292,6 +496,10 @@ impl TradePricing {
             // Tools
             _ if name.starts_with("common.items.weapons.") => &mut self.tools,
             _ if name.starts_with("common.items.tool.") => &mut self.tools,
+            // Modular components (before the generic crafting ingredients)
+            _ if name.starts_with("common.items.crafting_ing.modular.") => {
+                &mut self.modular_components
+            },
             // Ingredients
             _ if name.starts_with("common.items.crafting_ing.") => &mut self.ingredients,
             _ if name.starts_with("common.items.mineral.") => &mut self.ingredients,
@@ -300,12 +508,17 @@ impl TradePricing {
             _ if name.starts_with("common.items.consumable.") => &mut self.potions,
             // Food
             _ if name.starts_with("common.items.food.") => &mut self.food,
+            // Furniture
+            _ if name.starts_with("common.items.lantern.") => &mut self.furniture,
+            "common.items.boss_drops.lantern" => &mut self.furniture,
+            "common.items.utility.training_dummy" => &mut self.furniture,
+            // Pets
+            "common.items.utility.collar" => &mut self.pets,
             // Other
             _ if name.starts_with("common.items.glider.") => &mut self.other,
             _ if name.starts_with("common.items.utility.") => &mut self.other,
             _ if name.starts_with("common.items.boss_drops.") => &mut self.other,
             _ if name.starts_with("common.items.crafting_tools.") => &mut self.other,
-            _ if name.starts_with("common.items.lantern.") => &mut self.other,
             _ => {
                 warn!("unknown loot item {}", name);
                 &mut self.other
@@ -314,7 +527,7 @@ impl TradePricing {
     }
 
     // look up price (inverse frequency) of an item
-    fn price_lookup(&self, eqset: &EqualitySet, requested_name: &str) -> f32 {
+    fn price_lookup(&self, eqset: &EqualitySet, requested_name: &str) -> f64 {
         let canonical_name = eqset.canonical(requested_name);
 
         let goods = self.get_list_by_path(canonical_name);
@@ -324,19 +537,21 @@ impl TradePricing {
             .iter()
             .find(|(name, _, _)| name == canonical_name)
             .map_or(
-                Self::UNAVAILABLE_PRICE / Self::INVEST_FACTOR + 1.0,
-                |(_, freq, _)| 1.0 / freq,
+                f64::from(Self::UNAVAILABLE_PRICE / Self::INVEST_FACTOR + 1.0),
+                |(_, freq, _)| 1.0 / f64::from(*freq),
             )
     }
 
-    #[allow(clippy::cast_precision_loss)]
     fn calculate_material_cost(&self, r: &RememberedRecipe, eqset: &EqualitySet) -> f32 {
-        r.input
-            .iter()
-            .map(|(name, amount)| {
-                self.price_lookup(eqset, name) * (*amount as f32).max(Self::INVEST_FACTOR)
-            })
-            .sum()
+        round_price(
+            r.input
+                .iter()
+                .map(|(name, amount)| {
+                    self.price_lookup(eqset, name)
+                        * f64::from(*amount).max(f64::from(Self::INVEST_FACTOR))
+                })
+                .sum(),
+        )
     }
 
     // re-look up prices and sort the vector by ascending material cost, return
@@ -353,76 +568,170 @@ impl TradePricing {
             .is_some()
     }
 
-    #[allow(clippy::cast_precision_loss)]
     fn read() -> Self {
-        let mut result = Self::default();
         let price_config =
             TradingPriceFile::load_expect("common.trading.item_price_calculation").read();
         let eqset = EqualitySet::load_expect("common.trading.item_price_equality").read();
-        result.equality_set = eqset.clone();
-        for table in &price_config.loot_tables {
-            if PRICING_DEBUG {
-                info!(?table);
-            }
-            let (frequency, can_sell, asset_path) = table;
-            let loot = ProbabilityFile::load_expect(asset_path);
-            for (p, item_asset, amount) in &loot.read().content {
+        let loot_tables = price_config
+            .loot_tables
+            .iter()
+            .map(|(frequency, can_sell, asset_path)| {
+                if PRICING_DEBUG {
+                    info!(?asset_path);
+                }
+                let loot = ProbabilityFile::expanded(asset_path).to_vec();
+                (*frequency, *can_sell, loot)
+            })
+            .collect::<Vec<_>>();
+        let stats = ProbabilityFile::expansion_cache_stats();
+        debug!(
+            "Expanded {} loot tables, {} expansions reused",
+            stats.tables, stats.hits
+        );
+        let recipes = Self::remembered_recipes();
+
+        let loot_hash = hash_json(&(&loot_tables, eqset.sorted_classes()));
+        let recipe_hash = hash_json(&recipes);
+
+        let cache_path = pricing_cache_path();
+        let cache = cache_path.as_deref().and_then(PricingCache::load);
+
+        let mut result = match cache {
+            Some(cache) if cache.loot_hash == loot_hash && cache.recipe_hash == recipe_hash => {
+                info!("Using cached trade pricing");
+                cache.crafted
+            },
+            cache => {
+                let looted = match cache {
+                    Some(cache) if cache.loot_hash == loot_hash => {
+                        info!("Recipes changed, recomputing crafted trade prices");
+                        cache.looted
+                    },
+                    _ => {
+                        info!("Computing trade prices");
+                        Self::apply_loot_tables(&loot_tables, &eqset)
+                    },
+                };
+                let mut crafted = looted.clone();
+                crafted.apply_recipes(recipes, &eqset);
+
+                if let Some(path) = &cache_path {
+                    PricingCache {
+                        version: PRICING_CACHE_VERSION,
+                        loot_hash,
+                        recipe_hash,
+                        looted,
+                        crafted: crafted.clone(),
+                    }
+                    .save(path);
+                }
+                crafted
+            },
+        };
+
+        result.finalize(&price_config);
+        match PriceOverrides::load("common.trading.item_price_overrides") {
+            Ok(overrides) => result.apply_overrides(&overrides.read()),
+            Err(error) => warn!(?error, "Failed to load trade price overrides, ignoring them"),
+        }
+        result
+    }
+
+    /// Accumulate the frequency of items dropped from the configured loot
+    /// tables
+    fn apply_loot_tables(
+        loot_tables: &[(f32, bool, Vec<(f32, String, f32)>)],
+        eqset: &EqualitySet,
+    ) -> Self {
+        let mut result = Self {
+            equality_set: eqset.clone(),
+            ..Self::default()
+        };
+        for (frequency, can_sell, loot) in loot_tables {
+            for (p, item_asset, amount) in loot {
                 result.get_list_by_path_mut(item_asset).add(
-                    &eqset,
+                    eqset,
                     item_asset,
-                    frequency * p * *amount,
+                    round_price(f64::from(*frequency) * f64::from(*p) * f64::from(*amount)),
                     *can_sell,
                 );
             }
         }
+        result
+    }
 
-        // Apply recipe book
+    fn remembered_recipes() -> Vec<RememberedRecipe> {
         let book = default_recipe_book().read();
-        let mut ordered_recipes: Vec<RememberedRecipe> = Vec::new();
-        for (_, recipe) in book.iter() {
-            let (ref asset_path, amount) = recipe.output;
-            ordered_recipes.push(RememberedRecipe {
-                output: asset_path.id().into(),
-                amount,
-                material_cost: Self::UNAVAILABLE_PRICE,
-                input: recipe
-                    .inputs
-                    .iter()
-                    .filter_map(|&(ref recipe_input, count)| {
-                        if let RecipeInput::Item(it) = recipe_input {
-                            // If item is not consumed in craft, ignore it
-                            if count == 0 {
-                                None
+        let mut recipes: Vec<RememberedRecipe> = book
+            .iter()
+            .map(|(_, recipe)| {
+                let (ref asset_path, amount) = recipe.output;
+                RememberedRecipe {
+                    output: asset_path.id().into(),
+                    amount,
+                    material_cost: Self::UNAVAILABLE_PRICE,
+                    input: recipe
+                        .inputs
+                        .iter()
+                        .filter_map(|&(ref recipe_input, count)| {
+                            if let RecipeInput::Item(it) = recipe_input {
+                                // If item is not consumed in craft, ignore it
+                                if count == 0 {
+                                    None
+                                } else {
+                                    Some((it.id().into(), count))
+                                }
                             } else {
-                                Some((it.id().into(), count))
+                                None
                             }
-                        } else {
-                            None
-                        }
-                    })
-                    .collect(),
-            });
-        }
+                        })
+                        .collect(),
+                }
+            })
+            .collect();
+        recipes.extend(modular::recipes_for_pricing().into_iter().map(
+            |(output, amount, input)| RememberedRecipe {
+                output,
+                amount,
+                material_cost: Self::UNAVAILABLE_PRICE,
+                input,
+            },
+        ));
+        // The recipe book has no defined order, sort so the fixpoint and the
+        // cache hash are deterministic
+        recipes.sort_by(|a, b| a.output.cmp(&b.output).then_with(|| a.input.cmp(&b.input)));
+        recipes
+    }
 
+    fn apply_recipes(&mut self, mut ordered_recipes: Vec<RememberedRecipe>, eqset: &EqualitySet) {
         // re-evaluate prices based on crafting tables
         // (start with cheap ones to avoid changing material prices after evaluation)
-        while result.sort_by_price(&mut ordered_recipes, &eqset) {
+        while self.sort_by_price(&mut ordered_recipes, eqset) {
             ordered_recipes.retain(|recipe| {
                 if recipe.material_cost < 1e-5 {
                     false
                 } else if recipe.material_cost < Self::UNAVAILABLE_PRICE {
-                    let actual_cost = result.calculate_material_cost(recipe, &eqset);
+                    let actual_cost = self.calculate_material_cost(recipe, eqset);
                     let output_tradeable = recipe.input.iter().all(|(input, _)| {
-                        result
-                            .get_list_by_path(input)
+                        self.get_list_by_path(input)
                             .iter()
                             .find(|(item, _, _)| item == input)
                             .map_or(false, |(_, _, tradeable)| *tradeable)
                     });
-                    result.get_list_by_path_mut(&recipe.output).add(
-                        &eqset,
+                    let known = self
+                        .get_list_by_path(&recipe.output)
+                        .iter()
+                        .any(|(item, _, _)| *item == recipe.output);
+                    if !known && !output_tradeable {
+                        self.crafted_only.insert(recipe.output.clone());
+                    }
+                    self.get_list_by_path_mut(&recipe.output).add(
+                        eqset,
                         &recipe.output,
-                        (recipe.amount as f32) / actual_cost * Self::CRAFTING_FACTOR,
+                        round_price(
+                            f64::from(recipe.amount) / f64::from(actual_cost)
+                                * f64::from(Self::CRAFTING_FACTOR),
+                        ),
                         output_tradeable,
                     );
                     false
@@ -432,29 +741,85 @@ impl TradePricing {
             });
             //info!(?ordered_recipes);
         }
+    }
 
+    /// Normalize the frequencies and derive the material cache, cheap enough
+    /// to always be done after loading
+    fn finalize(&mut self, price_config: &AssetGuard<TradingPriceFile>) {
         let good_list = [
             Good::Armor,
             Good::Tools,
             Good::Potions,
             Good::Food,
             Good::Ingredients,
+            Good::ModularComponents,
+            Good::Furniture,
+            Good::Pets,
         ];
 
         for good in &good_list {
-            sort_and_normalize(
-                result.get_list_mut(*good),
-                get_scaling(&price_config, *good),
-            );
-            let mut materials = result
+            sort_and_normalize(self.get_list_mut(*good), get_scaling(price_config, *good));
+            let mut materials = self
                 .get_list(*good)
                 .iter()
-                .map(|i| (i.0.clone(), (*good, 1.0 / i.1)))
+                .map(|i| (i.0.clone(), (*good, round_price(1.0 / f64::from(i.1)))))
                 .collect::<Vec<_>>();
-            result.material_cache.extend(materials.drain(..));
+            self.material_cache.extend(materials.drain(..));
         }
-        result.coin_scale = get_scaling(&price_config, Good::Coin);
-        result
+        self.coin_scale = get_scaling(price_config, Good::Coin);
+    }
+
+    /// Pin the prices and mark the items unsellable listed in `overrides`,
+    /// invalid entries are skipped with a warning
+    fn apply_overrides(&mut self, overrides: &PriceOverrides) {
+        let known = |pricing: &Self, item: &str| {
+            pricing.find_entry(item).is_some() || Arc::<ItemDef>::load_cloned(item).is_ok()
+        };
+
+        for item in &overrides.unsellable {
+            if item == Self::COIN_ITEM || !known(self, item) {
+                warn!(?item, "Can't mark unknown item or coins unsellable");
+                continue;
+            }
+            info!(?item, old = ?self.query_price_impl(item), "Marking item unsellable");
+            for entries in [
+                &mut self.armor,
+                &mut self.tools,
+                &mut self.potions,
+                &mut self.food,
+                &mut self.ingredients,
+                &mut self.modular_components,
+                &mut self.furniture,
+                &mut self.pets,
+                &mut self.other,
+            ] {
+                entries
+                    .entries
+                    .iter_mut()
+                    .filter(|(name, _, _)| name == item)
+                    .for_each(|(_, _, can_sell)| *can_sell = false);
+            }
+            self.unsellable.insert(item.clone());
+        }
+
+        for (item, &price) in &overrides.prices {
+            if !price.is_finite() || price <= 0.0 {
+                warn!(?item, ?price, "Ignoring price override which isn't positive");
+            } else if item == Self::COIN_ITEM || !known(self, item) {
+                warn!(?item, "Ignoring price override of unknown item or coins");
+            } else if self.unsellable.contains(item) {
+                warn!(?item, "Ignoring price override of item marked unsellable");
+            } else {
+                info!(?item, old = ?self.query_price_impl(item), new = price, "Pinning price");
+                self.pinned_prices.insert(item.clone(), price);
+            }
+        }
+    }
+
+    /// Set the directory used to persist computed prices between runs
+    /// Has to be called before prices are first used to have an effect
+    pub fn set_cache_dir(path: &Path) {
+        *PRICING_CACHE_DIR.lock().unwrap() = Some(path.to_owned());
     }
 
     #[allow(
@@ -462,7 +827,13 @@ impl TradePricing {
         clippy::cast_sign_loss,
         clippy::cast_precision_loss
     )]
-    fn random_item_impl(&self, good: Good, amount: f32, selling: bool) -> Option<String> {
+    fn random_item_impl(
+        &self,
+        good: Good,
+        amount: f32,
+        selling: bool,
+        rng: &mut impl Rng,
+    ) -> Option<String> {
         if good == Good::Coin {
             Some(Self::COIN_ITEM.into())
         } else {
@@ -481,7 +852,7 @@ impl TradePricing {
                 .map_or(upper - 1, |i| i.0);
             loop {
                 let index =
-                    (rand::random::<f32>() * ((upper - lower) as f32)).floor() as usize + lower;
+                    (rng.gen::<f32>() * ((upper - lower) as f32)).floor() as usize + lower;
                 if table.get(index).map_or(false, |i| !selling || i.2) {
                     break table.get(index).map(|i| i.0.clone());
                 }
@@ -491,23 +862,174 @@ impl TradePricing {
 
     #[must_use]
     pub fn random_item(good: Good, amount: f32, selling: bool) -> Option<String> {
-        TRADE_PRICING.random_item_impl(good, amount, selling)
+        TRADE_PRICING.random_item_impl(good, amount, selling, &mut rand::thread_rng())
     }
 
+    /// Like `random_item` but draws from the given rng, so results can be
+    /// reproduced
     #[must_use]
-    pub fn get_material(item: &str) -> (Good, f32) {
+    pub fn random_item_seeded(
+        good: Good,
+        amount: f32,
+        selling: bool,
+        rng: &mut impl Rng,
+    ) -> Option<String> {
+        TRADE_PRICING.random_item_impl(good, amount, selling, rng)
+    }
+
+    /// The good an item counts as and how much of it, `None` for unsellable
+    /// and unknown items
+    #[must_use]
+    pub fn get_material(item: &str) -> Option<(Good, f32)> {
         if item == Self::COIN_ITEM {
-            (Good::Coin, 1.0)
+            Some((Good::Coin, 1.0))
+        } else if let Some(&price) = TRADE_PRICING.pinned_prices.get(item) {
+            Some((Good::Coin, price))
+        } else if TRADE_PRICING.unsellable.contains(item) {
+            None
         } else {
             let item = TRADE_PRICING.equality_set.canonical(item);
 
-            TRADE_PRICING.material_cache.get(item).copied().map_or(
-                (Good::Terrain(crate::terrain::BiomeKind::Void), 0.0),
-                |(a, b)| (a, b * TRADE_PRICING.coin_scale),
-            )
+            TRADE_PRICING
+                .material_cache
+                .get(item)
+                .map(|&(good, amount)| (good, amount * TRADE_PRICING.coin_scale))
         }
     }
 
+    fn find_entry(&self, item: &str) -> Option<&Entry> {
+        let canonical = self.equality_set.canonical(item);
+        self.get_list_by_path(canonical)
+            .iter()
+            .find(|(name, _, _)| name == canonical)
+    }
+
+    fn query_price_impl(&self, item: &str) -> PriceQueryResult {
+        if item == Self::COIN_ITEM {
+            return PriceQueryResult::Priced {
+                good: Good::Coin,
+                amount: 1.0,
+            };
+        }
+        let item_def = Arc::<ItemDef>::load_cloned(item).ok();
+        if item_def.as_ref().map_or(false, |def| {
+            matches!(def.quality, Quality::Artifact | Quality::Debug)
+        }) {
+            return PriceQueryResult::Sentimental;
+        }
+        if self.unsellable.contains(item) {
+            return PriceQueryResult::NotTradable;
+        }
+        if let Some(&amount) = self.pinned_prices.get(item) {
+            return PriceQueryResult::Priced {
+                good: Good::Coin,
+                amount,
+            };
+        }
+
+        let canonical = self.equality_set.canonical(item);
+        match self.material_cache.get(canonical) {
+            Some(&(good, amount)) if amount.is_finite() && amount > 0.0 => {
+                PriceQueryResult::Priced {
+                    good,
+                    amount: amount * self.coin_scale,
+                }
+            },
+            _ if self.crafted_only.contains(canonical) => PriceQueryResult::OnlyCraftable,
+            _ if item_def.is_some() || self.find_entry(item).is_some() => {
+                PriceQueryResult::NotTradable
+            },
+            _ => PriceQueryResult::UnknownItem,
+        }
+    }
+
+    fn query_merchant_stock_impl(&self, item: &str) -> PriceQueryResult {
+        match self.query_price_impl(item) {
+            priced @ PriceQueryResult::Priced { .. } => {
+                if item == Self::COIN_ITEM
+                    || self.find_entry(item).map_or(false, |(_, _, can_sell)| *can_sell)
+                {
+                    priced
+                } else if self.crafted_only.contains(self.equality_set.canonical(item)) {
+                    PriceQueryResult::OnlyCraftable
+                } else {
+                    PriceQueryResult::NotTradable
+                }
+            },
+            other => other,
+        }
+    }
+
+    /// Value of an item when traded, or why it has none
+    ///
+    /// Unlike `get_material` this distinguishes between the different reasons
+    /// an item can't be priced.
+    #[must_use]
+    pub fn query_price(item: &str) -> PriceQueryResult { TRADE_PRICING.query_price_impl(item) }
+
+    /// Like `query_price` but additionally requires that merchants are allowed
+    /// to stock the item
+    #[must_use]
+    pub fn query_merchant_stock(item: &str) -> PriceQueryResult {
+        TRADE_PRICING.query_merchant_stock_impl(item)
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn shopping_list_impl(
+        &self,
+        budget: f32,
+        needs: &ShoppingNeeds,
+        rng: &mut impl Rng,
+    ) -> ShoppingList {
+        let total_share = needs.0.iter().map(|(_, share)| share.max(0.0)).sum::<f32>();
+        let scale = if total_share > 1.0 { total_share.recip() } else { 1.0 };
+
+        let mut list = ShoppingList::default();
+        for &(good, share) in &needs.0 {
+            let mut left = budget * share.max(0.0) * scale;
+            let mut spent = 0.0;
+            // Prices in different goods can't be added up against one budget
+            let mut offers: Vec<(&str, f32)> = self
+                .get_list(good)
+                .iter()
+                .filter(|(_, _, can_sell)| *can_sell)
+                .filter_map(|(item, _, _)| match self.query_merchant_stock_impl(item) {
+                    PriceQueryResult::Priced {
+                        good: priced_in,
+                        amount,
+                    } if priced_in == good => Some((item.as_str(), amount)),
+                    _ => None,
+                })
+                .collect();
+            for _ in 0..Self::SHOPPING_LIST_MAX_PICKS {
+                offers.retain(|(_, price)| *price <= left);
+                if offers.is_empty() {
+                    break;
+                }
+                let (item, price) = offers[rng.gen_range(0..offers.len())];
+                left -= price;
+                spent += price;
+                match list.items.iter_mut().find(|(name, _)| name == item) {
+                    Some((_, amount)) => *amount += 1,
+                    None => list.items.push((item.to_owned(), 1)),
+                }
+            }
+            match list.costs.iter_mut().find(|(g, _)| *g == good) {
+                Some((_, cost)) => *cost += spent,
+                None if spent > 0.0 => list.costs.push((good, spent)),
+                None => {},
+            }
+        }
+        list
+    }
+
+    /// Picks items worth at most `budget` (in the units of `get_material`)
+    /// from what merchants stock, split between goods as given by `needs`
+    #[must_use]
+    pub fn shopping_list(budget: f32, needs: &ShoppingNeeds, rng: &mut impl Rng) -> ShoppingList {
+        TRADE_PRICING.shopping_list_impl(budget, needs, rng)
+    }
+
     #[cfg(test)]
     fn instance() -> &'static Self { &TRADE_PRICING }
 
@@ -621,11 +1143,81 @@ impl TradePricing {
             |_i, _p| String::new(),
             "",
         );
+        printvec(
+            "Modular components",
+            &self.modular_components.entries,
+            |_i, _p| String::new(),
+            "",
+        );
+        printvec("Furniture", &self.furniture.entries, |_i, _p| String::new(), "");
+        printvec("Pets", &self.pets.entries, |_i, _p| String::new(), "");
         printvec("Other", &self.other.entries, |_i, _p| String::new(), "");
         println!("{}, yes, {}, Coin, ,,,", Self::COIN_ITEM, self.coin_scale);
     }
 }
 
+/// Intermediate and final results of a pricing computation stored on disk
+///
+/// `looted` is valid as long as the loot tables and equality sets are
+/// unchanged, `crafted` additionally depends on the recipe book.
+#[derive(Serialize, Deserialize)]
+struct PricingCache {
+    version: u32,
+    loot_hash: String,
+    recipe_hash: String,
+    looted: TradePricing,
+    crafted: TradePricing,
+}
+
+impl PricingCache {
+    fn load(path: &Path) -> Option<Self> {
+        let file = std::fs::File::open(path).ok()?;
+        match serde_json::from_reader::<_, Self>(std::io::BufReader::new(file)) {
+            Ok(cache) if cache.version == PRICING_CACHE_VERSION => Some(cache),
+            Ok(_) => {
+                debug!(?path, "Ignoring trade pricing cache of an older version");
+                None
+            },
+            Err(error) => {
+                warn!(?error, ?path, "Failed to read trade pricing cache");
+                None
+            },
+        }
+    }
+
+    fn save(&self, path: &Path) {
+        let write = || -> std::io::Result<()> {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            // Write to a temporary file first so a crash can't leave a truncated cache
+            let tmp_path = path.with_extension("json.tmp");
+            let file = std::fs::File::create(&tmp_path)?;
+            serde_json::to_writer(std::io::BufWriter::new(file), self)?;
+            std::fs::rename(tmp_path, path)
+        };
+        if let Err(error) = write() {
+            warn!(?error, ?path, "Failed to write trade pricing cache");
+        }
+    }
+}
+
+fn pricing_cache_path() -> Option<PathBuf> {
+    PRICING_CACHE_DIR
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|dir| dir.join(PRICING_CACHE_FILE))
+}
+
+fn hash_json<T: Serialize>(value: &T) -> String {
+    let json = serde_json::to_string(value).unwrap_or_default();
+    Sha256::digest(json.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
 /// hierarchically combine and scale this loot table
 #[must_use]
 pub fn expand_loot_table(loot_table: &str) -> Vec<(f32, String, f32)> {
@@ -637,7 +1229,11 @@ pub fn expand_loot_table(loot_table: &str) -> Vec<(f32, String, f32)> {
 #[cfg(test)]
 mod tests {
     use crate::{
-        comp::inventory::trade_pricing::{expand_loot_table, ProbabilityFile, TradePricing},
+        comp::inventory::trade_pricing::{
+            expand_loot_table, hash_json, round_price, sort_and_normalize, EqualitySet,
+            PriceOverrides, PriceQueryResult, PricingCache, ProbabilityFile, RememberedRecipe,
+            ShoppingNeeds, TradePricing, PRICING_CACHE_FILE, PRICING_CACHE_VERSION,
+        },
         lottery::LootSpec,
         trade::Good,
     };
@@ -679,6 +1275,15 @@ mod tests {
         TradePricing::instance().print_sorted();
     }
 
+    #[test]
+    fn test_goods_have_items() {
+        init();
+        let pricing = TradePricing::instance();
+        for good in [Good::Furniture, Good::Pets] {
+            assert!(!pricing.get_list(good).is_empty(), "{:?} has no items", good);
+        }
+    }
+
     #[test]
     fn test_prices2() {
         init();
@@ -737,4 +1342,196 @@ mod tests {
         let probability: ProbabilityFile = loot_table.into();
         assert!(normalized(&probability));
     }
+
+    #[test]
+    fn test_expansion_cache() {
+        let table = "common.loot_tables.creature.quad_medium.catoblepas";
+        let first = ProbabilityFile::expanded(table);
+        let before = ProbabilityFile::expansion_cache_stats();
+        // Written differently, but the same table
+        let second = ProbabilityFile::expanded(&format!(" {}.ron", table));
+        let after = ProbabilityFile::expansion_cache_stats();
+
+        assert_eq!(first, second);
+        assert!(after.hits > before.hits);
+        assert!(after.tables >= 1);
+    }
+
+    #[test]
+    fn test_pricing_cache_roundtrip() {
+        init();
+        let pricing = TradePricing::instance().clone();
+        let path = std::env::temp_dir()
+            .join("veloren_trade_pricing_cache_test")
+            .join(PRICING_CACHE_FILE);
+        PricingCache {
+            version: PRICING_CACHE_VERSION,
+            loot_hash: hash_json(&"loot"),
+            recipe_hash: hash_json(&"recipes"),
+            looted: TradePricing::default(),
+            crafted: pricing.clone(),
+        }
+        .save(&path);
+
+        let loaded = PricingCache::load(&path).expect("cache should be readable");
+        assert_eq!(loaded.loot_hash, hash_json(&"loot"));
+        assert_eq!(loaded.crafted.armor.entries, pricing.armor.entries);
+        assert_eq!(loaded.crafted.tools.entries, pricing.tools.entries);
+        assert!(loaded.looted.armor.entries.is_empty());
+    }
+
+    #[test]
+    fn test_price_queries() {
+        init();
+
+        assert_eq!(
+            TradePricing::query_price(TradePricing::COIN_ITEM),
+            PriceQueryResult::Priced {
+                good: Good::Coin,
+                amount: 1.0
+            }
+        );
+        assert_eq!(
+            TradePricing::query_price("common.items.this_item_does_not_exist"),
+            PriceQueryResult::UnknownItem
+        );
+        // Whatever merchants stock must have a price
+        let pricing = TradePricing::instance();
+        for (item, _, _) in &pricing.armor.entries {
+            if let PriceQueryResult::Priced { amount, .. } = TradePricing::query_merchant_stock(item)
+            {
+                assert!(amount > 0.0);
+                assert!(TradePricing::query_price(item).is_priced());
+            }
+        }
+    }
+
+    #[test]
+    fn test_price_overrides() {
+        init();
+        let mut pricing = TradePricing::instance().clone();
+        let mut armor = pricing
+            .armor
+            .entries
+            .iter()
+            .filter(|(_, _, can_sell)| *can_sell)
+            .map(|(item, _, _)| item.clone());
+        let (pinned, unsellable, invalid) = (
+            armor.next().unwrap(),
+            armor.next().unwrap(),
+            armor.next().unwrap(),
+        );
+        let invalid_price = pricing.query_price_impl(&invalid);
+
+        let mut overrides = PriceOverrides::default();
+        overrides.prices.insert(pinned.clone(), 123.0);
+        overrides.prices.insert(invalid.clone(), -1.0);
+        overrides.prices.insert(unsellable.clone(), 5.0);
+        overrides
+            .prices
+            .insert("common.items.this_item_does_not_exist".to_owned(), 5.0);
+        overrides.unsellable.push(unsellable.clone());
+        pricing.apply_overrides(&overrides);
+
+        assert_eq!(pricing.query_price_impl(&pinned), PriceQueryResult::Priced {
+            good: Good::Coin,
+            amount: 123.0
+        });
+        // Unsellable wins over a price
+        assert_eq!(
+            pricing.query_merchant_stock_impl(&unsellable),
+            PriceQueryResult::NotTradable
+        );
+        assert_eq!(pricing.query_price_impl(&invalid), invalid_price);
+        assert!(!pricing.pinned_prices.contains_key("common.items.this_item_does_not_exist"));
+    }
+    #[test]
+    fn test_shopping_list() {
+        use rand::{rngs::StdRng, SeedableRng};
+        init();
+
+        let needs = ShoppingNeeds(vec![(Good::Food, 0.3), (Good::Tools, 0.2)]);
+        let list = TradePricing::shopping_list(1000.0, &needs, &mut StdRng::seed_from_u64(7));
+        assert!(list.cost(Good::Food) <= 300.0);
+        assert!(list.cost(Good::Tools) <= 200.0);
+        assert!(list.costs.iter().all(|(good, _)| needs.0.iter().any(|(g, _)| g == good)));
+        for (item, amount) in &list.items {
+            assert!(*amount > 0);
+            match TradePricing::query_merchant_stock(item) {
+                PriceQueryResult::Priced { good, .. } => {
+                    assert!(good == Good::Food || good == Good::Tools, "{} in {:?}", item, good)
+                },
+                other => panic!("{} is on the list but {:?}", item, other),
+            }
+        }
+        // Same seed, same list
+        assert_eq!(
+            TradePricing::shopping_list(1000.0, &needs, &mut StdRng::seed_from_u64(7)),
+            list
+        );
+        assert!(
+            TradePricing::shopping_list(0.0, &needs, &mut StdRng::seed_from_u64(7))
+                .items
+                .is_empty()
+        );
+    }
+    #[test]
+    fn test_round_price() {
+        assert_eq!(round_price(0.1).to_bits(), 0x3dcc_ccd0);
+        assert_eq!(round_price(1.0 / 3.0).to_bits(), 0x3eaa_aaa8);
+        assert_eq!(round_price(1e6), 1e6);
+        assert_eq!(round_price(f64::INFINITY), f32::INFINITY);
+        // Rounding is idempotent, so rounded prices can be accumulated again
+        assert_eq!(round_price(f64::from(round_price(0.1))), round_price(0.1));
+    }
+
+    // The values were computed independently of this implementation, they
+    // must match bit for bit on every platform
+    #[test]
+    fn test_pricing_golden_values() {
+        let (apple, cheese) = ("common.items.food.apple", "common.items.food.cheese");
+        let eqset = EqualitySet::default();
+        let mut pricing = TradePricing::apply_loot_tables(
+            &[
+                (1.0, true, vec![
+                    (0.5, apple.to_owned(), 1.0),
+                    (0.3, cheese.to_owned(), 2.0),
+                    (0.2, apple.to_owned(), 1.0),
+                ]),
+                (0.7, true, vec![(1.0, cheese.to_owned(), 1.5)]),
+            ],
+            &eqset,
+        );
+        let bits = |pricing: &TradePricing| {
+            pricing
+                .food
+                .entries
+                .iter()
+                .map(|(item, p, _)| (item.as_str(), p.to_bits()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(bits(&pricing), [(apple, 0x3f33_3338), (cheese, 0x3fd3_3338)]);
+
+        sort_and_normalize(&mut pricing.food.entries, 1.0);
+        assert_eq!(bits(&pricing), [(apple, 0x3ed9_3650), (cheese, 0x3f80_0000)]);
+
+        let recipes = vec![RememberedRecipe {
+            output: "common.items.food.apple_stick".to_owned(),
+            amount: 1,
+            material_cost: TradePricing::UNAVAILABLE_PRICE,
+            input: vec![(apple.to_owned(), 2), (cheese.to_owned(), 1)],
+        }];
+        assert_eq!(
+            pricing.calculate_material_cost(&recipes[0], &eqset).to_bits(),
+            0x40b6_db70
+        );
+        pricing.apply_recipes(recipes, &eqset);
+        let (_, crafted, _) = pricing
+            .food
+            .entries
+            .iter()
+            .find(|(item, _, _)| item == "common.items.food.apple_stick")
+            .unwrap();
+        assert_eq!(crafted.to_bits(), 0x3e2a_3d70);
+    }
 }
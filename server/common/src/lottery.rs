@@ -26,6 +26,8 @@
 // Cheese drop rate = 3/X = 29.6%
 // Coconut drop rate = 1/X = 9.85%
 
+pub mod writer;
+
 use crate::{
     assets::{self, AssetExt},
     comp::Item,
@@ -0,0 +1,605 @@
+//! Editing loot tables and writing them back to RON, while keeping the
+//! comments and blank lines of the file.
+//!
+//! Comments inside of an entry, e.g. between its weight and spec, have no
+//! place to be written back to, so files containing them are parsed but not
+//! saved.
+//!
+//! ```
+//! use veloren_common::lottery::{writer::LootTableFile, LootSpec};
+//!
+//! let mut table = LootTableFile::parse(
+//!     "[
+//!     // Gems
+//!     (0.4, Item(\"common.items.mineral.gem.ruby\")),
+//!     (2.0, Item(\"common.items.crafting_ing.stones\")),
+//! ]",
+//! )
+//! .unwrap();
+//! // +10% to all gem drops
+//! table.scale_weights(
+//!     |spec| matches!(spec, LootSpec::Item(item) if item.contains(".gem.")),
+//!     1.1,
+//! );
+//! assert!(table.to_ron().contains("// Gems"));
+//! ```
+
+use super::{LootSpec, Lottery};
+use crate::assets::ASSETS_PATH;
+use std::{error::Error, fmt, fs, io, path::PathBuf};
+
+/// A loot table as written in its file, see [`LootTableFile::parse`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct LootTableFile {
+    /// Comments before the opening bracket
+    header: Vec<String>,
+    pub entries: Vec<Entry>,
+    /// Comments after the last entry, inside of the brackets
+    footer: Vec<String>,
+    footer_blank_line: bool,
+    /// Comments after the closing bracket
+    trailer: Vec<String>,
+    /// Lines of the comments inside of entries, which [`Self::to_ron`] drops
+    inner_comment_lines: Vec<usize>,
+}
+
+/// A single `(weight, spec)` of a loot table, along with its comments
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+    pub weight: f32,
+    pub spec: LootSpec<String>,
+    /// Comments on the lines above the entry
+    pub comments: Vec<String>,
+    /// A comment on the same line, after the entry
+    pub trailing_comment: Option<String>,
+    blank_line_before: bool,
+    // Written back as is while the weight is unchanged, so `1` stays `1`
+    weight_text: String,
+}
+
+impl Entry {
+    pub fn new(weight: f32, spec: LootSpec<String>) -> Self {
+        Self {
+            weight,
+            spec,
+            comments: Vec::new(),
+            trailing_comment: None,
+            blank_line_before: false,
+            weight_text: String::new(),
+        }
+    }
+
+    fn write_weight(&self, out: &mut String) {
+        if self.weight_text.parse::<f32>().ok() == Some(self.weight) {
+            out.push_str(&self.weight_text);
+        } else {
+            out.push_str(&format!("{:?}", self.weight));
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+impl Error for ParseError {}
+
+impl LootTableFile {
+    pub fn parse(src: &str) -> Result<Self, ParseError> { Parser::new(src).table() }
+
+    /// Reads the table with the given asset specifier from the asset
+    /// directory, e.g. `common.loot_tables.materials.gems`
+    pub fn load(specifier: &str) -> Result<Self, Box<dyn Error>> {
+        let src = fs::read_to_string(path_of(specifier))?;
+        Ok(Self::parse(&src)?)
+    }
+
+    /// Writes the table back to the file of the given asset specifier
+    ///
+    /// Fails without touching the file if it has comments inside of entries,
+    /// which would be lost. The table is written next to the file and renamed
+    /// over it, so a failed write leaves the old table intact.
+    pub fn save(&self, specifier: &str) -> io::Result<()> {
+        if let Some(line) = self.inner_comment_lines.first() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "refusing to rewrite {}, the comment inside of an entry on line {} would be \
+                     lost",
+                    specifier, line
+                ),
+            ));
+        }
+        let path = path_of(specifier);
+        let tmp = path.with_extension("ron.tmp");
+        fs::write(&tmp, self.to_ron())?;
+        fs::rename(&tmp, &path)
+    }
+
+    /// Whether [`Self::to_ron`] writes back all comments of the parsed file
+    pub fn keeps_comments(&self) -> bool { self.inner_comment_lines.is_empty() }
+
+    /// Multiplies the weights of the entries matching `filter` by `factor`,
+    /// returns how many entries were changed
+    pub fn scale_weights(
+        &mut self,
+        mut filter: impl FnMut(&LootSpec<String>) -> bool,
+        factor: f32,
+    ) -> usize {
+        let mut changed = 0;
+        for entry in self.entries.iter_mut().filter(|entry| filter(&entry.spec)) {
+            entry.weight *= factor;
+            changed += 1;
+        }
+        changed
+    }
+
+    pub fn items(&self) -> Vec<(f32, LootSpec<String>)> {
+        self.entries
+            .iter()
+            .map(|entry| (entry.weight, entry.spec.clone()))
+            .collect()
+    }
+
+    pub fn to_lottery(&self) -> Lottery<LootSpec<String>> { Lottery::from(self.items()) }
+
+    pub fn to_ron(&self) -> String {
+        let mut out = String::new();
+        for comment in &self.header {
+            out.push_str(comment);
+            out.push('\n');
+        }
+        out.push_str("[\n");
+        for (i, entry) in self.entries.iter().enumerate() {
+            if entry.blank_line_before && i > 0 {
+                out.push('\n');
+            }
+            for comment in &entry.comments {
+                write_indented(&mut out, comment);
+            }
+            out.push_str("    (");
+            entry.write_weight(&mut out);
+            out.push_str(", ");
+            write_spec(&mut out, &entry.spec);
+            out.push_str("),");
+            if let Some(comment) = &entry.trailing_comment {
+                out.push(' ');
+                out.push_str(comment);
+            }
+            out.push('\n');
+        }
+        if self.footer_blank_line && !self.entries.is_empty() && !self.footer.is_empty() {
+            out.push('\n');
+        }
+        for comment in &self.footer {
+            write_indented(&mut out, comment);
+        }
+        out.push(']');
+        for comment in &self.trailer {
+            out.push('\n');
+            out.push_str(comment);
+        }
+        out.push('\n');
+        out
+    }
+}
+
+impl From<Vec<(f32, LootSpec<String>)>> for LootTableFile {
+    fn from(items: Vec<(f32, LootSpec<String>)>) -> Self {
+        Self {
+            header: Vec::new(),
+            entries: items
+                .into_iter()
+                .map(|(weight, spec)| Entry::new(weight, spec))
+                .collect(),
+            footer: Vec::new(),
+            footer_blank_line: false,
+            trailer: Vec::new(),
+            inner_comment_lines: Vec::new(),
+        }
+    }
+}
+
+fn path_of(specifier: &str) -> PathBuf {
+    let mut path = ASSETS_PATH.clone();
+    path.extend(specifier.split('.'));
+    path.set_extension("ron");
+    path
+}
+
+fn write_indented(out: &mut String, comment: &str) {
+    out.push_str("    ");
+    out.push_str(comment);
+    out.push('\n');
+}
+
+fn write_spec(out: &mut String, spec: &LootSpec<String>) {
+    match spec {
+        LootSpec::Item(item) => {
+            out.push_str("Item(");
+            write_str(out, item);
+            out.push(')');
+        },
+        LootSpec::ItemQuantity(item, lower, upper) => {
+            out.push_str("ItemQuantity(");
+            write_str(out, item);
+            out.push_str(&format!(", {}, {})", lower, upper));
+        },
+        LootSpec::LootTable(table) => {
+            out.push_str("LootTable(");
+            write_str(out, table);
+            out.push(')');
+        },
+        LootSpec::Nothing => out.push_str("Nothing"),
+    }
+}
+
+fn write_str(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        if c == '"' || c == '\\' {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out.push('"');
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Open(char),
+    Close(char),
+    Comma,
+    Number(String),
+    Str(String),
+    Ident(String),
+    Comment(String),
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+    line: usize,
+    peeked: Option<(Token, usize)>,
+    inner_comment_lines: Vec<usize>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(src: &'a str) -> Self {
+        Self {
+            src,
+            pos: 0,
+            line: 1,
+            peeked: None,
+            inner_comment_lines: Vec::new(),
+        }
+    }
+
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError {
+            line: self.line,
+            message: message.into(),
+        }
+    }
+
+    fn table(mut self) -> Result<LootTableFile, ParseError> {
+        let mut header = Vec::new();
+        loop {
+            match self.next_token()? {
+                Some((Token::Comment(comment), _)) => header.push(comment),
+                Some((Token::Open('['), _)) => break,
+                _ => return Err(self.error("expected `[`")),
+            }
+        }
+
+        let mut entries = Vec::new();
+        let mut comments = Vec::new();
+        let mut blank_line_before = false;
+        let (footer_blank_line, footer) = loop {
+            match self.next_token()? {
+                Some((Token::Comment(comment), breaks)) => {
+                    if breaks == 0 && comments.is_empty() {
+                        if let Some(entry) = entries.last_mut().filter(|e: &&mut Entry| {
+                            e.trailing_comment.is_none()
+                        }) {
+                            entry.trailing_comment = Some(comment);
+                            continue;
+                        }
+                    }
+                    if comments.is_empty() {
+                        blank_line_before = breaks > 1;
+                    }
+                    comments.push(comment);
+                },
+                Some((Token::Open('('), breaks)) => {
+                    if comments.is_empty() {
+                        blank_line_before = breaks > 1;
+                    }
+                    let mut entry = self.entry()?;
+                    entry.comments = std::mem::take(&mut comments);
+                    entry.blank_line_before = blank_line_before;
+                    entries.push(entry);
+                },
+                Some((Token::Close(']'), _)) => break (blank_line_before, comments),
+                _ => return Err(self.error("expected an entry or `]`")),
+            }
+        };
+
+        let mut trailer = Vec::new();
+        while let Some((token, _)) = self.next_token()? {
+            match token {
+                Token::Comment(comment) => trailer.push(comment),
+                _ => return Err(self.error("unexpected content after `]`")),
+            }
+        }
+
+        Ok(LootTableFile {
+            header,
+            entries,
+            footer,
+            footer_blank_line,
+            trailer,
+            inner_comment_lines: self.inner_comment_lines,
+        })
+    }
+
+    // Parses the rest of an entry after its `(`, including the comma after it
+    fn entry(&mut self) -> Result<Entry, ParseError> {
+        let weight_text = match self.next_content()? {
+            Token::Number(text) => text,
+            _ => return Err(self.error("expected a weight")),
+        };
+        let weight = weight_text
+            .parse()
+            .map_err(|_| self.error(format!("invalid weight `{}`", weight_text)))?;
+        self.expect(Token::Comma)?;
+        let spec = self.spec()?;
+        self.skip_comma()?;
+        self.expect(Token::Close(')'))?;
+        self.skip_comma()?;
+
+        Ok(Entry {
+            weight_text,
+            ..Entry::new(weight, spec)
+        })
+    }
+
+    fn spec(&mut self) -> Result<LootSpec<String>, ParseError> {
+        let kind = match self.next_content()? {
+            Token::Ident(kind) => kind,
+            _ => return Err(self.error("expected a loot spec")),
+        };
+        if kind == "Nothing" {
+            return Ok(LootSpec::Nothing);
+        }
+
+        self.expect(Token::Open('('))?;
+        let name = match self.next_content()? {
+            Token::Str(name) => name,
+            _ => return Err(self.error("expected a string")),
+        };
+        let spec = match kind.as_str() {
+            "Item" => LootSpec::Item(name),
+            "LootTable" => LootSpec::LootTable(name),
+            "ItemQuantity" => {
+                self.expect(Token::Comma)?;
+                let lower = self.amount()?;
+                self.expect(Token::Comma)?;
+                let upper = self.amount()?;
+                LootSpec::ItemQuantity(name, lower, upper)
+            },
+            _ => return Err(self.error(format!("unknown loot spec `{}`", kind))),
+        };
+        self.skip_comma()?;
+        self.expect(Token::Close(')'))?;
+        Ok(spec)
+    }
+
+    fn amount(&mut self) -> Result<u32, ParseError> {
+        match self.next_content()? {
+            Token::Number(text) => text
+                .parse()
+                .map_err(|_| self.error(format!("invalid amount `{}`", text))),
+            _ => Err(self.error("expected an amount")),
+        }
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), ParseError> {
+        let token = self.next_content()?;
+        if token == expected {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected {:?}, found {:?}", expected, token)))
+        }
+    }
+
+    fn skip_comma(&mut self) -> Result<(), ParseError> {
+        if matches!(self.peek()?, Some((Token::Comma, _))) {
+            self.peeked = None;
+        }
+        Ok(())
+    }
+
+    // Comments inside of an entry can't be kept apart from it, so they are
+    // dropped and remembered to keep the file from being saved
+    fn next_content(&mut self) -> Result<Token, ParseError> {
+        loop {
+            match self.next_token()? {
+                Some((Token::Comment(_), _)) => self.inner_comment_lines.push(self.line),
+                Some((token, _)) => return Ok(token),
+                None => return Err(self.error("unexpected end of file")),
+            }
+        }
+    }
+
+    fn peek(&mut self) -> Result<Option<&(Token, usize)>, ParseError> {
+        if self.peeked.is_none() {
+            self.peeked = self.lex()?;
+        }
+        Ok(self.peeked.as_ref())
+    }
+
+    fn next_token(&mut self) -> Result<Option<(Token, usize)>, ParseError> {
+        match self.peeked.take() {
+            Some(token) => Ok(Some(token)),
+            None => self.lex(),
+        }
+    }
+
+    // Returns the next token along with the line breaks before it
+    fn lex(&mut self) -> Result<Option<(Token, usize)>, ParseError> {
+        let src = self.src;
+        let rest = &src[self.pos..];
+        let trimmed = rest.trim_start();
+        let breaks = rest[..rest.len() - trimmed.len()].matches('\n').count();
+        self.line += breaks;
+        self.pos += rest.len() - trimmed.len();
+
+        let c = match trimmed.chars().next() {
+            Some(c) => c,
+            None => return Ok(None),
+        };
+        let len = match c {
+            '[' | '(' => {
+                self.pos += 1;
+                return Ok(Some((Token::Open(c), breaks)));
+            },
+            ']' | ')' => {
+                self.pos += 1;
+                return Ok(Some((Token::Close(c), breaks)));
+            },
+            ',' => {
+                self.pos += 1;
+                return Ok(Some((Token::Comma, breaks)));
+            },
+            '/' if trimmed.starts_with("//") => trimmed.find('\n').unwrap_or(trimmed.len()),
+            '/' if trimmed.starts_with("/*") => {
+                trimmed
+                    .find("*/")
+                    .ok_or_else(|| self.error("unterminated block comment"))?
+                    + 2
+            },
+            '"' => {
+                let mut escaped = false;
+                let mut value = String::new();
+                let mut len = None;
+                for (i, c) in trimmed.char_indices().skip(1) {
+                    match c {
+                        _ if escaped => {
+                            value.push(c);
+                            escaped = false;
+                        },
+                        '\\' => escaped = true,
+                        '"' => {
+                            len = Some(i + 1);
+                            break;
+                        },
+                        _ => value.push(c),
+                    }
+                }
+                let len = len.ok_or_else(|| self.error("unterminated string"))?;
+                self.pos += len;
+                return Ok(Some((Token::Str(value), breaks)));
+            },
+            _ if c.is_ascii_digit() || c == '-' || c == '+' || c == '.' => trimmed
+                .find(|c: char| !(c.is_ascii_alphanumeric() || "-+._".contains(c)))
+                .unwrap_or(trimmed.len()),
+            _ if c.is_alphabetic() || c == '_' => trimmed
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(trimmed.len()),
+            _ => return Err(self.error(format!("unexpected character `{}`", c))),
+        };
+
+        let text = &trimmed[..len];
+        self.pos += len;
+        self.line += text.matches('\n').count();
+        let token = match c {
+            '/' => Token::Comment(text.trim_end().to_owned()),
+            _ if c.is_alphabetic() || c == '_' => Token::Ident(text.to_owned()),
+            _ => Token::Number(text.to_owned()),
+        };
+        Ok(Some((token, breaks)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GEMS: &str = "// Dropped by mining
+[
+    // Common
+    (6.0, Item(\"common.items.mineral.gem.topaz\")),
+    (8, Item(\"common.items.mineral.gem.amethyst\")), // the most common one
+
+    // Rare
+    (0.4, ItemQuantity(\"common.items.mineral.gem.diamond\", 1, 2)),
+    (0.15, LootTable(\"common.loot_tables.materials.gems\")),
+    (0.1, Nothing),
+    // TODO: more gems
+]
+";
+
+    #[test]
+    fn round_trip() {
+        let table = LootTableFile::parse(GEMS).unwrap();
+        assert_eq!(table.entries.len(), 5);
+        assert_eq!(
+            table.entries[1].trailing_comment.as_deref(),
+            Some("// the most common one")
+        );
+        assert_eq!(table.to_ron(), GEMS);
+        assert!(table.keeps_comments());
+    }
+
+    #[test]
+    fn inner_comments() {
+        let src = "[\n    (1.0, /* rare */ Item(\"common.items.mineral.gem.ruby\")),\n]\n";
+        let table = LootTableFile::parse(src).unwrap();
+        assert!(!table.keeps_comments());
+        let err = table.save("common.loot_tables.nonexistent").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn scale_gems() {
+        let mut table = LootTableFile::parse(GEMS).unwrap();
+        let changed = table.scale_weights(
+            |spec| matches!(spec, LootSpec::Item(item) if item.contains(".gem.")),
+            1.5,
+        );
+        assert_eq!(changed, 2);
+
+        let written = table.to_ron();
+        assert!(written.contains("    (9.0, Item(\"common.items.mineral.gem.topaz\")),"));
+        assert!(written.contains("    // Rare\n    (0.4, ItemQuantity("));
+        assert_eq!(LootTableFile::parse(&written).unwrap().items(), table.items());
+    }
+
+    fn check_dir(dir: &std::path::Path) {
+        for entry in fs::read_dir(dir).unwrap().flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                check_dir(&path);
+                continue;
+            } else if path.extension().map_or(true, |ext| ext != "ron") {
+                continue;
+            }
+            let src = fs::read_to_string(&path).unwrap();
+            let table = LootTableFile::parse(&src)
+                .unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+            let reparsed = LootTableFile::parse(&table.to_ron()).unwrap();
+            assert_eq!(reparsed.items(), table.items(), "{}", path.display());
+        }
+    }
+
+    #[test]
+    fn loot_tables_round_trip() { check_dir(&ASSETS_PATH.join("common").join("loot_tables")); }
+}
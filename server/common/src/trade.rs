@@ -315,6 +315,10 @@ pub enum Good {
     Potions,
     Coin, // exchange material across sites
     RoadSecurity,
+    // The following aren't simulated by the site economy, see `economy_proxy`
+    ModularComponents,
+    Furniture,
+    Pets,
 }
 
 impl Default for Good {
@@ -327,8 +331,9 @@ impl Good {
     /// The discounting factor applied when selling goods back to a merchant
     pub fn trade_margin(&self) -> f32 {
         match self {
-            Good::Tools | Good::Armor => 0.5,
+            Good::Tools | Good::Armor | Good::ModularComponents | Good::Furniture => 0.5,
             Good::Food | Good::Potions | Good::Ingredients => 0.75,
+            Good::Pets => 0.25,
             Good::Coin => 1.0,
             // Certain abstract goods (like Territory) shouldn't be attached to concrete items;
             // give a sale price of 0 if the player is trying to sell a concrete item that somehow
@@ -336,6 +341,17 @@ impl Good {
             _ => 0.0,
         }
     }
+
+    /// The good whose site price stands in for this one, for goods which sites
+    /// don't produce themselves
+    pub fn economy_proxy(&self) -> Good {
+        match self {
+            Good::ModularComponents => Good::Tools,
+            Good::Furniture => Good::Wood,
+            Good::Pets => Good::Food,
+            _ => *self,
+        }
+    }
 }
 
 // ideally this would be a real Id<Site> but that is from the world crate
@@ -365,14 +381,13 @@ impl SitePrices {
             .map(|(slot, amount)| {
                 inventories[who]
                     .as_ref()
-                    .and_then(|ri| {
-                        ri.inventory.get(slot).map(|item| {
-                            let (material, factor) = TradePricing::get_material(&item.name);
-                            self.values.get(&material).cloned().unwrap_or_default()
-                                * factor
-                                * (*amount as f32)
-                                * if reduce { material.trade_margin() } else { 1.0 }
-                        })
+                    .and_then(|ri| ri.inventory.get(slot))
+                    .and_then(|item| TradePricing::get_material(&item.name))
+                    .map(|(material, factor)| {
+                        self.values.get(&material).cloned().unwrap_or_default()
+                            * factor
+                            * (*amount as f32)
+                            * if reduce { material.trade_margin() } else { 1.0 }
                     })
                     .unwrap_or_default()
             })
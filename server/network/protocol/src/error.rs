@@ -6,6 +6,8 @@ pub enum InitProtocolError {
     Closed,
     WrongMagicNumber([u8; 7]),
     WrongVersion([u32; 3]),
+    /// The remote secret was rejected before answering with the own one
+    InvalidSecret,
 }
 
 /// When you return closed you must stay closed!
@@ -46,6 +48,7 @@ impl core::fmt::Display for InitProtocolError {
                 &r,
                 &crate::types::VELOREN_NETWORK_VERSION
             ),
+            InitProtocolError::InvalidSecret => write!(f, "Remote secret was rejected"),
         }
     }
 }
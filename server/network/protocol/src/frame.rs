@@ -1,5 +1,6 @@
-use crate::types::{Bandwidth, Mid, Pid, Prio, Promises, Sid};
+use crate::types::{Bandwidth, HandshakeFeatures, Mid, Pid, Prio, Promises, Sid};
 use bytes::{Buf, BufMut, Bytes, BytesMut};
+use xxhash_rust::xxh32::xxh32;
 
 // const FRAME_RESERVED_1: u8 = 0;
 const FRAME_HANDSHAKE: u8 = 1;
@@ -10,7 +11,10 @@ const FRAME_CLOSE_STREAM: u8 = 5;
 const FRAME_DATA_HEADER: u8 = 6;
 const FRAME_DATA: u8 = 7;
 const FRAME_RAW: u8 = 8;
+const FRAME_PING: u8 = 9;
 //const FRAME_RESERVED_2: u8 = 10;
+const FRAME_PONG: u8 = 11;
+const FRAME_DATA_CHECKED: u8 = 12;
 //const FRAME_RESERVED_3: u8 = 13;
 
 /// Used for Communication between Channel <----(TCP/UDP)----> Channel
@@ -19,6 +23,7 @@ pub enum InitFrame {
     Handshake {
         magic_number: [u8; 7],
         version: [u32; 3],
+        features: HandshakeFeatures,
     },
     Init {
         pid: Pid,
@@ -52,6 +57,13 @@ pub enum OTFrame {
         mid: Mid,
         data: Bytes,
     },
+    /// Keepalive, the remote answers with a `Pong` carrying the same nonce
+    Ping {
+        nonce: u64,
+    },
+    Pong {
+        nonce: u64,
+    },
 }
 
 /// Used for IN TCP Communication between Channel <--(TCP)-- Channel
@@ -77,11 +89,29 @@ pub enum ITFrame {
         mid: Mid,
         data: BytesMut,
     },
+    /// A checked data frame which didn't match its checksum, `length` bytes of
+    /// the message are lost
+    CorruptData {
+        mid: Mid,
+        length: usize,
+    },
+    Ping {
+        nonce: u64,
+    },
+    Pong {
+        nonce: u64,
+    },
+}
+
+/// Checksum of checked data frames, seeded with the message so a frame can't
+/// be mistaken for one of another message
+pub(crate) fn data_checksum(mid: Mid, data: &[u8]) -> u32 {
+    xxh32(data, (mid ^ (mid >> 32)) as u32)
 }
 
 impl InitFrame {
     // Size WITHOUT the 1rst indicating byte
-    pub(crate) const HANDSHAKE_CNS: usize = 19;
+    pub(crate) const HANDSHAKE_CNS: usize = 20;
     pub(crate) const INIT_CNS: usize = 32;
     /// const part of the RAW frame, actual size is variable
     pub(crate) const RAW_CNS: usize = 2;
@@ -92,12 +122,14 @@ impl InitFrame {
             InitFrame::Handshake {
                 magic_number,
                 version,
+                features,
             } => {
                 bytes.put_u8(FRAME_HANDSHAKE);
                 bytes.put_slice(&magic_number);
                 bytes.put_u32_le(version[0]);
                 bytes.put_u32_le(version[1]);
                 bytes.put_u32_le(version[2]);
+                bytes.put_u8(features.bits());
             },
             InitFrame::Init { pid, secret } => {
                 bytes.put_u8(FRAME_INIT);
@@ -129,6 +161,8 @@ impl InitFrame {
                 InitFrame::Handshake {
                     magic_number,
                     version: [bytes.get_u32_le(), bytes.get_u32_le(), bytes.get_u32_le()],
+                    // features of newer versions are unknown to us, so not used
+                    features: HandshakeFeatures::from_bits_truncate(bytes.get_u8()),
                 }
             },
             FRAME_INIT => {
@@ -162,8 +196,11 @@ impl InitFrame {
 pub(crate) const TCP_CLOSE_STREAM_CNS: usize = 8;
 /// const part of the DATA frame, actual size is variable
 pub(crate) const TCP_DATA_CNS: usize = 10;
+/// const part of the checked DATA frame, actual size is variable
+pub(crate) const TCP_DATA_CHECKED_CNS: usize = 14;
 pub(crate) const TCP_DATA_HEADER_CNS: usize = 24;
 pub(crate) const TCP_OPEN_STREAM_CNS: usize = 18;
+pub(crate) const TCP_PING_CNS: usize = 8;
 // Size WITHOUT the 1rst indicating byte
 pub(crate) const TCP_SHUTDOWN_CNS: usize = 0;
 
@@ -201,6 +238,30 @@ impl OTFrame {
                 bytes.put_u16_le(data.len() as u16);
                 bytes.put_slice(&data);
             },
+            Self::Ping { nonce } => {
+                bytes.put_u8(FRAME_PING);
+                bytes.put_u64_le(nonce);
+            },
+            Self::Pong { nonce } => {
+                bytes.put_u8(FRAME_PONG);
+                bytes.put_u64_le(nonce);
+            },
+        }
+    }
+
+    /// Like [`OTFrame::write_bytes`], but data frames carry a checksum of
+    /// their content which is verified by the receiver. Only worth it for
+    /// backends which may corrupt data.
+    pub fn write_bytes_checked(self, bytes: &mut BytesMut) {
+        match self {
+            Self::Data { mid, data } => {
+                bytes.put_u8(FRAME_DATA_CHECKED);
+                bytes.put_u64_le(mid);
+                bytes.put_u16_le(data.len() as u16);
+                bytes.put_u32_le(data_checksum(mid, &data));
+                bytes.put_slice(&data);
+            },
+            frame => frame.write_bytes(bytes),
         }
     }
 }
@@ -218,11 +279,17 @@ impl ITFrame {
             FRAME_OPEN_STREAM => TCP_OPEN_STREAM_CNS,
             FRAME_CLOSE_STREAM => TCP_CLOSE_STREAM_CNS,
             FRAME_DATA_HEADER => TCP_DATA_HEADER_CNS,
-            FRAME_DATA => {
+            FRAME_PING | FRAME_PONG => TCP_PING_CNS,
+            FRAME_DATA | FRAME_DATA_CHECKED => {
                 if bytes.len() < 9 + 1 + 1 {
                     return Ok(None);
                 }
-                u16::from_le_bytes([bytes[8 + 1], bytes[9 + 1]]) as usize + TCP_DATA_CNS
+                let cns = if frame_no == FRAME_DATA {
+                    TCP_DATA_CNS
+                } else {
+                    TCP_DATA_CHECKED_CNS
+                };
+                u16::from_le_bytes([bytes[8 + 1], bytes[9 + 1]]) as usize + cns
             },
             _ => return Err(()),
         };
@@ -270,6 +337,32 @@ impl ITFrame {
                 let data = bytes.split_to(length as usize);
                 Self::Data { mid, data }
             },
+            FRAME_DATA_CHECKED => {
+                bytes.advance(1);
+                let mid = bytes.get_u64_le();
+                let length = bytes.get_u16_le() as usize;
+                let checksum = bytes.get_u32_le();
+                let data = bytes.split_to(length);
+                if data_checksum(mid, &data) == checksum {
+                    Self::Data { mid, data }
+                } else {
+                    Self::CorruptData { mid, length }
+                }
+            },
+            FRAME_PING => {
+                let mut bytes = bytes.split_to(size + 1);
+                bytes.advance(1);
+                Self::Ping {
+                    nonce: bytes.get_u64_le(),
+                }
+            },
+            FRAME_PONG => {
+                let mut bytes = bytes.split_to(size + 1);
+                bytes.advance(1);
+                Self::Pong {
+                    nonce: bytes.get_u64_le(),
+                }
+            },
             _ => unreachable!("Frame::to_frame should be handled before!"),
         };
         Ok(Some(frame))
@@ -297,6 +390,8 @@ impl PartialEq<ITFrame> for OTFrame {
                 matches!(other, ITFrame::DataHeader { mid, sid, length })
             },
             Self::Data { mid, data } => matches!(other, ITFrame::Data { mid, data }),
+            Self::Ping { nonce } => matches!(other, ITFrame::Ping { nonce }),
+            Self::Pong { nonce } => matches!(other, ITFrame::Pong { nonce }),
         }
     }
 }
@@ -311,6 +406,7 @@ mod tests {
             InitFrame::Handshake {
                 magic_number: VELOREN_MAGIC_NUMBER,
                 version: VELOREN_NETWORK_VERSION,
+                features: HandshakeFeatures::CHECKSUMS,
             },
             InitFrame::Init {
                 pid: Pid::fake(0),
@@ -341,6 +437,8 @@ mod tests {
                 mid: 0,
                 data: Bytes::from(&[42u8; 16][..]),
             },
+            OTFrame::Ping { nonce: 3 },
+            OTFrame::Pong { nonce: 3 },
             OTFrame::CloseStream {
                 sid: Sid::new(1337),
             },
@@ -422,6 +520,36 @@ mod tests {
         }
     }
 
+    #[test]
+    fn frame_checked() {
+        let mut buffer = BytesMut::with_capacity(3000);
+        for f in get_otframes() {
+            f.write_bytes_checked(&mut buffer);
+        }
+        for f in get_otframes() {
+            assert_eq!(f, ITFrame::read_frame(&mut buffer).expect("ERR").expect("NONE"));
+        }
+
+        let frame = OTFrame::Data {
+            mid: 7u64,
+            data: Bytes::from(&b"foobar"[..]),
+        };
+        frame.clone().write_bytes_checked(&mut buffer);
+        // flip a bit of the data
+        let last = buffer.len() - 1;
+        buffer[last] ^= 0b100;
+        frame.clone().write_bytes_checked(&mut buffer);
+        assert_eq!(
+            ITFrame::read_frame(&mut buffer),
+            Ok(Some(ITFrame::CorruptData { mid: 7, length: 6 }))
+        );
+        // the following frame is unaffected
+        assert_eq!(
+            frame,
+            ITFrame::read_frame(&mut buffer).expect("ERR").expect("NONE")
+        );
+    }
+
     #[test]
     fn frame_exact_size() {
         const SIZE: usize = TCP_CLOSE_STREAM_CNS+1/*first byte*/;
@@ -442,6 +570,7 @@ mod tests {
         let frame1 = InitFrame::Handshake {
             magic_number: VELOREN_MAGIC_NUMBER,
             version: VELOREN_NETWORK_VERSION,
+            features: HandshakeFeatures::empty(),
         };
         InitFrame::write_bytes(frame1, &mut buffer);
     }
@@ -453,6 +582,7 @@ mod tests {
         let frame1 = InitFrame::Handshake {
             magic_number: VELOREN_MAGIC_NUMBER,
             version: VELOREN_NETWORK_VERSION,
+            features: HandshakeFeatures::empty(),
         };
         let _ = InitFrame::write_bytes(frame1, &mut buffer);
         buffer.truncate(6); // simulate partial retrieve
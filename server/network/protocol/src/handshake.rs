@@ -2,7 +2,7 @@ use crate::{
     error::{InitProtocolError, ProtocolError},
     frame::InitFrame,
     types::{
-        Pid, Sid, STREAM_ID_OFFSET1, STREAM_ID_OFFSET2, VELOREN_MAGIC_NUMBER,
+        HandshakeFeatures, Pid, Sid, STREAM_ID_OFFSET1, STREAM_ID_OFFSET2, VELOREN_MAGIC_NUMBER,
         VELOREN_NETWORK_VERSION,
     },
     InitProtocol,
@@ -22,6 +22,13 @@ use tracing::{debug, error, info, trace};
 #[async_trait]
 pub trait ReliableDrain {
     async fn send(&mut self, frame: InitFrame) -> Result<(), ProtocolError>;
+
+    /// Features announced to the remote in the handshake
+    fn features(&self) -> HandshakeFeatures { HandshakeFeatures::empty() }
+
+    /// Called with the features both sides announced once the handshake
+    /// frames were exchanged
+    fn negotiated(&mut self, _features: HandshakeFeatures) {}
 }
 
 /// Implement this for auto Handshake with [`ReliableDrain`]. See
@@ -39,11 +46,12 @@ where
     D: ReliableDrain + Send,
     S: ReliableSink + Send,
 {
-    async fn initialize(
+    async fn initialize_verified(
         &mut self,
         initializer: bool,
         local_pid: Pid,
         local_secret: u128,
+        verify: &(dyn Fn(Pid, u128) -> bool + Sync),
     ) -> Result<(Pid, Sid, u128), InitProtocolError> {
         #[cfg(debug_assertions)]
         const WRONG_NUMBER: &str = "Handshake does not contain the magic number required by \
@@ -58,12 +66,14 @@ where
 
         let drain = &mut self.0;
         let sink = &mut self.1;
+        let local_features = drain.features();
 
         if initializer {
             drain
                 .send(InitFrame::Handshake {
                     magic_number: VELOREN_MAGIC_NUMBER,
                     version: VELOREN_NETWORK_VERSION,
+                    features: local_features,
                 })
                 .await?;
         }
@@ -72,8 +82,9 @@ where
             InitFrame::Handshake {
                 magic_number,
                 version,
+                features,
             } => {
-                trace!(?magic_number, ?version, "Recv handshake");
+                trace!(?magic_number, ?version, ?features, "Recv handshake");
                 if magic_number != VELOREN_MAGIC_NUMBER {
                     error!(?magic_number, "Connection with invalid magic_number");
                     #[cfg(debug_assertions)]
@@ -99,18 +110,20 @@ where
                     Err(InitProtocolError::WrongVersion(version))
                 } else {
                     trace!("Handshake Frame completed");
-                    if initializer {
-                        drain
-                            .send(InitFrame::Init {
-                                pid: local_pid,
-                                secret: local_secret,
-                            })
-                            .await?;
-                    } else {
+                    drain.negotiated(local_features & features);
+                    if !initializer {
                         drain
                             .send(InitFrame::Handshake {
                                 magic_number: VELOREN_MAGIC_NUMBER,
                                 version: VELOREN_NETWORK_VERSION,
+                                features: local_features,
+                            })
+                            .await?;
+                        // the remote answers with its own secret once it verified ours
+                        drain
+                            .send(InitFrame::Init {
+                                pid: local_pid,
+                                secret: local_secret,
                             })
                             .await?;
                     }
@@ -134,14 +147,19 @@ where
             InitFrame::Init { pid, secret } => {
                 debug!(?pid, "Participant send their ID");
                 let stream_id_offset = if initializer {
-                    STREAM_ID_OFFSET1
-                } else {
+                    // the remote must prove itself before it learns anything
+                    if !verify(pid, secret) {
+                        info!(?pid, "Remote secret rejected, not answering with ours");
+                        return Err(InitProtocolError::InvalidSecret);
+                    }
                     drain
                         .send(InitFrame::Init {
                             pid: local_pid,
                             secret: local_secret,
                         })
                         .await?;
+                    STREAM_ID_OFFSET1
+                } else {
                     STREAM_ID_OFFSET2
                 };
                 info!(?pid, "This Handshake is now configured!");
@@ -188,6 +206,7 @@ mod tests {
             p2.0.send(InitFrame::Handshake {
                 magic_number: *b"woopsie",
                 version: VELOREN_NETWORK_VERSION,
+                features: HandshakeFeatures::empty(),
             })
             .await?;
             let _ = p2.1.recv().await?;
@@ -210,6 +229,7 @@ mod tests {
             p2.0.send(InitFrame::Handshake {
                 magic_number: VELOREN_MAGIC_NUMBER,
                 version: [0, 1, 2],
+                features: HandshakeFeatures::empty(),
             })
             .await?;
             let _ = p2.1.recv().await?;
@@ -230,9 +250,9 @@ mod tests {
             p2.0.send(InitFrame::Handshake {
                 magic_number: VELOREN_MAGIC_NUMBER,
                 version: VELOREN_NETWORK_VERSION,
+                features: HandshakeFeatures::empty(),
             })
             .await?;
-            let _ = p2.1.recv().await?;
             p2.0.send(InitFrame::Raw(b"Hello World".to_vec())).await?;
             Result::<(), InitProtocolError>::Ok(())
         });
@@ -240,4 +260,33 @@ mod tests {
         assert_eq!(r1.unwrap(), Err(InitProtocolError::Closed));
         assert_eq!(r2.unwrap(), Ok(()));
     }
+
+    #[tokio::test]
+    async fn handshake_rejected_secret() {
+        let [mut p1, mut p2] = ac_bound(10, None);
+        let r1 = tokio::spawn(async move {
+            p1.initialize_verified(true, Pid::fake(3), 42, &|_, secret| secret == 1337)
+                .await
+        });
+        let r2 = tokio::spawn(async move {
+            let _ = p2.1.recv().await?;
+            p2.0.send(InitFrame::Handshake {
+                magic_number: VELOREN_MAGIC_NUMBER,
+                version: VELOREN_NETWORK_VERSION,
+                features: HandshakeFeatures::empty(),
+            })
+            .await?;
+            p2.0.send(InitFrame::Init {
+                pid: Pid::fake(2),
+                secret: 7,
+            })
+            .await?;
+            // nothing but the closed channel comes back
+            let _ = p2.1.recv().await?;
+            Result::<(), InitProtocolError>::Ok(())
+        });
+        let (r1, r2) = tokio::join!(r1, r2);
+        assert_eq!(r1.unwrap(), Err(InitProtocolError::InvalidSecret));
+        assert_eq!(r2.unwrap(), Err(InitProtocolError::Closed));
+    }
 }
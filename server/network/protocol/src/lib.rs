@@ -58,6 +58,7 @@ mod message;
 mod metrics;
 mod mpsc;
 mod prio;
+mod quality;
 mod quic;
 mod tcp;
 mod types;
@@ -65,13 +66,17 @@ mod util;
 
 pub use error::{InitProtocolError, ProtocolError};
 pub use event::ProtocolEvent;
-pub use metrics::ProtocolMetricCache;
+pub use metrics::{MessageCategory, ProtocolMetricCache};
 #[cfg(feature = "metrics")]
 pub use metrics::ProtocolMetrics;
 pub use mpsc::{MpscMsg, MpscRecvProtocol, MpscSendProtocol};
+pub use quality::{ConnectionQuality, QualityEstimator};
 pub use quic::{QuicDataFormat, QuicDataFormatStream, QuicRecvProtocol, QuicSendProtocol};
 pub use tcp::{TcpRecvProtocol, TcpSendProtocol};
-pub use types::{Bandwidth, Cid, Pid, Prio, Promises, Sid, HIGHEST_PRIO, VELOREN_NETWORK_VERSION};
+pub use types::{
+    Bandwidth, Cid, HandshakeFeatures, Pid, Prio, Promises, Sid, HIGHEST_PRIO,
+    VELOREN_NETWORK_VERSION,
+};
 
 ///use at own risk, might change any time, for internal benchmarks
 pub mod _internal {
@@ -91,6 +96,22 @@ pub trait InitProtocol {
         initializer: bool,
         local_pid: Pid,
         secret: u128,
+    ) -> Result<(Pid, Sid, u128), InitProtocolError> {
+        self.initialize_verified(initializer, local_pid, secret, &|_, _| true)
+            .await
+    }
+
+    /// Like [`initialize`], but the accepting side only answers with its own
+    /// secret if `verify` accepts the pid and secret of the initializer.
+    /// Otherwise it fails with [`InitProtocolError::InvalidSecret`].
+    ///
+    /// [`initialize`]: InitProtocol::initialize
+    async fn initialize_verified(
+        &mut self,
+        initializer: bool,
+        local_pid: Pid,
+        secret: u128,
+        verify: &(dyn Fn(Pid, u128) -> bool + Sync),
     ) -> Result<(Pid, Sid, u128), InitProtocolError>;
 }
 
@@ -3,6 +3,7 @@ use crate::{
     types::{Mid, Sid},
 };
 use bytes::{Bytes, BytesMut};
+use std::time::{Duration, Instant};
 
 pub(crate) const ALLOC_BLOCK: usize = 16_777_216;
 
@@ -22,6 +23,7 @@ pub(crate) struct OTMessage {
     mid: Mid,
     sid: Sid,
     start: u64, /* remove */
+    queued: Instant,
 }
 
 #[derive(Debug)]
@@ -29,6 +31,8 @@ pub(crate) struct ITMessage {
     pub data: BytesMut,
     pub sid: Sid,
     pub length: u64,
+    /// a data frame failed its checksum, the message is dropped once complete
+    pub corrupt: bool,
 }
 
 impl OTMessage {
@@ -43,6 +47,7 @@ impl OTMessage {
             mid,
             sid,
             start: 0,
+            queued: Instant::now(),
         }
     }
 
@@ -78,6 +83,12 @@ impl OTMessage {
     }
 
     pub(crate) fn get_sid_len(&self) -> (Sid, u64) { (self.sid, self.original_length) }
+
+    /// time since the message was created
+    pub(crate) fn queued_for(&self) -> Duration { self.queued.elapsed() }
+
+    /// whether all frames were returned by [`OTMessage::next`]
+    pub(crate) fn is_finished(&self) -> bool { self.send_header && self.data.is_empty() }
 }
 
 impl ITMessage {
@@ -88,6 +99,7 @@ impl ITMessage {
             sid,
             length,
             data: BytesMut::with_capacity((length as usize).min(ALLOC_BLOCK /* anti-ddos */)),
+            corrupt: false,
         }
     }
 }
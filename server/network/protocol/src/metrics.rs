@@ -1,12 +1,15 @@
 use crate::types::Sid;
 #[cfg(feature = "metrics")]
+use crate::frame::InitFrame;
+#[cfg(feature = "metrics")]
 use prometheus::{
-    core::{AtomicI64, AtomicU64, GenericCounter, GenericGauge},
-    IntCounterVec, IntGaugeVec, Opts, Registry,
+    core::{AtomicI64, AtomicU64, GenericCounter, GenericGauge, Metric},
+    exponential_buckets, Histogram, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts,
+    Registry,
 };
 #[cfg(feature = "metrics")]
 use std::collections::HashMap;
-use std::{error::Error, sync::Arc};
+use std::{error::Error, sync::Arc, time::Duration};
 
 #[allow(dead_code)]
 pub enum RemoveReason {
@@ -14,6 +17,19 @@ pub enum RemoveReason {
     Dropped,
 }
 
+/// What a sent message was for, sizes and latencies are recorded per category
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageCategory {
+    /// the handshake of a channel, latency is the duration of the whole
+    /// handshake
+    Handshake,
+    /// latency is the time until the open frame was written
+    OpenStream,
+    /// latency is the time from adding a message until its last frame was
+    /// written
+    Data,
+}
+
 /// Use 1 `ProtocolMetrics` per `Network`.
 /// I will contain all protocol related [`prometheus`] information
 ///
@@ -56,8 +72,16 @@ pub struct ProtocolMetrics {
     rdata_frames_t: IntCounterVec,
     /// data frames bytes send by prio by CHANNEL,
     rdata_frames_b: IntCounterVec,
+    /// checked data frames which failed their checksum by CHANNEL,
+    rdata_corrupt_t: IntCounterVec,
     /// ping per CHANNEL //TODO: implement
     ping: IntGaugeVec,
+
+    // based on CATEGORY, channels are too many to be told apart
+    /// size of send messages in bytes, by CATEGORY
+    smsg_size: HistogramVec,
+    /// seconds from queueing to sending messages, by CATEGORY
+    smsg_latency: HistogramVec,
 }
 
 /// Cache for [`ProtocolMetrics`], more optimized and cleared up after channel
@@ -74,8 +98,11 @@ pub struct ProtocolMetricCache {
     sdata_frames_b: GenericCounter<AtomicU64>,
     rdata_frames_t: GenericCounter<AtomicU64>,
     rdata_frames_b: GenericCounter<AtomicU64>,
+    rdata_corrupt_t: GenericCounter<AtomicU64>,
     #[allow(dead_code)]
     ping: GenericGauge<AtomicI64>,
+    smsg_size: [Histogram; 3],
+    smsg_latency: [Histogram; 3],
 }
 
 #[cfg(not(feature = "metrics"))]
@@ -174,7 +201,31 @@ impl ProtocolMetrics {
             ),
             &["channel"],
         )?;
+        let rdata_corrupt_t = IntCounterVec::new(
+            Opts::new(
+                "recv_corrupt_data_frames_total",
+                "Number of checked data frames received per channel which failed their checksum",
+            ),
+            &["channel"],
+        )?;
         let ping = IntGaugeVec::new(Opts::new("ping", "Ping per channel"), &["channel"])?;
+        let smsg_size = HistogramVec::new(
+            HistogramOpts::new(
+                "send_message_size_bytes",
+                "Size of messages send, by category(handshake/open_stream/data)",
+            )
+            .buckets(exponential_buckets(16.0, 4.0, 10)?),
+            &["category"],
+        )?;
+        let smsg_latency = HistogramVec::new(
+            HistogramOpts::new(
+                "send_message_latency_seconds",
+                "Time from queueing a message until it was written, by \
+                 category(handshake/open_stream/data)",
+            )
+            .buckets(exponential_buckets(0.0005, 2.0, 16)?),
+            &["category"],
+        )?;
 
         Ok(Self {
             smsg_it,
@@ -189,7 +240,10 @@ impl ProtocolMetrics {
             rmsg_ob,
             rdata_frames_t,
             rdata_frames_b,
+            rdata_corrupt_t,
             ping,
+            smsg_size,
+            smsg_latency,
         })
     }
 
@@ -206,7 +260,10 @@ impl ProtocolMetrics {
         registry.register(Box::new(self.rmsg_ob.clone()))?;
         registry.register(Box::new(self.rdata_frames_t.clone()))?;
         registry.register(Box::new(self.rdata_frames_b.clone()))?;
+        registry.register(Box::new(self.rdata_corrupt_t.clone()))?;
         registry.register(Box::new(self.ping.clone()))?;
+        registry.register(Box::new(self.smsg_size.clone()))?;
+        registry.register(Box::new(self.smsg_latency.clone()))?;
         Ok(())
     }
 }
@@ -235,7 +292,11 @@ impl ProtocolMetricCache {
         let sdata_frames_b = metrics.sdata_frames_b.with_label_values(&[&cid]);
         let rdata_frames_t = metrics.rdata_frames_t.with_label_values(&[&cid]);
         let rdata_frames_b = metrics.rdata_frames_b.with_label_values(&[&cid]);
+        let rdata_corrupt_t = metrics.rdata_corrupt_t.with_label_values(&[&cid]);
         let ping = metrics.ping.with_label_values(&[&cid]);
+        let categories = MessageCategory::ALL.map(MessageCategory::to_str);
+        let smsg_size = categories.map(|c| metrics.smsg_size.with_label_values(&[c]));
+        let smsg_latency = categories.map(|c| metrics.smsg_latency.with_label_values(&[c]));
         Self {
             cid,
             m: metrics,
@@ -244,7 +305,10 @@ impl ProtocolMetricCache {
             sdata_frames_b,
             rdata_frames_t,
             rdata_frames_b,
+            rdata_corrupt_t,
             ping,
+            smsg_size,
+            smsg_latency,
         }
     }
 
@@ -314,6 +378,32 @@ impl ProtocolMetricCache {
         self.rdata_frames_b.inc_by(bytes);
     }
 
+    pub(crate) fn rdata_corrupt(&mut self) { self.rdata_corrupt_t.inc(); }
+
+    /// Record a message which was sent `latency` after it was queued
+    pub fn smsg_sent(&self, category: MessageCategory, bytes: u64, latency: Duration) {
+        self.smsg_size[category.i()].observe(bytes as f64);
+        self.smsg_latency[category.i()].observe(latency.as_secs_f64());
+    }
+
+    /// Record a finished handshake, which took `latency`
+    pub fn handshake_done(&self, latency: Duration) {
+        let bytes = InitFrame::HANDSHAKE_CNS + InitFrame::INIT_CNS + 2;
+        self.smsg_sent(MessageCategory::Handshake, bytes as u64, latency);
+    }
+
+    /// Estimated size below which fraction `q` of the messages of `category`
+    /// were, over all channels. `None` until one was recorded.
+    pub fn size_percentile(&self, category: MessageCategory, q: f64) -> Option<f64> {
+        histogram_quantile(&self.smsg_size[category.i()], q)
+    }
+
+    /// Estimated latency below which fraction `q` of the messages of
+    /// `category` were sent, over all channels. `None` until one was recorded.
+    pub fn latency_percentile(&self, category: MessageCategory, q: f64) -> Option<Duration> {
+        histogram_quantile(&self.smsg_latency[category.i()], q).map(Duration::from_secs_f64)
+    }
+
     #[cfg(test)]
     pub(crate) fn assert_msg(&mut self, sid: Sid, cnt: u64, reason: RemoveReason) {
         let line = self.init_sid(sid);
@@ -338,6 +428,11 @@ impl ProtocolMetricCache {
         assert_eq!(self.rdata_frames_t.get(), cnt);
     }
 
+    #[cfg(test)]
+    pub(crate) fn assert_corrupt_frames(&mut self, cnt: u64) {
+        assert_eq!(self.rdata_corrupt_t.get(), cnt);
+    }
+
     #[cfg(test)]
     pub(crate) fn assert_data_frames_bytes(&mut self, bytes: u64) {
         assert_eq!(self.sdata_frames_b.get(), bytes);
@@ -345,6 +440,39 @@ impl ProtocolMetricCache {
     }
 }
 
+/// Interpolates linearly inside the bucket the quantile falls into, like
+/// prometheus' `histogram_quantile`. Values above the highest bucket are
+/// reported as its upper bound.
+#[cfg(feature = "metrics")]
+fn histogram_quantile(histogram: &Histogram, q: f64) -> Option<f64> {
+    let metric = histogram.metric();
+    let buckets = metric
+        .get_histogram()
+        .get_bucket()
+        .iter()
+        .map(|b| (b.get_upper_bound(), b.get_cumulative_count()));
+    quantile(buckets, histogram.get_sample_count(), q)
+}
+
+/// `buckets` are `(upper bound, cumulative count)` in ascending order
+#[cfg(feature = "metrics")]
+fn quantile(buckets: impl Iterator<Item = (f64, u64)>, count: u64, q: f64) -> Option<f64> {
+    if count == 0 {
+        return None;
+    }
+    let rank = q.clamp(0.0, 1.0) * count as f64;
+    let (mut lower, mut below) = (0.0, 0);
+    for (upper, cumulative) in buckets {
+        if cumulative as f64 >= rank && cumulative > below {
+            let fraction = (rank - below as f64) / (cumulative - below) as f64;
+            return Some(lower + (upper - lower) * fraction.max(0.0));
+        }
+        lower = upper;
+        below = cumulative;
+    }
+    Some(lower)
+}
+
 #[cfg(feature = "metrics")]
 impl Drop for ProtocolMetricCache {
     fn drop(&mut self) {
@@ -372,6 +500,7 @@ impl Drop for ProtocolMetricCache {
         let _ = m.sdata_frames_b.remove_label_values(&[cid]);
         let _ = m.rdata_frames_t.remove_label_values(&[cid]);
         let _ = m.rdata_frames_b.remove_label_values(&[cid]);
+        let _ = m.rdata_corrupt_t.remove_label_values(&[cid]);
     }
 }
 
@@ -398,6 +527,18 @@ impl ProtocolMetricCache {
     pub(crate) fn rmsg_ob(&mut self, _sid: Sid, _reason: RemoveReason, _b: u64) {}
 
     pub(crate) fn rdata_frames_b(&mut self, _b: u64) {}
+
+    pub(crate) fn rdata_corrupt(&mut self) {}
+
+    pub fn smsg_sent(&self, _category: MessageCategory, _b: u64, _latency: Duration) {}
+
+    pub fn handshake_done(&self, _latency: Duration) {}
+
+    pub fn size_percentile(&self, _category: MessageCategory, _q: f64) -> Option<f64> { None }
+
+    pub fn latency_percentile(&self, _category: MessageCategory, _q: f64) -> Option<Duration> {
+        None
+    }
 }
 
 #[cfg(not(feature = "metrics"))]
@@ -422,3 +563,58 @@ impl RemoveReason {
         }
     }
 }
+
+impl MessageCategory {
+    #[cfg(feature = "metrics")]
+    const ALL: [MessageCategory; 3] = [
+        MessageCategory::Handshake,
+        MessageCategory::OpenStream,
+        MessageCategory::Data,
+    ];
+
+    #[cfg(feature = "metrics")]
+    fn to_str(self) -> &'static str {
+        match self {
+            MessageCategory::Handshake => "handshake",
+            MessageCategory::OpenStream => "open_stream",
+            MessageCategory::Data => "data",
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    fn i(self) -> usize {
+        match self {
+            MessageCategory::Handshake => 0,
+            MessageCategory::OpenStream => 1,
+            MessageCategory::Data => 2,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interpolated_percentiles() {
+        let buckets = [(1.0, 10), (2.0, 10), (4.0, 30), (8.0, 40)];
+        let q = |q| quantile(buckets.iter().copied(), 40, q).unwrap();
+        assert_eq!(q(0.125), 0.5);
+        assert_eq!(q(0.25), 1.0);
+        assert_eq!(q(0.5), 3.0);
+        assert_eq!(q(1.0), 8.0);
+        assert_eq!(quantile(buckets.iter().copied(), 0, 0.5), None);
+        // samples above the last bucket
+        assert_eq!(quantile(buckets.iter().copied(), 50, 0.99), Some(8.0));
+
+        let metrics = Arc::new(ProtocolMetrics::new().unwrap());
+        let cache = ProtocolMetricCache::new("percentile", metrics);
+        assert_eq!(cache.latency_percentile(MessageCategory::OpenStream, 0.99), None);
+        for ms in 1..=100 {
+            cache.smsg_sent(MessageCategory::OpenStream, 19, Duration::from_millis(ms));
+        }
+        let p99 = cache.latency_percentile(MessageCategory::OpenStream, 0.99).unwrap();
+        assert!(p99 > Duration::from_millis(64) && p99 <= Duration::from_millis(128));
+        assert_eq!(cache.latency_percentile(MessageCategory::Data, 0.99), None);
+    }
+}
@@ -23,7 +23,13 @@ pub enum MpscMsg {
 
 /// MPSC implementation of [`SendProtocol`]
 ///
+/// Meant for connections within one process, e.g. singleplayer.
+/// [`ProtocolEvent`]s are moved to the remote as they are, there is no
+/// framing or serialization and message [`Bytes`] are never copied.
+///
 /// [`SendProtocol`]: crate::SendProtocol
+/// [`ProtocolEvent`]: crate::ProtocolEvent
+/// [`Bytes`]: bytes::Bytes
 #[derive(Debug)]
 pub struct MpscSendProtocol<D>
 where
@@ -59,6 +65,9 @@ where
         }
     }
 
+    /// the metrics of the channel, shared with the receiving side
+    pub fn metrics(&self) -> &ProtocolMetricCache { &self.metrics }
+
     /// returns all promises that this Protocol can take care of
     /// If you open a Stream anyway, unsupported promises are ignored.
     pub fn supported_promises() -> Promises {
@@ -1,11 +1,12 @@
 use crate::{
     frame::OTFrame,
     message::OTMessage,
-    metrics::{ProtocolMetricCache, RemoveReason},
+    metrics::{MessageCategory, ProtocolMetricCache, RemoveReason},
     types::{Bandwidth, Mid, Prio, Promises, Sid, HIGHEST_PRIO},
 };
 use bytes::Bytes;
 use std::{
+    cmp::Ordering,
     collections::{HashMap, VecDeque},
     time::Duration,
 };
@@ -17,25 +18,47 @@ struct StreamInfo {
     #[allow(dead_code)]
     pub(crate) promises: Promises,
     pub(crate) messages: VecDeque<OTMessage>,
+    /// virtual time at which the last frame of this stream was done
+    pub(crate) finish_tag: f64,
+}
+
+impl StreamInfo {
+    /// prio 0 gets twice the share of prio 1, which gets twice the share of
+    /// prio 2, ...
+    fn weight(&self) -> f64 { f64::from(1u32 << (HIGHEST_PRIO - self.prio.min(HIGHEST_PRIO))) }
 }
 
 /// Responsible for queueing messages.
 /// every stream has a guaranteed bandwidth and a prio 0-7.
-/// when `n` Bytes are available in the buffer, first the guaranteed bandwidth
-/// is used. Then remaining bandwidth is used to fill up the prios.
+/// Frames are picked by start-time fair queuing: streams with data share the
+/// available bandwidth weighted by their prio, so a saturated low prio stream
+/// can't starve a high prio one and still gets its share itself.
+/// The guaranteed bandwidth of a stream is sent even when that exceeds the
+/// available bandwidth.
 #[derive(Debug)]
 pub(crate) struct PrioManager {
     streams: HashMap<Sid, StreamInfo>,
     metrics: ProtocolMetricCache,
+    /// start tag of the last frame sent
+    virtual_time: f64,
 }
 
 // Send everything ONCE, then keep it till it's confirmed
 
+fn frame_bytes(frame: &OTFrame) -> u64 {
+    (if let OTFrame::Data { data, .. } = frame {
+        crate::frame::TCP_DATA_CNS + 1 + data.len()
+    } else {
+        crate::frame::TCP_DATA_HEADER_CNS + 1
+    }) as u64
+}
+
 impl PrioManager {
     pub fn new(metrics: ProtocolMetricCache) -> Self {
         Self {
             streams: HashMap::new(),
             metrics,
+            virtual_time: 0.0,
         }
     }
 
@@ -51,6 +74,7 @@ impl PrioManager {
             prio,
             promises,
             messages: VecDeque::new(),
+            finish_tag: 0.0,
         });
     }
 
@@ -81,58 +105,159 @@ impl PrioManager {
         let mut cur_bytes = 0u64;
         let mut frames = vec![];
 
-        let mut prios = [0u64; (HIGHEST_PRIO + 1) as usize];
-        let metrics = &mut self.metrics;
-
-        let mut process_stream =
-            |sid: &Sid, stream: &mut StreamInfo, mut bandwidth: i64, cur_bytes: &mut u64| {
-                let mut finished = None;
-                'outer: for (i, msg) in stream.messages.iter_mut().enumerate() {
-                    while let Some(frame) = msg.next() {
-                        let b = if let OTFrame::Data { data, .. } = &frame {
-                            crate::frame::TCP_DATA_CNS + 1 + data.len()
-                        } else {
-                            crate::frame::TCP_DATA_HEADER_CNS + 1
-                        } as u64;
-                        bandwidth -= b as i64;
-                        *cur_bytes += b;
-                        frames.push((*sid, frame));
-                        if bandwidth <= 0 {
-                            break 'outer;
-                        }
-                    }
-                    let (sid, bytes) = msg.get_sid_len();
-                    metrics.smsg_ob(sid, RemoveReason::Finished, bytes);
-                    finished = Some(i);
-                }
-                if let Some(i) = finished {
-                    //cleanup
-                    stream.messages.drain(..=i);
-                }
+        let mut guaranteed: HashMap<Sid, i64> = self
+            .streams
+            .iter()
+            .map(|(sid, stream)| {
+                let bytes = (stream.guaranteed_bandwidth as f64 * dt.as_secs_f64()) as i64;
+                (*sid, bytes)
+            })
+            .collect();
+
+        loop {
+            let budget_left = cur_bytes < total_bytes;
+            let virtual_time = self.virtual_time;
+            // Smallest start tag first, the higher prio wins ties
+            let next = self
+                .streams
+                .iter()
+                .filter(|(sid, stream)| {
+                    !stream.messages.is_empty() && (budget_left || guaranteed[*sid] > 0)
+                })
+                .map(|(sid, stream)| (stream.finish_tag.max(virtual_time), stream.prio, *sid))
+                .min_by(|a, b| {
+                    a.0.partial_cmp(&b.0)
+                        .unwrap_or(Ordering::Equal)
+                        .then(a.1.cmp(&b.1))
+                        .then(a.2.cmp(&b.2))
+                });
+            let (start_tag, _, sid) = match next {
+                Some(next) => next,
+                None => break,
+            };
+
+            let stream = self.streams.get_mut(&sid).unwrap();
+            let msg = stream.messages.front_mut().unwrap();
+            let frame = msg.next();
+            if msg.is_finished() {
+                let (sid, bytes) = msg.get_sid_len();
+                self.metrics.smsg_ob(sid, RemoveReason::Finished, bytes);
+                self.metrics
+                    .smsg_sent(MessageCategory::Data, bytes, msg.queued_for());
+                stream.messages.pop_front();
+            }
+            let frame = match frame {
+                Some(frame) => frame,
+                None => continue,
             };
 
-        // Add guaranteed bandwidth
-        for (sid, stream) in self.streams.iter_mut() {
-            prios[stream.prio as usize] += 1;
-            let stream_byte_cnt = (stream.guaranteed_bandwidth as f64 * dt.as_secs_f64()) as u64;
-            process_stream(sid, stream, stream_byte_cnt as i64, &mut cur_bytes);
+            let b = frame_bytes(&frame);
+            self.virtual_time = start_tag;
+            stream.finish_tag = start_tag + b as f64 / stream.weight();
+            *guaranteed.get_mut(&sid).unwrap() -= b as i64;
+            cur_bytes += b;
+            frames.push((sid, frame));
         }
+        (frames, cur_bytes)
+    }
+}
 
-        if cur_bytes < total_bytes {
-            // Add optional bandwidth
-            for prio in 0..=HIGHEST_PRIO {
-                if prios[prio as usize] == 0 {
-                    continue;
-                }
-                let per_stream_bytes = ((total_bytes - cur_bytes) / prios[prio as usize]) as i64;
-                for (sid, stream) in self.streams.iter_mut() {
-                    if stream.prio != prio {
-                        continue;
-                    }
-                    process_stream(sid, stream, per_stream_bytes, &mut cur_bytes);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::ProtocolMetrics;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+    use std::sync::Arc;
+
+    const MAX_FRAME_BYTES: u64 = crate::frame::TCP_DATA_CNS as u64 + 1 + OTMessage::FRAME_DATA_SIZE;
+
+    fn manager() -> PrioManager {
+        let metrics = ProtocolMetrics::new().unwrap();
+        PrioManager::new(ProtocolMetricCache::new("prio", Arc::new(metrics)))
+    }
+
+    fn sent_bytes(frames: &[(Sid, OTFrame)], sid: Sid) -> u64 {
+        frames
+            .iter()
+            .filter(|(s, _)| *s == sid)
+            .map(|(_, frame)| frame_bytes(frame))
+            .sum()
+    }
+
+    /// A small message on a high prio stream is done after at most two frames
+    /// of every other stream, no matter how much they have queued
+    #[test]
+    fn high_prio_latency_is_bounded() {
+        for seed in 0..200 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut mgr = manager();
+            let mut mid = 0;
+            let bulk = rng.gen_range(1..=4u64);
+            for i in 0..bulk {
+                let sid = Sid::new(i);
+                let guaranteed = rng.gen_range(0..200_000);
+                mgr.open_stream(sid, rng.gen_range(3..=7), Promises::empty(), guaranteed);
+                for _ in 0..rng.gen_range(1..10) {
+                    let len = rng.gen_range(1..300_000);
+                    mgr.add(Bytes::from(vec![0u8; len]), mid, sid);
+                    mid += 1;
                 }
             }
+            for _ in 0..rng.gen_range(0..5) {
+                let dt = Duration::from_millis(rng.gen_range(10..100));
+                mgr.grab(rng.gen_range(10_000..2_000_000), dt);
+            }
+
+            let chat = Sid::new(100);
+            mgr.open_stream(chat, rng.gen_range(0..=2), Promises::empty(), 0);
+            let len = rng.gen_range(1..=OTMessage::FRAME_DATA_SIZE as usize);
+            mgr.add(Bytes::from(vec![0u8; len]), mid, chat);
+            let budget = (2 * bulk + 2) * MAX_FRAME_BYTES;
+            let (frames, _) = mgr.grab(budget * 10, Duration::from_millis(100));
+
+            let chat_frames = frames
+                .iter()
+                .enumerate()
+                .filter(|(_, (sid, _))| *sid == chat)
+                .map(|(i, _)| i)
+                .collect::<Vec<_>>();
+            assert_eq!(chat_frames.len(), 2, "seed {}", seed);
+            assert!(chat_frames[1] as u64 <= 2 * bulk + 1, "seed {}", seed);
         }
-        (frames, cur_bytes)
+    }
+
+    #[test]
+    fn saturated_streams_share_by_prio() {
+        let mut mgr = manager();
+        let (high, low) = (Sid::new(1), Sid::new(2));
+        mgr.open_stream(high, 1, Promises::empty(), 0);
+        mgr.open_stream(low, 3, Promises::empty(), 0);
+        mgr.add(Bytes::from(vec![0u8; 10_000_000]), 0, high);
+        mgr.add(Bytes::from(vec![0u8; 10_000_000]), 1, low);
+
+        let (mut high_bytes, mut low_bytes) = (0, 0);
+        for _ in 0..50 {
+            let (frames, _) = mgr.grab(1_000_000, Duration::from_millis(100));
+            high_bytes += sent_bytes(&frames, high);
+            low_bytes += sent_bytes(&frames, low);
+        }
+        // prio 1 has 4 times the weight of prio 3
+        let ratio = high_bytes as f64 / low_bytes as f64;
+        assert!((3.8..4.2).contains(&ratio), "ratio {}", ratio);
+    }
+
+    #[test]
+    fn guaranteed_bandwidth_exceeds_budget() {
+        let mut mgr = manager();
+        let (high, guaranteed) = (Sid::new(1), Sid::new(2));
+        mgr.open_stream(high, 0, Promises::empty(), 0);
+        mgr.open_stream(guaranteed, HIGHEST_PRIO, Promises::empty(), 100_000);
+        mgr.add(Bytes::from(vec![0u8; 1_000_000]), 0, high);
+        mgr.add(Bytes::from(vec![0u8; 1_000_000]), 1, guaranteed);
+
+        let (frames, _) = mgr.grab(0, Duration::from_secs(1));
+        assert_eq!(sent_bytes(&frames, high), 0);
+        let bytes = sent_bytes(&frames, guaranteed);
+        assert!((100_000..100_000 + MAX_FRAME_BYTES).contains(&bytes));
     }
 }
@@ -1,12 +1,12 @@
 use crate::{
     error::ProtocolError,
     event::ProtocolEvent,
-    frame::{ITFrame, InitFrame, OTFrame},
+    frame::{ITFrame, InitFrame, OTFrame, TCP_OPEN_STREAM_CNS},
     handshake::{ReliableDrain, ReliableSink},
     message::{ITMessage, ALLOC_BLOCK},
-    metrics::{ProtocolMetricCache, RemoveReason},
+    metrics::{MessageCategory, ProtocolMetricCache, RemoveReason},
     prio::PrioManager,
-    types::{Bandwidth, Mid, Promises, Sid},
+    types::{Bandwidth, HandshakeFeatures, Mid, Promises, Sid},
     util::SortedVec,
     RecvProtocol, SendProtocol, UnreliableDrain, UnreliableSink,
 };
@@ -45,9 +45,13 @@ impl QuicDataFormat {
         }
     }
 
-    fn with_unreliable(frame: OTFrame) -> Self {
+    fn with_unreliable(frame: OTFrame, checksums: bool) -> Self {
         let mut buffer = BytesMut::new();
-        frame.write_bytes(&mut buffer);
+        if checksums {
+            frame.write_bytes_checked(&mut buffer);
+        } else {
+            frame.write_bytes(&mut buffer);
+        }
         Self {
             stream: QuicDataFormatStream::Unreliable,
             data: buffer,
@@ -73,6 +77,11 @@ where
     drain: D,
     #[allow(dead_code)]
     last: Instant,
+    /// announce checksums in the handshake
+    request_checksums: bool,
+    /// write data frames of unreliable streams with a checksum, both sides
+    /// agreed on it
+    checksums: bool,
     metrics: ProtocolMetricCache,
 }
 
@@ -115,10 +124,28 @@ where
             pending_shutdown: false,
             drain,
             last: Instant::now(),
+            request_checksums: false,
+            checksums: false,
             metrics,
         }
     }
 
+    /// Add a checksum to the data frames of unreliable streams, which are sent
+    /// as datagrams. Checksums are announced in the handshake and only used if
+    /// the remote announces them too. Corrupt frames are dropped by the
+    /// receiver.
+    #[must_use]
+    pub fn with_checksums(mut self, checksums: bool) -> Self {
+        self.request_checksums = checksums;
+        self
+    }
+
+    /// the metrics of the channel, shared with the receiving side
+    pub fn metrics(&self) -> &ProtocolMetricCache { &self.metrics }
+
+    /// The drain the frames are written to, e.g. to inspect the connection
+    pub fn drain(&self) -> &D { &self.drain }
+
     /// returns all promises that this Protocol can take care of
     /// If you open a Stream anyway, unsupported promises are ignored.
     pub fn supported_promises() -> Promises {
@@ -216,6 +243,7 @@ where
                 promises,
                 guaranteed_bandwidth,
             } => {
+                let queued = Instant::now();
                 self.store
                     .open_stream(sid, prio, promises, guaranteed_bandwidth);
                 if is_reliable(&promises) {
@@ -229,6 +257,11 @@ where
                 self.drain
                     .send(QuicDataFormat::with_main(&mut self.main_buffer))
                     .await?;
+                self.metrics.smsg_sent(
+                    MessageCategory::OpenStream,
+                    TCP_OPEN_STREAM_CNS as u64 + 1,
+                    queued.elapsed(),
+                );
             },
             ProtocolEvent::CloseStream { sid } => {
                 if self.store.try_close_stream(sid) {
@@ -255,6 +288,12 @@ where
                     self.pending_shutdown = true;
                 }
             },
+            ProtocolEvent::Ping { .. } | ProtocolEvent::Pong { .. } => {
+                event.to_frame().write_bytes(&mut self.main_buffer);
+                self.drain
+                    .send(QuicDataFormat::with_main(&mut self.main_buffer))
+                    .await?;
+            },
             ProtocolEvent::Message { data, sid } => {
                 self.metrics.smsg_ib(sid, data.len() as u64);
                 self.store.add(data, self.next_mid, sid);
@@ -282,7 +321,7 @@ where
                 Some(buffer) => frame.write_bytes(buffer),
                 None => {
                     self.drain
-                        .send(QuicDataFormat::with_unreliable(frame))
+                        .send(QuicDataFormat::with_unreliable(frame, self.checksums))
                         .await?
                 },
             }
@@ -372,6 +411,8 @@ where
                             //let _ = self.reliable_buffers.delete(sid); // if it was reliable
                             break 'outer Ok(ProtocolEvent::CloseStream { sid });
                         },
+                        ITFrame::Ping { nonce } => break 'outer Ok(ProtocolEvent::Ping { nonce }),
+                        ITFrame::Pong { nonce } => break 'outer Ok(ProtocolEvent::Pong { nonce }),
                         _ => break 'outer Err(ProtocolError::Violated),
                     };
                 },
@@ -465,6 +506,11 @@ where
                                         });
                                     }
                                 },
+                                // unreliable anyway, the message is lost like a dropped
+                                // datagram
+                                ITFrame::CorruptData { .. } if !reliable => {
+                                    self.metrics.rdata_corrupt();
+                                },
                                 _ => break 'outer Err(ProtocolError::Violated),
                             };
                         },
@@ -491,6 +537,18 @@ where
             .send(QuicDataFormat::with_main(&mut self.main_buffer))
             .await
     }
+
+    fn features(&self) -> HandshakeFeatures {
+        if self.request_checksums {
+            HandshakeFeatures::CHECKSUMS
+        } else {
+            HandshakeFeatures::empty()
+        }
+    }
+
+    fn negotiated(&mut self, features: HandshakeFeatures) {
+        self.checksums = features.contains(HandshakeFeatures::CHECKSUMS);
+    }
 }
 
 #[async_trait]
@@ -1,22 +1,28 @@
 use crate::{
     error::ProtocolError,
     event::ProtocolEvent,
-    frame::{ITFrame, InitFrame, OTFrame},
+    frame::{ITFrame, InitFrame, OTFrame, TCP_OPEN_STREAM_CNS},
     handshake::{ReliableDrain, ReliableSink},
     message::{ITMessage, ALLOC_BLOCK},
-    metrics::{ProtocolMetricCache, RemoveReason},
+    metrics::{MessageCategory, ProtocolMetricCache, RemoveReason},
     prio::PrioManager,
-    types::{Bandwidth, Mid, Promises, Sid},
+    types::{Bandwidth, HandshakeFeatures, Mid, Promises, Sid},
     RecvProtocol, SendProtocol, UnreliableDrain, UnreliableSink,
 };
 use async_trait::async_trait;
 use bytes::BytesMut;
 use hashbrown::HashMap;
 use std::time::{Duration, Instant};
-use tracing::info;
+use tracing::{info, warn};
 #[cfg(feature = "trace_pedantic")]
 use tracing::trace;
 
+/// Buffered frames are written without waiting once they fill about a packet
+const COALESCE_BYTES: usize = 1400;
+/// A stream without ordering or consistency promises is reset after this many
+/// corrupt data frames, others on the first one
+const MAX_CORRUPT_FRAMES: u32 = 3;
+
 /// TCP implementation of [`SendProtocol`]
 ///
 /// [`SendProtocol`]: crate::SendProtocol
@@ -34,6 +40,15 @@ where
     drain: D,
     #[allow(dead_code)]
     last: Instant,
+    /// how long frames may wait in `buffer` to be written together with others
+    max_delay: Duration,
+    buffered_since: Option<Instant>,
+    /// when the open stream frames in `buffer` were added
+    buffered_opens: Vec<Instant>,
+    /// announce checksums in the handshake
+    request_checksums: bool,
+    /// write data frames with a checksum, both sides agreed on it
+    checksums: bool,
     metrics: ProtocolMetricCache,
 }
 
@@ -48,6 +63,10 @@ where
     buffer: BytesMut,
     itmsg_allocator: BytesMut,
     incoming: HashMap<Mid, ITMessage>,
+    /// corrupt data frames per stream since its last reset
+    corrupt_frames: HashMap<Sid, u32>,
+    /// promises of the open streams, a corrupt frame breaks some of them
+    promises: HashMap<Sid, Promises>,
     sink: S,
     metrics: ProtocolMetricCache,
 }
@@ -66,10 +85,62 @@ where
             pending_shutdown: false,
             drain,
             last: Instant::now(),
+            max_delay: Duration::ZERO,
+            buffered_since: None,
+            buffered_opens: vec![],
+            request_checksums: false,
+            checksums: false,
             metrics,
         }
     }
 
+    /// Batch small frames into fewer writes, each frame is held back at most
+    /// `max_delay` (or until the next `flush` afterwards). Frames are written
+    /// immediately by default.
+    #[must_use]
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Add a checksum to each data frame, for transports which may corrupt data
+    /// on the way. Checksums are announced in the handshake and only used if
+    /// the remote announces them too. The receiver drops messages with
+    /// corrupt frames and resets their streams, see [`MAX_CORRUPT_FRAMES`].
+    #[must_use]
+    pub fn with_checksums(mut self, checksums: bool) -> Self {
+        self.request_checksums = checksums;
+        self
+    }
+
+    /// Writes the buffered frames once enough came together, the oldest one
+    /// waited `max_delay` or when `force`d
+    async fn send_buffer(&mut self, force: bool) -> Result<(), ProtocolError> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let now = Instant::now();
+        let since = *self.buffered_since.get_or_insert(now);
+        if force
+            || self.buffer.len() >= COALESCE_BYTES
+            || now.saturating_duration_since(since) >= self.max_delay
+        {
+            self.buffered_since = None;
+            self.drain.send(self.buffer.split()).await?;
+            for queued in self.buffered_opens.drain(..) {
+                self.metrics.smsg_sent(
+                    MessageCategory::OpenStream,
+                    TCP_OPEN_STREAM_CNS as u64 + 1,
+                    now.saturating_duration_since(queued),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// the metrics of the channel, shared with the receiving side
+    pub fn metrics(&self) -> &ProtocolMetricCache { &self.metrics }
+
     /// returns all promises that this Protocol can take care of
     /// If you open a Stream anyway, unsupported promises are ignored.
     pub fn supported_promises() -> Promises {
@@ -89,12 +160,39 @@ where
             buffer: BytesMut::new(),
             itmsg_allocator: BytesMut::with_capacity(ALLOC_BLOCK),
             incoming: HashMap::new(),
+            corrupt_frames: HashMap::new(),
+            promises: HashMap::new(),
             sink,
             metrics,
         }
     }
 }
 
+impl<S> TcpRecvProtocol<S>
+where
+    S: UnreliableSink<DataFormat = BytesMut>,
+{
+    /// Removes the message once all of it arrived, returns it unless it was
+    /// corrupt
+    fn try_finish(&mut self, mid: Mid) -> Option<ProtocolEvent> {
+        let m = self.incoming.get(&mid)?;
+        if m.data.len() != m.length as usize {
+            return None;
+        }
+        let m = self.incoming.remove(&mid).unwrap();
+        let reason = if m.corrupt {
+            RemoveReason::Dropped
+        } else {
+            RemoveReason::Finished
+        };
+        self.metrics.rmsg_ob(m.sid, reason, m.data.len() as u64);
+        (!m.corrupt).then(|| ProtocolEvent::Message {
+            sid: m.sid,
+            data: m.data.freeze(),
+        })
+    }
+}
+
 #[async_trait]
 impl<D> SendProtocol for TcpSendProtocol<D>
 where
@@ -135,12 +233,13 @@ where
                 self.store
                     .open_stream(sid, prio, promises, guaranteed_bandwidth);
                 event.to_frame().write_bytes(&mut self.buffer);
-                self.drain.send(self.buffer.split()).await?;
+                self.buffered_opens.push(Instant::now());
+                self.send_buffer(false).await?;
             },
             ProtocolEvent::CloseStream { sid } => {
                 if self.store.try_close_stream(sid) {
                     event.to_frame().write_bytes(&mut self.buffer);
-                    self.drain.send(self.buffer.split()).await?;
+                    self.send_buffer(false).await?;
                 } else {
                     #[cfg(feature = "trace_pedantic")]
                     trace!(?sid, "hold back close stream");
@@ -150,13 +249,17 @@ where
             ProtocolEvent::Shutdown => {
                 if self.store.is_empty() {
                     event.to_frame().write_bytes(&mut self.buffer);
-                    self.drain.send(self.buffer.split()).await?;
+                    self.send_buffer(true).await?;
                 } else {
                     #[cfg(feature = "trace_pedantic")]
                     trace!("hold back shutdown");
                     self.pending_shutdown = true;
                 }
             },
+            ProtocolEvent::Ping { .. } | ProtocolEvent::Pong { .. } => {
+                event.to_frame().write_bytes(&mut self.buffer);
+                self.send_buffer(false).await?;
+            },
             ProtocolEvent::Message { data, sid } => {
                 self.metrics.smsg_ib(sid, data.len() as u64);
                 self.store.add(data, self.next_mid, sid);
@@ -180,9 +283,12 @@ where
                 data_bandwidth += data.len();
                 data_frames += 1;
             }
-            frame.write_bytes(&mut self.buffer);
+            if self.checksums {
+                frame.write_bytes_checked(&mut self.buffer);
+            } else {
+                frame.write_bytes(&mut self.buffer);
+            }
         }
-        self.drain.send(self.buffer.split()).await?;
         self.metrics
             .sdata_frames_b(data_frames, data_bandwidth as u64);
 
@@ -192,7 +298,6 @@ where
                 #[cfg(feature = "trace_pedantic")]
                 trace!(?sid, "close stream, as it's now empty");
                 OTFrame::CloseStream { sid }.write_bytes(&mut self.buffer);
-                self.drain.send(self.buffer.split()).await?;
                 finished_streams.push(i);
             }
         }
@@ -216,9 +321,10 @@ where
             #[cfg(feature = "trace_pedantic")]
             trace!("shutdown, as it's now empty");
             OTFrame::Shutdown {}.write_bytes(&mut self.buffer);
-            self.drain.send(self.buffer.split()).await?;
+            self.send_buffer(true).await?;
             self.pending_shutdown = false;
         }
+        self.send_buffer(false).await?;
         Ok(data_bandwidth as u64)
     }
 }
@@ -243,6 +349,7 @@ where
                                 promises,
                                 guaranteed_bandwidth,
                             } => {
+                                self.promises.insert(sid, promises);
                                 break 'outer Ok(ProtocolEvent::OpenStream {
                                     sid,
                                     prio: prio.min(crate::types::HIGHEST_PRIO),
@@ -251,8 +358,16 @@ where
                                 });
                             },
                             ITFrame::CloseStream { sid } => {
+                                self.promises.remove(&sid);
+                                self.corrupt_frames.remove(&sid);
                                 break 'outer Ok(ProtocolEvent::CloseStream { sid });
                             },
+                            ITFrame::Ping { nonce } => {
+                                break 'outer Ok(ProtocolEvent::Ping { nonce });
+                            },
+                            ITFrame::Pong { nonce } => {
+                                break 'outer Ok(ProtocolEvent::Pong { nonce });
+                            },
                             ITFrame::DataHeader { sid, mid, length } => {
                                 let m = ITMessage::new(sid, length, &mut self.itmsg_allocator);
                                 self.metrics.rmsg_ib(sid, length);
@@ -272,18 +387,40 @@ where
                                     },
                                 };
                                 m.data.extend_from_slice(&data);
-                                if m.data.len() == m.length as usize {
-                                    // finished, yay
-                                    let m = self.incoming.remove(&mid).unwrap();
-                                    self.metrics.rmsg_ob(
-                                        m.sid,
-                                        RemoveReason::Finished,
-                                        m.data.len() as u64,
-                                    );
-                                    break 'outer Ok(ProtocolEvent::Message {
-                                        sid: m.sid,
-                                        data: m.data.freeze(),
-                                    });
+                                if let Some(event) = self.try_finish(mid) {
+                                    break 'outer Ok(event);
+                                }
+                            },
+                            ITFrame::CorruptData { mid, length } => {
+                                self.metrics.rdata_corrupt();
+                                // the mid itself might be what got corrupted
+                                let m = match self.incoming.get_mut(&mid) {
+                                    Some(m) => m,
+                                    None => continue,
+                                };
+                                let sid = m.sid;
+                                warn!(?sid, ?mid, "dropping message with a corrupt data frame");
+                                m.corrupt = true;
+                                // keep counting towards the end of the message
+                                let len = (m.data.len() + length).min(m.length as usize);
+                                m.data.resize(len, 0);
+                                let _ = self.try_finish(mid);
+
+                                // later messages can't be delivered in order or
+                                // without a gap anymore
+                                let strict = self.promises.get(&sid).map_or(false, |p| {
+                                    p.intersects(Promises::ORDERED | Promises::CONSISTENCY)
+                                });
+                                let count = self.corrupt_frames.entry(sid).or_insert(0);
+                                *count += 1;
+                                if strict || *count >= MAX_CORRUPT_FRAMES {
+                                    warn!(?sid, "resetting stream after corruption");
+                                    self.corrupt_frames.remove(&sid);
+                                    self.promises.remove(&sid);
+                                    for m in self.incoming.values_mut().filter(|m| m.sid == sid) {
+                                        m.corrupt = true;
+                                    }
+                                    break 'outer Ok(ProtocolEvent::CloseStream { sid });
                                 }
                             },
                         };
@@ -312,6 +449,18 @@ where
         frame.write_bytes(&mut buffer);
         self.drain.send(buffer).await
     }
+
+    fn features(&self) -> HandshakeFeatures {
+        if self.request_checksums {
+            HandshakeFeatures::CHECKSUMS
+        } else {
+            HandshakeFeatures::empty()
+        }
+    }
+
+    fn negotiated(&mut self, features: HandshakeFeatures) {
+        self.checksums = features.contains(HandshakeFeatures::CHECKSUMS);
+    }
 }
 
 #[async_trait]
@@ -432,6 +581,32 @@ mod tests {
         assert_eq!(event, e);
     }
 
+    #[tokio::test]
+    async fn coalesce_small_frames() {
+        let [p1, p2] = tcp_bound(10, None);
+        let (mut s, mut r) = (p1.0.with_max_delay(Duration::from_secs(3600)), p2.1);
+        for sid in [Sid::new(10), Sid::new(11)] {
+            let event = ProtocolEvent::OpenStream {
+                sid,
+                prio: 0u8,
+                promises: Promises::ORDERED,
+                guaranteed_bandwidth: 1_000_000,
+            };
+            s.send(event).await.unwrap();
+        }
+        s.flush(1_000_000, Duration::from_secs(1)).await.unwrap();
+        assert!(s.drain.sender.is_empty());
+        // shutdown doesn't wait
+        s.send(ProtocolEvent::Shutdown).await.unwrap();
+        assert_eq!(s.drain.sender.len(), 1);
+        let e = r.recv().await.unwrap();
+        assert!(matches!(e, ProtocolEvent::OpenStream { .. }));
+        let e = r.recv().await.unwrap();
+        assert!(matches!(e, ProtocolEvent::OpenStream { .. }));
+        let e = r.recv().await.unwrap();
+        assert!(matches!(e, ProtocolEvent::Shutdown));
+    }
+
     #[tokio::test]
     async fn send_short_msg() {
         let [p1, p2] = tcp_bound(10, None);
@@ -635,6 +810,81 @@ mod tests {
         assert!(matches!(e, ProtocolEvent::CloseStream { .. }));
     }
 
+    #[tokio::test]
+    async fn corrupt_frames_reset_stream() {
+        let (lossy, strict) = (Sid::new(1), Sid::new(2));
+        let (s, r) = async_channel::bounded(10);
+        let mut m = ProtocolMetricCache::new("tcp", Arc::new(ProtocolMetrics::new().unwrap()));
+        let mut r =
+            super::TcpRecvProtocol::new(super::test_utils::TcpSink { receiver: r }, m.clone());
+
+        let mut bytes = BytesMut::with_capacity(1500);
+        let write_msg = |bytes: &mut BytesMut, sid, mid, corrupt| {
+            OTFrame::DataHeader { mid, sid, length: 6 }.write_bytes(bytes);
+            OTFrame::Data {
+                mid,
+                data: Bytes::from(&b"foobar"[..]),
+            }
+            .write_bytes_checked(bytes);
+            if corrupt {
+                let last = bytes.len() - 1;
+                bytes[last] ^= 1;
+            }
+        };
+        for (sid, promises) in [(lossy, Promises::empty()), (strict, Promises::CONSISTENCY)] {
+            OTFrame::OpenStream {
+                sid,
+                prio: 5u8,
+                promises,
+                guaranteed_bandwidth: 0,
+            }
+            .write_bytes(&mut bytes);
+        }
+        // only the first message arrives intact
+        for mid in 0..4 {
+            write_msg(&mut bytes, lossy, mid, mid > 0);
+        }
+        write_msg(&mut bytes, strict, 4, true);
+        s.send(bytes.split()).await.unwrap();
+
+        for _ in 0..2 {
+            let e = r.recv().await.unwrap();
+            assert!(matches!(e, ProtocolEvent::OpenStream { .. }));
+        }
+        let e = r.recv().await.unwrap();
+        assert_eq!(e, ProtocolEvent::Message {
+            sid: lossy,
+            data: Bytes::from(&b"foobar"[..]),
+        });
+        // the lossy stream tolerates a few corrupt frames, the consistent one
+        // none
+        let e = r.recv().await.unwrap();
+        assert_eq!(e, ProtocolEvent::CloseStream { sid: lossy });
+        let e = r.recv().await.unwrap();
+        assert_eq!(e, ProtocolEvent::CloseStream { sid: strict });
+        m.assert_corrupt_frames(4);
+    }
+
+    #[tokio::test]
+    async fn negotiate_checksums() {
+        for (c1, c2) in [(true, true), (true, false), (false, false)] {
+            let [p1, p2] = tcp_bound(10, None);
+            let mut p1 = (p1.0.with_checksums(c1), p1.1);
+            let mut p2 = (p2.0.with_checksums(c2), p2.1);
+            let r1 = tokio::spawn(async move {
+                p1.initialize(true, Pid::fake(2), 1337).await.unwrap();
+                p1.0.checksums
+            });
+            let r2 = tokio::spawn(async move {
+                p2.initialize(false, Pid::fake(3), 42).await.unwrap();
+                p2.0.checksums
+            });
+            let (r1, r2) = tokio::join!(r1, r2);
+            assert_eq!(r1.unwrap(), c1 && c2);
+            assert_eq!(r2.unwrap(), c1 && c2);
+        }
+    }
+
     #[tokio::test]
     async fn drop_sink_while_recv() {
         let sid = Sid::new(1);
@@ -7,10 +7,10 @@ pub type Mid = u64;
 /// ChannelID, unique ID per Channel (Protocol)
 pub type Cid = u64;
 /// Every Stream has a `Prio` and guaranteed [`Bandwidth`].
-/// Every send, the guarantees part is used first.
-/// If there is still bandwidth left, it will be shared by all Streams with the
-/// same priority. Prio 0 will be send first, then 1, ... till the last prio 7
-/// is send. Prio must be < 8!
+/// Every send, the guarantees part is always send.
+/// The bandwidth left is shared by all Streams with something to send, weighted
+/// by priority: a Stream of Prio 0 gets twice the share of one with Prio 1, ...
+/// till the last prio 7. Prio must be < 8!
 ///
 /// [`Bandwidth`]: crate::Bandwidth
 pub type Prio = u8;
@@ -47,9 +47,18 @@ impl Promises {
     pub const fn to_le_bytes(self) -> [u8; 1] { self.bits.to_le_bytes() }
 }
 
+bitflags! {
+    /// Optional features announced in the handshake, a feature is only used
+    /// if both sides announce it
+    pub struct HandshakeFeatures: u8 {
+        /// data frames carry a checksum, which is verified by the receiver
+        const CHECKSUMS = 0b00000001;
+    }
+}
+
 pub(crate) const VELOREN_MAGIC_NUMBER: [u8; 7] = *b"VELOREN";
 /// When this semver differs, 2 Networks can't communicate.
-pub const VELOREN_NETWORK_VERSION: [u32; 3] = [0, 6, 0];
+pub const VELOREN_NETWORK_VERSION: [u32; 3] = [0, 10, 0];
 pub(crate) const STREAM_ID_OFFSET1: Sid = Sid::new(0);
 pub(crate) const STREAM_ID_OFFSET2: Sid = Sid::new(u64::MAX / 2);
 /// Maximal possible Prio to choose (for performance reasons)
@@ -0,0 +1,285 @@
+use crate::metrics::NetworkMetrics;
+use hashbrown::HashMap;
+use std::{
+    net::IpAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tracing::*;
+
+/// Limits for incoming connections, consulted by the listeners before any
+/// task is spawned for a new connection. No limits apply until one is set
+/// with [`Network::set_admission_policy`]. The default is lenient enough for
+/// many players sharing one NAT address and bans nobody.
+///
+/// [`Network::set_admission_policy`]: crate::api::Network::set_admission_policy
+#[derive(Debug, Clone)]
+pub struct AdmissionPolicy {
+    /// open connections per remote IP, `None` for unlimited
+    pub max_connections_per_ip: Option<usize>,
+    /// connect attempts per second an IP regains (token bucket refill rate)
+    pub connect_rate: f32,
+    /// connect attempts an IP can make at once (token bucket size)
+    pub connect_burst: f32,
+    /// rejected attempts after which an IP gets banned, `None` to never ban
+    pub rejections_until_ban: Option<u32>,
+    pub ban_duration: Duration,
+}
+
+impl Default for AdmissionPolicy {
+    fn default() -> Self {
+        Self {
+            max_connections_per_ip: Some(64),
+            connect_rate: 10.0,
+            connect_burst: 50.0,
+            rejections_until_ban: None,
+            ban_duration: Duration::from_secs(300),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Rejection {
+    TooManyConnections,
+    RateLimited,
+    Banned,
+}
+
+impl Rejection {
+    pub(crate) fn to_str(self) -> &'static str {
+        match self {
+            Rejection::TooManyConnections => "too_many_connections",
+            Rejection::RateLimited => "rate_limited",
+            Rejection::Banned => "banned",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Peer {
+    open: usize,
+    tokens: f32,
+    last_attempt: Instant,
+    rejections: u32,
+    banned_until: Option<Instant>,
+}
+
+#[derive(Debug)]
+struct State {
+    /// `None` admits every connection, open ones are still counted
+    policy: Option<AdmissionPolicy>,
+    peers: HashMap<IpAddr, Peer>,
+    last_cleanup: Instant,
+}
+
+/// Tracks connect attempts and open connections per IP for all listeners of
+/// a `Network`
+#[derive(Debug)]
+pub(crate) struct Admission {
+    state: Mutex<State>,
+    metrics: Arc<NetworkMetrics>,
+}
+
+/// Counts as an open connection of its IP until dropped
+#[derive(Debug)]
+pub(crate) struct AdmissionGuard {
+    admission: Arc<Admission>,
+    ip: IpAddr,
+}
+
+impl Admission {
+    /// Idle peers are forgotten after this, so the map can't grow unbounded
+    const CLEANUP_INTERVAL: Duration = Duration::from_secs(60);
+
+    pub(crate) fn new(metrics: Arc<NetworkMetrics>) -> Self {
+        Self {
+            state: Mutex::new(State {
+                policy: None,
+                peers: HashMap::new(),
+                last_cleanup: Instant::now(),
+            }),
+            metrics,
+        }
+    }
+
+    pub(crate) fn set_policy(&self, policy: AdmissionPolicy) {
+        self.state.lock().unwrap().set_policy(policy);
+    }
+
+    /// Either admits a new connection from `ip`, which counts as open until
+    /// the returned guard is dropped, or tells why it was rejected
+    pub(crate) fn admit(self: &Arc<Self>, ip: IpAddr) -> Result<AdmissionGuard, Rejection> {
+        let result = self.state.lock().unwrap().admit(ip, Instant::now());
+        match result {
+            Ok(()) => Ok(AdmissionGuard {
+                admission: Arc::clone(self),
+                ip,
+            }),
+            Err(rejection) => {
+                self.metrics.connection_rejected(rejection.to_str());
+                Err(rejection)
+            },
+        }
+    }
+}
+
+impl State {
+    fn set_policy(&mut self, policy: AdmissionPolicy) {
+        // Tokens aren't spent without a policy, so peers seen before start full
+        if self.policy.is_none() {
+            for peer in self.peers.values_mut() {
+                peer.tokens = policy.connect_burst;
+            }
+        }
+        self.policy = Some(policy);
+    }
+
+    fn admit(&mut self, ip: IpAddr, now: Instant) -> Result<(), Rejection> {
+        if now.saturating_duration_since(self.last_cleanup) >= Admission::CLEANUP_INTERVAL {
+            self.cleanup(now);
+        }
+
+        let policy = &self.policy;
+        let peer = self.peers.entry(ip).or_insert_with(|| Peer {
+            open: 0,
+            tokens: policy.as_ref().map_or(0.0, |p| p.connect_burst),
+            last_attempt: now,
+            rejections: 0,
+            banned_until: None,
+        });
+
+        let policy = match policy {
+            Some(policy) => policy,
+            None => {
+                peer.open += 1;
+                peer.last_attempt = now;
+                return Ok(());
+            },
+        };
+
+        if let Some(until) = peer.banned_until {
+            if now < until {
+                return Err(Rejection::Banned);
+            }
+            peer.banned_until = None;
+            peer.rejections = 0;
+        }
+
+        let elapsed = now.saturating_duration_since(peer.last_attempt);
+        peer.tokens =
+            (peer.tokens + elapsed.as_secs_f32() * policy.connect_rate).min(policy.connect_burst);
+        peer.last_attempt = now;
+
+        let rejection = if peer.tokens < 1.0 {
+            Rejection::RateLimited
+        } else if policy
+            .max_connections_per_ip
+            .map_or(false, |max| peer.open >= max)
+        {
+            Rejection::TooManyConnections
+        } else {
+            peer.tokens -= 1.0;
+            peer.open += 1;
+            return Ok(());
+        };
+
+        peer.rejections += 1;
+        if policy
+            .rejections_until_ban
+            .map_or(false, |max| peer.rejections >= max)
+        {
+            info!(?ip, "banning ip after too many rejected connection attempts");
+            peer.banned_until = Some(now + policy.ban_duration);
+        }
+        Err(rejection)
+    }
+
+    fn release(&mut self, ip: IpAddr) {
+        if let Some(peer) = self.peers.get_mut(&ip) {
+            peer.open = peer.open.saturating_sub(1);
+        }
+    }
+
+    fn cleanup(&mut self, now: Instant) {
+        self.peers.retain(|_, peer| {
+            peer.open > 0
+                || peer.banned_until.map_or(false, |until| until > now)
+                || now.saturating_duration_since(peer.last_attempt) < Admission::CLEANUP_INTERVAL
+        });
+        self.last_cleanup = now;
+    }
+}
+
+impl Drop for AdmissionGuard {
+    fn drop(&mut self) {
+        if let Ok(mut state) = self.admission.state.lock() {
+            state.release(self.ip);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(policy: Option<AdmissionPolicy>) -> State {
+        State {
+            policy,
+            peers: HashMap::new(),
+            last_cleanup: Instant::now(),
+        }
+    }
+
+    #[test]
+    fn limits_and_bans() {
+        let ip: IpAddr = "127.0.0.1".parse().unwrap();
+        let mut state = state(Some(AdmissionPolicy {
+            max_connections_per_ip: Some(2),
+            connect_rate: 1.0,
+            connect_burst: 3.0,
+            rejections_until_ban: Some(2),
+            ban_duration: Duration::from_secs(60),
+        }));
+        let now = Instant::now();
+        assert_eq!(state.admit(ip, now), Ok(()));
+        assert_eq!(state.admit(ip, now), Ok(()));
+        assert_eq!(state.admit(ip, now), Err(Rejection::TooManyConnections));
+        state.release(ip);
+        assert_eq!(state.admit(ip, now), Ok(()));
+        // the bucket is empty now
+        assert_eq!(state.admit(ip, now), Err(Rejection::RateLimited));
+        assert_eq!(state.admit(ip, now), Err(Rejection::Banned));
+        let later = now + Duration::from_secs(61);
+        state.release(ip);
+        assert_eq!(state.admit(ip, later), Ok(()));
+    }
+
+    #[test]
+    fn unlimited_without_policy() {
+        let ip: IpAddr = "127.0.0.1".parse().unwrap();
+        let mut state = state(None);
+        let now = Instant::now();
+        for _ in 0..1000 {
+            assert_eq!(state.admit(ip, now), Ok(()));
+        }
+        assert_eq!(state.peers[&ip].open, 1000);
+    }
+
+    #[test]
+    fn burst_for_peers_seen_without_policy() {
+        let ip: IpAddr = "127.0.0.1".parse().unwrap();
+        let mut state = state(None);
+        let now = Instant::now();
+        assert_eq!(state.admit(ip, now), Ok(()));
+        state.set_policy(AdmissionPolicy {
+            max_connections_per_ip: None,
+            connect_rate: 0.0,
+            connect_burst: 2.0,
+            rejections_until_ban: None,
+            ban_duration: Duration::from_secs(60),
+        });
+        assert_eq!(state.admit(ip, now), Ok(()));
+        assert_eq!(state.admit(ip, now), Ok(()));
+        assert_eq!(state.admit(ip, now), Err(Rejection::RateLimited));
+    }
+}
@@ -1,4 +1,6 @@
 use crate::{
+    admission::{Admission, AdmissionPolicy},
+    auth::{AuthProvider, StaticSecret},
     message::{partial_eq_bincode, Message},
     participant::{A2bStreamOpen, S2bShutdownBparticipant},
     scheduler::{A2sConnect, Scheduler},
@@ -7,7 +9,9 @@ use bytes::Bytes;
 use hashbrown::HashMap;
 #[cfg(feature = "compression")]
 use lz_fear::raw::DecodeError;
-use network_protocol::{Bandwidth, InitProtocolError, Pid, Prio, Promises, Sid};
+use network_protocol::{
+    Bandwidth, ConnectionQuality, InitProtocolError, Pid, Prio, Promises, Sid,
+};
 #[cfg(feature = "metrics")]
 use prometheus::Registry;
 use serde::{de::DeserializeOwned, Serialize};
@@ -61,6 +65,7 @@ pub struct Participant {
     a2b_open_stream_s: Mutex<mpsc::UnboundedSender<A2bStreamOpen>>,
     b2a_stream_opened_r: Mutex<mpsc::UnboundedReceiver<Stream>>,
     b2a_bandwidth_stats_r: watch::Receiver<f32>,
+    b2a_quality_r: watch::Receiver<ConnectionQuality>,
     a2s_disconnect_s: A2sDisconnect,
 }
 
@@ -188,6 +193,7 @@ pub struct Network {
     listen_sender: Mutex<mpsc::UnboundedSender<(ListenAddr, oneshot::Sender<io::Result<()>>)>>,
     connect_sender: Mutex<mpsc::UnboundedSender<A2sConnect>>,
     connected_receiver: Mutex<mpsc::UnboundedReceiver<Participant>>,
+    admission: Arc<Admission>,
     shutdown_network_s: Option<oneshot::Sender<oneshot::Sender<()>>>,
 }
 
@@ -228,6 +234,29 @@ impl Network {
         Self::internal_new(
             participant_id,
             runtime,
+            Arc::new(StaticSecret::random()),
+            #[cfg(feature = "metrics")]
+            None,
+        )
+    }
+
+    /// See [`new`]
+    ///
+    /// # additional Arguments
+    /// * `auth` - Provides the handshake secret and decides which remotes may
+    ///   connect, see [`AuthProvider`]
+    ///
+    /// [`new`]: crate::api::Network::new
+    /// [`AuthProvider`]: crate::AuthProvider
+    pub fn new_with_auth(
+        participant_id: Pid,
+        runtime: &Runtime,
+        auth: Arc<dyn AuthProvider>,
+    ) -> Self {
+        Self::internal_new(
+            participant_id,
+            runtime,
+            auth,
             #[cfg(feature = "metrics")]
             None,
         )
@@ -253,23 +282,50 @@ impl Network {
     /// [`new`]: crate::api::Network::new
     #[cfg(feature = "metrics")]
     pub fn new_with_registry(participant_id: Pid, runtime: &Runtime, registry: &Registry) -> Self {
-        Self::internal_new(participant_id, runtime, Some(registry))
+        Self::internal_new(
+            participant_id,
+            runtime,
+            Arc::new(StaticSecret::random()),
+            Some(registry),
+        )
+    }
+
+    /// See [`new_with_auth`] and [`new_with_registry`]
+    ///
+    /// [`new_with_auth`]: crate::api::Network::new_with_auth
+    /// [`new_with_registry`]: crate::api::Network::new_with_registry
+    #[cfg(feature = "metrics")]
+    pub fn new_with_registry_and_auth(
+        participant_id: Pid,
+        runtime: &Runtime,
+        registry: &Registry,
+        auth: Arc<dyn AuthProvider>,
+    ) -> Self {
+        Self::internal_new(participant_id, runtime, auth, Some(registry))
     }
 
     fn internal_new(
         participant_id: Pid,
         runtime: &Runtime,
+        auth: Arc<dyn AuthProvider>,
         #[cfg(feature = "metrics")] registry: Option<&Registry>,
     ) -> Self {
         let p = participant_id;
         let span = tracing::info_span!("network", ?p);
         span.in_scope(|| trace!("Starting Network"));
-        let (scheduler, listen_sender, connect_sender, connected_receiver, shutdown_sender) =
-            Scheduler::new(
-                participant_id,
-                #[cfg(feature = "metrics")]
-                registry,
-            );
+        let (
+            scheduler,
+            listen_sender,
+            connect_sender,
+            connected_receiver,
+            shutdown_sender,
+            admission,
+        ) = Scheduler::new(
+            participant_id,
+            auth,
+            #[cfg(feature = "metrics")]
+            registry,
+        );
         let participant_disconnect_sender = Arc::new(Mutex::new(HashMap::new()));
         let (shutdown_network_s, shutdown_network_r) = oneshot::channel();
         let f = Self::shutdown_mgr(
@@ -293,10 +349,20 @@ impl Network {
             listen_sender: Mutex::new(listen_sender),
             connect_sender: Mutex::new(connect_sender),
             connected_receiver: Mutex::new(connected_receiver),
+            admission,
             shutdown_network_s: Some(shutdown_network_s),
         }
     }
 
+    /// Replaces the [`AdmissionPolicy`] that decides which incoming connections
+    /// are accepted on all listened addresses. Already open connections are
+    /// kept. Without calling this, every connection is accepted.
+    ///
+    /// [`AdmissionPolicy`]: crate::AdmissionPolicy
+    pub fn set_admission_policy(&self, policy: AdmissionPolicy) {
+        self.admission.set_policy(policy);
+    }
+
     /// starts listening on an [`ListenAddr`].
     /// When the method returns the `Network` is ready to listen for incoming
     /// connections OR has returned a [`NetworkError`] (e.g. port already used).
@@ -520,6 +586,7 @@ impl Participant {
         a2b_open_stream_s: mpsc::UnboundedSender<A2bStreamOpen>,
         b2a_stream_opened_r: mpsc::UnboundedReceiver<Stream>,
         b2a_bandwidth_stats_r: watch::Receiver<f32>,
+        b2a_quality_r: watch::Receiver<ConnectionQuality>,
         a2s_disconnect_s: mpsc::UnboundedSender<(Pid, S2bShutdownBparticipant)>,
     ) -> Self {
         Self {
@@ -528,6 +595,7 @@ impl Participant {
             a2b_open_stream_s: Mutex::new(a2b_open_stream_s),
             b2a_stream_opened_r: Mutex::new(b2a_stream_opened_r),
             b2a_bandwidth_stats_r,
+            b2a_quality_r,
             a2s_disconnect_s: Arc::new(Mutex::new(Some(a2s_disconnect_s))),
         }
     }
@@ -755,6 +823,10 @@ impl Participant {
     /// This WILL fluctuate based on the amount/size of send messages.
     pub fn bandwidth(&self) -> f32 { *self.b2a_bandwidth_stats_r.borrow() }
 
+    /// Returns the latest estimate of round trip time, jitter and loss,
+    /// measured with keepalive pings about once a second.
+    pub fn connection_quality(&self) -> ConnectionQuality { *self.b2a_quality_r.borrow() }
+
     /// Returns the remote [`Pid`](network_protocol::Pid)
     pub fn remote_pid(&self) -> Pid { self.remote_pid }
 }
@@ -0,0 +1,202 @@
+use hashbrown::HashSet;
+use network_protocol::Pid;
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use std::hash::{Hash, Hasher};
+
+/// Decides which secret a `Network` presents in the handshake of every channel
+/// and which remote secrets it accepts.
+///
+/// The secret of every channel is checked with [`verify_channel`] before the
+/// accepting side answers with its own secret, every further channel of a
+/// [`Participant`] must present the same secret as its first one.
+/// Inject your own implementation with [`Network::new_with_auth`] to tie
+/// connections to an auth backend.
+///
+/// [`Participant`]: crate::api::Participant
+/// [`verify_channel`]: AuthProvider::verify_channel
+/// [`Network::new_with_auth`]: crate::api::Network::new_with_auth
+pub trait AuthProvider: Send + Sync + std::fmt::Debug {
+    /// Secret sent to the remote, the same one is used for all channels.
+    /// It goes over the wire unencrypted unless QUIC is used, so it must not
+    /// reveal anything a remote could use to pass [`verify`] itself.
+    ///
+    /// [`verify`]: AuthProvider::verify
+    fn local_secret(&self, local_pid: Pid) -> u128;
+
+    /// Whether a remote with this secret may connect
+    fn verify(&self, remote_pid: Pid, remote_secret: u128) -> bool;
+
+    /// Like [`verify`], additionally given the DER encoded TLS certificate
+    /// the remote presented. Only QUIC channels carry one.
+    ///
+    /// [`verify`]: AuthProvider::verify
+    fn verify_channel(
+        &self,
+        remote_pid: Pid,
+        remote_secret: u128,
+        _peer_certificate: Option<&[u8]>,
+    ) -> bool {
+        self.verify(remote_pid, remote_secret)
+    }
+}
+
+/// Presents a fixed secret and accepts every remote, this is the default with
+/// a random secret
+#[derive(Debug, Clone, Copy)]
+pub struct StaticSecret(pub u128);
+
+impl StaticSecret {
+    pub fn random() -> Self { Self(rand::thread_rng().gen()) }
+}
+
+impl AuthProvider for StaticSecret {
+    fn local_secret(&self, _local_pid: Pid) -> u128 { self.0 }
+
+    fn verify(&self, _remote_pid: Pid, _remote_secret: u128) -> bool { true }
+}
+
+/// Only accepts remotes which know the same token, e.g. for private servers.
+///
+/// The token itself never goes over the wire. The secret is a random nonce
+/// and a keyed SHA-256 of that nonce and the pid of the sender, so it only
+/// passes for the pid it was made for. Secrets seen on the wire can still be
+/// replayed with the same pid, use QUIC to keep them private.
+#[derive(Clone, Copy)]
+pub struct TokenSecret {
+    key: [u8; 32],
+    nonce: u64,
+}
+
+/// Collects the bytes a value hashes into SHA-256, [`Pid`] doesn't expose
+/// them otherwise
+struct Sha256Hasher(Sha256);
+
+impl Hasher for Sha256Hasher {
+    fn write(&mut self, bytes: &[u8]) { self.0.update(bytes); }
+
+    // Only the digest is used
+    fn finish(&self) -> u64 { 0 }
+}
+
+impl TokenSecret {
+    pub fn new(token: &str) -> Self {
+        let mut key = [0; 32];
+        key.copy_from_slice(&Sha256::digest(token.as_bytes()));
+        Self {
+            key,
+            nonce: rand::thread_rng().gen(),
+        }
+    }
+
+    fn mac(&self, nonce: u64, pid: Pid) -> u64 {
+        let mut hasher = Sha256Hasher(Sha256::new());
+        hasher.write(&self.key);
+        hasher.write(&nonce.to_le_bytes());
+        pid.hash(&mut hasher);
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&hasher.0.finalize()[..8]);
+        u64::from_le_bytes(bytes)
+    }
+}
+
+impl std::fmt::Debug for TokenSecret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TokenSecret")
+            .field("nonce", &self.nonce)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AuthProvider for TokenSecret {
+    fn local_secret(&self, local_pid: Pid) -> u128 {
+        (self.nonce as u128) << 64 | self.mac(self.nonce, local_pid) as u128
+    }
+
+    fn verify(&self, remote_pid: Pid, remote_secret: u128) -> bool {
+        let nonce = (remote_secret >> 64) as u64;
+        remote_secret as u64 == self.mac(nonce, remote_pid)
+    }
+}
+
+/// Ties the secret to TLS client certificates (mTLS): the local secret is the
+/// fingerprint of the own certificate and a remote is only accepted if it
+/// presented one of the trusted certificates to TLS and sent its fingerprint.
+///
+/// Only QUIC channels carry certificates, all others are rejected. The
+/// `ServerConfig` of the listener has to request client certificates, e.g.
+/// with `rustls::server::AllowAnyAuthenticatedClient`.
+#[derive(Debug, Clone)]
+pub struct CertificateSecret {
+    local: u128,
+    trusted: HashSet<u128>,
+}
+
+impl CertificateSecret {
+    /// `certificate` is the DER encoded one this side presents, `trusted` the
+    /// ones of the remotes which may connect
+    pub fn new<'a>(certificate: &[u8], trusted: impl IntoIterator<Item = &'a [u8]>) -> Self {
+        Self {
+            local: Self::fingerprint(certificate),
+            trusted: trusted.into_iter().map(Self::fingerprint).collect(),
+        }
+    }
+
+    /// The first 128 bit of the SHA-256 of a DER encoded certificate
+    pub fn fingerprint(certificate: &[u8]) -> u128 {
+        let mut bytes = [0; 16];
+        bytes.copy_from_slice(&Sha256::digest(certificate)[..16]);
+        u128::from_be_bytes(bytes)
+    }
+}
+
+impl AuthProvider for CertificateSecret {
+    fn local_secret(&self, _local_pid: Pid) -> u128 { self.local }
+
+    // Without a certificate there is nothing to check the secret against
+    fn verify(&self, _remote_pid: Pid, _remote_secret: u128) -> bool { false }
+
+    fn verify_channel(
+        &self,
+        _remote_pid: Pid,
+        remote_secret: u128,
+        peer_certificate: Option<&[u8]>,
+    ) -> bool {
+        peer_certificate.map_or(false, |certificate| {
+            let fingerprint = Self::fingerprint(certificate);
+            fingerprint == remote_secret && self.trusted.contains(&fingerprint)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_secret() {
+        let (server, client) = (Pid::fake(0), Pid::fake(1));
+        let secret = TokenSecret::new("hunter2");
+        let same = TokenSecret::new("hunter2").local_secret(client);
+        assert!(secret.verify(client, same));
+        assert!(!secret.verify(client, TokenSecret::new("hunter3").local_secret(client)));
+        // a secret only passes for the pid it was made for
+        assert!(!secret.verify(server, same));
+        let own = secret.local_secret(server);
+        assert!(!secret.verify(client, own));
+        assert!(!secret.verify(client, own ^ 1));
+    }
+
+    #[test]
+    fn certificate_secret() {
+        let (server, client, stranger): (&[u8], &[u8], &[u8]) = (b"server", b"client", b"other");
+        let secret = CertificateSecret::new(server, [client]);
+        let remote = CertificateSecret::new(client, [server]).local_secret(Pid::fake(1));
+        assert!(secret.verify_channel(Pid::fake(0), remote, Some(client)));
+        assert!(!secret.verify_channel(Pid::fake(0), remote, None));
+        assert!(!secret.verify_channel(Pid::fake(0), remote, Some(stranger)));
+        let forged = CertificateSecret::fingerprint(stranger);
+        assert!(!secret.verify_channel(Pid::fake(0), forged, Some(stranger)));
+        assert!(!secret.verify(Pid::fake(0), remote));
+    }
+}
@@ -1,4 +1,7 @@
-use crate::api::NetworkConnectError;
+use crate::{
+    admission::{Admission, AdmissionGuard},
+    api::NetworkConnectError,
+};
 use async_trait::async_trait;
 use bytes::BytesMut;
 use futures_util::FutureExt;
@@ -19,7 +22,7 @@ use std::{
         atomic::{AtomicU64, Ordering},
         Arc,
     },
-    time::Duration,
+    time::{Duration, Instant},
 };
 use tokio::{
     io::{AsyncReadExt, AsyncWriteExt},
@@ -55,6 +58,10 @@ pub(crate) enum RecvProtocols {
     Quic(QuicRecvProtocol<QuicSink>),
 }
 
+/// Small TCP frames are held back this long to be written together, as every
+/// write is a packet with `TCP_NODELAY`
+const TCP_MAX_COALESCE_DELAY: Duration = Duration::from_millis(5);
+
 lazy_static::lazy_static! {
     pub(crate) static ref MPSC_POOL: Mutex<HashMap<u64, mpsc::UnboundedSender<C2cMpscConnect>>> = {
         Mutex::new(HashMap::new())
@@ -84,13 +91,14 @@ impl Protocols {
             "Connecting Tcp to: {}",
             stream.peer_addr().map_err(NetworkConnectError::Io)?
         );
-        Ok(Self::new_tcp(stream, metrics))
+        Ok(Self::new_tcp(stream, metrics, None))
     }
 
     pub(crate) async fn with_tcp_listen(
         addr: SocketAddr,
         cids: Arc<AtomicU64>,
         metrics: Arc<ProtocolMetrics>,
+        admission: Arc<Admission>,
         s2s_stop_listening_r: oneshot::Receiver<()>,
         c2s_protocol_s: mpsc::UnboundedSender<(Self, Cid)>,
     ) -> std::io::Result<()> {
@@ -123,6 +131,13 @@ impl Protocols {
                         continue;
                     },
                 };
+                let admission = match admission.admit(remote_addr.ip()) {
+                    Ok(guard) => guard,
+                    Err(rejection) => {
+                        trace!(?remote_addr, ?rejection, "Rejecting Tcp connection attempt");
+                        continue;
+                    },
+                };
                 if let Err(e) = stream.set_nodelay(true) {
                     warn!(
                         ?e,
@@ -132,15 +147,25 @@ impl Protocols {
                 let cid = cids.fetch_add(1, Ordering::Relaxed);
                 info!(?remote_addr, ?cid, "Accepting Tcp from");
                 let metrics = ProtocolMetricCache::new(&cid.to_string(), Arc::clone(&metrics));
-                let _ = c2s_protocol_s.send((Self::new_tcp(stream, metrics.clone()), cid));
+                let tcp = Self::new_tcp(stream, metrics.clone(), Some(admission));
+                let _ = c2s_protocol_s.send((tcp, cid));
             }
         });
         Ok(())
     }
 
-    pub(crate) fn new_tcp(stream: tokio::net::TcpStream, metrics: ProtocolMetricCache) -> Self {
+    pub(crate) fn new_tcp(
+        stream: tokio::net::TcpStream,
+        metrics: ProtocolMetricCache,
+        admission: Option<AdmissionGuard>,
+    ) -> Self {
         let (r, w) = stream.into_split();
-        let sp = TcpSendProtocol::new(TcpDrain { half: w }, metrics.clone());
+        let drain = TcpDrain {
+            half: w,
+            _admission: admission,
+        };
+        let sp = TcpSendProtocol::new(drain, metrics.clone())
+            .with_max_delay(TCP_MAX_COALESCE_DELAY);
         let rp = TcpRecvProtocol::new(
             TcpSink {
                 half: r,
@@ -267,7 +292,7 @@ impl Protocols {
                 e,
             ))
         })?;
-        Self::new_quic(connection, false, metrics)
+        Self::new_quic(connection, false, metrics, None)
             .await
             .map_err(|e| {
                 trace!(?e, "error with quic");
@@ -284,6 +309,7 @@ impl Protocols {
         server_config: quinn::ServerConfig,
         cids: Arc<AtomicU64>,
         metrics: Arc<ProtocolMetrics>,
+        admission: Arc<Admission>,
         s2s_stop_listening_r: oneshot::Receiver<()>,
         c2s_protocol_s: mpsc::UnboundedSender<(Self, Cid)>,
     ) -> std::io::Result<()> {
@@ -299,6 +325,13 @@ impl Protocols {
                 _ = &mut end_receiver => None,
             } {
                 let remote_addr = connecting.remote_address();
+                let admission = match admission.admit(remote_addr.ip()) {
+                    Ok(guard) => guard,
+                    Err(rejection) => {
+                        trace!(?remote_addr, ?rejection, "Rejecting Quic connection attempt");
+                        continue;
+                    },
+                };
                 let connection = match connecting.await {
                     Ok(c) => c,
                     Err(e) => {
@@ -310,7 +343,7 @@ impl Protocols {
                 let cid = cids.fetch_add(1, Ordering::Relaxed);
                 info!(?remote_addr, ?cid, "Accepting Quic from");
                 let metrics = ProtocolMetricCache::new(&cid.to_string(), Arc::clone(&metrics));
-                match Protocols::new_quic(connection, true, metrics).await {
+                match Protocols::new_quic(connection, true, metrics, Some(admission)).await {
                     Ok(quic) => {
                         let _ = c2s_protocol_s.send((quic, cid));
                     },
@@ -329,6 +362,7 @@ impl Protocols {
         mut connection: quinn::NewConnection,
         listen: bool,
         metrics: ProtocolMetricCache,
+        admission: Option<AdmissionGuard>,
     ) -> Result<Self, quinn::ConnectionError> {
         let (sendstream, recvstream) = if listen {
             connection.connection.open_bi().await?
@@ -349,6 +383,7 @@ impl Protocols {
                 reliables: HashMap::new(),
                 recvstreams_s: streams_s_clone,
                 sendstreams_r,
+                _admission: admission,
             },
             metrics.clone(),
         );
@@ -366,6 +401,24 @@ impl Protocols {
         Ok(Protocols::Quic((sp, rp)))
     }
 
+    pub(crate) fn metrics(&self) -> &ProtocolMetricCache {
+        match self {
+            Protocols::Tcp((s, _)) => s.metrics(),
+            Protocols::Mpsc((s, _)) => s.metrics(),
+            #[cfg(feature = "quic")]
+            Protocols::Quic((s, _)) => s.metrics(),
+        }
+    }
+
+    /// DER encoded TLS certificate the remote presented, only QUIC has one
+    pub(crate) fn peer_certificate(&self) -> Option<Vec<u8>> {
+        match self {
+            #[cfg(feature = "quic")]
+            Protocols::Quic((s, _)) => s.drain().peer_certificate(),
+            _ => None,
+        }
+    }
+
     pub(crate) fn split(self) -> (SendProtocols, RecvProtocols) {
         match self {
             Protocols::Tcp((s, r)) => (SendProtocols::Tcp(s), RecvProtocols::Tcp(r)),
@@ -378,18 +431,33 @@ impl Protocols {
 
 #[async_trait]
 impl network_protocol::InitProtocol for Protocols {
-    async fn initialize(
+    async fn initialize_verified(
         &mut self,
         initializer: bool,
         local_pid: Pid,
         secret: u128,
+        verify: &(dyn Fn(Pid, u128) -> bool + Sync),
     ) -> Result<(Pid, Sid, u128), InitProtocolError> {
-        match self {
-            Protocols::Tcp(p) => p.initialize(initializer, local_pid, secret).await,
-            Protocols::Mpsc(p) => p.initialize(initializer, local_pid, secret).await,
+        let start = Instant::now();
+        let result = match self {
+            Protocols::Tcp(p) => {
+                p.initialize_verified(initializer, local_pid, secret, verify)
+                    .await
+            },
+            Protocols::Mpsc(p) => {
+                p.initialize_verified(initializer, local_pid, secret, verify)
+                    .await
+            },
             #[cfg(feature = "quic")]
-            Protocols::Quic(p) => p.initialize(initializer, local_pid, secret).await,
+            Protocols::Quic(p) => {
+                p.initialize_verified(initializer, local_pid, secret, verify)
+                    .await
+            },
+        };
+        if result.is_ok() {
+            self.metrics().handshake_done(start.elapsed());
         }
+        result
     }
 }
 
@@ -444,6 +512,8 @@ impl network_protocol::RecvProtocol for RecvProtocols {
 #[derive(Debug)]
 pub struct TcpDrain {
     half: OwnedWriteHalf,
+    /// keeps the connection counted by the admission policy while open
+    _admission: Option<AdmissionGuard>,
 }
 
 #[derive(Debug)]
@@ -529,6 +599,16 @@ pub struct QuicDrain {
     reliables: HashMap<Sid, quinn::SendStream>,
     recvstreams_s: mpsc::UnboundedSender<QuicStream>,
     sendstreams_r: mpsc::UnboundedReceiver<quinn::SendStream>,
+    _admission: Option<AdmissionGuard>,
+}
+
+#[cfg(feature = "quic")]
+impl QuicDrain {
+    fn peer_certificate(&self) -> Option<Vec<u8>> {
+        let identity = self.con.peer_identity()?;
+        let certificates = identity.downcast::<Vec<rustls::Certificate>>().ok()?;
+        certificates.first().map(|certificate| certificate.0.clone())
+    }
 }
 
 #[cfg(feature = "quic")]
@@ -682,8 +762,8 @@ mod tests {
         let client = TcpStream::connect("127.0.0.1:5000").await.unwrap();
         let (_listener, server) = r1.await.unwrap();
         let metrics = ProtocolMetricCache::new("0", Arc::new(ProtocolMetrics::new().unwrap()));
-        let client = Protocols::new_tcp(client, metrics.clone());
-        let server = Protocols::new_tcp(server, metrics);
+        let client = Protocols::new_tcp(client, metrics.clone(), None);
+        let server = Protocols::new_tcp(server, metrics, None);
         let (mut s, _) = client.split();
         let (_, mut r) = server.split();
         let event = ProtocolEvent::OpenStream {
@@ -731,8 +811,8 @@ mod tests {
         let client = TcpStream::connect("127.0.0.1:5001").await.unwrap();
         let (_listener, server) = r1.await.unwrap();
         let metrics = ProtocolMetricCache::new("0", Arc::new(ProtocolMetrics::new().unwrap()));
-        let client = Protocols::new_tcp(client, metrics.clone());
-        let server = Protocols::new_tcp(server, metrics);
+        let client = Protocols::new_tcp(client, metrics.clone(), None);
+        let server = Protocols::new_tcp(server, metrics, None);
         let (s, _) = client.split();
         let (_, mut r) = server.split();
         let e = tokio::spawn(async move { r.recv().await });
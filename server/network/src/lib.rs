@@ -98,7 +98,9 @@
 //! [`ConnectAddr`]: crate::api::ConnectAddr
 //! [`Promises`]: network_protocol::Promises
 
+mod admission;
 mod api;
+mod auth;
 mod channel;
 mod message;
 mod metrics;
@@ -106,9 +108,11 @@ mod participant;
 mod scheduler;
 mod util;
 
+pub use admission::AdmissionPolicy;
+pub use auth::{AuthProvider, CertificateSecret, StaticSecret, TokenSecret};
 pub use api::{
     ConnectAddr, ListenAddr, Network, NetworkConnectError, NetworkError, Participant,
     ParticipantError, Stream, StreamError, StreamParams,
 };
 pub use message::Message;
-pub use network_protocol::{InitProtocolError, Pid, Promises};
+pub use network_protocol::{ConnectionQuality, InitProtocolError, Pid, Promises};
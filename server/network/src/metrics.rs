@@ -2,7 +2,7 @@ use crate::api::{ConnectAddr, ListenAddr};
 use network_protocol::{Cid, Pid};
 #[cfg(feature = "metrics")]
 use prometheus::{IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry};
-use std::{error::Error, net::SocketAddr};
+use std::{error::Error, net::SocketAddr, time::Duration};
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub(crate) enum ProtocolInfo {
@@ -32,12 +32,16 @@ pub struct NetworkMetrics {
     pub connect_requests_total: IntCounterVec,
     pub incoming_connections_total: IntCounterVec,
     pub failed_handshakes_total: IntCounter,
+    // connect attempts refused by the admission policy, seperated by REASON
+    pub rejected_connections_total: IntCounterVec,
     pub participants_connected_total: IntCounter,
     pub participants_disconnected_total: IntCounter,
     // channel id's, seperated by PARTICIPANT, max 5
     pub participants_channel_ids: IntGaugeVec,
     // upload to remote, averaged, seperated by PARTICIPANT
     pub participants_bandwidth: IntGaugeVec,
+    // round trip time in ms, seperated by PARTICIPANT
+    pub participants_ping: IntGaugeVec,
     // opened Channels, seperated by PARTICIPANT
     pub channels_connected_total: IntCounterVec,
     pub channels_disconnected_total: IntCounterVec,
@@ -78,6 +82,13 @@ impl NetworkMetrics {
             "failed_handshakes_total",
             "Shows the number of failed handshakes",
         ))?;
+        let rejected_connections_total = IntCounterVec::new(
+            Opts::new(
+                "rejected_connections_total",
+                "Shows the number of connection attempts refused by the admission policy",
+            ),
+            &["reason"],
+        )?;
         let participants_connected_total = IntCounter::with_opts(Opts::new(
             "participants_connected_total",
             "Shows the number of participants connected to the network",
@@ -100,6 +111,13 @@ impl NetworkMetrics {
             ),
             &["participant"],
         )?;
+        let participants_ping = IntGaugeVec::new(
+            Opts::new(
+                "participants_ping",
+                "smoothed round trip time to Participant in ms",
+            ),
+            &["participant"],
+        )?;
         let channels_connected_total = IntCounterVec::new(
             Opts::new(
                 "channels_connected_total",
@@ -146,10 +164,12 @@ impl NetworkMetrics {
             connect_requests_total,
             incoming_connections_total,
             failed_handshakes_total,
+            rejected_connections_total,
             participants_connected_total,
             participants_disconnected_total,
             participants_channel_ids,
             participants_bandwidth,
+            participants_ping,
             channels_connected_total,
             channels_disconnected_total,
             streams_opened_total,
@@ -163,10 +183,12 @@ impl NetworkMetrics {
         registry.register(Box::new(self.connect_requests_total.clone()))?;
         registry.register(Box::new(self.incoming_connections_total.clone()))?;
         registry.register(Box::new(self.failed_handshakes_total.clone()))?;
+        registry.register(Box::new(self.rejected_connections_total.clone()))?;
         registry.register(Box::new(self.participants_connected_total.clone()))?;
         registry.register(Box::new(self.participants_disconnected_total.clone()))?;
         registry.register(Box::new(self.participants_channel_ids.clone()))?;
         registry.register(Box::new(self.participants_bandwidth.clone()))?;
+        registry.register(Box::new(self.participants_ping.clone()))?;
         registry.register(Box::new(self.channels_connected_total.clone()))?;
         registry.register(Box::new(self.channels_disconnected_total.clone()))?;
         registry.register(Box::new(self.streams_opened_total.clone()))?;
@@ -189,6 +211,12 @@ impl NetworkMetrics {
             .set(cid as i64);
     }
 
+    pub(crate) fn connection_rejected(&self, reason: &str) {
+        self.rejected_connections_total
+            .with_label_values(&[reason])
+            .inc();
+    }
+
     pub(crate) fn channels_disconnected(&self, remote_p: &str) {
         self.channels_disconnected_total
             .with_label_values(&[remote_p])
@@ -201,6 +229,12 @@ impl NetworkMetrics {
             .set(bandwidth as i64);
     }
 
+    pub(crate) fn participant_ping(&self, remote_p: &str, rtt: Duration) {
+        self.participants_ping
+            .with_label_values(&[remote_p])
+            .set(rtt.as_millis() as i64);
+    }
+
     pub(crate) fn streams_opened(&self, remote_p: &str) {
         self.streams_opened_total
             .with_label_values(&[remote_p])
@@ -238,6 +272,7 @@ impl NetworkMetrics {
             .channels_disconnected_total
             .remove_label_values(&[remote_p]);
         let _ = self.participants_bandwidth.remove_label_values(&[remote_p]);
+        let _ = self.participants_ping.remove_label_values(&[remote_p]);
         let _ = self.streams_opened_total.remove_label_values(&[remote_p]);
         let _ = self.streams_closed_total.remove_label_values(&[remote_p]);
     }
@@ -271,10 +306,14 @@ impl NetworkMetrics {
 
     pub(crate) fn channels_connected(&self, _remote_p: &str, _no: usize, _cid: Cid) {}
 
+    pub(crate) fn connection_rejected(&self, _reason: &str) {}
+
     pub(crate) fn channels_disconnected(&self, _remote_p: &str) {}
 
     pub(crate) fn participant_bandwidth(&self, _remote_p: &str, _bandwidth: f32) {}
 
+    pub(crate) fn participant_ping(&self, _remote_p: &str, _rtt: Duration) {}
+
     pub(crate) fn streams_opened(&self, _remote_p: &str) {}
 
     pub(crate) fn streams_closed(&self, _remote_p: &str) {}
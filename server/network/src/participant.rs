@@ -8,8 +8,8 @@ use bytes::Bytes;
 use futures_util::{FutureExt, StreamExt};
 use hashbrown::HashMap;
 use network_protocol::{
-    Bandwidth, Cid, Pid, Prio, Promises, ProtocolEvent, RecvProtocol, SendProtocol, Sid,
-    _internal::SortedVec,
+    Bandwidth, Cid, ConnectionQuality, Pid, Prio, Promises, ProtocolEvent, QualityEstimator,
+    RecvProtocol, SendProtocol, Sid, _internal::SortedVec,
 };
 use std::{
     sync::{
@@ -54,6 +54,7 @@ struct ControlChannels {
     b2a_stream_opened_s: mpsc::UnboundedSender<Stream>,
     s2b_create_channel_r: mpsc::UnboundedReceiver<S2bCreateChannel>,
     b2a_bandwidth_stats_s: watch::Sender<f32>,
+    b2a_quality_s: watch::Sender<ConnectionQuality>,
     s2b_shutdown_bparticipant_r: oneshot::Receiver<S2bShutdownBparticipant>, /* own */
 }
 
@@ -97,18 +98,21 @@ impl BParticipant {
         mpsc::UnboundedSender<S2bCreateChannel>,
         oneshot::Sender<S2bShutdownBparticipant>,
         watch::Receiver<f32>,
+        watch::Receiver<ConnectionQuality>,
     ) {
         let (a2b_open_stream_s, a2b_open_stream_r) = mpsc::unbounded_channel::<A2bStreamOpen>();
         let (b2a_stream_opened_s, b2a_stream_opened_r) = mpsc::unbounded_channel::<Stream>();
         let (s2b_shutdown_bparticipant_s, s2b_shutdown_bparticipant_r) = oneshot::channel();
         let (s2b_create_channel_s, s2b_create_channel_r) = mpsc::unbounded_channel();
         let (b2a_bandwidth_stats_s, b2a_bandwidth_stats_r) = watch::channel::<f32>(0.0);
+        let (b2a_quality_s, b2a_quality_r) = watch::channel(ConnectionQuality::default());
 
         let run_channels = Some(ControlChannels {
             a2b_open_stream_r,
             b2a_stream_opened_s,
             s2b_create_channel_r,
             b2a_bandwidth_stats_s,
+            b2a_quality_s,
             s2b_shutdown_bparticipant_r,
         });
 
@@ -132,6 +136,7 @@ impl BParticipant {
             s2b_create_channel_s,
             s2b_shutdown_bparticipant_s,
             b2a_bandwidth_stats_r,
+            b2a_quality_r,
         )
     }
 
@@ -148,6 +153,10 @@ impl BParticipant {
             crossbeam_channel::unbounded::<(Cid, Sid, Prio, Promises, u64)>();
         let (b2b_notify_send_of_recv_close_s, b2b_notify_send_of_recv_close_r) =
             crossbeam_channel::unbounded::<(Cid, Sid)>();
+        let (b2b_notify_send_of_recv_ping_s, b2b_notify_send_of_recv_ping_r) =
+            crossbeam_channel::unbounded::<(Cid, u64)>();
+        let (b2b_notify_send_of_recv_pong_s, b2b_notify_send_of_recv_pong_r) =
+            crossbeam_channel::unbounded::<(u64, Instant)>();
 
         let (a2b_close_stream_s, a2b_close_stream_r) = mpsc::unbounded_channel::<Sid>();
         let (a2b_msg_s, a2b_msg_r) = crossbeam_channel::unbounded::<(Sid, Bytes)>();
@@ -167,8 +176,11 @@ impl BParticipant {
                 b2b_close_send_protocol_r,
                 b2b_notify_send_of_recv_open_r,
                 b2b_notify_send_of_recv_close_r,
+                b2b_notify_send_of_recv_ping_r,
+                b2b_notify_send_of_recv_pong_r,
                 b2s_prio_statistic_s,
                 run_channels.b2a_bandwidth_stats_s,
+                run_channels.b2a_quality_s,
             )
             .instrument(tracing::info_span!("send")),
             self.recv_mgr(
@@ -178,6 +190,8 @@ impl BParticipant {
                 b2b_close_send_protocol_s.clone(),
                 b2b_notify_send_of_recv_open_s,
                 b2b_notify_send_of_recv_close_s,
+                b2b_notify_send_of_recv_ping_s,
+                b2b_notify_send_of_recv_pong_s,
             )
             .instrument(tracing::info_span!("recv")),
             self.create_channel_mgr(
@@ -237,8 +251,11 @@ impl BParticipant {
             Bandwidth,
         )>,
         b2b_notify_send_of_recv_close_r: crossbeam_channel::Receiver<(Cid, Sid)>,
+        b2b_notify_send_of_recv_ping_r: crossbeam_channel::Receiver<(Cid, u64)>,
+        b2b_notify_send_of_recv_pong_r: crossbeam_channel::Receiver<(u64, Instant)>,
         _b2s_prio_statistic_s: mpsc::UnboundedSender<B2sPrioStatistic>,
         b2a_bandwidth_stats_s: watch::Sender<f32>,
+        b2a_quality_s: watch::Sender<ConnectionQuality>,
     ) {
         let mut sorted_send_protocols = SortedVec::<Cid, SendProtocols>::default();
         let mut sorted_stream_protocols = SortedVec::<Sid, Cid>::default();
@@ -246,6 +263,7 @@ impl BParticipant {
         let mut last_instant = Instant::now();
         let mut stream_ids = self.offset_sid;
         let mut part_bandwidth = 0.0f32;
+        let mut quality = QualityEstimator::new();
         trace!("workaround, actively wait for first protocol");
         if let Some((c, p)) = b2b_add_protocol_r.recv().await {
             sorted_send_protocols.insert(c, p)
@@ -342,6 +360,30 @@ impl BParticipant {
                     };
                 }
 
+                // keepalive, answer the remote on the channel it pinged and measure our own
+                for (c, nonce) in b2b_notify_send_of_recv_ping_r.try_iter() {
+                    if let Some(p) = sorted_send_protocols.get_mut(&c) {
+                        cid = c;
+                        p.send(ProtocolEvent::Pong { nonce }).await?;
+                    }
+                }
+                for (nonce, received) in b2b_notify_send_of_recv_pong_r.try_iter() {
+                    quality.pong(nonce, received);
+                }
+                if let Some(nonce) = quality.ping(Instant::now()) {
+                    cid = Self::best_protocol(&sorted_send_protocols, Promises::empty())
+                        .unwrap();
+                    sorted_send_protocols
+                        .get_mut(&cid)
+                        .unwrap()
+                        .send(ProtocolEvent::Ping { nonce })
+                        .await?;
+                    let quality = quality.quality();
+                    self.metrics
+                        .participant_ping(&self.remote_pid_string, quality.rtt);
+                    let _ = b2a_quality_s.send(quality);
+                }
+
                 if let Some(sid) = close {
                     trace!(?stream_ids, "delete stream");
                     self.delete_stream(sid).await;
@@ -362,9 +404,11 @@ impl BParticipant {
                 let diff = send_time.duration_since(last_instant);
                 last_instant = send_time;
                 let mut cnt = 0;
+                // back off while pings get lost
+                let bandwidth = quality.quality().send_bandwidth(part_bandwidth);
                 for (c, p) in sorted_send_protocols.data.iter_mut() {
                     cid = *c;
-                    cnt += p.flush(1_000_000_000, diff).await?; //this actually blocks, so we cant set streams while it.
+                    cnt += p.flush(bandwidth, diff).await?; //this actually blocks, so we cant set streams while it.
                 }
                 let flush_time = send_time.elapsed().as_secs_f32();
                 part_bandwidth = 0.99 * part_bandwidth + 0.01 * (cnt as f32 / flush_time);
@@ -425,6 +469,8 @@ impl BParticipant {
             Bandwidth,
         )>,
         b2b_notify_send_of_recv_close_s: crossbeam_channel::Sender<(Cid, Sid)>,
+        b2b_notify_send_of_recv_ping_s: crossbeam_channel::Sender<(Cid, u64)>,
+        b2b_notify_send_of_recv_pong_s: crossbeam_channel::Sender<(u64, Instant)>,
     ) {
         let mut recv_protocols: HashMap<Cid, JoinHandle<()>> = HashMap::new();
         // we should be able to directly await futures imo
@@ -515,6 +561,14 @@ impl BParticipant {
                         };
                         retrigger(cid, p, &mut recv_protocols);
                     },
+                    Ok(ProtocolEvent::Ping { nonce }) => {
+                        let _ = b2b_notify_send_of_recv_ping_s.send((cid, nonce));
+                        retrigger(cid, p, &mut recv_protocols);
+                    },
+                    Ok(ProtocolEvent::Pong { nonce }) => {
+                        let _ = b2b_notify_send_of_recv_pong_s.send((nonce, Instant::now()));
+                        retrigger(cid, p, &mut recv_protocols);
+                    },
                     Ok(ProtocolEvent::Shutdown) => {
                         info!(?cid, "shutdown protocol");
                         if let Err(e) = b2b_close_send_protocol_s.send(cid).await {
@@ -787,6 +841,7 @@ mod tests {
         oneshot::Sender<S2bShutdownBparticipant>,
         mpsc::UnboundedReceiver<B2sPrioStatistic>,
         watch::Receiver<f32>,
+        watch::Receiver<ConnectionQuality>,
         JoinHandle<()>,
     ) {
         let runtime = Arc::new(tokio::runtime::Runtime::new().unwrap());
@@ -802,6 +857,7 @@ mod tests {
             s2b_create_channel_s,
             s2b_shutdown_bparticipant_s,
             b2a_bandwidth_stats_r,
+            b2a_quality_r,
         ) = runtime_clone.block_on(async move {
             let local_pid = Pid::fake(0);
             let remote_pid = Pid::fake(1);
@@ -820,6 +876,7 @@ mod tests {
             s2b_shutdown_bparticipant_s,
             b2s_prio_statistic_r,
             b2a_bandwidth_stats_r,
+            b2a_quality_r,
             handle,
         )
     }
@@ -853,6 +910,7 @@ mod tests {
             s2b_shutdown_bparticipant_s,
             b2s_prio_statistic_r,
             _b2a_bandwidth_stats_r,
+            _b2a_quality_r,
             handle,
         ) = mock_bparticipant();
 
@@ -889,6 +947,7 @@ mod tests {
             s2b_shutdown_bparticipant_s,
             b2s_prio_statistic_r,
             _b2a_bandwidth_stats_r,
+            _b2a_quality_r,
             handle,
         ) = mock_bparticipant();
 
@@ -926,6 +985,7 @@ mod tests {
             s2b_shutdown_bparticipant_s,
             b2s_prio_statistic_r,
             _b2a_bandwidth_stats_r,
+            _b2a_quality_r,
             handle,
         ) = mock_bparticipant();
 
@@ -939,7 +999,13 @@ mod tests {
             .send((7u8, Promises::ENCRYPTED, 1_000_000, stream_sender))
             .unwrap();
 
-        let stream_event = runtime.block_on(rr.recv()).unwrap();
+        // skip keepalive pings
+        let stream_event = loop {
+            match runtime.block_on(rr.recv()).unwrap() {
+                ProtocolEvent::Ping { .. } => continue,
+                event => break event,
+            }
+        };
         match stream_event {
             ProtocolEvent::OpenStream {
                 sid,
@@ -981,6 +1047,7 @@ mod tests {
             s2b_shutdown_bparticipant_s,
             b2s_prio_statistic_r,
             _b2a_bandwidth_stats_r,
+            _b2a_quality_r,
             handle,
         ) = mock_bparticipant();
 
@@ -1016,4 +1083,50 @@ mod tests {
         drop((a2b_open_stream_s, b2a_stream_opened_r, b2s_prio_statistic_r));
         drop(runtime);
     }
+
+    #[test]
+    fn answer_ping() {
+        let (
+            runtime,
+            a2b_open_stream_s,
+            b2a_stream_opened_r,
+            mut s2b_create_channel_s,
+            s2b_shutdown_bparticipant_s,
+            b2s_prio_statistic_r,
+            _b2a_bandwidth_stats_r,
+            _b2a_quality_r,
+            handle,
+        ) = mock_bparticipant();
+
+        let remote = runtime.block_on(mock_mpsc(0, &runtime, &mut s2b_create_channel_s));
+        std::thread::sleep(Duration::from_millis(50));
+
+        let (mut rs, mut rr) = remote.split();
+        runtime
+            .block_on(rs.send(ProtocolEvent::Ping { nonce: 42 }))
+            .unwrap();
+        let nonce = loop {
+            match runtime.block_on(rr.recv()).unwrap() {
+                ProtocolEvent::Pong { nonce } => break nonce,
+                ProtocolEvent::Ping { .. } => continue,
+                _ => panic!("wrong event"),
+            }
+        };
+        assert_eq!(nonce, 42);
+
+        let (s, r) = oneshot::channel();
+        runtime.block_on(async {
+            drop(s2b_create_channel_s);
+            s2b_shutdown_bparticipant_s
+                .send((Duration::from_secs(1), s))
+                .unwrap();
+            drop((rs, rr));
+            r.await.unwrap().unwrap();
+        });
+
+        runtime.block_on(handle).unwrap();
+
+        drop((a2b_open_stream_s, b2a_stream_opened_r, b2s_prio_statistic_r));
+        drop(runtime);
+    }
 }
@@ -1,5 +1,7 @@
 use crate::{
+    admission::Admission,
     api::{ConnectAddr, ListenAddr, NetworkConnectError, Participant},
+    auth::AuthProvider,
     channel::Protocols,
     metrics::{NetworkMetrics, ProtocolInfo},
     participant::{B2sPrioStatistic, BParticipant, S2bCreateChannel, S2bShutdownBparticipant},
@@ -9,7 +11,6 @@ use hashbrown::HashMap;
 use network_protocol::{Cid, Pid, ProtocolMetricCache, ProtocolMetrics};
 #[cfg(feature = "metrics")]
 use prometheus::Registry;
-use rand::Rng;
 use std::{
     sync::{
         atomic::{AtomicBool, AtomicU64, Ordering},
@@ -67,7 +68,7 @@ struct ParticipantChannels {
 #[derive(Debug)]
 pub struct Scheduler {
     local_pid: Pid,
-    local_secret: u128,
+    auth: Arc<dyn AuthProvider>,
     closed: AtomicBool,
     run_channels: Option<ControlChannels>,
     participant_channels: Arc<Mutex<Option<ParticipantChannels>>>,
@@ -76,11 +77,13 @@ pub struct Scheduler {
     channel_listener: Mutex<HashMap<ProtocolInfo, oneshot::Sender<()>>>,
     metrics: Arc<NetworkMetrics>,
     protocol_metrics: Arc<ProtocolMetrics>,
+    admission: Arc<Admission>,
 }
 
 impl Scheduler {
     pub fn new(
         local_pid: Pid,
+        auth: Arc<dyn AuthProvider>,
         #[cfg(feature = "metrics")] registry: Option<&Registry>,
     ) -> (
         Self,
@@ -88,6 +91,7 @@ impl Scheduler {
         mpsc::UnboundedSender<A2sConnect>,
         mpsc::UnboundedReceiver<Participant>,
         oneshot::Sender<()>,
+        Arc<Admission>,
     ) {
         let (a2s_listen_s, a2s_listen_r) = mpsc::unbounded_channel::<A2sListen>();
         let (a2s_connect_s, a2s_connect_r) = mpsc::unbounded_channel::<A2sConnect>();
@@ -122,13 +126,12 @@ impl Scheduler {
             }
         }
 
-        let mut rng = rand::thread_rng();
-        let local_secret: u128 = rng.gen();
+        let admission = Arc::new(Admission::new(Arc::clone(&metrics)));
 
         (
             Self {
                 local_pid,
-                local_secret,
+                auth,
                 closed: AtomicBool::new(false),
                 run_channels,
                 participant_channels: Arc::new(Mutex::new(Some(participant_channels))),
@@ -137,11 +140,13 @@ impl Scheduler {
                 channel_listener: Mutex::new(HashMap::new()),
                 metrics,
                 protocol_metrics,
+                admission: Arc::clone(&admission),
             },
             a2s_listen_s,
             a2s_connect_s,
             s2a_connected_r,
             a2s_scheduler_shutdown_s,
+            admission,
         )
     }
 
@@ -176,6 +181,7 @@ impl Scheduler {
                 let (s2s_stop_listening_s, s2s_stop_listening_r) = oneshot::channel::<()>();
                 let (c2s_protocol_s, mut c2s_protocol_r) = mpsc::unbounded_channel();
                 let metrics = Arc::clone(&self.protocol_metrics);
+                let admission = Arc::clone(&self.admission);
 
                 async move {
                     self.channel_listener
@@ -192,6 +198,7 @@ impl Scheduler {
                                 addr,
                                 cids,
                                 metrics,
+                                admission,
                                 s2s_stop_listening_r,
                                 c2s_protocol_s,
                             )
@@ -204,6 +211,7 @@ impl Scheduler {
                                 server_config.clone(),
                                 cids,
                                 metrics,
+                                admission,
                                 s2s_stop_listening_r,
                                 c2s_protocol_s,
                             )
@@ -393,14 +401,28 @@ impl Scheduler {
         let participants = Arc::clone(&self.participants);
         let metrics = Arc::clone(&self.metrics);
         let local_pid = self.local_pid;
-        let local_secret = self.local_secret;
+        let auth = Arc::clone(&self.auth);
         // this is necessary for UDP to work at all and to remove code duplication
         tokio::spawn(
             async move {
                 trace!(?cid, "Open channel and be ready for Handshake");
                 use network_protocol::InitProtocol;
+                let peer_certificate = protocol.peer_certificate();
+                // when accepting, a remote is checked before it gets our secret
+                let verify = |pid: Pid, secret: u128| {
+                    let accepted = auth.verify_channel(pid, secret, peer_certificate.as_deref());
+                    if !accepted {
+                        info!(?cid, ?pid, "Remote secret was rejected by the auth provider");
+                    }
+                    accepted
+                };
                 let init_result = protocol
-                    .initialize(send_handshake, local_pid, local_secret)
+                    .initialize_verified(
+                        send_handshake,
+                        local_pid,
+                        auth.local_secret(local_pid),
+                        &verify,
+                    )
                     .instrument(tracing::info_span!("handshake", ?cid))
                     .await;
                 match init_result {
@@ -411,7 +433,18 @@ impl Scheduler {
                             "Detected that my channel is ready!, activating it :)"
                         );
                         let mut participants = participants.lock().await;
-                        if !participants.contains_key(&pid) {
+                        if !participants.contains_key(&pid)
+                            && !auth.verify_channel(pid, secret, peer_certificate.as_deref())
+                        {
+                            drop(participants);
+                            info!(?cid, ?pid, "Remote secret was rejected by the auth provider");
+                            #[cfg(feature = "metrics")]
+                            metrics.failed_handshakes_total.inc();
+                            if let Some(pid_oneshot) = s2a_return_pid_s {
+                                // someone is waiting with `connect`, so give them their Error
+                                let _ = pid_oneshot.send(Err(NetworkConnectError::InvalidSecret));
+                            }
+                        } else if !participants.contains_key(&pid) {
                             debug!(?cid, "New participant connected via a channel");
                             let (
                                 bparticipant,
@@ -420,6 +453,7 @@ impl Scheduler {
                                 s2b_create_channel_s,
                                 s2b_shutdown_bparticipant_s,
                                 b2a_bandwidth_stats_r,
+                                b2a_quality_r,
                             ) = BParticipant::new(local_pid, pid, sid, Arc::clone(&metrics));
 
                             let participant = Participant::new(
@@ -428,6 +462,7 @@ impl Scheduler {
                                 a2b_open_stream_s,
                                 b2a_stream_opened_r,
                                 b2a_bandwidth_stats_r,
+                                b2a_quality_r,
                                 participant_channels.a2s_disconnect_s,
                             );
 
@@ -4,7 +4,10 @@ use veloren_network::{NetworkError, StreamError};
 mod helper;
 use helper::{mpsc, network_participant_stream, quic, tcp, udp, SLEEP_EXTERNAL, SLEEP_INTERNAL};
 use std::io::ErrorKind;
-use veloren_network::{ConnectAddr, ListenAddr, Network, Pid, Promises};
+use veloren_network::{
+    ConnectAddr, InitProtocolError, ListenAddr, Network, NetworkConnectError, Pid, Promises,
+    TokenSecret,
+};
 
 #[test]
 fn stream_simple() {
@@ -307,3 +310,20 @@ fn listen_on_ipv6_doesnt_block_ipv4() {
     drop((s1_a, s1_b, _n_a, _n_b, _p_a, _p_b));
     drop((s1_a2, s1_b2, _n_a2, _n_b2, _p_a2, _p_b2)); //clean teardown
 }
+
+#[test]
+fn wrong_token_gets_no_secret() {
+    let (_, _) = helper::setup(false, 0);
+    let r = Arc::new(Runtime::new().unwrap());
+    let server = Network::new_with_auth(Pid::fake(0), &r, Arc::new(TokenSecret::new("hunter2")));
+    let stranger = Network::new_with_auth(Pid::fake(1), &r, Arc::new(TokenSecret::new("hunter3")));
+    let friend = Network::new_with_auth(Pid::fake(2), &r, Arc::new(TokenSecret::new("hunter2")));
+    let (listen, connect) = tcp();
+    r.block_on(server.listen(listen)).unwrap();
+    // the server closes before sending its own secret, so there is nothing to reuse
+    assert!(matches!(
+        r.block_on(stranger.connect(connect.clone())),
+        Err(NetworkConnectError::Handshake(InitProtocolError::Closed))
+    ));
+    assert!(r.block_on(friend.connect(connect)).is_ok());
+}
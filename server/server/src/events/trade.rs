@@ -1,13 +1,16 @@
-use crate::Server;
+use crate::{restock::Restock, Server};
 use common::{
     comp::{
         agent::{Agent, AgentEvent},
         inventory::{
             item::{tool::AbilityMap, MaterialStatManifest},
+            trade_pricing::TradePricing,
             Inventory,
         },
     },
-    trade::{PendingTrade, ReducedInventory, TradeAction, TradeId, TradeResult, Trades},
+    trade::{
+        Good, PendingTrade, ReducedInventory, SiteId, TradeAction, TradeId, TradeResult, Trades,
+    },
 };
 use common_net::{
     msg::ServerGeneral,
@@ -91,7 +94,14 @@ pub fn handle_process_trade_action(
             if let Entry::Occupied(entry) = trades.trades.entry(trade_id) {
                 let parties = entry.get().parties;
                 if entry.get().should_commit() {
+                    let sales = merchant_sales(server.state.ecs(), entry.get());
                     let result = commit_trade(server.state.ecs(), entry.get());
+                    if let TradeResult::Completed = result {
+                        let mut restock = server.state.ecs().write_resource::<Restock>();
+                        for (site, good, amount) in sales {
+                            restock.record_sale(site, good, amount);
+                        }
+                    }
                     entry.remove();
                     for party in parties.iter() {
                         if let Some(e) = server.state.ecs().entity_from_uid(party.0) {
@@ -190,6 +200,31 @@ pub fn cancel_trade_for(server: &mut Server, entity: EcsEntity) {
     }
 }
 
+/// Goods given away by merchants in a trade, by the site they trade for
+fn merchant_sales(ecs: &specs::World, trade: &PendingTrade) -> Vec<(SiteId, Good, f32)> {
+    let agents = ecs.read_storage::<Agent>();
+    let inventories = ecs.read_storage::<Inventory>();
+    let mut sales = Vec::new();
+    for (who, party) in trade.parties.iter().enumerate() {
+        let entity = match ecs.entity_from_uid(party.0) {
+            Some(entity) => entity,
+            None => continue,
+        };
+        let site = agents.get(entity).and_then(|agent| agent.behavior.trade_site);
+        if let (Some(site), Some(inventory)) = (site, inventories.get(entity)) {
+            for (slot, amount) in trade.offers[who].iter() {
+                let material = inventory
+                    .get(*slot)
+                    .and_then(|item| TradePricing::get_material(item.item_definition_id()));
+                if let Some((good, factor)) = material {
+                    sales.push((site, good, factor * *amount as f32));
+                }
+            }
+        }
+    }
+    sales
+}
+
 /// Commit a trade that both parties have agreed to, modifying their respective
 /// inventories
 fn commit_trade(ecs: &specs::World, trade: &PendingTrade) -> TradeResult {
@@ -24,11 +24,13 @@ pub mod error;
 pub mod events;
 pub mod input;
 pub mod location;
+pub mod login_challenge;
 pub mod login_provider;
 pub mod metrics;
 pub mod persistence;
 mod pet;
 pub mod presence;
+pub mod restock;
 pub mod rtsim;
 pub mod settings;
 pub mod state_ext;
@@ -57,6 +59,7 @@ use crate::{
     connection_handler::ConnectionHandler,
     data_dir::DataDir,
     location::Locations,
+    login_challenge::LoginChallenger,
     login_provider::LoginProvider,
     persistence::PersistedComponents,
     presence::{Presence, RegionSubscription, RepositionOnChunkLoad},
@@ -250,6 +253,9 @@ impl Server {
             settings.auth_server_address.clone(),
             Arc::clone(&runtime),
         ));
+        state
+            .ecs_mut()
+            .insert(LoginChallenger::new(&settings.login_challenge));
         state.ecs_mut().insert(HwStats {
             hardware_threads: num_cpus::get() as u32,
             rayon_threads: num_cpus::get() as u32,
@@ -281,6 +287,9 @@ impl Server {
                  compiled with the feature. Terrain modifications will *not* be persisted."
             );
         }
+        state
+            .ecs_mut()
+            .insert(restock::Restock::load(data_dir));
         state
             .ecs_mut()
             .write_resource::<SlowJobPool>()
@@ -319,6 +328,7 @@ impl Server {
         state.ecs_mut().register::<comp::Anchor>();
         state.ecs_mut().register::<comp::Pet>();
         state.ecs_mut().register::<login_provider::PendingLogin>();
+        state.ecs_mut().register::<login_challenge::PendingChallenge>();
         state.ecs_mut().register::<RepositionOnChunkLoad>();
 
         //Alias validator
@@ -352,6 +362,9 @@ impl Server {
         tracing::trace!(?banned_words);
         state.ecs_mut().insert(AliasValidator::new(banned_words));
 
+        // Reuse trade prices computed by previous runs if the assets didn't change
+        comp::inventory::trade_pricing::TradePricing::set_cache_dir(data_dir);
+
         #[cfg(feature = "worldgen")]
         let (world, index) = World::generate(
             settings.world_seed,
@@ -460,6 +473,7 @@ impl Server {
         state.ecs_mut().insert(DeletedEntities::default());
 
         let network = Network::new_with_registry(Pid::new(), &runtime, &registry);
+        network.set_admission_policy(settings.connection_limits.admission_policy());
         let metrics_shutdown = Arc::new(Notify::new());
         let metrics_shutdown_clone = Arc::clone(&metrics_shutdown);
         let addr = settings.metrics_address;
@@ -1402,6 +1416,11 @@ impl Drop for Server {
         self.state
             .notify_players(ServerGeneral::Disconnect(DisconnectReason::Shutdown));
 
+        self.state
+            .ecs()
+            .try_fetch_mut::<restock::Restock>()
+            .map(|mut restock| restock.save());
+
         #[cfg(feature = "persistent_world")]
         self.state
             .ecs()
@@ -0,0 +1,158 @@
+//! Challenges new logins have to solve before they are authenticated, see
+//! [`LoginChallengeMode`]
+use crate::settings::LoginChallengeMode;
+use common_net::msg::{challenge::MAX_PROOF_OF_WORK_DIFFICULTY, ChallengeAnswer, LoginChallenge};
+use rand::{seq::SliceRandom, thread_rng, Rng};
+use specs::Component;
+use specs_idvs::IdvStorage;
+use std::{fs, io, path::Path};
+use tracing::{info, warn};
+
+/// A challenge sent to a client which didn't answer it yet
+pub struct PendingChallenge {
+    challenge: LoginChallenge,
+    /// the text of a captcha
+    text: String,
+}
+
+impl PendingChallenge {
+    pub fn challenge(&self) -> &LoginChallenge { &self.challenge }
+
+    pub fn check(&self, answer: &ChallengeAnswer) -> bool {
+        answer.solves(&self.challenge, &self.text)
+    }
+}
+
+impl Component for PendingChallenge {
+    type Storage = IdvStorage<Self>;
+}
+
+enum Issuer {
+    Disabled,
+    ProofOfWork { difficulty: u8 },
+    /// the text and PNG data of each captcha
+    Captcha(Vec<(String, Vec<u8>)>),
+}
+
+pub struct LoginChallenger {
+    issuer: Issuer,
+}
+
+impl LoginChallenger {
+    pub fn new(mode: &LoginChallengeMode) -> Self {
+        let issuer = match mode {
+            LoginChallengeMode::Disabled => Issuer::Disabled,
+            LoginChallengeMode::ProofOfWork { difficulty } => {
+                if *difficulty > MAX_PROOF_OF_WORK_DIFFICULTY {
+                    warn!(
+                        "Proof of work difficulty {} is more than clients solve, using {}",
+                        difficulty, MAX_PROOF_OF_WORK_DIFFICULTY
+                    );
+                }
+                Issuer::ProofOfWork {
+                    difficulty: (*difficulty).min(MAX_PROOF_OF_WORK_DIFFICULTY),
+                }
+            },
+            LoginChallengeMode::Captcha { directory } => match load_captchas(directory) {
+                Ok(captchas) if !captchas.is_empty() => {
+                    info!(?directory, "Loaded {} captchas", captchas.len());
+                    Issuer::Captcha(captchas)
+                },
+                Ok(_) => {
+                    warn!(?directory, "No captchas found, logins are not challenged");
+                    Issuer::Disabled
+                },
+                Err(e) => {
+                    warn!(?e, ?directory, "Failed to load captchas, logins are not challenged");
+                    Issuer::Disabled
+                },
+            },
+        };
+        Self { issuer }
+    }
+
+    /// A new challenge for a login, `None` if they are disabled
+    pub fn issue(&self) -> Option<PendingChallenge> {
+        let mut rng = thread_rng();
+        match &self.issuer {
+            Issuer::Disabled => None,
+            Issuer::ProofOfWork { difficulty } => Some(PendingChallenge {
+                challenge: LoginChallenge::ProofOfWork {
+                    seed: rng.gen(),
+                    difficulty: *difficulty,
+                },
+                text: String::new(),
+            }),
+            Issuer::Captcha(captchas) => {
+                let (text, image) = captchas.choose(&mut rng)?;
+                Some(PendingChallenge {
+                    challenge: LoginChallenge::Captcha {
+                        image: image.clone(),
+                    },
+                    text: text.clone(),
+                })
+            },
+        }
+    }
+}
+
+/// The PNG files of `directory`, their names without extension are the texts
+fn load_captchas(directory: &Path) -> io::Result<Vec<(String, Vec<u8>)>> {
+    let mut captchas = Vec::new();
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+        if path.extension().map_or(true, |ext| !ext.eq_ignore_ascii_case("png")) {
+            continue;
+        }
+        if let Some(text) = path.file_stem().and_then(|stem| stem.to_str()) {
+            captchas.push((text.to_owned(), fs::read(&path)?));
+        }
+    }
+    Ok(captchas)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common_net::msg::challenge::solve_proof_of_work;
+
+    #[test]
+    fn issued_challenges() {
+        assert!(LoginChallenger::new(&LoginChallengeMode::Disabled)
+            .issue()
+            .is_none());
+
+        let challenger = LoginChallenger::new(&LoginChallengeMode::ProofOfWork { difficulty: 8 });
+        let pending = challenger.issue().unwrap();
+        let nonce = match pending.challenge() {
+            LoginChallenge::ProofOfWork { seed, difficulty } => {
+                solve_proof_of_work(seed, *difficulty).unwrap()
+            },
+            LoginChallenge::Captcha { .. } => panic!("expected a proof of work"),
+        };
+        assert!(pending.check(&ChallengeAnswer::ProofOfWork(nonce)));
+        assert!(!pending.check(&ChallengeAnswer::Captcha(String::new())));
+
+        let challenger = LoginChallenger::new(&LoginChallengeMode::ProofOfWork { difficulty: 99 });
+        assert!(matches!(
+            challenger.issue().unwrap().challenge(),
+            LoginChallenge::ProofOfWork { difficulty, .. }
+                if *difficulty == MAX_PROOF_OF_WORK_DIFFICULTY
+        ));
+
+        let directory = std::env::temp_dir().join("veloren_captcha_test");
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("xK4p.png"), [1, 2, 3]).unwrap();
+        fs::write(directory.join("notes.txt"), "not a captcha").unwrap();
+        let challenger = LoginChallenger::new(&LoginChallengeMode::Captcha {
+            directory: directory.clone(),
+        });
+        let pending = challenger.issue().unwrap();
+        assert_eq!(pending.challenge(), &LoginChallenge::Captcha {
+            image: vec![1, 2, 3]
+        });
+        assert!(pending.check(&ChallengeAnswer::Captcha("XK4P".into())));
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,256 @@
+//! Periodic regeneration of merchant inventories
+//!
+//! Merchants only get their goods once, when they are spawned. Every merchant
+//! profile has a restock interval after which a portion of the inventory is
+//! refilled, preferring the goods which sold well at the merchant's site.
+//! What was sold and how long ago each site was restocked survives restarts.
+
+use atomicwrites::{AtomicFile, OverwriteBehavior};
+use common::trade::{Good, SiteId};
+use hashbrown::HashMap;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+use tracing::{error, info, warn};
+
+const PROFILES_FILE: &str = "restock_profiles.ron";
+const STATE_FILE: &str = "restock_state.ron";
+/// Sales are written at most this often while the server runs, and once more
+/// when it shuts down
+const SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RestockProfile {
+    /// In game seconds between two restocks
+    pub interval: f64,
+    /// Portion of the free inventory slots filled per restock
+    pub portion: f32,
+    /// Goods restocked even if nothing was sold, with their base weight
+    pub goods: Vec<(Good, f32)>,
+    /// Value of the goods bought per restocked slot, higher values allow
+    /// more valuable items
+    pub supply: f32,
+}
+
+impl Default for RestockProfile {
+    fn default() -> Self {
+        Self {
+            interval: 30.0 * 60.0,
+            portion: 0.25,
+            goods: vec![
+                (Good::Armor, 1.0),
+                (Good::Tools, 1.0),
+                (Good::Ingredients, 1.0),
+                (Good::Food, 1.0),
+                (Good::Potions, 0.5),
+            ],
+            supply: 50.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RestockProfiles {
+    pub default: RestockProfile,
+    pub profiles: HashMap<String, RestockProfile>,
+    /// Which profile the merchants of a site use, `default` if absent
+    pub sites: HashMap<SiteId, String>,
+}
+
+impl RestockProfiles {
+    pub fn for_site(&self, site: SiteId) -> &RestockProfile {
+        self.sites
+            .get(&site)
+            .and_then(|name| self.profiles.get(name))
+            .unwrap_or(&self.default)
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct RestockState {
+    sold: HashMap<SiteId, HashMap<Good, f32>>,
+    /// In game seconds since the last restock of each site as of the save,
+    /// `Time` starts over with every server start
+    elapsed: HashMap<SiteId, f64>,
+}
+
+pub struct Restock {
+    path: PathBuf,
+    pub profiles: RestockProfiles,
+    state: RestockState,
+    /// `Time` of the last restock of each site seen in this session
+    last_restock: HashMap<SiteId, f64>,
+    /// Latest `Time` passed in, to turn `last_restock` into elapsed seconds
+    now: f64,
+    last_save: Instant,
+    dirty: bool,
+}
+
+impl Restock {
+    pub fn load(data_dir: &Path) -> Self {
+        let profiles_path = data_dir.join(PROFILES_FILE);
+        let profiles = match fs::read_to_string(&profiles_path) {
+            Ok(content) => ron::de::from_str(&content).unwrap_or_else(|e| {
+                warn!(?e, "Failed to parse restock profiles, using defaults");
+                RestockProfiles::default()
+            }),
+            Err(_) => {
+                let profiles = RestockProfiles::default();
+                if let Err(e) = write_ron(&profiles_path, &profiles) {
+                    warn!(?e, "Failed to write default restock profiles");
+                }
+                profiles
+            },
+        };
+        let path = data_dir.join(STATE_FILE);
+        let state = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| match ron::de::from_str(&content) {
+                Ok(state) => Some(state),
+                Err(e) => {
+                    error!(?e, "Failed to parse restock state, starting fresh");
+                    None
+                },
+            })
+            .unwrap_or_default();
+        info!("Using {:?} for merchant restock state", path);
+
+        Self {
+            path,
+            profiles,
+            state,
+            last_restock: HashMap::new(),
+            now: 0.0,
+            last_save: Instant::now(),
+            dirty: false,
+        }
+    }
+
+    /// Remember that a merchant of `site` sold `amount` units of `good`
+    pub fn record_sale(&mut self, site: SiteId, good: Good, amount: f32) {
+        if amount > 0.0 {
+            *self
+                .state
+                .sold
+                .entry(site)
+                .or_default()
+                .entry(good)
+                .or_default() += amount;
+            self.dirty = true;
+        }
+    }
+
+    /// Whether the merchants of `site` are due, sites never restocked before
+    /// start their interval now
+    pub fn is_due(&mut self, site: SiteId, now: f64) -> bool {
+        self.now = now;
+        let interval = self.profiles.for_site(site).interval;
+        let elapsed = &self.state.elapsed;
+        let last = *self
+            .last_restock
+            .entry(site)
+            .or_insert_with(|| now - elapsed.get(&site).copied().unwrap_or(0.0));
+        now - last >= interval
+    }
+
+    /// Goods to restock at `site` with their weights, the profile's base
+    /// weights plus everything sold since the last restock
+    pub fn weights(&self, site: SiteId) -> Vec<(Good, f32)> {
+        let mut weights: HashMap<Good, f32> = self
+            .profiles
+            .for_site(site)
+            .goods
+            .iter()
+            .copied()
+            .collect();
+        if let Some(sold) = self.state.sold.get(&site) {
+            // Normalize so sales matter as much as the base weights together
+            let total = sold.values().sum::<f32>().max(1.0);
+            let base = weights.values().sum::<f32>().max(1.0);
+            for (good, amount) in sold {
+                *weights.entry(*good).or_default() += amount / total * base;
+            }
+        }
+        let mut weights = weights.into_iter().collect::<Vec<_>>();
+        // HashMap order isn't stable, keep seeded restocks reproducible
+        weights.sort_by(|a, b| format!("{:?}", a.0).cmp(&format!("{:?}", b.0)));
+        weights
+    }
+
+    pub fn finish_restock(&mut self, site: SiteId, now: f64) {
+        self.now = now;
+        self.state.sold.remove(&site);
+        self.last_restock.insert(site, now);
+        self.dirty = true;
+    }
+
+    /// Saves changes every [`SAVE_INTERVAL`], called every tick
+    pub fn maintain(&mut self) {
+        if self.dirty && self.last_save.elapsed() >= SAVE_INTERVAL {
+            self.save();
+        }
+    }
+
+    /// Write the state back to disk, e.g. on shutdown
+    pub fn save(&mut self) {
+        for (site, last) in &self.last_restock {
+            self.state.elapsed.insert(*site, (self.now - last).max(0.0));
+        }
+        self.last_save = Instant::now();
+        match write_ron(&self.path, &self.state) {
+            Ok(()) => self.dirty = false,
+            Err(e) => error!(?e, "Failed to save restock state"),
+        }
+    }
+}
+
+fn write_ron<T: Serialize>(path: &Path, value: &T) -> std::io::Result<()> {
+    let ron = ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+    AtomicFile::new(path, OverwriteBehavior::AllowOverwrite)
+        .write(|file| file.write_all(ron.as_bytes()))
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sales_shift_weights() {
+        let dir = std::env::temp_dir().join(format!("veloren-restock-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let mut restock = Restock::load(&dir);
+        let base = restock.weights(1);
+        restock.record_sale(1, Good::Potions, 10.0);
+        let weight = |weights: &[(Good, f32)]| {
+            weights
+                .iter()
+                .find(|(good, _)| *good == Good::Potions)
+                .map_or(0.0, |(_, w)| *w)
+        };
+        assert!(weight(&restock.weights(1)) > weight(&base));
+
+        let interval = restock.profiles.default.interval;
+        assert!(!restock.is_due(1, 0.0));
+        assert!(restock.is_due(1, 1e9));
+        restock.finish_restock(1, 1e9);
+        // Half the interval passes before the server stops
+        assert!(!restock.is_due(1, 1e9 + interval / 2.0));
+        restock.save();
+
+        // `Time` starts over after a restart, the remaining half still counts
+        let mut reloaded = Restock::load(&dir);
+        assert!(reloaded.state.sold.is_empty());
+        assert!(!reloaded.is_due(1, 10.0));
+        assert!(reloaded.is_due(1, 10.0 + interval / 2.0));
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
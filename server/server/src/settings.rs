@@ -19,6 +19,7 @@ use common::{
     resources::BattleMode,
 };
 use core::time::Duration;
+use network::AdmissionPolicy;
 use portpicker::pick_unused_port;
 use serde::{Deserialize, Serialize};
 use std::{
@@ -94,6 +95,69 @@ impl CalendarMode {
     }
 }
 
+/// What new logins have to solve before they are authenticated, to make bot
+/// floods expensive
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum LoginChallengeMode {
+    Disabled,
+    /// Clients search a hash with `difficulty` leading zero bits, each step
+    /// doubles the work. Around 20 takes a second on a desktop cpu.
+    ProofOfWork { difficulty: u8 },
+    /// Players type the text of a random PNG image from this directory, which
+    /// is the name of the file
+    Captcha { directory: PathBuf },
+}
+
+impl Default for LoginChallengeMode {
+    fn default() -> Self { Self::Disabled }
+}
+
+/// Limits for incoming connections from a single IP, see [`AdmissionPolicy`]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConnectionLimits {
+    /// Open connections per IP, `None` for unlimited
+    pub max_connections_per_ip: Option<usize>,
+    /// Connect attempts per second an IP regains
+    pub connect_rate: f32,
+    /// Connect attempts an IP can make at once
+    pub connect_burst: f32,
+    /// Rejected attempts after which an IP gets banned, `None` to never ban
+    pub rejections_until_ban: Option<u32>,
+    pub ban_duration: Duration,
+}
+
+impl Default for ConnectionLimits {
+    fn default() -> Self {
+        let AdmissionPolicy {
+            max_connections_per_ip,
+            connect_rate,
+            connect_burst,
+            rejections_until_ban,
+            ban_duration,
+        } = AdmissionPolicy::default();
+        Self {
+            max_connections_per_ip,
+            connect_rate,
+            connect_burst,
+            rejections_until_ban,
+            ban_duration,
+        }
+    }
+}
+
+impl ConnectionLimits {
+    pub fn admission_policy(&self) -> AdmissionPolicy {
+        AdmissionPolicy {
+            max_connections_per_ip: self.max_connections_per_ip,
+            connect_rate: self.connect_rate,
+            connect_burst: self.connect_burst,
+            rejections_until_ban: self.rejections_until_ban,
+            ban_duration: self.ban_duration,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(default)]
 pub struct Settings {
@@ -116,6 +180,8 @@ pub struct Settings {
     pub safe_spawn: bool,
     pub max_player_for_kill_broadcast: Option<usize>,
     pub calendar_mode: CalendarMode,
+    pub login_challenge: LoginChallengeMode,
+    pub connection_limits: ConnectionLimits,
 
     /// Experimental feature. No guaranteed forwards-compatibility, may be
     /// removed at *any time* with no migration.
@@ -146,6 +212,8 @@ impl Default for Settings {
             banned_words_files: Vec::new(),
             max_player_group_size: 6,
             calendar_mode: CalendarMode::Auto,
+            login_challenge: LoginChallengeMode::Disabled,
+            connection_limits: ConnectionLimits::default(),
             client_timeout: Duration::from_secs(360),
             spawn_town: None,
             safe_spawn: true,
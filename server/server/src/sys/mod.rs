@@ -6,6 +6,7 @@ pub mod msg;
 pub mod object;
 pub mod persistence;
 pub mod pets;
+pub mod restock;
 pub mod sentinel;
 pub mod subscription;
 pub mod terrain;
@@ -33,6 +34,7 @@ pub fn add_server_systems(dispatch_builder: &mut DispatcherBuilder) {
     dispatch::<persistence::Sys>(dispatch_builder, &[]);
     dispatch::<object::Sys>(dispatch_builder, &[]);
     dispatch::<wiring::Sys>(dispatch_builder, &[]);
+    dispatch::<restock::Sys>(dispatch_builder, &[]);
 }
 
 pub fn run_sync_systems(ecs: &mut specs::World) {
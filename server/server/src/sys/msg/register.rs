@@ -1,5 +1,6 @@
 use crate::{
     client::Client,
+    login_challenge::{LoginChallenger, PendingChallenge},
     login_provider::{LoginProvider, PendingLogin},
     metrics::PlayerMetrics,
     EditableSettings, Settings,
@@ -40,6 +41,7 @@ pub struct ReadData<'a> {
     player_metrics: ReadExpect<'a, PlayerMetrics>,
     settings: ReadExpect<'a, Settings>,
     editable_settings: ReadExpect<'a, EditableSettings>,
+    login_challenger: ReadExpect<'a, LoginChallenger>,
     _healths: ReadStorage<'a, Health>, // used by plugin feature
     _plugin_mgr: ReadPlugin<'a>,       // used by plugin feature
     _uid_allocator: Read<'a, UidAllocator>, // used by plugin feature
@@ -54,6 +56,7 @@ impl<'a> System<'a> for Sys {
         WriteStorage<'a, Player>,
         WriteStorage<'a, Admin>,
         WriteStorage<'a, PendingLogin>,
+        WriteStorage<'a, PendingChallenge>,
         WriteExpect<'a, LoginProvider>,
     );
 
@@ -68,6 +71,7 @@ impl<'a> System<'a> for Sys {
             mut players,
             mut admins,
             mut pending_logins,
+            mut pending_challenges,
             mut login_provider,
         ): Self::SystemData,
     ) {
@@ -96,7 +100,30 @@ impl<'a> System<'a> for Sys {
 
         // defer auth lockup
         for (entity, client) in (&read_data.entities, &read_data.clients).join() {
-            let _ = super::try_recv_all(client, 0, |_, msg: ClientRegister| {
+            let _ = super::try_recv_all(client, 0, |client, msg: ClientRegister| {
+                // The challenge is solved before the auth server is bothered
+                if let Some(pending) = pending_challenges.remove(entity) {
+                    let solved = msg
+                        .challenge_answer
+                        .as_ref()
+                        .map_or(false, |answer| pending.check(answer));
+                    if !solved {
+                        trace!(?msg.token_or_username, "login challenge failed");
+                        server_emitter.emit(ServerEvent::ClientDisconnect(
+                            entity,
+                            common::comp::DisconnectReason::Kicked,
+                        ));
+                        client.send(ServerRegisterAnswer::Err(RegisterError::ChallengeFailed))?;
+                        return Ok(());
+                    }
+                } else if let Some(pending) = read_data.login_challenger.issue() {
+                    trace!(?msg.token_or_username, "challenge login");
+                    client.send(ServerRegisterAnswer::Err(RegisterError::Challenge(
+                        pending.challenge().clone(),
+                    )))?;
+                    let _ = pending_challenges.insert(entity, pending);
+                    return Ok(());
+                }
                 trace!(?msg.token_or_username, "defer auth lockup");
                 let pending = login_provider.verify(&msg.token_or_username);
                 let _ = pending_logins.insert(entity, pending);
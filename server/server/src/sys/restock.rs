@@ -0,0 +1,80 @@
+use crate::restock::Restock;
+use common::{
+    comp::{
+        inventory::trade_pricing::{ShoppingNeeds, TradePricing},
+        Agent, Inventory, Item,
+    },
+    resources::Time,
+};
+use common_ecs::{Job, Origin, Phase, System};
+use hashbrown::HashSet;
+use rand::{rngs::SmallRng, SeedableRng};
+use specs::{Join, Read, ReadStorage, WriteExpect, WriteStorage};
+use tracing::debug;
+
+/// This system refills the inventories of merchants whose restock interval
+/// passed
+#[derive(Default)]
+pub struct Sys;
+impl<'a> System<'a> for Sys {
+    type SystemData = (
+        Read<'a, Time>,
+        WriteExpect<'a, Restock>,
+        ReadStorage<'a, Agent>,
+        WriteStorage<'a, Inventory>,
+    );
+
+    const NAME: &'static str = "restock";
+    const ORIGIN: Origin = Origin::Server;
+    const PHASE: Phase = Phase::Create;
+
+    fn run(_job: &mut Job<Self>, (time, mut restock, agents, mut inventories): Self::SystemData) {
+        let mut due = HashSet::new();
+        for (agent, inventory) in (&agents, &mut inventories).join() {
+            let site = match agent.behavior.trade_site {
+                Some(site) => site,
+                None => continue,
+            };
+            if !due.contains(&site) && !restock.is_due(site, time.0) {
+                continue;
+            }
+            due.insert(site);
+
+            let profile = restock.profiles.for_site(site);
+            let (portion, supply) = (profile.portion, profile.supply);
+            let weights = restock.weights(site);
+            let total = weights.iter().map(|(_, w)| w).sum::<f32>();
+            if total <= 0.0 {
+                continue;
+            }
+            // Same site and time always give the same goods
+            let mut rng = SmallRng::seed_from_u64(site ^ time.0.to_bits());
+            let count = (inventory.free_slots() as f32 * portion).ceil() as usize;
+            // The goods are bought by value, `supply` per slot split by weight
+            let needs = ShoppingNeeds(
+                weights
+                    .iter()
+                    .map(|(good, weight)| (*good, weight / total))
+                    .collect(),
+            );
+            let list = TradePricing::shopping_list(count as f32 * supply, &needs, &mut rng);
+            let items = list
+                .items
+                .iter()
+                .flat_map(|(id, amount)| std::iter::repeat(id).take(*amount as usize))
+                .filter_map(|id| Item::new_from_asset(id).ok())
+                .take(count);
+            for item in items {
+                if inventory.push(item).is_err() {
+                    break;
+                }
+            }
+        }
+
+        for site in due {
+            debug!(?site, "Restocked merchants");
+            restock.finish_restock(site, time.0);
+        }
+        restock.maintain();
+    }
+}
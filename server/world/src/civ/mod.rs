@@ -5,7 +5,11 @@ mod econ;
 use crate::{
     config::CONFIG,
     sim::WorldSim,
-    site::{namegen::NameGen, Castle, Settlement, Site as WorldSite, Tree},
+    site::{
+        caravan::{self, CaravanRoute},
+        namegen::NameGen,
+        Castle, Settlement, Site as WorldSite, Tree,
+    },
     site2,
     util::{attempt, seed_expan, DHashMap, DHashSet, NEIGHBORS},
     Index, Land,
@@ -289,6 +293,13 @@ impl Civs {
                                 .get_mut(index2)
                                 .economy
                                 .add_neighbor(index1, cost);
+                            index.caravans.register_route(CaravanRoute {
+                                from: index1,
+                                to: index2,
+                                distance: cost as f32,
+                                speed: caravan::DEFAULT_SPEED,
+                                capacity: 1.0,
+                            });
                         }
                     }
                 }
@@ -1,6 +1,10 @@
 use crate::{
     layer::wildlife::{self, DensityFn, SpawnEntry},
-    site::{economy::TradeInformation, Site},
+    site::{
+        caravan::{Arbitrage, Caravans},
+        economy::TradeInformation,
+        Site,
+    },
     Colors, Features,
 };
 use common::{
@@ -21,6 +25,7 @@ pub struct Index {
     pub noise: Noise,
     pub sites: Store<Site>,
     pub trade: TradeInformation,
+    pub caravans: Caravans,
     pub wildlife_spawns: Vec<(AssetHandle<SpawnEntry>, DensityFn)>,
     colors: AssetHandle<Arc<Colors>>,
     features: AssetHandle<Arc<Features>>,
@@ -76,6 +81,7 @@ impl Index {
             noise: Noise::new(seed),
             sites: Store::default(),
             trade: Default::default(),
+            caravans: Default::default(),
             wildlife_spawns,
             colors,
             features,
@@ -89,8 +95,15 @@ impl Index {
     pub fn get_site_prices(&self, site_id: SiteId) -> Option<SitePrices> {
         self.sites
             .recreate_id(site_id)
-            .map(|i| self.sites.get(i))
-            .map(|s| s.economy.get_site_prices())
+            .map(|i| self.caravans.prices(&self.sites, i))
+    }
+
+    /// Trades between neighbouring sites worth doing for NPC traders at
+    /// `site_id`, best first
+    pub fn arbitrage(&self, site_id: SiteId) -> Vec<Arbitrage> {
+        self.sites
+            .recreate_id(site_id)
+            .map_or_else(Vec::new, |i| self.caravans.arbitrage(&self.sites, i))
     }
 }
 
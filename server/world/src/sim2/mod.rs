@@ -292,6 +292,7 @@ pub fn tick(index: &mut Index, _world: &mut WorldSim, dt: f32, mut vc: vergleich
             }
         }
     }
+    index.caravans.tick(&index.sites, dt);
     //check_money(index);
 
     index.time += dt;
@@ -0,0 +1,233 @@
+use super::Site;
+use crate::util::DHashMap;
+use common::{
+    store::{Id, Store},
+    trade::{Good, SitePrices},
+};
+
+/// Travelled distance (in track segments) per day
+pub const DEFAULT_SPEED: f32 = 20.0;
+/// Portion of the price difference a fully loaded caravan removes per trip
+const EQUALIZE_RATE: f32 = 0.1;
+/// Smallest relative margin reported as an arbitrage opportunity
+const MIN_MARGIN: f32 = 0.05;
+
+#[derive(Clone, Debug)]
+pub struct CaravanRoute {
+    pub from: Id<Site>,
+    pub to: Id<Site>,
+    pub distance: f32,
+    pub speed: f32,
+    /// 1.0 is a normal caravan, larger values move prices faster
+    pub capacity: f32,
+}
+
+impl CaravanRoute {
+    pub fn travel_days(&self) -> f32 { self.distance / self.speed.max(f32::EPSILON) }
+}
+
+#[derive(Clone, Debug)]
+pub struct Arbitrage {
+    pub good: Good,
+    pub to: Id<Site>,
+    pub buy_price: f32,
+    pub sell_price: f32,
+    pub travel_days: f32,
+}
+
+impl Arbitrage {
+    pub fn margin(&self) -> f32 { self.sell_price / self.buy_price - 1.0 }
+}
+
+/// Slowly equalizes the prices between sites connected by caravan routes
+///
+/// Site economies keep their own prices, caravans only remember a factor per
+/// site and good which is applied on top of them.
+#[derive(Debug, Default)]
+pub struct Caravans {
+    routes: Vec<CaravanRoute>,
+    factors: DHashMap<Id<Site>, DHashMap<Good, f32>>,
+}
+
+impl Caravans {
+    /// Connect two sites, routes are travelled in both directions
+    pub fn register_route(&mut self, route: CaravanRoute) {
+        if !self.routes.iter().any(|r| {
+            (r.from, r.to) == (route.from, route.to) || (r.from, r.to) == (route.to, route.from)
+        }) {
+            self.routes.push(route);
+        }
+    }
+
+    pub fn routes(&self) -> &[CaravanRoute] { &self.routes }
+
+    fn factor(&self, site: Id<Site>, good: Good) -> f32 {
+        self.factors
+            .get(&site)
+            .and_then(|f| f.get(&good))
+            .copied()
+            .unwrap_or(1.0)
+    }
+
+    /// The site's prices with caravan trade taken into account
+    pub fn adjust(&self, site: Id<Site>, mut prices: SitePrices) -> SitePrices {
+        if let Some(factors) = self.factors.get(&site) {
+            for (good, price) in prices.values.iter_mut() {
+                *price *= factors.get(good).copied().unwrap_or(1.0);
+            }
+        }
+        prices
+    }
+
+    pub fn prices(&self, sites: &Store<Site>, site: Id<Site>) -> SitePrices {
+        self.adjust(site, sites[site].economy.get_site_prices())
+    }
+
+    /// Advance caravan trade by `dt` days
+    pub fn tick(&mut self, sites: &Store<Site>, dt: f32) {
+        let mut prices = DHashMap::<Id<Site>, SitePrices>::default();
+        for route in &self.routes {
+            for site in [route.from, route.to] {
+                prices
+                    .entry(site)
+                    .or_insert_with(|| sites[site].economy.get_site_prices());
+            }
+        }
+
+        let routes = std::mem::take(&mut self.routes);
+        for route in &routes {
+            // Caravans which didn't arrive yet still carry part of their goods
+            let strength = (dt / route.travel_days().max(1.0)).min(1.0) * EQUALIZE_RATE;
+            let strength = (strength * route.capacity).min(0.5);
+            for (&good, &base_from) in prices[&route.from].values.iter() {
+                let base_to = match prices[&route.to].values.get(&good) {
+                    Some(&base) => base,
+                    None => continue,
+                };
+                if good.trade_margin() <= 0.0
+                    || good == Good::Coin
+                    || base_from <= 0.0
+                    || base_to <= 0.0
+                {
+                    continue;
+                }
+                let price_from = base_from * self.factor(route.from, good);
+                let price_to = base_to * self.factor(route.to, good);
+                let delta = (price_to - price_from) * strength;
+                self.factors
+                    .entry(route.from)
+                    .or_default()
+                    .insert(good, (price_from + delta) / base_from);
+                self.factors
+                    .entry(route.to)
+                    .or_default()
+                    .insert(good, (price_to - delta) / base_to);
+            }
+        }
+        self.routes = routes;
+    }
+
+    /// Profitable trades for an NPC trader starting at `from`, best first
+    pub fn arbitrage(&self, sites: &Store<Site>, from: Id<Site>) -> Vec<Arbitrage> {
+        let here = self.prices(sites, from);
+        let mut result = self
+            .routes
+            .iter()
+            .filter_map(|r| {
+                if r.from == from {
+                    Some((r.to, r))
+                } else if r.to == from {
+                    Some((r.from, r))
+                } else {
+                    None
+                }
+            })
+            .flat_map(|(to, route)| {
+                let there = self.prices(sites, to);
+                here.values
+                    .iter()
+                    .filter(|(good, _)| good.trade_margin() > 0.0 && **good != Good::Coin)
+                    .filter_map(move |(&good, &buy_price)| {
+                        let sell_price = there.values.get(&good)? * good.trade_margin();
+                        Some(Arbitrage {
+                            good,
+                            to,
+                            buy_price,
+                            sell_price,
+                            travel_days: route.travel_days(),
+                        })
+                    })
+                    .filter(|a| a.buy_price > 0.0 && a.margin() > MIN_MARGIN)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        result.sort_by(|a, b| {
+            b.margin()
+                .partial_cmp(&a.margin())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{site::economy::GoodIndex, site2};
+    use std::convert::TryFrom;
+
+    fn site(food: f32, tools: f32) -> Site {
+        let mut site = Site::refactor(site2::Site::default());
+        site.economy.values[GoodIndex::try_from(Good::Food).unwrap()] = Some(food);
+        site.economy.values[GoodIndex::try_from(Good::Tools).unwrap()] = Some(tools);
+        site
+    }
+
+    fn food_prices(
+        caravans: &Caravans,
+        sites: &Store<Site>,
+        a: Id<Site>,
+        b: Id<Site>,
+    ) -> (f32, f32) {
+        (
+            caravans.prices(sites, a).values[&Good::Food],
+            caravans.prices(sites, b).values[&Good::Food],
+        )
+    }
+
+    #[test]
+    fn prices_converge_along_routes() {
+        let mut sites = Store::default();
+        // food is cheap at a and dear at b, tools the other way round
+        let a = sites.insert(site(1.0, 3.0));
+        let b = sites.insert(site(3.0, 1.0));
+        let mut caravans = Caravans::default();
+        let route = |from, to| CaravanRoute {
+            from,
+            to,
+            distance: 40.0,
+            speed: DEFAULT_SPEED,
+            capacity: 1.0,
+        };
+        caravans.register_route(route(a, b));
+        caravans.register_route(route(b, a));
+        assert_eq!(caravans.routes().len(), 1);
+
+        let best = &caravans.arbitrage(&sites, a)[0];
+        assert_eq!((best.good, best.to), (Good::Food, b));
+        assert!((best.travel_days - 2.0).abs() < f32::EPSILON);
+        assert!(caravans.arbitrage(&sites, b).iter().all(|t| t.good != Good::Food));
+
+        let (start_a, start_b) = food_prices(&caravans, &sites, a, b);
+        for _ in 0..100 {
+            caravans.tick(&sites, 1.0);
+        }
+        let (end_a, end_b) = food_prices(&caravans, &sites, a, b);
+        assert!(end_a > start_a && end_b < start_b);
+        assert!(end_b - end_a < (start_b - start_a) * 0.1);
+        // the site economies themselves are left alone
+        assert_eq!(sites[a].economy.get_site_prices().values[&Good::Food], start_a);
+        // once prices are close, hauling food no longer pays off
+        assert!(caravans.arbitrage(&sites, a).iter().all(|t| t.good != Good::Food));
+    }
+}
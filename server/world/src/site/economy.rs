@@ -570,7 +570,15 @@ impl Economy {
                         + value.unwrap_or(Economy::MINIMUM_PRICE))
                         * 0.5
                 });
-                prices.iter().map(|(g, v)| (Good::from(g), *v)).collect()
+                let mut values: hashbrown::HashMap<Good, f32> =
+                    prices.iter().map(|(g, v)| (Good::from(g), *v)).collect();
+                // Goods sites don't produce are priced like the good they're made of
+                for good in [ModularComponents, Furniture, Pets] {
+                    if let Some(price) = values.get(&good.economy_proxy()).copied() {
+                        values.insert(good, price);
+                    }
+                }
+                values
             },
         }
     }
@@ -1,4 +1,5 @@
 mod block_mask;
+pub mod caravan;
 mod castle;
 pub mod economy;
 pub mod namegen;